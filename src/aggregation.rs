@@ -0,0 +1,463 @@
+// src/aggregation.rs
+// Multi-bot aggregation - bots report their open positions to one aggregator, which combines
+// exposure and P&L across them and answers with pause commands while an exposure cap is exceeded
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::leader;
+use crate::metrics;
+use crate::position_tracker::{ArbPosition, SharedPositionTracker};
+use crate::runtimes;
+
+/// What a bot sends: its open positions and realized P&L
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BotReport {
+    pub bot: String,
+    pub positions: Vec<ArbPosition>,
+    pub daily_pnl: f64,
+    pub all_time_pnl: f64,
+}
+
+impl BotReport {
+    /// From a store's rows: the open positions, with P&L from the resolved ones (`today` = YYYY-MM-DD)
+    pub fn from_positions(bot: &str, positions: Vec<ArbPosition>, today: &str) -> Self {
+        let resolved = positions.iter().filter(|p| p.status == "resolved");
+        let (mut daily_pnl, mut all_time_pnl) = (0.0, 0.0);
+        for p in resolved {
+            let pnl = p.realized_pnl.unwrap_or(0.0);
+            all_time_pnl += pnl;
+            if p.resolved_at.as_deref().is_some_and(|t| t.starts_with(today)) {
+                daily_pnl += pnl;
+            }
+        }
+        Self {
+            bot: bot.to_string(),
+            positions: positions.into_iter().filter(|p| p.status == "open").collect(),
+            daily_pnl,
+            all_time_pnl,
+        }
+    }
+
+    /// Forget positions whose market ended by `now_ms` (they settle without a report)
+    pub fn drop_ended(&mut self, now_ms: i64) {
+        self.positions.retain(|p| !p.ended(now_ms / 1000));
+    }
+
+    /// Capital in open positions
+    pub fn exposure(&self) -> f64 {
+        self.positions.iter().fold(0.0, |sum, p| sum + p.total_cost())
+    }
+
+    /// Unhedged exposure of the open positions
+    pub fn unmatched(&self) -> f64 {
+        self.positions.iter().fold(0.0, |sum, p| sum + p.unmatched_exposure())
+    }
+}
+
+/// The aggregator's answer to a report
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Control {
+    /// Stop opening new positions (exits and hedges continue)
+    pub pause: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Aggregator configuration from environment
+#[derive(Debug, Clone)]
+pub struct AggregatorConfig {
+    /// Combined open exposure (dollars) at which every bot is paused (0 = no cap)
+    pub max_exposure: f64,
+
+    /// One bot's open exposure (dollars) at which that bot is paused (0 = no cap)
+    pub max_bot_exposure: f64,
+
+    /// A pause lifts once exposure is back under this fraction of its cap
+    pub resume_fraction: f64,
+
+    /// A pushed report older than this gives way to the shared store's rows for that bot
+    pub stale_secs: u64,
+}
+
+impl AggregatorConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_exposure: std::env::var("AGG_MAX_EXPOSURE_USD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+
+            max_bot_exposure: std::env::var("AGG_MAX_BOT_EXPOSURE_USD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+
+            resume_fraction: std::env::var("AGG_RESUME_FRACTION")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.9),
+
+            stale_secs: std::env::var("AGG_STALE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+        }
+    }
+}
+
+/// Where a bot's positions came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// The bot's own HTTP push
+    Push,
+    /// Rows of the shared position store
+    Store,
+}
+
+#[derive(Debug)]
+struct BotEntry {
+    report: BotReport,
+    source: Source,
+    seen_ms: i64,
+    paused: Option<String>,
+}
+
+/// One bot in the combined view
+#[derive(Debug, Clone, Serialize)]
+pub struct BotStatus {
+    pub bot: String,
+    pub source: Source,
+    pub age_secs: i64,
+    pub open_positions: usize,
+    pub exposure: f64,
+    pub unmatched: f64,
+    pub daily_pnl: f64,
+    pub all_time_pnl: f64,
+    pub paused: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Exposure and P&L across every bot
+#[derive(Debug, Clone, Serialize)]
+pub struct Combined {
+    pub exposure: f64,
+    pub unmatched: f64,
+    pub daily_pnl: f64,
+    pub all_time_pnl: f64,
+    pub max_exposure: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_pause: Option<String>,
+    pub bots: Vec<BotStatus>,
+}
+
+/// Latest positions per bot, deduplicated across sources, and the pauses they lead to
+pub struct Aggregator {
+    config: AggregatorConfig,
+    bots: HashMap<String, BotEntry>,
+    global_pause: Option<String>,
+}
+
+impl Aggregator {
+    pub fn new(config: AggregatorConfig) -> Self {
+        Self { config, bots: HashMap::new(), global_pause: None }
+    }
+
+    /// Take a bot's report. Each report replaces the bot's previous one, so repeated pushes
+    /// never count a position twice; store rows only stand in while the bot isn't pushing.
+    pub fn ingest(&mut self, mut report: BotReport, source: Source, now_ms: i64) {
+        report.drop_ended(now_ms);
+        let stale_ms = self.config.stale_secs as i64 * 1000;
+        if let Some(entry) = self.bots.get_mut(&report.bot) {
+            if source == Source::Store && entry.source == Source::Push && now_ms - entry.seen_ms < stale_ms {
+                return;
+            }
+            entry.report = report;
+            entry.source = source;
+            entry.seen_ms = now_ms;
+            return;
+        }
+        self.bots.insert(report.bot.clone(), BotEntry { report, source, seen_ms: now_ms, paused: None });
+    }
+
+    fn total_exposure(&self) -> f64 {
+        self.bots.values().fold(0.0, |sum, b| sum + b.report.exposure())
+    }
+
+    /// New pause state against `cap` (hysteresis: resumes below `resume_fraction` of it)
+    fn gate(&self, paused: &Option<String>, exposure: f64, cap: f64, what: &str) -> Option<String> {
+        if cap <= 0.0 {
+            return None;
+        }
+        match paused {
+            Some(_) if exposure >= cap * self.config.resume_fraction => paused.clone(),
+            None if exposure >= cap => Some(format!("{} exposure ${:.2} over the ${:.2} cap", what, exposure, cap)),
+            _ => None,
+        }
+    }
+
+    /// Re-check the caps, logging every pause and resume. Positions whose market has ended
+    /// by `now_ms` stop counting, since nothing reports their settlement.
+    pub fn evaluate(&mut self, now_ms: i64) {
+        for entry in self.bots.values_mut() {
+            entry.report.drop_ended(now_ms);
+        }
+        let total = self.total_exposure();
+        let global = self.gate(&self.global_pause, total, self.config.max_exposure, "combined");
+        if global != self.global_pause {
+            match &global {
+                Some(reason) => warn!("[AGG] ⏸️ Pausing every bot: {}", reason),
+                None => info!("[AGG] ▶️ Combined exposure ${:.2} back under the cap - resuming", total),
+            }
+            metrics::incr(if global.is_some() { "aggregator.global_pause" } else { "aggregator.global_resume" });
+            self.global_pause = global;
+        }
+
+        let mut updates = Vec::new();
+        for (bot, entry) in &self.bots {
+            let exposure = entry.report.exposure();
+            let paused = self.gate(&entry.paused, exposure, self.config.max_bot_exposure, bot);
+            if paused != entry.paused {
+                match &paused {
+                    Some(reason) => warn!("[AGG] ⏸️ Pausing {}: {}", bot, reason),
+                    None => info!("[AGG] ▶️ {} exposure ${:.2} back under its cap - resuming", bot, exposure),
+                }
+                updates.push((bot.clone(), paused));
+            }
+        }
+        for (bot, paused) in updates {
+            if let Some(entry) = self.bots.get_mut(&bot) {
+                entry.paused = paused;
+            }
+        }
+    }
+
+    /// What `bot` is told on its next push
+    pub fn control(&self, bot: &str) -> Control {
+        let reason = self.global_pause.clone().or_else(|| self.bots.get(bot).and_then(|b| b.paused.clone()));
+        Control { pause: reason.is_some(), reason }
+    }
+
+    pub fn status(&self, now_ms: i64) -> Combined {
+        let mut bots: Vec<BotStatus> = self.bots.iter()
+            .map(|(bot, entry)| {
+                let control = self.control(bot);
+                BotStatus {
+                    bot: bot.clone(),
+                    source: entry.source,
+                    age_secs: (now_ms - entry.seen_ms) / 1000,
+                    open_positions: entry.report.positions.len(),
+                    exposure: entry.report.exposure(),
+                    unmatched: entry.report.unmatched(),
+                    daily_pnl: entry.report.daily_pnl,
+                    all_time_pnl: entry.report.all_time_pnl,
+                    paused: control.pause,
+                    reason: control.reason,
+                }
+            })
+            .collect();
+        bots.sort_by(|a, b| a.bot.cmp(&b.bot));
+        Combined {
+            exposure: bots.iter().fold(0.0, |sum, b| sum + b.exposure),
+            unmatched: bots.iter().fold(0.0, |sum, b| sum + b.unmatched),
+            daily_pnl: bots.iter().fold(0.0, |sum, b| sum + b.daily_pnl),
+            all_time_pnl: bots.iter().fold(0.0, |sum, b| sum + b.all_time_pnl),
+            max_exposure: self.config.max_exposure,
+            global_pause: self.global_pause.clone(),
+            bots,
+        }
+    }
+}
+
+// === Bot side ===
+
+/// Reporting to an aggregator, from environment
+#[derive(Debug, Clone)]
+pub struct ReporterConfig {
+    /// Aggregator base URL (unset = not reporting)
+    pub url: Option<String>,
+
+    /// Seconds between reports
+    pub push_secs: u64,
+
+    /// Shared secret sent as a bearer token
+    pub token: Option<String>,
+}
+
+impl ReporterConfig {
+    pub fn from_env() -> Self {
+        Self {
+            url: std::env::var("AGGREGATOR_URL").ok().filter(|v| !v.trim().is_empty()),
+
+            push_secs: std::env::var("AGGREGATOR_PUSH_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+
+            token: std::env::var("AGGREGATOR_TOKEN").ok().filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+fn pause_state() -> &'static RwLock<Option<String>> {
+    static PAUSE: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+    PAUSE.get_or_init(|| RwLock::new(None))
+}
+
+/// Why the aggregator has this bot paused (None = trading)
+pub fn paused() -> Option<String> {
+    pause_state().read().unwrap().clone()
+}
+
+fn apply(control: Control) {
+    let mut state = pause_state().write().unwrap();
+    let reason = control.pause.then(|| control.reason.unwrap_or_else(|| "paused by aggregator".into()));
+    if reason != *state {
+        match &reason {
+            Some(reason) => warn!("[AGG] ⏸️ Paused by aggregator: {} - no new positions", reason),
+            None => info!("[AGG] ▶️ Aggregator lifted the pause"),
+        }
+        *state = reason;
+    }
+}
+
+/// Push this bot's open positions every `push_secs` and follow the aggregator's answer.
+/// An unreachable aggregator leaves the last answer in place.
+pub fn spawn_reporter(config: ReporterConfig, bot: String, tracker: SharedPositionTracker) {
+    let Some(url) = config.url.clone() else { return };
+    let url = format!("{}/report", url.trim_end_matches('/'));
+    info!("[AGG] Reporting positions to {} every {}s as {:?}", url, config.push_secs, bot);
+    runtimes::spawn_bookkeeping(async move {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client");
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.push_secs.max(1)));
+        loop {
+            ticker.tick().await;
+            // The leader reports for both instances of a warm-standby pair
+            if !leader::is_leader() {
+                continue;
+            }
+            let mut report = {
+                let tracker = tracker.read().await;
+                BotReport {
+                    bot: bot.clone(),
+                    positions: tracker.open_positions().into_iter().cloned().collect(),
+                    daily_pnl: tracker.daily_pnl(),
+                    all_time_pnl: tracker.all_time_pnl,
+                }
+            };
+            report.drop_ended(chrono::Utc::now().timestamp_millis());
+            let mut request = http.post(&url).json(&report);
+            if let Some(token) = &config.token {
+                request = request.bearer_auth(token);
+            }
+            let response = match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(r) => r.json::<Control>().await,
+                Err(e) => Err(e),
+            };
+            match response {
+                Ok(control) => apply(control),
+                Err(e) => {
+                    warn!("[AGG] Report to {} failed: {}", url, e);
+                    metrics::incr("aggregator.push_failed");
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_key::MarketKey;
+    use crate::position_tracker::FillRecord;
+
+    fn report(bot: &str, cost_per_market: &[f64]) -> BotReport {
+        let positions = cost_per_market.iter().enumerate().map(|(i, cost)| {
+            let mut p = ArbPosition::new(&format!("m{}", i), "market");
+            p.apply_fill(&FillRecord::new(&p.market_id.clone(), "market", "polymarket", "yes", cost / 0.5, 0.5, 0.0, "o"));
+            p
+        }).collect();
+        BotReport { bot: bot.into(), positions, daily_pnl: 1.0, all_time_pnl: 2.0 }
+    }
+
+    fn config() -> AggregatorConfig {
+        AggregatorConfig { max_exposure: 100.0, max_bot_exposure: 70.0, resume_fraction: 0.9, stale_secs: 60 }
+    }
+
+    #[test]
+    fn test_reports_replace_not_add() {
+        let mut agg = Aggregator::new(config());
+        agg.ingest(report("a", &[20.0, 10.0]), Source::Push, 0);
+        agg.ingest(report("a", &[20.0, 10.0]), Source::Push, 1_000);
+        agg.ingest(report("b", &[5.0]), Source::Store, 1_000);
+        let status = agg.status(2_000);
+        assert!((status.exposure - 35.0).abs() < 1e-9);
+        assert_eq!((status.daily_pnl, status.bots.len()), (2.0, 2));
+
+        // Store rows don't override a fresh push, only a stale one
+        agg.ingest(report("a", &[90.0]), Source::Store, 30_000);
+        assert!((agg.status(30_000).bots[0].exposure - 30.0).abs() < 1e-9);
+        agg.ingest(report("a", &[90.0]), Source::Store, 62_000);
+        assert_eq!(agg.status(62_000).bots[0].source, Source::Store);
+    }
+
+    #[test]
+    fn test_caps_pause_and_resume_with_hysteresis() {
+        let mut agg = Aggregator::new(config());
+        agg.ingest(report("a", &[75.0]), Source::Push, 0);
+        agg.ingest(report("b", &[10.0]), Source::Push, 0);
+        agg.evaluate(0);
+        assert!(agg.control("a").pause);
+        assert!(!agg.control("b").pause);
+
+        agg.ingest(report("b", &[30.0]), Source::Push, 0);
+        agg.evaluate(0);
+        assert!(agg.control("b").reason.unwrap().contains("combined"));
+
+        // 95 < 100 but above 90: still paused; 85 resumes
+        agg.ingest(report("b", &[20.0]), Source::Push, 0);
+        agg.evaluate(0);
+        assert!(agg.control("b").pause);
+        agg.ingest(report("b", &[10.0]), Source::Push, 0);
+        agg.evaluate(0);
+        assert!(!agg.control("b").pause);
+        assert!(agg.control("a").pause);   // a's own cap: 75 >= 63
+    }
+
+    #[test]
+    fn test_ended_markets_stop_counting() {
+        let mut agg = Aggregator::new(config());
+        let mut r = report("a", &[60.0, 50.0]);
+        r.positions[0].key = Some(MarketKey::polymarket("btc-updown-15m-1766100600", "y", "n", 1_766_100_600));
+        agg.ingest(r, Source::Push, 1_766_100_000_000);
+        agg.evaluate(1_766_100_000_000);
+        assert!(agg.control("a").reason.unwrap().contains("combined"));
+
+        // Nothing resolves the position: its market ending lifts the pause
+        agg.evaluate(1_766_100_600_000);
+        let status = agg.status(1_766_100_600_000);
+        assert_eq!(status.bots[0].open_positions, 1);
+        assert!((status.exposure - 50.0).abs() < 1e-9);
+        assert!(!agg.control("a").pause);
+    }
+
+    #[test]
+    fn test_report_from_store_rows() {
+        let mut rows = report("a", &[10.0, 20.0]).positions;
+        rows[1].resolve(true);
+        rows[1].resolved_at = Some("2026-10-15T12:00:00Z".into());
+        let r = BotReport::from_positions("a", rows, "2026-10-15");
+        assert_eq!(r.positions.len(), 1);
+        assert!((r.daily_pnl - 20.0).abs() < 1e-9);
+        assert_eq!(r.daily_pnl, r.all_time_pnl);
+        assert_eq!(serde_json::to_value(Control::default()).unwrap(), serde_json::json!({"pause": false}));
+    }
+}
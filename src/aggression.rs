@@ -0,0 +1,133 @@
+// src/aggression.rs
+// IOC price improvement - submit a few ticks through the ask so a one-tick move doesn't miss the fill
+
+use crate::metrics;
+use crate::types::{FastExecutionRequest, PriceCents};
+
+/// Highest price a leg may be sent at (a 100¢ contract can't pay out)
+const MAX_PRICE_CENTS: PriceCents = 99;
+
+/// Aggression configuration from environment
+#[derive(Debug, Clone)]
+pub struct AggressionConfig {
+    /// Ticks (1¢) above the observed ask each IOC leg is sent at (0 = at the ask)
+    pub offset_ticks: u8,
+
+    /// Fee-adjusted edge per contract that must survive the offset; the offset shrinks to fit
+    pub min_edge_cents: i16,
+}
+
+impl AggressionConfig {
+    pub fn from_env() -> Self {
+        Self {
+            offset_ticks: std::env::var("IOC_OFFSET_TICKS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+
+            min_edge_cents: std::env::var("IOC_MIN_EDGE_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.offset_ticks > 0
+    }
+
+    /// Limit prices for `req`: the largest offset up to IOC_OFFSET_TICKS at which paying the
+    /// limit on both legs (fees recomputed at the higher prices) still clears the minimum edge.
+    /// Returns the ticks applied and the request with its prices raised by them.
+    pub fn apply(&self, req: &FastExecutionRequest) -> (u8, FastExecutionRequest) {
+        if !self.enabled() {
+            return (0, *req);
+        }
+        (1..=self.offset_ticks).rev()
+            .map(|ticks| (ticks, offset(req, ticks)))
+            .find(|(_, r)| r.profit_cents() >= self.min_edge_cents)
+            .unwrap_or((0, *req))
+    }
+}
+
+fn offset(req: &FastExecutionRequest, ticks: u8) -> FastExecutionRequest {
+    FastExecutionRequest {
+        yes_price: req.yes_price.saturating_add(ticks as PriceCents).min(MAX_PRICE_CENTS),
+        no_price: req.no_price.saturating_add(ticks as PriceCents).min(MAX_PRICE_CENTS),
+        ..*req
+    }
+}
+
+/// Count one live execution under the offset it was sent with, so fill rate and realized
+/// edge can be compared across IOC_OFFSET_TICKS settings (`exec.offset_<k>.*`)
+pub fn record_outcome(ticks: u8, contracts: i64, matched: i64, profit_cents: i64) {
+    let prefix = format!("exec.offset_{}", ticks);
+    metrics::incr(&format!("{}.sent", prefix));
+    metrics::add(&format!("{}.contracts", prefix), contracts.max(0) as u64);
+    if matched > 0 {
+        metrics::incr(&format!("{}.filled", prefix));
+        metrics::add(&format!("{}.matched", prefix), matched as u64);
+    }
+    // Counters are unsigned: gains and losses are kept apart
+    if profit_cents >= 0 {
+        metrics::add(&format!("{}.edge_cents", prefix), profit_cents as u64);
+    } else {
+        metrics::add(&format!("{}.loss_cents", prefix), profit_cents.unsigned_abs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ArbType;
+
+    fn req(arb_type: ArbType, yes: PriceCents, no: PriceCents) -> FastExecutionRequest {
+        FastExecutionRequest {
+            market_id: 0,
+            yes_price: yes,
+            no_price: no,
+            yes_size: 1000,
+            no_size: 1000,
+            arb_type,
+            detected_ns: 0,
+            poly_fee_bps: 0,
+        }
+    }
+
+    fn cfg(ticks: u8, min_edge: i16) -> AggressionConfig {
+        AggressionConfig { offset_ticks: ticks, min_edge_cents: min_edge }
+    }
+
+    #[test]
+    fn test_offset_applied_when_edge_allows() {
+        // Poly-only has no fees: 40 + 50 leaves 10¢, two ticks per leg cost 4¢
+        let (ticks, r) = cfg(2, 1).apply(&req(ArbType::PolyOnly, 40, 50));
+        assert_eq!((ticks, r.yes_price, r.no_price), (2, 42, 52));
+        assert_eq!(r.profit_cents(), 6);
+        // Off: prices untouched
+        let (ticks, r) = cfg(0, 1).apply(&req(ArbType::PolyOnly, 40, 50));
+        assert_eq!((ticks, r.yes_price, r.no_price), (0, 40, 50));
+    }
+
+    #[test]
+    fn test_offset_shrinks_to_keep_min_edge() {
+        // 5¢ edge: 3 ticks would leave -1¢, 2 ticks leave 1¢
+        let (ticks, r) = cfg(3, 1).apply(&req(ArbType::PolyOnly, 45, 50));
+        assert_eq!((ticks, r.profit_cents()), (2, 1));
+        // Raising the minimum edge leaves no room at all
+        let (ticks, r) = cfg(3, 5).apply(&req(ArbType::PolyOnly, 45, 50));
+        assert_eq!((ticks, r.yes_price), (0, 45));
+        // Kalshi fees are recomputed at the offset price
+        let base = req(ArbType::PolyYesKalshiNo, 40, 50);
+        let (ticks, r) = cfg(5, 1).apply(&base);
+        assert!(ticks < 5);
+        assert!(r.profit_cents() >= 1);
+        assert!(offset(&base, ticks + 1).profit_cents() < 1);
+    }
+
+    #[test]
+    fn test_offset_capped_below_a_dollar() {
+        let r = offset(&req(ArbType::PolyOnly, 98, 1), 3);
+        assert_eq!((r.yes_price, r.no_price), (99, 4));
+    }
+}
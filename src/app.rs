@@ -0,0 +1,223 @@
+// src/app.rs
+// Shared process bootstrap for all binaries (.env, logging, credentials, clients)
+
+use anyhow::{Context, Result};
+use std::sync::Mutex;
+use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer, Registry};
+
+#[cfg(feature = "kalshi")]
+use crate::kalshi::{KalshiApiClient, KalshiConfig};
+use crate::exec_mode::{self, ExecMode};
+use crate::logging::{FileSink, JsonFormat, LogFormat, LoggingConfig, RotatingWriter};
+use crate::polymarket_clob::{PolymarketAsyncClient, PreparedCreds, SharedAsyncClient};
+use crate::storage;
+
+/// Polymarket CLOB API host
+pub const POLY_CLOB_HOST: &str = "https://clob.polymarket.com";
+/// Polygon chain ID
+pub const POLYGON_CHAIN_ID: u64 = 137;
+
+/// Start building the process environment: `app::bootstrap().log("updown_bot=info").init()`
+pub fn bootstrap() -> Bootstrap {
+    Bootstrap {
+        crate_level: "info",
+        directives: Vec::new(),
+        dotenv: true,
+    }
+}
+
+/// Per-binary overrides for the shared bootstrap
+#[derive(Debug, Clone)]
+pub struct Bootstrap {
+    crate_level: &'static str,
+    directives: Vec<String>,
+    dotenv: bool,
+}
+
+#[allow(dead_code)]
+impl Bootstrap {
+    /// Default level for `arb_bot` logs (RUST_LOG still wins)
+    pub fn crate_level(mut self, level: &'static str) -> Self {
+        self.crate_level = level;
+        self
+    }
+
+    /// Extra log directive, e.g. "updown_bot=info"
+    pub fn log(mut self, directive: &str) -> Self {
+        self.directives.push(directive.to_string());
+        self
+    }
+
+    /// Don't read `.env` (tools that must only see the real environment)
+    pub fn without_dotenv(mut self) -> Self {
+        self.dotenv = false;
+        self
+    }
+
+    fn filter(&self) -> EnvFilter {
+        let mut filter = EnvFilter::from_default_env()
+            .add_directive(format!("arb_bot={}", self.crate_level).parse().expect("valid log level"));
+        for directive in &self.directives {
+            filter = filter.add_directive(directive.parse().expect("valid log directive"));
+        }
+        filter
+    }
+
+    /// Console filter with bot.toml's level in place of the defaults (RUST_LOG still wins)
+    fn console_filter(&self, level: &str) -> EnvFilter {
+        self.sink_filter(EnvFilter::from_default_env(), level)
+    }
+
+    /// File filter: only bot.toml's level (dependencies at warn), independent of RUST_LOG
+    fn file_filter(&self, level: &str) -> EnvFilter {
+        self.sink_filter(EnvFilter::new("warn"), level)
+    }
+
+    /// `arb_bot` and every binary target in `directives` at `level`
+    fn sink_filter(&self, mut filter: EnvFilter, level: &str) -> EnvFilter {
+        let targets = std::iter::once("arb_bot")
+            .chain(self.directives.iter().map(|d| d.split('=').next().unwrap_or(d)));
+        for target in targets {
+            match format!("{}={}", target, level).parse() {
+                Ok(directive) => filter = filter.add_directive(directive),
+                Err(e) => eprintln!("[LOG] Ignoring level '{}' for {}: {}", level, target, e),
+            }
+        }
+        filter
+    }
+
+    fn file_layer(&self, sink: &FileSink) -> Result<Box<dyn Layer<Registry> + Send + Sync>> {
+        let path = storage::data_path(&sink.path);
+        let writer = Mutex::new(RotatingWriter::open(sink, &path)?);
+        let layer = fmt::layer().with_ansi(false).with_writer(writer);
+        let filter = self.file_filter(&sink.level);
+        Ok(match sink.format {
+            LogFormat::Json => layer.event_format(JsonFormat).with_filter(filter).boxed(),
+            LogFormat::Pretty => layer.with_filter(filter).boxed(),
+        })
+    }
+
+    /// Load `.env` (before logging, so RUST_LOG can come from it) and install the
+    /// console sink plus the file sink configured in bot.toml
+    pub fn init(self) -> App {
+        if self.dotenv {
+            dotenvy::dotenv().ok();
+        }
+        let config = LoggingConfig::load().unwrap_or_else(|e| {
+            eprintln!("[LOG] {:#} - logging to console only", e);
+            LoggingConfig::default()
+        });
+
+        let console_filter = match &config.console.level {
+            Some(level) => self.console_filter(level),
+            None => self.filter(),
+        };
+        let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![match config.console.format {
+            LogFormat::Pretty => fmt::layer().with_filter(console_filter).boxed(),
+            LogFormat::Json => fmt::layer().event_format(JsonFormat).with_filter(console_filter).boxed(),
+        }];
+        if let Some(sink) = &config.file {
+            match self.file_layer(sink) {
+                Ok(layer) => layers.push(layer),
+                Err(e) => eprintln!("[LOG] File sink disabled: {:#}", e),
+            }
+        }
+
+        // A subscriber may already be installed (tests, embedding) - keep it
+        let _ = tracing_subscriber::registry().with(layers).try_init();
+        App { _private: () }
+    }
+}
+
+/// Polymarket signing key and funder wallet
+#[derive(Clone)]
+pub struct PolyCredentials {
+    pub private_key: String,
+    pub funder: String,
+}
+
+/// Handle returned by `bootstrap().init()`; builds clients from the environment
+pub struct App {
+    _private: (),
+}
+
+#[allow(dead_code)]
+impl App {
+    /// Execution mode from EXEC_MODE (or DRY_RUN / SHADOW_MODE), logged and published as gauges.
+    /// Defaults to a dry run; conflicting settings are an error.
+    pub fn exec_mode(&self) -> Result<ExecMode> {
+        exec_mode::start()
+    }
+
+    #[cfg(feature = "kalshi")]
+    pub fn kalshi_config(&self) -> Result<KalshiConfig> {
+        KalshiConfig::from_env()
+    }
+
+    #[cfg(feature = "kalshi")]
+    pub fn kalshi_client(&self) -> Result<KalshiApiClient> {
+        Ok(KalshiApiClient::new(self.kalshi_config()?))
+    }
+
+    pub fn poly_credentials(&self) -> Result<PolyCredentials> {
+        Ok(PolyCredentials {
+            private_key: std::env::var("POLY_PRIVATE_KEY").context("POLY_PRIVATE_KEY not set")?,
+            funder: std::env::var("POLY_FUNDER").context("POLY_FUNDER not set (your wallet address)")?,
+        })
+    }
+
+    /// Polymarket CLOB client with derived API credentials
+    pub async fn poly_client(&self) -> Result<SharedAsyncClient> {
+        let creds = self.poly_credentials()?;
+        info!("[POLYMARKET] Creating async client and deriving API credentials...");
+        let client = PolymarketAsyncClient::new(POLY_CLOB_HOST, POLYGON_CHAIN_ID, &creds.private_key, &creds.funder)?;
+        // Signed timestamps follow the exchange's clock; without a sync, responses refine it later
+        match client.sync_clock().await {
+            Ok(()) => info!("[POLYMARKET] Clock skew vs CLOB: {:+}ms", crate::clock_skew::signing_clock().skew_ms().unwrap_or(0)),
+            Err(e) => warn!("[POLYMARKET] Clock sync failed ({}); signing with local time until responses arrive", e),
+        }
+        let api_creds = client.derive_api_key(0).await?;
+        let prepared_creds = PreparedCreds::from_api_creds(&api_creds)?;
+        Ok(SharedAsyncClient::new(client, prepared_creds, POLYGON_CHAIN_ID))
+    }
+}
+
+/// Boolean env var: "1"/"true" on, "0"/"false" off, anything else the default
+pub fn env_flag(name: &str, default: bool) -> bool {
+    parse_flag(std::env::var(name).ok().as_deref(), default)
+}
+
+fn parse_flag(value: Option<&str>, default: bool) -> bool {
+    match value.map(|v| v.trim().to_lowercase()) {
+        Some(v) if v == "1" || v == "true" => true,
+        Some(v) if v == "0" || v == "false" => false,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flag() {
+        assert!(parse_flag(None, true));
+        assert!(!parse_flag(Some("0"), true));
+        assert!(parse_flag(Some("TRUE"), false));
+        assert!(!parse_flag(Some("false"), true));
+        // Typos keep the safe default
+        assert!(parse_flag(Some("ture"), true));
+    }
+
+    #[test]
+    fn test_bootstrap_overrides() {
+        let boot = bootstrap().crate_level("warn").log("updown_bot=debug").without_dotenv();
+        assert!(!boot.dotenv);
+        let filter = boot.filter().to_string();
+        assert!(filter.contains("arb_bot=warn"), "{}", filter);
+        assert!(filter.contains("updown_bot=debug"), "{}", filter);
+    }
+}
@@ -0,0 +1,242 @@
+// src/arb_confirm.rs
+// Time-weighted arb confirmation - ignore one-tick blips below the threshold
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Confirmation requirements (0 disables a requirement; both must hold when set)
+#[derive(Debug, Clone, Copy)]
+pub struct ArbConfirmConfig {
+    /// Consecutive updates the arb must survive before executing
+    pub min_updates: u32,
+
+    /// Milliseconds the arb must persist before executing
+    pub min_duration_ms: u64,
+}
+
+impl ArbConfirmConfig {
+    pub fn from_env() -> Self {
+        Self {
+            min_updates: std::env::var("ARB_CONFIRM_UPDATES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+
+            min_duration_ms: std::env::var("ARB_CONFIRM_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+
+    /// True when every detection executes immediately
+    pub fn is_instant(&self) -> bool {
+        self.min_updates <= 1 && self.min_duration_ms == 0
+    }
+
+    #[inline]
+    fn satisfied(&self, streak: u32, elapsed_ms: u64) -> bool {
+        streak >= self.min_updates && elapsed_ms >= self.min_duration_ms
+    }
+}
+
+/// Process-wide confirmation config (read once from environment)
+pub fn config() -> &'static ArbConfirmConfig {
+    static CACHED: OnceLock<ArbConfirmConfig> = OnceLock::new();
+    CACHED.get_or_init(ArbConfirmConfig::from_env)
+}
+
+/// Monotonic nanoseconds since first use, shared by all feeds
+#[inline]
+fn now_ns() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// Lock-free per-market persistence state (lives in `AtomicMarketState`)
+#[derive(Debug, Default)]
+pub struct ArbPersistence {
+    streak: AtomicU32,
+    first_seen_ns: AtomicU64,
+}
+
+impl ArbPersistence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one book update; returns true when the arb is confirmed
+    #[inline]
+    pub fn observe(&self, has_arb: bool) -> bool {
+        let cfg = config();
+        if cfg.is_instant() {
+            return has_arb;
+        }
+        self.observe_at(has_arb, now_ns(), cfg)
+    }
+
+    /// Same as `observe` with explicit clock and config (for tests)
+    pub fn observe_at(&self, has_arb: bool, now_ns: u64, cfg: &ArbConfirmConfig) -> bool {
+        if !has_arb {
+            self.streak.store(0, Ordering::Relaxed);
+            return false;
+        }
+
+        let streak = self.streak.fetch_add(1, Ordering::AcqRel) + 1;
+        if streak == 1 {
+            self.first_seen_ns.store(now_ns, Ordering::Release);
+        }
+        let first = self.first_seen_ns.load(Ordering::Acquire);
+        let elapsed_ms = now_ns.saturating_sub(first) / 1_000_000;
+        cfg.satisfied(streak, elapsed_ms)
+    }
+}
+
+/// Single-threaded persistence state for callers that already hold a lock
+#[derive(Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct ArbStreak {
+    streak: u32,
+    first_seen: Option<Instant>,
+}
+
+#[allow(dead_code)]
+impl ArbStreak {
+    /// Record one book update; returns true when the arb is confirmed
+    pub fn observe(&mut self, has_arb: bool) -> bool {
+        self.observe_at(has_arb, Instant::now(), config())
+    }
+
+    pub fn observe_at(&mut self, has_arb: bool, now: Instant, cfg: &ArbConfirmConfig) -> bool {
+        if !has_arb {
+            self.streak = 0;
+            self.first_seen = None;
+            return false;
+        }
+
+        self.streak = self.streak.saturating_add(1);
+        let first = *self.first_seen.get_or_insert(now);
+        let elapsed_ms = now.saturating_duration_since(first).as_millis() as u64;
+        cfg.satisfied(self.streak, elapsed_ms)
+    }
+}
+
+/// Minimum best-ask depth each side must show in a snapshot before a new market is tradable
+/// (WARMUP_MIN_DEPTH, contracts)
+#[allow(dead_code)]
+pub fn warmup_min_depth() -> f64 {
+    static CACHED: OnceLock<f64> = OnceLock::new();
+    *CACHED.get_or_init(|| {
+        std::env::var("WARMUP_MIN_DEPTH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0)
+    })
+}
+
+/// Warm-up gate for a freshly subscribed market: both sides must have received a
+/// snapshot with at least the minimum depth before the market is eligible for execution.
+/// A half-loaded market (one side priced, the other still zero) never counts as an arb.
+#[derive(Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct BookWarmup {
+    yes_ready: bool,
+    no_ready: bool,
+}
+
+#[allow(dead_code)]
+impl BookWarmup {
+    /// Record a snapshot for one side; returns true on the update that completes warm-up
+    pub fn observe(&mut self, is_yes: bool, depth: f64, min_depth: f64) -> bool {
+        let was_warm = self.is_warm();
+        if depth >= min_depth {
+            if is_yes {
+                self.yes_ready = true;
+            } else {
+                self.no_ready = true;
+            }
+        }
+        !was_warm && self.is_warm()
+    }
+
+    pub fn is_warm(&self) -> bool {
+        self.yes_ready && self.no_ready
+    }
+
+    /// One side's asks vanished - that side warms up again; returns true if the book was warm
+    pub fn clear(&mut self, is_yes: bool) -> bool {
+        let was_warm = self.is_warm();
+        if is_yes {
+            self.yes_ready = false;
+        } else {
+            self.no_ready = false;
+        }
+        was_warm
+    }
+
+    /// Books invalidated (e.g. reconnect) - warm up again
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_requires_consecutive_updates() {
+        let cfg = ArbConfirmConfig { min_updates: 3, min_duration_ms: 0 };
+        let p = ArbPersistence::new();
+
+        assert!(!p.observe_at(true, 0, &cfg));
+        assert!(!p.observe_at(true, 1, &cfg));
+        assert!(p.observe_at(true, 2, &cfg));
+
+        // A non-arb update resets the streak
+        assert!(!p.observe_at(false, 3, &cfg));
+        assert!(!p.observe_at(true, 4, &cfg));
+    }
+
+    #[test]
+    fn test_requires_duration() {
+        let cfg = ArbConfirmConfig { min_updates: 1, min_duration_ms: 50 };
+        let mut s = ArbStreak::default();
+        let t0 = Instant::now();
+
+        assert!(!s.observe_at(true, t0, &cfg));
+        assert!(!s.observe_at(true, t0 + Duration::from_millis(20), &cfg));
+        assert!(s.observe_at(true, t0 + Duration::from_millis(60), &cfg));
+
+        assert!(!s.observe_at(false, t0 + Duration::from_millis(70), &cfg));
+        assert!(!s.observe_at(true, t0 + Duration::from_millis(80), &cfg));
+    }
+
+    #[test]
+    fn test_warmup_needs_both_sides_with_depth() {
+        let mut w = BookWarmup::default();
+        assert!(!w.observe(true, 50.0, 5.0));
+        assert!(!w.observe(false, 2.0, 5.0)); // too thin
+        assert!(!w.is_warm());
+        assert!(w.observe(false, 10.0, 5.0));
+        assert!(!w.observe(true, 50.0, 5.0)); // only reported once
+
+        w.reset();
+        assert!(!w.is_warm());
+    }
+
+    #[test]
+    fn test_one_sided_book_cools_until_repopulated() {
+        let mut w = BookWarmup::default();
+        w.observe(true, 50.0, 5.0);
+        w.observe(false, 50.0, 5.0);
+        assert!(w.clear(false));
+        assert!(!w.is_warm());
+        // Already cold: not reported again
+        assert!(!w.clear(false));
+        assert!(!w.observe(true, 50.0, 5.0));
+        assert!(w.observe(false, 10.0, 5.0));
+    }
+}
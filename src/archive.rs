@@ -0,0 +1,410 @@
+// src/archive.rs
+// Data file retention - rolls append-only files daily, gzips old segments, deletes expired ones
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+use crate::logging::LoggingConfig;
+use crate::cost_model;
+use crate::metrics;
+use crate::opportunities::DEFAULT_OPP_FILE;
+use crate::runtimes;
+use crate::storage;
+
+const DAY_SECS: u64 = 86_400;
+
+/// Archival configuration from environment
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    /// Seconds between archival runs (0 = off)
+    pub interval_secs: u64,
+}
+
+impl ArchiveConfig {
+    pub fn from_env() -> Self {
+        Self {
+            interval_secs: std::env::var("ARCHIVE_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DAY_SECS),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.interval_secs > 0
+    }
+}
+
+/// How the live file of a data set is turned into a segment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Roll {
+    /// The writer rotates it itself (log sink)
+    None,
+    /// Writer reopens per append: rename, the next append starts a fresh file
+    Rename,
+    /// Writer keeps the file open in append mode: copy, then truncate in place
+    CopyTruncate,
+}
+
+/// Per-data-type retention (days, 0 = never)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    pub compress_after_days: u64,
+    pub delete_after_days: u64,
+}
+
+impl RetentionPolicy {
+    /// ARCHIVE_<NAME>_COMPRESS_DAYS / ARCHIVE_<NAME>_DELETE_DAYS over the given defaults
+    fn from_env(name: &str, compress_after_days: u64, delete_after_days: u64) -> Self {
+        let var = |field: &str| format!("ARCHIVE_{}_{}_DAYS", name.to_uppercase(), field);
+        Self {
+            compress_after_days: std::env::var(var("COMPRESS"))
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(compress_after_days),
+
+            delete_after_days: std::env::var(var("DELETE"))
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(delete_after_days),
+        }
+    }
+
+    /// What to do with a segment this old
+    pub fn action(&self, age_days: u64, compressed: bool) -> Action {
+        if self.delete_after_days > 0 && age_days >= self.delete_after_days {
+            Action::Delete
+        } else if !compressed && self.compress_after_days > 0 && age_days >= self.compress_after_days {
+            Action::Compress
+        } else {
+            Action::Keep
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Keep,
+    Compress,
+    Delete,
+}
+
+/// A live data file plus its segments (`<file>.<timestamp>[.gz]` next to it)
+#[derive(Debug, Clone)]
+pub struct DataSet {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub roll: Roll,
+    pub policy: RetentionPolicy,
+}
+
+impl DataSet {
+    fn new(name: &'static str, path: PathBuf, roll: Roll, compress_after_days: u64, delete_after_days: u64) -> Self {
+        Self { name, path, roll, policy: RetentionPolicy::from_env(name, compress_after_days, delete_after_days) }
+    }
+}
+
+/// The files this bot grows: rotated logs, the event audit log, opportunity history and shadow trades.
+/// (The intent journal is rewritten in place and stays small; state dumps prune themselves.)
+pub fn default_sets() -> Vec<DataSet> {
+    let mut sets = Vec::new();
+    if let Some(sink) = LoggingConfig::load().ok().and_then(|c| c.file) {
+        sets.push(DataSet::new("logs", storage::data_path(&sink.path), Roll::None, 1, 14));
+    }
+    if let Ok(path) = std::env::var("EVENT_LOG_FILE") {
+        sets.push(DataSet::new("audit", PathBuf::from(path), Roll::CopyTruncate, 1, 30));
+    }
+    let opps = std::env::var("OPP_FILE").unwrap_or_else(|_| DEFAULT_OPP_FILE.to_string());
+    sets.push(DataSet::new("opportunities", storage::data_path(opps), Roll::CopyTruncate, 1, 90));
+    let shadow = std::env::var("SHADOW_FILE").unwrap_or_else(|_| "shadow_trades.csv".to_string());
+    sets.push(DataSet::new("shadow", storage::data_path(shadow), Roll::Rename, 1, 30));
+    let samples = std::env::var("COST_SAMPLES_FILE").unwrap_or_else(|_| cost_model::DEFAULT_SAMPLES_FILE.to_string());
+    sets.push(DataSet::new("fill_samples", storage::data_path(samples), Roll::CopyTruncate, 1, 180));
+    sets
+}
+
+fn dir_of(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Rolled segments of `path`, oldest first
+pub fn segments(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name() else { return Vec::new() };
+    let prefix = format!("{}.", name.to_string_lossy());
+    let Ok(entries) = std::fs::read_dir(dir_of(path)) else { return Vec::new() };
+    let mut found: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let n = e.file_name().to_string_lossy().to_string();
+            n.starts_with(&prefix) && !n.ends_with(".tmp") && !n.ends_with(".lock")
+        })
+        .map(|e| e.path())
+        .collect();
+    found.sort();
+    found
+}
+
+/// Move the live file's contents to `<file>.<timestamp>`; None if it was empty or missing
+pub fn roll(path: &Path, mode: Roll) -> Result<Option<PathBuf>> {
+    if mode == Roll::None || std::fs::metadata(path).map(|m| m.len()).unwrap_or(0) == 0 {
+        return Ok(None);
+    }
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    let mut n = 0;
+    let target = loop {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", stamp));
+        if n > 0 {
+            name.push(format!("-{}", n));
+        }
+        let candidate = PathBuf::from(name);
+        if !candidate.exists() {
+            break candidate;
+        }
+        n += 1;
+    };
+    match mode {
+        Roll::Rename => std::fs::rename(path, &target)
+            .with_context(|| format!("renaming {}", path.display()))?,
+        Roll::CopyTruncate => {
+            // Lines appended between the copy and the truncate are lost (as with logrotate's copytruncate)
+            std::fs::copy(path, &target).with_context(|| format!("copying {}", path.display()))?;
+            std::fs::OpenOptions::new().write(true).open(path)?.set_len(0)?;
+        }
+        Roll::None => unreachable!(),
+    }
+    Ok(Some(target))
+}
+
+/// gzip in place (`<file>` → `<file>.gz`, modification time kept)
+fn compress(path: &Path) -> Result<()> {
+    let status = std::process::Command::new("gzip").arg("-f").arg(path).status()
+        .context("running gzip")?;
+    anyhow::ensure!(status.success(), "gzip exited with {}", status);
+    Ok(())
+}
+
+/// Segments (oldest first, gzipped ones decompressed) followed by the live file, for readers
+/// of history files that archival has rolled
+pub fn read_all(path: &Path) -> Result<String> {
+    let mut contents = String::new();
+    for segment in segments(path) {
+        if segment.extension().is_some_and(|e| e == "gz") {
+            let out = std::process::Command::new("gzip").arg("-dc").arg(&segment).output()
+                .context("running gzip")?;
+            anyhow::ensure!(out.status.success(), "gzip -dc {} failed", segment.display());
+            contents.push_str(&String::from_utf8_lossy(&out.stdout));
+        } else {
+            contents.push_str(&std::fs::read_to_string(&segment)?);
+        }
+    }
+    match std::fs::read_to_string(path) {
+        Ok(live) => contents.push_str(&live),
+        Err(e) if contents.is_empty() => return Err(e.into()),
+        Err(_) => {}
+    }
+    Ok(contents)
+}
+
+fn age_days(path: &Path, now: SystemTime) -> u64 {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+        .and_then(|t| now.duration_since(t).ok())
+        .map(|d| d.as_secs() / DAY_SECS)
+        .unwrap_or(0)
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Total size of a directory tree
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else { return 0 };
+    entries.filter_map(|e| e.ok()).map(|e| match e.file_type() {
+        Ok(t) if t.is_dir() => dir_size(&e.path()),
+        _ => e.metadata().map(|m| m.len()).unwrap_or(0),
+    }).sum()
+}
+
+/// What one run did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveReport {
+    pub rolled: usize,
+    pub compressed: usize,
+    pub deleted: usize,
+    /// Bytes on disk per data set (live file + segments) after the run
+    pub bytes: Vec<(&'static str, u64)>,
+}
+
+/// Roll, compress and expire every set once
+pub fn run_once(sets: &[DataSet], now: SystemTime) -> ArchiveReport {
+    let mut report = ArchiveReport::default();
+    for set in sets {
+        match roll(&set.path, set.roll) {
+            Ok(Some(segment)) => {
+                debug!("[ARCHIVE] Rolled {} → {}", set.path.display(), segment.display());
+                report.rolled += 1;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("[ARCHIVE] Roll of {} failed: {}", set.path.display(), e),
+        }
+
+        for segment in segments(&set.path) {
+            let compressed = segment.extension().is_some_and(|e| e == "gz");
+            match set.policy.action(age_days(&segment, now), compressed) {
+                Action::Keep => {}
+                Action::Delete => match std::fs::remove_file(&segment) {
+                    Ok(()) => report.deleted += 1,
+                    Err(e) => warn!("[ARCHIVE] Delete of {} failed: {}", segment.display(), e),
+                },
+                Action::Compress => match compress(&segment) {
+                    Ok(()) => report.compressed += 1,
+                    Err(e) => warn!("[ARCHIVE] Compress of {} failed: {}", segment.display(), e),
+                },
+            }
+        }
+
+        let bytes = file_size(&set.path) + segments(&set.path).iter().map(|s| file_size(s)).sum::<u64>();
+        metrics::set_gauge(&format!("disk.{}.bytes", set.name), bytes as i64);
+        report.bytes.push((set.name, bytes));
+    }
+    metrics::set_gauge("disk.data_dir.bytes", dir_size(storage::data_dir()) as i64);
+    metrics::add("archive.compressed", report.compressed as u64);
+    metrics::add("archive.deleted", report.deleted as u64);
+    report
+}
+
+/// Claim archival of the data directory (`archive.lock` in DATA_DIR). Both bots share
+/// DATA_DIR and the same data files; only the holder rolls and expires them.
+fn claim_owner(data_dir: &Path) -> Option<storage::FileLock> {
+    storage::FileLock::acquire(data_dir.join("archive")).ok()
+}
+
+/// Run archival now and then every ARCHIVE_INTERVAL_SECS, while this process owns it
+pub fn spawn(config: ArchiveConfig, sets: Vec<DataSet>) {
+    if !config.enabled() || sets.is_empty() {
+        return;
+    }
+    info!("[ARCHIVE] Retention on {} every {}h",
+          sets.iter().map(|s| s.name).collect::<Vec<_>>().join(", "), config.interval_secs / 3600);
+    runtimes::spawn_bookkeeping(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        // Held for the life of the task; a bot that loses the race takes over if the owner exits
+        let mut owner = None;
+        loop {
+            ticker.tick().await;
+            if owner.is_none() {
+                owner = claim_owner(storage::data_dir());
+                if owner.is_none() {
+                    debug!("[ARCHIVE] Another process owns archival of {}", storage::data_dir().display());
+                    continue;
+                }
+            }
+            let sets = sets.clone();
+            let Ok(report) = tokio::task::spawn_blocking(move || run_once(&sets, SystemTime::now())).await else {
+                continue;
+            };
+            let total: u64 = report.bytes.iter().map(|(_, b)| b).sum();
+            info!("[ARCHIVE] 🗄️  Rolled {}, compressed {}, deleted {} | {:.1} MB on disk",
+                  report.rolled, report.compressed, report.deleted, total as f64 / 1_048_576.0);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(compress: u64, delete: u64) -> RetentionPolicy {
+        RetentionPolicy { compress_after_days: compress, delete_after_days: delete }
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("archive_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_policy_actions() {
+        let p = policy(1, 30);
+        assert_eq!(p.action(0, false), Action::Keep);
+        assert_eq!(p.action(1, false), Action::Compress);
+        assert_eq!(p.action(5, true), Action::Keep);
+        assert_eq!(p.action(30, true), Action::Delete);
+        // 0 = never
+        assert_eq!(policy(0, 0).action(365, false), Action::Keep);
+    }
+
+    #[test]
+    fn test_archival_has_one_owner() {
+        let dir = test_dir("owner");
+        let owner = claim_owner(&dir).unwrap();
+        assert!(claim_owner(&dir).is_none());
+        drop(owner);
+        assert!(claim_owner(&dir).is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_roll_modes() {
+        let dir = test_dir("roll");
+        let opps = dir.join("opportunities.jsonl");
+        std::fs::write(&opps, "{\"a\":1}\n").unwrap();
+        std::fs::write(dir.join("opportunities.jsonl.tmp"), "x").unwrap();
+
+        // Copy-truncate keeps the live file (an open append handle continues at offset 0)
+        let segment = roll(&opps, Roll::CopyTruncate).unwrap().unwrap();
+        assert_eq!(std::fs::read_to_string(&segment).unwrap(), "{\"a\":1}\n");
+        assert_eq!(file_size(&opps), 0);
+        assert_eq!(segments(&opps), vec![segment]);
+        // Nothing to roll
+        assert_eq!(roll(&opps, Roll::CopyTruncate).unwrap(), None);
+
+        let shadow = dir.join("shadow_trades.csv");
+        std::fs::write(&shadow, "ts,market\n1,x\n").unwrap();
+        roll(&shadow, Roll::Rename).unwrap().unwrap();
+        assert!(!shadow.exists());
+        assert_eq!(segments(&shadow).len(), 1);
+
+        // Readers see rolled history first, then the live file
+        std::fs::write(&opps, "{\"a\":2}\n").unwrap();
+        assert_eq!(read_all(&opps).unwrap(), "{\"a\":1}\n{\"a\":2}\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_run_compresses_and_expires_segments() {
+        let dir = test_dir("run");
+        let path = dir.join("audit.jsonl");
+        let now = SystemTime::now();
+        let aged = |name: &str, days: u64| {
+            let p = dir.join(name);
+            std::fs::write(&p, "line\n".repeat(100)).unwrap();
+            let file = std::fs::File::options().write(true).open(&p).unwrap();
+            file.set_modified(now - Duration::from_secs(days * DAY_SECS + 60)).unwrap();
+            p
+        };
+        let fresh = aged("audit.jsonl.20250103-000000", 0);
+        let old = aged("audit.jsonl.20250102-000000", 2);
+        let expired = aged("audit.jsonl.20250101-000000.gz", 40);
+
+        let set = DataSet { name: "audit", path: path.clone(), roll: Roll::CopyTruncate, policy: policy(1, 30) };
+        let report = run_once(&[set], now);
+        assert_eq!((report.rolled, report.deleted), (0, 1));
+        assert!(fresh.exists());
+        assert!(!expired.exists());
+        if report.compressed == 1 {
+            assert!(!old.exists());
+            assert!(dir.join("audit.jsonl.20250102-000000.gz").exists());
+        }
+        assert_eq!(report.bytes[0].0, "audit");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
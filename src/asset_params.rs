@@ -0,0 +1,230 @@
+// src/asset_params.rs
+// Per-asset trading parameters - arb threshold, size limits and no-trade window from bot.toml [assets]
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use crate::logging;
+
+/// Trading parameters of one asset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AssetParams {
+    /// Trade when YES + NO asks sum below this (dollars)
+    pub threshold: f64,
+    /// Smallest size worth sending (contracts per leg)
+    pub min_size: f64,
+    /// Largest size sent per leg (contracts)
+    pub max_size: f64,
+    /// No new trades this close to the end (None = the series default)
+    pub no_trade_secs: Option<u64>,
+    /// Child orders a large arb is split into (1 = one full-size order)
+    pub ladder_children: u32,
+    /// Smallest size that is laddered (contracts per leg)
+    pub ladder_min_size: f64,
+    /// Pause between child orders
+    pub ladder_delay_ms: u64,
+}
+
+impl Default for AssetParams {
+    fn default() -> Self {
+        Self {
+            threshold: 0.995,
+            min_size: 1.0,
+            max_size: 50.0,
+            no_trade_secs: None,
+            ladder_children: 1,
+            ladder_min_size: 25.0,
+            ladder_delay_ms: 100,
+        }
+    }
+}
+
+impl AssetParams {
+    /// Whether a size this large is split into child orders
+    pub fn ladders(&self, contracts: f64) -> bool {
+        self.ladder_children > 1 && contracts >= self.ladder_min_size
+    }
+
+    /// No-trade window before the end, falling back to the series' own
+    pub fn no_trade_secs_or(&self, series_default: u64) -> u64 {
+        self.no_trade_secs.unwrap_or(series_default)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.threshold > 0.0 && self.threshold <= 1.0) {
+            return Err(anyhow!("threshold {} must be in (0, 1]", self.threshold));
+        }
+        if self.min_size <= 0.0 || self.max_size < self.min_size {
+            return Err(anyhow!("need 0 < min_size <= max_size (got {} / {})", self.min_size, self.max_size));
+        }
+        if !(1..=5).contains(&self.ladder_children) {
+            return Err(anyhow!("ladder_children {} must be 1-5", self.ladder_children));
+        }
+        if self.ladder_delay_ms > 5_000 {
+            return Err(anyhow!("ladder_delay_ms {} is over 5000 - the edge would be long gone", self.ladder_delay_ms));
+        }
+        Ok(())
+    }
+}
+
+/// One `[assets.<name>]` table: unset fields inherit from `[assets.default]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawParams {
+    threshold: Option<f64>,
+    min_size: Option<f64>,
+    max_size: Option<f64>,
+    no_trade_secs: Option<u64>,
+    ladder_children: Option<u32>,
+    ladder_min_size: Option<f64>,
+    ladder_delay_ms: Option<u64>,
+}
+
+impl RawParams {
+    fn over(&self, base: AssetParams) -> AssetParams {
+        AssetParams {
+            threshold: self.threshold.unwrap_or(base.threshold),
+            min_size: self.min_size.unwrap_or(base.min_size),
+            max_size: self.max_size.unwrap_or(base.max_size),
+            no_trade_secs: self.no_trade_secs.or(base.no_trade_secs),
+            ladder_children: self.ladder_children.unwrap_or(base.ladder_children),
+            ladder_min_size: self.ladder_min_size.unwrap_or(base.ladder_min_size),
+            ladder_delay_ms: self.ladder_delay_ms.unwrap_or(base.ladder_delay_ms),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BotToml {
+    #[serde(default)]
+    assets: HashMap<String, RawParams>,
+}
+
+/// Parameters for every asset: `[assets.default]` plus per-asset overrides
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetTable {
+    pub default: AssetParams,
+    pub assets: HashMap<String, AssetParams>,
+}
+
+impl AssetTable {
+    /// `[assets]` section of BOT_CONFIG (default bot.toml); missing file or section = built-in defaults
+    pub fn load() -> Result<Self> {
+        let path = logging::config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("invalid [assets] in {}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut raw = toml::from_str::<BotToml>(contents)?.assets;
+        let default = raw.remove("default").unwrap_or_default().over(AssetParams::default());
+        default.validate().context("[assets.default]")?;
+        let assets = raw.into_iter()
+            .map(|(name, params)| {
+                let params = params.over(default);
+                params.validate().with_context(|| format!("[assets.{}]", name))?;
+                Ok((name.to_lowercase(), params))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(Self { default, assets })
+    }
+
+    pub fn get(&self, asset: &str) -> AssetParams {
+        self.assets.get(&asset.to_lowercase()).copied().unwrap_or(self.default)
+    }
+}
+
+fn global() -> &'static OnceLock<AssetTable> {
+    static TABLE: OnceLock<AssetTable> = OnceLock::new();
+    &TABLE
+}
+
+/// Process-wide table (loaded by `start`; without it, loaded on first use)
+pub fn table() -> &'static AssetTable {
+    global().get_or_init(|| AssetTable::load().unwrap_or_else(|e| {
+        warn!("[ASSETS] {:#} - using default parameters for every asset", e);
+        AssetTable::default()
+    }))
+}
+
+/// Parameters of `asset` (case-insensitive)
+pub fn params(asset: &str) -> AssetParams {
+    table().get(asset)
+}
+
+fn describe(p: &AssetParams) -> String {
+    let ladder = if p.ladder_children > 1 {
+        format!(", {} child orders {}ms apart from {:.0}", p.ladder_children, p.ladder_delay_ms, p.ladder_min_size)
+    } else {
+        String::new()
+    };
+    format!("threshold <{:.1}¢ ({:.1}% profit), size {:.0}-{:.0} per leg{}{}", p.threshold * 100.0, (1.0 - p.threshold) * 100.0,
+            p.min_size, p.max_size, p.no_trade_secs.map(|s| format!(", no trades in last {}s", s)).unwrap_or_default(), ladder)
+}
+
+/// Load the table (an invalid [assets] is a startup error) and log the defaults and each override
+pub fn start() -> Result<()> {
+    let _ = global().set(AssetTable::load()?);
+    let table = table();
+    info!("[ASSETS] default: {}", describe(&table.default));
+    let mut names: Vec<&String> = table.assets.keys().collect();
+    names.sort();
+    for name in names {
+        info!("[ASSETS] {}: {}", name.to_uppercase(), describe(&table.assets[name]));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_inherit_defaults() {
+        let table = AssetTable::parse(r#"
+            [assets.default]
+            max_size = 100.0
+
+            [assets.BTC]
+            max_size = 250.0
+
+            [assets.xrp]
+            threshold = 0.98
+            no_trade_secs = 20
+            ladder_children = 3
+        "#).unwrap();
+        assert_eq!(table.get("btc"), AssetParams { max_size: 250.0, ..AssetParams::default() });
+        assert_eq!(table.get("XRP"), AssetParams {
+            threshold: 0.98, max_size: 100.0, no_trade_secs: Some(20), ladder_children: 3, ..AssetParams::default()
+        });
+        assert!(table.get("xrp").ladders(30.0) && !table.get("xrp").ladders(10.0) && !table.get("btc").ladders(30.0));
+        // Not listed: the [assets.default] values
+        assert_eq!(table.get("eth").max_size, 100.0);
+        assert_eq!(table.get("eth").no_trade_secs_or(10), 10);
+        assert_eq!(table.get("xrp").no_trade_secs_or(10), 20);
+    }
+
+    #[test]
+    fn test_missing_section_is_builtin_defaults() {
+        let table = AssetTable::parse("[schedule]\ntimezone = \"utc\"\n").unwrap();
+        assert_eq!(table, AssetTable::default());
+        assert_eq!(table.get("sol"), AssetParams::default());
+    }
+
+    #[test]
+    fn test_invalid_params_rejected() {
+        assert!(AssetTable::parse("[assets.btc]\nthreshold = 1.2\n").is_err());
+        assert!(AssetTable::parse("[assets.btc]\nmin_size = 60.0\n").is_err());
+        assert!(AssetTable::parse("[assets.default]\nmin_size = 0.0\n").is_err());
+        assert!(AssetTable::parse("[assets.btc]\nladder_children = 0\n").is_err());
+        assert!(AssetTable::parse("[assets.btc]\nladder_delay_ms = 60000\n").is_err());
+        // Typos aren't silently ignored
+        assert!(AssetTable::parse("[assets.btc]\nmax_sise = 10.0\n").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+// src/balances.rs
+// Venue balances - Polymarket USDC and Kalshi cash/exposure, polled on an interval with staleness
+
+use anyhow::Result;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::kalshi::KalshiApiClient;
+use crate::metrics;
+use crate::polymarket_clob::{PolymarketOrderResponse, SharedAsyncClient};
+
+/// Balance polling configuration from environment
+#[derive(Debug, Clone)]
+pub struct BalanceConfig {
+    /// Seconds between balance fetches (BALANCE_POLL_SECS, 0 = off)
+    pub poll_secs: u64,
+
+    /// A balance older than this is flagged stale (BALANCE_STALE_SECS)
+    pub stale_secs: u64,
+}
+
+impl BalanceConfig {
+    pub fn from_env() -> Self {
+        Self {
+            poll_secs: std::env::var("BALANCE_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+
+            stale_secs: std::env::var("BALANCE_STALE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(180),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.poll_secs > 0
+    }
+}
+
+/// Last good balance of one venue (dollars)
+#[derive(Debug, Clone, PartialEq)]
+pub struct VenueBalance {
+    /// Spendable cash: USDC in the wallet (Poly) / account cash (Kalshi)
+    pub cash: f64,
+    /// Poly: USDC committed to resting buy orders; Kalshi: value of open positions
+    pub committed: f64,
+    /// Unix seconds of the last successful fetch
+    pub fetched_at: i64,
+    /// Error of the most recent fetch, if it failed (values above are then older)
+    pub last_error: Option<String>,
+}
+
+impl VenueBalance {
+    pub fn new(cash: f64, committed: f64, fetched_at: i64) -> Self {
+        Self { cash, committed, fetched_at, last_error: None }
+    }
+
+    pub fn age_secs(&self, now: i64) -> u64 {
+        now.saturating_sub(self.fetched_at).max(0) as u64
+    }
+
+    pub fn is_stale(&self, now: i64, stale_secs: u64) -> bool {
+        self.age_secs(now) > stale_secs
+    }
+
+    pub fn total(&self) -> f64 {
+        self.cash + self.committed
+    }
+}
+
+/// Both venues' balances (None until the first successful fetch)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Balances {
+    pub poly: Option<VenueBalance>,
+    pub kalshi: Option<VenueBalance>,
+}
+
+impl Balances {
+    /// One-line view: cash and committed per venue, total, and which venues are stale
+    pub fn summary(&self, now: i64, stale_secs: u64) -> String {
+        let age = |b: &VenueBalance| {
+            if b.is_stale(now, stale_secs) {
+                format!(" ⚠️ STALE {}s", b.age_secs(now))
+            } else {
+                String::new()
+            }
+        };
+        let poly = match &self.poly {
+            Some(b) => format!("Poly ${:.2} USDC + ${:.2} in orders{}", b.cash, b.committed, age(b)),
+            None => "Poly n/a".to_string(),
+        };
+        let kalshi = match &self.kalshi {
+            Some(b) => format!("Kalshi ${:.2} cash + ${:.2} exposure{}", b.cash, b.committed, age(b)),
+            None => "Kalshi n/a".to_string(),
+        };
+        let total: f64 = self.poly.iter().chain(self.kalshi.iter()).fold(0.0, |sum, b| sum + b.total());
+        format!("💰 {} | {} | total ${:.2}", poly, kalshi, total)
+    }
+}
+
+/// USDC tied up in resting buy orders (sells lock tokens, not cash)
+pub fn committed_in_orders(orders: &[PolymarketOrderResponse]) -> f64 {
+    orders.iter()
+        .filter(|o| o.side.eq_ignore_ascii_case("BUY"))
+        .fold(0.0, |sum, o| {
+            let price: f64 = o.price.parse().unwrap_or(0.0);
+            let original: f64 = o.original_size.parse().unwrap_or(0.0);
+            let matched: f64 = o.size_matched.parse().unwrap_or(0.0);
+            sum + price * (original - matched).max(0.0)
+        })
+}
+
+fn global() -> &'static RwLock<Balances> {
+    static BALANCES: OnceLock<RwLock<Balances>> = OnceLock::new();
+    BALANCES.get_or_init(|| RwLock::new(Balances::default()))
+}
+
+/// Latest polled balances
+pub fn snapshot() -> Balances {
+    global().read().unwrap().clone()
+}
+
+pub async fn fetch_poly(poly: &SharedAsyncClient, now: i64) -> Result<VenueBalance> {
+    let cash = poly.usdc_balance().await?;
+    let committed = committed_in_orders(&poly.open_orders().await?);
+    Ok(VenueBalance::new(cash, committed, now))
+}
+
+#[cfg(feature = "kalshi")]
+pub async fn fetch_kalshi(kalshi: &KalshiApiClient, now: i64) -> Result<VenueBalance> {
+    let b = kalshi.get_balance().await?;
+    Ok(VenueBalance::new(b.balance as f64 / 100.0, b.portfolio_value as f64 / 100.0, now))
+}
+
+/// Store a fetch result: a failure keeps the last good balance (which then ages into stale)
+fn update(slot: &mut Option<VenueBalance>, venue: &str, result: Result<VenueBalance>) {
+    match result {
+        Ok(b) => {
+            metrics::set_gauge(&format!("balance.{}.cash_cents", venue), (b.cash * 100.0).round() as i64);
+            metrics::set_gauge(&format!("balance.{}.committed_cents", venue), (b.committed * 100.0).round() as i64);
+            *slot = Some(b);
+        }
+        Err(e) => {
+            warn!("[BALANCE] {} balance fetch failed: {}", venue, e);
+            metrics::incr(&format!("balance.{}.errors", venue));
+            if let Some(b) = slot {
+                b.last_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Poll both venues every BALANCE_POLL_SECS into the shared snapshot
+pub fn spawn_poller(kalshi: Option<Arc<KalshiApiClient>>, poly: Option<Arc<SharedAsyncClient>>, config: BalanceConfig) {
+    if !config.enabled() {
+        return;
+    }
+    info!("[BALANCE] Polling venue balances every {}s (stale after {}s)", config.poll_secs, config.stale_secs);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.poll_secs));
+        loop {
+            ticker.tick().await;
+            let now = chrono::Utc::now().timestamp();
+            let poly_result = match &poly {
+                Some(p) => Some(fetch_poly(p, now).await),
+                None => None,
+            };
+            let kalshi_result = match &kalshi {
+                #[cfg(feature = "kalshi")]
+                Some(k) => Some(fetch_kalshi(k, now).await),
+                #[cfg(not(feature = "kalshi"))]
+                Some(k) => match **k {},
+                None => None,
+            };
+
+            let mut balances = global().write().unwrap();
+            if let Some(result) = poly_result {
+                update(&mut balances.poly, "poly", result);
+            }
+            if let Some(result) = kalshi_result {
+                update(&mut balances.kalshi, "kalshi", result);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(side: &str, price: &str, original: &str, matched: &str) -> PolymarketOrderResponse {
+        serde_json::from_value(serde_json::json!({
+            "id": "0x1", "status": "LIVE", "price": price, "side": side,
+            "size_matched": matched, "original_size": original,
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_committed_counts_unfilled_buys_only() {
+        let orders = vec![
+            order("BUY", "0.40", "100", "25"),  // 75 left × 40¢ = $30
+            order("SELL", "0.60", "50", "0"),
+            order("BUY", "0.10", "10", "10"),   // fully filled
+        ];
+        assert!((committed_in_orders(&orders) - 30.0).abs() < 1e-9);
+        assert_eq!(format!("{:.2}", committed_in_orders(&[])), "0.00");
+    }
+
+    #[test]
+    fn test_failed_fetch_keeps_last_balance_until_stale() {
+        let mut slot = None;
+        update(&mut slot, "poly", Ok(VenueBalance::new(120.0, 30.0, 1_000)));
+        update(&mut slot, "poly", Err(anyhow::anyhow!("timeout")));
+        let b = slot.clone().unwrap();
+        assert_eq!((b.cash, b.last_error.as_deref()), (120.0, Some("timeout")));
+        assert!(!b.is_stale(1_100, 180));
+        assert!(b.is_stale(1_200, 180));
+    }
+
+    #[test]
+    fn test_summary_flags_stale_venue() {
+        let balances = Balances {
+            poly: Some(VenueBalance::new(120.0, 30.0, 1_000)),
+            kalshi: Some(VenueBalance::new(200.0, 50.0, 800)),
+        };
+        let line = balances.summary(1_010, 180);
+        assert!(line.contains("Poly $120.00 USDC + $30.00 in orders |"));
+        assert!(line.contains("Kalshi $200.00 cash + $50.00 exposure ⚠️ STALE 210s"));
+        assert!(line.ends_with("total $400.00"));
+        assert_eq!(Balances::default().summary(0, 180), "💰 Poly n/a | Kalshi n/a | total $0.00");
+    }
+}
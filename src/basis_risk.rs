@@ -0,0 +1,185 @@
+// src/basis_risk.rs
+// Cross-venue basis risk - settlement time and reference-source mismatch between the two legs of a pair
+
+use tracing::warn;
+
+/// Basis risk configuration from environment
+#[derive(Debug, Clone)]
+pub struct BasisRiskConfig {
+    /// Settlement-time gap that adds one point of risk score (seconds)
+    pub gap_secs_per_point: f64,
+
+    /// Score added when the venues settle against different reference sources
+    pub oracle_score: f64,
+
+    /// Edge discount per point of score at execution (cents, 0 = off)
+    pub discount_cents: f64,
+
+    /// Pairs scoring above this are refused at discovery (0 = off)
+    pub max_score: f64,
+
+    /// Leagues known to settle against different reference data on each venue
+    pub mismatch_leagues: Vec<String>,
+}
+
+impl BasisRiskConfig {
+    pub fn from_env() -> Self {
+        Self {
+            gap_secs_per_point: std::env::var("BASIS_GAP_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600.0),
+
+            oracle_score: std::env::var("BASIS_ORACLE_SCORE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1.0),
+
+            discount_cents: std::env::var("BASIS_DISCOUNT_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+
+            max_score: std::env::var("BASIS_MAX_SCORE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+
+            mismatch_leagues: std::env::var("BASIS_MISMATCH_LEAGUES")
+                .ok()
+                .map(|v| v.split(',').map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Edge (cents) left after discounting a pair's risk score
+    pub fn net_edge(&self, edge_cents: f64, score: f64) -> f64 {
+        edge_cents - self.discount_cents.max(0.0) * score
+    }
+
+    pub fn refuses(&self, score: f64) -> bool {
+        self.max_score > 0.0 && score > self.max_score
+    }
+}
+
+/// How far apart the two legs of a pair settle, and on what
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasisRisk {
+    /// |Kalshi settlement - Polymarket end| (None if either venue didn't say)
+    pub settle_gap_secs: Option<u64>,
+    pub oracle_mismatch: bool,
+    pub score: f64,
+}
+
+impl BasisRisk {
+    /// Score a pair: one point per BASIS_GAP_SECS of settlement gap plus BASIS_ORACLE_SCORE
+    /// if the reference sources differ. An unknown time contributes nothing.
+    pub fn assess(
+        config: &BasisRiskConfig,
+        league: &str,
+        kalshi_settles_at: Option<i64>,
+        poly_ends_at: Option<i64>,
+        kalshi_sources: &[String],
+        poly_source: Option<&str>,
+    ) -> Self {
+        let settle_gap_secs = kalshi_settles_at.zip(poly_ends_at).map(|(k, p)| k.abs_diff(p));
+        let oracle_mismatch = config.mismatch_leagues.iter().any(|l| l.eq_ignore_ascii_case(league))
+            || sources_differ(kalshi_sources, poly_source);
+
+        let mut score = 0.0;
+        if let Some(gap) = settle_gap_secs {
+            if config.gap_secs_per_point > 0.0 {
+                score += gap as f64 / config.gap_secs_per_point;
+            }
+        }
+        if oracle_mismatch {
+            score += config.oracle_score;
+        }
+        Self { settle_gap_secs, oracle_mismatch, score }
+    }
+
+    pub fn describe(&self) -> String {
+        let gap = match self.settle_gap_secs {
+            Some(secs) => format!("settle gap {:.1}h", secs as f64 / 3600.0),
+            None => "settle gap unknown".to_string(),
+        };
+        format!("{}{} (score {:.2})", gap, if self.oracle_mismatch { ", different reference source" } else { "" }, self.score)
+    }
+}
+
+/// Host of a source URL, without scheme or "www." ("https://www.espn.com/x" -> "espn.com")
+fn source_host(source: &str) -> Option<String> {
+    let s = source.trim();
+    let s = s.split_once("://").map(|(_, rest)| rest).unwrap_or(s);
+    let host = s.split(['/', '?', '#']).next()?.trim_start_matches("www.").to_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+/// Both venues named their sources and Polymarket's is none of Kalshi's
+fn sources_differ(kalshi_sources: &[String], poly_source: Option<&str>) -> bool {
+    let kalshi: Vec<String> = kalshi_sources.iter().filter_map(|s| source_host(s)).collect();
+    match poly_source.and_then(source_host) {
+        Some(poly) if !kalshi.is_empty() => !kalshi.contains(&poly),
+        _ => false,
+    }
+}
+
+/// Log a refused pair at discovery
+pub fn log_refused(description: &str, risk: &BasisRisk, config: &BasisRiskConfig) {
+    warn!("[BASIS] 🚫 Refusing {}: {} > max {:.2}", description, risk.describe(), config.max_score);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> BasisRiskConfig {
+        BasisRiskConfig {
+            gap_secs_per_point: 3600.0,
+            oracle_score: 1.0,
+            discount_cents: 1.0,
+            max_score: 2.0,
+            mismatch_leagues: vec!["crypto".into()],
+        }
+    }
+
+    #[test]
+    fn test_score_grows_with_settlement_gap() {
+        // Same hour: riskless
+        let same = BasisRisk::assess(&cfg(), "epl", Some(10_000), Some(10_000), &[], None);
+        assert_eq!((same.settle_gap_secs, same.score), (Some(0), 0.0));
+        // Polymarket 15-min market vs Kalshi hourly: settles 45 minutes apart
+        let gap = BasisRisk::assess(&cfg(), "epl", Some(3_600), Some(900), &[], None);
+        assert!((gap.score - 0.75).abs() < 1e-9);
+        // Unknown end time adds nothing
+        let unknown = BasisRisk::assess(&cfg(), "epl", Some(3_600), None, &[], None);
+        assert_eq!((unknown.settle_gap_secs, unknown.score), (None, 0.0));
+        assert!(unknown.describe().contains("unknown"));
+    }
+
+    #[test]
+    fn test_oracle_mismatch_from_sources_or_league() {
+        let kalshi = vec!["https://www.cfbenchmarks.com/data/indices/BRTI".to_string()];
+        let differ = BasisRisk::assess(&cfg(), "nba", Some(0), Some(0), &kalshi, Some("https://data.chain.link/streams/btc-usd"));
+        assert!(differ.oracle_mismatch);
+        let same = BasisRisk::assess(&cfg(), "nba", Some(0), Some(0), &kalshi, Some("cfbenchmarks.com/indices"));
+        assert!(!same.oracle_mismatch);
+        // Sports markets usually leave the source blank: no evidence of a mismatch
+        assert!(!BasisRisk::assess(&cfg(), "nba", Some(0), Some(0), &kalshi, Some("")).oracle_mismatch);
+        // Configured league
+        let league = BasisRisk::assess(&cfg(), "CRYPTO", Some(0), Some(5_400), &[], None);
+        assert!(league.oracle_mismatch);
+        assert!((league.score - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_discount_and_refusal() {
+        let c = cfg();
+        assert!((c.net_edge(3.0, 1.5) - 1.5).abs() < 1e-9);
+        assert!(c.refuses(2.5));
+        assert!(!c.refuses(2.0));
+        let off = BasisRiskConfig { max_score: 0.0, discount_cents: 0.0, ..cfg() };
+        assert!(!off.refuses(100.0));
+        assert_eq!(off.net_edge(3.0, 5.0), 3.0);
+    }
+}
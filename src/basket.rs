@@ -0,0 +1,318 @@
+// src/basket.rs
+// Basket executor - N legs across venues: pre-checks, release by leg policy, compensation of incomplete baskets
+
+use anyhow::{bail, Result};
+use futures_util::future::join_all;
+use std::future::Future;
+use tracing::{debug, info, warn};
+
+use crate::leg_order::{self, LegOrderConfig, LegPolicy};
+use crate::metrics;
+use crate::venue_health::{self, Venue};
+
+/// Fill differences below this are rounding, not exposure
+const EPSILON: f64 = 1e-6;
+
+/// What happens to a basket whose legs filled different sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compensation {
+    /// Sell the excess of the legs that filled more than the rest
+    Unwind,
+    /// Buy the shortfall of the lagging legs, if the completed basket still pays; unwind otherwise
+    Complete,
+    /// Leave it (held as a position, handled by exits or settlement)
+    Hold,
+}
+
+impl Compensation {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "unwind" => Some(Self::Unwind),
+            "complete" => Some(Self::Complete),
+            "hold" => Some(Self::Hold),
+            _ => None,
+        }
+    }
+}
+
+/// A Sequenced leg held back because an earlier leg filled nothing (travels inside
+/// `anyhow::Error`; see `not_sent`) - a zero fill, not an execution failure
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegNotSent {
+    pub venue: Venue,
+    pub side: &'static str,
+}
+
+impl std::fmt::Display for LegNotSent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} leg not sent: an earlier leg filled nothing", self.venue.name(), self.side)
+    }
+}
+
+impl std::error::Error for LegNotSent {}
+
+/// Whether a leg result is a Sequenced leg that was never sent
+pub fn not_sent(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<LegNotSent>().is_some()
+}
+
+/// Basket compensation configuration from environment
+#[derive(Debug, Clone)]
+pub struct BasketConfig {
+    /// Incomplete baskets (BASKET_COMPENSATION); the default is the executor's own
+    pub compensation: Compensation,
+
+    /// Unwind sells go this far below the leg's entry price (cents)
+    pub unwind_cents: f64,
+
+    /// Completing buys may pay this much above the leg's limit price (cents)
+    pub complete_slack_cents: f64,
+}
+
+impl BasketConfig {
+    pub fn from_env(default: Compensation) -> Self {
+        Self {
+            compensation: std::env::var("BASKET_COMPENSATION")
+                .ok()
+                .and_then(|v| Compensation::parse(&v))
+                .unwrap_or(default),
+
+            unwind_cents: std::env::var("BASKET_UNWIND_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10.0),
+
+            complete_slack_cents: std::env::var("BASKET_COMPLETE_SLACK_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2.0),
+        }
+    }
+}
+
+/// One order of a basket
+#[derive(Debug, Clone)]
+pub struct BasketLeg {
+    pub venue: Venue,
+    pub side: &'static str,
+    /// Kalshi ticker or Polymarket token
+    pub instrument: String,
+    /// Limit price (dollars per contract)
+    pub price: f64,
+    pub contracts: f64,
+}
+
+/// Legs that pay `payout` per contract once all of them are held
+#[derive(Debug, Clone)]
+pub struct Basket {
+    pub label: String,
+    pub legs: Vec<BasketLeg>,
+    pub payout: f64,
+}
+
+/// Direction of a compensating order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairSide {
+    Buy,
+    Sell,
+}
+
+/// A compensating order for one leg
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+    pub leg: usize,
+    pub side: RepairSide,
+    pub contracts: f64,
+    pub price: f64,
+}
+
+impl Basket {
+    pub fn new(label: &str, legs: Vec<BasketLeg>, payout: f64) -> Self {
+        Self { label: label.to_string(), legs, payout }
+    }
+
+    /// Limit cost of one complete basket
+    pub fn cost(&self) -> f64 {
+        self.legs.iter().map(|l| l.price).sum()
+    }
+
+    /// Refuse a basket that can't be sent as a whole: no legs, a malformed leg, a venue
+    /// that's down, or limit prices that leave no edge
+    pub fn precheck(&self) -> Result<()> {
+        if self.legs.is_empty() {
+            bail!("{}: empty basket", self.label);
+        }
+        for leg in &self.legs {
+            if !(leg.price > 0.0 && leg.price < self.payout && leg.contracts > 0.0 && leg.contracts.is_finite()) {
+                bail!("{}: {} {} leg {:.1} @ {:.3} is malformed", self.label, leg.venue.name(), leg.side, leg.contracts, leg.price);
+            }
+            if venue_health::health().size_factor(leg.venue) <= 0.0 {
+                bail!("{}: {} is down", self.label, leg.venue.name());
+            }
+        }
+        if self.cost() >= self.payout {
+            bail!("{}: legs cost {:.3} for a {:.2} payout", self.label, self.cost(), self.payout);
+        }
+        Ok(())
+    }
+
+    /// Compensating orders for legs that filled `filled` contracts each (none if they match)
+    pub fn plan(&self, filled: &[f64], config: &BasketConfig) -> Vec<Repair> {
+        let matched = filled.iter().cloned().fold(f64::INFINITY, f64::min);
+        let most = filled.iter().cloned().fold(0.0, f64::max);
+        if self.legs.len() != filled.len() || most - matched < EPSILON {
+            return Vec::new();
+        }
+        let unwind = || self.legs.iter().zip(filled).enumerate()
+            .filter(|(_, (_, f))| **f - matched >= EPSILON)
+            .map(|(leg, (l, f))| Repair {
+                leg,
+                side: RepairSide::Sell,
+                contracts: f - matched,
+                price: (l.price - config.unwind_cents / 100.0).max(0.01),
+            })
+            .collect();
+        match config.compensation {
+            Compensation::Hold => Vec::new(),
+            Compensation::Unwind => unwind(),
+            Compensation::Complete => {
+                let slack = config.complete_slack_cents / 100.0;
+                let short: Vec<Repair> = self.legs.iter().zip(filled).enumerate()
+                    .filter(|(_, (_, f))| most - **f >= EPSILON)
+                    .map(|(leg, (l, f))| Repair { leg, side: RepairSide::Buy, contracts: most - f, price: l.price + slack })
+                    .collect();
+                let completed_cost = self.cost() + short.len() as f64 * slack;
+                if completed_cost < self.payout { short } else { unwind() }
+            }
+        }
+    }
+}
+
+/// Send every leg of `basket` under `order`'s policy and return one result per leg.
+/// `send(leg, contracts)` places one order; `filled` reads a result's filled size, which a
+/// sequenced basket uses to size (or skip) the legs after it.
+pub async fn submit<T, F, Fut>(basket: &Basket, order: &LegOrderConfig, send: F, filled: impl Fn(&T) -> f64) -> Vec<Result<T>>
+where
+    F: Fn(usize, f64) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    metrics::incr("basket.sent");
+    match order.policy {
+        LegPolicy::Sequenced => {
+            let mut results = Vec::with_capacity(basket.legs.len());
+            let mut size = f64::INFINITY;
+            for (i, leg) in basket.legs.iter().enumerate() {
+                let contracts = leg.contracts.min(size);
+                if contracts < EPSILON {
+                    results.push(Err(LegNotSent { venue: leg.venue, side: leg.side }.into()));
+                    continue;
+                }
+                let result = send(i, contracts).await;
+                size = result.as_ref().map_or(0.0, &filled).min(contracts);
+                results.push(result);
+            }
+            results
+        }
+        _ => {
+            let venues: Vec<Venue> = basket.legs.iter().map(|l| l.venue).collect();
+            let delays = order.venue_delays(&venues, leg_order::latency());
+            if delays.iter().any(|d| !d.is_zero()) {
+                metrics::incr("exec.leg_order.staggered");
+                debug!("[BASKET] Staggering {}: {:?}", basket.label, delays);
+            }
+            join_all(basket.legs.iter().zip(delays).enumerate()
+                .map(|(i, (leg, delay))| leg_order::delayed(delay, send(i, leg.contracts)))).await
+        }
+    }
+}
+
+/// Send the compensating orders of `plan` (all at once) and return their results
+pub async fn compensate<T, F, Fut>(basket: &Basket, plan: &[Repair], send: F) -> Vec<Result<T>>
+where
+    F: Fn(&Repair) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    if plan.is_empty() {
+        return Vec::new();
+    }
+    for r in plan {
+        let leg = &basket.legs[r.leg];
+        info!("[BASKET] 🔧 {}: {:?} {:.2} {} {} ({}) @ {:.3}",
+              basket.label, r.side, r.contracts, leg.venue.name(), leg.side, leg.instrument, r.price);
+    }
+    let kind = if plan[0].side == RepairSide::Buy { "complete" } else { "unwind" };
+    metrics::incr(&format!("basket.compensated.{}", kind));
+    let results = join_all(plan.iter().map(send)).await;
+    for (r, result) in plan.iter().zip(&results) {
+        if let Err(e) = result {
+            warn!("[BASKET] ⚠️ {} leg {} {:?} failed: {}", basket.label, r.leg, r.side, e);
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::Mutex;
+
+    fn leg(venue: Venue, side: &'static str, price: f64) -> BasketLeg {
+        BasketLeg { venue, side, instrument: "x".into(), price, contracts: 10.0 }
+    }
+
+    fn basket(prices: &[f64]) -> Basket {
+        Basket::new("test", prices.iter().map(|p| leg(Venue::Polymarket, "yes", *p)).collect(), 1.0)
+    }
+
+    fn config(compensation: Compensation) -> BasketConfig {
+        BasketConfig { compensation, unwind_cents: 10.0, complete_slack_cents: 2.0 }
+    }
+
+    #[test]
+    fn test_precheck() {
+        assert!(basket(&[0.30, 0.30, 0.35]).precheck().is_ok());
+        assert!(basket(&[0.50, 0.51]).precheck().is_err());
+        assert!(basket(&[]).precheck().is_err());
+        assert!(basket(&[0.0, 0.5]).precheck().is_err());
+    }
+
+    #[test]
+    fn test_compensation_plans() {
+        let b = basket(&[0.30, 0.30, 0.35]);
+        let filled = [10.0, 6.0, 10.0];
+        assert!(b.plan(&filled, &config(Compensation::Hold)).is_empty());
+        assert!(b.plan(&[10.0, 10.0, 10.0], &config(Compensation::Unwind)).is_empty());
+
+        let unwind = b.plan(&filled, &config(Compensation::Unwind));
+        assert_eq!(unwind.len(), 2);
+        assert_eq!((unwind[0].leg, unwind[0].side, unwind[0].contracts), (0, RepairSide::Sell, 4.0));
+        assert!((unwind[1].price - 0.25).abs() < 1e-9);
+
+        let complete = b.plan(&filled, &config(Compensation::Complete));
+        assert_eq!(complete, vec![Repair { leg: 1, side: RepairSide::Buy, contracts: 4.0, price: 0.32 }]);
+        // No edge left once the slack is paid: unwind instead
+        let thin = basket(&[0.49, 0.50]);
+        assert_eq!(thin.plan(&[5.0, 0.0], &config(Compensation::Complete))[0].side, RepairSide::Sell);
+    }
+
+    #[tokio::test]
+    async fn test_sequenced_sizes_to_previous_fills() {
+        let b = basket(&[0.30, 0.30, 0.35]);
+        let order = LegOrderConfig { policy: LegPolicy::Sequenced, min_samples: 1, max_stagger_ms: 0 };
+        let sent = Mutex::new(Vec::new());
+        let fills: [f64; 3] = [6.0, 0.0, 10.0];
+        let results = submit(&b, &order, |i, contracts| {
+            sent.lock().unwrap().push((i, contracts));
+            async move { Ok(fills[i].min(contracts)) }
+        }, |f: &f64| *f).await;
+        // Second leg sized to the first's 6 fills and filled nothing: the third is never sent
+        assert_eq!(*sent.lock().unwrap(), vec![(0, 10.0), (1, 6.0)]);
+        assert!(results[2].as_ref().is_err_and(not_sent));
+        assert!(!not_sent(&anyhow!("timeout")));
+
+        let order = LegOrderConfig { policy: LegPolicy::Simultaneous, ..order };
+        let results = submit(&b, &order, |i, _| async move { Ok(fills[i]) }, |f: &f64| *f).await;
+        assert_eq!(results.into_iter().map(|r| r.unwrap()).collect::<Vec<_>>(), fills.to_vec());
+    }
+}
@@ -0,0 +1,201 @@
+// Multi-Bot Aggregator
+//
+// Combines open exposure and P&L of up/down bots running on several servers or
+// accounts. Bots push their positions (AGGREGATOR_URL) and get a pause/resume
+// answer back; bots sharing a SQLite/Postgres position store (POSITION_STORE)
+// are also read from it, so a bot that stops pushing keeps counting. Every bot
+// is paused while the combined exposure is over AGG_MAX_EXPOSURE_USD.
+//
+// Usage: cargo run --release --bin aggregator
+//   GET  /status   combined and per-bot exposure, P&L and pauses (JSON)
+//   POST /report   a bot's BotReport; answers with its Control
+
+use anyhow::{anyhow, Result};
+use arb_bot::aggregation::{Aggregator, AggregatorConfig, BotReport, Control, Source};
+use arb_bot::app;
+use arb_bot::control;
+use arb_bot::position_store::{self, PositionStoreConfig, SqlStore, StoreBackend};
+use arb_bot::position_tracker::ArbPosition;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+/// Largest request body accepted (a report of a few hundred open positions)
+const MAX_BODY: usize = 1024 * 1024;
+
+/// Largest request line plus headers
+const MAX_HEAD_BYTES: u64 = 8 * 1024;
+
+/// Time a connection gets to send its request and read the answer
+const REQUEST_DEADLINE: Duration = Duration::from_secs(10);
+
+type Shared = Arc<Mutex<Aggregator>>;
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// The shared SQL store bots write to, if POSITION_STORE names one
+fn shared_store(config: &PositionStoreConfig) -> Result<Option<SqlStore>> {
+    let target = match config.backend {
+        StoreBackend::Json => return Ok(None),
+        StoreBackend::Sqlite => position_store::sqlite_path(config).to_string_lossy().into_owned(),
+        StoreBackend::Postgres => config.url.clone()
+            .ok_or_else(|| anyhow!("POSITION_STORE=postgres needs POSITION_STORE_URL"))?,
+    };
+    Ok(Some(SqlStore::open(config.backend, &target, &config.bot)?))
+}
+
+/// Read every bot's rows from the store every `poll_secs`
+fn spawn_store_poll(store: SqlStore, agg: Shared, poll_secs: u64) {
+    let store = Arc::new(store);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(poll_secs.max(1)));
+        loop {
+            ticker.tick().await;
+            let reader = store.clone();
+            let rows = match tokio::task::spawn_blocking(move || reader.positions_by_bot()).await {
+                Ok(Ok(rows)) => rows,
+                Ok(Err(e)) => { warn!("[AGG] Store read failed: {}", e); continue; }
+                Err(e) => { warn!("[AGG] Store read panicked: {}", e); continue; }
+            };
+            let mut by_bot: BTreeMap<String, Vec<ArbPosition>> = BTreeMap::new();
+            for (bot, position) in rows {
+                by_bot.entry(bot).or_default().push(position);
+            }
+            let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+            let mut agg = agg.lock().unwrap();
+            for (bot, positions) in by_bot {
+                agg.ingest(BotReport::from_positions(&bot, positions, &today), Source::Store, now_ms());
+            }
+            agg.evaluate(now_ms());
+        }
+    });
+}
+
+fn respond(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body,
+    )
+}
+
+/// One request: method, path, bearer token and body
+async fn read_request(stream: &mut TcpStream) -> Result<(String, String, Option<String>, Vec<u8>)> {
+    let mut reader = BufReader::new(stream).take(MAX_HEAD_BYTES);
+    let mut line = String::new();
+    head_line(&mut reader, &mut line).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let (mut length, mut token) = (0usize, None);
+    loop {
+        let mut header = String::new();
+        if head_line(&mut reader, &mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse()?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                token = value.strip_prefix("Bearer ").map(String::from);
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(anyhow!("body of {} bytes over the {} limit", length, MAX_BODY));
+    }
+    reader.set_limit(length as u64);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok((method, path, token, body))
+}
+
+/// One line of the request head; a line cut off by the head limit is an error
+async fn head_line<R: AsyncBufRead + Unpin>(reader: &mut R, line: &mut String) -> Result<usize> {
+    let n = reader.read_line(line).await?;
+    if n > 0 && !line.ends_with('\n') {
+        return Err(anyhow!("request head over {} bytes", MAX_HEAD_BYTES));
+    }
+    Ok(n)
+}
+
+fn route(agg: &Shared, method: &str, path: &str, body: &[u8]) -> (&'static str, String) {
+    match (method, path) {
+        ("GET", "/status") => {
+            let status = agg.lock().unwrap().status(now_ms());
+            ("200 OK", serde_json::to_string_pretty(&status).unwrap_or_default())
+        }
+        ("POST", "/report") => {
+            let report: BotReport = match serde_json::from_slice(body) {
+                Ok(r) => r,
+                Err(e) => return ("400 Bad Request", serde_json::json!({"error": e.to_string()}).to_string()),
+            };
+            if report.bot.trim().is_empty() {
+                return ("400 Bad Request", r#"{"error":"missing bot"}"#.into());
+            }
+            let mut agg = agg.lock().unwrap();
+            let bot = report.bot.clone();
+            agg.ingest(report, Source::Push, now_ms());
+            agg.evaluate(now_ms());
+            let control: Control = agg.control(&bot);
+            ("200 OK", serde_json::to_string(&control).unwrap_or_default())
+        }
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.into()),
+    }
+}
+
+async fn handle(mut stream: TcpStream, agg: Shared, token: Option<Arc<String>>) -> Result<()> {
+    let (method, path, presented, body) = read_request(&mut stream).await?;
+    let authorized = |t: &String| presented.as_deref().is_some_and(|p| control::secret_eq(p.as_bytes(), t.as_bytes()));
+    let (status, body) = if token.as_deref().is_some_and(|t| !authorized(t)) {
+        ("401 Unauthorized", r#"{"error":"unauthorized"}"#.to_string())
+    } else {
+        route(&agg, &method, &path, &body)
+    };
+    stream.write_all(respond(status, &body).as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    app::bootstrap().log("aggregator=info").init();
+
+    let config = AggregatorConfig::from_env();
+    let listen = std::env::var("AGG_LISTEN").unwrap_or_else(|_| "127.0.0.1:8790".to_string());
+    let poll_secs: u64 = std::env::var("AGG_POLL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(15);
+    let token = std::env::var("AGGREGATOR_TOKEN").ok().filter(|v| !v.is_empty()).map(Arc::new);
+
+    info!("[AGG] Caps: combined ${:.2}, per bot ${:.2} (0 = none), resume under {:.0}%",
+          config.max_exposure, config.max_bot_exposure, config.resume_fraction * 100.0);
+    let agg: Shared = Arc::new(Mutex::new(Aggregator::new(config)));
+
+    let store_config = PositionStoreConfig::from_env();
+    if let Some(store) = shared_store(&store_config)? {
+        info!("[AGG] Reading every bot's positions from the {} store every {}s", store_config.backend.name(), poll_secs);
+        spawn_store_poll(store, agg.clone(), poll_secs);
+    }
+
+    let listener = TcpListener::bind(&listen).await?;
+    info!("[AGG] Listening on {}{}", listen, if token.is_some() { " (token required)" } else { "" });
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let (agg, token) = (agg.clone(), token.clone());
+        tokio::spawn(async move {
+            match tokio::time::timeout(REQUEST_DEADLINE, handle(stream, agg, token)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => warn!("[AGG] Request from {} failed: {}", peer, e),
+                Err(_) => warn!("[AGG] Request from {} timed out after {}s", peer, REQUEST_DEADLINE.as_secs()),
+            }
+        });
+    }
+}
@@ -0,0 +1,142 @@
+// Venue Balances
+//
+// Prints USDC on Polymarket (wallet + committed in resting orders) and Kalshi
+// account cash and position exposure, fetched from both venues now. With
+// --watch it refreshes on BALANCE_POLL_SECS and flags a venue whose last good
+// balance is older than BALANCE_STALE_SECS.
+//
+// Usage: cargo run --release --bin balances -- [options]
+//   --poly-only     Skip Kalshi
+//   --kalshi-only   Skip Polymarket
+//   --watch         Keep polling instead of printing once
+//   --rebalance     Also print the transfer that restores the REBALANCE_* cash split
+//   --confirm       With --rebalance: send a Polymarket -> Kalshi transfer on chain
+//                   (USDC to REBALANCE_KALSHI_DEPOSIT_ADDRESS, which must be in
+//                   TREASURY_ALLOWLIST). Kalshi has no withdrawal API, so
+//                   Kalshi -> Polymarket is always manual.
+//   --confirm-code CODE
+//                   Second step for transfers above TREASURY_CONFIRM_ABOVE_USD:
+//                   the code printed by the first --confirm run (sends the amount
+//                   that run printed, not a fresh suggestion)
+
+use anyhow::{anyhow, Result};
+use arb_bot::app::{self, App};
+use arb_bot::balances::{self, BalanceConfig, Balances};
+use arb_bot::onchain::{OnchainClient, OnchainConfig};
+use arb_bot::rebalance::{self, RebalanceConfig};
+use arb_bot::treasury::{self, TreasuryConfig, Withdrawal};
+use std::time::Duration;
+
+/// Print the suggested transfer and, with --confirm, send it if it can go by API.
+/// Returns whether a transfer was sent.
+async fn rebalance(app: &App, view: &Balances, config: &RebalanceConfig, stale_secs: u64, confirm: bool,
+                   code: Option<&str>) -> Result<bool> {
+    let now = chrono::Utc::now().timestamp();
+    let Some(transfer) = rebalance::suggest(view, config, now, stale_secs) else {
+        println!("⚖️  Balanced (within {:.0}% of the target split)", config.threshold * 100.0);
+        return Ok(false);
+    };
+    println!("⚖️  Move ${:.2} from {} to {}: {}", transfer.amount, transfer.from, transfer.to,
+             rebalance::instructions(&transfer, config));
+    let Some(deposit) = config.kalshi_deposit_address.as_deref().filter(|_| transfer.automatable(config)) else {
+        return Ok(false);
+    };
+    if !confirm {
+        println!("Not sent - re-run with --confirm to submit");
+        return Ok(false);
+    }
+
+    // The code is bound to the amount it was issued for; balances will have moved a few cents since
+    let treasury_config = TreasuryConfig::from_env();
+    let amount = match code {
+        Some(code) => treasury::pending_amount(&treasury_config, deposit, code)?
+            .ok_or_else(|| anyhow!("no pending transfer to {} for code {} (expired?) - re-run with --confirm alone", deposit, code))?,
+        None => transfer.amount,
+    };
+    if code.is_some() && (amount - transfer.amount).abs() >= 0.005 {
+        println!("   Sending the confirmed ${:.2} (balances now suggest ${:.2})", amount, transfer.amount);
+    }
+
+    let call = match treasury::usdc_transfer(&treasury_config, deposit, amount, code)? {
+        Withdrawal::Ready(call) => call,
+        Withdrawal::NeedsConfirmation { code, expires_at } => {
+            println!("🔐 ${:.2} is above TREASURY_CONFIRM_ABOVE_USD - re-run with --confirm --confirm-code {} before {} to send exactly that",
+                     amount, code,
+                     chrono::DateTime::from_timestamp(expires_at, 0).map(|t| t.format("%H:%M:%S UTC").to_string()).unwrap_or_default());
+            return Ok(false);
+        }
+    };
+    let creds = app.poly_credentials()?;
+    let tx = OnchainClient::new(OnchainConfig::from_env(), &creds.private_key, &creds.funder)?
+        .submit(&[call]).await?;
+    println!("✅ {}", tx);
+    Ok(true)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let app = app::bootstrap().crate_level("warn").init();
+
+    let (mut poly, mut kalshi, mut watch) = (true, true, false);
+    let (mut rebalance_view, mut confirm) = (false, false);
+    let mut code: Option<String> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--poly-only" => kalshi = false,
+            "--kalshi-only" => poly = false,
+            "--watch" => watch = true,
+            "--rebalance" => rebalance_view = true,
+            "--confirm" => confirm = true,
+            "--confirm-code" => code = Some(args.next().ok_or_else(|| anyhow!("--confirm-code needs a code"))?),
+            "-h" | "--help" => {
+                println!("Usage: balances [--poly-only | --kalshi-only] [--watch] [--rebalance [--confirm [--confirm-code CODE]]]");
+                return Ok(());
+            }
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    if rebalance_view && !(poly && kalshi) {
+        return Err(anyhow!("--rebalance needs both venues"));
+    }
+
+    let config = BalanceConfig::from_env();
+    let rebalance_config = RebalanceConfig::from_env();
+    let mut last_transfer: Option<i64> = None;
+    let poly_client = if poly { Some(app.poly_client().await?) } else { None };
+    let kalshi_client = if kalshi { Some(app.kalshi_client()?) } else { None };
+
+    let mut view = Balances::default();
+    loop {
+        let now = chrono::Utc::now().timestamp();
+        if let Some(client) = &poly_client {
+            match balances::fetch_poly(client, now).await {
+                Ok(b) => view.poly = Some(b),
+                Err(e) => eprintln!("❌ Polymarket: {}", e),
+            }
+        }
+        if let Some(client) = &kalshi_client {
+            match balances::fetch_kalshi(client, now).await {
+                Ok(b) => view.kalshi = Some(b),
+                Err(e) => eprintln!("❌ Kalshi: {}", e),
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        println!("{} {}", chrono::Utc::now().format("%H:%M:%S"), view.summary(now, config.stale_secs));
+
+        // A sent transfer takes a while to show up on Kalshi; don't send it again meanwhile
+        let cooling = last_transfer.is_some_and(|at| now - at < rebalance_config.cooldown_secs as i64);
+        if rebalance_view && !cooling
+            && rebalance(&app, &view, &rebalance_config, config.stale_secs, confirm, code.as_deref()).await? {
+            last_transfer = Some(now);
+            // Codes are single-use: later transfers in --watch start a new confirmation
+            code = None;
+        }
+        if !watch {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(config.poll_secs.max(1))).await;
+    }
+}
@@ -0,0 +1,361 @@
+// Order Book Watcher
+//
+// Live terminal view of both books for one Up/Down asset, the YES+NO sum,
+// and a tape of detected opportunities. Useful for debugging why the bot
+// does or doesn't fire.
+//
+// Usage: cargo run --release --bin book_watch -- [asset] [threshold]
+//   asset:     btc | eth | sol | xrp (default: btc)
+//   threshold: YES+NO sum below which an opportunity is logged (default: 0.995)
+//
+// Watches the UPDOWN_SERIES cycle (15m | 5m | 1m, default 15m).
+// Full-screen ratatui view redrawn every RENDER_INTERVAL_MS; q / Esc / Ctrl-C quits.
+
+use anyhow::{anyhow, Context, Result};
+use arb_bot::app;
+use arb_bot::book::{BookSide, LocalBook};
+use arb_bot::config::POLYMARKET_WS_URL;
+use arb_bot::polymarket_ws_types::{BookSnapshot, LastTradeEvent, MarketSubscribe, PriceChangeEvent};
+use arb_bot::trade_tape::{TradeTape, TradeTapeConfig};
+use arb_bot::updown_scanner::{ActiveUpDownMarket, MarketSeries, UpDownScanner};
+use chrono::Local;
+use futures_util::{SinkExt, StreamExt};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::{interval, sleep, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// Number of price levels shown per side
+const DEPTH_LEVELS: usize = 8;
+
+/// Number of opportunities kept in the tape
+const TAPE_LEN: usize = 15;
+
+/// Screen refresh interval
+const RENDER_INTERVAL_MS: u64 = 250;
+
+/// One detected opportunity
+struct TapeEntry {
+    time: String,
+    up_ask: f64,
+    down_ask: f64,
+    size: f64,
+}
+
+/// Everything rendered on screen for the watched market
+struct WatchState {
+    market: ActiveUpDownMarket,
+    threshold: f64,
+    up: LocalBook,
+    down: LocalBook,
+    tape: VecDeque<TapeEntry>,
+    in_arb: bool,
+    messages: u64,
+    trades: TradeTape,
+}
+
+impl WatchState {
+    fn new(market: ActiveUpDownMarket, threshold: f64) -> Self {
+        Self {
+            market,
+            threshold,
+            up: LocalBook::new(),
+            down: LocalBook::new(),
+            tape: VecDeque::with_capacity(TAPE_LEN),
+            in_arb: false,
+            messages: 0,
+            trades: TradeTape::new(TradeTapeConfig::from_env()),
+        }
+    }
+
+    fn book_mut(&mut self, asset_id: &str) -> Option<&mut LocalBook> {
+        if asset_id == self.market.yes_token {
+            Some(&mut self.up)
+        } else if asset_id == self.market.no_token {
+            Some(&mut self.down)
+        } else {
+            None
+        }
+    }
+
+    /// Record an opportunity on the rising edge of the arb condition
+    fn check_opportunity(&mut self) {
+        let (Some((up_ask, up_size)), Some((down_ask, down_size))) = (self.up.best_ask(), self.down.best_ask()) else {
+            self.in_arb = false;
+            return;
+        };
+
+        let in_arb = up_ask + down_ask < self.threshold;
+        if in_arb && !self.in_arb {
+            if self.tape.len() == TAPE_LEN {
+                self.tape.pop_back();
+            }
+            self.tape.push_front(TapeEntry {
+                time: Local::now().format("%H:%M:%S%.3f").to_string(),
+                up_ask,
+                down_ask,
+                size: up_size.min(down_size),
+            });
+        }
+        self.in_arb = in_arb;
+    }
+
+    fn fmt_last_trade(&self, label: &str, token: &str) -> String {
+        match (self.trades.last_trade(token), self.trades.vwap(token)) {
+            (Some(last), Some(vwap)) => format!("{} {:.3} x {:.1} (vwap {:.3})", label, last.price, last.size, vwap),
+            (Some(last), None) => format!("{} {:.3} x {:.1}", label, last.price, last.size),
+            _ => format!("{} -", label),
+        }
+    }
+
+    /// One book as a table: asks worst-to-best above bids best-to-worst, so the spread sits
+    /// in the middle
+    fn book_table<'a>(title: &'a str, book: &LocalBook) -> Table<'a> {
+        let asks = book.top_asks(DEPTH_LEVELS);
+        let bids = book.top_bids(DEPTH_LEVELS);
+        let level = |side: &'static str, level: Option<&(f64, f64)>, color: Color| match level {
+            Some((price, size)) => Row::new([side.to_string(), format!("{:.3}", price), format!("{:.2}", size)]).fg(color),
+            None => Row::new(["", "", ""]),
+        };
+        let rows = (0..DEPTH_LEVELS).rev().map(|i| level("ask", asks.get(i), Color::Red))
+            .chain(std::iter::once(Row::new(["·····", "·······", "············"]).dim()))
+            .chain((0..DEPTH_LEVELS).map(|i| level("bid", bids.get(i), Color::Green)));
+        Table::new(rows, [Constraint::Length(5), Constraint::Length(8), Constraint::Min(12)])
+            .header(Row::new(["", "PRICE", "SIZE"]).bold())
+            .block(Block::bordered().title(title))
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, books, summary, tape] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(DEPTH_LEVELS as u16 * 2 + 4),
+            Constraint::Length(2),
+            Constraint::Min(3),
+        ]).areas(frame.area());
+
+        frame.render_widget(Paragraph::new(vec![
+            Line::from(format!("📖 {} | {}", self.market.asset.to_uppercase(), self.market.question)).bold(),
+            Line::from(format!("ends in {}s | threshold {:.3} | {} WS messages | q quits",
+                               self.market.end_timestamp.saturating_sub(now_secs()), self.threshold, self.messages)),
+        ]), header);
+
+        let [up, down] = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(books);
+        frame.render_widget(Self::book_table("UP (YES)", &self.up), up);
+        frame.render_widget(Self::book_table("DOWN (NO)", &self.down), down);
+
+        let sum = match (self.up.best_ask(), self.down.best_ask()) {
+            (Some((up, _)), Some((down, _))) if up + down < self.threshold => {
+                Line::from(format!("YES {:.3} + NO {:.3} = {:.3} ({:+.1}¢ vs $1) 🟢 ARB", up, down, up + down, (1.0 - up - down) * 100.0))
+                    .style(Style::new().fg(Color::Green).bold())
+            }
+            (Some((up, _)), Some((down, _))) => {
+                Line::from(format!("YES {:.3} + NO {:.3} = {:.3} ({:+.1}¢ vs $1)", up, down, up + down, (1.0 - up - down) * 100.0))
+            }
+            _ => Line::from("Waiting for both books...").dim(),
+        };
+        frame.render_widget(Paragraph::new(vec![
+            sum,
+            Line::from(format!("Last trade: {}   {}",
+                               self.fmt_last_trade("UP", &self.market.yes_token),
+                               self.fmt_last_trade("DOWN", &self.market.no_token))),
+        ]), summary);
+
+        let rows: Vec<Row> = self.tape.iter()
+            .map(|e| Row::new([
+                e.time.clone(),
+                format!("{:.3} + {:.3} = {:.3}", e.up_ask, e.down_ask, e.up_ask + e.down_ask),
+                format!("size {:.1}", e.size),
+            ]))
+            .collect();
+        let title = if rows.is_empty() { "Opportunity tape (none yet)" } else { "Opportunity tape (newest first)" };
+        frame.render_widget(
+            Table::new(rows, [Constraint::Length(13), Constraint::Length(22), Constraint::Min(10)])
+                .block(Block::bordered().title(title)),
+            tape,
+        );
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Whether q, Esc or Ctrl-C was pressed since the last check (never blocks)
+fn quit_pressed() -> Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Show `status` for `wait`, returning true if the user quit meanwhile
+async fn pause(terminal: &mut DefaultTerminal, status: &str, wait: Duration) -> Result<bool> {
+    let until = Instant::now() + wait;
+    while Instant::now() < until {
+        terminal.draw(|frame| frame.render_widget(Paragraph::new(format!("📖 {}\n\nq quits", status)), frame.area()))?;
+        if quit_pressed()? {
+            return Ok(true);
+        }
+        sleep(Duration::from_millis(RENDER_INTERVAL_MS)).await;
+    }
+    Ok(false)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Quiet logging by default so it doesn't fight with the screen
+    app::bootstrap().crate_level("warn").init();
+
+    let mut args = std::env::args().skip(1);
+    let asset = args.next().unwrap_or_else(|| "btc".to_string()).to_lowercase();
+    let threshold: f64 = match args.next() {
+        Some(v) => v.parse().context("threshold must be a number, e.g. 0.995")?,
+        None => 0.995,
+    };
+
+    let mut terminal = ratatui::try_init()?;
+    let result = run(&mut terminal, &asset, threshold).await;
+    ratatui::restore();
+    result
+}
+
+/// Watch the asset's market of each interval until the user quits
+async fn run(terminal: &mut DefaultTerminal, asset: &str, threshold: f64) -> Result<()> {
+    let series = MarketSeries::from_env();
+    let scanner = UpDownScanner::with_series(series);
+
+    loop {
+        let markets = scanner.scan_markets_for_interval(0).await?;
+        let Some(market) = markets.into_iter().find(|m| m.asset == asset) else {
+            let status = format!("No active {} market for '{}', retrying in {}s...", series, asset, series.retry_secs());
+            if pause(terminal, &status, Duration::from_secs(series.retry_secs())).await? {
+                return Ok(());
+            }
+            continue;
+        };
+
+        let end = market.end_timestamp;
+        let mut state = WatchState::new(market, threshold);
+
+        match watch(terminal, &mut state).await {
+            Ok(Watch::Quit) => return Ok(()),
+            Ok(Watch::Expired) => {}
+            Err(e) => {
+                if pause(terminal, &format!("WebSocket error: {} - reconnecting...", e), Duration::from_secs(2)).await? {
+                    return Ok(());
+                }
+            }
+        }
+
+        // Move on to the next interval once this market expires
+        if now_secs() >= end && pause(terminal, "Market expired - finding the next interval...", Duration::from_secs(2)).await? {
+            return Ok(());
+        }
+    }
+}
+
+/// How a watch ended without an error
+enum Watch {
+    Expired,
+    Quit,
+}
+
+/// Stream both books until the market expires, the user quits or the socket drops
+async fn watch(terminal: &mut DefaultTerminal, state: &mut WatchState) -> Result<Watch> {
+    let (ws_stream, _) = connect_async(POLYMARKET_WS_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    let subscribe_msg = MarketSubscribe::new(vec![state.market.yes_token.clone(), state.market.no_token.clone()]);
+    write.send(Message::Text(serde_json::to_string(&subscribe_msg)?)).await?;
+
+    let mut render_interval = interval(Duration::from_millis(RENDER_INTERVAL_MS));
+    let mut ping_interval = interval(Duration::from_secs(30));
+    let mut last_message = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = render_interval.tick() => {
+                terminal.draw(|frame| state.draw(frame))?;
+                if quit_pressed()? {
+                    return Ok(Watch::Quit);
+                }
+                if now_secs() >= state.market.end_timestamp {
+                    return Ok(Watch::Expired);
+                }
+                if last_message.elapsed() > Duration::from_secs(120) {
+                    return Err(anyhow!("stale connection"));
+                }
+            }
+
+            _ = ping_interval.tick() => {
+                write.send(Message::Ping(vec![])).await?;
+            }
+
+            msg = read.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        last_message = Instant::now();
+                        state.messages += 1;
+
+                        if let Ok(books) = serde_json::from_str::<Vec<BookSnapshot>>(&text) {
+                            for snapshot in &books {
+                                if let Some(book) = state.book_mut(&snapshot.asset_id) {
+                                    book.apply_snapshot(snapshot);
+                                }
+                            }
+                        } else if let Ok(event) = serde_json::from_str::<PriceChangeEvent>(&text) {
+                            for change in event.price_changes.iter().flatten() {
+                                let (Some(price), Some(size), Some(side)) = (
+                                    change.price.as_deref(),
+                                    change.size.as_deref(),
+                                    change.side.as_deref().and_then(BookSide::parse),
+                                ) else {
+                                    continue;
+                                };
+                                if let Some(book) = state.book_mut(&change.asset_id) {
+                                    book.apply_level(side, price, size);
+                                }
+                            }
+                            if event.event_type.as_deref() == Some("last_trade_price") {
+                                if let Ok(trade) = serde_json::from_str::<LastTradeEvent>(&text) {
+                                    if let Some(print) = trade.to_print() {
+                                        state.trades.record(&trade.asset_id, print);
+                                    }
+                                }
+                            }
+                        }
+
+                        state.check_opportunity();
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        let _ = write.send(Message::Pong(data)).await;
+                        last_message = Instant::now();
+                    }
+                    Some(Ok(Message::Pong(_))) => {
+                        last_message = Instant::now();
+                    }
+                    Some(Ok(Message::Close(frame))) => {
+                        return Err(anyhow!("server closed: {:?}", frame));
+                    }
+                    Some(Err(e)) => return Err(e.into()),
+                    None => return Err(anyhow!("stream ended")),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+// Market Inspector
+//
+// Given a Polymarket slug, a Polymarket token ID, or a Kalshi ticker, prints
+// the resolved metadata (tokens, outcome ordering, tick size, min size, end
+// time), the current best bid/ask and recent trades. Helps debug scanner and
+// matching issues such as wrong outcome ordering.
+//
+// Usage: cargo run --release --bin inspect_market -- <slug | token_id | KALSHI-TICKER>
+
+use anyhow::{anyhow, Result};
+use arb_bot::config::{GAMMA_API_BASE, KALSHI_API_BASE};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Polymarket CLOB API host (public book endpoint)
+const POLY_CLOB_HOST: &str = "https://clob.polymarket.com";
+
+/// Polymarket data API (public trade history)
+const POLY_DATA_API_BASE: &str = "https://data-api.polymarket.com";
+
+/// Number of recent trades shown
+const RECENT_TRADES: usize = 10;
+
+/// What the user handed us
+#[derive(Debug, PartialEq)]
+enum Target {
+    PolySlug(String),
+    PolyToken(String),
+    KalshiTicker(String),
+}
+
+impl Target {
+    fn parse(input: &str) -> Self {
+        if !input.is_empty() && input.chars().all(|c| c.is_ascii_digit()) {
+            Target::PolyToken(input.to_string())
+        } else if input.chars().any(|c| c.is_ascii_uppercase())
+            && input.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        {
+            Target::KalshiTicker(input.to_string())
+        } else {
+            Target::PolySlug(input.to_string())
+        }
+    }
+}
+
+// === Polymarket response types ===
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaMarketInfo {
+    question: Option<String>,
+    slug: Option<String>,
+    condition_id: Option<String>,
+    clob_token_ids: Option<String>,
+    outcomes: Option<String>,
+    outcome_prices: Option<String>,
+    end_date: Option<String>,
+    active: Option<bool>,
+    closed: Option<bool>,
+    accepting_orders: Option<bool>,
+    neg_risk: Option<bool>,
+    order_price_min_tick_size: Option<f64>,
+    order_min_size: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClobLevel {
+    price: String,
+    size: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClobBook {
+    #[serde(default)]
+    bids: Vec<ClobLevel>,
+    #[serde(default)]
+    asks: Vec<ClobLevel>,
+    tick_size: Option<String>,
+    min_order_size: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolyTrade {
+    side: Option<String>,
+    asset: Option<String>,
+    outcome: Option<String>,
+    price: Option<f64>,
+    size: Option<f64>,
+    timestamp: Option<i64>,
+}
+
+// === Kalshi response types ===
+
+#[derive(Debug, Deserialize)]
+struct KalshiMarketResponse {
+    market: KalshiMarketInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct KalshiMarketInfo {
+    ticker: String,
+    event_ticker: Option<String>,
+    title: Option<String>,
+    yes_sub_title: Option<String>,
+    no_sub_title: Option<String>,
+    status: Option<String>,
+    close_time: Option<String>,
+    expiration_time: Option<String>,
+    tick_size: Option<i64>,
+    yes_bid: Option<i64>,
+    yes_ask: Option<i64>,
+    no_bid: Option<i64>,
+    no_ask: Option<i64>,
+    last_price: Option<i64>,
+    volume: Option<i64>,
+    open_interest: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KalshiTradesResponse {
+    #[serde(default)]
+    trades: Vec<KalshiTrade>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KalshiTrade {
+    yes_price: Option<i64>,
+    no_price: Option<i64>,
+    count: Option<i64>,
+    taker_side: Option<String>,
+    created_time: Option<String>,
+}
+
+fn show<T: std::fmt::Display>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_else(|| "?".to_string())
+}
+
+fn parse_json_array(s: &Option<String>) -> Vec<String> {
+    s.as_deref()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default()
+}
+
+fn best_level(levels: &[ClobLevel], want_max: bool) -> Option<(f64, f64)> {
+    let parsed = levels.iter().filter_map(|l| Some((l.price.parse::<f64>().ok()?, l.size.parse::<f64>().ok()?)));
+    if want_max {
+        parsed.max_by(|a, b| a.0.total_cmp(&b.0))
+    } else {
+        parsed.min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+async fn inspect_poly(http: &reqwest::Client, query: &str) -> Result<()> {
+    let url = format!("{}/markets?{}", GAMMA_API_BASE, query);
+    let markets: Vec<GammaMarketInfo> = http.get(&url).send().await?.json().await?;
+    let market = markets.into_iter().next().ok_or_else(|| anyhow!("No Polymarket market found for {}", query))?;
+
+    println!("🟣 Polymarket");
+    println!("   Question:     {}", show(&market.question));
+    println!("   Slug:         {}", show(&market.slug));
+    println!("   Condition:    {}", show(&market.condition_id));
+    println!("   End date:     {}", show(&market.end_date));
+    println!("   Active: {} | Closed: {} | Accepting orders: {} | Neg risk: {}",
+             show(&market.active), show(&market.closed), show(&market.accepting_orders), show(&market.neg_risk));
+    println!("   Tick size:    {} | Min size: {}", show(&market.order_price_min_tick_size), show(&market.order_min_size));
+
+    let tokens = parse_json_array(&market.clob_token_ids);
+    let outcomes = parse_json_array(&market.outcomes);
+    let prices = parse_json_array(&market.outcome_prices);
+
+    // Index 0 is what the bots treat as YES/Up, index 1 as NO/Down
+    for (i, token) in tokens.iter().enumerate() {
+        let role = match i { 0 => "YES", 1 => "NO", _ => "--" };
+        println!();
+        println!("   [{}] {} → {} (gamma price {})",
+                 i, role,
+                 outcomes.get(i).map(|s| s.as_str()).unwrap_or("?"),
+                 prices.get(i).map(|s| s.as_str()).unwrap_or("?"));
+        println!("       token {}", token);
+
+        let book_url = format!("{}/book?token_id={}", POLY_CLOB_HOST, token);
+        match http.get(&book_url).send().await?.json::<ClobBook>().await {
+            Ok(book) => {
+                let bid = best_level(&book.bids, true);
+                let ask = best_level(&book.asks, false);
+                println!("       bid {} | ask {} | tick {} | min size {}",
+                         bid.map(|(p, s)| format!("{:.3} x {:.2}", p, s)).unwrap_or_else(|| "-".into()),
+                         ask.map(|(p, s)| format!("{:.3} x {:.2}", p, s)).unwrap_or_else(|| "-".into()),
+                         show(&book.tick_size), show(&book.min_order_size));
+            }
+            Err(e) => println!("       book unavailable: {}", e),
+        }
+    }
+
+    if let Some(condition_id) = &market.condition_id {
+        let trades_url = format!("{}/trades?market={}&limit={}", POLY_DATA_API_BASE, condition_id, RECENT_TRADES);
+        match http.get(&trades_url).send().await?.json::<Vec<PolyTrade>>().await {
+            Ok(trades) => {
+                println!();
+                println!("   Recent trades:");
+                if trades.is_empty() {
+                    println!("     (none)");
+                }
+                for t in trades.iter().take(RECENT_TRADES) {
+                    let when = t.timestamp
+                        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_else(|| "?".into());
+                    let is_yes = t.asset.as_deref() == tokens.first().map(|s| s.as_str());
+                    println!("     {} | {:<4} {:<6} ({}) | {} x {}",
+                             when, show(&t.side), show(&t.outcome),
+                             if is_yes { "YES" } else { "NO" },
+                             show(&t.price), show(&t.size));
+                }
+            }
+            Err(e) => println!("   Recent trades unavailable: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+async fn inspect_kalshi(http: &reqwest::Client, ticker: &str) -> Result<()> {
+    let url = format!("{}/markets/{}", KALSHI_API_BASE, ticker);
+    let resp = http.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("Kalshi market {} not found ({})", ticker, resp.status()));
+    }
+    let market = resp.json::<KalshiMarketResponse>().await?.market;
+
+    println!("🔵 Kalshi");
+    println!("   Ticker:       {}", market.ticker);
+    println!("   Event:        {}", show(&market.event_ticker));
+    println!("   Title:        {}", show(&market.title));
+    println!("   YES means:    {}", show(&market.yes_sub_title));
+    println!("   NO means:     {}", show(&market.no_sub_title));
+    println!("   Status:       {}", show(&market.status));
+    println!("   Close time:   {} | Expiration: {}", show(&market.close_time), show(&market.expiration_time));
+    println!("   Tick size:    {}¢", show(&market.tick_size));
+    println!("   YES bid/ask:  {}¢ / {}¢", show(&market.yes_bid), show(&market.yes_ask));
+    println!("   NO  bid/ask:  {}¢ / {}¢", show(&market.no_bid), show(&market.no_ask));
+    println!("   Last: {}¢ | Volume: {} | Open interest: {}",
+             show(&market.last_price), show(&market.volume), show(&market.open_interest));
+
+    let trades_url = format!("{}/markets/trades?ticker={}&limit={}", KALSHI_API_BASE, ticker, RECENT_TRADES);
+    match http.get(&trades_url).send().await?.json::<KalshiTradesResponse>().await {
+        Ok(resp) => {
+            println!();
+            println!("   Recent trades:");
+            if resp.trades.is_empty() {
+                println!("     (none)");
+            }
+            for t in &resp.trades {
+                println!("     {} | taker {:<3} | yes {}¢ / no {}¢ x{}",
+                         show(&t.created_time), show(&t.taker_side),
+                         show(&t.yes_price), show(&t.no_price), show(&t.count));
+            }
+        }
+        Err(e) => println!("   Recent trades unavailable: {}", e),
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let input = std::env::args().nth(1)
+        .ok_or_else(|| anyhow!("Usage: inspect_market <slug | token_id | KALSHI-TICKER>"))?;
+
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    match Target::parse(input.trim()) {
+        Target::PolySlug(slug) => inspect_poly(&http, &format!("slug={}", slug)).await,
+        Target::PolyToken(token) => inspect_poly(&http, &format!("clob_token_ids={}", token)).await,
+        Target::KalshiTicker(ticker) => inspect_kalshi(&http, &ticker).await,
+    }
+}
@@ -0,0 +1,225 @@
+// Manual Order Tool
+//
+// Place, cancel and query orders on Polymarket/Kalshi from the command line
+// using the bot's own signing stack, so an operator can hedge or flatten by
+// hand while the bot is halted.
+//
+// Usage: cargo run --release --bin manual_order -- <command> ...
+//
+//   place poly   <buy|sell> <token_id> <price 0-1> <size>              [--confirm]
+//   place kalshi <buy|sell> <ticker> <yes|no> <price_cents> <count>    [--confirm]
+//   cancel poly   <order_id>
+//   cancel kalshi <order_id>
+//   query poly    <order_id>
+//   query kalshi  <order_id>
+//   approve poly                                                      [--confirm]
+//   redeem poly   <condition_id>                                      [--confirm]
+//
+// `approve` sets the USDC / outcome-token allowances the exchanges need and
+// `redeem` turns resolved tokens back into USDC. Both go through Polymarket's
+// gasless relayer when POLY_TX_MODE=relayer (falling back to a direct, MATIC-paid
+// transaction unless POLY_RELAYER_FALLBACK=false).
+//
+// A cancel or redemption that fails because the venue is out (5xx, timeout) is
+// queued in the replay buffer (REPLAY_FILE) for a running bot to retry on recovery.
+//
+// Orders are immediate (Polymarket FAK / Kalshi IOC). Without --confirm,
+// `place` only prints what would be sent.
+
+use anyhow::{anyhow, bail, Result};
+use arb_bot::app::{self, App};
+use arb_bot::onchain::{self, ContractCall, OnchainClient, OnchainConfig};
+use arb_bot::order_tags::OrderStrategy;
+use arb_bot::replay::{self, DeferredAction};
+use arb_bot::venue_health::Venue;
+
+const USAGE: &str = "\
+Usage:
+  manual_order place poly   <buy|sell> <token_id> <price 0-1> <size>           [--confirm]
+  manual_order place kalshi <buy|sell> <ticker> <yes|no> <price_cents> <count> [--confirm]
+  manual_order cancel <poly|kalshi> <order_id>
+  manual_order query  <poly|kalshi> <order_id>
+  manual_order approve poly                                                    [--confirm]
+  manual_order redeem poly <condition_id>                                      [--confirm]";
+
+/// Submit funder-wallet calls (relayer or direct per POLY_TX_MODE)
+async fn submit_onchain(app: &App, calls: Vec<ContractCall>, confirm: bool) -> Result<()> {
+    let config = OnchainConfig::from_env();
+    for call in &calls {
+        println!("{}", call.description);
+    }
+    println!("via {:?}{}", config.mode,
+             if config.attempts().len() > 1 { " (direct fallback)" } else { "" });
+    if !confirm {
+        println!("Not sent - re-run with --confirm to submit");
+        return Ok(());
+    }
+
+    let creds = app.poly_credentials()?;
+    let tx = OnchainClient::new(config, &creds.private_key, &creds.funder)?.submit(&calls).await?;
+    println!("✅ {}", tx);
+    Ok(())
+}
+
+/// A cancel or redemption that hit a venue outage is queued for a running bot to replay
+fn defer<T>(err: anyhow::Error, action: DeferredAction) -> Result<Option<T>> {
+    if !replay::is_outage(action.venue(), &err) {
+        return Err(err);
+    }
+    replay::buffer().push(action.clone(), &err.to_string());
+    println!("⏸️ {} is out ({}) - {} queued for replay once it recovers",
+             action.venue().name(), err, action.describe());
+    Ok(None)
+}
+
+fn parse<T: std::str::FromStr>(value: Option<&String>, name: &str) -> Result<T> {
+    value
+        .ok_or_else(|| anyhow!("missing <{}>\n\n{}", name, USAGE))?
+        .parse()
+        .map_err(|_| anyhow!("invalid <{}>", name))
+}
+
+fn arg<'a>(value: Option<&'a String>, name: &str) -> Result<&'a str> {
+    value.map(|s| s.as_str()).ok_or_else(|| anyhow!("missing <{}>\n\n{}", name, USAGE))
+}
+
+fn parse_action(value: Option<&String>) -> Result<&'static str> {
+    match arg(value, "buy|sell")?.to_lowercase().as_str() {
+        "buy" => Ok("buy"),
+        "sell" => Ok("sell"),
+        other => bail!("action must be buy or sell, got '{}'", other),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let app = app::bootstrap().init();
+
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let confirm = raw.iter().any(|a| a == "--confirm");
+    let args: Vec<String> = raw.into_iter().filter(|a| a != "--confirm").collect();
+
+    let command = args.first().map(|s| s.as_str());
+    let venue = args.get(1).map(|s| s.as_str());
+
+    match (command, venue) {
+        (Some("place"), Some("poly")) => {
+            let action = parse_action(args.get(2))?;
+            let token_id = arg(args.get(3), "token_id")?;
+            let price: f64 = parse(args.get(4), "price")?;
+            let size: f64 = parse(args.get(5), "size")?;
+
+            if !(price > 0.0 && price < 1.0) {
+                bail!("price must be between 0 and 1 (exclusive)");
+            }
+            if size < 1.0 {
+                bail!("size must be >= 1");
+            }
+
+            println!("Polymarket FAK {} {:.2} @ {:.3} on token {}", action.to_uppercase(), size, price, token_id);
+            if !confirm {
+                println!("Not sent - re-run with --confirm to place this order");
+                return Ok(());
+            }
+
+            let client = app.poly_client().await?;
+            let fill = if action == "buy" {
+                client.buy_fak(token_id, price, size).await?
+            } else {
+                client.sell_fak(token_id, price, size).await?
+            };
+            println!("✅ order {} | filled {:.2} | cost ${:.4}", fill.order_id, fill.filled_size, fill.fill_cost);
+        }
+
+        (Some("place"), Some("kalshi")) => {
+            let action = parse_action(args.get(2))?;
+            let ticker = arg(args.get(3), "ticker")?;
+            let side = match arg(args.get(4), "yes|no")?.to_lowercase().as_str() {
+                "yes" => "yes",
+                "no" => "no",
+                other => bail!("side must be yes or no, got '{}'", other),
+            };
+            let price_cents: i64 = parse(args.get(5), "price_cents")?;
+            let count: i64 = parse(args.get(6), "count")?;
+
+            if !(1..=99).contains(&price_cents) {
+                bail!("price_cents must be 1-99");
+            }
+            if count < 1 {
+                bail!("count must be >= 1");
+            }
+
+            println!("Kalshi IOC {} {} {} @{}¢ x{}", action.to_uppercase(), side.to_uppercase(), ticker, price_cents, count);
+            if !confirm {
+                println!("Not sent - re-run with --confirm to place this order");
+                return Ok(());
+            }
+
+            let client = app.kalshi_client()?;
+            let resp = if action == "buy" {
+                client.buy_ioc(ticker, side, price_cents, count, OrderStrategy::Manual).await?
+            } else {
+                client.sell_ioc(ticker, side, price_cents, count, OrderStrategy::Manual).await?
+            };
+            let cost = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
+            println!("✅ order {} | status {} | filled {} | cost {}¢",
+                     resp.order.order_id, resp.order.status, resp.order.filled_count(), cost);
+        }
+
+        (Some("cancel"), Some("poly")) => {
+            let order_id = arg(args.get(2), "order_id")?;
+            let Some(resp) = app.poly_client().await?.cancel_order(order_id).await.map(Some)
+                .or_else(|e| defer(e, DeferredAction::Cancel { venue: Venue::Polymarket, order_id: order_id.into() }))?
+            else {
+                return Ok(());
+            };
+            println!("{}", serde_json::to_string_pretty(&resp)?);
+        }
+
+        (Some("cancel"), Some("kalshi")) => {
+            let order_id = arg(args.get(2), "order_id")?;
+            let Some(resp) = app.kalshi_client()?.cancel_order(order_id).await.map(Some)
+                .or_else(|e| defer(e, DeferredAction::Cancel { venue: Venue::Kalshi, order_id: order_id.into() }))?
+            else {
+                return Ok(());
+            };
+            println!("✅ canceled {} | status {} | reduced by {}",
+                     resp.order.order_id, resp.order.status, resp.reduced_by.unwrap_or(0));
+        }
+
+        (Some("query"), Some("poly")) => {
+            let order_id = arg(args.get(2), "order_id")?;
+            let order = app.poly_client().await?.get_order(order_id).await?;
+            println!("order {} | status {} | {} {} @ {} | matched {}/{}",
+                     order.id, order.status, order.side, order.asset_id.as_deref().unwrap_or("?"),
+                     order.price, order.size_matched, order.original_size);
+        }
+
+        (Some("query"), Some("kalshi")) => {
+            let order_id = arg(args.get(2), "order_id")?;
+            let order = app.kalshi_client()?.get_order(order_id).await?.order;
+            println!("order {} | status {} | {} {} {} | yes={:?}¢ no={:?}¢ | filled {} | remaining {}",
+                     order.order_id, order.status, order.action, order.side, order.ticker,
+                     order.yes_price, order.no_price, order.filled_count(),
+                     order.remaining_count.unwrap_or(0));
+        }
+
+        (Some("approve"), Some("poly")) => {
+            submit_onchain(&app, onchain::trading_approvals(), confirm).await?;
+        }
+
+        (Some("redeem"), Some("poly")) => {
+            let condition_id = arg(args.get(2), "condition_id")?;
+            if let Err(e) = submit_onchain(&app, vec![onchain::redeem_positions(condition_id)?], confirm).await {
+                defer::<()>(e, DeferredAction::Redeem { condition_id: condition_id.into() })?;
+            }
+        }
+
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    Ok(())
+}
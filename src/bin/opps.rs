@@ -0,0 +1,222 @@
+// Opportunity History
+//
+// Queries the opportunity history the bots record (every detected arb, taken or
+// not, with its book) to see which assets and hours are worth running for.
+//
+// Usage: cargo run --release --bin opps -- [options]
+//   --file <path>     History file (default: opportunities.jsonl in DATA_DIR)
+//   --days <n>        Only the last n days (default: 30, 0 = everything)
+//   --asset <name>    Only this asset / league
+//   --taken           Only opportunities that were executed
+//   --by-hour         Opportunity count by UTC hour of day instead of by asset
+//   --skips           Why opportunities were not taken, by reason
+//   --by-cycle        Opportunities, fills and exposure by time since the interval opened
+//                     (Up/Down markets), with a per-asset heatmap
+//   --bucket <secs>   Slice width for --by-cycle (default: 30)
+//   --economics       Net profit per trade (mean, std dev) of executed arbs by asset, edge
+//                     band (UNIT_EDGE_BANDS) and interval third, flagging losing buckets
+//   --min-trades <n>  Trades a bucket needs before --economics calls it losing (default: 10)
+
+use anyhow::{anyhow, Result};
+use arb_bot::opportunities::{self, OpportunityRecord};
+use arb_bot::unit_economics;
+use std::collections::BTreeMap;
+
+struct Query {
+    file: String,
+    days: i64,
+    asset: Option<String>,
+    taken_only: bool,
+    by_hour: bool,
+    skips: bool,
+    by_cycle: bool,
+    bucket_secs: i64,
+    economics: bool,
+    min_trades: usize,
+}
+
+impl Query {
+    fn from_args() -> Result<Self> {
+        let mut query = Query {
+            file: opportunities::default_path().display().to_string(),
+            days: 30,
+            asset: None,
+            taken_only: false,
+            by_hour: false,
+            skips: false,
+            by_cycle: false,
+            bucket_secs: 30,
+            economics: false,
+            min_trades: 10,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} requires a value", arg));
+            match arg.as_str() {
+                "--file" => query.file = value()?,
+                "--days" => query.days = value()?.parse().map_err(|e| anyhow!("--days: {}", e))?,
+                "--asset" => query.asset = Some(value()?.to_lowercase()),
+                "--taken" => query.taken_only = true,
+                "--by-hour" => query.by_hour = true,
+                "--skips" => query.skips = true,
+                "--by-cycle" => query.by_cycle = true,
+                "--bucket" => query.bucket_secs = value()?.parse().map_err(|e| anyhow!("--bucket: {}", e))?,
+                "--economics" => query.economics = true,
+                "--min-trades" => query.min_trades = value()?.parse().map_err(|e| anyhow!("--min-trades: {}", e))?,
+                "-h" | "--help" => {
+                    println!("Usage: opps [--file PATH] [--days N] [--asset NAME] [--taken] [--by-hour | --skips | --by-cycle [--bucket SECS] | --economics [--min-trades N]]");
+                    std::process::exit(0);
+                }
+                other => return Err(anyhow!("Unknown argument: {}", other)),
+            }
+        }
+
+        Ok(query)
+    }
+
+    fn matches(&self, opp: &OpportunityRecord) -> bool {
+        if self.taken_only && !opp.taken {
+            return false;
+        }
+        self.asset.as_ref().is_none_or(|a| opp.asset.to_lowercase() == *a)
+    }
+}
+
+fn print_by_asset(records: &[OpportunityRecord]) {
+    println!("{:<12} {:>8} {:>7} {:>9} {:>11}", "ASSET", "OPPS", "TAKEN", "AVG EDGE", "DAILY EDGE");
+    for asset in opportunities::edge_by_asset(records) {
+        println!("{:<12} {:>8} {:>7} {:>8.2}¢ {:>11}",
+                 asset.asset, asset.opportunities, asset.taken, asset.avg_edge * 100.0,
+                 format!("${:.2}", asset.daily_edge));
+    }
+}
+
+fn print_by_hour(records: &[OpportunityRecord]) {
+    let hours = opportunities::by_hour(records);
+    let busiest = hours.iter().map(|h| h.opportunities).max().unwrap_or(0).max(1);
+    println!("{:<6} {:>8} {:>7} {:>10}", "HOUR", "OPPS", "TAKEN", "EDGE");
+    for hour in &hours {
+        let bar = "█".repeat(hour.opportunities * 30 / busiest);
+        println!("{:02}:00  {:>8} {:>7} {:>10}  {}",
+                 hour.hour, hour.opportunities, hour.taken, format!("${:.2}", hour.dollar_edge), bar);
+    }
+}
+
+fn print_skips(records: &[OpportunityRecord]) {
+    let mut reasons: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+    for opp in records.iter().filter(|o| !o.taken) {
+        let entry = reasons.entry(opp.skip_reason.as_deref().unwrap_or("unknown")).or_default();
+        entry.0 += 1;
+        entry.1 += opp.dollar_edge();
+    }
+    println!("{:<24} {:>8} {:>12}", "REASON", "OPPS", "MISSED EDGE");
+    for (reason, (count, edge)) in reasons {
+        println!("{:<24} {:>8} {:>12}", reason, count, format!("${:.2}", edge));
+    }
+}
+
+/// Shade for `value` relative to `max` (heatmap cell)
+fn shade(value: f64, max: f64) -> char {
+    const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+    if value <= 0.0 || max <= 0.0 {
+        return SHADES[0];
+    }
+    SHADES[1 + ((value / max * 3.0).round() as usize).min(3)]
+}
+
+fn print_by_cycle(records: &[OpportunityRecord], bucket_secs: i64) -> Result<()> {
+    let buckets = opportunities::by_cycle(records, bucket_secs);
+    if buckets.is_empty() {
+        return Err(anyhow!("No opportunities carry interval timing (recorded by updown_bot)"));
+    }
+    let busiest = buckets.iter().map(|b| b.opportunities).max().unwrap_or(0).max(1);
+    let total_spent: f64 = buckets.iter().map(|b| b.spent).sum();
+    println!("{:<11} {:>8} {:>7} {:>10} {:>7}", "SINCE OPEN", "OPPS", "TAKEN", "EXPOSURE", "SHARE");
+    for b in &buckets {
+        let bar = "█".repeat(b.opportunities * 30 / busiest);
+        let share = if total_spent > 0.0 { b.spent / total_spent * 100.0 } else { 0.0 };
+        println!("{:>4}-{:<4}s  {:>8} {:>7} {:>10} {:>6.1}%  {}",
+                 b.start_secs, b.start_secs + bucket_secs, b.opportunities, b.taken,
+                 format!("${:.2}", b.spent), share, bar);
+    }
+
+    // Exposure by asset across the interval, one cell per slice
+    let mut by_asset: BTreeMap<&str, Vec<OpportunityRecord>> = BTreeMap::new();
+    for r in records.iter().filter(|r| r.secs_into_interval().is_some()) {
+        by_asset.entry(r.asset.as_str()).or_default().push(r.clone());
+    }
+    let rows: Vec<(&str, Vec<f64>)> = by_asset.into_iter()
+        .map(|(asset, recs)| {
+            let mut cells: Vec<f64> = opportunities::by_cycle(&recs, bucket_secs).iter().map(|b| b.spent).collect();
+            cells.resize(buckets.len(), 0.0);
+            (asset, cells)
+        })
+        .collect();
+    let max = rows.iter().flat_map(|(_, cells)| cells.iter().copied()).fold(0.0, f64::max);
+    println!();
+    println!("Exposure heatmap ({}s per cell, · none → █ most)", bucket_secs);
+    for (asset, cells) in rows {
+        println!("{:<8} {}", asset, cells.iter().map(|v| shade(*v, max)).collect::<String>());
+    }
+    Ok(())
+}
+
+fn print_economics(records: &[OpportunityRecord], min_trades: usize) -> Result<()> {
+    let config = unit_economics::config();
+    let buckets = unit_economics::report(records, &config.edge_bands);
+    if buckets.is_empty() {
+        return Err(anyhow!("No executed arbs with a recorded profit"));
+    }
+    println!("{:<10} {:>9} {:>8} {:>7} {:>10} {:>9} {:>10}", "ASSET", "EDGE ¢", "PHASE", "TRADES", "AVG NET", "STD DEV", "TOTAL");
+    let mut losing = Vec::new();
+    for (bucket, stats) in &buckets {
+        let flag = if stats.unprofitable(min_trades) { "  ⚠️ losing" } else { "" };
+        println!("{:<10} {:>9} {:>8} {:>7} {:>10} {:>9} {:>10}{}",
+                 bucket.asset, bucket.edge, bucket.phase.as_str(), stats.trades,
+                 format!("${:.4}", stats.mean), format!("${:.4}", stats.std_dev()), format!("${:.2}", stats.total), flag);
+        if !flag.is_empty() {
+            losing.push(bucket.to_string());
+        }
+    }
+    if !losing.is_empty() {
+        println!();
+        println!("Buckets losing money over ≥{} trades - to stop trading them:", min_trades);
+        println!("  UNIT_DISABLED_BUCKETS={}", losing.join(","));
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let query = Query::from_args()?;
+
+    let records = opportunities::load(&query.file)
+        .map_err(|e| anyhow!("Could not read opportunity history {}: {}", query.file, e))?;
+    let records = if query.days > 0 {
+        opportunities::within_days(records, query.days, chrono::Utc::now())
+    } else {
+        records
+    };
+    let records: Vec<OpportunityRecord> = records.into_iter().filter(|o| query.matches(o)).collect();
+    if records.is_empty() {
+        return Err(anyhow!("No opportunities in {} match", query.file));
+    }
+
+    let taken = records.iter().filter(|o| o.taken).count();
+    println!("📊 {} | {} opportunities, {} taken{}", query.file, records.len(), taken,
+             if query.days > 0 { format!(" | last {} days", query.days) } else { String::new() });
+    println!();
+
+    if query.by_hour {
+        print_by_hour(&records);
+    } else if query.skips {
+        print_skips(&records);
+    } else if query.by_cycle {
+        print_by_cycle(&records, query.bucket_secs.max(1))?;
+    } else if query.economics {
+        print_economics(&records, query.min_trades)?;
+    } else {
+        print_by_asset(&records);
+    }
+    Ok(())
+}
@@ -0,0 +1,275 @@
+// Positions Viewer
+//
+// Prints open positions, matched pairs, unmatched exposure and P&L from a
+// position file as a formatted table, instead of reading the JSON by hand.
+//
+// Usage: cargo run --release --bin positions -- [options]
+//   --file <path>        Position file (default: positions.json in DATA_DIR;
+//                        the Up/Down bot writes positions_updown.json)
+//   --asset <name>       Filter by asset/market text (btc, eth, sol, xrp or any substring)
+//   --date <YYYY-MM-DD>  Only positions opened on this (UTC) date
+//   --strategy <name>    cross | poly_only | kalshi_only | basket (multi-outcome)
+//   --all                Include resolved positions (default: open only)
+//   --by-asset           Open exposure aggregated per asset instead of per position
+//   --tax-lots           Print per-lot acquisitions/disposals of resolved positions as CSV
+//                        (asset, dates, proceeds, cost basis, gain) for tax tools
+//   --year <YYYY>        With --tax-lots: only lots disposed of in this year
+//   --equity <path>      Summarize a P&L series CSV (equity, peak, max drawdown)
+//                        instead of the position table
+//   --shadow <path>      Per-day simulated vs achievable P&L from a SHADOW_MODE file
+//   --ladder <parent>    Fills of a laddered execution's child orders (parent id from the
+//                        [LADDER] log)
+
+use anyhow::{anyhow, Result};
+use arb_bot::pnl_series::{self, PnlSeries};
+use arb_bot::storage;
+use arb_bot::position_query::{self, PositionFilter};
+use arb_bot::position_tracker::{ArbPosition, PositionTracker};
+use arb_bot::shadow;
+use arb_bot::tax_lots::{self, TaxLot};
+
+/// Width of the market column
+const MARKET_COL: usize = 44;
+
+struct Filters {
+    file: String,
+    query: PositionFilter,
+    by_asset: bool,
+    tax_lots: bool,
+    year: Option<String>,
+    equity: Option<String>,
+    shadow: Option<String>,
+    ladder: Option<String>,
+}
+
+impl Filters {
+    fn from_args() -> Result<Self> {
+        let mut filters = Filters {
+            file: storage::state_path("positions.json").display().to_string(),
+            query: PositionFilter::default(),
+            by_asset: false,
+            tax_lots: false,
+            year: None,
+            equity: None,
+            shadow: None,
+            ladder: None,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} requires a value", arg));
+            match arg.as_str() {
+                "--file" => filters.file = value()?,
+                "--asset" => filters.query.asset = Some(value()?),
+                "--date" => filters.query.date = Some(value()?),
+                "--strategy" => filters.query.strategy = Some(value()?),
+                "--all" => filters.query.include_resolved = true,
+                "--by-asset" => filters.by_asset = true,
+                // Only resolved positions produce lots
+                "--tax-lots" => {
+                    filters.tax_lots = true;
+                    filters.query.include_resolved = true;
+                }
+                "--year" => filters.year = Some(value()?),
+                "--equity" => filters.equity = Some(value()?),
+                "--shadow" => filters.shadow = Some(value()?),
+                "--ladder" => filters.ladder = Some(value()?),
+                "-h" | "--help" => {
+                    println!("Usage: positions [--file PATH] [--asset NAME] [--date YYYY-MM-DD] [--strategy NAME] [--all] [--by-asset] [--tax-lots [--year YYYY]] [--equity PATH] [--shadow PATH] [--ladder PARENT]");
+                    std::process::exit(0);
+                }
+                other => return Err(anyhow!("Unknown argument: {}", other)),
+            }
+        }
+
+        Ok(filters)
+    }
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        format!("{:<width$}", s, width = width)
+    } else {
+        let cut: String = s.chars().take(width - 1).collect();
+        format!("{}…", cut)
+    }
+}
+
+/// Tax lot CSV for the filtered positions (only resolved ones produce lots)
+fn print_tax_lots(filters: &Filters, positions: &[&ArbPosition]) {
+    let lots: Vec<TaxLot> = positions.iter()
+        .flat_map(|p| tax_lots::lots_for_position(p))
+        .filter(|lot| filters.year.as_ref().is_none_or(|y| lot.date_disposed.starts_with(y.as_str())))
+        .collect();
+    print!("{}", tax_lots::to_csv(&lots));
+
+    let gain: f64 = lots.iter().map(|l| l.gain()).sum();
+    eprintln!("📄 {} lot(s) from {} | net gain ${:+.2}", lots.len(), filters.file, gain);
+}
+
+/// Open exposure per asset
+fn print_by_asset(file: &str, tracker: &PositionTracker) {
+    let exposure = position_query::exposure_by_asset(tracker);
+    println!("📂 {} | {} asset(s) with open positions", file, exposure.len());
+    println!();
+    println!("{:<10} {:>9} {:>10} {:>9} {:>11} {:>9} {:>10}",
+             "ASSET", "POSITIONS", "COST", "MATCHED", "UNMATCHED", "NET", "LOCKED");
+    println!("{}", "─".repeat(74));
+    for e in &exposure {
+        println!("{:<10} {:>9} {:>10} {:>9.2} {:>11.2} {:>+9.2} {:>10}",
+                 e.asset, e.positions, format!("${:.2}", e.cost), e.matched, e.unmatched,
+                 e.net_exposure, format!("${:+.2}", e.locked_profit));
+    }
+}
+
+/// Child order fills of one laddered execution
+fn print_ladder(tracker: &PositionTracker, parent: &str) {
+    let fills = position_query::ladder_fills(tracker, parent);
+    println!("🪜 {} | {} child order fill(s)", parent, fills.len());
+    println!();
+    println!("{:<32} {:<4} {:>9} {:>7} {:>9}  ORDER", "TIME", "SIDE", "CONTRACTS", "PRICE", "COST");
+    println!("{}", "─".repeat(80));
+    for f in &fills {
+        println!("{:<32} {:<4} {:>9.2} {:>7.3} {:>9}  {}",
+                 f.timestamp, f.side.to_uppercase(), f.contracts, f.price,
+                 format!("${:.2}", f.contracts * f.price), f.order_id);
+    }
+    let cost: f64 = fills.iter().map(|f| f.contracts * f.price).sum();
+    println!("Total: ${:.2}", cost);
+}
+
+/// Equity curve summary from a P&L series written by the bots
+fn print_equity(path: &str) -> Result<()> {
+    let series = PnlSeries::new(path).load()
+        .map_err(|e| anyhow!("Could not read P&L series {}: {}", path, e))?;
+    let (Some(first), Some(last)) = (series.first(), series.last()) else {
+        return Err(anyhow!("P&L series {} is empty", path));
+    };
+    let peak = series.iter().map(|s| s.equity).fold(f64::MIN, f64::max);
+
+    println!("📈 {} | {} snapshot(s) | {} → {}", path, series.len(), first.timestamp, last.timestamp);
+    println!("Equity: ${:+.2} (start ${:+.2}, peak ${:+.2})", last.equity, first.equity, peak);
+    println!("Open: {} position(s) | cost ${:.2} | unmatched {:.2} | locked ${:.2}",
+             last.open_positions, last.open_cost, last.unmatched_exposure, last.locked_profit);
+    if let Some((dd, from, to)) = pnl_series::max_drawdown(&series) {
+        println!("Max drawdown: ${:.2} ({} → {})", dd, from, to);
+    }
+    Ok(())
+}
+
+/// Simulated (dry-run) vs achievable (live) P&L per day from a shadow trade file
+fn print_shadow(path: &str) -> Result<()> {
+    let days = shadow::daily_summary(path)
+        .map_err(|e| anyhow!("Could not read shadow file {}: {}", path, e))?;
+    if days.is_empty() {
+        return Err(anyhow!("Shadow file {} is empty", path));
+    }
+
+    println!("{:<10}  {:>6}  {:>10}  {:>10}  {:>7}  {:>9}  {:>9}",
+             "DATE", "ARBS", "SIMULATED", "ACHIEVABLE", "CAPTURE", "FULL FILL", "UNMATCHED");
+    for day in &days {
+        let capture = if day.simulated_pnl > 0.0 { day.achievable_pnl / day.simulated_pnl * 100.0 } else { 0.0 };
+        println!("{:<10}  {:>6}  {:>10}  {:>10}  {:>6.0}%  {:>8.0}%  {:>9.0}",
+                 day.date, day.trades,
+                 format!("${:+.2}", day.simulated_pnl), format!("${:+.2}", day.achievable_pnl),
+                 capture, day.full_fills as f64 / day.trades as f64 * 100.0, day.unmatched);
+    }
+    let simulated: f64 = days.iter().map(|d| d.simulated_pnl).sum();
+    let achievable: f64 = days.iter().map(|d| d.achievable_pnl).sum();
+    println!("Total: simulated ${:+.2} | achievable ${:+.2}", simulated, achievable);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let filters = Filters::from_args()?;
+
+    if let Some(path) = &filters.equity {
+        return print_equity(path);
+    }
+    if let Some(path) = &filters.shadow {
+        return print_shadow(path);
+    }
+
+    if !std::path::Path::new(&filters.file).exists() {
+        return Err(anyhow!("Position file not found: {}", filters.file));
+    }
+    let tracker = PositionTracker::load_from(&filters.file)?;
+
+    if filters.tax_lots {
+        let positions: Vec<&ArbPosition> = tracker.all_positions()
+            .into_iter()
+            .filter(|p| filters.query.matches(p))
+            .collect();
+        print_tax_lots(&filters, &positions);
+        return Ok(());
+    }
+    if filters.by_asset {
+        print_by_asset(&filters.file, &tracker);
+        return Ok(());
+    }
+    if let Some(parent) = &filters.ladder {
+        print_ladder(&tracker, parent);
+        return Ok(());
+    }
+
+    let positions = position_query::positions(&tracker, &filters.query);
+
+    println!("📂 {} | {} position(s) shown", filters.file, positions.len());
+    println!();
+    println!("{} {:<11} {:<10} {:<8} {:>9} {:>11} {:>10} {:>10}",
+             truncate("MARKET", MARKET_COL), "STRATEGY", "OPENED", "STATUS",
+             "MATCHED", "UNMATCHED", "COST", "P&L");
+    println!("{}", "─".repeat(MARKET_COL + 76));
+
+    let mut total_cost = 0.0;
+    let mut total_matched = 0.0;
+    let mut total_unmatched = 0.0;
+    let mut total_pnl = 0.0;
+
+    for pos in &positions {
+        let net = pos.net_exposure;
+        let unmatched = if pos.multi_outcome && pos.unmatched > 1e-9 {
+            // Baskets: payout spread across possible winners
+            format!("{:.2} ±", pos.unmatched)
+        } else if net.abs() < 1e-9 {
+            "-".to_string()
+        } else {
+            format!("{:.2} {}", net.abs(), if net > 0.0 { "YES" } else { "NO" })
+        };
+
+        // Open positions show locked-in (guaranteed) profit, resolved ones realized P&L
+        println!("{} {:<11} {:<10} {:<8} {:>9.2} {:>11} {:>10} {:>10}",
+                 truncate(&pos.description, MARKET_COL),
+                 pos.strategy,
+                 pos.opened_at.get(..10).unwrap_or(&pos.opened_at),
+                 pos.status,
+                 pos.matched,
+                 unmatched,
+                 format!("${:.2}", pos.cost),
+                 format!("${:+.2}", pos.pnl));
+
+        total_cost += pos.cost;
+        total_matched += pos.matched;
+        total_unmatched += pos.unmatched;
+        total_pnl += pos.pnl;
+    }
+
+    println!("{}", "─".repeat(MARKET_COL + 76));
+    println!("{} {:<11} {:<10} {:<8} {:>9.2} {:>11.2} {:>10} {:>10}",
+             truncate("TOTAL", MARKET_COL), "", "", "",
+             total_matched, total_unmatched,
+             format!("${:.2}", total_cost),
+             format!("${:+.2}", total_pnl));
+    println!();
+
+    let summary = tracker.summary();
+    println!("Open: {} | Resolved: {} | Daily realized: ${:.2} | All-time: ${:.2}",
+             summary.open_positions, summary.resolved_positions,
+             tracker.daily_pnl(), tracker.all_time_pnl);
+    let windows: Vec<String> = position_query::pnl_windows(&tracker, chrono::Utc::now()).iter()
+        .map(|w| format!("{} ${:+.2} ({})", w.label, w.realized, w.resolved))
+        .collect();
+    println!("Realized by resolution: {}", windows.join(" | "));
+
+    Ok(())
+}
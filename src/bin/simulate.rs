@@ -0,0 +1,136 @@
+// What-If Fill Simulator
+//
+// Walks the current Polymarket books of one binary market the way the bot's
+// FAK orders would fill a YES + NO pair of a given size, and prints the fill
+// prices per leg, fees and net edge. For sanity-checking sizing before
+// changing [assets] max_size or IOC settings.
+//
+// Usage: cargo run --release --bin simulate -- <slug | asset> <contracts>[,<contracts>...] [--ticks N]
+//   slug | asset:  a market slug, or btc | eth | sol | xrp for the current UPDOWN_SERIES interval
+//   contracts:     size per leg; a comma-separated list prints one line per size
+//   --ticks N:     limit each leg N ticks above its best ask (default: 0, at the ask as the bot sends)
+
+use anyhow::{anyhow, Context, Result};
+use arb_bot::app::POLY_CLOB_HOST;
+use arb_bot::asset_params;
+use arb_bot::book::LocalBook;
+use arb_bot::polymarket_clob::fetch_fee_rate_bps;
+use arb_bot::updown_scanner::{UpDownScanner, UPDOWN_ASSETS};
+use arb_bot::what_if::{LegFill, PairFill};
+
+struct Args {
+    target: String,
+    sizes: Vec<f64>,
+    ticks: u32,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let usage = || anyhow!("Usage: simulate <slug | asset> <contracts>[,<contracts>...] [--ticks N]");
+        let mut positional = Vec::new();
+        let mut ticks = 0;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--ticks" => ticks = args.next().ok_or_else(usage)?.parse().map_err(|e| anyhow!("--ticks: {}", e))?,
+                "-h" | "--help" => return Err(usage()),
+                _ => positional.push(arg),
+            }
+        }
+        let [target, sizes] = <[String; 2]>::try_from(positional).map_err(|_| usage())?;
+        let sizes = sizes.split(',')
+            .map(|s| s.trim().parse::<f64>().ok().filter(|n| *n > 0.0).ok_or_else(|| anyhow!("bad size {:?}", s)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { target, sizes, ticks })
+    }
+}
+
+fn print_leg(name: &str, leg: &LegFill) {
+    println!("   {:<4} limit {:.3} | filled {:.2} of {:.2} over {} level(s) | avg {}",
+             name, leg.limit, leg.filled(), leg.wanted, leg.fills.len(),
+             leg.vwap().map(|p| format!("{:.4}", p)).unwrap_or_else(|| "-".into()));
+    for (price, size) in &leg.fills {
+        println!("          {:.3} x {:.2}", price, size);
+    }
+}
+
+fn print_detail(sim: &PairFill) {
+    print_leg("YES", &sim.yes);
+    print_leg("NO", &sim.no);
+    let (cost, fees) = sim.matched_cost();
+    println!();
+    println!("   Matched pairs:  {:.2}", sim.matched());
+    println!("   Cost:           ${:.4} + ${:.4} fees", cost, fees);
+    println!("   Net edge:       ${:.4}{}", sim.net_edge(),
+             sim.edge_per_contract_cents().map(|c| format!(" ({:.2}¢ per pair)", c)).unwrap_or_default());
+    if sim.unmatched() > 0.0 {
+        println!("   ⚠️  Unmatched:   {:.2} contracts (${:.4} held in one outcome)", sim.unmatched(), sim.unmatched_cost());
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let scanner = UpDownScanner::new();
+
+    let slug = if UPDOWN_ASSETS.contains(&args.target.to_lowercase().as_str()) {
+        let _ = scanner.sync_clock().await;
+        let series = scanner.series();
+        series.slug(&args.target.to_lowercase(), series.interval_end(scanner.clock().now_secs(), 0))
+    } else {
+        args.target.clone()
+    };
+
+    let market = scanner.refresh_market(&slug).await?
+        .ok_or_else(|| anyhow!("market {} not found", slug))?;
+    let (yes_token, no_token) = market.get_token_ids()
+        .ok_or_else(|| anyhow!("{} has no token IDs", slug))?;
+    let tick_size = market.order_price_min_tick_size.filter(|t| *t > 0.0).unwrap_or(0.01);
+    let fee_rate_bps = match fetch_fee_rate_bps(&reqwest::Client::new(), POLY_CLOB_HOST, &yes_token).await {
+        Ok(bps) => bps,
+        Err(e) => market.taker_base_fee.map(|f| f.round() as u32)
+            .ok_or(e).context("fee rate unavailable")?,
+    };
+
+    let snapshots = scanner.fetch_books(&[yes_token.clone(), no_token.clone()]).await?;
+    let book = |token: &str| -> Result<LocalBook> {
+        let snapshot = snapshots.iter().find(|b| b.asset_id == token)
+            .ok_or_else(|| anyhow!("no book for token {}", token))?;
+        let mut book = LocalBook::new();
+        book.apply_snapshot(snapshot);
+        Ok(book)
+    };
+    let (yes_book, no_book) = (book(&yes_token)?, book(&no_token)?);
+
+    println!("📊 {}", market.question);
+    println!("   {} | tick {} | min size {} | fee {}bps{}", slug, tick_size,
+             market.order_min_size.unwrap_or(0.0), fee_rate_bps, if market.is_active() { "" } else { " | ⏸️ not accepting orders" });
+    let (yes_ask, no_ask) = (yes_book.best_ask(), no_book.best_ask());
+    println!("   Best asks: YES {} | NO {}",
+             yes_ask.map(|(p, s)| format!("{:.3} x {:.2}", p, s)).unwrap_or_else(|| "-".into()),
+             no_ask.map(|(p, s)| format!("{:.3} x {:.2}", p, s)).unwrap_or_else(|| "-".into()));
+    if let (Some((yes, _)), Some((no, _))) = (yes_ask, no_ask) {
+        let asset = market.get_asset().unwrap_or_default();
+        let threshold = asset_params::params(asset).threshold;
+        println!("   YES + NO: {:.3} vs threshold {:.3} ({})", yes + no, threshold,
+                 if yes + no < threshold { "the bot would fire, fees aside" } else { "no arb at the asks" });
+    }
+    println!();
+
+    if let [size] = args.sizes[..] {
+        println!("🔎 {:.2} contracts per leg, limits {} tick(s) above the asks", size, args.ticks);
+        print_detail(&PairFill::simulate(&yes_book, &no_book, size, args.ticks, tick_size, fee_rate_bps));
+        return Ok(());
+    }
+
+    println!("{:>10} {:>9} {:>9} {:>9} {:>10} {:>9} {:>10}", "SIZE", "YES AVG", "NO AVG", "MATCHED", "NET EDGE", "¢/PAIR", "UNMATCHED");
+    for size in args.sizes {
+        let sim = PairFill::simulate(&yes_book, &no_book, size, args.ticks, tick_size, fee_rate_bps);
+        let avg = |leg: &LegFill| leg.vwap().map(|p| format!("{:.4}", p)).unwrap_or_else(|| "-".into());
+        println!("{:>10.2} {:>9} {:>9} {:>9.2} {:>10.4} {:>9} {:>10.2}",
+                 size, avg(&sim.yes), avg(&sim.no), sim.matched(), sim.net_edge(),
+                 sim.edge_per_contract_cents().map(|c| format!("{:.2}", c)).unwrap_or_else(|| "-".into()),
+                 sim.unmatched());
+    }
+    Ok(())
+}
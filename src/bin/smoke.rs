@@ -0,0 +1,265 @@
+// Smoke Test
+//
+// Exercises every external integration read-only and reports pass / fail per
+// integration, to validate a deployment before DRY_RUN=0. Nothing is ordered,
+// cancelled or sent on chain. Checks whose input comes from an earlier check
+// (a token from Gamma, a ticker from the Kalshi market list) are skipped when
+// that check fails.
+//
+// Usage: cargo run --release --bin smoke -- [options]
+//   --poly-only     Skip Kalshi
+//   --kalshi-only   Skip Polymarket and Polygon
+//
+// Exits non-zero if any check fails.
+
+use anyhow::{anyhow, bail, Result};
+use arb_bot::app::{self, App, POLY_CLOB_HOST};
+use arb_bot::config::POLYMARKET_WS_URL;
+use arb_bot::polymarket_ws_types::{BookSnapshot, MarketSubscribe};
+use arb_bot::updown_scanner::{ActiveUpDownMarket, MarketSeries, UpDownScanner};
+#[cfg(not(feature = "onchain"))]
+use ethers::providers::{Http, Middleware, Provider};
+use futures_util::{SinkExt, StreamExt};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// Longest any single check may take
+const CHECK_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Pass / fail / skip counts, printed as checks complete
+#[derive(Default)]
+struct Report {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl Report {
+    /// Run one check; its value is handed to dependent checks when it passes
+    async fn check<T>(&mut self, name: &str, fut: impl Future<Output = Result<(T, String)>>) -> Option<T> {
+        let start = Instant::now();
+        let result = match tokio::time::timeout(CHECK_TIMEOUT, fut).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("timed out after {}s", CHECK_TIMEOUT.as_secs())),
+        };
+        let ms = start.elapsed().as_millis();
+        match result {
+            Ok((value, detail)) => {
+                self.passed += 1;
+                println!("✅ {:<20} {:>6}ms  {}", name, ms, detail);
+                Some(value)
+            }
+            Err(e) => {
+                self.failed += 1;
+                println!("❌ {:<20} {:>6}ms  {:#}", name, ms, e);
+                None
+            }
+        }
+    }
+
+    fn skip(&mut self, name: &str, why: &str) {
+        self.skipped += 1;
+        println!("⏭️  {:<20} {:>8}  skipped ({})", name, "", why);
+    }
+}
+
+/// Current Up/Down markets from Gamma
+async fn gamma_markets() -> Result<(ActiveUpDownMarket, String)> {
+    let scanner = UpDownScanner::with_series(MarketSeries::from_env());
+    let markets = scanner.scan_active_markets().await?;
+    let Some(first) = markets.first().cloned() else {
+        bail!("no active Up/Down markets (expected {})", scanner.expected_markets());
+    };
+    let assets: Vec<String> = markets.iter().map(|m| m.asset.to_uppercase()).collect();
+    Ok((first, format!("{} markets ({})", markets.len(), assets.join(", "))))
+}
+
+/// REST book of one token
+async fn clob_book(http: &reqwest::Client, token: &str) -> Result<((), String)> {
+    let url = format!("{}/book?token_id={}", POLY_CLOB_HOST, token);
+    let book: BookSnapshot = http.get(&url).send().await?.error_for_status()?.json().await?;
+    if !book.is_well_formed() {
+        bail!("book for {} is empty or malformed", token);
+    }
+    Ok(((), format!("{} bids / {} asks", book.bids.len(), book.asks.len())))
+}
+
+/// Market channel connect, subscribe and first book
+async fn poly_ws(market: &ActiveUpDownMarket) -> Result<((), String)> {
+    let (ws, _) = connect_async(POLYMARKET_WS_URL).await?;
+    let (mut write, mut read) = ws.split();
+    let subscribe = MarketSubscribe::new(vec![market.yes_token.clone(), market.no_token.clone()]);
+    write.send(Message::Text(serde_json::to_string(&subscribe)?)).await?;
+    let start = Instant::now();
+    while let Some(msg) = read.next().await {
+        match msg? {
+            Message::Text(text) => {
+                let books = serde_json::from_str::<Vec<BookSnapshot>>(&text).map(|b| b.len()).unwrap_or(0);
+                return Ok(((), format!("subscribed, first message after {}ms ({} books)",
+                                       start.elapsed().as_millis(), books)));
+            }
+            Message::Close(frame) => bail!("closed by server: {:?}", frame),
+            _ => {}
+        }
+    }
+    bail!("stream ended before any message")
+}
+
+/// API key derivation and USDC balance (authenticated CLOB)
+async fn poly_balance(app: &App) -> Result<((), String)> {
+    let client = app.poly_client().await?;
+    let usdc = client.usdc_balance().await?;
+    Ok(((), format!("${:.2} USDC", usdc)))
+}
+
+/// Latest block from every RPC endpoint used for approvals, redemptions and CTF checks
+#[cfg(feature = "onchain")]
+async fn polygon_rpc() -> Result<((), String)> {
+    use arb_bot::rpc_pool::{RpcConfig, RpcPool};
+    let pool = RpcPool::new(RpcConfig::from_env())?;
+    let up = pool.check_health().await;
+    let shown: Vec<String> = pool.status().iter()
+        .map(|s| match (s.up, s.block) {
+            (true, Some(block)) => format!("{} block {} ({}ms)", s.host, block, s.latency_ms),
+            _ => format!("{} down", s.host),
+        })
+        .collect();
+    if up == 0 {
+        bail!("no endpoint healthy: {}", shown.join(", "));
+    }
+    Ok(((), format!("{}/{} healthy: {}", up, pool.len(), shown.join(", "))))
+}
+
+/// Latest block from the RPC used for approvals, redemptions and CTF checks
+#[cfg(not(feature = "onchain"))]
+async fn polygon_rpc() -> Result<((), String)> {
+    let url = std::env::var("POLYGON_RPC_URL").unwrap_or_else(|_| "https://polygon-rpc.com".to_string());
+    let block = Provider::<Http>::try_from(url.as_str())?.get_block_number().await?;
+    Ok(((), format!("block {} via {}", block, url)))
+}
+
+#[cfg(feature = "oracles")]
+async fn pyth_latest(http: &reqwest::Client) -> Result<((), String)> {
+    use arb_bot::pyth::{self, PythConfig};
+    let body = http.get(PythConfig::from_env().latest_url()).send().await?.error_for_status()?.text().await?;
+    let prices = pyth::parse_update(&body)?;
+    if prices.len() < pyth::FEEDS.len() {
+        bail!("{} of {} feeds returned", prices.len(), pyth::FEEDS.len());
+    }
+    let shown: Vec<String> = prices.iter().map(|(a, p)| format!("{} {:.4}", a.to_uppercase(), p.price)).collect();
+    Ok(((), shown.join(", ")))
+}
+
+#[cfg(feature = "kalshi")]
+mod kalshi_checks {
+    use super::*;
+    use arb_bot::config::get_league_configs;
+    use arb_bot::kalshi::{self, KalshiApiClient, KalshiConfig};
+
+    /// First open event's market across the configured leagues
+    pub async fn markets(client: &KalshiApiClient) -> Result<(Option<String>, String)> {
+        let leagues = get_league_configs();
+        for league in &leagues {
+            let Some(event) = client.get_events(league.kalshi_series_game, 1).await?.into_iter().next() else {
+                continue;
+            };
+            let markets = client.get_markets(&event.event_ticker).await?;
+            let Some(market) = markets.first() else { continue };
+            return Ok((Some(market.ticker.clone()),
+                       format!("{} ({} markets in {})", market.ticker, markets.len(), event.event_ticker)));
+        }
+        Ok((None, format!("no open events in {} series", leagues.len())))
+    }
+
+    pub async fn balance(client: &KalshiApiClient) -> Result<((), String)> {
+        let b = client.get_balance().await?;
+        Ok(((), format!("${:.2} cash, ${:.2} in positions", b.balance as f64 / 100.0, b.portfolio_value as f64 / 100.0)))
+    }
+
+    /// Signed connect and, with a ticker, an orderbook subscription answered by the server
+    pub async fn ws(config: &KalshiConfig, ticker: Option<String>) -> Result<((), String)> {
+        let (mut write, mut read) = kalshi::connect_ws(config).await?.split();
+        let Some(ticker) = ticker else {
+            return Ok(((), "connected (no market to subscribe to)".to_string()));
+        };
+        write.send(kalshi::orderbook_subscribe(vec![ticker])?).await?;
+        while let Some(msg) = read.next().await {
+            match msg? {
+                Message::Text(text) => {
+                    let value: serde_json::Value = serde_json::from_str(&text)?;
+                    if value["type"] == "error" {
+                        bail!("subscribe rejected: {}", value["msg"]);
+                    }
+                    return Ok(((), format!("subscribed, first message: {}", value["type"].as_str().unwrap_or("?"))));
+                }
+                Message::Close(frame) => bail!("closed by server: {:?}", frame),
+                _ => {}
+            }
+        }
+        bail!("stream ended before any message")
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let app = app::bootstrap().crate_level("warn").init();
+
+    let (mut poly, mut kalshi) = (true, true);
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--poly-only" => kalshi = false,
+            "--kalshi-only" => poly = false,
+            "-h" | "--help" => {
+                println!("Usage: smoke [--poly-only | --kalshi-only]");
+                return Ok(());
+            }
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    let http = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()?;
+    let mut report = Report::default();
+
+    if poly {
+        match report.check("Gamma markets", gamma_markets()).await {
+            Some(market) => {
+                report.check("CLOB book", clob_book(&http, &market.yes_token)).await;
+                report.check("Polymarket WS", poly_ws(&market)).await;
+            }
+            None => {
+                report.skip("CLOB book", "no market from Gamma");
+                report.skip("Polymarket WS", "no market from Gamma");
+            }
+        }
+        report.check("Polymarket balance", poly_balance(&app)).await;
+        report.check("Polygon RPC", polygon_rpc()).await;
+        #[cfg(feature = "oracles")]
+        report.check("Pyth prices", pyth_latest(&http)).await;
+    }
+
+    if kalshi {
+        #[cfg(feature = "kalshi")]
+        match app.kalshi_config().and_then(|config| Ok((app.kalshi_client()?, config))) {
+            Ok((client, config)) => {
+                let ticker = report.check("Kalshi markets", kalshi_checks::markets(&client)).await.flatten();
+                report.check("Kalshi balance", kalshi_checks::balance(&client)).await;
+                report.check("Kalshi WS", kalshi_checks::ws(&config, ticker)).await;
+            }
+            Err(e) => {
+                report.failed += 1;
+                println!("❌ {:<20} {:>8}  {:#}", "Kalshi credentials", "", e);
+            }
+        }
+        #[cfg(not(feature = "kalshi"))]
+        report.skip("Kalshi", "built without the kalshi feature");
+    }
+
+    println!("\n{} passed, {} failed, {} skipped", report.passed, report.failed, report.skipped);
+    if report.failed > 0 {
+        bail!("{} integration check(s) failed", report.failed);
+    }
+    Ok(())
+}
@@ -1,17 +1,13 @@
 // Test program for Up/Down market scanner
 
+use arb_bot::app;
 use arb_bot::updown_scanner::UpDownScanner;
 use tracing::info;
 
 #[tokio::main]
 async fn main() {
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("arb_bot=debug".parse().unwrap()),
-        )
-        .init();
+    app::bootstrap().crate_level("debug").init();
 
     info!("🔍 Testing Up/Down Market Scanner");
 
@@ -1,87 +1,244 @@
 // Up/Down Arbitrage Bot
 //
 // Strategy: Buy YES + NO when sum < 100¢ (e.g., 28¢ + 66¢ = 94¢ → 6% profit)
-// Markets: BTC, ETH, SOL, XRP 15-minute Up/Down markets
-
-use anyhow::{Context, Result};
+// Markets: BTC, ETH, SOL, XRP Up/Down markets (UPDOWN_SERIES = 15m | 5m | 1m)
+
+use anyhow::Result;
+use arb_bot::aggregation::{self, ReporterConfig};
+use arb_bot::app;
+use arb_bot::arb_confirm::{self, ArbStreak, BookWarmup};
+use arb_bot::archive::{self, ArchiveConfig};
+use arb_bot::asset_params::{self, AssetParams};
+use arb_bot::basket::{self, Basket, BasketConfig, BasketLeg, Compensation, RepairSide};
+use arb_bot::close_race::{self, CloseRace, CloseRaceConfig, UnverifiedFill};
 use arb_bot::config::POLYMARKET_WS_URL;
-use arb_bot::polymarket_clob::{PolymarketAsyncClient, PreparedCreds, SharedAsyncClient};
+use arb_bot::config_check;
+use arb_bot::control::{self, Caller, ControlConfig, ControlHandler};
+use arb_bot::cost_model::{self, CostModelConfig, FillSample};
+use arb_bot::depth_guard;
+use arb_bot::events::{self, BotEvent};
+use arb_bot::exec_mode::{self, ExecMode};
+use arb_bot::exec_pool::{ExecPool, ExecPoolConfig};
+use arb_bot::exits::{self, ExitConfig};
+#[cfg(feature = "oracles")]
+use arb_bot::exits::ExitReason;
+use arb_bot::feed_liveness::{FeedLiveness, Liveness, LivenessConfig};
+use arb_bot::instruments::{InstrumentCache, InstrumentConfig};
+use arb_bot::intents::{self, IntentJournal, OrderIntent};
+use arb_bot::ladder;
+use arb_bot::leader::{self, LeaderConfig};
+use arb_bot::leg_order::LegOrderConfig;
+use arb_bot::market_key::MarketKey;
+use arb_bot::market_quality::{self, QualityGate};
+use arb_bot::market_sets::MarketSets;
+use arb_bot::metrics;
+#[cfg(feature = "onchain")]
+use arb_bot::onchain;
+use arb_bot::opp_cost::OppCostConfig;
+use arb_bot::notify;
+use arb_bot::opp_rate;
+use arb_bot::opportunities::{self, OpportunityRecord};
+use arb_bot::order_errors::{self, RejectReason};
+use arb_bot::order_manager::{self, ChildOrder};
+use arb_bot::order_rate;
+use arb_bot::overrides::{self, OverridesConfig};
+use arb_bot::pnl_series;
+use arb_bot::position_query::PositionQueries;
+use arb_bot::settlement::{self, SettlementConfig};
+use arb_bot::shadow::{self, ShadowLeg, ShadowRecorder, ShadowTrade};
+use arb_bot::snapshots::{Marks, SnapshotConfig, SnapshotStore};
+use arb_bot::storage;
+use arb_bot::polymarket_ws_types::{BookSnapshot, LastTradeEvent, MarketSubscribe};
+use arb_bot::polymarket_clob::{PolyFillAsync, SharedAsyncClient};
+use arb_bot::position_store::{self, PositionStore, PositionStoreConfig};
+use arb_bot::replay;
 use arb_bot::position_tracker::{FillRecord, PositionTracker, PositionChannel, create_position_channel, position_writer_loop};
-use arb_bot::updown_scanner::{ActiveUpDownMarket, UpDownScanner};
+use arb_bot::runtimes::{self, RuntimeConfig};
+use arb_bot::schema_watch::{self, Outcome};
+use arb_bot::run_windows;
+#[cfg(feature = "oracles")]
+use arb_bot::pyth::{self, PythConfig};
+use arb_bot::risk::{CorrelationConfig, CorrelationGuard};
+use arb_bot::scheduler::{self, CapitalPool, Opportunity};
+#[cfg(feature = "oracles")]
+use arb_bot::settle_predict::{self, Decision, SettlePredictConfig};
+use arb_bot::subscriptions::{self as subs, SubscriptionManager};
+use arb_bot::telemetry;
+use arb_bot::throttle::{ThrottleConfig, TradeThrottle};
+use arb_bot::trade_tape::{self, MarkSource};
+use arb_bot::unit_economics;
+use arb_bot::units;
+use arb_bot::types::poly_fee;
+use arb_bot::venue_health::{self, Venue};
+use arb_bot::updown_scanner::{ActiveUpDownMarket, MarketSeries, UpDownMarket, UpDownScanner};
+use arb_bot::updown_lifecycle;
+use arb_bot::watchdog::{self, WatchdogConfig};
 use futures_util::{SinkExt, StreamExt};
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
 use std::time::Duration;
-use tokio::sync::RwLock;
-use tokio::time::{interval, sleep, Instant};
+use tokio::sync::{mpsc, watch, Notify, RwLock};
+use tokio::time::{interval, sleep, timeout_at, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{error, info, warn};
-
-/// Polymarket CLOB API host
-const POLY_CLOB_HOST: &str = "https://clob.polymarket.com";
-/// Polygon chain ID
-const POLYGON_CHAIN_ID: u64 = 137;
+use tracing::{debug, error, info, warn};
 
 /// Position tracking file (separate from main arb bot)
 const POSITIONS_FILE: &str = "positions_updown.json";
 
-/// Arbitrage threshold - sum of YES + NO must be below this for execution
-/// Example: 0.94 means 94¢, which gives 6% profit (100¢ - 94¢ = 6¢)
-const ARB_THRESHOLD: f64 = 0.995;
+/// In-flight order intent journal (separate from main arb bot)
+const INTENTS_FILE: &str = "inflight_intents_updown.json";
 
-/// Minimum size to trade (in dollars)
-const MIN_TRADE_SIZE: f64 = 1.0;
+/// Default P&L / exposure time series (PNL_SERIES_FILE)
+const PNL_SERIES_FILE: &str = "pnl_series_updown.csv";
 
-/// Maximum size to trade per leg (in dollars)
-const MAX_TRADE_SIZE: f64 = 50.0;
+/// Strategy state snapshot (separate from main arb bot)
+const SNAPSHOT_FILE: &str = "strategy_state_updown.json";
 
-/// Buffer time before market ends to preload next market (seconds)
-/// Example: 60s means we start watching the next 15-min market 1 minute early
-const PRELOAD_BUFFER_SECS: u64 = 60;
+/// Control API halt and asset pauses, restored on startup (separate from main arb bot)
+const CONTROL_STATE_FILE: &str = "control_updown.json";
 
-/// WebSocket book snapshot
-#[derive(Deserialize, Debug)]
-struct BookSnapshot {
-    asset_id: String,
-    #[allow(dead_code)]
-    bids: Vec<PriceLevel>,
-    asks: Vec<PriceLevel>,
-}
+/// Default seconds between metadata re-checks of active markets (MARKET_RECHECK_SECS, 0 = off)
+const DEFAULT_RECHECK_SECS: u64 = 30;
 
-#[derive(Deserialize, Debug)]
-struct PriceLevel {
-    price: String,
-    size: String,
-}
+/// Default window for collecting concurrent opportunities before ranking them (SCHEDULER_WINDOW_MS)
+const DEFAULT_SCHEDULER_WINDOW_MS: u64 = 25;
+
+/// Seconds between syncing the WS subscription with the market map
+const SUBSCRIPTION_SYNC_SECS: u64 = 1;
+
+/// Re-request a token's snapshot if none arrived within this many seconds
+const SNAPSHOT_ACK_TIMEOUT_SECS: u64 = 5;
+
+/// Detected opportunities waiting for the scheduler
+const OPPORTUNITY_QUEUE: usize = 64;
+
+/// Default seconds between mark-to-market logs of open positions (MTM_LOG_SECS, 0 = off)
+const DEFAULT_MTM_LOG_SECS: u64 = 60;
 
 /// Market state with current prices
 #[derive(Debug, Clone)]
 struct MarketState {
     asset: String,
+    slug: String,
     question: String,
+    /// Market key positions, intents and per-market state are recorded under
+    key: MarketKey,
     yes_token: String,
     no_token: String,
     yes_price: f64,
     no_price: f64,
     yes_size: f64,
     no_size: f64,
+    /// Best bids (what an unmatched leg can be sold at)
+    yes_bid: f64,
+    no_bid: f64,
     last_update: Instant,
+    /// Persistence of the current arb across updates
+    streak: ArbStreak,
+    /// Both books loaded with depth - not eligible for execution before this
+    warmup: BookWarmup,
+    /// Unix timestamp when the market closes (refreshed from Gamma)
+    end_timestamp: u64,
+    /// Unix timestamp the interval opened (the reference price Up/Down is measured from)
+    #[cfg_attr(not(feature = "oracles"), allow(dead_code))]
+    open_timestamp: u64,
+    /// No new trades at or after this Unix timestamp (end minus no-trade window)
+    trade_cutoff: u64,
+    /// Threshold, size limits and no-trade window of this asset
+    params: AssetParams,
+    /// Market paused (accepting_orders=false or closed) - never trade into a frozen book
+    halted: bool,
+    /// Taker fee rate (bps) from the scan; profit and threshold checks are net of it
+    fee_rate_bps: u32,
+    /// Minimum price increment from the scan; order prices are rounded to it
+    tick_size: f64,
+    /// From a configured market set rather than the Up/Down series (no interval: the open
+    /// timestamp means nothing, and the set scan, not the interval scan, drops it)
+    from_set: bool,
+    /// New listing's observation window (QUALITY_*); not traded until enrolled
+    quality: Option<QualityGate>,
 }
 
 impl MarketState {
-    fn new(market: &ActiveUpDownMarket) -> Self {
+    fn new(market: &ActiveUpDownMarket, series: MarketSeries) -> Self {
+        let mut params = asset_params::params(&market.asset);
+        // Never size below what the venue accepts
+        params.min_size = params.min_size.max(market.min_size);
         Self {
             asset: market.asset.clone(),
+            slug: market.slug.clone(),
             question: market.question.clone(),
+            key: market.key(),
             yes_token: market.yes_token.clone(),
             no_token: market.no_token.clone(),
             yes_price: 0.0,
             no_price: 0.0,
             yes_size: 0.0,
             no_size: 0.0,
+            yes_bid: 0.0,
+            no_bid: 0.0,
             last_update: Instant::now(),
+            streak: ArbStreak::default(),
+            warmup: BookWarmup::default(),
+            end_timestamp: market.end_timestamp,
+            open_timestamp: market.end_timestamp.saturating_sub(series.interval_secs()),
+            trade_cutoff: market.end_timestamp.saturating_sub(params.no_trade_secs_or(series.no_trade_window_secs())),
+            params,
+            halted: false,
+            fee_rate_bps: market.fee_rate_bps,
+            tick_size: market.tick_size,
+            from_set: false,
+            quality: None,
+        }
+    }
+
+    /// Market of a `[[market_sets.set]]`
+    fn in_set(market: &ActiveUpDownMarket, series: MarketSeries) -> Self {
+        Self {
+            from_set: true,
+            quality: market_quality::gate_at_discovery(market_quality::config(), market_quality::enrolled(),
+                                                       &market.slug, std::time::Instant::now()),
+            ..Self::new(market, series)
+        }
+    }
+
+    /// Apply re-fetched Gamma metadata: halt/resume and end-time changes
+    fn apply_metadata(&mut self, market: &UpDownMarket, series: MarketSeries) {
+        let halted = !market.is_active();
+        if halted != self.halted {
+            if halted {
+                warn!("[SCANNER] ⏸️  {} halted (accepting_orders={:?}, closed={:?}) - pausing execution",
+                      self.asset.to_uppercase(), market.accepting_orders, market.closed);
+            } else {
+                info!("[SCANNER] ▶️  {} accepting orders again - resuming", self.asset.to_uppercase());
+            }
+            self.halted = halted;
         }
+
+        if let Some(end) = market.end_timestamp() {
+            if end != self.end_timestamp {
+                warn!("[SCANNER] {} end time changed: {} → {}", self.asset.to_uppercase(), self.end_timestamp, end);
+                self.end_timestamp = end;
+                self.open_timestamp = end.saturating_sub(series.interval_secs());
+                self.trade_cutoff = end.saturating_sub(self.params.no_trade_secs_or(series.no_trade_window_secs()));
+            }
+        }
+    }
+
+    /// Too close to expiry to open new positions
+    fn in_no_trade_window(&self, now: u64) -> bool {
+        now >= self.trade_cutoff
+    }
+
+    /// Taker fees of one YES + NO pair at the current asks (dollars)
+    fn fee_per_contract(&self) -> f64 {
+        poly_fee(self.yes_price, self.fee_rate_bps) + poly_fee(self.no_price, self.fee_rate_bps)
+    }
+
+    /// Cost of one YES + NO pair at the current asks, fees included (dollars)
+    fn cost_per_contract(&self) -> f64 {
+        self.yes_price + self.no_price + self.fee_per_contract()
     }
 
     /// Check if arbitrage exists
@@ -90,118 +247,287 @@ impl MarketState {
             return false;
         }
 
-        let sum = self.yes_price + self.no_price;
-        sum < ARB_THRESHOLD
+        self.cost_per_contract() < self.params.threshold
     }
 
-    /// Calculate expected profit in cents
+    /// Calculate expected profit in cents (net of fees)
     fn profit_cents(&self) -> f64 {
         if self.yes_price <= 0.0 || self.no_price <= 0.0 {
             return 0.0;
         }
-        (1.0 - (self.yes_price + self.no_price)) * 100.0
+        (1.0 - self.cost_per_contract()) * 100.0
     }
 
     /// Calculate tradeable size based on available liquidity
     fn trade_size(&self) -> f64 {
-        // Use the smaller of the two sides to ensure we can fill both
-        let available = self.yes_size.min(self.no_size);
-        available.min(MAX_TRADE_SIZE).max(MIN_TRADE_SIZE)
+        // Size both legs to the thinner side's best-ask size that has rested DEPTH_MIN_PERSIST_MS
+        // by now (not when the book last updated); below the asset's min size the scheduler drops
+        // it rather than sending more than the thin side can fill
+        let (guard, now) = (depth_guard::guard(), std::time::Instant::now());
+        let yes = guard.persisted(&self.yes_token, self.yes_price, self.yes_size, now);
+        let no = guard.persisted(&self.no_token, self.no_price, self.no_size, now);
+        yes.min(no).min(self.params.max_size)
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("arb_bot=info".parse().unwrap())
-                .add_directive("updown_bot=info".parse().unwrap()),
-        )
-        .init();
+/// Control API halts stop new arbs until re-armed; exits, hedges and settlement carry on
+struct UpDownControl {
+    markets: Arc<RwLock<HashMap<String, MarketState>>>,
+}
 
-    // Load .env file
-    dotenvy::dotenv().ok();
+impl ControlHandler for UpDownControl {
+    async fn halt(&self, caller: &Caller) -> Result<String> {
+        control::switches().halt(&format!("halted through the control API by {}", caller));
+        Ok("no new arbs until re-armed".into())
+    }
 
-    info!("🎯 Up/Down Arbitrage Bot");
-    info!("   Threshold: <{:.0}¢ ({:.1}% profit)", ARB_THRESHOLD * 100.0, (1.0 - ARB_THRESHOLD) * 100.0);
-    info!("   Size: ${:.0}-${:.0} per leg", MIN_TRADE_SIZE, MAX_TRADE_SIZE);
+    async fn rearm(&self, _caller: &Caller) -> Result<String> {
+        Ok(if control::switches().rearm() { "trading re-armed".into() } else { "was not halted".into() })
+    }
+
+    async fn status(&self) -> serde_json::Value {
+        let markets = self.markets.read().await;
+        serde_json::json!({
+            "exec_mode": exec_mode::current().as_str(),
+            "halted": control::switches().halted(),
+            "aggregator_pause": aggregation::paused(),
+            "markets": markets.len(),
+            "halted_markets": markets.values().filter(|m| m.halted).count(),
+        })
+    }
+}
 
-    // Check for dry run mode
-    let dry_run = std::env::var("DRY_RUN")
-        .map(|v| v == "1" || v == "true")
-        .unwrap_or(true);
+fn main() -> Result<()> {
+    // Load .env and initialize logging
+    let app = app::bootstrap().log("updown_bot=info").init();
 
-    if dry_run {
-        info!("   Mode: DRY RUN (set DRY_RUN=0 to execute)");
-    } else {
-        warn!("   Mode: LIVE EXECUTION");
+    // Market data and execution own this runtime; bookkeeping runs on its own (BOOKKEEPING_THREADS)
+    runtimes::hot(&RuntimeConfig::from_env())?.block_on(run(app))
+}
+
+async fn run(app: app::App) -> Result<()> {
+
+    // Fail fast on bad config; `--check-config` stops here
+    config_check::validate(&["updown"])?;
+    if config_check::check_only() {
+        info!("✅ Configuration OK (updown)");
+        return Ok(());
+    }
+
+    info!("🎯 Up/Down Arbitrage Bot");
+    // Threshold and sizes per asset ([assets] in bot.toml)
+    asset_params::start()?;
+    // Blacklist / per-market thresholds / pinned outcomes (OVERRIDES_FILE, reloaded on change)
+    overrides::start(OverridesConfig::from_env())?;
+    let confirm = arb_confirm::config();
+    if !confirm.is_instant() {
+        info!("   Confirmation: {} updates / {}ms", confirm.min_updates, confirm.min_duration_ms);
+    }
+    if depth_guard::guard().enabled() {
+        info!("   Depth guard: ask size counts after resting {}ms", depth_guard::guard().min_persist().as_millis());
     }
 
-    // Load Polymarket credentials
-    let poly_private_key = std::env::var("POLY_PRIVATE_KEY")
-        .context("POLY_PRIVATE_KEY not set")?;
-    let poly_funder = std::env::var("POLY_FUNDER")
-        .context("POLY_FUNDER not set (your wallet address)")?;
+    // Live / dry run / simulated / shadow (EXEC_MODE)
+    let mode = exec_mode::confirm(app.exec_mode()?);
 
     // Create async Polymarket client
-    info!("[POLYMARKET] Creating async client...");
-    let poly_async_client = PolymarketAsyncClient::new(
-        POLY_CLOB_HOST,
-        POLYGON_CHAIN_ID,
-        &poly_private_key,
-        &poly_funder,
-    )?;
-    let api_creds = poly_async_client.derive_api_key(0).await?;
-    let prepared_creds = PreparedCreds::from_api_creds(&api_creds)?;
-    let poly_client = Arc::new(SharedAsyncClient::new(
-        poly_async_client,
-        prepared_creds,
-        POLYGON_CHAIN_ID,
-    ));
+    let poly_client = Arc::new(app.poly_client().await?);
 
     info!("[POLYMARKET] Client ready");
 
+    // Warm standby (LEADER_LEASE): only the lease holder trades, the other keeps its books warm
+    let leadership = leader::start(LeaderConfig::from_env(), "updown")?;
+    let standby = leadership.as_ref().is_some_and(|rx| !*rx.borrow());
+
     // Create position tracker with separate file
-    let position_tracker = Arc::new(RwLock::new(PositionTracker::load_from(POSITIONS_FILE)));
+    // Locked: a second updown bot on the same data directory exits here (a standby reads it unlocked)
+    // Positions recorded under their question before market keys move to the key of the
+    // interval they were opened in when the file is upgraded
+    let series = MarketSeries::from_env();
+    let positions_path = storage::state_path(POSITIONS_FILE);
+    let position_tracker = Arc::new(RwLock::new(if standby {
+        PositionTracker::load_from(&positions_path)?.into_standby()
+    } else {
+        PositionTracker::open_locked(&positions_path, Some(&|q: &str, opened: &str| series.legacy_key(q, opened)))?
+    }));
     let (position_channel, position_rx) = create_position_channel();
+    // Trades behind Polymarket fills are looked up after the order returns
+    position_channel.collect_fill_details();
 
-    // Spawn position writer task
+    // Spawn position writer task; a SQL store (POSITION_STORE) is the record the tracker loads from
     let tracker_clone = position_tracker.clone();
-    tokio::spawn(position_writer_loop(position_rx, tracker_clone));
+    let store_config = PositionStoreConfig::from_env();
+    let position_store = position_store::open_store(&store_config)?;
+    if let Some(store) = &position_store {
+        position_tracker.write().await.load_positions(store.query(None)?);
+    }
+    runtimes::spawn_bookkeeping(position_writer_loop(position_rx, tracker_clone,
+        position_store.map(|s| Box::new(s) as Box<dyn PositionStore>)));
+
+    // Report to a multi-bot aggregator under the store's bot name, so pushed and stored rows dedupe
+    aggregation::spawn_reporter(ReporterConfig::from_env(), store_config.bot.clone(), position_tracker.clone());
+
+    // Equity / exposure time series for long-run analysis
+    pnl_series::spawn_recorder(position_tracker.clone(),
+        storage::state_path(std::env::var("PNL_SERIES_FILE").unwrap_or_else(|_| PNL_SERIES_FILE.to_string())));
 
     // Print initial position summary
     {
         let tracker = position_tracker.read().await;
         let summary = tracker.summary();
-        info!("[POSITIONS] Loaded from {}", POSITIONS_FILE);
+        info!("[POSITIONS] Loaded from {}", tracker.path().display());
         info!("   Open positions: {}", summary.open_positions);
         info!("   Daily P&L: ${:.2}", tracker.daily_pnl());
         info!("   All-time P&L: ${:.2}", tracker.all_time_pnl);
     }
 
-    // Create scanner
-    let scanner = UpDownScanner::new();
+    // Reconcile orders journaled by a run that died mid-execution
+    let intent_journal = Arc::new(IntentJournal::open(storage::state_path(INTENTS_FILE)));
+    if !standby {
+        intents::recover(&intent_journal, None, Some(&poly_client), &position_channel).await;
+    }
+
+    // Trade throttle (limits repeated executions on an oscillating book)
+    let throttle_config = ThrottleConfig::from_env();
+    info!("   Throttle: {}/min global, {}/min per market, {}s min interval",
+          throttle_config.max_per_minute,
+          throttle_config.max_per_market_per_minute,
+          throttle_config.min_interval_secs);
+    let throttle = Arc::new(TradeThrottle::new(throttle_config));
+
+    // Strategy state carried over from the last run: throttle cooldowns, markets halted by a
+    // closed-market rejection (until their interval ends), hold timers of unmatched legs
+    let snapshots = Arc::new(SnapshotStore::load(&SnapshotConfig::from_env(SNAPSHOT_FILE), chrono::Utc::now().timestamp()));
+    let halts = Arc::new(Marks::new("updown.halted"));
+    let unmatched_since = Arc::new(Marks::new("updown.unmatched_since"));
+    snapshots.register(throttle.clone());
+    snapshots.register(halts.clone());
+    snapshots.register(unmatched_since.clone());
+    snapshots.clone().spawn();
+
+    // Correlation guard (Up/Down markets across assets are one volatility bet)
+    let corr_config = CorrelationConfig::from_env();
+    info!("   Correlation cap: {:.0} contracts net per group {:?}",
+          corr_config.max_group_exposure, corr_config.groups);
+    let correlation = Arc::new(CorrelationGuard::new(corr_config));
+    correlation.seed_from_positions(position_tracker.read().await.open_positions());
+
+    if let Some(leadership) = leadership {
+        if !standby {
+            leader::election().expect("election started").promoted();
+        }
+        tokio::spawn(run_takeover(leadership, position_tracker.clone(), intent_journal.clone(),
+                                  poly_client.clone(), position_channel.clone(), correlation.clone(), series));
+    }
+
+    // Capital budget - committed per market until it resolves
+    let capital = Arc::new(CapitalPool::from_env());
+    if let Some(available) = capital.available() {
+        info!("   Capital: ${:.2} (best-ranked opportunities funded first)", available);
+    }
+
+    // Event bus consumers
+    events::spawn_metrics_consumer();
+    events::spawn_audit_consumer();
+    notify::start()?;
+    opportunities::spawn_writer();
+    cost_model::spawn(cost_model::CostModelConfig::from_env());
+    opp_rate::spawn(opp_rate::OppRateConfig::from_env());
+    archive::spawn(ArchiveConfig::from_env(), archive::default_sets());
+    telemetry::spawn(telemetry::TelemetryConfig::from_env());
+    #[cfg(feature = "oracles")]
+    let pyth_config = PythConfig::from_env();
+    #[cfg(feature = "oracles")]
+    pyth::spawn(pyth_config.clone());
+    run_windows::start(&["updown"])?;
+
+    // Polymarket status polling (degrades / pauses trading during incidents)
+    venue_health::spawn_poller(None, true);
+
+    // Deferred cancels and redemptions (queued by any process sharing the buffer; one replays them)
+    replay::spawn(replay::VenueActions {
+        kalshi: None,
+        polymarket: Some(poly_client.clone()),
+        #[cfg(feature = "onchain")]
+        onchain: app.poly_credentials().and_then(|creds| onchain::OnchainClient::new(
+            onchain::OnchainConfig::from_env(), &creds.private_key, &creds.funder,
+        )).map(Arc::new).map_err(|e| warn!("[REPLAY] No onchain client, redemptions stay queued: {}", e)).ok(),
+    });
+
+    // Create scanner for the configured market series
+    info!("   Series: {} | preload {}s early | no trades in last {}s",
+          series, series.preload_buffer_secs(), series.no_trade_window_secs());
+
+    let mut scanner = UpDownScanner::with_series(series);
+    let instrument_config = InstrumentConfig::from_env();
+    if instrument_config.enabled() {
+        scanner = scanner.with_instruments(InstrumentCache::load(&instrument_config, chrono::Utc::now().timestamp() as u64));
+    }
+    let scanner = Arc::new(scanner);
+    if let Err(e) = scanner.sync_clock().await {
+        warn!("[SCANNER] Clock sync failed ({}) - scheduling on the local clock until Gamma responses refine it", e);
+    }
+
+    // Ended markets resolve from Gamma's outcome prices (SETTLE_POLL_SECS)
+    let settle_scanner = scanner.clone();
+    settlement::spawn(SettlementConfig::from_env(), position_tracker.clone(), position_channel.clone(), move |position| {
+        let scanner = settle_scanner.clone();
+        async move {
+            let Some(key) = position.key else { return Ok(None) };
+            Ok(scanner.refresh_market(&key.market).await?.and_then(|m| m.settlement()))
+        }
+    });
+
+    // Scanner → WS feed: new tokens to subscribe right away instead of on the next sync tick
+    let tokens_changed = Arc::new(Notify::new());
 
     // Shared state for active markets
     let markets: Arc<RwLock<HashMap<String, MarketState>>> = Arc::new(RwLock::new(HashMap::new()));
 
+    // Resource leak watch + periodic state dumps
+    let watchdog_markets = markets.clone();
+    let watchdog_positions = position_tracker.clone();
+    let watchdog_capital = capital.clone();
+    watchdog::spawn(WatchdogConfig::from_env(), move || {
+        let (markets, positions, capital) = (watchdog_markets.clone(), PositionQueries::new(watchdog_positions.clone()), watchdog_capital.clone());
+        async move {
+            let markets = markets.read().await;
+            serde_json::json!({
+                "exec_mode": exec_mode::current().as_str(),
+                "markets": markets.len(),
+                "halted_markets": markets.values().filter(|m| m.halted).count(),
+                "observing_markets": markets.values().filter(|m| m.quality.as_ref().is_some_and(|q| !q.enrolled())).count(),
+                "positions": positions.summary().await,
+                "exposure_by_asset": positions.exposure_by_asset().await,
+                "realized_pnl": positions.pnl_windows().await,
+                "capital_available": capital.available(),
+                "capital_reserved": capital.reserved(),
+            })
+        }
+    });
+
+    // Remote halt / pause / re-arm (CONTROL_LISTEN); a halt or pause from before a restart holds
+    control::switches().persist_to(storage::state_path(CONTROL_STATE_FILE))?;
+    control::spawn(ControlConfig::from_env(), Arc::new(UpDownControl { markets: markets.clone() }))?;
+
     // Market scanner task - scans on market expiry with preload buffer
     let scanner_markets = markets.clone();
+    let interval_scanner = scanner.clone();
+    let scanner_capital = capital.clone();
+    let scanner_tokens_changed = tokens_changed.clone();
+    #[cfg(feature = "oracles")]
+    let scanner_pyth = pyth_config.clone();
     let scanner_handle = tokio::spawn(async move {
+        let clock = interval_scanner.clock();
         loop {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+            let now = clock.now_secs();
 
             // Scan for current interval markets
-            match scanner.scan_markets_for_interval(0).await {
+            match interval_scanner.scan_markets_for_interval(0).await {
                 Ok(active_markets) => {
                     if active_markets.is_empty() {
-                        warn!("[SCANNER] No active markets found, retrying in 10s...");
-                        sleep(Duration::from_secs(10)).await;
+                        warn!("[SCANNER] No active markets found, retrying in {}s...", series.retry_secs());
+                        sleep(Duration::from_secs(series.retry_secs())).await;
                         continue;
                     }
 
@@ -216,73 +542,102 @@ async fn main() -> Result<()> {
                             info!("[SCANNER] Current: {} (ends in {}s)",
                                   market.asset.to_uppercase(),
                                   current_end_time.saturating_sub(now));
-                            map.insert(market.yes_token.clone(), MarketState::new(market));
+                            map.insert(market.yes_token.clone(), MarketState::new(market, series));
                         }
                     }
 
                     drop(map);
+                    scanner_tokens_changed.notify_one();
 
-                    // Calculate when to preload next interval
-                    let preload_time = current_end_time.saturating_sub(PRELOAD_BUFFER_SECS);
-                    let time_until_preload = preload_time.saturating_sub(now);
+                    // Calculate when to preload next interval (exchange time, ms precision)
+                    let end_ms = current_end_time * 1000;
+                    let preload_ms = end_ms.saturating_sub(series.preload_buffer_secs() * 1000);
+                    let time_until_preload = clock.until(preload_ms);
 
-                    if time_until_preload > 0 {
-                        info!("[SCANNER] {} active markets | preload in {}s | next scan at expiry+{}s",
+                    if !time_until_preload.is_zero() {
+                        info!("[SCANNER] {} active markets | preload in {:.1}s | next scan at expiry+{}s",
                               active_markets.len(),
-                              time_until_preload,
-                              PRELOAD_BUFFER_SECS);
+                              time_until_preload.as_secs_f64(),
+                              series.preload_buffer_secs());
 
                         // Sleep until preload time
-                        sleep(Duration::from_secs(time_until_preload)).await;
+                        sleep(time_until_preload).await;
                     }
 
                     // Preload next interval markets
-                    info!("[SCANNER] Preloading next interval ({}s early)...", PRELOAD_BUFFER_SECS);
-
-                    match scanner.scan_markets_for_interval(1).await {
-                        Ok(next_markets) => {
-                            let mut map = scanner_markets.write().await;
-
-                            for market in &next_markets {
-                                if !map.contains_key(&market.yes_token) {
-                                    info!("[SCANNER] Next: {} (starts in {}s)",
-                                          market.asset.to_uppercase(),
-                                          current_end_time.saturating_sub(now));
-                                    map.insert(market.yes_token.clone(), MarketState::new(market));
+                    info!("[SCANNER] Preloading next interval ({}s early)...", series.preload_buffer_secs());
+
+                    // Polymarket sometimes lists the next markets late: re-query the
+                    // missing slugs until the interval starts (cached ones cost nothing)
+                    let next_end = current_end_time + series.interval_secs();
+                    let expected = interval_scanner.expected_markets();
+                    let retry = Duration::from_secs(series.preload_retry_secs());
+                    loop {
+                        let listed = match interval_scanner.scan_markets_ending(next_end).await {
+                            Ok(next_markets) => {
+                                let mut map = scanner_markets.write().await;
+                                let mut added = 0;
+
+                                for market in &next_markets {
+                                    if !map.contains_key(&market.yes_token) {
+                                        info!("[SCANNER] Next: {} (starts in {:.1}s)",
+                                              market.asset.to_uppercase(),
+                                              clock.until(end_ms).as_secs_f64());
+                                        map.insert(market.yes_token.clone(), MarketState::new(market, series));
+                                        added += 1;
+                                    }
                                 }
-                            }
 
-                            info!("[SCANNER] Preloaded {} next markets | total active: {}",
-                                  next_markets.len(), map.len());
+                                if added > 0 {
+                                    info!("[SCANNER] Preloaded {} next markets | total active: {}",
+                                          added, map.len());
+                                }
 
-                            drop(map);
+                                drop(map);
+                                // Subscribe now so the books are warm when the interval opens
+                                if added > 0 {
+                                    scanner_tokens_changed.notify_one();
+                                }
+                                next_markets.len()
+                            }
+                            Err(e) => {
+                                warn!("[SCANNER] Failed to preload next markets: {}", e);
+                                0
+                            }
+                        };
+
+                        if listed >= expected {
+                            break;
                         }
-                        Err(e) => {
-                            warn!("[SCANNER] Failed to preload next markets: {}", e);
+                        if clock.until(end_ms) <= retry {
+                            warn!("[SCANNER] Only {}/{} next markets listed by interval start - the rest are picked up by the post-expiry scan",
+                                  listed, expected);
+                            break;
                         }
+                        info!("[SCANNER] {}/{} next markets listed - retrying in {}s", listed, expected, retry.as_secs());
+                        sleep(retry).await;
                     }
 
-                    // Wait until current markets expire, then clean them up
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs();
-                    let time_until_expiry = current_end_time.saturating_sub(now) + 5; // +5s buffer
-
-                    if time_until_expiry > 0 {
-                        info!("[SCANNER] Waiting {}s for current markets to expire...", time_until_expiry);
-                        sleep(Duration::from_secs(time_until_expiry)).await;
+                    // Wake exactly at the boundary - the next interval's first seconds
+                    // are when mispricings are largest
+                    let time_until_end = clock.until(end_ms);
+                    if !time_until_end.is_zero() {
+                        info!("[SCANNER] Next interval opens in {:.1}s", time_until_end.as_secs_f64());
+                        sleep(time_until_end).await;
                     }
+                    info!("[SCANNER] ⏱️  Interval {} open (+{}ms)", current_end_time,
+                          clock.now_ms().saturating_sub(end_ms));
+
+                    // Then give the expired markets their grace period and clean them up
+                    sleep(clock.until(end_ms + series.expiry_grace_secs() * 1000)).await;
 
                     // Remove expired current markets
                     let mut map = scanner_markets.write().await;
                     let before = map.len();
 
-                    map.retain(|token, _| {
-                        // Keep tokens not in expired list
-                        // Check if this token belongs to an expired market
-                        !active_markets.iter().any(|m| &m.yes_token == token || &m.no_token == token)
-                    });
+                    let expired = updown_lifecycle::tokens(active_markets.iter()
+                        .filter(|m| !m.is_live(clock.now_secs(), series.expiry_grace_secs())));
+                    map.retain(|token, _| !expired.contains(token));
 
                     if map.len() < before {
                         info!("[SCANNER] Cleaned up {} expired markets | {} remain",
@@ -291,80 +646,574 @@ async fn main() -> Result<()> {
 
                     drop(map);
 
+                    // Resolved markets pay out - their capital is available again
+                    for market in &active_markets {
+                        scanner_capital.release(&market.key().id());
+                        #[cfg(feature = "oracles")]
+                        log_reference_outcome(&scanner_pyth, market, series);
+                    }
+
                     // Loop continues to scan next interval
                 }
                 Err(e) => {
                     warn!("[SCANNER] Failed: {}", e);
-                    sleep(Duration::from_secs(10)).await;
+                    sleep(Duration::from_secs(series.retry_secs())).await;
+                }
+            }
+        }
+    });
+
+    // Market sets ([market_sets] in bot.toml): slower binary markets found by Gamma tag or
+    // keyword, traded with the same YES+NO check; rescanned for new listings and resolutions
+    let market_sets = MarketSets::load()?;
+    market_sets.log();
+    let sets_markets = markets.clone();
+    let sets_scanner = scanner.clone();
+    let sets_capital = capital.clone();
+    let sets_tokens_changed = tokens_changed.clone();
+    let sets_handle = tokio::spawn(async move {
+        if market_sets.is_empty() {
+            return;
+        }
+        let mut ticker = interval(Duration::from_secs(market_sets.rescan_secs));
+        loop {
+            ticker.tick().await;
+            let mut found = Vec::new();
+            let mut complete = true;
+            for set in &market_sets.sets {
+                match sets_scanner.scan_market_set(set).await {
+                    Ok(markets) => {
+                        debug!("[SETS] {}: {} markets", set.name, markets.len());
+                        found.extend(markets);
+                    }
+                    Err(e) => {
+                        warn!("[SETS] {} scan failed: {}", set.name, e);
+                        complete = false;
+                    }
+                }
+            }
+
+            let mut map = sets_markets.write().await;
+            let mut added = 0;
+            for market in &found {
+                if !map.contains_key(&market.yes_token) {
+                    let state = MarketState::in_set(market, series);
+                    info!("[SETS] Watching {} | {} | ends in {:.1}d{}", market.asset.to_uppercase(), market.question,
+                          market.end_timestamp.saturating_sub(sets_scanner.clock().now_secs()) as f64 / 86_400.0,
+                          if state.quality.is_some() { " | scoring book quality before trading" } else { "" });
+                    map.insert(market.yes_token.clone(), state);
+                    added += 1;
+                }
+            }
+            // Markets that left their set (ended, closed, out of range); only after a complete
+            // scan, so a failed request doesn't drop markets that are still listed
+            let (mut dropped, mut dropped_slugs) = (Vec::new(), Vec::new());
+            if complete {
+                map.retain(|token, state| {
+                    let keep = !state.from_set || found.iter().any(|m| &m.yes_token == token);
+                    if !keep {
+                        info!("[SETS] Dropped {} | {}", state.asset.to_uppercase(), state.question);
+                        dropped.push(state.key.id());
+                        dropped_slugs.push(state.slug.clone());
+                    }
+                    keep
+                });
+            }
+            drop(map);
+            market_quality::enrolled().forget(&dropped_slugs);
+            for id in &dropped {
+                sets_capital.release(id);
+            }
+            if added > 0 || !dropped.is_empty() {
+                sets_tokens_changed.notify_one();
+            }
+        }
+    });
+
+    // Metadata re-check task - pauses markets that stop accepting orders mid-interval
+    let recheck_secs: u64 = std::env::var("MARKET_RECHECK_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RECHECK_SECS);
+    let recheck_markets = markets.clone();
+    let recheck_scanner = scanner.clone();
+    let recheck_handle = tokio::spawn(async move {
+        if recheck_secs == 0 {
+            return;
+        }
+        let mut ticker = interval(Duration::from_secs(recheck_secs));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+
+            let slugs: Vec<(String, String)> = {
+                let map = recheck_markets.read().await;
+                map.iter().map(|(key, m)| (key.clone(), m.slug.clone())).collect()
+            };
+
+            for (key, slug) in slugs {
+                match recheck_scanner.refresh_market(&slug).await {
+                    Ok(Some(market)) => {
+                        if let Some(state) = recheck_markets.write().await.get_mut(&key) {
+                            state.apply_metadata(&market, series);
+                        }
+                    }
+                    Ok(None) => debug!("[SCANNER] Re-check: {} not returned by Gamma", slug),
+                    Err(e) => warn!("[SCANNER] Re-check of {} failed: {}", slug, e),
+                }
+            }
+        }
+    });
+
+    // Exit management for unmatched legs (take profit / stop loss / max hold)
+    let exit_config = ExitConfig::from_env();
+    let exit_handle = if exit_config.enabled() {
+        info!("   Exits: take profit +{:.2}, stop loss -{:.2}, max hold {}s (checked every {}s)",
+              exit_config.take_profit, exit_config.stop_loss, exit_config.max_hold_secs, exit_config.check_secs);
+        tokio::spawn(run_exit_manager(
+            exit_config,
+            markets.clone(),
+            position_tracker.clone(),
+            poly_client.clone(),
+            position_channel.clone(),
+            correlation.clone(),
+            unmatched_since,
+            mode,
+        ))
+    } else {
+        tokio::spawn(async {})
+    };
+
+    // Mark-to-market task - values open positions with trade-based marks when books are thin
+    let mtm_secs: u64 = std::env::var("MTM_LOG_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MTM_LOG_SECS);
+    let mtm_markets = markets.clone();
+    let mtm_tracker = position_tracker.clone();
+    let mtm_handle = tokio::spawn(async move {
+        if mtm_secs == 0 {
+            return;
+        }
+        let mut ticker = interval(Duration::from_secs(mtm_secs));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+            let map = mtm_markets.read().await;
+            let tracker = mtm_tracker.read().await;
+            for state in map.values() {
+                let Some(pos) = tracker.get(&state.key.id()) else { continue };
+                if pos.total_contracts() <= 0.0 {
+                    continue;
                 }
+                let tape = trade_tape::tape();
+                let (Some((yes_mark, yes_src)), Some((no_mark, no_src))) = (
+                    tape.mark(&state.yes_token, None, Some(state.yes_price), now_ms),
+                    tape.mark(&state.no_token, None, Some(state.no_price), now_ms),
+                ) else {
+                    continue;
+                };
+                let unrealized = pos.poly_yes.unrealized_pnl(yes_mark) + pos.poly_no.unrealized_pnl(no_mark);
+                info!("[MTM] {} | Up {:.3} ({}) Down {:.3} ({}) | unrealized ${:+.2}",
+                      state.asset.to_uppercase(), yes_mark, mark_label(yes_src),
+                      no_mark, mark_label(no_src), unrealized);
             }
         }
     });
 
+    // Opportunity scheduler (ranks arbs that arrive together)
+    let window_ms: u64 = std::env::var("SCHEDULER_WINDOW_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_SCHEDULER_WINDOW_MS);
+    let pool_config = ExecPoolConfig::from_env();
+    info!("   Execution pool: {} concurrent, queued arbs expire after {}ms",
+          pool_config.max_concurrent, pool_config.queue_expiry_ms);
+    let exec_pool = Arc::new(ExecPool::new(pool_config));
+    let (opp_tx, opp_rx) = mpsc::channel::<MarketState>(OPPORTUNITY_QUEUE);
+    let sched_handle = tokio::spawn(run_scheduler(
+        opp_rx,
+        Duration::from_millis(window_ms),
+        poly_client.clone(),
+        position_channel.clone(),
+        throttle.clone(),
+        correlation.clone(),
+        intent_journal.clone(),
+        capital.clone(),
+        exec_pool,
+        markets.clone(),
+        ShadowRecorder::from_env(mode).map(Arc::new),
+        halts,
+        mode,
+    ));
+
     // WebSocket price feed task
     let ws_markets = markets.clone();
-    let ws_poly_client = poly_client.clone();
-    let ws_position_channel = position_channel.clone();
+    let ws_tokens_changed = tokens_changed.clone();
+    let ws_scanner = scanner.clone();
+    let subscriptions = Arc::new(Mutex::new(SubscriptionManager::new(Duration::from_secs(SNAPSHOT_ACK_TIMEOUT_SECS))));
     let ws_handle = tokio::spawn(async move {
         loop {
-            if let Err(e) = run_ws_feed(
-                ws_markets.clone(),
-                ws_poly_client.clone(),
-                ws_position_channel.clone(),
-                dry_run,
-            ).await {
+            let mut connected = false;
+            if let Err(e) = run_ws_feed(ws_markets.clone(), subscriptions.clone(), ws_tokens_changed.clone(), opp_tx.clone(),
+                                         ws_scanner.clone(), &mut connected).await {
                 error!("[WS] Disconnected: {} - reconnecting in 5s...", e);
+                events::publish(BotEvent::WsReconnected { feed: "polymarket".into(), error: e.to_string() });
                 sleep(Duration::from_secs(5)).await;
             }
+            venue_health::health().record_feed_exit(Venue::Polymarket, connected);
         }
     });
 
     // Wait for tasks
-    let _ = tokio::join!(scanner_handle, ws_handle);
+    let _ = tokio::join!(scanner_handle, sets_handle, recheck_handle, exit_handle, mtm_handle, sched_handle, ws_handle);
 
     Ok(())
 }
 
-/// Run WebSocket price feed
-async fn run_ws_feed(
+/// How long a leader that lost the lease waits for in-flight fills to reach the tracker
+const LEASE_HANDOVER_FLUSH: Duration = Duration::from_secs(1);
+
+/// Follow the lease: on winning it, take the position file over from the old leader,
+/// re-seed exposure and reconcile its journaled orders before trading; on losing it, hand the file back
+async fn run_takeover(
+    mut leadership: watch::Receiver<bool>,
+    tracker: Arc<RwLock<PositionTracker>>,
+    journal: Arc<IntentJournal>,
+    poly_client: Arc<SharedAsyncClient>,
+    position_channel: PositionChannel,
+    correlation: Arc<CorrelationGuard>,
+    series: MarketSeries,
+) {
+    let Some(election) = leader::election() else { return };
+    while leadership.changed().await.is_ok() {
+        let holds = *leadership.borrow_and_update();
+        if !holds {
+            // Fills still on their way to the tracker belong in the file the next leader loads
+            if !position_channel.flush(LEASE_HANDOVER_FLUSH).await {
+                warn!("[LEADER] Position writer didn't catch up within {}s - handing over what it has",
+                      LEASE_HANDOVER_FLUSH.as_secs());
+            }
+            tracker.write().await.demote();
+            warn!("[LEADER] ⏸️ Lost the lease - standing by, position file saved and released");
+            continue;
+        }
+        if !tracker.read().await.is_standby() {
+            election.promoted();
+            continue;
+        }
+        let promoted = tracker.write().await.promote(Some(&|q: &str, opened: &str| series.legacy_key(q, opened)));
+        let promoted = match promoted {
+            Ok(()) => reload_from_store(&tracker).await,
+            Err(e) => Err(e),
+        };
+        match promoted {
+            Ok(()) => {
+                let tracker = tracker.read().await;
+                correlation.reset();
+                correlation.seed_from_positions(tracker.open_positions());
+                info!("[LEADER] Took over {} ({} open positions)", tracker.path().display(), tracker.summary().open_positions);
+            }
+            Err(e) => {
+                warn!("[LEADER] Takeover failed ({:#}) - giving the lease back", e);
+                election.resign();
+                continue;
+            }
+        }
+        intents::recover(&journal, None, Some(&poly_client), &position_channel).await;
+        election.promoted();
+    }
+}
+
+/// With a SQL store of record (POSITION_STORE), take the positions the old leader wrote there
+async fn reload_from_store(tracker: &RwLock<PositionTracker>) -> Result<()> {
+    let config = PositionStoreConfig::from_env();
+    let positions = tokio::task::spawn_blocking(move || {
+        position_store::open_store(&config)?.map(|s| s.query(None)).transpose()
+    }).await??;
+    if let Some(positions) = positions {
+        tracker.write().await.load_positions(positions);
+    }
+    Ok(())
+}
+
+/// Periodically check unmatched legs against the exit rules and sell the ones that hit.
+/// Sales are recorded as negative fills (hedging trades) so the position nets down.
+#[allow(clippy::too_many_arguments)]
+async fn run_exit_manager(
+    config: ExitConfig,
     markets: Arc<RwLock<HashMap<String, MarketState>>>,
+    tracker: Arc<RwLock<PositionTracker>>,
     poly_client: Arc<SharedAsyncClient>,
     position_channel: PositionChannel,
-    dry_run: bool,
-) -> Result<()> {
-    // Get token list
-    let tokens = {
-        let map = markets.read().await;
-        map.values()
-            .flat_map(|m| vec![m.yes_token.clone(), m.no_token.clone()])
-            .collect::<Vec<_>>()
+    correlation: Arc<CorrelationGuard>,
+    unmatched_since: Arc<Marks>,
+    mode: ExecMode,
+) {
+    let mut dry_run_reported: HashSet<String> = HashSet::new();
+    let close_race = CloseRaceConfig::from_env();
+    #[cfg(feature = "oracles")]
+    let (predictor, pyth_config) = (SettlePredictConfig::from_env(), PythConfig::from_env());
+    #[cfg(feature = "oracles")]
+    let mut last_decision: HashMap<String, Decision> = HashMap::new();
+    let mut ticker = interval(Duration::from_secs(config.check_secs));
+
+    loop {
+        ticker.tick().await;
+        if !leader::is_leader() {
+            continue;
+        }
+
+        // (state, side, contracts, entry) for every market with an unmatched leg
+        let candidates: Vec<(MarketState, &'static str, f64, f64)> = {
+            let map = markets.read().await;
+            let tracker = tracker.read().await;
+            map.values()
+                .filter_map(|state| {
+                    let pos = tracker.get(&state.key.id())?;
+                    let (side, contracts, entry) = exits::unmatched_poly_leg(pos)?;
+                    Some((state.clone(), side, contracts, entry))
+                })
+                .collect()
+        };
+        let live: HashSet<String> = candidates.iter().map(|(s, ..)| s.key.id()).collect();
+        unmatched_since.retain(|id, _| live.contains(id));
+        #[cfg(feature = "oracles")]
+        last_decision.retain(|id, _| live.contains(id));
+
+        for (state, side, contracts, entry) in candidates {
+            let now = chrono::Utc::now().timestamp();
+            let held_secs = (now - unmatched_since.get_or_insert(&state.key.id(), now)).max(0) as u64;
+            // Closing markets are held to settlement: a sell would race the frozen book
+            if state.halted || close_race.closing(Some(state.end_timestamp as i64), chrono::Utc::now().timestamp()) {
+                continue;
+            }
+            let (token, bid) = if side == "yes" { (&state.yes_token, state.yes_bid) } else { (&state.no_token, state.no_bid) };
+            let reason = config.evaluate(entry, bid, held_secs);
+            // Final minute: hold or dump on where the reference says the interval settles
+            #[cfg(feature = "oracles")]
+            let reason = match predict_exit(&predictor, &pyth_config, &state, side, bid, &mut last_decision) {
+                Some(Decision::Hold) => None,
+                Some(Decision::Dump) => reason.or(Some(ExitReason::SettlePrediction)),
+                _ => reason,
+            };
+            let Some(reason) = reason else { continue };
+
+            let label = if side == "yes" { "Up" } else { "Down" };
+            if mode == ExecMode::Simulated {
+                info!("[EXIT] {} {}: paper sell {:.1} @ {:.3} (entry {:.3}) - {} (SIMULATED)",
+                      state.asset.to_uppercase(), label, contracts, bid, entry, reason);
+                position_channel.record_fill(FillRecord::keyed(
+                    &state.key, &state.question, "polymarket", side,
+                    -contracts, bid, 0.0, &exec_mode::simulated_order_id(),
+                ));
+                let (yes, no) = if side == "yes" { (-contracts, 0.0) } else { (0.0, -contracts) };
+                correlation.record_fill(&state.key, &state.asset, yes, no);
+                continue;
+            }
+            if !mode.is_live() {
+                if dry_run_reported.insert(state.key.id()) {
+                    info!("[EXIT] {} {}: would sell {:.1} @ {:.3} (entry {:.3}) - {} (DRY RUN)",
+                          state.asset.to_uppercase(), label, contracts, bid, entry, reason);
+                }
+                continue;
+            }
+
+            info!("[EXIT] {} {}: selling {:.1} @ {:.3} (entry {:.3}) - {}",
+                  state.asset.to_uppercase(), label, contracts, bid, entry, reason);
+            match poly_client.sell_fak(token, bid, contracts).await {
+                Ok(fill) if fill.filled_size > 0.0 => {
+                    let avg_price = fill.fill_cost / fill.filled_size;
+                    position_channel.record_fill(FillRecord::keyed(
+                        &state.key, &state.question, "polymarket", side,
+                        -fill.filled_size, avg_price, 0.0, &fill.order_id,
+                    ).with_detail(&fill.detail));
+                    let (yes, no) = if side == "yes" { (-fill.filled_size, 0.0) } else { (0.0, -fill.filled_size) };
+                    correlation.record_fill(&state.key, &state.asset, yes, no);
+                    events::publish(BotEvent::Hedged {
+                        market: state.question.clone(),
+                        platform: "polymarket".into(),
+                        side: side.into(),
+                        contracts: fill.filled_size,
+                        proceeds: fill.fill_cost,
+                    });
+                    metrics::incr(&format!("exits.{}", reason.to_string().replace(' ', "_")));
+                    info!("[EXIT] ✅ Sold {:.1} for ${:.2} ({:+.2} vs entry)",
+                          fill.filled_size, fill.fill_cost, fill.fill_cost - entry * fill.filled_size);
+                }
+                Ok(_) => warn!("[EXIT] {} {}: no fill at {:.3}", state.asset.to_uppercase(), label, bid),
+                Err(e) => warn!("[EXIT] {} {}: sell failed: {}", state.asset.to_uppercase(), label, e),
+            }
+        }
+    }
+}
+
+/// Settlement prediction for an unmatched leg inside SETTLE_PREDICT_WINDOW_SECS of its
+/// interval's end (PYTH=1). Logged whenever the decision changes, for post-hoc validation
+/// against the resolution.
+#[cfg(feature = "oracles")]
+fn predict_exit(
+    predictor: &SettlePredictConfig,
+    pyth_config: &PythConfig,
+    state: &MarketState,
+    side: &str,
+    bid: f64,
+    last_decision: &mut HashMap<String, Decision>,
+) -> Option<Decision> {
+    let now = chrono::Utc::now().timestamp() as u64;
+    // Market-set markets have no reference price to predict from
+    if state.from_set || !pyth_config.enabled() || !predictor.in_window(state.end_timestamp, now) {
+        return None;
+    }
+    let Some(p) = predictor.predict(pyth::prices(), &state.asset, state.open_timestamp, state.end_timestamp,
+                                    now, pyth_config.max_age_secs) else {
+        metrics::incr("settle_predict.no_reference");
+        return None;
     };
+    let decision = settle_predict::decide(p.p_win(side), bid, predictor.margin);
+    let label = if side == "yes" { "Up" } else { "Down" };
+    if last_decision.insert(state.key.id(), decision) != Some(decision) {
+        metrics::incr(&format!("settle_predict.{}", decision.as_str()));
+        info!("[PREDICT] {} {}: P(Up)={:.3} (spot {:.4} vs open {:.4}, {}s left, σ {:.1}bps/min) bid {:.3} → {}",
+              state.asset.to_uppercase(), label, p.p_up, p.spot, p.open, p.secs_left, p.vol_bps_per_min, bid, decision.as_str());
+    } else {
+        debug!("[PREDICT] {} {}: P(Up)={:.3} bid {:.3} → {}", state.asset.to_uppercase(), label, p.p_up, bid, decision.as_str());
+    }
+    Some(decision)
+}
+
+/// Canonical token set = both tokens of every tracked market
+async fn sync_desired_tokens(
+    markets: &RwLock<HashMap<String, MarketState>>,
+    subscriptions: &Mutex<SubscriptionManager>,
+) {
+    let tokens: Vec<String> = markets.read().await.values()
+        .flat_map(|m| [m.yes_token.clone(), m.no_token.clone()])
+        .collect();
+    subscriptions.lock().unwrap().set_desired(tokens);
+}
+
+/// Send subscribe/unsubscribe operations for the difference between the market map and the
+/// live subscription, returning the tokens subscribed to
+async fn sync_subscriptions<S>(
+    write: &mut S,
+    markets: &RwLock<HashMap<String, MarketState>>,
+    subscriptions: &Mutex<SubscriptionManager>,
+) -> Result<Vec<String>>
+where
+    S: futures_util::Sink<Message, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+{
+    sync_desired_tokens(markets, subscriptions).await;
+    let diff = subscriptions.lock().unwrap().diff(Instant::now());
+    if !diff.subscribe.is_empty() {
+        let msg = subs::operation_message(&diff.subscribe, "subscribe");
+        write.send(Message::Text(serde_json::to_string(&msg)?)).await?;
+        info!("[WS] Subscribed to {} more tokens", diff.subscribe.len());
+    }
+    if !diff.unsubscribe.is_empty() {
+        let msg = subs::operation_message(&diff.unsubscribe, "unsubscribe");
+        write.send(Message::Text(serde_json::to_string(&msg)?)).await?;
+        debug!("[WS] Unsubscribed from {} expired tokens", diff.unsubscribe.len());
+    }
+    Ok(diff.subscribe)
+}
 
+/// Fill the books of newly subscribed tokens from REST instead of waiting for the server's
+/// first push. Awaited before the WS stream is read again, so the snapshots queued there
+/// are applied on top.
+async fn cold_start_books(
+    scanner: &UpDownScanner,
+    markets: &Arc<RwLock<HashMap<String, MarketState>>>,
+    opp_tx: &mpsc::Sender<MarketState>,
+    tokens: &[String],
+) {
     if tokens.is_empty() {
+        return;
+    }
+    let start = Instant::now();
+    match scanner.fetch_books(tokens).await {
+        Ok(books) => {
+            let mut loaded = 0;
+            for book in books.iter().filter(|b| b.is_well_formed()) {
+                match process_book(markets, opp_tx, book).await {
+                    Ok(()) => loaded += 1,
+                    Err(e) => warn!("[WS] Error processing REST book: {}", e),
+                }
+            }
+            metrics::incr("ws.polymarket.cold_start");
+            info!("[WS] Cold-started {}/{} books from REST in {}ms", loaded, tokens.len(), start.elapsed().as_millis());
+        }
+        Err(e) => warn!("[WS] REST book fetch failed ({}) - waiting for the WS snapshots", e),
+    }
+}
+
+/// Forget cached prices so nothing trades on a book from a dropped connection
+async fn invalidate_books(markets: &RwLock<HashMap<String, MarketState>>) {
+    for state in markets.write().await.values_mut() {
+        state.yes_price = 0.0;
+        state.no_price = 0.0;
+        state.yes_size = 0.0;
+        state.no_size = 0.0;
+        state.yes_bid = 0.0;
+        state.no_bid = 0.0;
+        state.warmup.reset();
+    }
+}
+
+fn mark_label(source: MarkSource) -> &'static str {
+    match source {
+        MarkSource::Mid => "mid",
+        MarkSource::LastTrade => "trade",
+        MarkSource::Quote => "ask",
+    }
+}
+
+/// Run WebSocket price feed (`connected` is set once a socket is open)
+async fn run_ws_feed(
+    markets: Arc<RwLock<HashMap<String, MarketState>>>,
+    subscriptions: Arc<Mutex<SubscriptionManager>>,
+    tokens_changed: Arc<Notify>,
+    opp_tx: mpsc::Sender<MarketState>,
+    scanner: Arc<UpDownScanner>,
+    connected: &mut bool,
+) -> Result<()> {
+    let cold_start = app::env_flag("BOOK_COLD_START", true);
+    sync_desired_tokens(&markets, &subscriptions).await;
+    if subscriptions.lock().unwrap().desired().is_empty() {
         info!("[WS] No markets to monitor, waiting...");
-        sleep(Duration::from_secs(10)).await;
+        let _ = tokio::time::timeout(Duration::from_secs(10), tokens_changed.notified()).await;
         return Ok(());
     }
 
     info!("[WS] Connecting to Polymarket WebSocket...");
     let (ws_stream, _) = connect_async(POLYMARKET_WS_URL).await?;
     info!("[WS] Connected");
+    *connected = true;
 
     let (mut write, mut read) = ws_stream.split();
 
+    // Books cached from the previous connection are stale until fresh snapshots arrive
+    invalidate_books(&markets).await;
+    sync_desired_tokens(&markets, &subscriptions).await;
+    let tokens = subscriptions.lock().unwrap().on_connect(Instant::now());
+
     // Subscribe to all tokens
-    let subscribe_msg = serde_json::json!({
-        "assets_ids": tokens,
-        "type": "market"
-    });
+    let subscribe_msg = MarketSubscribe::new(tokens.clone());
 
     write
         .send(Message::Text(serde_json::to_string(&subscribe_msg)?))
         .await?;
     info!("[WS] Subscribed to {} tokens", tokens.len());
+    if cold_start {
+        cold_start_books(&scanner, &markets, &opp_tx, &tokens).await;
+    }
 
     let mut ping_interval = interval(Duration::from_secs(30));
-    let mut last_message = Instant::now();
+    let mut sync_interval = interval(Duration::from_secs(SUBSCRIPTION_SYNC_SECS));
+    let mut liveness = FeedLiveness::new(LivenessConfig::from_env(), "ws.polymarket", Instant::now());
+    liveness.set_tokens(&tokens, Instant::now());
+    let messages = metrics::counter("ws.polymarket.messages");
 
     loop {
         tokio::select! {
@@ -373,34 +1222,81 @@ async fn run_ws_feed(
                     error!("[WS] Failed to send ping: {}", e);
                     break;
                 }
+
+                // Pongs keep the transport alive; books going quiet gets snapshots re-requested
+                match liveness.check(Instant::now()) {
+                    Liveness::Alive => {}
+                    Liveness::Refresh(idle) => {
+                        info!("[WS] 🔄 Re-requesting {} quiet snapshot(s) (last market data {}s ago)",
+                              idle.len(), liveness.data_age(Instant::now()).as_secs());
+                        let msg = subs::operation_message(&idle, "subscribe");
+                        write.send(Message::Text(serde_json::to_string(&msg)?)).await?;
+                        if cold_start {
+                            cold_start_books(&scanner, &markets, &opp_tx, &idle).await;
+                        }
+                    }
+                    Liveness::Reconnect(reason) => {
+                        warn!("[WS] Stale connection ({}), reconnecting...", reason);
+                        break;
+                    }
+                }
+            }
+
+            // Pick up markets added/removed since connect and re-request missing snapshots
+            _ = sync_interval.tick() => {
+                let added = sync_subscriptions(&mut write, &markets, &subscriptions).await?;
+                liveness.set_tokens(subscriptions.lock().unwrap().desired(), Instant::now());
+                if cold_start {
+                    cold_start_books(&scanner, &markets, &opp_tx, &added).await;
+                }
+            }
+
+            // Scanner added markets: subscribe immediately
+            _ = tokens_changed.notified() => {
+                let added = sync_subscriptions(&mut write, &markets, &subscriptions).await?;
+                liveness.set_tokens(subscriptions.lock().unwrap().desired(), Instant::now());
+                if cold_start {
+                    cold_start_books(&scanner, &markets, &opp_tx, &added).await;
+                }
             }
 
             msg = read.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        last_message = Instant::now();
+                        liveness.on_transport(Instant::now());
+                        messages.fetch_add(1, Ordering::Relaxed);
 
                         // Try to parse as book snapshot
                         if let Ok(books) = serde_json::from_str::<Vec<BookSnapshot>>(&text) {
                             for book in &books {
-                                if let Err(e) = process_book(
-                                    &markets,
-                                    &poly_client,
-                                    &position_channel,
-                                    book,
-                                    dry_run,
-                                ).await {
+                                schema_watch::monitor().observe("polymarket", "book",
+                                    Outcome::Parsed { populated: book.is_well_formed() });
+                                subscriptions.lock().unwrap().acknowledge(&book.asset_id);
+                                liveness.on_data(&book.asset_id, Instant::now());
+                                if let Err(e) = process_book(&markets, &opp_tx, book).await {
                                     warn!("[WS] Error processing book: {}", e);
                                 }
                             }
                         }
+                        // Trade prints feed realized prices and mark-to-market
+                        else if let Ok(trade) = serde_json::from_str::<LastTradeEvent>(&text) {
+                            if trade.event_type.as_deref() == Some("last_trade_price") {
+                                schema_watch::monitor().observe("polymarket", "last_trade_price",
+                                    Outcome::Parsed { populated: trade.to_print().is_some() });
+                                liveness.on_data(&trade.asset_id, Instant::now());
+                                process_trade(&markets, &trade).await;
+                            }
+                        }
+                        else {
+                            schema_watch::observe_unparsed("polymarket", &text, &["book", "last_trade_price"]);
+                        }
                     }
                     Some(Ok(Message::Ping(data))) => {
                         let _ = write.send(Message::Pong(data)).await;
-                        last_message = Instant::now();
+                        liveness.on_transport(Instant::now());
                     }
                     Some(Ok(Message::Pong(_))) => {
-                        last_message = Instant::now();
+                        liveness.on_transport(Instant::now());
                     }
                     Some(Ok(Message::Close(frame))) => {
                         warn!("[WS] Server closed: {:?}", frame);
@@ -418,42 +1314,96 @@ async fn run_ws_feed(
                 }
             }
         }
-
-        // Check for stale connection
-        if last_message.elapsed() > Duration::from_secs(120) {
-            warn!("[WS] Stale connection, reconnecting...");
-            break;
-        }
     }
 
     Ok(())
 }
 
-/// Process book snapshot and check for arbitrage
+/// Record a trade print and flag it if it went through our cached ask
+async fn process_trade(markets: &Arc<RwLock<HashMap<String, MarketState>>>, trade: &LastTradeEvent) {
+    let Some(print) = trade.to_print() else { return };
+    trade_tape::tape().record(&trade.asset_id, print);
+    metrics::incr("poly.trades");
+
+    let map = markets.read().await;
+    for state in map.values() {
+        let (label, ask) = if state.yes_token == trade.asset_id {
+            ("Up", state.yes_price)
+        } else if state.no_token == trade.asset_id {
+            ("Down", state.no_price)
+        } else {
+            continue;
+        };
+        if trade_tape::crosses_quote(&print, None, Some(ask)) {
+            metrics::incr("poly.trades.crossed_quote");
+            debug!("[WS] {} {} traded {:.3} x {:.1} through cached ask {:.3}",
+                   state.asset.to_uppercase(), label, print.price, print.size, ask);
+        }
+        break;
+    }
+}
+
+/// Process book snapshot and hand confirmed arbs to the scheduler
 async fn process_book(
     markets: &Arc<RwLock<HashMap<String, MarketState>>>,
-    poly_client: &Arc<SharedAsyncClient>,
-    position_channel: &PositionChannel,
+    opp_tx: &mpsc::Sender<MarketState>,
     book: &BookSnapshot,
-    dry_run: bool,
 ) -> Result<()> {
-    // Find best ask (lowest price for buying)
-    let best_ask = book
+    let asks: Vec<(f64, f64)> = book
         .asks
         .iter()
         .filter_map(|l| {
-            let price: f64 = l.price.parse().ok()?;
-            let size: f64 = l.size.parse().ok()?;
+            let price = units::parse_amount(&l.price)?;
+            let size = units::parse_amount(&l.size)?;
             if price > 0.0 && size > 0.0 {
                 Some((price, size))
             } else {
                 None
             }
         })
+        .collect();
+    // Level persistence, so depth flashed and pulled doesn't size trades
+    let now = std::time::Instant::now();
+    let guard = depth_guard::guard();
+    guard.observe(&book.asset_id, &asks, now);
+
+    // Find best ask (lowest price for buying)
+    let best_ask = asks.iter()
+        .copied()
         .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
         .unwrap_or((0.0, 0.0));
+    let depth = guard.persisted(&book.asset_id, best_ask.0, best_ask.1, now);
+
+    // Best bid (highest price we could sell at)
+    let best_bid = book
+        .bids
+        .iter()
+        .filter_map(|l| {
+            let price = units::parse_amount(&l.price)?;
+            let size = units::parse_amount(&l.size)?;
+            if price > 0.0 && size > 0.0 { Some(price) } else { None }
+        })
+        .fold(0.0, f64::max);
 
     if best_ask.0 == 0.0 {
+        // One-sided (or empty) book, typical near expiry: the side's last ask is gone, so clear
+        // it rather than keep pricing arbs off it, and keep the bid current for exits
+        if let Some(state) = markets.write().await.values_mut()
+            .find(|m| m.yes_token == book.asset_id || m.no_token == book.asset_id)
+        {
+            let is_yes = state.yes_token == book.asset_id;
+            if is_yes {
+                (state.yes_price, state.yes_size, state.yes_bid) = (0.0, 0.0, best_bid);
+            } else {
+                (state.no_price, state.no_size, state.no_bid) = (0.0, 0.0, best_bid);
+            }
+            state.streak.observe(false);
+            if state.warmup.clear(is_yes) {
+                metrics::incr("updown.one_sided");
+                warn!("[WS] {} {} book has no asks - ineligible until both sides repopulate",
+                      state.asset.to_uppercase(), if is_yes { "Up" } else { "Down" });
+            }
+        }
         return Ok(());
     }
 
@@ -462,50 +1412,553 @@ async fn process_book(
 
     // Find which market this token belongs to
     let mut updated_market: Option<MarketState> = None;
+    let min_depth = arb_confirm::warmup_min_depth();
 
     for state in map.values_mut() {
-        if state.yes_token == book.asset_id {
+        let is_yes = if state.yes_token == book.asset_id {
             state.yes_price = best_ask.0;
             state.yes_size = best_ask.1;
-            state.last_update = Instant::now();
-
-            // Check for arb after update
-            if state.has_arb() {
-                updated_market = Some(state.clone());
-            }
-            break;
+            state.yes_bid = best_bid;
+            true
         } else if state.no_token == book.asset_id {
             state.no_price = best_ask.0;
             state.no_size = best_ask.1;
-            state.last_update = Instant::now();
+            state.no_bid = best_bid;
+            false
+        } else {
+            continue;
+        };
+        state.last_update = Instant::now();
+
+        if state.warmup.observe(is_yes, best_ask.1, min_depth) {
+            info!("[WS] {} books warm (Up {:.3} x {:.1}, Down {:.3} x {:.1}) - eligible for execution",
+                  state.asset.to_uppercase(), state.yes_price, state.yes_size, state.no_price, state.no_size);
+        }
 
-            // Check for arb after update
-            if state.has_arb() {
-                updated_market = Some(state.clone());
+        // Operator threshold for this market, else the asset's (the overrides file may change)
+        state.params.threshold = overrides::current().threshold(&state.slug)
+            .unwrap_or_else(|| asset_params::params(&state.asset).threshold);
+
+        // A new listing trades only once its book has cleared the quality bar
+        if let Some(gate) = &mut state.quality {
+            let config = market_quality::config();
+            if let Some(score) = gate.observe(config, best_ask.0 - best_bid, depth, now) {
+                market_quality::log_verdict(&state.slug, gate, &score, config);
+                if gate.enrolled() {
+                    market_quality::enrolled().enroll(&state.slug, chrono::Utc::now().timestamp());
+                }
             }
-            break;
         }
+
+        // Check for arb after update (must persist per ARB_CONFIRM_*)
+        let has_arb = state.warmup.is_warm() && state.quality.as_ref().is_none_or(QualityGate::enrolled) && state.has_arb();
+        if state.streak.observe(has_arb) {
+            updated_market = Some(state.clone());
+        }
+        break;
     }
 
-    drop(map); // Release lock before execution
+    drop(map); // Release lock before scheduling
 
-    // Execute if arb found
+    // Queue for ranking if arb found
     if let Some(state) = updated_market {
-        execute_arb(poly_client, position_channel, &state, dry_run).await?;
+        if state.halted {
+            debug!("[UPDOWN] Skipping {}: market halted", state.asset.to_uppercase());
+            return Ok(());
+        }
+        if !leader::is_leader() {
+            debug!("[UPDOWN] Skipping {}: standby (the leader trades)", state.slug);
+            return Ok(());
+        }
+        if overrides::current().is_blacklisted(&[&state.asset, &state.slug]) {
+            debug!("[UPDOWN] Skipping {}: blacklisted in overrides", state.slug);
+            return Ok(());
+        }
+        if let Some(reason) = aggregation::paused() {
+            debug!("[UPDOWN] Skipping {}: paused by aggregator ({})", state.slug, reason);
+            return Ok(());
+        }
+        if let Some(reason) = control::switches().halted() {
+            debug!("[UPDOWN] Skipping {}: {}", state.slug, reason);
+            return Ok(());
+        }
+        if control::switches().is_paused(&[&state.asset, &state.slug]) {
+            debug!("[UPDOWN] Skipping {}: paused through the control API", state.slug);
+            return Ok(());
+        }
+        if state.in_no_trade_window(chrono::Utc::now().timestamp() as u64) {
+            debug!("[UPDOWN] Skipping {}: inside no-trade window before expiry", state.asset.to_uppercase());
+            return Ok(());
+        }
+        if opp_tx.try_send(state).is_err() {
+            warn!("[SCHED] Opportunity queue full - dropping arb");
+        }
     }
 
     Ok(())
 }
 
+/// Collect opportunities that arrive within `window` of each other, rank them by
+/// edge × depth ÷ time-to-expiry and fund the best ones from the capital pool first.
+#[allow(clippy::too_many_arguments)]
+async fn run_scheduler(
+    mut rx: mpsc::Receiver<MarketState>,
+    window: Duration,
+    poly_client: Arc<SharedAsyncClient>,
+    position_channel: PositionChannel,
+    throttle: Arc<TradeThrottle>,
+    correlation: Arc<CorrelationGuard>,
+    intents: Arc<IntentJournal>,
+    capital: Arc<CapitalPool>,
+    exec_pool: Arc<ExecPool>,
+    markets: Arc<RwLock<HashMap<String, MarketState>>>,
+    shadow: Option<Arc<ShadowRecorder>>,
+    halts: Arc<Marks>,
+    mode: ExecMode,
+) {
+    let opp_cost = OppCostConfig::from_env();
+    let cost_config = CostModelConfig::from_env();
+    #[cfg(feature = "oracles")]
+    let pyth_config = PythConfig::from_env();
+    while let Some(first) = rx.recv().await {
+        // Latest state per market wins
+        let mut batch: HashMap<String, MarketState> = HashMap::new();
+        batch.insert(first.slug.clone(), first);
+        let deadline = Instant::now() + window;
+        while let Ok(Some(state)) = timeout_at(deadline, rx.recv()).await {
+            batch.insert(state.slug.clone(), state);
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let opportunities: Vec<Opportunity<MarketState>> = batch.into_values()
+            // Opportunity classes switched off for losing money (UNIT_DISABLED_BUCKETS)
+            .filter(|state| {
+                let opp = opportunity(state);
+                let Some(rule) = unit_economics::config().disabled_by(&opp) else {
+                    return true;
+                };
+                debug!("[UNITS] Skipping {}: bucket disabled by {}", state.asset.to_uppercase(), rule);
+                opportunities::record(opp.skipped("Bucket disabled"));
+                false
+            })
+            .map(|state| Opportunity {
+                edge: opp_cost.net_edge(1.0 - state.cost_per_contract(), state.cost_per_contract(),
+                                        state.end_timestamp.saturating_sub(now)),
+                depth: state.trade_size(),
+                cost_per_contract: state.cost_per_contract(),
+                min_contracts: state.params.min_size,
+                secs_to_expiry: state.end_timestamp.saturating_sub(now),
+                item: state,
+            })
+            .collect();
+        let candidates = opportunities.len();
+        // By market key: Up/Down questions repeat across intervals
+        let detected: Vec<(MarketKey, OpportunityRecord)> = opportunities.iter()
+            .map(|o| (o.item.key.clone(), opportunity(&o.item)))
+            .collect();
+
+        let allocations = scheduler::allocate(opportunities, capital.available(), 0.0);
+        for (_, opp) in detected.into_iter().filter(|(key, _)| !allocations.iter().any(|a| a.item.key == *key)) {
+            opportunities::record(opp.skipped("Unfunded"));
+        }
+        if candidates > 1 || allocations.len() < candidates {
+            let ranked: Vec<String> = allocations.iter()
+                .map(|a| format!("{} {:.1}@{:.5}", a.item.asset.to_uppercase(), a.contracts, a.score))
+                .collect();
+            info!("[SCHED] {} concurrent arbs, funded {}: [{}] (capital left: {})",
+                  candidates, allocations.len(), ranked.join(", "),
+                  capital.available().map(|c| format!("${:.2}", c)).unwrap_or_else(|| "unlimited".into()));
+        }
+
+        let size_factor = venue_health::health().size_factor(Venue::Polymarket);
+        let mut executions = Vec::with_capacity(allocations.len());
+        for alloc in allocations {
+            let state = alloc.item;
+            if halts.contains(&state.key.id()) {
+                debug!("[UPDOWN] Skipping {}: market halted", state.asset.to_uppercase());
+                opportunities::record(opportunity(&state).skipped("Halted"));
+                continue;
+            }
+            // A side's asks vanished since detection (one-sided book near expiry)
+            if !markets.read().await.get(&state.yes_token).is_some_and(|m| m.warmup.is_warm()) {
+                debug!("[UPDOWN] Skipping {}: one-sided book", state.asset.to_uppercase());
+                opportunities::record(opportunity(&state).skipped("One-sided book"));
+                continue;
+            }
+            let contracts = if size_factor < 1.0 { (alloc.contracts * size_factor).floor() } else { alloc.contracts };
+            if size_factor <= 0.0 {
+                debug!("[UPDOWN] Skipping {}: Polymarket unhealthy", state.asset.to_uppercase());
+                opportunities::record(opportunity(&state).skipped("Venue unhealthy"));
+                continue;
+            }
+            if contracts < state.params.min_size {
+                debug!("[UPDOWN] Skipping {}: {:.1} contracts below the {:.1} minimum",
+                       state.asset.to_uppercase(), contracts, state.params.min_size);
+                opportunities::record(opportunity(&state).skipped("Below minimum size"));
+                continue;
+            }
+            // Cost model: size for the fills and slippage this book has historically delivered
+            let secs_to_expiry = Some(state.end_timestamp as i64 - now as i64);
+            let contracts = match cost_model::model() {
+                Some(model) => match model.best_size(state.profit_cents(), contracts, state.params.min_size,
+                                                     state.yes_size, state.no_size, secs_to_expiry,
+                                                     cost_config.unwind_cents) {
+                    Some((size, _)) => size,
+                    None => {
+                        debug!("[UPDOWN] Skipping {}: no positive expected profit after fills/slippage",
+                               state.asset.to_uppercase());
+                        opportunities::record(opportunity(&state).skipped("Negative expected profit"));
+                        continue;
+                    }
+                },
+                None => contracts,
+            };
+            if !run_windows::is_open("updown") {
+                debug!("[UPDOWN] Skipping {}: outside run window", state.asset.to_uppercase());
+                opportunities::record(opportunity(&state).skipped("Outside run window"));
+                continue;
+            }
+            #[cfg(feature = "oracles")]
+            if let Some(mismatch) = pyth_config.sanity(pyth::prices(), &state.asset, state.open_timestamp, now,
+                                                       state.yes_price, state.no_price) {
+                warn!("[PYTH] Skipping {}: {}", state.asset.to_uppercase(), mismatch);
+                opportunities::record(opportunity(&state).skipped("Reference mismatch"));
+                continue;
+            }
+            if let Err(breach) = correlation.check(&state.asset, contracts) {
+                warn!("[RISK] Skipping {}: {}", state.asset.to_uppercase(), breach);
+                opportunities::record(opportunity(&state).skipped("Correlation limit"));
+                continue;
+            }
+            // Hold the expected cost until the orders complete, so overlapping executions can't overcommit
+            let expected_cost = contracts * state.cost_per_contract();
+            let Some(reservation) = capital.reserve(&state.key.id(), expected_cost) else {
+                warn!("[CAPITAL] Skipping {}: ${:.2} needed, ${:.2} available ({:.2} reserved in flight)",
+                      state.asset.to_uppercase(), expected_cost, capital.available().unwrap_or(0.0), capital.reserved());
+                opportunities::record(opportunity(&state).skipped("Capital reserved"));
+                continue;
+            };
+            if let Some(shadow) = &shadow {
+                spawn_shadow(shadow.clone(), markets.clone(), &state, contracts);
+            }
+            let (poly_client, position_channel, correlation, intents, capital, exec_pool, throttle, markets, halts) =
+                (&poly_client, &position_channel, &correlation, &intents, &capital, &exec_pool, &throttle, &markets, &halts);
+            executions.push(async move {
+                // Detection time is the last book update; stale arbs are dropped from the queue
+                let Some(_permit) = exec_pool.acquire(state.last_update).await else {
+                    warn!("[SCHED] ⏳ Dropped stale {} arb (execution pool busy)", state.asset.to_uppercase());
+                    capital.cancel(reservation);
+                    opportunities::record(opportunity(&state).skipped("Stale"));
+                    return;
+                };
+                // Order-rate budget: wait briefly for room, or refuse rather than crowd out market-data calls
+                if mode.is_live() {
+                    if let Err(breach) = order_rate::rates().acquire(&[Venue::Polymarket, Venue::Polymarket]).await {
+                        warn!("[RATE] ⏳ Refused {}: {}", state.asset.to_uppercase(), breach);
+                        capital.cancel(reservation);
+                        opportunities::record(opportunity(&state).skipped("Order rate limit"));
+                        return;
+                    }
+                }
+                // Taken last, so an arb dropped by any check above doesn't use up a throttle slot
+                if let Err(reason) = throttle.try_acquire(&state.key.id()) {
+                    debug!("[UPDOWN] Throttled {}: {}", state.asset.to_uppercase(), reason);
+                    capital.cancel(reservation);
+                    opportunities::record(opportunity(&state).skipped("Throttled"));
+                    return;
+                }
+                match execute_ladder(poly_client, position_channel, correlation, intents, markets, &state, contracts, mode).await {
+                    Ok(Executed { spent, profit, closed, .. }) => {
+                        let opp = opportunity(&state).filled(spent);
+                        opportunities::record(match profit {
+                            Some(profit) => opp.with_profit(profit),
+                            None => opp,
+                        });
+                        let credited = capital.settle(reservation, spent);
+                        debug!("[CAPITAL] {} spent ${:.2}, credited back ${:.2}", state.asset.to_uppercase(), spent, credited);
+                        if closed {
+                            throttle.record_failure(&state.key.id());
+                            halt_closed(markets, halts, &state).await;
+                        } else {
+                            throttle.record_success(&state.key.id());
+                        }
+                    }
+                    Err(e) => {
+                        capital.cancel(reservation);
+                        opportunities::record(opportunity(&state).skipped("Execution failed"));
+                        // Rejected (rate limit, paused market...): back off instead of retrying next tick
+                        throttle.record_failure(&state.key.id());
+                        match order_errors::rejection(&e).map(|r| r.reason) {
+                            Some(RejectReason::MarketClosed) => halt_closed(markets, halts, &state).await,
+                            Some(reason) => error!("   ❌ REJECTED on {} ({}): {}", state.asset.to_uppercase(), reason, e),
+                            None => error!("   ❌ FAILED on {}: {}", state.asset.to_uppercase(), e),
+                        }
+                    }
+                }
+            });
+        }
+        futures_util::future::join_all(executions).await;
+    }
+}
+
+/// Halt a market Polymarket refused as closed until the next interval
+async fn halt_closed(markets: &RwLock<HashMap<String, MarketState>>, halts: &Marks, state: &MarketState) {
+    warn!("   🚫 {} not accepting orders - halting it until the next interval", state.asset.to_uppercase());
+    if let Some(market) = markets.write().await.get_mut(&state.yes_token) {
+        market.halted = true;
+    }
+    // Remembered across restarts until the interval ends
+    let now = chrono::Utc::now().timestamp();
+    halts.retain(|_, end| end > now);
+    halts.insert(&state.key.id(), state.end_timestamp as i64);
+}
+
+/// A leg Polymarket refused as closed / not accepting orders
+fn rejected_closed<T>(result: &Result<T>) -> bool {
+    result.as_ref().err()
+        .and_then(order_errors::rejection)
+        .is_some_and(|r| r.reason == RejectReason::MarketClosed)
+}
+
+/// Cross-check an expired interval's resolution against the Pyth reference (PYTH=1)
+#[cfg(feature = "oracles")]
+fn log_reference_outcome(config: &PythConfig, market: &ActiveUpDownMarket, series: MarketSeries) {
+    if !config.enabled() {
+        return;
+    }
+    let open = market.end_timestamp.saturating_sub(series.interval_secs());
+    match pyth::prices().outcome(&market.asset, open, market.end_timestamp, config.max_age_secs) {
+        Some(r) if r.decisive => info!("[PYTH] {} resolves {} by reference ({:.4} → {:.4})",
+                                       market.asset.to_uppercase(), if r.up { "Up" } else { "Down" }, r.open, r.close),
+        Some(r) => warn!("[PYTH] {} too close to call by reference ({:.4} → {:.4}, within confidence) - check the resolution",
+                         market.asset.to_uppercase(), r.open, r.close),
+        None => {
+            metrics::incr("pyth.outcome_unknown");
+            warn!("[PYTH] {} no reference prices for {} → {}", market.asset.to_uppercase(), open, market.end_timestamp);
+        }
+    }
+}
+
+/// History entry for a detected arb (edge net of the market's taker fees), placed in its interval
+/// when it has one
+fn opportunity(state: &MarketState) -> OpportunityRecord {
+    let opp = OpportunityRecord::new(&state.asset, &state.question, "PolyOnly", state.yes_price, state.no_price,
+                                     state.yes_size, state.no_size, state.fee_per_contract());
+    if state.from_set {
+        return opp;
+    }
+    opp.timed(state.end_timestamp as i64 - chrono::Utc::now().timestamp(),
+              state.end_timestamp.saturating_sub(state.open_timestamp) as i64)
+}
+
+/// Record the two FAK orders live execution would have sent and, after the order
+/// latency, how much of them the re-read books could still have filled
+fn spawn_shadow(
+    shadow: Arc<ShadowRecorder>,
+    markets: Arc<RwLock<HashMap<String, MarketState>>>,
+    state: &MarketState,
+    contracts: f64,
+) {
+    let detected = state.clone();
+    tokio::spawn(async move {
+        sleep(shadow.latency()).await;
+        let Some(now) = markets.read().await.get(&detected.yes_token).cloned() else { return };
+        let legs = vec![
+            ShadowLeg {
+                venue: "polymarket",
+                side: "yes",
+                instrument: detected.yes_token.clone(),
+                price: detected.yes_price,
+                contracts,
+                fee: 0.0,
+                filled: shadow::leg_fill(detected.yes_price, contracts, now.yes_price, now.yes_size),
+            },
+            ShadowLeg {
+                venue: "polymarket",
+                side: "no",
+                instrument: detected.no_token.clone(),
+                price: detected.no_price,
+                contracts,
+                fee: 0.0,
+                filled: shadow::leg_fill(detected.no_price, contracts, now.no_price, now.no_size),
+            },
+        ];
+        shadow.record(&ShadowTrade::new(&detected.question, "PolyOnly", legs, shadow.unwind_cents()));
+    });
+}
+
+/// Send the compensating orders of an incomplete basket and record their fills, returning the
+/// capital they spent (negative when unwinding) and the contracts bought to complete pairs
+#[allow(clippy::too_many_arguments)]
+async fn compensate<F, Fut>(
+    poly_client: &Arc<SharedAsyncClient>,
+    position_channel: &PositionChannel,
+    correlation: &CorrelationGuard,
+    intents: &IntentJournal,
+    state: &MarketState,
+    basket: &Basket,
+    plan: &[basket::Repair],
+    buy: &F,
+    parent: Option<&str>,
+) -> (f64, f64)
+where
+    F: Fn(&str, &BasketLeg, f64, f64) -> Fut,
+    Fut: std::future::Future<Output = Result<PolyFillAsync>>,
+{
+    let group_id = intents::new_group_id(&state.slug);
+    let results = basket::compensate(basket, plan, |r| {
+        let (leg, price, contracts) = (&basket.legs[r.leg], r.price, r.contracts);
+        let buy = (r.side == RepairSide::Buy).then(|| buy(&group_id, leg, price, contracts));
+        async move {
+            match buy {
+                Some(buy) => buy.await,
+                None => poly_client.sell_fak(&leg.instrument, price, contracts).await,
+            }
+        }
+    }).await;
+    intents.complete_group(&group_id);
+
+    let (mut spent, mut bought) = (0.0, 0.0);
+    for (r, fill) in plan.iter().zip(results).filter_map(|(r, res)| res.ok().map(|f| (r, f))) {
+        if fill.filled_size <= 0.0 {
+            continue;
+        }
+        let side = basket.legs[r.leg].side;
+        let signed = if r.side == RepairSide::Buy { fill.filled_size } else { -fill.filled_size };
+        position_channel.record_fill(FillRecord::keyed(
+            &state.key, &state.question, "polymarket", side,
+            signed, fill.fill_cost / fill.filled_size, 0.0, &fill.order_id,
+        ).with_detail(&fill.detail).with_parent(parent));
+        let (yes, no) = if side == "yes" { (signed, 0.0) } else { (0.0, signed) };
+        correlation.record_fill(&state.key, &state.asset, yes, no);
+        if r.side == RepairSide::Buy {
+            spent += fill.fill_cost;
+            bought += fill.filled_size;
+        } else {
+            spent -= fill.fill_cost;
+        }
+    }
+    (spent, bought)
+}
+
+/// What an execution spent and made
+#[derive(Default)]
+struct Executed {
+    /// Capital committed (dollars)
+    spent: f64,
+    /// Net profit locked in: $1 per matched pair less every leg's cost and fees (None when
+    /// nothing was traded)
+    profit: Option<f64>,
+    /// (side, order id) of the legs sent
+    orders: Vec<(&'static str, String)>,
+    /// A leg was refused because the market closed: halt it for the rest of the interval
+    closed: bool,
+    /// A leg failed while the other filled (its fills are still in `spent`)
+    leg_failed: bool,
+}
+
+/// Execute an arb as one order per leg or, when its asset ladders this size, as a few smaller
+/// child orders a few ms apart. The book is re-checked before each child, so the ladder stops
+/// once the earlier children (or anyone else) took the edge away.
+#[allow(clippy::too_many_arguments)]
+async fn execute_ladder(
+    poly_client: &Arc<SharedAsyncClient>,
+    position_channel: &PositionChannel,
+    correlation: &CorrelationGuard,
+    intents: &IntentJournal,
+    markets: &RwLock<HashMap<String, MarketState>>,
+    state: &MarketState,
+    contracts: f64,
+    mode: ExecMode,
+) -> Result<Executed> {
+    let sizes = ladder::split(contracts, &state.params);
+    if sizes.len() < 2 {
+        return execute_arb(poly_client, position_channel, correlation, intents, state, contracts, mode, None).await;
+    }
+    let asset = state.asset.to_uppercase();
+    let parent = intents::new_group_id(&state.slug);
+    info!("[LADDER] {} {:.0} contracts as {} child orders ({})", asset, contracts, sizes.len(),
+          sizes.iter().map(|s| format!("{:.0}", s)).collect::<Vec<_>>().join(" / "));
+
+    let children = sizes.len();
+    let mut total = Executed::default();
+    for (index, planned) in sizes.into_iter().enumerate() {
+        let (child, size) = if index == 0 {
+            (state.clone(), planned)
+        } else {
+            sleep(ladder::delay(&state.params)).await;
+            let current = markets.read().await.get(&state.yes_token).cloned();
+            let next = current.as_ref().and_then(|m| {
+                ladder::next_child(planned, m.has_arb() && !m.halted, m.trade_size(), state.params.min_size)
+            });
+            let (Some(current), Some(size)) = (current, next) else {
+                info!("[LADDER] {} edge gone after {} of {} child orders - stopping", asset, index, children);
+                metrics::incr("ladder.stopped_early");
+                break;
+            };
+            if mode.is_live() {
+                if let Err(breach) = order_rate::rates().acquire(&[Venue::Polymarket, Venue::Polymarket]).await {
+                    warn!("[LADDER] ⏳ Stopping {} after {} of {} child orders: {}", asset, index, children, breach);
+                    break;
+                }
+            }
+            (current, size)
+        };
+
+        match execute_arb(poly_client, position_channel, correlation, intents, &child, size, mode, Some(&parent)).await {
+            Ok(executed) => {
+                for (side, order_id) in &executed.orders {
+                    order_manager::child_orders().record(&parent, ChildOrder {
+                        index,
+                        side,
+                        order_id: order_id.clone(),
+                        contracts: size,
+                    });
+                }
+                total.spent += executed.spent;
+                total.profit = match (total.profit, executed.profit) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+                total.orders.extend(executed.orders);
+                if executed.closed {
+                    total.closed = true;
+                    info!("[LADDER] {} market closed after {} of {} child orders - stopping", asset, index + 1, children);
+                    break;
+                }
+                // What the child's other leg filled is counted above - don't send more on top
+                if executed.leg_failed {
+                    total.leg_failed = true;
+                    warn!("[LADDER] {} child order {} of {} had a failed leg - stopping", asset, index + 1, children);
+                    break;
+                }
+            }
+            // Nothing went out yet: the arb failed as a whole
+            Err(e) if index == 0 => return Err(e),
+            Err(e) => {
+                warn!("[LADDER] {} child order {} of {} failed - stopping: {}", asset, index + 1, children, e);
+                break;
+            }
+        }
+    }
+    let sent = order_manager::child_orders().children(&parent);
+    info!("[LADDER] {} parent {}: {} child order(s) sent, ${:.2} spent", asset, parent, sent.len(), total.spent);
+    Ok(total)
+}
+
 /// Execute arbitrage trade
+#[allow(clippy::too_many_arguments)]
 async fn execute_arb(
     poly_client: &Arc<SharedAsyncClient>,
     position_channel: &PositionChannel,
+    correlation: &CorrelationGuard,
+    intents: &IntentJournal,
     state: &MarketState,
-    dry_run: bool,
-) -> Result<()> {
+    size: f64,
+    mode: ExecMode,
+    parent: Option<&str>,
+) -> Result<Executed> {
     let profit = state.profit_cents();
-    let size = state.trade_size();
 
     info!("");
     info!("🎯 ARBITRAGE FOUND: {}", state.asset.to_uppercase());
@@ -519,26 +1972,167 @@ async fn execute_arb(
           size,
           (size * profit) / 100.0);
 
-    if dry_run {
+    events::publish(BotEvent::ArbDetected {
+        market: state.question.clone(),
+        arb_type: "PolyOnly".into(),
+        yes_price: state.yes_price,
+        no_price: state.no_price,
+        contracts: size,
+    });
+
+    // Both legs as one basket: refused as a whole if a leg is malformed or the asks leave no edge
+    let basket = Basket::new(&state.question, vec![
+        BasketLeg { venue: Venue::Polymarket, side: "yes", instrument: state.yes_token.clone(), price: state.yes_price, contracts: size },
+        BasketLeg { venue: Venue::Polymarket, side: "no", instrument: state.no_token.clone(), price: state.no_price, contracts: size },
+    ], 1.0);
+    basket.precheck()?;
+
+    if mode == ExecMode::Simulated {
+        // Paper fill: both legs in full at the ask
+        info!("   📝 SIMULATED - paper fill {:.2} per leg", size);
+        let mut orders = Vec::with_capacity(2);
+        for (side, price) in [("yes", state.yes_price), ("no", state.no_price)] {
+            let order_id = exec_mode::simulated_order_id();
+            position_channel.record_fill(FillRecord::keyed(
+                &state.key, &state.question, "polymarket", side,
+                size, price, size * poly_fee(price, state.fee_rate_bps), &order_id,
+            ).with_parent(parent));
+            orders.push((side, order_id));
+        }
+        correlation.record_fill(&state.key, &state.asset, size, size);
+        return Ok(Executed {
+            spent: size * state.cost_per_contract(),
+            profit: Some(size * (1.0 - state.cost_per_contract())),
+            orders,
+            ..Executed::default()
+        });
+    }
+    if !mode.is_live() {
         info!("   ⚠️  DRY RUN - Skipping execution");
-        return Ok(());
+        return Ok(Executed::default());
     }
 
-    // Execute both legs in parallel
+    // Execute both legs (released per LEG_ORDER)
     info!("   ⚡ Executing...");
+    // Sign with the fee rate the scan found instead of looking it up on the order path,
+    // and price on the market's tick
+    for token in [&state.yes_token, &state.no_token] {
+        poly_client.set_fee_rate(token, state.fee_rate_bps);
+        poly_client.set_tick_size(token, state.tick_size);
+    }
+    for (side, price) in [("yes", state.yes_price), ("no", state.no_price)] {
+        events::publish(BotEvent::OrderSubmitted {
+            market: state.question.clone(),
+            platform: "polymarket".into(),
+            side: side.into(),
+            price,
+            contracts: size,
+        });
+    }
     let start = Instant::now();
 
-    let yes_fut = poly_client.buy_fak(&state.yes_token, state.yes_price, size);
-    let no_fut = poly_client.buy_fak(&state.no_token, state.no_price, size);
-
-    let (yes_result, no_result) = tokio::join!(yes_fut, no_fut);
+    // Each leg is journaled under its order hash before it is posted
+    let group_id = intents::new_group_id(&state.slug);
+    let buy = |group_id: &str, leg: &BasketLeg, price: f64, contracts: f64| {
+        let (group_id, token) = (group_id.to_string(), leg.instrument.clone());
+        let side = leg.side;
+        async move {
+            poly_client.buy_fak_with(&token, price, contracts, |order_hash| {
+                intents.record(OrderIntent::new(order_hash, &group_id, "polymarket", &state.key.id(), &state.question,
+                                                &token, side, price, contracts))
+            }).await
+        }
+    };
+    let results = basket::submit(&basket, &LegOrderConfig::from_env(), |i, contracts| {
+        let leg = &basket.legs[i];
+        buy(&group_id, leg, leg.price, contracts)
+    }, |fill: &PolyFillAsync| fill.filled_size).await;
+    intents.complete_group(&group_id);
+    let [yes_result, no_result]: [Result<PolyFillAsync>; 2] = results.try_into()
+        .map_err(|_| anyhow::anyhow!("basket returned the wrong number of legs"))?;
+    // A rejection is the venue answering - only transport/server errors count against its health
+    for result in [&yes_result, &no_result] {
+        let answered = result.as_ref().map_or_else(|e| order_errors::rejection(e).is_some(), |_| true);
+        venue_health::health().record_outcome(Venue::Polymarket, answered);
+    }
 
     let elapsed = start.elapsed();
+    let executed = match (yes_result, no_result) {
+        (yes_result, no_result) if [&yes_result, &no_result].iter().any(|r| rejected_closed(r)) => {
+            // The book froze mid-arb: a leg that still filled is held to settlement (never
+            // hedged into the closed market) and tagged to be checked against the resolution
+            let close_race = CloseRaceConfig::from_env();
+            close_race::record_outcome(CloseRace::Rejected);
+            let mut executed = Executed { closed: true, ..Executed::default() };
+            let (mut filled, mut fees) = ([0.0; 2], 0.0);
+            for (i, (side, result)) in [("yes", &yes_result), ("no", &no_result)].into_iter().enumerate() {
+                let fill = match result {
+                    Ok(fill) if fill.filled_size > 0.0 => fill,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        warn!("   🚫 {} leg refused: {}", side.to_uppercase(), e);
+                        continue;
+                    }
+                };
+                let price = fill.fill_cost / fill.filled_size;
+                let fee = fill.filled_size * poly_fee(price, state.fee_rate_bps);
+                position_channel.record_fill(FillRecord::keyed(
+                    &state.key, &state.question, "polymarket", side,
+                    fill.filled_size, price, fee, &fill.order_id,
+                ).with_detail(&fill.detail).with_parent(parent));
+                let (yes, no) = if side == "yes" { (fill.filled_size, 0.0) } else { (0.0, fill.filled_size) };
+                correlation.record_fill(&state.key, &state.asset, yes, no);
+                close_race::tag_fill(&close_race, &UnverifiedFill::new(
+                    &state.key.id(), "polymarket", side, fill.filled_size, price, &fill.order_id,
+                ));
+                events::publish(BotEvent::Filled {
+                    market: state.question.clone(),
+                    platform: "polymarket".into(),
+                    side: side.into(),
+                    contracts: fill.filled_size,
+                    cost: fill.fill_cost,
+                    order_id: fill.order_id.clone(),
+                });
+                filled[i] = fill.filled_size;
+                fees += fee;
+                executed.spent += fill.fill_cost;
+                executed.orders.push((side, fill.order_id.clone()));
+            }
+            // The leg that filled is spent and held: raised on its own, not as the rejection
+            let unhedged = (filled[0] - filled[1]).abs();
+            if unhedged > 0.0 {
+                warn!("   ⚠️  UNHEDGED: {:.2} {} contracts held to settlement in a closed market",
+                      unhedged, if filled[0] > filled[1] { "YES" } else { "NO" });
+                metrics::incr("close_race.unhedged");
+            }
+            if !executed.orders.is_empty() {
+                executed.profit = Some(filled[0].min(filled[1]) - executed.spent - fees);
+            }
+            return Ok(executed);
+        }
+        (yes_result, no_result) => {
+            // A failed leg is a zero fill: whatever the other leg got is recorded and compensated.
+            // A Sequenced leg held back after an empty first leg is just that, not a failure.
+            let mut failure: Option<anyhow::Error> = None;
+            let [yes_fill, no_fill] = [("yes", yes_result), ("no", no_result)].map(|(side, result)| {
+                result.unwrap_or_else(|e| {
+                    if !basket::not_sent(&e) {
+                        warn!("   ❌ {} leg failed: {}", side.to_uppercase(), e);
+                        failure.get_or_insert(e);
+                    }
+                    PolyFillAsync::default()
+                })
+            });
+            // Nothing filled and an order failed: the arb failed as a whole
+            if let Some(e) = failure.take_if(|_| yes_fill.filled_size <= 0.0 && no_fill.filled_size <= 0.0) {
+                return Err(e);
+            }
+            let leg_failed = failure.is_some();
 
-    match (yes_result, no_result) {
-        (Ok(yes_fill), Ok(no_fill)) => {
             let total_cost = yes_fill.fill_cost + no_fill.fill_cost;
-            let actual_profit = (yes_fill.filled_size.min(no_fill.filled_size)) - total_cost;
+            let fees = yes_fill.filled_size * poly_fee(state.yes_price, state.fee_rate_bps)
+                + no_fill.filled_size * poly_fee(state.no_price, state.fee_rate_bps);
+            let actual_profit = (yes_fill.filled_size.min(no_fill.filled_size)) - total_cost - fees;
 
             info!("   ✅ FILLED in {:.0}ms", elapsed.as_millis());
             info!("      YES: {:.2} @ {:.3} = ${:.2}",
@@ -548,43 +2142,77 @@ async fn execute_arb(
             info!("      Profit: ${:.2}", actual_profit);
 
             // Record fills to position tracker
-            let fill_yes = FillRecord::new(
-                &state.question,      // market_id (use question as unique ID)
+            let fill_yes = FillRecord::keyed(
+                &state.key,           // market key (slug, tokens, end)
                 &state.question,      // description
                 "polymarket",         // platform
                 "yes",                // side
                 yes_fill.filled_size, // contracts
                 state.yes_price,      // price
-                0.0,                  // fees (Polymarket has 0 maker fees!)
+                yes_fill.filled_size * poly_fee(state.yes_price, state.fee_rate_bps), // taker fees
                 &yes_fill.order_id,
-            );
+            ).with_detail(&yes_fill.detail).with_parent(parent);
 
-            let fill_no = FillRecord::new(
-                &state.question,
+            let fill_no = FillRecord::keyed(
+                &state.key,
                 &state.question,
                 "polymarket",
                 "no",
                 no_fill.filled_size,
                 state.no_price,
-                0.0,
+                no_fill.filled_size * poly_fee(state.no_price, state.fee_rate_bps),
                 &no_fill.order_id,
-            );
+            ).with_detail(&no_fill.detail).with_parent(parent);
 
-            position_channel.record_fill(fill_yes);
-            position_channel.record_fill(fill_no);
+            for fill in [fill_yes, fill_no] {
+                if fill.contracts > 0.0 {
+                    position_channel.record_fill(fill);
+                }
+            }
+
+            let secs_to_expiry = Some(state.end_timestamp as i64 - chrono::Utc::now().timestamp());
+            for (ask, depth, fill) in [(state.yes_price, state.yes_size, &yes_fill), (state.no_price, state.no_size, &no_fill)] {
+                cost_model::record(FillSample::new("polymarket", ask, depth, size, secs_to_expiry,
+                                                   fill.filled_size, fill.fill_cost));
+            }
+
+            for (side, fill) in [("yes", &yes_fill), ("no", &no_fill)] {
+                if fill.filled_size > 0.0 {
+                    events::publish(BotEvent::Filled {
+                        market: state.question.clone(),
+                        platform: "polymarket".into(),
+                        side: side.into(),
+                        contracts: fill.filled_size,
+                        cost: fill.fill_cost,
+                        order_id: fill.order_id.clone(),
+                    });
+                }
+            }
+            correlation.record_fill(&state.key, &state.asset, yes_fill.filled_size, no_fill.filled_size);
 
             // Check for unmatched exposure
+            let orders: Vec<_> = [("yes", &yes_fill), ("no", &no_fill)].into_iter()
+                .filter(|(_, fill)| !fill.order_id.is_empty())
+                .map(|(side, fill)| (side, fill.order_id.clone()))
+                .collect();
             let unmatched = (yes_fill.filled_size - no_fill.filled_size).abs();
             if unmatched > 0.5 {
                 warn!("   ⚠️  UNMATCHED: {:.2} contracts ({} side)",
                       unmatched,
                       if yes_fill.filled_size > no_fill.filled_size { "YES" } else { "NO" });
+                // Held by default: the exit manager decides what happens to the unmatched leg
+                let config = BasketConfig::from_env(Compensation::Hold);
+                let plan: Vec<_> = basket.plan(&[yes_fill.filled_size, no_fill.filled_size], &config).into_iter()
+                    .filter(|r| r.contracts >= 1.0)
+                    .collect();
+                let (comp_spent, bought) = compensate(poly_client, position_channel, correlation, intents, state, &basket, &plan, &buy, parent).await;
+                // Completing buys add pairs; unwinding sells return cash
+                Executed { spent: total_cost + comp_spent, profit: Some(actual_profit - comp_spent + bought), orders, closed: false, leg_failed }
+            } else {
+                Executed { spent: total_cost, profit: Some(actual_profit), orders, closed: false, leg_failed }
             }
         }
-        (Err(e), _) | (_, Err(e)) => {
-            error!("   ❌ FAILED: {}", e);
-        }
-    }
+    };
 
-    Ok(())
+    Ok(executed)
 }
@@ -0,0 +1,157 @@
+// src/book.rs
+// Full-depth local order book for a single Polymarket token
+
+use std::collections::BTreeMap;
+
+use crate::polymarket_ws_types::{BookSnapshot, PriceLevel};
+use crate::units;
+
+/// Price key in units of 0.001 (Polymarket's finest tick size)
+type PriceKey = u32;
+
+#[inline]
+fn price_key(price: &str) -> Option<PriceKey> {
+    let p: f64 = price.parse().ok()?;
+    if !(0.0..=1.0).contains(&p) {
+        return None;
+    }
+    Some((p * 1000.0).round() as PriceKey)
+}
+
+#[inline]
+fn key_to_price(key: PriceKey) -> f64 {
+    key as f64 / 1000.0
+}
+
+/// Side of a book level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+impl BookSide {
+    /// Parse the side field of a `price_change` item ("BUY"/"SELL" or "BID"/"ASK")
+    pub fn parse(side: &str) -> Option<Self> {
+        match side.to_ascii_uppercase().as_str() {
+            "BUY" | "BID" => Some(BookSide::Bid),
+            "SELL" | "ASK" => Some(BookSide::Ask),
+            _ => None,
+        }
+    }
+}
+
+/// Local order book rebuilt from WS snapshots and incremental level updates
+#[derive(Debug, Default, Clone)]
+pub struct LocalBook {
+    bids: BTreeMap<PriceKey, f64>,
+    asks: BTreeMap<PriceKey, f64>,
+    /// Number of snapshots + level updates applied
+    pub updates: u64,
+}
+
+impl LocalBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the whole book with a snapshot
+    pub fn apply_snapshot(&mut self, snapshot: &BookSnapshot) {
+        self.bids = Self::levels(&snapshot.bids);
+        self.asks = Self::levels(&snapshot.asks);
+        self.updates += 1;
+    }
+
+    fn levels(levels: &[PriceLevel]) -> BTreeMap<PriceKey, f64> {
+        levels.iter()
+            .filter_map(|l| {
+                let key = price_key(&l.price)?;
+                let size = units::parse_amount(&l.size)?;
+                if size > 0.0 { Some((key, size)) } else { None }
+            })
+            .collect()
+    }
+
+    /// Set the size at one price level (size 0 removes the level)
+    pub fn apply_level(&mut self, side: BookSide, price: &str, size: &str) {
+        let (Some(key), Some(size)) = (price_key(price), units::parse_amount(size)) else {
+            return;
+        };
+        let book = match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        };
+        if size > 0.0 {
+            book.insert(key, size);
+        } else {
+            book.remove(&key);
+        }
+        self.updates += 1;
+    }
+
+    /// Best (highest) bid as (price, size)
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids.iter().next_back().map(|(k, s)| (key_to_price(*k), *s))
+    }
+
+    /// Best (lowest) ask as (price, size)
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks.iter().next().map(|(k, s)| (key_to_price(*k), *s))
+    }
+
+    /// Top `n` bids, best first
+    pub fn top_bids(&self, n: usize) -> Vec<(f64, f64)> {
+        self.bids.iter().rev().take(n).map(|(k, s)| (key_to_price(*k), *s)).collect()
+    }
+
+    /// Top `n` asks, best first
+    pub fn top_asks(&self, n: usize) -> Vec<(f64, f64)> {
+        self.asks.iter().take(n).map(|(k, s)| (key_to_price(*k), *s)).collect()
+    }
+
+    /// Every ask as (price, size), best first
+    pub fn asks(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.asks.iter().map(|(k, s)| (key_to_price(*k), *s))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: &str, size: &str) -> PriceLevel {
+        PriceLevel { price: price.to_string(), size: size.to_string() }
+    }
+
+    #[test]
+    fn test_snapshot_best_levels() {
+        let mut book = LocalBook::new();
+        book.apply_snapshot(&BookSnapshot {
+            asset_id: "t".to_string(),
+            bids: vec![level("0.40", "10"), level("0.42", "5")],
+            asks: vec![level("0.47", "3"), level("0.45", "8"), level("0.50", "0")],
+            ..Default::default()
+        });
+
+        assert_eq!(book.best_bid(), Some((0.42, 5.0)));
+        assert_eq!(book.best_ask(), Some((0.45, 8.0)));
+        assert_eq!(book.top_asks(5).len(), 2, "zero-size levels are dropped");
+    }
+
+    #[test]
+    fn test_level_updates() {
+        let mut book = LocalBook::new();
+        book.apply_level(BookSide::Ask, "0.55", "20");
+        book.apply_level(BookSide::Ask, "0.53", "4");
+        assert_eq!(book.best_ask(), Some((0.53, 4.0)));
+
+        book.apply_level(BookSide::Ask, "0.53", "0");
+        assert_eq!(book.best_ask(), Some((0.55, 20.0)));
+        assert_eq!(BookSide::parse("sell"), Some(BookSide::Ask));
+        assert_eq!(BookSide::parse("BUY"), Some(BookSide::Bid));
+    }
+}
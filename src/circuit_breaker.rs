@@ -1,11 +1,21 @@
 // src/circuit_breaker.rs
 // Safety circuit breakers - halt trading on various conditions
 
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{error, warn, info};
 
+use crate::events::{self, BotEvent};
+use crate::snapshots::{from_unix_ms, to_unix_ms};
+use crate::storage;
+
+/// Minimum gap between risk state writes caused by profitable executions alone
+const SUCCESS_PERSIST_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Circuit breaker configuration from environment
 #[derive(Debug, Clone)]
 pub struct CircuitBreakerConfig {
@@ -64,7 +74,7 @@ impl CircuitBreakerConfig {
 }
 
 /// Reason why circuit breaker was tripped
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TripReason {
     MaxPositionPerMarket { market: String, position: i64, limit: i64 },
     MaxTotalPosition { position: i64, limit: i64 },
@@ -95,6 +105,45 @@ impl std::fmt::Display for TripReason {
     }
 }
 
+/// Risk state file (RISK_STATE_FILE under DATA_DIR)
+pub fn risk_state_path() -> PathBuf {
+    storage::state_path(std::env::var("RISK_STATE_FILE").unwrap_or_else(|_| "risk_state.json".to_string()))
+}
+
+fn today_string() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// Daily counters and breaker state as written to the risk state file on every change
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskState {
+    /// UTC date the counters belong to
+    pub trading_date: String,
+    pub daily_pnl_cents: i64,
+    pub daily_trades: i64,
+    pub consecutive_errors: i64,
+    pub trip_reason: Option<TripReason>,
+    /// Wall-clock ms of the trip, so a cooldown keeps counting while the bot is stopped
+    pub tripped_at_ms: Option<i64>,
+}
+
+impl RiskState {
+    /// The state to resume on `today`: a file from an earlier day restarts the counters
+    /// and lifts its trips, except a manual halt, which only an operator reset lifts
+    pub fn for_day(self, today: &str) -> Self {
+        if self.trading_date == today {
+            return self;
+        }
+        let manual = self.trip_reason == Some(TripReason::ManualHalt);
+        Self {
+            trading_date: today.to_string(),
+            trip_reason: manual.then_some(TripReason::ManualHalt),
+            tripped_at_ms: self.tripped_at_ms.filter(|_| manual),
+            ..Self::default()
+        }
+    }
+}
+
 /// Position tracking for a single market
 #[derive(Debug, Default)]
 pub struct MarketPosition {
@@ -134,6 +183,18 @@ pub struct CircuitBreaker {
     
     /// Daily P&L tracking (in cents)
     daily_pnl_cents: AtomicI64,
+
+    /// Executions recorded today
+    daily_trades: AtomicI64,
+
+    /// UTC date of the daily counters
+    trading_date: std::sync::Mutex<String>,
+
+    /// Where the daily counters and trips are persisted (None = in memory only)
+    state_file: Option<PathBuf>,
+
+    /// When the risk state was last written
+    persisted_at: std::sync::Mutex<Option<Instant>>,
     
     /// Positions per market
     positions: RwLock<std::collections::HashMap<String, MarketPosition>>,
@@ -156,9 +217,107 @@ impl CircuitBreaker {
             trip_reason: RwLock::new(None),
             consecutive_errors: AtomicI64::new(0),
             daily_pnl_cents: AtomicI64::new(0),
+            daily_trades: AtomicI64::new(0),
+            trading_date: std::sync::Mutex::new(today_string()),
+            state_file: None,
+            persisted_at: std::sync::Mutex::new(None),
             positions: RwLock::new(std::collections::HashMap::new()),
         }
     }
+
+    /// A breaker whose daily counters and trips survive restarts: today's state is restored
+    /// from `path` and rewritten on every change (profitable executions at most every
+    /// `SUCCESS_PERSIST_INTERVAL`), so restarting doesn't reset a daily loss limit or lift a halt. An unreadable file is an error rather than a fresh day.
+    pub fn persistent(config: CircuitBreakerConfig, path: PathBuf) -> Result<Self> {
+        let mut cb = Self::new(config);
+        let stored = match std::fs::read_to_string(&path) {
+            Ok(contents) => Some(serde_json::from_str::<RiskState>(&contents)
+                .with_context(|| format!("risk state {} is corrupt (delete it only if today's limits may restart)", path.display()))?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        if let Some(stored) = stored {
+            let state = stored.for_day(&today_string());
+            cb.apply(&state, Instant::now(), chrono::Utc::now().timestamp_millis());
+            info!("[CB] Restored risk state for {}: P&L ${:.2}, {} trades, {} consecutive errors{}",
+                  state.trading_date, state.daily_pnl_cents as f64 / 100.0, state.daily_trades, state.consecutive_errors,
+                  state.trip_reason.as_ref().map(|r| format!(", HALTED ({})", r)).unwrap_or_default());
+        }
+        cb.state_file = Some(path);
+        Ok(cb)
+    }
+
+    fn apply(&mut self, state: &RiskState, now: Instant, now_ms: i64) {
+        self.daily_pnl_cents = AtomicI64::new(state.daily_pnl_cents);
+        self.daily_trades = AtomicI64::new(state.daily_trades);
+        self.consecutive_errors = AtomicI64::new(state.consecutive_errors);
+        self.trading_date = std::sync::Mutex::new(state.trading_date.clone());
+        if let Some(reason) = &state.trip_reason {
+            self.halted = AtomicBool::new(true);
+            // A trip from before this clock's origin restarts its cooldown
+            let tripped_at = state.tripped_at_ms.and_then(|ms| from_unix_ms(ms, now, now_ms)).unwrap_or(now);
+            self.tripped_at = RwLock::new(Some(tripped_at));
+            self.trip_reason = RwLock::new(Some(reason.clone()));
+        }
+    }
+
+    /// Current daily counters and trip
+    pub async fn risk_state(&self) -> RiskState {
+        let (now, now_ms) = (Instant::now(), chrono::Utc::now().timestamp_millis());
+        let trading_date = self.trading_date.lock().unwrap().clone();
+        RiskState {
+            trading_date,
+            daily_pnl_cents: self.daily_pnl_cents.load(Ordering::SeqCst),
+            daily_trades: self.daily_trades.load(Ordering::SeqCst),
+            consecutive_errors: self.consecutive_errors.load(Ordering::SeqCst),
+            trip_reason: self.trip_reason.read().await.clone(),
+            tripped_at_ms: self.tripped_at.read().await.map(|t| to_unix_ms(t, now, now_ms)),
+        }
+    }
+
+    async fn persist(&self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        *self.persisted_at.lock().unwrap() = Some(Instant::now());
+        let state = self.risk_state().await;
+        let written = serde_json::to_vec_pretty(&state)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| storage::write_atomic(path, &contents));
+        if let Err(e) = written {
+            warn!("[CB] ⚠️ Failed to persist risk state to {}: {:#}", path.display(), e);
+        }
+    }
+
+    /// Persist unless the state was written within `SUCCESS_PERSIST_INTERVAL`. Only for
+    /// changes that may be lost on a crash without loosening a limit: a restart that misses
+    /// them restores a lower P&L, never a higher one.
+    async fn persist_debounced(&self) {
+        let recent = self.persisted_at.lock().unwrap()
+            .is_some_and(|at| at.elapsed() < SUCCESS_PERSIST_INTERVAL);
+        if !recent {
+            self.persist().await;
+        }
+    }
+
+    /// Start a new trading day's counters once the UTC date changes (lifting a daily-loss trip)
+    async fn roll_day(&self) {
+        let today = today_string();
+        {
+            let mut date = self.trading_date.lock().unwrap();
+            if *date == today {
+                return;
+            }
+            info!("[CB] New trading day {} (was {})", today, date);
+            *date = today;
+        }
+        self.daily_pnl_cents.store(0, Ordering::SeqCst);
+        self.daily_trades.store(0, Ordering::SeqCst);
+        if matches!(*self.trip_reason.read().await, Some(TripReason::MaxDailyLoss { .. })) {
+            self.reset().await;
+        }
+        self.persist().await;
+    }
     
     /// Check if trading is allowed
     #[allow(dead_code)]
@@ -174,6 +333,7 @@ impl CircuitBreaker {
         if !self.config.enabled {
             return Ok(());
         }
+        self.roll_day().await;
         
         if self.halted.load(Ordering::SeqCst) {
             let reason = self.trip_reason.read().await;
@@ -219,17 +379,26 @@ impl CircuitBreaker {
     /// Record a successful execution
     pub async fn record_success(&self, market_id: &str, kalshi_contracts: i64, poly_contracts: i64, pnl: f64) {
         // Reset consecutive errors
-        self.consecutive_errors.store(0, Ordering::SeqCst);
+        let errors = self.consecutive_errors.swap(0, Ordering::SeqCst);
         
         // Update P&L
         let pnl_cents = (pnl * 100.0) as i64;
         self.daily_pnl_cents.fetch_add(pnl_cents, Ordering::SeqCst);
+        self.daily_trades.fetch_add(1, Ordering::SeqCst);
         
         // Update positions
-        let mut positions = self.positions.write().await;
-        let pos = positions.entry(market_id.to_string()).or_default();
-        pos.kalshi_yes += kalshi_contracts;
-        pos.poly_no += poly_contracts;
+        {
+            let mut positions = self.positions.write().await;
+            let pos = positions.entry(market_id.to_string()).or_default();
+            pos.kalshi_yes += kalshi_contracts;
+            pos.poly_no += poly_contracts;
+        }
+        // Losses and cleared errors are written at once; a profit alone can wait
+        if errors != 0 || pnl_cents < 0 {
+            self.persist().await;
+        } else {
+            self.persist_debounced().await;
+        }
     }
     
     /// Record an error
@@ -241,14 +410,17 @@ impl CircuitBreaker {
                 count: errors as u32,
                 limit: self.config.max_consecutive_errors,
             }).await;
+        } else {
+            self.persist().await;
         }
     }
     
     /// Record P&L update (for tracking without execution)
     #[allow(dead_code)]
-    pub fn record_pnl(&self, pnl: f64) {
+    pub async fn record_pnl(&self, pnl: f64) {
         let pnl_cents = (pnl * 100.0) as i64;
         self.daily_pnl_cents.fetch_add(pnl_cents, Ordering::SeqCst);
+        self.persist().await;
     }
 
     /// Trip the circuit breaker
@@ -258,34 +430,37 @@ impl CircuitBreaker {
         }
         
         error!("🚨 CIRCUIT BREAKER TRIPPED: {}", reason);
+        events::publish(BotEvent::RiskTripped { reason: reason.to_string() });
         
         self.halted.store(true, Ordering::SeqCst);
         *self.tripped_at.write().await = Some(Instant::now());
         *self.trip_reason.write().await = Some(reason);
+        self.persist().await;
     }
     
     /// Manually halt trading
-    #[allow(dead_code)]
     pub async fn halt(&self) {
         warn!("[CB] Manual halt triggered");
         self.trip(TripReason::ManualHalt).await;
     }
 
     /// Reset the circuit breaker (after cooldown or manual reset)
-    #[allow(dead_code)]
     pub async fn reset(&self) {
         info!("[CB] Circuit breaker reset");
         self.halted.store(false, Ordering::SeqCst);
         *self.tripped_at.write().await = None;
         *self.trip_reason.write().await = None;
         self.consecutive_errors.store(0, Ordering::SeqCst);
+        self.persist().await;
     }
 
-    /// Reset daily P&L (call at midnight)
+    /// Reset daily P&L (the date rollover in `can_execute` does this at UTC midnight)
     #[allow(dead_code)]
-    pub fn reset_daily_pnl(&self) {
+    pub async fn reset_daily_pnl(&self) {
         info!("[CB] Daily P&L reset");
         self.daily_pnl_cents.store(0, Ordering::SeqCst);
+        self.daily_trades.store(0, Ordering::SeqCst);
+        self.persist().await;
     }
 
     /// Check if cooldown has elapsed and auto-reset if so
@@ -319,6 +494,7 @@ impl CircuitBreaker {
             trip_reason: self.trip_reason.read().await.clone(),
             consecutive_errors: self.consecutive_errors.load(Ordering::SeqCst) as u32,
             daily_pnl: self.daily_pnl_cents.load(Ordering::SeqCst) as f64 / 100.0,
+            daily_trades: self.daily_trades.load(Ordering::SeqCst),
             total_position,
             market_count: positions.len(),
         }
@@ -333,6 +509,7 @@ pub struct CircuitBreakerStatus {
     pub trip_reason: Option<TripReason>,
     pub consecutive_errors: u32,
     pub daily_pnl: f64,
+    pub daily_trades: i64,
     pub total_position: i64,
     pub market_count: usize,
 }
@@ -352,8 +529,8 @@ impl std::fmt::Display for CircuitBreakerStatus {
             write!(f, "Circuit Breaker: ✅ OK")?;
         }
         
-        write!(f, " | P&L: ${:.2} | Pos: {} contracts across {} markets | Errors: {}",
-               self.daily_pnl, self.total_position, self.market_count, self.consecutive_errors)
+        write!(f, " | P&L: ${:.2} ({} trades) | Pos: {} contracts across {} markets | Errors: {}",
+               self.daily_pnl, self.daily_trades, self.total_position, self.market_count, self.consecutive_errors)
     }
 }
 
@@ -407,4 +584,83 @@ mod tests {
         cb.record_error().await;
         assert!(!cb.is_trading_allowed());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_daily_loss_and_trip_survive_restart() {
+        let config = CircuitBreakerConfig {
+            max_position_per_market: 100,
+            max_total_position: 500,
+            max_daily_loss: 10.0,
+            max_consecutive_errors: 3,
+            cooldown_secs: 60,
+            enabled: true,
+        };
+        let path = std::env::temp_dir().join(format!("risk_state_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let cb = CircuitBreaker::persistent(config.clone(), path.clone()).unwrap();
+        cb.record_success("market1", 5, 5, -12.0).await;
+        assert!(matches!(cb.can_execute("market1", 1).await, Err(TripReason::MaxDailyLoss { .. })));
+        for _ in 0..3 {
+            cb.record_error().await;
+        }
+        drop(cb);
+
+        // The restarted breaker is still over the limit and halted
+        let cb = CircuitBreaker::persistent(config.clone(), path.clone()).unwrap();
+        let state = cb.risk_state().await;
+        assert_eq!((state.daily_pnl_cents, state.daily_trades), (-1200, 1));
+        assert!(!cb.is_trading_allowed());
+        assert!(matches!(state.trip_reason, Some(TripReason::ConsecutiveErrors { count: 3, .. })));
+
+        // A corrupt file refuses to start rather than forgetting the day
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(CircuitBreaker::persistent(config, path.clone()).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_profitable_executions_persist_debounced() {
+        let config = CircuitBreakerConfig {
+            max_position_per_market: 100,
+            max_total_position: 500,
+            max_daily_loss: 10.0,
+            max_consecutive_errors: 3,
+            cooldown_secs: 60,
+            enabled: true,
+        };
+        let path = std::env::temp_dir().join(format!("risk_state_debounce_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let stored = || serde_json::from_str::<RiskState>(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        let cb = CircuitBreaker::persistent(config, path.clone()).unwrap();
+        cb.record_success("market1", 1, 1, 0.5).await;
+        cb.record_success("market1", 1, 1, 0.5).await;
+        // The second profit falls inside the interval and isn't written yet
+        assert_eq!((stored().daily_pnl_cents, stored().daily_trades), (50, 1));
+
+        // A loss is written straight away, carrying the pending profit with it
+        cb.record_success("market1", 1, 1, -0.25).await;
+        assert_eq!((stored().daily_pnl_cents, stored().daily_trades), (75, 3));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_new_day_restarts_counters_but_keeps_manual_halt() {
+        let state = RiskState {
+            trading_date: "2025-12-01".into(),
+            daily_pnl_cents: -5000,
+            daily_trades: 40,
+            consecutive_errors: 2,
+            trip_reason: Some(TripReason::MaxDailyLoss { loss: 50.0, limit: 40.0 }),
+            tripped_at_ms: Some(1_764_600_000_000),
+        };
+        assert_eq!(state.clone().for_day("2025-12-01"), state);
+        let next = state.clone().for_day("2025-12-02");
+        assert_eq!(next, RiskState { trading_date: "2025-12-02".into(), ..RiskState::default() });
+
+        let manual = RiskState { trip_reason: Some(TripReason::ManualHalt), ..state }.for_day("2025-12-02");
+        assert_eq!(manual.trip_reason, Some(TripReason::ManualHalt));
+        assert_eq!(manual.daily_pnl_cents, 0);
+    }
+}
@@ -0,0 +1,248 @@
+// src/clock_skew.rs
+// Exchange clock offset - signed CLOB requests carry exchange time, and skew past a safe bound is alerted
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::events::{self, BotEvent};
+use crate::metrics;
+
+/// Clock-sync requests made at startup (spread across a second to narrow the offset)
+const CLOCK_SYNC_SAMPLES: u64 = 5;
+
+/// Local Unix time in milliseconds
+pub fn local_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Local → exchange clock offset in milliseconds.
+///
+/// Servers only report whole seconds, so each sample bounds the offset rather than
+/// fixing it: a reply stamped `s` seen between local `sent` and `recv` means exchange
+/// time was in [s, s+1000) ms somewhere in that window. Intersecting the bounds of
+/// samples taken at different sub-second phases narrows the estimate to well under a
+/// second. A sample that contradicts the bounds (local clock stepped) restarts them.
+#[derive(Debug, Default)]
+pub struct ExchangeClock {
+    /// (lowest, highest) offset consistent with every sample so far
+    bounds: Mutex<Option<(i64, i64)>>,
+}
+
+impl ExchangeClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a server timestamp (whole seconds) received between local `sent_ms` and `recv_ms`
+    pub fn observe(&self, server_secs: u64, sent_ms: u64, recv_ms: u64) {
+        let server_ms = server_secs as i64 * 1000;
+        let lo = server_ms - recv_ms as i64;
+        let hi = server_ms + 999 - sent_ms as i64;
+        let mut bounds = self.bounds.lock().unwrap();
+        *bounds = match *bounds {
+            Some((cur_lo, cur_hi)) if lo.max(cur_lo) <= hi.min(cur_hi) => Some((lo.max(cur_lo), hi.min(cur_hi))),
+            _ => Some((lo, hi)),
+        };
+    }
+
+    /// Best offset estimate (0 until a sample arrives)
+    pub fn offset_ms(&self) -> i64 {
+        self.bounds.lock().unwrap().map(|(lo, hi)| (lo + hi) / 2).unwrap_or(0)
+    }
+
+    /// Width of the offset bounds - how far off `now_ms` can be (None = unsynced)
+    pub fn uncertainty_ms(&self) -> Option<i64> {
+        self.bounds.lock().unwrap().map(|(lo, hi)| hi - lo)
+    }
+
+    /// Exchange time in Unix milliseconds
+    pub fn now_ms(&self) -> u64 {
+        (local_ms() as i64 + self.offset_ms()).max(0) as u64
+    }
+
+    /// Exchange time in Unix seconds
+    pub fn now_secs(&self) -> u64 {
+        self.now_ms() / 1000
+    }
+
+    /// Time until exchange time reaches `target_ms` (zero if already past)
+    pub fn until(&self, target_ms: u64) -> Duration {
+        Duration::from_millis(target_ms.saturating_sub(self.now_ms()))
+    }
+}
+
+/// Sample `{host}/time` a few times at shifted sub-second phases, passing each
+/// (server secs, sent ms, received ms) to `observe`
+pub async fn sync(http: &reqwest::Client, host: &str, observe: impl Fn(u64, u64, u64)) -> Result<()> {
+    let url = format!("{}/time", host);
+    for i in 0..CLOCK_SYNC_SAMPLES {
+        if i > 0 {
+            // Shift the sub-second phase of each sample so the bounds narrow
+            tokio::time::sleep(Duration::from_millis(1000 / CLOCK_SYNC_SAMPLES + 37)).await;
+        }
+        let sent = local_ms();
+        let body = http.get(&url).send().await?.error_for_status()?.text().await?;
+        let recv = local_ms();
+        let server_secs: u64 = body.trim().trim_matches('"').parse()
+            .map_err(|e| anyhow::anyhow!("unexpected /time response '{}': {}", body.trim(), e))?;
+        observe(server_secs, sent, recv);
+    }
+    Ok(())
+}
+
+/// Unix seconds of an HTTP `Date` header
+pub fn http_date_secs(date: &str) -> Option<u64> {
+    chrono::DateTime::parse_from_rfc2822(date).ok()
+        .and_then(|d| u64::try_from(d.timestamp()).ok())
+}
+
+/// Signing clock configuration from environment
+#[derive(Debug, Clone)]
+pub struct SkewConfig {
+    /// Stamp signed requests with exchange time instead of local time (CLOB_SKEW_ADJUST)
+    pub adjust: bool,
+
+    /// Alert when the local clock is further than this off the exchange's (ms)
+    pub max_skew_ms: i64,
+}
+
+impl SkewConfig {
+    pub fn from_env() -> Self {
+        Self {
+            adjust: crate::app::env_flag("CLOB_SKEW_ADJUST", true),
+
+            max_skew_ms: std::env::var("CLOB_MAX_SKEW_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5000),
+        }
+    }
+}
+
+/// Timestamps of the CLOB's signed requests, corrected by the skew measured from its
+/// responses (`/time` at startup, then the `Date` header of every signed request)
+pub struct SigningClock {
+    config: SkewConfig,
+    clock: ExchangeClock,
+    /// Over the bound and already alerted (re-armed once back under)
+    alerted: AtomicBool,
+}
+
+impl SigningClock {
+    pub fn new(config: SkewConfig) -> Self {
+        Self { config, clock: ExchangeClock::new(), alerted: AtomicBool::new(false) }
+    }
+
+    /// Unix seconds to sign a request with
+    pub fn timestamp(&self) -> u64 {
+        if self.config.adjust {
+            self.clock.now_secs()
+        } else {
+            local_ms() / 1000
+        }
+    }
+
+    /// Measured exchange-minus-local offset (None until a response has been seen)
+    pub fn skew_ms(&self) -> Option<i64> {
+        self.clock.uncertainty_ms().map(|_| self.clock.offset_ms())
+    }
+
+    /// Record the `Date` header of a response sent at `sent_ms` and received at `recv_ms`
+    pub fn observe_date(&self, date: Option<&str>, sent_ms: u64, recv_ms: u64) {
+        if let Some(secs) = date.and_then(http_date_secs) {
+            self.observe(secs, sent_ms, recv_ms);
+        }
+    }
+
+    /// Record a server timestamp and alert when the skew crosses the bound
+    pub fn observe(&self, server_secs: u64, sent_ms: u64, recv_ms: u64) {
+        self.clock.observe(server_secs, sent_ms, recv_ms);
+        let skew = self.clock.offset_ms();
+        metrics::set_gauge("clob.clock_skew_ms", skew);
+        let over = skew.abs() > self.config.max_skew_ms;
+        if over && !self.alerted.swap(true, Ordering::Relaxed) {
+            warn!("[CLOCK] ⏰ Local clock is {:+}ms off the CLOB's (bound ±{}ms){}",
+                  skew, self.config.max_skew_ms,
+                  if self.config.adjust { " - signing with exchange time" } else { " - fix NTP or set CLOB_SKEW_ADJUST=1" });
+            metrics::incr("clob.clock_skew_alerts");
+            events::publish(BotEvent::ClockSkew {
+                venue: "polymarket".into(),
+                skew_ms: skew,
+                limit_ms: self.config.max_skew_ms,
+            });
+        } else if !over && self.alerted.swap(false, Ordering::Relaxed) {
+            info!("[CLOCK] Local clock back within ±{}ms of the CLOB's ({:+}ms)", self.config.max_skew_ms, skew);
+        }
+    }
+
+    /// Explanation to attach to a 401 when clock skew is the likelier cause than the credentials
+    pub fn auth_hint(&self) -> Option<String> {
+        let skew = self.skew_ms()?;
+        (skew.abs() > self.config.max_skew_ms).then(|| format!(
+            "local clock is {:+}ms off the exchange's{}", skew,
+            if self.config.adjust { "" } else { " and CLOB_SKEW_ADJUST is off" },
+        ))
+    }
+}
+
+/// Process-wide signing clock (CLOB_SKEW_ADJUST / CLOB_MAX_SKEW_MS)
+pub fn signing_clock() -> &'static SigningClock {
+    static CLOCK: OnceLock<SigningClock> = OnceLock::new();
+    CLOCK.get_or_init(|| SigningClock::new(SkewConfig::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing(adjust: bool) -> SigningClock {
+        SigningClock::new(SkewConfig { adjust, max_skew_ms: 5000 })
+    }
+
+    #[test]
+    fn test_http_date() {
+        assert_eq!(http_date_secs("Thu, 18 Dec 2025 23:30:00 GMT"), Some(1766100600));
+        assert_eq!(http_date_secs("yesterday"), None);
+    }
+
+    #[test]
+    fn test_signing_timestamp_follows_exchange() {
+        let clock = signing(true);
+        assert_eq!(clock.skew_ms(), None);
+        assert!(clock.auth_hint().is_none());
+
+        // Exchange 30s ahead of local time
+        let local = local_ms();
+        clock.observe((local + 30_000) / 1000, local, local + 10);
+        let skew = clock.skew_ms().unwrap();
+        assert!((skew - 30_000).abs() <= 1000, "{}", skew);
+        assert!(clock.timestamp().abs_diff(local_ms() / 1000 + 30) <= 2);
+        assert!(clock.auth_hint().unwrap().contains("off the exchange's"));
+
+        // Adjustment off: local time is signed, the skew still measured
+        let raw = signing(false);
+        raw.observe((local + 30_000) / 1000, local, local + 10);
+        assert!(raw.timestamp().abs_diff(local_ms() / 1000) <= 1);
+        assert!(raw.auth_hint().unwrap().contains("CLOB_SKEW_ADJUST is off"));
+    }
+
+    #[test]
+    fn test_alert_rearms_once_back_in_bound() {
+        let clock = signing(true);
+        let local = local_ms();
+        clock.observe((local + 10_000) / 1000, local, local + 10);
+        assert!(clock.alerted.load(Ordering::Relaxed));
+
+        // NTP stepped the local clock: the contradicting sample restarts the bounds
+        clock.observe(local / 1000, local, local + 10);
+        assert!(clock.skew_ms().unwrap().abs() <= 1000);
+        assert!(!clock.alerted.load(Ordering::Relaxed));
+        assert!(clock.auth_hint().is_none());
+    }
+}
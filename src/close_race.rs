@@ -0,0 +1,177 @@
+// src/close_race.rs
+// Market-closure races - orders sent in a market's last seconds, tagged for settlement-time checks
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::metrics;
+use crate::storage;
+
+/// Default file (under DATA_DIR) for fills that need checking at settlement
+pub const DEFAULT_CLOSE_RACE_FILE: &str = "close_race_fills.jsonl";
+
+/// Close-race configuration from environment
+#[derive(Debug, Clone)]
+pub struct CloseRaceConfig {
+    /// Seconds before a market's close in which its orders count as racing the close (0 = off)
+    pub window_secs: u64,
+
+    pub file: PathBuf,
+}
+
+impl CloseRaceConfig {
+    pub fn from_env() -> Self {
+        Self {
+            window_secs: std::env::var("CLOSE_RACE_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            file: storage::state_path(std::env::var("CLOSE_RACE_FILE")
+                .unwrap_or_else(|_| DEFAULT_CLOSE_RACE_FILE.to_string())),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.window_secs > 0
+    }
+
+    /// `now` is within the window before `closes_at` (or past it)
+    pub fn closing(&self, closes_at: Option<i64>, now: i64) -> bool {
+        self.enabled() && closes_at.is_some_and(|t| now >= t - self.window_secs as i64)
+    }
+}
+
+/// How an order lost (or half-won) the race against a market's close
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseRace {
+    /// Refused as closed / not accepting orders
+    Rejected,
+    /// Filled while the market was closing: the book may already have been frozen,
+    /// so the fill is only trusted once settlement confirms it
+    LateFill,
+}
+
+impl CloseRace {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rejected => "rejected",
+            Self::LateFill => "late_fill",
+        }
+    }
+}
+
+/// Classify one leg. `closing` is true when the market was within its close window or
+/// any leg of the same order group was refused as closed.
+pub fn classify(filled: bool, rejected_closed: bool, closing: bool) -> Option<CloseRace> {
+    if rejected_closed {
+        Some(CloseRace::Rejected)
+    } else if filled && closing {
+        Some(CloseRace::LateFill)
+    } else {
+        None
+    }
+}
+
+/// A fill tagged for settlement-time verification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnverifiedFill {
+    pub ts: String,
+    pub market: String,
+    pub platform: String,
+    pub side: String,
+    pub contracts: f64,
+    pub price: f64,
+    pub order_id: String,
+}
+
+impl UnverifiedFill {
+    pub fn new(market: &str, platform: &str, side: &str, contracts: f64, price: f64, order_id: &str) -> Self {
+        Self {
+            ts: chrono::Utc::now().to_rfc3339(),
+            market: market.to_string(),
+            platform: platform.to_string(),
+            side: side.to_string(),
+            contracts,
+            price,
+            order_id: order_id.to_string(),
+        }
+    }
+}
+
+/// Count a leg's race outcome (`close_race.rejected` / `close_race.late_fill`)
+pub fn record_outcome(race: CloseRace) {
+    metrics::incr(&format!("close_race.{}", race.as_str()));
+}
+
+/// Append a late fill to the close-race file
+pub fn tag_fill(config: &CloseRaceConfig, fill: &UnverifiedFill) {
+    warn!("[CLOSE] 🏁 {} {} {} x{} filled while closing (order {}) - verify at settlement",
+          fill.market, fill.platform, fill.side, fill.contracts, fill.order_id);
+    record_outcome(CloseRace::LateFill);
+    if let Err(e) = append(&config.file, fill) {
+        warn!("[CLOSE] Cannot write {}: {}", config.file.display(), e);
+    }
+}
+
+fn append(path: &Path, fill: &UnverifiedFill) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(fill)?)?;
+    Ok(())
+}
+
+/// Tagged fills still to be checked (oldest first)
+pub fn load(path: &Path) -> Vec<UnverifiedFill> {
+    std::fs::read_to_string(path)
+        .map(|s| s.lines().filter_map(|l| serde_json::from_str(l).ok()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(window_secs: u64) -> CloseRaceConfig {
+        CloseRaceConfig { window_secs, file: PathBuf::new() }
+    }
+
+    #[test]
+    fn test_closing_window() {
+        let c = cfg(5);
+        assert!(!c.closing(Some(1_000), 994));
+        assert!(c.closing(Some(1_000), 995));
+        assert!(c.closing(Some(1_000), 1_200));
+        assert!(!c.closing(None, 1_200));
+        assert!(!cfg(0).closing(Some(1_000), 1_000));
+    }
+
+    #[test]
+    fn test_classify_legs() {
+        // One leg refused as closed, the other filled: the fill is a late fill
+        assert_eq!(classify(false, true, true), Some(CloseRace::Rejected));
+        assert_eq!(classify(true, false, true), Some(CloseRace::LateFill));
+        // Normal fills and plain misses aren't races
+        assert_eq!(classify(true, false, false), None);
+        assert_eq!(classify(false, false, true), None);
+    }
+
+    #[test]
+    fn test_tagged_fills_round_trip() {
+        let dir = std::env::temp_dir().join(format!("close_race_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = CloseRaceConfig { window_secs: 5, file: dir.join("fills.jsonl") };
+
+        tag_fill(&config, &UnverifiedFill::new("BTC Up or Down", "polymarket", "yes", 12.0, 0.48, "0xabc"));
+        tag_fill(&config, &UnverifiedFill::new("BTC Up or Down", "kalshi", "no", 3.0, 0.50, "k-1"));
+        let fills = load(&config.file);
+        assert_eq!(fills.len(), 2);
+        assert_eq!((fills[1].platform.as_str(), fills[1].order_id.as_str()), ("kalshi", "k-1"));
+        assert!(load(&dir.join("missing.jsonl")).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
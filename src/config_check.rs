@@ -0,0 +1,513 @@
+// src/config_check.rs
+// Config validation - bot.toml sections, env value ranges, exclusive options and per-strategy credentials
+
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+
+use crate::basket::Compensation;
+use crate::events::BotEvent;
+use crate::exec_mode;
+use crate::asset_params::AssetTable;
+use crate::leader::LeaseKind;
+use crate::leg_order::LegPolicy;
+use crate::logging::{self, LoggingConfig};
+use crate::notify::NotifyConfig;
+use crate::market_sets::MarketSets;
+use crate::position_store::StoreBackend;
+use crate::run_windows::RunWindows;
+use crate::synthetic::SyntheticSpec;
+use crate::unit_economics::BucketRule;
+
+/// Every strategy a run window or `validate` may name
+pub const STRATEGIES: &[&str] = &["cross", "poly_only", "kalshi_only", "updown"];
+
+/// Top-level bot.toml sections; anything else is probably a typo
+const SECTIONS: &[&str] = &["logging", "schedule", "notify", "assets", "market_sets"];
+
+/// Numeric env vars: (name, min, max, integer)
+const BOUNDS: &[(&str, f64, f64, bool)] = &[
+    ("CB_MAX_POSITION_PER_MARKET", 0.0, f64::MAX, true),
+    ("CB_MAX_TOTAL_POSITION", 0.0, f64::MAX, true),
+    ("CB_MAX_DAILY_LOSS", 0.0, f64::MAX, false),
+    ("CB_MAX_CONSECUTIVE_ERRORS", 1.0, f64::MAX, true),
+    ("CB_COOLDOWN_SECS", 0.0, f64::MAX, true),
+    ("OVERRIDES_POLL_SECS", 0.0, 3600.0, true),
+    ("THROTTLE_MAX_PER_MINUTE", 0.0, f64::MAX, true),
+    ("THROTTLE_MAX_PER_MARKET_PER_MINUTE", 0.0, f64::MAX, true),
+    ("THROTTLE_MIN_INTERVAL_SECS", 0.0, f64::MAX, true),
+    ("EXEC_MAX_CONCURRENT", 1.0, f64::MAX, true),
+    ("EXEC_QUEUE_EXPIRY_MS", 0.0, f64::MAX, true),
+    ("HOT_WORKER_THREADS", 0.0, 1024.0, true),
+    ("BOOKKEEPING_THREADS", 0.0, 1024.0, true),
+    ("CLOB_MAX_SKEW_MS", 0.0, f64::MAX, true),
+    ("REPLAY_POLL_SECS", 0.0, 86400.0, true),
+    ("SYNTHETIC_POLL_SECS", 1.0, 3600.0, true),
+    ("SETTLE_POLL_SECS", 0.0, 86400.0, true),
+    ("SYNTHETIC_MIN_EDGE_CENTS", 0.0, 99.0, true),
+    ("REPLAY_MAX_ATTEMPTS", 1.0, f64::MAX, true),
+    ("REPLAY_MAX_AGE_SECS", 0.0, f64::MAX, true),
+    ("QUALITY_WINDOW_SECS", 0.0, f64::MAX, true),
+    ("QUALITY_MIN_SCORE", 0.0, 1.0, false),
+    ("QUALITY_MAX_SPREAD", 0.001, 1.0, false),
+    ("QUALITY_TARGET_DEPTH", 0.001, f64::MAX, false),
+    ("QUALITY_TARGET_UPDATES_PER_MIN", 0.001, f64::MAX, false),
+    ("EXEC_CAPITAL", 0.0, f64::MAX, false),
+    ("ARB_CONFIRM_UPDATES", 0.0, f64::MAX, true),
+    ("ARB_CONFIRM_MS", 0.0, f64::MAX, true),
+    ("TRANCHE_MAX", 1.0, f64::MAX, true),
+    ("FILL_RETRY_ATTEMPTS", 0.0, 10.0, true),
+    ("FILL_RETRY_WINDOW_MS", 0.0, 60_000.0, true),
+    ("GAMMA_PAGE_SIZE", 1.0, 1000.0, true),
+    ("GAMMA_PAGE_CONCURRENCY", 1.0, 32.0, true),
+    ("GAMMA_MAX_REQUESTS", 1.0, 1000.0, true),
+    ("GAMMA_MAX_BODY_MB", 1.0, 512.0, true),
+    ("COST_MODEL_REFIT_HOURS", 0.0, 720.0, true),
+    ("COST_MODEL_MIN_SAMPLES", 1.0, 1_000_000.0, true),
+    ("COST_MODEL_UNWIND_CENTS", 0.0, 100.0, false),
+    ("CLOSE_RACE_WINDOW_SECS", 0.0, 3600.0, true),
+    ("IOC_OFFSET_TICKS", 0.0, 98.0, true),
+    ("IOC_MIN_EDGE_CENTS", -99.0, 99.0, true),
+    ("OPP_COST_APR", 0.0, 1.0, false),
+    ("OPP_COST_MIN_EDGE_CENTS", 0.0, 99.0, false),
+    ("OPP_RATE_CHECK_MINS", 0.0, 1440.0, true),
+    ("OPP_RATE_BASELINE_DAYS", 0.0, 365.0, true),
+    ("OPP_RATE_BAND_SIGMAS", 0.0, 10.0, false),
+    ("OPP_RATE_FEED_DROP", 0.0, 1.0, false),
+    ("BASIS_GAP_SECS", 0.0, f64::MAX, false),
+    ("BASIS_ORACLE_SCORE", 0.0, f64::MAX, false),
+    ("BASIS_DISCOUNT_CENTS", 0.0, 99.0, false),
+    ("BASIS_MAX_SCORE", 0.0, f64::MAX, false),
+    ("VENUE_DEGRADED_SIZE_FACTOR", 0.0, 1.0, false),
+    ("VENUE_DEGRADED_ERROR_RATE", 0.0, 1.0, false),
+    ("VENUE_DOWN_ERROR_RATE", 0.0, 1.0, false),
+    ("VENUE_ERROR_MAX_AGE_SECS", 1.0, f64::MAX, true),
+    ("WS_STORM_RECONNECTS", 0.0, f64::MAX, true),
+    ("WS_STORM_WINDOW_SECS", 1.0, f64::MAX, true),
+    ("WS_STORM_COOLDOWN_SECS", 0.0, f64::MAX, true),
+    ("WS_TRANSPORT_TIMEOUT_SECS", 1.0, f64::MAX, true),
+    ("WS_DATA_IDLE_SECS", 0.0, f64::MAX, true),
+    ("WS_TOKEN_IDLE_SECS", 0.0, f64::MAX, true),
+    ("WS_IDLE_REFRESHES", 0.0, 1000.0, true),
+    ("CONTROL_MAX_CONNECTIONS", 1.0, 1000.0, true),
+    ("DEPTH_MIN_PERSIST_MS", 0.0, f64::MAX, true),
+    ("LEADER_LEASE_SECS", 1.0, f64::MAX, true),
+    ("LEADER_RENEW_SECS", 1.0, f64::MAX, true),
+    ("AGG_MAX_EXPOSURE_USD", 0.0, f64::MAX, false),
+    ("AGG_MAX_BOT_EXPOSURE_USD", 0.0, f64::MAX, false),
+    ("AGG_RESUME_FRACTION", 0.0, 1.0, false),
+    ("AGG_STALE_SECS", 1.0, f64::MAX, true),
+    ("AGG_POLL_SECS", 1.0, f64::MAX, true),
+    ("AGGREGATOR_PUSH_SECS", 1.0, f64::MAX, true),
+    ("SCHEMA_MIN_PARSE_RATE", 0.0, 1.0, false),
+    ("SCHEMA_MIN_FIELD_RATE", 0.0, 1.0, false),
+    ("REBALANCE_THRESHOLD", 0.0, 1.0, false),
+    ("REBALANCE_POLY_SHARE", 0.0, 1.0, false),
+    ("REBALANCE_MIN_USD", 0.0, f64::MAX, false),
+    ("REBALANCE_MAX_USD", 0.0, f64::MAX, false),
+    ("TREASURY_CONFIRM_ABOVE_USD", 0.0, f64::MAX, false),
+    ("TREASURY_CONFIRM_TTL_SECS", 1.0, f64::MAX, true),
+    ("RPC_TIMEOUT_MS", 100.0, 120_000.0, true),
+    ("RPC_FAIL_LIMIT", 1.0, 100.0, true),
+    ("RPC_COOLDOWN_SECS", 0.0, 86_400.0, true),
+    ("RPC_MAX_LAG_BLOCKS", 1.0, f64::MAX, true),
+    ("RPC_VERIFY_QUORUM", 1.0, 16.0, true),
+    ("RPC_HEALTH_SECS", 0.0, 86_400.0, true),
+    ("BALANCE_POLL_SECS", 0.0, f64::MAX, true),
+    ("BALANCE_STALE_SECS", 0.0, f64::MAX, true),
+    ("KALSHI_MARGIN_BUFFER_CENTS", 0.0, f64::MAX, true),
+    ("WATCHDOG_SECS", 0.0, f64::MAX, true),
+    ("WATCHDOG_GROWTH", 0.0, f64::MAX, false),
+    ("ARCHIVE_INTERVAL_SECS", 0.0, f64::MAX, true),
+    ("CTF_RECONCILE_SECS", 0.0, f64::MAX, true),
+    ("CTF_RECONCILE_TOLERANCE", 0.0, f64::MAX, false),
+    ("LEG_ORDER_MIN_SAMPLES", 1.0, f64::MAX, true),
+    ("LEG_ORDER_MAX_STAGGER_MS", 0.0, 5_000.0, true),
+    ("BASKET_UNWIND_CENTS", 0.0, 99.0, false),
+    ("STATE_SNAPSHOT_SECS", 0.0, f64::MAX, true),
+    ("STATE_SNAPSHOT_MAX_AGE_SECS", 0.0, f64::MAX, true),
+    ("BASKET_COMPLETE_SLACK_CENTS", 0.0, 99.0, false),
+    ("KALSHI_ORDER_LIMIT_PER_MIN", 0.0, f64::MAX, true),
+    ("POLY_ORDER_LIMIT_PER_MIN", 0.0, f64::MAX, true),
+    ("ORDER_RATE_HEADROOM", 0.0, 1.0, false),
+    ("ORDER_RATE_MAX_DEFER_MS", 0.0, 60_000.0, true),
+    ("TELEMETRY_CHECK_HOURS", 0.0, 168.0, true),
+    ("PYTH_MAX_AGE_SECS", 1.0, 3_600.0, true),
+    ("PYTH_SANITY_BPS", 0.0, 10_000.0, false),
+    ("SETTLE_PREDICT_WINDOW_SECS", 0.0, 3_600.0, true),
+    ("SETTLE_PREDICT_VOL_MINS", 1.0, 60.0, true),
+    ("SETTLE_PREDICT_DEFAULT_VOL_BPS", 0.0, 1_000.0, false),
+    ("SETTLE_PREDICT_MARGIN", 0.0, 1.0, false),
+];
+
+/// One thing wrong with the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Env var or bot.toml section it concerns
+    pub key: String,
+    pub message: String,
+}
+
+struct Checker<'a> {
+    env: &'a dyn Fn(&str) -> Option<String>,
+    problems: Vec<Problem>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, key: &str, message: impl Into<String>) {
+        self.problems.push(Problem { key: key.to_string(), message: message.into() });
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        (self.env)(name).filter(|v| !v.trim().is_empty())
+    }
+
+    /// Settings read with `.parse().ok().unwrap_or(default)` silently fall back on a typo
+    fn bounds(&mut self) {
+        for &(name, min, max, integer) in BOUNDS {
+            let Some(raw) = self.var(name) else { continue };
+            match raw.trim().parse::<f64>() {
+                Ok(v) if integer && v.fract() != 0.0 => self.report(name, format!("{:?} must be a whole number", raw)),
+                Ok(v) if v < min || v > max => {
+                    let range = if max == f64::MAX { format!(">= {}", min) } else { format!("{} to {}", min, max) };
+                    self.report(name, format!("{} out of range (expected {})", v, range));
+                }
+                Ok(_) => {}
+                Err(_) => self.report(name, format!("{:?} is not a number", raw)),
+            }
+        }
+        let num = |c: &Self, name: &str| c.var(name).and_then(|v| v.trim().parse::<f64>().ok());
+        if let (Some(min), Some(max)) = (num(self, "REBALANCE_MIN_USD"), num(self, "REBALANCE_MAX_USD")) {
+            if min > max {
+                self.report("REBALANCE_MIN_USD", format!("{} is above REBALANCE_MAX_USD {}", min, max));
+            }
+        }
+    }
+
+    fn exclusive(&mut self) {
+        let flag = |c: &Self, name: &str| c.var(name).map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true"));
+        let mode = exec_mode::resolve(self.var("EXEC_MODE").as_deref(), self.var("DRY_RUN").as_deref(), self.var("SHADOW_MODE").as_deref());
+        match mode {
+            Ok(mode) if mode.is_live() && flag(self, "TEST_ARB") == Some(true) => {
+                self.report("TEST_ARB", "injects fake arbs and can't be combined with live execution");
+            }
+            Ok(_) => {}
+            Err(e) => self.report("EXEC_MODE", e.to_string()),
+        }
+        if let (Some(path), Some(file)) = (self.var("KALSHI_PRIVATE_KEY_PATH"), self.var("KALSHI_PRIVATE_KEY_FILE")) {
+            if path != file {
+                self.report("KALSHI_PRIVATE_KEY_FILE", "set together with KALSHI_PRIVATE_KEY_PATH to a different file; keep one");
+            }
+        }
+        if let Some(mode) = self.var("POLY_TX_MODE") {
+            if !matches!(mode.trim().to_lowercase().as_str(), "relayer" | "direct") {
+                self.report("POLY_TX_MODE", format!("{:?}: expected relayer or direct", mode));
+            }
+        }
+        let allowlist = self.var("TREASURY_ALLOWLIST").unwrap_or_default();
+        let allowed: Vec<String> = allowlist.split(',').map(|a| a.trim().to_lowercase()).filter(|a| !a.is_empty()).collect();
+        for a in allowed.iter().filter(|a| !is_hex(a, 40)) {
+            self.report("TREASURY_ALLOWLIST", format!("{:?} is not a 0x address (40 hex digits)", a));
+        }
+        if let Some(deposit) = self.var("REBALANCE_KALSHI_DEPOSIT_ADDRESS") {
+            if !allowed.contains(&deposit.trim().to_lowercase()) {
+                self.report("REBALANCE_KALSHI_DEPOSIT_ADDRESS", "not in TREASURY_ALLOWLIST - automatic transfers to it would be refused");
+            }
+        }
+        for url in self.var("POLYGON_RPC_URLS").unwrap_or_default().split(',').map(str::trim).filter(|u| !u.is_empty()) {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                self.report("POLYGON_RPC_URLS", format!("{:?} is not an http(s) URL", url));
+            }
+        }
+        if let Some(policy) = self.var("LEG_ORDER") {
+            if LegPolicy::parse(&policy).is_none() {
+                self.report("LEG_ORDER", format!("{:?}: expected simultaneous, staggered or sequenced", policy));
+            }
+        }
+        match self.var("POSITION_STORE").map(|b| (StoreBackend::parse(&b), b)) {
+            Some((None, raw)) => self.report("POSITION_STORE", format!("{:?}: expected json, sqlite or postgres", raw)),
+            Some((Some(StoreBackend::Postgres), _)) if self.var("POSITION_STORE_URL").is_none() => {
+                self.report("POSITION_STORE_URL", "POSITION_STORE=postgres needs a connection URL");
+            }
+            _ => {}
+        }
+        match self.var("LEADER_LEASE").map(|k| (LeaseKind::parse(&k), k)) {
+            Some((None, raw)) => self.report("LEADER_LEASE", format!("{:?}: expected file, sqlite or postgres", raw)),
+            Some((Some(LeaseKind::Postgres), _)) if self.var("LEADER_LEASE_URL").is_none() => {
+                self.report("LEADER_LEASE_URL", "LEADER_LEASE=postgres needs a connection URL");
+            }
+            _ => {}
+        }
+        let secs = |name, default| self.var(name).and_then(|v| v.parse::<u64>().ok()).unwrap_or(default);
+        let (renew, lease) = (secs("LEADER_RENEW_SECS", 2), secs("LEADER_LEASE_SECS", 10));
+        if self.var("LEADER_LEASE").is_some() && renew >= lease {
+            self.report("LEADER_RENEW_SECS", format!("{}s renewals can't keep a {}s lease", renew, lease));
+        }
+        if let Some(compensation) = self.var("BASKET_COMPENSATION") {
+            if Compensation::parse(&compensation).is_none() {
+                self.report("BASKET_COMPENSATION", format!("{:?}: expected unwind, complete or hold", compensation));
+            }
+        }
+        if let Some(pairs) = self.var("SYNTHETIC_PAIRS") {
+            for pair in pairs.split(',').filter(|p| !p.trim().is_empty() && SyntheticSpec::parse(p).is_none()) {
+                self.report("SYNTHETIC_PAIRS", format!("{:?}: expected EVENT:slug:strike", pair.trim()));
+            }
+        }
+        if let Some(rules) = self.var("UNIT_DISABLED_BUCKETS") {
+            for rule in rules.split(',').filter(|r| !r.trim().is_empty() && BucketRule::parse(r).is_none()) {
+                self.report("UNIT_DISABLED_BUCKETS", format!("{:?}: expected asset:edge:phase (phase open, mid, close or untimed)", rule.trim()));
+            }
+        }
+    }
+
+    fn credentials(&mut self, strategies: &[&str]) {
+        let uses = |names: &[&str]| strategies.iter().any(|s| names.contains(s));
+        let needed_by = |names: &[&str]| {
+            strategies.iter().filter(|s| names.contains(s)).copied().collect::<Vec<_>>().join(", ")
+        };
+
+        if uses(&["cross", "kalshi_only"]) {
+            let by = needed_by(&["cross", "kalshi_only"]);
+            if self.var("KALSHI_API_KEY_ID").is_none() {
+                self.report("KALSHI_API_KEY_ID", format!("required by {}", by));
+            }
+            let (key, path) = match (self.var("KALSHI_PRIVATE_KEY_PATH"), self.var("KALSHI_PRIVATE_KEY_FILE")) {
+                (Some(p), _) => ("KALSHI_PRIVATE_KEY_PATH", p),
+                (None, Some(p)) => ("KALSHI_PRIVATE_KEY_FILE", p),
+                (None, None) => ("KALSHI_PRIVATE_KEY_PATH", "kalshi_private_key.txt".to_string()),
+            };
+            if !std::path::Path::new(&path).is_file() {
+                self.report(key, format!("private key {} not found (required by {})", path, by));
+            }
+        }
+
+        if uses(&["cross", "poly_only", "updown"]) {
+            let by = needed_by(&["cross", "poly_only", "updown"]);
+            match self.var("POLY_PRIVATE_KEY") {
+                None => self.report("POLY_PRIVATE_KEY", format!("required by {}", by)),
+                Some(k) if !is_hex(&k, 64) => self.report("POLY_PRIVATE_KEY", "expected 64 hex digits (0x prefix optional)"),
+                Some(_) => {}
+            }
+            match self.var("POLY_FUNDER") {
+                None => self.report("POLY_FUNDER", format!("required by {} (your wallet address)", by)),
+                Some(a) if !is_hex(&a, 40) => self.report("POLY_FUNDER", "expected a 0x address (40 hex digits)"),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn bot_toml(&mut self, contents: &str) {
+        let table = match toml::from_str::<toml::Table>(contents) {
+            Ok(t) => t,
+            Err(e) => {
+                self.report("bot.toml", e.to_string().trim().to_string());
+                return;
+            }
+        };
+        for section in table.keys().filter(|k| !SECTIONS.contains(&k.as_str())) {
+            self.report(&format!("[{}]", section), format!("unknown section (expected one of {})", SECTIONS.join(", ")));
+        }
+
+        match LoggingConfig::parse(contents) {
+            Ok(config) => {
+                if let Some(file) = &config.file {
+                    if file.max_files == 0 {
+                        self.report("[logging.file]", "max_files must be at least 1");
+                    }
+                }
+            }
+            Err(e) => self.report("[logging]", format!("{:#}", e)),
+        }
+
+        match RunWindows::parse(contents) {
+            Ok(windows) => {
+                for w in &windows.windows {
+                    for s in w.strategies.iter().filter(|s| !STRATEGIES.contains(&s.as_str())) {
+                        self.report("[schedule]", format!("unknown strategy {:?} (expected {})", s, STRATEGIES.join(", ")));
+                    }
+                }
+            }
+            Err(e) => self.report("[schedule]", format!("{:#}", e)),
+        }
+
+        match NotifyConfig::parse(contents) {
+            Ok(config) => self.notify(&config),
+            Err(e) => self.report("[notify]", format!("{:#}", e)),
+        }
+
+        if let Err(e) = AssetTable::parse(contents) {
+            self.report("[assets]", format!("{:#}", e));
+        }
+
+        if let Err(e) = MarketSets::parse(contents) {
+            self.report("[market_sets]", format!("{:#}", e));
+        }
+    }
+
+    fn notify(&mut self, config: &NotifyConfig) {
+        let mut names = HashSet::new();
+        for c in &config.channels {
+            let key = format!("[notify.channel] {}", c.name);
+            if !names.insert(c.name.as_str()) {
+                self.report(&key, "duplicate channel name");
+            }
+            match (&c.url, &c.url_env) {
+                (Some(_), Some(_)) => self.report(&key, "url and url_env are mutually exclusive"),
+                (None, None) => self.report(&key, "needs url or url_env"),
+                (None, Some(var)) if self.var(var).is_none() => self.report(&key, format!("{} is not set", var)),
+                _ => {}
+            }
+            for kind in c.events.iter().filter(|k| !BotEvent::KINDS.contains(&k.as_str())) {
+                self.report(&key, format!("unknown event kind {:?}", kind));
+            }
+        }
+        for kind in config.severity.keys().chain(config.templates.keys()) {
+            if !BotEvent::KINDS.contains(&kind.as_str()) {
+                self.report("[notify]", format!("unknown event kind {:?}", kind));
+            }
+        }
+    }
+}
+
+/// `digits` hex digits, 0x prefix optional
+fn is_hex(s: &str, digits: usize) -> bool {
+    let h = s.trim().trim_start_matches("0x");
+    h.len() == digits && h.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Every problem with `bot_toml` (None = no file) and the environment for running `strategies`
+pub fn check(bot_toml: Option<&str>, env: &dyn Fn(&str) -> Option<String>, strategies: &[&str]) -> Vec<Problem> {
+    let mut checker = Checker { env, problems: Vec::new() };
+    if let Some(contents) = bot_toml {
+        checker.bot_toml(contents);
+    }
+    checker.bounds();
+    checker.exclusive();
+    checker.credentials(strategies);
+    checker.problems
+}
+
+/// Check BOT_CONFIG and the process environment; all problems are listed in one error
+pub fn validate(strategies: &[&str]) -> Result<()> {
+    let path = logging::config_path();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(c) => Some(c),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(anyhow!("reading {}: {}", path, e)),
+    };
+    let problems = check(contents.as_deref(), &|name| std::env::var(name).ok(), strategies);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let lines: Vec<String> = problems.iter().map(|p| format!("  - {}: {}", p.key, p.message)).collect();
+    Err(anyhow!("{} configuration problem(s) ({}, environment):\n{}", problems.len(), path, lines.join("\n")))
+}
+
+/// `--check-config` was passed: validate, then exit without starting
+pub fn check_only() -> bool {
+    std::env::args().any(|a| a == "--check-config")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| map.get(name).cloned()
+    }
+
+    fn keys(problems: &[Problem]) -> Vec<&str> {
+        problems.iter().map(|p| p.key.as_str()).collect()
+    }
+
+    #[test]
+    fn test_env_ranges_and_exclusive_options() {
+        let e = env(&[
+            ("IOC_OFFSET_TICKS", "2.5"),
+            ("VENUE_DEGRADED_SIZE_FACTOR", "1.5"),
+            ("CB_MAX_DAILY_LOSS", "lots"),
+            ("REBALANCE_MIN_USD", "500"),
+            ("REBALANCE_MAX_USD", "100"),
+            ("DRY_RUN", "0"),
+            ("TEST_ARB", "1"),
+            ("POLY_TX_MODE", "gasless"),
+            ("EXEC_MAX_CONCURRENT", "4"),
+        ]);
+        let problems = check(None, &e, &[]);
+        assert_eq!(keys(&problems), vec![
+            "CB_MAX_DAILY_LOSS", "IOC_OFFSET_TICKS", "VENUE_DEGRADED_SIZE_FACTOR",
+            "REBALANCE_MIN_USD", "TEST_ARB", "POLY_TX_MODE",
+        ]);
+        assert!(problems[1].message.contains("whole number"));
+        assert!(problems[2].message.contains("0 to 1"));
+        assert!(check(None, &env(&[]), &[]).is_empty());
+        let problems = check(None, &env(&[("EXEC_MODE", "live"), ("DRY_RUN", "1")]), &[]);
+        assert_eq!(keys(&problems), vec!["EXEC_MODE"]);
+        assert_eq!(keys(&check(None, &env(&[("EXEC_MODE", "live"), ("TEST_ARB", "1")]), &[])), vec!["TEST_ARB"]);
+        assert_eq!(keys(&check(None, &env(&[("POSITION_STORE", "postgres")]), &[])), vec!["POSITION_STORE_URL"]);
+        assert_eq!(keys(&check(None, &env(&[("POSITION_STORE", "mysql")]), &[])), vec!["POSITION_STORE"]);
+        assert_eq!(keys(&check(None, &env(&[("LEADER_LEASE", "postgres")]), &[])), vec!["LEADER_LEASE_URL"]);
+        assert_eq!(keys(&check(None, &env(&[("LEADER_LEASE", "file"), ("LEADER_RENEW_SECS", "10")]), &[])), vec!["LEADER_RENEW_SECS"]);
+        assert_eq!(keys(&check(None, &env(&[("UNIT_DISABLED_BUCKETS", "xrp:<0.7, *:*:noon")]), &[])), vec!["UNIT_DISABLED_BUCKETS"]);
+    }
+
+    #[test]
+    fn test_treasury_allowlist() {
+        let deposit = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+        let e = env(&[("TREASURY_ALLOWLIST", "0x1234, kalshi"), ("REBALANCE_KALSHI_DEPOSIT_ADDRESS", deposit)]);
+        assert_eq!(keys(&check(None, &e, &[])), vec!["TREASURY_ALLOWLIST", "TREASURY_ALLOWLIST", "REBALANCE_KALSHI_DEPOSIT_ADDRESS"]);
+        // Listed (in any case): fine
+        let e = env(&[("TREASURY_ALLOWLIST", &deposit.to_lowercase()), ("REBALANCE_KALSHI_DEPOSIT_ADDRESS", deposit)]);
+        assert!(check(None, &e, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_credentials_follow_enabled_strategies() {
+        let e = env(&[("POLY_PRIVATE_KEY", "abc"), ("KALSHI_PRIVATE_KEY_PATH", "/nonexistent/kalshi.pem")]);
+        // Poly-only strategies don't need Kalshi credentials
+        let problems = check(None, &e, &["updown"]);
+        assert_eq!(keys(&problems), vec!["POLY_PRIVATE_KEY", "POLY_FUNDER"]);
+        assert!(problems[1].message.contains("updown"));
+
+        let problems = check(None, &e, &["cross"]);
+        assert_eq!(keys(&problems), vec!["KALSHI_API_KEY_ID", "KALSHI_PRIVATE_KEY_PATH", "POLY_PRIVATE_KEY", "POLY_FUNDER"]);
+
+        let ok = env(&[
+            ("POLY_PRIVATE_KEY", &format!("0x{}", "a1".repeat(32))),
+            ("POLY_FUNDER", &format!("0x{}", "B2".repeat(20))),
+        ]);
+        assert!(check(None, &ok, &["poly_only"]).is_empty());
+    }
+
+    #[test]
+    fn test_bot_toml_sections() {
+        let toml = r#"
+            [shedule]
+            timezone = "utc"
+
+            [[schedule.window]]
+            strategies = ["crosss"]
+            hours = "09:00-17:00"
+
+            [[notify.channel]]
+            name = "pager"
+            url = "https://hooks.example/p"
+            url_env = "PAGER"
+            events = ["fill"]
+
+            [notify.templates]
+            filled = "{market}"
+        "#;
+        let problems = check(Some(toml), &env(&[]), &[]);
+        assert_eq!(keys(&problems), vec!["[shedule]", "[schedule]", "[notify.channel] pager", "[notify.channel] pager"]);
+        assert!(problems[2].message.contains("mutually exclusive"));
+
+        let problems = check(Some("[schedule]\ntimezone = \"mars\""), &env(&[]), &[]);
+        assert_eq!(keys(&problems), vec!["[schedule]"]);
+        assert!(problems[0].message.contains("mars"));
+        assert_eq!(keys(&check(Some("not toml ["), &env(&[]), &[])), vec!["bot.toml"]);
+        let sets = "[[market_sets.set]]\nname = \"btc\"\ntag = \"crypto\"";
+        assert_eq!(keys(&check(Some(sets), &env(&[]), &[])), vec!["[market_sets]"]);
+    }
+}
@@ -0,0 +1,923 @@
+// src/control.rs
+// Remote control API - halt / pause-asset / re-arm endpoints behind bearer tokens with per-action
+// scopes, an audit log of every control request, and optional TLS with client certificates (mTLS)
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::future::Future;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::events::{self, BotEvent};
+use crate::metrics;
+use crate::runtimes;
+use crate::storage;
+
+/// Largest request body accepted (control requests carry none)
+const MAX_BODY: usize = 64 * 1024;
+
+/// Largest request line plus headers accepted (read before anyone is authenticated)
+const MAX_HEADER_BYTES: u64 = 8 * 1024;
+
+/// Most headers accepted in one request
+const MAX_HEADERS: usize = 64;
+
+/// Time a connection gets for its TLS handshake, request and response. A per-read timeout
+/// alone restarts on every byte a slow client trickles in.
+const REQUEST_DEADLINE_SECS: u64 = 10;
+
+/// What a token may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// GET /status
+    Read,
+    /// POST /halt
+    Halt,
+    /// POST /pause/<asset>, POST /resume/<asset>
+    Pause,
+    /// POST /rearm
+    Rearm,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Read, Scope::Halt, Scope::Pause, Scope::Rearm];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Halt => "halt",
+            Scope::Pause => "pause",
+            Scope::Rearm => "rearm",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Scope::ALL.into_iter().find(|scope| scope.as_str() == s)
+    }
+}
+
+/// One API token: `name:secret:scope+scope` in CONTROL_TOKENS (`*` = every scope)
+#[derive(Clone, PartialEq)]
+pub struct ApiToken {
+    /// Caller identity recorded in the audit log
+    pub name: String,
+    secret: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+impl ApiToken {
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut parts = s.trim().splitn(3, ':');
+        let (Some(name), Some(secret), Some(scopes)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(anyhow!("expected name:secret:scope+scope"));
+        };
+        if name.is_empty() || secret.len() < 16 {
+            return Err(anyhow!("token '{}' needs a name and a secret of at least 16 characters", name));
+        }
+        let scopes = if scopes.trim() == "*" {
+            Scope::ALL.into_iter().collect()
+        } else {
+            scopes.split('+')
+                .map(|s| Scope::parse(s.trim()).ok_or_else(|| anyhow!("unknown scope '{}' (read, halt, pause, rearm)", s.trim())))
+                .collect::<Result<BTreeSet<_>>>()?
+        };
+        Ok(Self { name: name.to_string(), secret: secret.to_string(), scopes })
+    }
+}
+
+impl fmt::Debug for ApiToken {
+    /// The secret never reaches logs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiToken").field("name", &self.name).field("scopes", &self.scopes).finish_non_exhaustive()
+    }
+}
+
+/// Control API configuration from environment
+#[derive(Debug, Clone)]
+pub struct ControlConfig {
+    /// Listen address (unset = no control API)
+    pub listen: Option<String>,
+
+    /// Accepted tokens (CONTROL_TOKENS, comma-separated)
+    pub tokens: Vec<ApiToken>,
+
+    /// Audit log of control requests, one JSON line each (under DATA_DIR)
+    pub audit_file: PathBuf,
+
+    /// Server certificate chain (PEM); with `tls_key`, the API speaks HTTPS
+    pub tls_cert: Option<PathBuf>,
+
+    /// Server private key (PEM)
+    pub tls_key: Option<PathBuf>,
+
+    /// CA that client certificates must chain to (set = mTLS, clients without one are refused)
+    pub client_ca: Option<PathBuf>,
+
+    /// Connections served at once; more are refused (503 over plain HTTP)
+    pub max_connections: usize,
+}
+
+impl ControlConfig {
+    pub fn from_env() -> Self {
+        let path = |var: &str| std::env::var(var).ok().filter(|v| !v.trim().is_empty()).map(PathBuf::from);
+        Self {
+            listen: std::env::var("CONTROL_LISTEN").ok().filter(|v| !v.trim().is_empty()),
+
+            tokens: std::env::var("CONTROL_TOKENS")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|t| !t.trim().is_empty())
+                .filter_map(|t| match ApiToken::parse(t) {
+                    Ok(token) => Some(token),
+                    Err(e) => {
+                        // Only the name: the rest of the entry is the secret
+                        warn!("[CONTROL] Ignoring invalid CONTROL_TOKENS entry '{}': {}",
+                              t.trim().split(':').next().unwrap_or_default(), e);
+                        None
+                    }
+                })
+                .collect(),
+
+            audit_file: storage::data_path(std::env::var("CONTROL_AUDIT_FILE")
+                .unwrap_or_else(|_| "control_audit.jsonl".to_string())),
+
+            tls_cert: path("CONTROL_TLS_CERT"),
+
+            tls_key: path("CONTROL_TLS_KEY"),
+
+            client_ca: path("CONTROL_TLS_CLIENT_CA"),
+
+            max_connections: std::env::var("CONTROL_MAX_CONNECTIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(8),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.listen.is_some()
+    }
+
+    pub fn tls(&self) -> bool {
+        self.tls_cert.is_some()
+    }
+
+    /// An enabled API with no usable token, or half a TLS setup, is refused rather than served open
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        if self.tokens.is_empty() {
+            return Err(anyhow!("CONTROL_LISTEN is set but CONTROL_TOKENS has no valid token"));
+        }
+        if self.max_connections == 0 {
+            return Err(anyhow!("CONTROL_MAX_CONNECTIONS must be at least 1"));
+        }
+        if self.tls_cert.is_some() != self.tls_key.is_some() {
+            return Err(anyhow!("CONTROL_TLS_CERT and CONTROL_TLS_KEY must be set together"));
+        }
+        if self.client_ca.is_some() && !self.tls() {
+            return Err(anyhow!("CONTROL_TLS_CLIENT_CA needs CONTROL_TLS_CERT / CONTROL_TLS_KEY"));
+        }
+        if !cfg!(feature = "mtls") && self.tls() {
+            return Err(anyhow!("CONTROL_TLS_* set but this build lacks the mtls feature"));
+        }
+        Ok(())
+    }
+}
+
+/// A control request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    Status,
+    /// Stop opening positions until re-armed
+    Halt,
+    /// Stop opening positions in one asset (league / series)
+    Pause { asset: String },
+    Resume { asset: String },
+    /// Lift a halt / reset the circuit breaker
+    Rearm,
+}
+
+impl Action {
+    pub fn route(method: &str, path: &str) -> Option<Self> {
+        let path = path.split('?').next().unwrap_or_default().trim_end_matches('/');
+        let asset = |rest: &str| (!rest.is_empty() && !rest.contains('/')).then(|| rest.to_lowercase());
+        match method {
+            "GET" if path == "/status" => Some(Action::Status),
+            "POST" if path == "/halt" => Some(Action::Halt),
+            "POST" if path == "/rearm" => Some(Action::Rearm),
+            "POST" => {
+                if let Some(rest) = path.strip_prefix("/pause/") {
+                    asset(rest).map(|asset| Action::Pause { asset })
+                } else if let Some(rest) = path.strip_prefix("/resume/") {
+                    asset(rest).map(|asset| Action::Resume { asset })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn scope(&self) -> Scope {
+        match self {
+            Action::Status => Scope::Read,
+            Action::Halt => Scope::Halt,
+            Action::Pause { .. } | Action::Resume { .. } => Scope::Pause,
+            Action::Rearm => Scope::Rearm,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Action::Status => "status".into(),
+            Action::Halt => "halt".into(),
+            Action::Pause { asset } => format!("pause {}", asset),
+            Action::Resume { asset } => format!("resume {}", asset),
+            Action::Rearm => "rearm".into(),
+        }
+    }
+}
+
+/// Who sent a request
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Caller {
+    /// Name of the token presented (None until authenticated)
+    pub token: Option<String>,
+    pub peer: String,
+    /// Subject CN of the client certificate (mTLS)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+}
+
+impl fmt::Display for Caller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.token.as_deref().unwrap_or("anonymous"), self.peer)?;
+        if let Some(cn) = &self.client_cert {
+            write!(f, " (cert {})", cn)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Denied {
+    /// No token, or not one of ours
+    Unauthenticated,
+    /// Valid token without the scope
+    Forbidden(Scope),
+}
+
+/// Byte comparison that takes as long for a near miss as for a wrong first byte
+pub fn secret_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = a.len() ^ b.len();
+    for (i, x) in a.iter().enumerate() {
+        diff |= (x ^ b.get(i).copied().unwrap_or(!x)) as usize;
+    }
+    diff == 0
+}
+
+/// The token `presented` is
+pub fn identify<'a>(tokens: &'a [ApiToken], presented: &str) -> Option<&'a ApiToken> {
+    // Every token is compared, so timing doesn't tell which one nearly matched
+    tokens.iter().fold(None, |found, t| {
+        if secret_eq(t.secret.as_bytes(), presented.as_bytes()) { Some(t) } else { found }
+    })
+}
+
+/// The token `presented` belongs to, if it carries `scope`
+pub fn authorize<'a>(tokens: &'a [ApiToken], presented: Option<&str>, scope: Scope) -> Result<&'a ApiToken, Denied> {
+    let token = presented.and_then(|p| identify(tokens, p)).ok_or(Denied::Unauthenticated)?;
+    if token.scopes.contains(&scope) { Ok(token) } else { Err(Denied::Forbidden(scope)) }
+}
+
+/// One audit log line
+#[derive(Debug, Serialize)]
+struct AuditEntry<'a> {
+    ts: String,
+    #[serde(flatten)]
+    caller: &'a Caller,
+    #[serde(flatten)]
+    action: &'a Action,
+    ok: bool,
+    outcome: &'a str,
+}
+
+fn audit(path: &PathBuf, caller: &Caller, action: &Action, ok: bool, outcome: &str) {
+    let entry = AuditEntry { ts: chrono::Utc::now().to_rfc3339(), caller, action, ok, outcome };
+    let line = serde_json::to_string(&entry).unwrap_or_default();
+    let written = std::fs::OpenOptions::new().create(true).append(true).open(path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = written {
+        warn!("[CONTROL] Audit write to {} failed: {} - entry: {}", path.display(), e, line);
+    }
+}
+
+/// What `Switches` writes to its state file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SwitchState {
+    #[serde(default)]
+    halted: Option<String>,
+    #[serde(default)]
+    paused: BTreeSet<String>,
+}
+
+/// Operator switches the bots check before opening a position
+#[derive(Debug, Default)]
+pub struct Switches {
+    halted: RwLock<Option<String>>,
+    paused: RwLock<BTreeSet<String>>,
+    /// File every change is written through to (None = kept in memory only)
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl Switches {
+    /// Restore the halt and pauses saved in `path` and write every later change to it, so an
+    /// operator's halt survives a restart. A missing file restores nothing; an unreadable one
+    /// is an error rather than a silent re-arm.
+    pub fn persist_to(&self, path: PathBuf) -> Result<()> {
+        let state: SwitchState = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .with_context(|| format!("control state file {} is unreadable", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => SwitchState::default(),
+            Err(e) => return Err(e).with_context(|| format!("reading control state file {}", path.display())),
+        };
+        if let Some(reason) = &state.halted {
+            warn!("[CONTROL] 🛑 Still halted from before the restart: {}", reason);
+        }
+        if !state.paused.is_empty() {
+            warn!("[CONTROL] ⏸️  Still paused from before the restart: {}", state.paused.iter().cloned().collect::<Vec<_>>().join(", "));
+        }
+        *self.halted.write().unwrap() = state.halted;
+        *self.paused.write().unwrap() = state.paused;
+        *self.path.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path.read().unwrap().clone() else {
+            return;
+        };
+        let state = SwitchState { halted: self.halted(), paused: self.paused.read().unwrap().clone() };
+        let result = serde_json::to_vec_pretty(&state).map_err(anyhow::Error::from)
+            .and_then(|data| storage::write_atomic(&path, &data));
+        if let Err(e) = result {
+            warn!("[CONTROL] Failed to save control state to {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn halt(&self, reason: &str) {
+        *self.halted.write().unwrap() = Some(reason.to_string());
+        self.save();
+    }
+
+    /// Lift a halt; false if there was none
+    pub fn rearm(&self) -> bool {
+        let was_halted = self.halted.write().unwrap().take().is_some();
+        if was_halted {
+            self.save();
+        }
+        was_halted
+    }
+
+    /// Why trading is halted (None = not halted)
+    pub fn halted(&self) -> Option<String> {
+        self.halted.read().unwrap().clone()
+    }
+
+    /// Pause an asset; false if it already was
+    pub fn pause(&self, asset: &str) -> bool {
+        let added = self.paused.write().unwrap().insert(asset.to_lowercase());
+        if added {
+            self.save();
+        }
+        added
+    }
+
+    /// Resume an asset; false if it wasn't paused
+    pub fn resume(&self, asset: &str) -> bool {
+        let removed = self.paused.write().unwrap().remove(&asset.to_lowercase());
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Whether any of `names` (asset, league, slug...) is paused (case-insensitive)
+    pub fn is_paused(&self, names: &[&str]) -> bool {
+        let paused = self.paused.read().unwrap();
+        !paused.is_empty() && names.iter().any(|n| paused.contains(&n.to_lowercase()))
+    }
+
+    pub fn paused(&self) -> Vec<String> {
+        self.paused.read().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Process-wide operator switches
+pub fn switches() -> &'static Switches {
+    static SWITCHES: OnceLock<Switches> = OnceLock::new();
+    SWITCHES.get_or_init(Switches::default)
+}
+
+/// How a bot carries out halt / re-arm and reports its state. Asset pauses are
+/// common to every bot and go to `switches()`.
+pub trait ControlHandler: Send + Sync + 'static {
+    /// Stop opening positions; returns what was done
+    fn halt(&self, caller: &Caller) -> impl Future<Output = Result<String>> + Send;
+
+    /// Lift a halt / reset the circuit breaker; returns what was done
+    fn rearm(&self, caller: &Caller) -> impl Future<Output = Result<String>> + Send;
+
+    /// Bot state for GET /status
+    fn status(&self) -> impl Future<Output = serde_json::Value> + Send;
+}
+
+/// Authenticate, authorize, carry out and audit one request. Returns the HTTP status and JSON body.
+pub async fn process<H: ControlHandler>(
+    config: &ControlConfig,
+    handler: &H,
+    action: Option<Action>,
+    token: Option<&str>,
+    mut caller: Caller,
+) -> (&'static str, String) {
+    let Some(action) = action else {
+        return ("404 Not Found", r#"{"error":"not found"}"#.into());
+    };
+    caller.token = token.and_then(|t| identify(&config.tokens, t)).map(|t| t.name.clone());
+    if let Err(denied) = authorize(&config.tokens, token, action.scope()) {
+        metrics::incr("control.denied");
+        let (status, outcome) = match denied {
+            Denied::Unauthenticated => ("401 Unauthorized", "unauthenticated".to_string()),
+            Denied::Forbidden(scope) => ("403 Forbidden", format!("missing scope {}", scope.as_str())),
+        };
+        warn!("[CONTROL] 🚫 {} refused for {}: {}", action.describe(), caller, outcome);
+        audit(&config.audit_file, &caller, &action, false, &outcome);
+        return (status, serde_json::json!({"error": outcome}).to_string());
+    }
+
+    let result = match &action {
+        Action::Status => {
+            let mut status = handler.status().await;
+            if let Some(map) = status.as_object_mut() {
+                map.insert("paused_assets".into(), serde_json::json!(switches().paused()));
+            }
+            return ("200 OK", serde_json::to_string_pretty(&status).unwrap_or_default());
+        }
+        Action::Halt => handler.halt(&caller).await,
+        Action::Rearm => handler.rearm(&caller).await,
+        Action::Pause { asset } => Ok(if switches().pause(asset) {
+            format!("{} paused", asset)
+        } else {
+            format!("{} was already paused", asset)
+        }),
+        Action::Resume { asset } => Ok(if switches().resume(asset) {
+            format!("{} resumed", asset)
+        } else {
+            format!("{} was not paused", asset)
+        }),
+    };
+    let (ok, outcome) = match &result {
+        Ok(done) => (true, done.clone()),
+        Err(e) => (false, format!("failed: {:#}", e)),
+    };
+    metrics::incr(&format!("control.{}", action.scope().as_str()));
+    if ok {
+        warn!("[CONTROL] 🎛️ {} by {}: {}", action.describe(), caller, outcome);
+    } else {
+        warn!("[CONTROL] ❌ {} by {} {}", action.describe(), caller, outcome);
+    }
+    audit(&config.audit_file, &caller, &action, ok, &outcome);
+    events::publish(BotEvent::ControlAction {
+        caller: caller.to_string(),
+        action: action.describe(),
+        outcome: outcome.clone(),
+    });
+    match result {
+        Ok(done) => ("200 OK", serde_json::json!({"ok": true, "result": done}).to_string()),
+        Err(_) => ("500 Internal Server Error", serde_json::json!({"ok": false, "error": outcome}).to_string()),
+    }
+}
+
+/// One line of the request head; a line cut off by the size cap is an error, not a short header
+fn head_line<R: BufRead>(head: &mut std::io::Take<R>, line: &mut String) -> Result<usize> {
+    let n = head.read_line(line)?;
+    if head.limit() == 0 && !line.ends_with('\n') {
+        return Err(anyhow!("request head over {} bytes", MAX_HEADER_BYTES));
+    }
+    Ok(n)
+}
+
+/// Method, path and bearer token of one request (its body is read and dropped)
+fn read_request<R: BufRead>(reader: &mut R) -> Result<(String, String, Option<String>)> {
+    let mut head = reader.by_ref().take(MAX_HEADER_BYTES);
+    let mut line = String::new();
+    head_line(&mut head, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let (mut length, mut token, mut headers) = (0usize, None, 0);
+    loop {
+        let mut header = String::new();
+        if head_line(&mut head, &mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Err(anyhow!("more than {} headers", MAX_HEADERS));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse()?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                token = value.strip_prefix("Bearer ").map(|t| t.trim().to_string());
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(anyhow!("body of {} bytes over the {} limit", length, MAX_BODY));
+    }
+    std::io::copy(&mut reader.take(length as u64), &mut std::io::sink())?;
+    Ok((method, path, token))
+}
+
+/// A connection that has to finish by a deadline: every read / write waits at most until then
+#[derive(Debug)]
+struct TimedStream {
+    stream: TcpStream,
+    until: Instant,
+}
+
+impl TimedStream {
+    fn new(stream: TcpStream, deadline: Duration) -> Self {
+        Self { stream, until: Instant::now() + deadline }
+    }
+
+    fn remaining(&self) -> std::io::Result<Duration> {
+        let left = self.until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(std::io::Error::new(ErrorKind::TimedOut, "control request deadline passed"));
+        }
+        Ok(left)
+    }
+}
+
+impl Read for TimedStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stream.set_read_timeout(Some(self.remaining()?))?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for TimedStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.set_write_timeout(Some(self.remaining()?))?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// One of `max_connections` connection slots, given back on drop
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(active: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        if active.fetch_add(1, Ordering::SeqCst) >= max {
+            active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Self(active.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn respond(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body,
+    )
+}
+
+/// Serve one connection (plain or TLS) to completion
+fn serve<S: Read + Write, H: ControlHandler>(
+    mut stream: S,
+    config: &ControlConfig,
+    handler: &H,
+    runtime: &tokio::runtime::Handle,
+    caller: Caller,
+) -> Result<()> {
+    let (method, path, token) = read_request(&mut BufReader::new(&mut stream))?;
+    let action = Action::route(&method, &path);
+    let (status, body) = runtime.block_on(process(config, handler, action, token.as_deref(), caller));
+    stream.write_all(respond(status, &body).as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "mtls")]
+mod tls {
+    use super::*;
+    use openssl::nid::Nid;
+    use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslStream, SslVerifyMode};
+
+    pub type Acceptor = SslAcceptor;
+
+    pub fn acceptor(config: &ControlConfig) -> Result<Option<Acceptor>> {
+        let (Some(cert), Some(key)) = (&config.tls_cert, &config.tls_key) else {
+            return Ok(None);
+        };
+        let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())?;
+        builder.set_certificate_chain_file(cert).with_context(|| format!("CONTROL_TLS_CERT {}", cert.display()))?;
+        builder.set_private_key_file(key, SslFiletype::PEM).with_context(|| format!("CONTROL_TLS_KEY {}", key.display()))?;
+        builder.check_private_key().context("CONTROL_TLS_KEY doesn't match CONTROL_TLS_CERT")?;
+        if let Some(ca) = &config.client_ca {
+            builder.set_ca_file(ca).with_context(|| format!("CONTROL_TLS_CLIENT_CA {}", ca.display()))?;
+            builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        }
+        Ok(Some(builder.build()))
+    }
+
+    /// Handshake; the client certificate's subject CN (if one was presented) identifies the caller
+    pub fn accept(acceptor: &Acceptor, stream: TimedStream) -> Result<(SslStream<TimedStream>, Option<String>)> {
+        let stream = acceptor.accept(stream).map_err(|e| anyhow!("TLS handshake: {}", e))?;
+        let cn = stream.ssl().peer_certificate().and_then(|cert| {
+            cert.subject_name().entries_by_nid(Nid::COMMONNAME).next()
+                .and_then(|e| e.data().as_utf8().ok())
+                .map(|s| s.to_string())
+        });
+        Ok((stream, cn))
+    }
+}
+
+#[cfg(not(feature = "mtls"))]
+mod tls {
+    use super::*;
+
+    pub enum Acceptor {}
+
+    pub fn acceptor(_config: &ControlConfig) -> Result<Option<Acceptor>> {
+        Ok(None)
+    }
+
+    pub fn accept(acceptor: &Acceptor, _stream: TimedStream) -> Result<(TimedStream, Option<String>)> {
+        match *acceptor {}
+    }
+}
+
+fn connection<H: ControlHandler>(
+    stream: TcpStream,
+    config: &ControlConfig,
+    handler: &H,
+    acceptor: Option<&tls::Acceptor>,
+    runtime: &tokio::runtime::Handle,
+) -> Result<()> {
+    let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_else(|_| "?".into());
+    let stream = TimedStream::new(stream, Duration::from_secs(REQUEST_DEADLINE_SECS));
+    match acceptor {
+        Some(acceptor) => {
+            let (stream, client_cert) = tls::accept(acceptor, stream).inspect_err(|_| metrics::incr("control.tls_failures"))?;
+            serve(stream, config, handler, runtime, Caller { token: None, peer, client_cert })
+        }
+        None => serve(stream, config, handler, runtime, Caller { token: None, peer, client_cert: None }),
+    }
+}
+
+/// Start the control API on its own thread (a no-op without CONTROL_LISTEN). Handler calls
+/// run on the bookkeeping runtime, or the caller's when there is none.
+pub fn spawn<H: ControlHandler>(config: ControlConfig, handler: Arc<H>) -> Result<()> {
+    let Some(listen) = config.listen.clone() else {
+        return Ok(());
+    };
+    config.validate()?;
+    let acceptor = tls::acceptor(&config)?.map(Arc::new);
+    let listener = TcpListener::bind(&listen).with_context(|| format!("CONTROL_LISTEN {}", listen))?;
+    let runtime = runtimes::bookkeeping().cloned().unwrap_or_else(tokio::runtime::Handle::current);
+    let mode = match (config.tls(), config.client_ca.is_some()) {
+        (true, true) => "mTLS",
+        (true, false) => "TLS",
+        _ => "plain HTTP",
+    };
+    info!("[CONTROL] 🎛️ Control API on {} ({}), {} token(s), audit log {}",
+          listen, mode, config.tokens.len(), config.audit_file.display());
+    if mode == "plain HTTP" && !listener.local_addr().is_ok_and(|a| a.ip().is_loopback()) {
+        warn!("[CONTROL] ⚠️ Control API reachable off-host without TLS - set CONTROL_TLS_CERT / CONTROL_TLS_KEY");
+    }
+
+    let config = Arc::new(config);
+    let active = Arc::new(AtomicUsize::new(0));
+    std::thread::Builder::new().name("control-api".into()).spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("[CONTROL] Accept failed: {}", e);
+                    continue;
+                }
+            };
+            let Some(slot) = Slot::take(&active, config.max_connections) else {
+                metrics::incr("control.busy");
+                debug!("[CONTROL] {} connections open - refusing {:?}", config.max_connections, stream.peer_addr());
+                // Never block the accept loop on a client; over TLS there is no plain answer to give
+                if acceptor.is_none() && stream.set_nonblocking(true).is_ok() {
+                    let _ = stream.write_all(respond("503 Service Unavailable", r#"{"error":"busy"}"#).as_bytes());
+                }
+                continue;
+            };
+            let (config, handler, acceptor, runtime) = (config.clone(), handler.clone(), acceptor.clone(), runtime.clone());
+            // Control requests are rare: one short-lived thread each (at most max_connections, each
+            // bounded by the request deadline) keeps a slow client from blocking the next
+            std::thread::spawn(move || {
+                let _slot = slot;
+                if let Err(e) = connection(stream, &config, handler.as_ref(), acceptor.as_deref(), &runtime) {
+                    warn!("[CONTROL] Request failed: {:#}", e);
+                }
+            });
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> Vec<ApiToken> {
+        vec![
+            ApiToken::parse("ops:0123456789abcdef0123:*").unwrap(),
+            ApiToken::parse("grafana:fedcba9876543210fedc:read").unwrap(),
+        ]
+    }
+
+    /// Records halts / re-arms instead of touching a breaker
+    #[derive(Default)]
+    struct MockBot {
+        halted: RwLock<bool>,
+    }
+
+    impl ControlHandler for MockBot {
+        async fn halt(&self, _caller: &Caller) -> Result<String> {
+            *self.halted.write().unwrap() = true;
+            Ok("halted".into())
+        }
+
+        async fn rearm(&self, _caller: &Caller) -> Result<String> {
+            *self.halted.write().unwrap() = false;
+            Ok("re-armed".into())
+        }
+
+        async fn status(&self) -> serde_json::Value {
+            serde_json::json!({"bot": "mock"})
+        }
+    }
+
+    #[test]
+    fn test_switches_survive_restart() {
+        let path = std::env::temp_dir().join(format!("control_state_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let switches = Switches::default();
+        switches.persist_to(path.clone()).unwrap();
+        switches.halt("halted through the control API by ops");
+        assert!(switches.pause("BTC") && switches.pause("eth"));
+        assert!(switches.resume("eth"));
+
+        let restarted = Switches::default();
+        restarted.persist_to(path.clone()).unwrap();
+        assert_eq!(restarted.halted().as_deref(), Some("halted through the control API by ops"));
+        assert!(restarted.is_paused(&["btc"]) && !restarted.is_paused(&["eth"]));
+
+        assert!(restarted.rearm());
+        let rearmed = Switches::default();
+        rearmed.persist_to(path.clone()).unwrap();
+        assert_eq!(rearmed.halted(), None);
+
+        // A corrupt file refuses to start rather than silently re-arming
+        std::fs::write(&path, b"{not json").unwrap();
+        assert!(Switches::default().persist_to(path.clone()).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tokens_and_scopes() {
+        let tokens = tokens();
+        assert_eq!(tokens[0].scopes.len(), 4);
+        assert_eq!(authorize(&tokens, Some("0123456789abcdef0123"), Scope::Halt).unwrap().name, "ops");
+        assert_eq!(authorize(&tokens, Some("fedcba9876543210fedc"), Scope::Read).unwrap().name, "grafana");
+        assert_eq!(authorize(&tokens, Some("fedcba9876543210fedc"), Scope::Halt), Err(Denied::Forbidden(Scope::Halt)));
+        assert_eq!(authorize(&tokens, Some("0123456789abcdef012"), Scope::Read), Err(Denied::Unauthenticated));
+        assert_eq!(authorize(&tokens, None, Scope::Read), Err(Denied::Unauthenticated));
+
+        assert!(ApiToken::parse("short:secret:read").is_err());
+        assert!(ApiToken::parse("ops:0123456789abcdef0123:read+deploy").is_err());
+        assert!(ApiToken::parse("ops:0123456789abcdef0123").is_err());
+        // The secret stays out of Debug output
+        assert!(!format!("{:?}", tokens[0]).contains("0123456789"));
+    }
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(Action::route("GET", "/status"), Some(Action::Status));
+        assert_eq!(Action::route("POST", "/pause/BTC"), Some(Action::Pause { asset: "btc".into() }));
+        assert_eq!(Action::route("POST", "/resume/nba/"), Some(Action::Resume { asset: "nba".into() }));
+        assert_eq!(Action::route("GET", "/halt"), None);
+        assert_eq!(Action::route("POST", "/pause/"), None);
+        assert_eq!(Action::route("POST", "/pause/a/b"), None);
+        assert_eq!(Action::Resume { asset: "x".into() }.scope(), Scope::Pause);
+
+        let raw = "POST /halt HTTP/1.1\r\nHost: x\r\nAuthorization: Bearer abc\r\nContent-Length: 2\r\n\r\n{}";
+        let (method, path, token) = read_request(&mut BufReader::new(raw.as_bytes())).unwrap();
+        assert_eq!((method.as_str(), path.as_str(), token.as_deref()), ("POST", "/halt", Some("abc")));
+    }
+
+    #[test]
+    fn test_unauthenticated_requests_are_bounded() {
+        // An endless header line stops at the head cap instead of growing
+        let endless = format!("GET /status HTTP/1.1\r\nX-Pad: {}", "a".repeat(MAX_HEADER_BYTES as usize * 4));
+        let err = read_request(&mut BufReader::new(endless.as_bytes())).unwrap_err();
+        assert!(err.to_string().contains("request head over"), "{}", err);
+
+        let many = format!("GET /status HTTP/1.1\r\n{}\r\n", "X: y\r\n".repeat(MAX_HEADERS + 1));
+        assert!(read_request(&mut BufReader::new(many.as_bytes())).is_err());
+        let body = format!("POST /halt HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY + 1);
+        assert!(read_request(&mut BufReader::new(body.as_bytes())).is_err());
+
+        // Connection slots are capped and handed back when a connection ends
+        let active = Arc::new(AtomicUsize::new(0));
+        let (a, b) = (Slot::take(&active, 2), Slot::take(&active, 2));
+        assert!(a.is_some() && b.is_some());
+        assert!(Slot::take(&active, 2).is_none());
+        drop(a);
+        assert!(Slot::take(&active, 2).is_some());
+        assert_eq!(active.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_actions_are_scoped_and_audited() {
+        let dir = std::env::temp_dir().join(format!("control_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = ControlConfig {
+            listen: Some("127.0.0.1:0".into()),
+            tokens: tokens(),
+            audit_file: dir.join("audit.jsonl"),
+            tls_cert: None,
+            tls_key: None,
+            client_ca: None,
+            max_connections: 8,
+        };
+        assert!(config.validate().is_ok());
+        let bot = MockBot::default();
+        let caller = || Caller { token: None, peer: "10.0.0.5:4000".into(), client_cert: Some("alice".into()) };
+
+        // Read-only token can't halt
+        let (status, _) = process(&config, &bot, Some(Action::Halt), Some("fedcba9876543210fedc"), caller()).await;
+        assert_eq!(status, "403 Forbidden");
+        assert!(!*bot.halted.read().unwrap());
+
+        let (status, _) = process(&config, &bot, Some(Action::Halt), Some("0123456789abcdef0123"), caller()).await;
+        assert_eq!(status, "200 OK");
+        assert!(*bot.halted.read().unwrap());
+        let (status, _) = process(&config, &bot, Some(Action::Pause { asset: "zz-test".into() }),
+                                  Some("0123456789abcdef0123"), caller()).await;
+        assert_eq!(status, "200 OK");
+        assert!(switches().is_paused(&["ZZ-TEST"]));
+        switches().resume("zz-test");
+
+        let (status, body) = process(&config, &bot, Some(Action::Status), Some("fedcba9876543210fedc"), caller()).await;
+        assert_eq!(status, "200 OK");
+        assert!(body.contains("\"bot\": \"mock\"") && body.contains("paused_assets"));
+        let (status, _) = process(&config, &bot, Some(Action::Rearm), None, caller()).await;
+        assert_eq!(status, "401 Unauthorized");
+
+        // Every control action lands in the audit log with the caller (status reads aren't audited)
+        let log = std::fs::read_to_string(&config.audit_file).unwrap();
+        let lines: Vec<serde_json::Value> = log.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!((lines[0]["token"].as_str(), lines[0]["ok"].as_bool()), (Some("grafana"), Some(false)));
+        assert_eq!(lines[1]["action"], "halt");
+        assert_eq!((lines[1]["token"].as_str(), lines[1]["client_cert"].as_str()), (Some("ops"), Some("alice")));
+        assert_eq!((lines[2]["action"].as_str(), lines[2]["asset"].as_str()), (Some("pause"), Some("zz-test")));
+        assert_eq!((lines[3]["action"].as_str(), lines[3]["token"].as_str()), (Some("rearm"), None));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
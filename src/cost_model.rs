@@ -0,0 +1,371 @@
+// src/cost_model.rs
+// Execution cost model - fill rate and slippage fitted from realized fills, used to size trades on expected profit
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::archive;
+use crate::runtimes;
+use crate::storage;
+
+/// Default fill sample file in DATA_DIR (override with COST_SAMPLES_FILE)
+pub const DEFAULT_SAMPLES_FILE: &str = "fill_samples.jsonl";
+
+/// Order size / displayed depth bucket edges
+const RATIO_EDGES: [f64; 3] = [0.25, 0.5, 1.0];
+
+/// Seconds-to-expiry bucket edges (5 min, 1 h, 1 day); unknown expiry goes in the last bucket
+const EXPIRY_EDGES: [i64; 3] = [300, 3600, 86_400];
+
+/// Cost model configuration from environment
+#[derive(Debug, Clone)]
+pub struct CostModelConfig {
+    /// Hours between refits (0 = off: no samples recorded, sizing uses the book as displayed)
+    pub refit_hours: u64,
+
+    /// Samples a bucket needs before its own fit is used over the coarser one
+    pub min_samples: usize,
+
+    /// Loss per contract left unmatched when one leg fills short (cents)
+    pub unwind_cents: f64,
+
+    pub samples_file: PathBuf,
+
+    pub model_file: PathBuf,
+}
+
+impl CostModelConfig {
+    pub fn from_env() -> Self {
+        Self {
+            refit_hours: std::env::var("COST_MODEL_REFIT_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+
+            min_samples: std::env::var("COST_MODEL_MIN_SAMPLES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20),
+
+            unwind_cents: std::env::var("COST_MODEL_UNWIND_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10.0),
+
+            samples_file: storage::data_path(std::env::var("COST_SAMPLES_FILE")
+                .unwrap_or_else(|_| DEFAULT_SAMPLES_FILE.to_string())),
+
+            model_file: storage::data_path(std::env::var("COST_MODEL_FILE")
+                .unwrap_or_else(|_| "cost_model.json".to_string())),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.refit_hours > 0
+    }
+}
+
+/// One IOC leg as sent and as filled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillSample {
+    pub ts: String,
+    pub venue: String,
+    /// Ask the leg was priced off (0-1)
+    pub ask: f64,
+    /// Contracts displayed at that ask
+    pub depth: f64,
+    /// Contracts sent
+    pub size: f64,
+    /// Seconds until the market closes (None = unknown)
+    pub secs_to_expiry: Option<i64>,
+    pub filled: f64,
+    /// Average fill price (0-1; 0 when nothing filled)
+    pub avg_price: f64,
+}
+
+impl FillSample {
+    pub fn new(venue: &str, ask: f64, depth: f64, size: f64, secs_to_expiry: Option<i64>, filled: f64, cost: f64) -> Self {
+        Self {
+            ts: chrono::Utc::now().to_rfc3339(),
+            venue: venue.to_string(),
+            ask,
+            depth,
+            size,
+            secs_to_expiry,
+            filled,
+            avg_price: if filled > 0.0 { cost / filled } else { 0.0 },
+        }
+    }
+}
+
+fn ratio_bucket(size: f64, depth: f64) -> usize {
+    let ratio = if depth > 0.0 { size / depth } else { f64::INFINITY };
+    RATIO_EDGES.iter().take_while(|e| ratio > **e).count()
+}
+
+fn expiry_bucket(secs: Option<i64>) -> usize {
+    secs.map_or(EXPIRY_EDGES.len(), |s| EXPIRY_EDGES.iter().take_while(|e| s >= **e).count())
+}
+
+/// Fill rate and slippage of one bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BucketFit {
+    pub samples: usize,
+    /// Mean filled / sent
+    pub fill_rate: f64,
+    /// Lowest filled / sent seen (0 in models saved before it was fitted: assume the worst)
+    #[serde(default)]
+    pub worst_fill_rate: f64,
+    /// Contract-weighted fill price minus ask (cents, positive = paid more)
+    pub slippage_cents: f64,
+}
+
+impl BucketFit {
+    fn of(samples: &[&FillSample]) -> Self {
+        let filled: f64 = samples.iter().map(|s| s.filled).sum();
+        let paid: f64 = samples.iter().map(|s| (s.avg_price - s.ask) * s.filled).sum();
+        let rates: Vec<f64> = samples.iter().map(|s| (s.filled / s.size).clamp(0.0, 1.0)).collect();
+        Self {
+            samples: samples.len(),
+            fill_rate: rates.iter().sum::<f64>() / samples.len().max(1) as f64,
+            worst_fill_rate: rates.iter().copied().fold(f64::INFINITY, f64::min).min(1.0),
+            slippage_cents: if filled > 0.0 { paid / filled * 100.0 } else { 0.0 },
+        }
+    }
+}
+
+/// Expected execution of one leg
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Expected {
+    pub fill_rate: f64,
+    pub worst_fill_rate: f64,
+    pub slippage_cents: f64,
+}
+
+/// Fitted model: per (size/depth, time-to-expiry) bucket, with per-ratio and overall fallbacks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostModel {
+    pub fitted_at: String,
+    pub samples: usize,
+    /// Keyed `r<ratio>_t<expiry>`, `r<ratio>` and `all`
+    pub buckets: BTreeMap<String, BucketFit>,
+    pub min_samples: usize,
+}
+
+impl CostModel {
+    /// Fit every bucket from `samples` (legs sent with no size are ignored)
+    pub fn fit(samples: &[FillSample], min_samples: usize) -> Self {
+        let samples: Vec<&FillSample> = samples.iter().filter(|s| s.size > 0.0).collect();
+        let mut groups: BTreeMap<String, Vec<&FillSample>> = BTreeMap::new();
+        for s in &samples {
+            let r = ratio_bucket(s.size, s.depth);
+            groups.entry(format!("r{}_t{}", r, expiry_bucket(s.secs_to_expiry))).or_default().push(s);
+            groups.entry(format!("r{}", r)).or_default().push(s);
+            groups.entry("all".to_string()).or_default().push(s);
+        }
+        Self {
+            fitted_at: chrono::Utc::now().to_rfc3339(),
+            samples: samples.len(),
+            buckets: groups.iter().map(|(k, v)| (k.clone(), BucketFit::of(v))).collect(),
+            min_samples,
+        }
+    }
+
+    /// Most specific bucket with enough samples; None means no evidence (take the book at face value)
+    pub fn expect(&self, size: f64, depth: f64, secs_to_expiry: Option<i64>) -> Option<Expected> {
+        let r = ratio_bucket(size, depth);
+        [format!("r{}_t{}", r, expiry_bucket(secs_to_expiry)), format!("r{}", r), "all".to_string()].iter()
+            .filter_map(|k| self.buckets.get(k))
+            .find(|b| b.samples >= self.min_samples.max(1))
+            .map(|b| Expected { fill_rate: b.fill_rate, worst_fill_rate: b.worst_fill_rate, slippage_cents: b.slippage_cents })
+    }
+
+    /// Expected profit (cents) of sending `size` on both legs of an arb with `edge_cents` per
+    /// contract: matched contracts earn the edge less both legs' slippage. Unmatched exposure is
+    /// the worse of either leg filling at its worst while the other fills as usual (two legs
+    /// with the same mean still fill unevenly), unwound at `unwind_cents` each
+    pub fn expected_profit(&self, edge_cents: f64, size: f64, yes_depth: f64, no_depth: f64,
+                           secs_to_expiry: Option<i64>, unwind_cents: f64) -> f64 {
+        let face = Expected { fill_rate: 1.0, worst_fill_rate: 1.0, slippage_cents: 0.0 };
+        let yes = self.expect(size, yes_depth, secs_to_expiry).unwrap_or(face);
+        let no = self.expect(size, no_depth, secs_to_expiry).unwrap_or(face);
+        let matched = size * yes.fill_rate.min(no.fill_rate);
+        let unmatched = size * (yes.fill_rate - no.worst_fill_rate)
+            .max(no.fill_rate - yes.worst_fill_rate)
+            .max(0.0);
+        matched * (edge_cents - yes.slippage_cents - no.slippage_cents) - unmatched * unwind_cents
+    }
+
+    /// Size up to `max` with the highest expected profit among max, ¾, ½, ¼ and the smallest
+    /// order, with that profit; None when no size is expected to make money
+    #[allow(clippy::too_many_arguments)]
+    pub fn best_size(&self, edge_cents: f64, max: f64, min: f64, yes_depth: f64, no_depth: f64,
+                     secs_to_expiry: Option<i64>, unwind_cents: f64) -> Option<(f64, f64)> {
+        [1.0, 0.75, 0.5, 0.25].iter()
+            .map(|f| (max * f).floor().max(min).min(max))
+            .chain(std::iter::once(min.min(max)))
+            .filter(|size| *size > 0.0)
+            .map(|size| (size, self.expected_profit(edge_cents, size, yes_depth, no_depth, secs_to_expiry, unwind_cents)))
+            .filter(|(_, profit)| *profit > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+fn current() -> &'static RwLock<Option<Arc<CostModel>>> {
+    static MODEL: OnceLock<RwLock<Option<Arc<CostModel>>>> = OnceLock::new();
+    MODEL.get_or_init(|| RwLock::new(None))
+}
+
+/// The fitted model in use (None until the first fit or a saved model was loaded)
+pub fn model() -> Option<Arc<CostModel>> {
+    current().read().unwrap().clone()
+}
+
+fn install(model: CostModel) {
+    *current().write().unwrap() = Some(Arc::new(model));
+}
+
+fn writer() -> &'static OnceLock<mpsc::UnboundedSender<FillSample>> {
+    static WRITER: OnceLock<mpsc::UnboundedSender<FillSample>> = OnceLock::new();
+    &WRITER
+}
+
+/// Queue a leg's outcome for calibration (no-op unless `spawn` ran with the model on)
+pub fn record(sample: FillSample) {
+    if let Some(tx) = writer().get() {
+        let _ = tx.send(sample);
+    }
+}
+
+/// Samples in a file and its archived segments
+pub fn load_samples(path: &Path) -> Result<Vec<FillSample>> {
+    Ok(archive::read_all(path)?.lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// Refit from the sample file and save the model
+pub fn calibrate(config: &CostModelConfig) -> Result<CostModel> {
+    let model = CostModel::fit(&load_samples(&config.samples_file)?, config.min_samples);
+    storage::write_atomic(&config.model_file, serde_json::to_string_pretty(&model)?.as_bytes())?;
+    Ok(model)
+}
+
+/// Record fill samples and refit every COST_MODEL_REFIT_HOURS, starting from the last saved model
+pub fn spawn(config: CostModelConfig) {
+    if !config.enabled() {
+        return;
+    }
+    if let Some(saved) = std::fs::read_to_string(&config.model_file).ok()
+        .and_then(|s| serde_json::from_str::<CostModel>(&s).ok()) {
+        info!("[COST] Loaded cost model fitted {} from {} samples", saved.fitted_at, saved.samples);
+        install(saved);
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<FillSample>();
+    if writer().set(tx).is_err() {
+        return;
+    }
+    let path = config.samples_file.clone();
+    runtimes::spawn_bookkeeping(async move {
+        while let Some(sample) = rx.recv().await {
+            let line = serde_json::to_string(&sample).unwrap_or_default();
+            let appended = std::fs::OpenOptions::new().create(true).append(true).open(&path)
+                .and_then(|mut f| writeln!(f, "{}", line));
+            if let Err(e) = appended {
+                warn!("[COST] Cannot write {}: {}", path.display(), e);
+            }
+        }
+    });
+
+    runtimes::spawn_bookkeeping(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.refit_hours * 3600));
+        loop {
+            ticker.tick().await;
+            let cfg = config.clone();
+            match tokio::task::spawn_blocking(move || calibrate(&cfg)).await {
+                Ok(Ok(model)) => {
+                    if let Some(all) = model.buckets.get("all") {
+                        info!("[COST] 📐 Refit on {} fills: {:.0}% filled, {:+.2}¢ slippage overall",
+                              model.samples, all.fill_rate * 100.0, all.slippage_cents);
+                    }
+                    install(model);
+                }
+                Ok(Err(e)) => warn!("[COST] Calibration failed: {}", e),
+                Err(e) => warn!("[COST] Calibration task failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(size: f64, depth: f64, secs: i64, filled: f64, avg_price: f64) -> FillSample {
+        FillSample { ts: String::new(), venue: "polymarket".into(), ask: 0.50, depth, size,
+                     secs_to_expiry: Some(secs), filled, avg_price }
+    }
+
+    #[test]
+    fn test_fit_buckets() {
+        let mut samples: Vec<FillSample> = (0..10).map(|_| sample(10.0, 100.0, 7200, 10.0, 0.50)).collect();
+        // Taking the whole displayed depth fills half, one cent worse
+        samples.extend((0..10).map(|_| sample(100.0, 100.0, 7200, 50.0, 0.51)));
+        let model = CostModel::fit(&samples, 5);
+        assert_eq!(model.samples, 20);
+
+        let small = model.expect(10.0, 100.0, Some(7200)).unwrap();
+        assert_eq!((small.fill_rate, small.worst_fill_rate, small.slippage_cents), (1.0, 1.0, 0.0));
+        let large = model.expect(90.0, 100.0, Some(4000)).unwrap();
+        assert!((large.fill_rate - 0.5).abs() < 1e-9);
+        assert!((large.slippage_cents - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_falls_back_to_coarser_buckets() {
+        let samples: Vec<FillSample> = (0..6).map(|_| sample(100.0, 100.0, 7200, 60.0, 0.50)).collect();
+        let model = CostModel::fit(&samples, 5);
+        // Same size ratio near expiry: no samples there, the ratio bucket answers
+        assert!((model.expect(100.0, 100.0, Some(60)).unwrap().fill_rate - 0.6).abs() < 1e-9);
+        // Different ratio: the overall fit answers
+        assert!((model.expect(1.0, 100.0, None).unwrap().fill_rate - 0.6).abs() < 1e-9);
+        // Too few samples anywhere: no opinion
+        assert_eq!(CostModel::fit(&samples, 10).expect(100.0, 100.0, Some(7200)), None);
+    }
+
+    #[test]
+    fn test_best_size_uses_expected_not_displayed_profit() {
+        let mut samples: Vec<FillSample> = (0..10).map(|_| sample(20.0, 100.0, 7200, 20.0, 0.50)).collect();
+        // Sweeping the book: legs fill unevenly and 2¢ worse
+        samples.extend((0..5).map(|_| sample(100.0, 100.0, 7200, 30.0, 0.52)));
+        samples.extend((0..5).map(|_| sample(100.0, 100.0, 7200, 90.0, 0.52)));
+        let model = CostModel::fit(&samples, 5);
+
+        // 3¢ edge: the full size is expected to lose, a quarter of it keeps the edge
+        assert!(model.expected_profit(3.0, 100.0, 100.0, 100.0, Some(7200), 10.0) < 0.0);
+        assert_eq!(model.best_size(3.0, 100.0, 1.0, 100.0, 100.0, Some(7200), 10.0).map(|b| b.0), Some(25.0));
+        // No edge left after expected costs at any size
+        assert_eq!(model.best_size(0.0, 100.0, 1.0, 100.0, 100.0, Some(7200), 10.0), None);
+    }
+
+    #[test]
+    fn test_unmatched_risk_from_worst_leg_fill() {
+        // Both legs average 60% but any one fill can be 30%: 30 of 100 may be left unmatched
+        let samples: Vec<FillSample> = [30.0, 90.0, 30.0, 90.0, 60.0].iter()
+            .map(|f| sample(100.0, 100.0, 7200, *f, 0.50))
+            .collect();
+        let model = CostModel::fit(&samples, 5);
+        assert!((model.expect(100.0, 100.0, Some(7200)).unwrap().worst_fill_rate - 0.3).abs() < 1e-9);
+        // 60 matched × 5¢ - 30 unmatched × 10¢
+        let profit = model.expected_profit(5.0, 100.0, 100.0, 100.0, Some(7200), 10.0);
+        assert!((profit - 0.0).abs() < 1e-9, "{}", profit);
+    }
+}
@@ -0,0 +1,258 @@
+// src/ctf_reconcile.rs
+// On-chain position check - tracked Polymarket legs vs ERC-1155 CTF token balances on Polygon
+
+use anyhow::{anyhow, Result};
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
+use ethers::utils::id;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::events::{self, BotEvent};
+use crate::metrics;
+use crate::onchain::CTF_ADDRESS;
+use crate::position_tracker::{PositionTracker, SharedPositionTracker};
+use crate::rpc_pool::RpcPool;
+use crate::types::{GlobalState, MarketPair};
+
+/// Outcome tokens use USDC's 6 decimals
+const TOKEN_DECIMALS: f64 = 1e6;
+
+/// Token ids per balanceOfBatch call
+const BATCH_SIZE: usize = 100;
+
+/// CTF reconciliation configuration from environment
+#[derive(Debug, Clone)]
+pub struct CtfReconcileConfig {
+    /// Seconds between checks (0 = off)
+    pub interval_secs: u64,
+
+    /// Contracts of difference tolerated before a token is reported
+    pub tolerance: f64,
+}
+
+impl CtfReconcileConfig {
+    pub fn from_env() -> Self {
+        Self {
+            interval_secs: std::env::var("CTF_RECONCILE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+
+            tolerance: std::env::var("CTF_RECONCILE_TOLERANCE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.01),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.interval_secs > 0
+    }
+}
+
+/// One Polymarket outcome token we trade and what the tracker says we hold of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub market: String,
+    /// "yes" / "no"
+    pub side: &'static str,
+    pub token_id: String,
+    /// Open positions count their contracts; resolved ones should have been redeemed (0)
+    pub tracked: f64,
+}
+
+/// Both tokens of every active pair, with the tracker's Polymarket legs
+pub fn holdings(pairs: &[Arc<MarketPair>], tracker: &PositionTracker) -> Vec<Holding> {
+    let mut out = Vec::with_capacity(pairs.len() * 2);
+    for pair in pairs {
+        let (yes, no) = match tracker.get(&pair.pair_id) {
+            Some(p) if p.status == "open" => (p.poly_yes.contracts, p.poly_no.contracts),
+            _ => (0.0, 0.0),
+        };
+        for (side, token, tracked) in [("yes", &pair.poly_yes_token, yes), ("no", &pair.poly_no_token, no)] {
+            out.push(Holding { market: pair.pair_id.to_string(), side, token_id: token.to_string(), tracked });
+        }
+    }
+    out
+}
+
+/// A token whose on-chain balance isn't what the tracker explains
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub holding: Holding,
+    /// Tracked contracts
+    pub expected: f64,
+    pub onchain: f64,
+}
+
+/// Balances that differ from the tracked holdings by more than `tolerance`: redemptions that
+/// didn't happen, transfers in or out, fills the tracker missed, and tokens held from outside
+/// the tracker (earlier sessions, manual trades) - none of these are explained away
+pub fn compare(holdings: &[Holding], balances: &[f64], tolerance: f64) -> Vec<Divergence> {
+    holdings.iter().zip(balances)
+        .filter(|(h, &onchain)| (onchain - h.tracked).abs() > tolerance)
+        .map(|(h, &onchain)| Divergence { holding: h.clone(), expected: h.tracked, onchain })
+        .collect()
+}
+
+/// Pairs currently in the state (discovery may have added some since the last check)
+fn active_pairs(state: &GlobalState) -> Vec<Arc<MarketPair>> {
+    state.markets.iter()
+        .take(state.market_count())
+        .filter_map(|m| m.pair.clone())
+        .collect()
+}
+
+/// balanceOfBatch(address[] accounts, uint256[] ids) for one holder
+fn balance_of_batch(holder: Address, ids: &[U256]) -> Bytes {
+    let mut data = id("balanceOfBatch(address[],uint256[])").to_vec();
+    data.extend(encode(&[
+        Token::Array(ids.iter().map(|_| Token::Address(holder)).collect()),
+        Token::Array(ids.iter().map(|&i| Token::Uint(i)).collect()),
+    ]));
+    data.into()
+}
+
+/// balanceOfBatch's uint256[] as contracts
+fn decode_balances(data: &[u8]) -> Result<Vec<f64>> {
+    let tokens = decode(&[ParamType::Array(Box::new(ParamType::Uint(256)))], data)?;
+    let Some(Token::Array(values)) = tokens.into_iter().next() else {
+        return Err(anyhow!("unexpected balanceOfBatch result"));
+    };
+    values.into_iter()
+        .map(|t| t.into_uint().map(|u| u.low_u128() as f64 / TOKEN_DECIMALS).ok_or_else(|| anyhow!("non-uint balance")))
+        .collect()
+}
+
+/// On-chain balances of `token_ids` (decimal CTF position ids) held by `holder`, each batch
+/// cross-verified across the RPC providers
+pub async fn fetch_balances(rpc: &RpcPool, holder: &str, token_ids: &[String]) -> Result<Vec<f64>> {
+    let holder: Address = holder.parse().map_err(|_| anyhow!("not a valid address: {}", holder))?;
+    let ctf: Address = CTF_ADDRESS.parse()?;
+    let ids = token_ids.iter()
+        .map(|t| U256::from_dec_str(t).map_err(|_| anyhow!("not a CTF token id: {}", t)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut balances = Vec::with_capacity(ids.len());
+    for chunk in ids.chunks(BATCH_SIZE) {
+        let tx = TransactionRequest::new().to(ctf).data(balance_of_batch(holder, chunk));
+        let result = rpc.call_verified(&tx.into()).await?;
+        balances.extend(decode_balances(&result)?);
+    }
+    Ok(balances)
+}
+
+/// Check every active pair's tokens held by `holder` (the Polymarket funder wallet) against
+/// the tracker every CTF_RECONCILE_SECS. Divergences are logged and published once per change.
+pub fn spawn(config: CtfReconcileConfig, rpc: Arc<RpcPool>, holder: String, state: Arc<GlobalState>, tracker: SharedPositionTracker) {
+    if !config.enabled() {
+        return;
+    }
+    rpc.spawn_health();
+    info!("[CTF] Reconciling {} outcome tokens on-chain every {}s", active_pairs(&state).len() * 2, config.interval_secs);
+
+    tokio::spawn(async move {
+        let mut reported: HashMap<String, f64> = HashMap::new();
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        loop {
+            ticker.tick().await;
+            let holdings = holdings(&active_pairs(&state), &*tracker.read().await);
+            let token_ids: Vec<String> = holdings.iter().map(|h| h.token_id.clone()).collect();
+            let balances = match fetch_balances(&rpc, &holder, &token_ids).await {
+                Ok(b) => b,
+                Err(e) => {
+                    metrics::incr("ctf.errors");
+                    warn!("[CTF] Balance query failed: {}", e);
+                    continue;
+                }
+            };
+
+            let divergences = compare(&holdings, &balances, config.tolerance);
+            metrics::set_gauge("ctf.divergences", divergences.len() as i64);
+            for d in &divergences {
+                if reported.get(&d.holding.token_id) == Some(&d.onchain) {
+                    continue;
+                }
+                warn!("[CTF] ⚠️ {} {}: {:.2} on-chain, {:.2} tracked",
+                      d.holding.market, d.holding.side, d.onchain, d.expected);
+                events::publish(BotEvent::PositionDivergence {
+                    market: d.holding.market.clone(),
+                    platform: "polymarket".into(),
+                    side: d.holding.side.into(),
+                    expected: d.expected,
+                    actual: d.onchain,
+                });
+            }
+            reported = divergences.into_iter().map(|d| (d.holding.token_id, d.onchain)).collect();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(token: &str, tracked: f64) -> Holding {
+        Holding { market: "pair".into(), side: "yes", token_id: token.into(), tracked }
+    }
+
+    fn pair() -> MarketPair {
+        MarketPair {
+            pair_id: "p1".into(), league: "epl".into(), market_type: crate::types::MarketType::Moneyline,
+            description: "A vs B".into(), kalshi_event_ticker: "E".into(), kalshi_market_ticker: "K".into(),
+            poly_slug: "s".into(), poly_yes_token: "11".into(), poly_no_token: "22".into(),
+            line_value: None, team_suffix: None, settles_at: None, poly_ends_at: None, basis_risk: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_every_unexplained_balance_diverges() {
+        // Tracked fills are explained
+        assert!(compare(&[holding("a", 20.0), holding("b", 10.0)], &[20.0, 10.005], 0.01).is_empty());
+        // 5 contracts the tracker doesn't know of, from the first check on
+        let d = compare(&[holding("a", 0.0), holding("b", 10.0)], &[5.0, 10.0], 0.01);
+        assert_eq!((d.len(), d[0].expected, d[0].onchain), (1, 0.0, 5.0));
+        // Tokens moved out without the tracker knowing
+        let d = compare(&[holding("a", 20.0), holding("b", 10.0)], &[20.0, 4.0], 0.01);
+        assert_eq!(d.len(), 1);
+        assert_eq!((d[0].holding.token_id.as_str(), d[0].expected, d[0].onchain), ("b", 10.0, 4.0));
+    }
+
+    #[test]
+    fn test_pairs_reread_from_state() {
+        let mut state = GlobalState::new();
+        assert!(active_pairs(&state).is_empty());
+        state.add_pair(pair());
+        assert_eq!(active_pairs(&state).len(), 1);
+    }
+
+    #[test]
+    fn test_resolved_position_expects_redemption() {
+        let mut tracker = PositionTracker::new();
+        let pair = Arc::new(pair());
+        tracker.get_or_create("p1", "A vs B").poly_yes.add(10.0, 0.4);
+        let open = holdings(std::slice::from_ref(&pair), &tracker);
+        assert_eq!((open[0].tracked, open[1].tracked), (10.0, 0.0));
+
+        assert!(compare(&open, &[10.0, 0.0], 0.01).is_empty());
+        // Resolved but the tokens are still in the wallet: redemption failed
+        tracker.resolve_position("p1", true);
+        let resolved = holdings(std::slice::from_ref(&pair), &tracker);
+        let d = compare(&resolved, &[10.0, 0.0], 0.01);
+        assert_eq!((d.len(), d[0].expected), (1, 0.0));
+    }
+
+    #[test]
+    fn test_balance_of_batch_encoding() {
+        let holder: Address = "0x00000000000000000000000000000000000000aa".parse().unwrap();
+        let data = balance_of_batch(holder, &[U256::from(1), U256::from(2)]);
+        assert_eq!(&data[..4], &id("balanceOfBatch(address[],uint256[])"));
+
+        let result = encode(&[Token::Array(vec![Token::Uint(U256::from(12_500_000u64)), Token::Uint(U256::zero())])]);
+        assert_eq!(decode_balances(&result).unwrap(), vec![12.5, 0.0]);
+        assert!(decode_balances(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+// src/depth_guard.rs
+// Spoofed-depth guard - tracks how long each ask level's size has rested and only counts
+// size that has survived a minimum time toward trade size, so flashed asks don't size IOCs
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::metrics;
+
+/// Tokens not seen in a snapshot for this long are forgotten (their interval has ended)
+const TOKEN_TTL: Duration = Duration::from_secs(600);
+
+/// Spoofed-depth guard configuration from environment
+#[derive(Debug, Clone)]
+pub struct DepthGuardConfig {
+    /// Milliseconds size must rest at a level before it counts toward trade size (0 = off)
+    pub min_persist_ms: u64,
+}
+
+impl DepthGuardConfig {
+    pub fn from_env() -> Self {
+        Self {
+            min_persist_ms: std::env::var("DEPTH_MIN_PERSIST_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Size at one level, in the order it was added: growth is a new tranche, shrinkage
+/// takes from the newest first (a flashed ask on top of resting size is pulled first)
+#[derive(Debug, Default)]
+struct Level {
+    tranches: Vec<(Instant, f64)>,
+}
+
+impl Level {
+    fn size(&self) -> f64 {
+        self.tranches.iter().map(|(_, s)| s).sum()
+    }
+
+    /// Move to `size`; returns the size that vanished before resting `min_age`
+    fn update(&mut self, size: f64, now: Instant, min_age: Duration) -> f64 {
+        let current = self.size();
+        if size > current {
+            self.tranches.push((now, size - current));
+            return 0.0;
+        }
+        let (mut remove, mut flashed) = (current - size, 0.0);
+        while remove > 1e-9 {
+            let Some((since, tranche)) = self.tranches.last_mut() else { break };
+            let taken = tranche.min(remove);
+            if now.duration_since(*since) < min_age {
+                flashed += taken;
+            }
+            *tranche -= taken;
+            remove -= taken;
+            if *tranche <= 1e-9 {
+                self.tranches.pop();
+            }
+        }
+        flashed
+    }
+
+    fn persisted(&self, now: Instant, min_age: Duration) -> f64 {
+        self.tranches.iter()
+            .filter(|(since, _)| now.duration_since(*since) >= min_age)
+            .map(|(_, s)| s)
+            .sum()
+    }
+}
+
+#[derive(Debug)]
+struct TokenLevels {
+    asks: HashMap<u32, Level>,
+    seen: Instant,
+}
+
+/// Per-level persistence of every token's asks
+pub struct DepthGuard {
+    min_age: Duration,
+    tokens: Mutex<HashMap<String, TokenLevels>>,
+}
+
+fn price_key(price: f64) -> u32 {
+    (price * 1000.0).round() as u32
+}
+
+impl DepthGuard {
+    pub fn new(config: DepthGuardConfig) -> Self {
+        Self { min_age: Duration::from_millis(config.min_persist_ms), tokens: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.min_age.is_zero()
+    }
+
+    pub fn min_persist(&self) -> Duration {
+        self.min_age
+    }
+
+    /// Record a full ask snapshot of `token` as (price, size); levels missing from it are gone
+    pub fn observe(&self, token: &str, asks: &[(f64, f64)], now: Instant) {
+        if !self.enabled() {
+            return;
+        }
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.retain(|t, levels| t == token || now.duration_since(levels.seen) < TOKEN_TTL);
+        let levels = tokens.entry(token.to_string())
+            .or_insert_with(|| TokenLevels { asks: HashMap::new(), seen: now });
+        levels.seen = now;
+
+        let mut flashed = 0.0;
+        let current: HashMap<u32, f64> = asks.iter().map(|(p, s)| (price_key(*p), *s)).collect();
+        levels.asks.retain(|key, level| {
+            if current.contains_key(key) {
+                return true;
+            }
+            flashed += level.update(0.0, now, self.min_age);
+            false
+        });
+        for (key, size) in current {
+            flashed += levels.asks.entry(key).or_default().update(size, now, self.min_age);
+        }
+        if flashed > 0.0 {
+            metrics::incr("depth_guard.flashed_levels");
+            metrics::add("depth_guard.flashed_contracts", flashed.round() as u64);
+        }
+    }
+
+    /// Size at `price` that has rested at least the minimum time (`raw` when the guard is off)
+    pub fn persisted(&self, token: &str, price: f64, raw: f64, now: Instant) -> f64 {
+        if !self.enabled() {
+            return raw;
+        }
+        self.tokens.lock().unwrap()
+            .get(token)
+            .and_then(|t| t.asks.get(&price_key(price)))
+            .map(|level| level.persisted(now, self.min_age).min(raw))
+            .unwrap_or(0.0)
+    }
+}
+
+/// Process-wide guard (DEPTH_MIN_PERSIST_MS)
+pub fn guard() -> &'static DepthGuard {
+    static GUARD: OnceLock<DepthGuard> = OnceLock::new();
+    GUARD.get_or_init(|| DepthGuard::new(DepthGuardConfig::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(ms: u64) -> DepthGuard {
+        DepthGuard::new(DepthGuardConfig { min_persist_ms: ms })
+    }
+
+    #[test]
+    fn test_new_size_counts_once_it_rests() {
+        let g = guard(500);
+        let t0 = Instant::now();
+        g.observe("t", &[(0.45, 100.0)], t0);
+        assert_eq!(g.persisted("t", 0.45, 100.0, t0 + Duration::from_millis(100)), 0.0);
+        assert_eq!(g.persisted("t", 0.45, 100.0, t0 + Duration::from_millis(600)), 100.0);
+        assert_eq!(g.persisted("t", 0.46, 10.0, t0 + Duration::from_millis(600)), 0.0, "unknown level");
+    }
+
+    #[test]
+    fn test_flash_on_top_of_resting_size() {
+        let g = guard(500);
+        let t0 = Instant::now();
+        g.observe("t", &[(0.45, 50.0)], t0);
+        // 5000 flashed on top: only the resting 50 counts
+        let t1 = t0 + Duration::from_secs(1);
+        g.observe("t", &[(0.45, 5050.0)], t1);
+        assert_eq!(g.persisted("t", 0.45, 5050.0, t1 + Duration::from_millis(100)), 50.0);
+        // Pulled down to 2000: the newest size goes first, the resting 50 stays mature
+        g.observe("t", &[(0.45, 2000.0)], t1 + Duration::from_millis(200));
+        assert_eq!(g.persisted("t", 0.45, 2000.0, t1 + Duration::from_millis(300)), 50.0);
+        assert_eq!(g.persisted("t", 0.45, 2000.0, t1 + Duration::from_millis(600)), 2000.0);
+        // Level gone and back: its clock restarts
+        g.observe("t", &[(0.46, 10.0)], t1 + Duration::from_secs(2));
+        g.observe("t", &[(0.45, 2000.0)], t1 + Duration::from_secs(3));
+        assert_eq!(g.persisted("t", 0.45, 2000.0, t1 + Duration::from_secs(3)), 0.0);
+    }
+
+    #[test]
+    fn test_disabled_passes_raw_size() {
+        let g = guard(0);
+        let t0 = Instant::now();
+        g.observe("t", &[(0.45, 100.0)], t0);
+        assert!(!g.enabled());
+        assert_eq!(g.persisted("t", 0.45, 80.0, t0), 80.0);
+        // Persisted size is capped by what the book shows now
+        let g = guard(10);
+        g.observe("t", &[(0.45, 100.0)], t0);
+        assert_eq!(g.persisted("t", 0.45, 80.0, t0 + Duration::from_millis(20)), 80.0);
+    }
+}
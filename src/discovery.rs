@@ -11,10 +11,13 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Semaphore;
 use tracing::{info, warn};
 
+use crate::basis_risk::{self, BasisRisk, BasisRiskConfig};
 use crate::cache::TeamCache;
 use crate::config::{LeagueConfig, get_league_configs, get_league_config};
 use crate::kalshi::KalshiApiClient;
+use crate::overrides::Overrides;
 use crate::polymarket::GammaClient;
+use crate::synthetic::SyntheticPair;
 use crate::types::{MarketPair, MarketType, DiscoveryResult, KalshiMarket, KalshiEvent};
 
 /// Max concurrent Gamma API requests
@@ -98,6 +101,8 @@ pub struct DiscoveryClient {
     kalshi_limiter: Arc<KalshiRateLimiter>,
     kalshi_semaphore: Arc<Semaphore>,  // Global concurrency limit for Kalshi
     gamma_semaphore: Arc<Semaphore>,
+    /// Settlement-mismatch scoring; pairs above BASIS_MAX_SCORE are not matched
+    basis: Arc<BasisRiskConfig>,
 }
 
 impl DiscoveryClient {
@@ -113,6 +118,7 @@ impl DiscoveryClient {
             kalshi_limiter,
             kalshi_semaphore: Arc::new(Semaphore::new(KALSHI_GLOBAL_CONCURRENCY)),
             gamma_semaphore: Arc::new(Semaphore::new(GAMMA_CONCURRENCY)),
+            basis: Arc::new(BasisRiskConfig::from_env()),
         }
     }
 
@@ -426,23 +432,42 @@ impl DiscoveryClient {
             .map(|task| {
                 let gamma = self.gamma.clone();
                 let semaphore = self.gamma_semaphore.clone();
+                let basis = self.basis.clone();
                 async move {
                     let _permit = semaphore.acquire().await.ok()?;
                     match gamma.lookup_market(&task.poly_slug).await {
-                        Ok(Some((yes_token, no_token))) => {
+                        Ok(Some(listing)) => {
                             let team_suffix = extract_team_suffix(&task.market.ticker);
+                            let settles_at = task.market.settles_at();
+                            let description = format!("{} - {}", task.event.title, task.market.title);
+
+                            // Both legs must settle on the same thing at (nearly) the same time
+                            let kalshi_sources: Vec<String> = task.event.settlement_sources.iter()
+                                .filter_map(|s| s.url.clone())
+                                .collect();
+                            let risk = BasisRisk::assess(
+                                &basis, &task.league, settles_at, listing.ends_at,
+                                &kalshi_sources, listing.resolution_source.as_deref(),
+                            );
+                            if basis.refuses(risk.score) {
+                                basis_risk::log_refused(&description, &risk, &basis);
+                                return None;
+                            }
                             Some(MarketPair {
                                 pair_id: format!("{}-{}", task.poly_slug, task.market.ticker).into(),
                                 league: task.league.into(),
                                 market_type: task.market_type,
-                                description: format!("{} - {}", task.event.title, task.market.title).into(),
+                                description: description.into(),
                                 kalshi_event_ticker: task.event.event_ticker.clone().into(),
                                 kalshi_market_ticker: task.market.ticker.into(),
                                 poly_slug: task.poly_slug.into(),
-                                poly_yes_token: yes_token.into(),
-                                poly_no_token: no_token.into(),
+                                poly_yes_token: listing.yes_token.into(),
+                                poly_no_token: listing.no_token.into(),
                                 line_value: task.market.floor_strike,
                                 team_suffix: team_suffix.map(|s| s.into()),
+                                settles_at,
+                                poly_ends_at: listing.ends_at,
+                                basis_risk: risk.score,
                             })
                         }
                         Ok(None) => None,
@@ -461,6 +486,29 @@ impl DiscoveryClient {
         Ok(pairs)
     }
     
+    /// Markets of one Kalshi event, under the discovery rate limits
+    pub async fn kalshi_event_markets(&self, event_ticker: &str) -> Result<Vec<KalshiMarket>> {
+        {
+            let _permit = self.kalshi_semaphore.acquire().await.map_err(|e| anyhow::anyhow!("semaphore closed: {}", e))?;
+            self.kalshi_limiter.until_ready().await;
+        }
+        self.kalshi.get_markets(event_ticker).await
+    }
+
+    /// Match a Polymarket "above `strike`" market to the Kalshi range brackets of `event_ticker`,
+    /// composed into synthetics of both sides (None if the slug isn't listed)
+    pub async fn discover_synthetic(&self, event_ticker: &str, poly_slug: &str, strike: f64) -> Result<Option<SyntheticPair>> {
+        let markets = self.kalshi_event_markets(event_ticker).await?;
+        let Some(listing) = self.gamma.lookup_market(poly_slug).await? else {
+            return Ok(None);
+        };
+        let description = format!("{} vs {} @ {}", poly_slug, event_ticker, strike);
+        let pair = SyntheticPair::build(&description, event_ticker, &markets, strike, &listing.yes_token, &listing.no_token)?;
+        info!("  🧩 Synthetic {}: {} legs above, {} below (asks {}¢ / {}¢)",
+              description, pair.above.legs.len(), pair.below.legs.len(), pair.above.ask_cents(), pair.below.ask_cents());
+        Ok(Some(pair))
+    }
+
     /// Build Polymarket slug from Kalshi event data
     fn build_poly_slug(
         &self,
@@ -643,6 +691,22 @@ fn kalshi_date_to_iso(kalshi_date: &str) -> String {
     format!("{}-{}-{}", year, month, day)
 }
 
+/// Pin the Polymarket outcome tokens of discovered pairs (OVERRIDES_FILE, by slug): a pair
+/// whose pinned YES token was matched as NO has its Polymarket tokens swapped
+pub fn apply_overrides(overrides: &Overrides, pairs: &mut [MarketPair]) {
+    for pair in pairs.iter_mut() {
+        let Some(pin) = overrides.pinned_yes(&pair.poly_slug) else {
+            continue;
+        };
+        if pin == &*pair.poly_no_token {
+            warn!("[OVERRIDE] 🔀 {}: pinned YES token was matched as NO - swapping Polymarket outcomes", pair.poly_slug);
+            std::mem::swap(&mut pair.poly_yes_token, &mut pair.poly_no_token);
+        } else if pin != &*pair.poly_yes_token {
+            warn!("[OVERRIDE] ⚠️ {}: pinned YES token {} is not one of the market's tokens - ignored", pair.poly_slug, pin);
+        }
+    }
+}
+
 /// Extract team suffix from market ticker (e.g., "KXEPLGAME-25DEC27CFCAVL-CFC" -> "CFC")
 fn extract_team_suffix(ticker: &str) -> Option<String> {
     let mut splits = ticker.splitn(3, '-');
@@ -668,4 +732,26 @@ mod tests {
         assert_eq!(kalshi_date_to_iso("25DEC27"), "2025-12-27");
         assert_eq!(kalshi_date_to_iso("25JAN01"), "2025-01-01");
     }
+
+    #[test]
+    fn test_pinned_yes_token_swaps_pair() {
+        let pair = |slug: &str| MarketPair {
+            pair_id: slug.into(), league: "epl".into(), market_type: MarketType::Moneyline,
+            description: "A vs B".into(), kalshi_event_ticker: "E".into(), kalshi_market_ticker: "K".into(),
+            poly_slug: slug.into(), poly_yes_token: "11".into(), poly_no_token: "22".into(),
+            line_value: None, team_suffix: None, settles_at: None, poly_ends_at: None, basis_risk: 0.0,
+        };
+        let overrides = Overrides::parse(r#"
+            [markets.swapped]
+            yes_token = "22"
+            [markets.unknown]
+            yes_token = "33"
+        "#).unwrap();
+        let mut pairs = vec![pair("swapped"), pair("unknown"), pair("plain")];
+        apply_overrides(&overrides, &mut pairs);
+        assert_eq!((&*pairs[0].poly_yes_token, &*pairs[0].poly_no_token), ("22", "11"));
+        for p in &pairs[1..] {
+            assert_eq!((&*p.poly_yes_token, &*p.poly_no_token), ("11", "22"));
+        }
+    }
 }
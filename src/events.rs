@@ -0,0 +1,221 @@
+// src/events.rs
+// Internal event bus - execution publishes, consumers subscribe independently
+
+use serde::Serialize;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::metrics;
+use crate::runtimes;
+
+/// Events buffered per subscriber before a slow consumer starts lagging
+const BUS_CAPACITY: usize = 1024;
+
+/// Something that happened inside the bot
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BotEvent {
+    /// Arb passed pre-trade checks and is about to be executed
+    ArbDetected { market: String, arb_type: String, yes_price: f64, no_price: f64, contracts: f64 },
+    /// Order sent to a venue
+    OrderSubmitted { market: String, platform: String, side: String, price: f64, contracts: f64 },
+    /// Order (partially) filled
+    Filled { market: String, platform: String, side: String, contracts: f64, cost: f64, order_id: String },
+    /// Unmatched excess was closed out
+    Hedged { market: String, platform: String, side: String, contracts: f64, proceeds: f64 },
+    /// A risk control halted trading
+    RiskTripped { reason: String },
+    /// A WebSocket feed dropped and is reconnecting
+    WsReconnected { feed: String, error: String },
+    /// A feed's messages stopped parsing / lost their fields - the API schema probably changed
+    SchemaDrift { feed: String, message_type: String, parse_rate: f64, field_rate: f64 },
+    /// A venue refused an order (reason is an `order_errors::RejectReason`)
+    OrderRejected { platform: String, instrument: String, reason: String, message: String },
+    /// Venue cash drifted off its target split; `amount` dollars should move `from` -> `to`
+    RebalanceSuggested { from: String, to: String, amount: f64 },
+    /// A process resource (RSS, open FDs, tasks) keeps growing - probably a leak
+    ResourceTrend { resource: String, from: u64, to: u64 },
+    /// A venue-side holding doesn't match what the position tracker expects (contracts)
+    #[cfg_attr(not(feature = "onchain"), allow(dead_code))]
+    PositionDivergence { market: String, platform: String, side: String, expected: f64, actual: f64 },
+    /// An asset's opportunities/hour left its historical band (cause: market_quiet | feed_broken | surge)
+    OppRateAnomaly { asset: String, rate: f64, low: f64, high: f64, cause: String },
+    /// The local clock is further than `limit_ms` off a venue's (signed requests may be refused)
+    ClockSkew { venue: String, skew_ms: i64, limit_ms: i64 },
+    /// An operator halted, paused, resumed or re-armed trading through the control API
+    ControlAction { caller: String, action: String, outcome: String },
+}
+
+impl BotEvent {
+    /// Every `kind()` (kept in step with the enum by `test_kinds_cover_every_variant`)
+    pub const KINDS: &'static [&'static str] = &[
+        "arb_detected", "order_submitted", "filled", "hedged", "risk_tripped", "ws_reconnected",
+        "schema_drift", "order_rejected", "rebalance_suggested", "resource_trend", "position_divergence",
+        "opp_rate_anomaly", "clock_skew", "control_action",
+    ];
+
+    /// Short snake_case name (metric suffix / log key)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BotEvent::ArbDetected { .. } => "arb_detected",
+            BotEvent::OrderSubmitted { .. } => "order_submitted",
+            BotEvent::Filled { .. } => "filled",
+            BotEvent::Hedged { .. } => "hedged",
+            BotEvent::RiskTripped { .. } => "risk_tripped",
+            BotEvent::WsReconnected { .. } => "ws_reconnected",
+            BotEvent::SchemaDrift { .. } => "schema_drift",
+            BotEvent::OrderRejected { .. } => "order_rejected",
+            BotEvent::RebalanceSuggested { .. } => "rebalance_suggested",
+            BotEvent::ResourceTrend { .. } => "resource_trend",
+            BotEvent::PositionDivergence { .. } => "position_divergence",
+            BotEvent::OppRateAnomaly { .. } => "opp_rate_anomaly",
+            BotEvent::ClockSkew { .. } => "clock_skew",
+            BotEvent::ControlAction { .. } => "control_action",
+        }
+    }
+}
+
+fn bus() -> &'static broadcast::Sender<BotEvent> {
+    static BUS: OnceLock<broadcast::Sender<BotEvent>> = OnceLock::new();
+    BUS.get_or_init(|| broadcast::channel(BUS_CAPACITY).0)
+}
+
+/// Publish an event (never blocks; dropped if nobody is subscribed)
+#[inline]
+pub fn publish(event: BotEvent) {
+    let _ = bus().send(event);
+}
+
+/// Subscribe to all events published from now on
+pub fn subscribe() -> broadcast::Receiver<BotEvent> {
+    bus().subscribe()
+}
+
+/// Run a consumer over the bus until the process exits, skipping over lag
+pub async fn consume<F>(name: &'static str, mut rx: broadcast::Receiver<BotEvent>, mut handler: F)
+where
+    F: FnMut(BotEvent),
+{
+    loop {
+        match rx.recv().await {
+            Ok(event) => handler(event),
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                warn!("[EVENTS] {} consumer lagged, dropped {} events", name, n);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Count every event as `events.<kind>`
+pub fn spawn_metrics_consumer() {
+    let rx = subscribe();
+    runtimes::spawn_bookkeeping(consume("metrics", rx, |event| {
+        metrics::incr(&format!("events.{}", event.kind()));
+    }));
+}
+
+/// Append every event as a JSON line to EVENT_LOG_FILE (audit trail), if set
+pub fn spawn_audit_consumer() {
+    let Ok(path) = std::env::var("EVENT_LOG_FILE") else {
+        return;
+    };
+    let file = match std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            warn!("[EVENTS] Cannot open audit log {}: {}", path, e);
+            return;
+        }
+    };
+    info!("[EVENTS] Audit log: {}", path);
+
+    let rx = subscribe();
+    runtimes::spawn_bookkeeping(async move {
+        use std::io::Write;
+        let mut out = std::io::LineWriter::new(file);
+        consume("audit", rx, |event| {
+            let line = serde_json::json!({
+                "ts": chrono::Utc::now().to_rfc3339(),
+                "data": event,
+            });
+            if let Err(e) = writeln!(out, "{}", line) {
+                warn!("[EVENTS] Audit write failed: {}", e);
+            }
+        }).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_reaches_every_subscriber() {
+        let mut a = subscribe();
+        let mut b = subscribe();
+
+        let event = BotEvent::RiskTripped { reason: "bus test".into() };
+        publish(event.clone());
+
+        // Other tests share the global bus - skip anything they published
+        for rx in [&mut a, &mut b] {
+            loop {
+                if rx.recv().await.unwrap() == event {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_serializes_with_tag() {
+        let ev = BotEvent::WsReconnected { feed: "kalshi".into(), error: "eof".into() };
+        let json = serde_json::to_value(&ev).unwrap();
+        assert_eq!(json["event"], "ws_reconnected");
+        assert_eq!(json["feed"], "kalshi");
+    }
+
+    /// One of each variant. The match has no wildcard, so a new variant fails to compile
+    /// here until it is added to the list (and `test_kinds_cover_every_variant` checks KINDS)
+    fn one_of_each() -> Vec<BotEvent> {
+        let s = String::new;
+        let events = vec![
+            BotEvent::ArbDetected { market: s(), arb_type: s(), yes_price: 0.0, no_price: 0.0, contracts: 0.0 },
+            BotEvent::OrderSubmitted { market: s(), platform: s(), side: s(), price: 0.0, contracts: 0.0 },
+            BotEvent::Filled { market: s(), platform: s(), side: s(), contracts: 0.0, cost: 0.0, order_id: s() },
+            BotEvent::Hedged { market: s(), platform: s(), side: s(), contracts: 0.0, proceeds: 0.0 },
+            BotEvent::RiskTripped { reason: s() },
+            BotEvent::WsReconnected { feed: s(), error: s() },
+            BotEvent::SchemaDrift { feed: s(), message_type: s(), parse_rate: 0.0, field_rate: 0.0 },
+            BotEvent::OrderRejected { platform: s(), instrument: s(), reason: s(), message: s() },
+            BotEvent::RebalanceSuggested { from: s(), to: s(), amount: 0.0 },
+            BotEvent::ResourceTrend { resource: s(), from: 0, to: 0 },
+            BotEvent::PositionDivergence { market: s(), platform: s(), side: s(), expected: 0.0, actual: 0.0 },
+            BotEvent::OppRateAnomaly { asset: s(), rate: 0.0, low: 0.0, high: 0.0, cause: s() },
+            BotEvent::ClockSkew { venue: s(), skew_ms: 0, limit_ms: 0 },
+            BotEvent::ControlAction { caller: s(), action: s(), outcome: s() },
+        ];
+        for event in &events {
+            match event {
+                BotEvent::ArbDetected { .. } | BotEvent::OrderSubmitted { .. } | BotEvent::Filled { .. }
+                | BotEvent::Hedged { .. } | BotEvent::RiskTripped { .. } | BotEvent::WsReconnected { .. }
+                | BotEvent::SchemaDrift { .. } | BotEvent::OrderRejected { .. } | BotEvent::RebalanceSuggested { .. }
+                | BotEvent::ResourceTrend { .. } | BotEvent::PositionDivergence { .. } | BotEvent::OppRateAnomaly { .. }
+                | BotEvent::ClockSkew { .. } | BotEvent::ControlAction { .. } => {}
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_kinds_cover_every_variant() {
+        let events = one_of_each();
+        let kinds: Vec<&str> = events.iter().map(|e| e.kind()).collect();
+        assert_eq!(kinds, BotEvent::KINDS);
+        // kind() is also the serialized tag
+        for event in &events {
+            assert_eq!(serde_json::to_value(event).unwrap()["event"], event.kind());
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// src/exec_mode.rs
+// Execution mode - live, dry run, simulated or shadow, resolved once per process for every strategy and executor
+
+use anyhow::{bail, Result};
+use std::sync::OnceLock;
+use tracing::{error, info, warn};
+
+use crate::metrics;
+
+/// What executors do with an opportunity they would trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecMode {
+    /// Orders are sent
+    Live,
+    /// Logged only
+    DryRun,
+    /// Paper trading: filled in full at the limit price and recorded as positions
+    Simulated,
+    /// Dry run plus a record of what live orders would have achieved (SHADOW_FILE)
+    Shadow,
+}
+
+impl ExecMode {
+    pub const ALL: [ExecMode; 4] = [Self::Live, Self::DryRun, Self::Simulated, Self::Shadow];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "live" => Some(Self::Live),
+            "dry_run" | "dryrun" | "dry" => Some(Self::DryRun),
+            "simulated" | "sim" | "paper" => Some(Self::Simulated),
+            "shadow" => Some(Self::Shadow),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::DryRun => "dry_run",
+            Self::Simulated => "simulated",
+            Self::Shadow => "shadow",
+        }
+    }
+
+    /// Orders reach a venue
+    pub fn is_live(&self) -> bool {
+        *self == Self::Live
+    }
+
+    pub fn records_shadow(&self) -> bool {
+        *self == Self::Shadow
+    }
+
+    /// `exec.mode` gauge value (0 = live)
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::Live => 0,
+            Self::DryRun => 1,
+            Self::Simulated => 2,
+            Self::Shadow => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for ExecMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn flag(value: Option<&str>) -> Option<bool> {
+    match value.map(|v| v.trim().to_lowercase()).as_deref() {
+        Some("1" | "true") => Some(true),
+        Some("0" | "false") => Some(false),
+        _ => None,
+    }
+}
+
+/// Mode from EXEC_MODE, or from the older DRY_RUN / SHADOW_MODE pair when it's unset.
+/// Anything but an explicit choice of live is a non-live mode, and EXEC_MODE contradicting
+/// an explicitly set DRY_RUN is an error rather than a guess.
+pub fn resolve(exec_mode: Option<&str>, dry_run: Option<&str>, shadow_mode: Option<&str>) -> Result<ExecMode> {
+    let dry_run = flag(dry_run);
+    let Some(value) = exec_mode.filter(|v| !v.trim().is_empty()) else {
+        return Ok(match (dry_run, flag(shadow_mode)) {
+            (Some(false), _) => ExecMode::Live,
+            (_, Some(true)) => ExecMode::Shadow,
+            _ => ExecMode::DryRun,
+        });
+    };
+    let Some(mode) = ExecMode::parse(value) else {
+        bail!("EXEC_MODE={:?}: expected one of {}", value, ExecMode::ALL.map(|m| m.as_str()).join(", "));
+    };
+    match (mode.is_live(), dry_run) {
+        (true, Some(true)) => bail!("EXEC_MODE=live contradicts DRY_RUN=1 - unset one of them"),
+        (false, Some(false)) => bail!("EXEC_MODE={} contradicts DRY_RUN=0 - unset one of them", mode),
+        _ => Ok(mode),
+    }
+}
+
+pub fn from_env() -> Result<ExecMode> {
+    let var = |name| std::env::var(name).ok();
+    resolve(var("EXEC_MODE").as_deref(), var("DRY_RUN").as_deref(), var("SHADOW_MODE").as_deref())
+}
+
+fn global() -> &'static OnceLock<ExecMode> {
+    static MODE: OnceLock<ExecMode> = OnceLock::new();
+    &MODE
+}
+
+/// Resolve the process's mode, log it and publish it as gauges (`exec.mode`, `exec.live`)
+pub fn start() -> Result<ExecMode> {
+    let resolved = from_env()?;
+    let mode = *global().get_or_init(|| resolved);
+    if mode != resolved {
+        warn!("[MODE] Execution mode already started as {} - ignoring {}", mode, resolved);
+    }
+    match mode {
+        ExecMode::Live => warn!("   Mode: LIVE EXECUTION"),
+        ExecMode::DryRun => info!("   Mode: DRY RUN (set EXEC_MODE=live to execute)"),
+        ExecMode::Simulated => info!("   Mode: SIMULATED (paper fills at limit prices, recorded as positions)"),
+        ExecMode::Shadow => info!("   Mode: SHADOW (dry run, recording what live orders would have achieved)"),
+    }
+    metrics::set_gauge("exec.mode", mode.code());
+    metrics::set_gauge("exec.live", mode.is_live() as i64);
+    Ok(mode)
+}
+
+/// Name of shared trading state (a lease, a bot's rows in a shared database) for the mode in
+/// the environment: simulated runs get their own, so paper trading never touches live state
+pub fn state_name(name: &str) -> String {
+    match from_env() {
+        Ok(ExecMode::Simulated) => format!("{}-simulated", name),
+        _ => name.to_string(),
+    }
+}
+
+/// Order id for a simulated fill (never sent anywhere)
+pub fn simulated_order_id() -> String {
+    format!("sim-{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+}
+
+/// Mode an executor may run in: live only if the process was started live through `start`
+/// (an executor handed `Live` by a binary that skipped it runs dry instead)
+pub fn confirm(requested: ExecMode) -> ExecMode {
+    if requested.is_live() && !current().is_live() {
+        error!("[MODE] Live execution requested but the process mode is {} - running dry", current());
+        return ExecMode::DryRun;
+    }
+    requested
+}
+
+/// The process's mode; dry run until `start` resolved it, so an executor in a binary that
+/// never calls `start` can't trade live by accident
+pub fn current() -> ExecMode {
+    global().get().copied().unwrap_or(ExecMode::DryRun)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_flags() {
+        assert_eq!(resolve(None, None, None).unwrap(), ExecMode::DryRun);
+        assert_eq!(resolve(None, Some("0"), None).unwrap(), ExecMode::Live);
+        assert_eq!(resolve(None, Some("false"), Some("1")).unwrap(), ExecMode::Live);
+        assert_eq!(resolve(None, None, Some("true")).unwrap(), ExecMode::Shadow);
+        // Anything unrecognized stays dry
+        assert_eq!(resolve(None, Some("no"), None).unwrap(), ExecMode::DryRun);
+        assert_eq!(resolve(Some(" "), Some("1"), None).unwrap(), ExecMode::DryRun);
+    }
+
+    #[test]
+    fn test_exec_mode_wins_but_contradictions_fail() {
+        for mode in ExecMode::ALL {
+            assert_eq!(resolve(Some(mode.as_str()), None, None).unwrap(), mode);
+            assert_eq!(ExecMode::parse(&mode.as_str().to_uppercase()), Some(mode));
+        }
+        assert_eq!(resolve(Some("dry-run"), Some("1"), None).unwrap(), ExecMode::DryRun);
+        assert_eq!(resolve(Some("paper"), None, Some("1")).unwrap(), ExecMode::Simulated);
+        assert!(resolve(Some("live"), Some("1"), None).is_err());
+        assert!(resolve(Some("shadow"), Some("0"), None).is_err());
+        assert!(resolve(Some("yolo"), None, None).is_err());
+    }
+
+    #[test]
+    fn test_semantics() {
+        assert!(ExecMode::Live.is_live() && !ExecMode::Live.records_shadow());
+        assert!(!ExecMode::Simulated.is_live() && !ExecMode::DryRun.records_shadow());
+        assert!(ExecMode::Shadow.records_shadow() && !ExecMode::Shadow.is_live());
+        // Never started: executors can't go live
+        assert_eq!(current(), ExecMode::DryRun);
+        assert_eq!(confirm(ExecMode::Live), ExecMode::DryRun);
+        assert_eq!(confirm(ExecMode::Simulated), ExecMode::Simulated);
+    }
+}
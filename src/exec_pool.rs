@@ -0,0 +1,126 @@
+// src/exec_pool.rs
+// Bounded execution pool - caps concurrent executions and drops stale queued ones
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::metrics;
+
+/// Execution pool configuration from environment
+#[derive(Debug, Clone)]
+pub struct ExecPoolConfig {
+    /// Maximum executions running at once (0 = unlimited)
+    pub max_concurrent: usize,
+
+    /// Queued executions older than this are dropped instead of run (ms, 0 = never expire)
+    pub queue_expiry_ms: u64,
+}
+
+impl ExecPoolConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_concurrent: std::env::var("EXEC_MAX_CONCURRENT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+
+            queue_expiry_ms: std::env::var("EXEC_QUEUE_EXPIRY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(500),
+        }
+    }
+}
+
+/// Slot in the pool; frees the slot when dropped
+pub struct ExecPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for ExecPermit {
+    fn drop(&mut self) {
+        metrics::gauge("exec.pool.running").fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Semaphore-based pool shared by everything that sends orders
+pub struct ExecPool {
+    semaphore: Option<Arc<Semaphore>>,
+    queue_expiry: Option<Duration>,
+}
+
+impl ExecPool {
+    pub fn new(config: ExecPoolConfig) -> Self {
+        Self {
+            semaphore: (config.max_concurrent > 0).then(|| Arc::new(Semaphore::new(config.max_concurrent))),
+            queue_expiry: (config.queue_expiry_ms > 0).then(|| Duration::from_millis(config.queue_expiry_ms)),
+        }
+    }
+
+    /// Wait for a slot. `queued_at` is when the opportunity was detected; if no slot frees
+    /// up before it is `queue_expiry` old, the execution is stale and None is returned.
+    pub async fn acquire(&self, queued_at: Instant) -> Option<ExecPermit> {
+        let depth = metrics::gauge("exec.pool.queued");
+        depth.fetch_add(1, Ordering::Relaxed);
+
+        let permit = match &self.semaphore {
+            None => Some(None),
+            Some(sem) => {
+                let acquire = sem.clone().acquire_owned();
+                match self.queue_expiry {
+                    Some(expiry) => tokio::time::timeout_at(queued_at + expiry, acquire).await
+                        .ok()
+                        .and_then(|r| r.ok())
+                        .map(Some),
+                    None => acquire.await.ok().map(Some),
+                }
+            }
+        };
+        depth.fetch_sub(1, Ordering::Relaxed);
+
+        let waited_us = queued_at.elapsed().as_micros() as u64;
+        let Some(permit) = permit else {
+            metrics::incr("exec.pool.expired");
+            return None;
+        };
+        metrics::incr("exec.pool.acquired");
+        metrics::add("exec.pool.wait_us_total", waited_us);
+        let max_wait = metrics::gauge("exec.pool.max_wait_us");
+        max_wait.fetch_max(waited_us as i64, Ordering::Relaxed);
+        metrics::gauge("exec.pool.running").fetch_add(1, Ordering::Relaxed);
+
+        Some(ExecPermit { _permit: permit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_concurrency() {
+        let pool = ExecPool::new(ExecPoolConfig { max_concurrent: 1, queue_expiry_ms: 0 });
+        let first = pool.acquire(Instant::now()).await.unwrap();
+
+        // Second caller waits until the first slot is released
+        let waiter = pool.acquire(Instant::now());
+        tokio::pin!(waiter);
+        assert!(tokio::time::timeout(Duration::from_millis(20), &mut waiter).await.is_err());
+        drop(first);
+        assert!(waiter.await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stale_queued_execution_is_dropped() {
+        let pool = ExecPool::new(ExecPoolConfig { max_concurrent: 1, queue_expiry_ms: 30 });
+        let _held = pool.acquire(Instant::now()).await.unwrap();
+
+        // Detected long ago - already past expiry, never gets a slot
+        let stale = Instant::now() - Duration::from_millis(100);
+        assert!(pool.acquire(stale).await.is_none());
+        assert!(pool.acquire(Instant::now()).await.is_none());
+    }
+}
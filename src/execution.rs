@@ -6,17 +6,44 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use tracing::{info, warn, error};
+use tracing::{debug, info, warn, error};
 
 use crate::kalshi::KalshiApiClient;
+use crate::kalshi_margin::{self, KalshiMarginConfig};
 use crate::polymarket_clob::SharedAsyncClient;
 use crate::types::{
-    ArbType, MarketPair,
-    FastExecutionRequest, GlobalState,
-    cents_to_price,
+    ArbType, AtomicMarketState, MarketPair,
+    FastExecutionRequest, GlobalState, PriceCents, SizeCents,
+    cents_to_price, kalshi_fee_cents, poly_fee_cents,
 };
+use crate::aggression::{self, AggressionConfig};
+use crate::leg_order::{self, LegOrderConfig};
+use crate::basis_risk::BasisRiskConfig;
+use crate::basket::{self, Basket, BasketConfig, BasketLeg, Compensation, RepairSide};
 use crate::circuit_breaker::CircuitBreaker;
+use crate::control;
+use crate::overrides;
+use crate::close_race::{self, CloseRace, CloseRaceConfig, UnverifiedFill};
+use crate::cost_model::{self, CostModelConfig, FillSample};
+use crate::exec_mode::{self, ExecMode};
+use crate::exec_pool::{ExecPool, ExecPoolConfig};
+use crate::fill_detail::FillDetail;
+use crate::fill_retry::{FillRetryConfig, RetryPlan};
+use crate::venue_health::{self, Venue};
+use crate::intents::{self, IntentJournal, OrderIntent};
+use crate::metrics;
+use crate::opportunities::{self, OpportunityRecord};
+use crate::opp_cost::{self, OppCostConfig};
+use crate::order_errors::{self, RejectReason};
+use crate::order_rate;
+use crate::order_tags::OrderStrategy;
+use crate::events::{self, BotEvent};
 use crate::position_tracker::{FillRecord, PositionChannel};
+use crate::run_windows;
+use crate::shadow::{self, ShadowLeg, ShadowRecorder, ShadowTrade};
+use crate::throttle::TradeThrottle;
+use crate::tranches::{TrancheConfig, TranchePlan};
+use crate::units;
 
 // =============================================================================
 // EXECUTION ENGINE
@@ -50,22 +77,51 @@ pub struct ExecutionEngine {
     poly_async: Arc<SharedAsyncClient>,
     state: Arc<GlobalState>,
     circuit_breaker: Arc<CircuitBreaker>,
+    throttle: Arc<TradeThrottle>,
     position_channel: PositionChannel,
+    intents: Arc<IntentJournal>,
     in_flight: Arc<[AtomicU64; 8]>,
     clock: NanoClock,
-    pub dry_run: bool,
+    /// Live, dry run, simulated (paper fills) or shadow
+    pub mode: ExecMode,
     test_mode: bool,
+    /// Shadow mode: record what live orders would have achieved for each arb
+    shadow: Option<Arc<ShadowRecorder>>,
+    /// Follow-up tranches when one side's depth capped the size
+    tranches: TrancheConfig,
+    /// Retries of the unmatched size after a partial IOC fill
+    fill_retry: FillRetryConfig,
+    /// Edge required for the time capital stays locked until settlement
+    opp_cost: OppCostConfig,
+    /// Ticks through the ask each IOC leg is sent at
+    aggression: AggressionConfig,
+    /// Edge discount for pairs whose legs settle at different times / on different sources
+    basis: BasisRiskConfig,
+    /// Release order of cross-venue legs by measured venue latency
+    leg_order: LegOrderConfig,
+    /// Orders racing a market's close: no hedging, fills tagged for settlement checks
+    close_race: CloseRaceConfig,
+    /// Fitted fill rate / slippage: trades are sized on expected, not displayed, profit
+    cost_model: CostModelConfig,
+    /// Kalshi legs sized to the cash that can fund their premium + fees
+    kalshi_margin: KalshiMarginConfig,
+    /// Compensation of pairs whose legs filled different sizes
+    basket: BasketConfig,
 }
 
 impl ExecutionEngine {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         kalshi: Arc<KalshiApiClient>,
         poly_async: Arc<SharedAsyncClient>,
         state: Arc<GlobalState>,
         circuit_breaker: Arc<CircuitBreaker>,
+        throttle: Arc<TradeThrottle>,
         position_channel: PositionChannel,
-        dry_run: bool,
+        intents: Arc<IntentJournal>,
+        mode: ExecMode,
     ) -> Self {
+        let mode = exec_mode::confirm(mode);
         let test_mode = std::env::var("TEST_ARB")
             .map(|v| v == "1" || v == "true")
             .unwrap_or(false);
@@ -75,11 +131,24 @@ impl ExecutionEngine {
             poly_async,
             state,
             circuit_breaker,
+            throttle,
             position_channel,
+            intents,
             in_flight: Arc::new(std::array::from_fn(|_| AtomicU64::new(0))),
             clock: NanoClock::new(),
-            dry_run,
+            mode,
             test_mode,
+            shadow: ShadowRecorder::from_env(mode).map(Arc::new),
+            tranches: TrancheConfig::from_env(),
+            fill_retry: FillRetryConfig::from_env(),
+            opp_cost: OppCostConfig::from_env(),
+            aggression: AggressionConfig::from_env(),
+            basis: BasisRiskConfig::from_env(),
+            leg_order: LegOrderConfig::from_env(),
+            close_race: CloseRaceConfig::from_env(),
+            cost_model: CostModelConfig::from_env(),
+            kalshi_margin: KalshiMarginConfig::from_env(),
+            basket: BasketConfig::from_env(Compensation::Unwind),
         }
     }
 
@@ -101,10 +170,19 @@ impl ExecutionEngine {
                     profit_cents: 0,
                     latency_ns: self.clock.now_ns() - req.detected_ns,
                     error: Some("Already in-flight"),
+                    unfilled: 0,
                 });
             }
         }
 
+        self.execute_claimed(req, false).await
+    }
+
+    /// Execute a request whose market is already marked in flight. Follow-up tranches
+    /// skip the throttle: they are the same opportunity, not a new trade.
+    async fn execute_claimed(&self, req: FastExecutionRequest, tranche: bool) -> Result<ExecutionResult> {
+        let market_id = req.market_id;
+
         // Get market pair 
         let market = self.state.get_by_id(market_id)
             .ok_or_else(|| anyhow!("Unknown market_id {}", market_id))?;
@@ -122,9 +200,49 @@ impl ExecutionEngine {
                 profit_cents: 0,
                 latency_ns: self.clock.now_ns() - req.detected_ns,
                 error: Some("Profit below threshold"),
+                unfilled: 0,
             });
         }
 
+        // Long-dated pairs lock capital until settlement: the edge has to pay for that too
+        if self.opp_cost.enabled() {
+            let locked_until = opp_cost::locked_until(pair.settles_at, pair.poly_ends_at);
+            let secs = opp_cost::secs_until(locked_until, chrono::Utc::now().timestamp());
+            let cost_cents = 100.0 - profit_cents as f64;
+            let net = self.opp_cost.net_edge(profit_cents as f64, cost_cents, secs);
+            if !self.opp_cost.clears(profit_cents as f64, cost_cents, secs) {
+                debug!("[EXEC] {} edge {}¢ is {:.2}¢ after {:.1}h of lockup - skipping",
+                       pair.description, profit_cents, net, secs as f64 / 3600.0);
+                self.release_in_flight(market_id);
+                return Ok(ExecutionResult {
+                    market_id,
+                    success: false,
+                    profit_cents: 0,
+                    latency_ns: self.clock.now_ns() - req.detected_ns,
+                    error: Some("Below lockup-adjusted edge"),
+                    unfilled: 0,
+                });
+            }
+        }
+
+        // Legs that settle apart (or on different reference data) aren't a riskless arb
+        if pair.basis_risk > 0.0 {
+            let net = self.basis.net_edge(profit_cents as f64, pair.basis_risk);
+            if net < 1.0 {
+                debug!("[EXEC] {} edge {}¢ is {:.2}¢ after basis risk {:.2} - skipping",
+                       pair.description, profit_cents, net, pair.basis_risk);
+                self.release_in_flight(market_id);
+                return Ok(ExecutionResult {
+                    market_id,
+                    success: false,
+                    profit_cents: 0,
+                    latency_ns: self.clock.now_ns() - req.detected_ns,
+                    error: Some("Below basis-adjusted edge"),
+                    unfilled: 0,
+                });
+            }
+        }
+
         // Calculate max contracts from size (min of both sides)
         let mut max_contracts = (req.yes_size.min(req.no_size) / 100) as i64;
 
@@ -148,6 +266,152 @@ impl ExecutionEngine {
                 profit_cents: 0,
                 latency_ns: self.clock.now_ns() - req.detected_ns,
                 error: Some("Insufficient liquidity"),
+                unfilled: 0,
+            });
+        }
+
+        // Venue health: degraded venues trade reduced size, down venues not at all
+        let (leg1, _, leg2, _) = leg_venues(req.arb_type);
+        let size_factor = [leg1, leg2].iter()
+            .filter_map(|v| Venue::from_name(v))
+            .map(|v| venue_health::health().size_factor(v))
+            .fold(1.0_f64, f64::min);
+        if size_factor < 1.0 {
+            let scaled = (max_contracts as f64 * size_factor).floor() as i64;
+            if scaled < 1 {
+                let reason = if size_factor <= 0.0 { "Venue unhealthy" } else { "Below minimum size" };
+                debug!("[EXEC] {}, skipping {}", reason, pair.description);
+                self.release_in_flight(market_id);
+                return Ok(ExecutionResult {
+                    market_id,
+                    success: false,
+                    profit_cents: 0,
+                    latency_ns: self.clock.now_ns() - req.detected_ns,
+                    error: Some(reason),
+                    unfilled: 0,
+                });
+            }
+            debug!("[EXEC] Venue degraded, scaling {} → {} contracts", max_contracts, scaled);
+            max_contracts = scaled;
+        }
+
+        // Cost model: size for the fills and slippage this book has historically delivered
+        let secs_to_expiry = pair.settles_at.map(|t| t - chrono::Utc::now().timestamp());
+        if let Some(model) = cost_model::model() {
+            let (yes_depth, no_depth) = (req.yes_size as f64 / 100.0, req.no_size as f64 / 100.0);
+            match model.best_size(profit_cents as f64, max_contracts as f64, 1.0, yes_depth, no_depth,
+                                  secs_to_expiry, self.cost_model.unwind_cents) {
+                Some((size, expected)) if (size as i64) < max_contracts => {
+                    debug!("[EXEC] Cost model: {} → {} contracts ({:.1}¢ expected)", max_contracts, size, expected);
+                    max_contracts = size as i64;
+                }
+                Some(_) => {}
+                None => {
+                    debug!("[EXEC] {} edge {}¢ has no positive expected profit after fills/slippage - skipping",
+                           pair.description, profit_cents);
+                    self.release_in_flight(market_id);
+                    return Ok(ExecutionResult {
+                        market_id,
+                        success: false,
+                        profit_cents: 0,
+                        latency_ns: self.clock.now_ns() - req.detected_ns,
+                        error: Some("Negative expected profit"),
+                        unfilled: 0,
+                    });
+                }
+            }
+        }
+
+        // Price improvement: send the legs a few ticks through the ask, as far as the edge allows
+        let (offset_ticks, limits) = self.aggression.apply(&req);
+
+        // Kalshi collects premium + fees up front: size the trade to the cash its Kalshi leg(s)
+        // can fund, so a cross-venue arb never loses its Kalshi leg to a balance rejection
+        let kalshi_collateral = match req.arb_type {
+            ArbType::PolyYesKalshiNo => kalshi_margin::collateral_per_contract(limits.no_price as i64),
+            ArbType::KalshiYesPolyNo => kalshi_margin::collateral_per_contract(limits.yes_price as i64),
+            ArbType::KalshiOnly => kalshi_margin::collateral_per_contract(limits.yes_price as i64)
+                + kalshi_margin::collateral_per_contract(limits.no_price as i64),
+            ArbType::PolyOnly => 0,
+        };
+        let _margin = if self.kalshi_margin.enabled() && kalshi_collateral > 0 {
+            match kalshi_margin::reserve(&self.kalshi_margin, kalshi_collateral, max_contracts) {
+                Ok(reservation) => {
+                    if let Some(r) = reservation.as_ref().filter(|r| r.contracts < max_contracts) {
+                        debug!("[EXEC] Kalshi collateral covers {} of {} contracts", r.contracts, max_contracts);
+                        max_contracts = r.contracts;
+                    }
+                    reservation
+                }
+                Err(short) => {
+                    warn!("[EXEC] 💸 {} needs {}¢/contract on Kalshi, {}¢ available - skipping",
+                          pair.description, short.required_cents, short.available_cents);
+                    self.release_in_flight(market_id);
+                    return Ok(ExecutionResult {
+                        market_id,
+                        success: false,
+                        profit_cents: 0,
+                        latency_ns: self.clock.now_ns() - req.detected_ns,
+                        error: Some("Insufficient Kalshi collateral"),
+                        unfilled: 0,
+                    });
+                }
+            }
+        } else {
+            None
+        };
+
+        // Both legs as one basket: refused as a whole if a leg is malformed or the limits leave no edge
+        let basket = basket_for(&limits, pair, max_contracts);
+        if let Err(e) = basket.precheck() {
+            debug!("[EXEC] Basket precheck failed: {}", e);
+            self.release_in_flight(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: false,
+                profit_cents: 0,
+                latency_ns: self.clock.now_ns() - req.detected_ns,
+                error: Some("Basket precheck failed"),
+                unfilled: 0,
+            });
+        }
+
+        // Run windows: outside its trading hours a strategy only watches
+        if !run_windows::is_open(strategy_name(req.arb_type)) {
+            self.release_in_flight(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: false,
+                profit_cents: 0,
+                latency_ns: self.clock.now_ns() - req.detected_ns,
+                error: Some("Outside run window"),
+                unfilled: 0,
+            });
+        }
+
+        // Operator blacklist (OVERRIDES_FILE)
+        if overrides::current().is_blacklisted(&[&pair.league, &pair.poly_slug, &pair.kalshi_market_ticker, &pair.kalshi_event_ticker]) {
+            self.release_in_flight(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: false,
+                profit_cents: 0,
+                latency_ns: self.clock.now_ns() - req.detected_ns,
+                error: Some("Blacklisted"),
+                unfilled: 0,
+            });
+        }
+
+        // Leagues paused through the control API
+        if control::switches().is_paused(&[&pair.league, &pair.poly_slug, &pair.kalshi_event_ticker]) {
+            self.release_in_flight(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: false,
+                profit_cents: 0,
+                latency_ns: self.clock.now_ns() - req.detected_ns,
+                error: Some("Paused"),
+                unfilled: 0,
             });
         }
 
@@ -160,48 +424,201 @@ impl ExecutionEngine {
                 profit_cents: 0,
                 latency_ns: self.clock.now_ns() - req.detected_ns,
                 error: Some("Circuit breaker"),
+                unfilled: 0,
+            });
+        }
+
+        // Trade throttle (reserves a slot when allowed; tranches ride on the first one's slot)
+        let throttled = if tranche { Ok(()) } else { self.throttle.try_acquire(&pair.pair_id) };
+        if let Err(reason) = throttled {
+            debug!("[EXEC] Throttled {}: {}", pair.description, reason);
+            self.release_in_flight(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: false,
+                profit_cents: 0,
+                latency_ns: self.clock.now_ns() - req.detected_ns,
+                error: Some("Throttled"),
+                unfilled: 0,
             });
         }
 
         let latency_to_exec = self.clock.now_ns() - req.detected_ns;
         info!(
-            "[EXEC] 🎯 {} | {:?} y={}¢ n={}¢ | profit={}¢ | {}x | {}µs",
+            "[EXEC] 🎯 {} | {:?} y={}¢ n={}¢ | profit={}¢ | {}x | {}µs{}",
             pair.description,
             req.arb_type,
             req.yes_price,
             req.no_price,
             profit_cents,
             max_contracts,
-            latency_to_exec / 1000
+            latency_to_exec / 1000,
+            if offset_ticks > 0 { format!(" | +{} tick limits", offset_ticks) } else { String::new() }
         );
 
-        if self.dry_run {
+        events::publish(BotEvent::ArbDetected {
+            market: pair.description.to_string(),
+            arb_type: format!("{:?}", req.arb_type),
+            yes_price: cents_to_price(req.yes_price),
+            no_price: cents_to_price(req.no_price),
+            contracts: max_contracts as f64,
+        });
+
+        if self.mode == ExecMode::Simulated {
+            // Paper fill: both legs in full at their limit prices
+            info!("[EXEC] 📝 SIMULATED - paper fill {} contracts", max_contracts);
+            let (platform1, side1, platform2, side2) = leg_venues(req.arb_type);
+            for (platform, side, price) in [(platform1, side1, limits.yes_price), (platform2, side2, limits.no_price)] {
+                self.position_channel.record_fill(FillRecord::new(
+                    &pair.pair_id, &pair.description, platform, side,
+                    max_contracts as f64, cents_to_price(price), 0.0, &exec_mode::simulated_order_id(),
+                ));
+            }
+            self.release_in_flight_delayed(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: true,
+                profit_cents: profit_cents.into(),
+                latency_ns: latency_to_exec,
+                error: Some("SIMULATED"),
+                unfilled: 0,
+            });
+        }
+        if !self.mode.is_live() {
             info!("[EXEC] 🏃 DRY RUN - would execute {} contracts", max_contracts);
+            if let Some(shadow) = &self.shadow {
+                self.spawn_shadow(shadow.clone(), limits, pair.clone(), max_contracts);
+            }
             self.release_in_flight_delayed(market_id);
             return Ok(ExecutionResult {
                 market_id,
                 success: true,
-                profit_cents,
+                profit_cents: profit_cents.into(),
                 latency_ns: latency_to_exec,
                 error: Some("DRY_RUN"),
+                unfilled: 0,
+            });
+        }
+
+        // Order-rate budget: wait briefly for room, or refuse rather than crowd out market-data calls
+        let venues: Vec<Venue> = basket.legs.iter().map(|l| l.venue).collect();
+        if let Err(breach) = order_rate::rates().acquire(&venues).await {
+            warn!("[EXEC] ⏳ {} refused: {}", pair.description, breach);
+            self.release_in_flight(market_id);
+            return Ok(ExecutionResult {
+                market_id,
+                success: false,
+                profit_cents: 0,
+                latency_ns: self.clock.now_ns() - req.detected_ns,
+                error: Some("Order rate limit"),
+                unfilled: 0,
+            });
+        }
+
+        let (platform1, side1, platform2, side2) = leg_venues(req.arb_type);
+        for (platform, side, price) in [(platform1, side1, limits.yes_price), (platform2, side2, limits.no_price)] {
+            events::publish(BotEvent::OrderSubmitted {
+                market: pair.description.to_string(),
+                platform: platform.to_string(),
+                side: side.to_string(),
+                price: cents_to_price(price),
+                contracts: max_contracts as f64,
             });
         }
 
-        // Execute both legs concurrently 
-        let result = self.execute_both_legs_async(&req, pair, max_contracts).await;
+        // Execute both legs concurrently (each journaled as an intent before sending)
+        let group_id = intents::new_group_id(&pair.pair_id);
+        let strategy = OrderStrategy::for_arb(req.arb_type);
+        let result = self.execute_both_legs_async(&basket, strategy, pair, &group_id).await;
+
+        // Outcome is now handled in-process (fills recorded / auto-close), so the
+        // intents are only needed if we crash before this point
+        self.intents.complete_group(&group_id);
 
         // Release in-flight after delay
         self.release_in_flight_delayed(market_id);
 
         match result {
             // Note: For same-platform arbs (PolyOnly/KalshiOnly), these are YES/NO fills, not platform fills
-            Ok((yes_filled, no_filled, yes_cost, no_cost, yes_order_id, no_order_id)) => {
+            Ok(((yes_filled, no_filled, yes_cost, no_cost, yes_order_id, no_order_id), closed, [yes_detail, no_detail])) => {
+                let matched = yes_filled.min(no_filled);
+                let actual_profit = units::pair_profit_cents(matched, yes_cost + no_cost);
+                aggression::record_outcome(offset_ticks, max_contracts, matched, actual_profit);
+                for (platform, ask, depth, filled, cost) in [
+                    (platform1, req.yes_price, req.yes_size, yes_filled, yes_cost),
+                    (platform2, req.no_price, req.no_size, no_filled, no_cost),
+                ] {
+                    cost_model::record(FillSample::new(
+                        platform, cents_to_price(ask), depth as f64 / 100.0, max_contracts as f64,
+                        secs_to_expiry, filled as f64, cost as f64 / 100.0,
+                    ));
+                }
+
+                // A leg refused as closed, or the market inside its close window: fills may have
+                // landed on a frozen book, so they are tagged and not hedged into a closed market
+                let closing = closed.iter().any(|c| *c)
+                    || self.close_race.closing(pair.settles_at, chrono::Utc::now().timestamp());
+                if closing {
+                    for (rejected, platform, side, filled, cost, order_id) in [
+                        (closed[0], platform1, side1, yes_filled, yes_cost, &yes_order_id),
+                        (closed[1], platform2, side2, no_filled, no_cost, &no_order_id),
+                    ] {
+                        match close_race::classify(filled > 0, rejected, closing) {
+                            Some(CloseRace::LateFill) => close_race::tag_fill(&self.close_race, &UnverifiedFill::new(
+                                &pair.description, platform, side, filled as f64,
+                                cost as f64 / 100.0 / filled as f64, order_id,
+                            )),
+                            Some(race) => close_race::record_outcome(race),
+                            None => {}
+                        }
+                    }
+                }
+
+                // === COMPLETE THE LAGGING LEG (BASKET_COMPENSATION=complete) ===
+                let (mut yes_filled, mut no_filled, mut yes_cost, mut no_cost) = (yes_filled, no_filled, yes_cost, no_cost);
+                if !closing && self.basket.compensation == Compensation::Complete && yes_filled != no_filled {
+                    // Unwind plans (no edge left to complete) are left to the auto-close below
+                    let plan: Vec<_> = basket.plan(&[yes_filled as f64, no_filled as f64], &self.basket).into_iter()
+                        .filter(|r| r.side == RepairSide::Buy)
+                        .collect();
+                    let group_id = intents::new_group_id(&pair.pair_id);
+                    let results = basket::compensate(&basket, &plan, |r| {
+                        self.send_leg(&group_id, pair, strategy, &basket.legs[r.leg], r.contracts as i64, units::dollars_to_cents(r.price))
+                    }).await;
+                    self.intents.complete_group(&group_id);
+                    for (r, fill) in plan.iter().zip(results).filter_map(|(r, res)| res.ok().map(|f| (r, f))) {
+                        let (filled, cost) = if r.leg == 0 { (&mut yes_filled, &mut yes_cost) } else { (&mut no_filled, &mut no_cost) };
+                        *filled += fill.filled;
+                        *cost += fill.cost;
+                    }
+                }
                 let matched = yes_filled.min(no_filled);
                 let success = matched > 0;
-                let actual_profit = matched as i16 * 100 - (yes_cost + no_cost) as i16;
+                let actual_profit = units::pair_profit_cents(matched, yes_cost + no_cost);
 
                 // === AUTO-CLOSE MISMATCHED EXPOSURE (non-blocking) ===
-                if yes_filled != no_filled && (yes_filled > 0 || no_filled > 0) {
+                let hold = self.basket.compensation == Compensation::Hold;
+                if (closing || hold) && yes_filled != no_filled {
+                    let excess = (yes_filled - no_filled).abs();
+                    if closing {
+                        warn!("[EXEC] 🏁 {} is closing - not hedging {} unmatched contracts (left for settlement)",
+                              pair.description, excess);
+                        metrics::incr("close_race.hedge_suppressed");
+                    } else {
+                        warn!("[EXEC] ✋ {} - holding {} unmatched contracts (BASKET_COMPENSATION=hold)",
+                              pair.description, excess);
+                    }
+                    // Held to settlement, so the unhedged leg is a real position
+                    let (platform, side, filled, cost, order_id, detail) = if yes_filled > no_filled {
+                        (platform1, side1, yes_filled, yes_cost, &yes_order_id, &yes_detail)
+                    } else {
+                        (platform2, side2, no_filled, no_cost, &no_order_id, &no_detail)
+                    };
+                    self.position_channel.record_fill(FillRecord::new(
+                        &pair.pair_id, &pair.description, platform, side,
+                        excess as f64, cost as f64 / 100.0 / filled as f64, 0.0, order_id,
+                    ).with_detail(detail));
+                } else if yes_filled != no_filled && (yes_filled > 0 || no_filled > 0) {
                     let excess = (yes_filled - no_filled).abs();
                     let (leg1_name, leg2_name) = match req.arb_type {
                         ArbType::PolyYesKalshiNo => ("P_yes", "K_no"),
@@ -221,6 +638,8 @@ impl ExecutionEngine {
                     let poly_yes_token = pair.poly_yes_token.clone();
                     let poly_no_token = pair.poly_no_token.clone();
                     let kalshi_ticker = pair.kalshi_market_ticker.clone();
+                    let market_desc = pair.description.clone();
+                    let unwind_cents = self.basket.unwind_cents.round() as i64;
                     let original_cost_per_contract = if yes_filled > no_filled {
                         if yes_filled > 0 { yes_cost / yes_filled } else { 0 }
                     } else {
@@ -231,33 +650,43 @@ impl ExecutionEngine {
                         Self::auto_close_background(
                             kalshi, poly_async, arb_type, yes_filled, no_filled,
                             yes_price, no_price, poly_yes_token, poly_no_token,
-                            kalshi_ticker, original_cost_per_contract
+                            kalshi_ticker, market_desc, original_cost_per_contract, unwind_cents,
                         ).await;
                     });
                 }
 
                 if success {
                     self.circuit_breaker.record_success(&pair.pair_id, matched, matched, actual_profit as f64 / 100.0).await;
+                    self.throttle.record_success(&pair.pair_id);
                 }
 
-                if matched > 0 {
-                    let (platform1, side1, platform2, side2) = match req.arb_type {
-                        ArbType::PolyYesKalshiNo => ("polymarket", "yes", "kalshi", "no"),
-                        ArbType::KalshiYesPolyNo => ("kalshi", "yes", "polymarket", "no"),
-                        ArbType::PolyOnly => ("polymarket", "yes", "polymarket", "no"),
-                        ArbType::KalshiOnly => ("kalshi", "yes", "kalshi", "no"),
-                    };
+                for (platform, side, filled, cost, order_id) in [
+                    (platform1, side1, yes_filled, yes_cost, &yes_order_id),
+                    (platform2, side2, no_filled, no_cost, &no_order_id),
+                ] {
+                    if filled > 0 {
+                        events::publish(BotEvent::Filled {
+                            market: pair.description.to_string(),
+                            platform: platform.to_string(),
+                            side: side.to_string(),
+                            contracts: filled as f64,
+                            cost: cost as f64 / 100.0,
+                            order_id: order_id.to_string(),
+                        });
+                    }
+                }
 
+                if matched > 0 {
                     self.position_channel.record_fill(FillRecord::new(
                         &pair.pair_id, &pair.description, platform1, side1,
                         matched as f64, yes_cost as f64 / 100.0 / yes_filled.max(1) as f64,
                         0.0, &yes_order_id,
-                    ));
+                    ).with_detail(&yes_detail));
                     self.position_channel.record_fill(FillRecord::new(
                         &pair.pair_id, &pair.description, platform2, side2,
                         matched as f64, no_cost as f64 / 100.0 / no_filled.max(1) as f64,
                         0.0, &no_order_id,
-                    ));
+                    ).with_detail(&no_detail));
                 }
 
                 Ok(ExecutionResult {
@@ -265,196 +694,188 @@ impl ExecutionEngine {
                     success,
                     profit_cents: actual_profit,
                     latency_ns: self.clock.now_ns() - req.detected_ns,
-                    error: if success { None } else { Some("Partial/no fill") },
+                    error: if success { None } else if closing { Some("Market closing") } else { Some("Partial/no fill") },
+                    unfilled: if success && !closing { max_contracts - matched } else { 0 },
                 })
             }
             Err(_e) => {
                 self.circuit_breaker.record_error().await;
+                // Rejected (rate limit, paused market...): don't hammer it again next tick
+                self.throttle.record_failure(&pair.pair_id);
                 Ok(ExecutionResult {
                     market_id,
                     success: false,
                     profit_cents: 0,
                     latency_ns: self.clock.now_ns() - req.detected_ns,
                     error: Some("Execution failed"),
+                    unfilled: 0,
                 })
             }
         }
     }
 
-    async fn execute_both_legs_async(
-        &self,
-        req: &FastExecutionRequest,
-        pair: &MarketPair,
-        contracts: i64,
-    ) -> Result<(i64, i64, i64, i64, String, String)> {
-        match req.arb_type {
-            // === CROSS-PLATFORM: Poly YES + Kalshi NO ===
-            ArbType::PolyYesKalshiNo => {
-                let kalshi_fut = self.kalshi.buy_ioc(
-                    &pair.kalshi_market_ticker,
-                    "no",
-                    req.no_price as i64,
-                    contracts,
-                );
-                let poly_fut = self.poly_async.buy_fak(
-                    &pair.poly_yes_token,
-                    cents_to_price(req.yes_price),
-                    contracts as f64,
-                );
-                let (kalshi_res, poly_res) = tokio::join!(kalshi_fut, poly_fut);
-                self.extract_cross_results(kalshi_res, poly_res)
-            }
-
-            // === CROSS-PLATFORM: Kalshi YES + Poly NO ===
-            ArbType::KalshiYesPolyNo => {
-                let kalshi_fut = self.kalshi.buy_ioc(
-                    &pair.kalshi_market_ticker,
-                    "yes",
-                    req.yes_price as i64,
-                    contracts,
-                );
-                let poly_fut = self.poly_async.buy_fak(
-                    &pair.poly_no_token,
-                    cents_to_price(req.no_price),
-                    contracts as f64,
-                );
-                let (kalshi_res, poly_res) = tokio::join!(kalshi_fut, poly_fut);
-                self.extract_cross_results(kalshi_res, poly_res)
-            }
+    /// Record the orders live execution would have sent and, after the order latency,
+    /// how much of them the book could still have filled
+    fn spawn_shadow(&self, shadow: Arc<ShadowRecorder>, req: FastExecutionRequest, pair: Arc<MarketPair>, contracts: i64) {
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(shadow.latency()).await;
+            let Some(market) = state.get_by_id(req.market_id) else { return };
+            let (platform1, side1, platform2, side2) = leg_venues(req.arb_type);
 
-            // === SAME-PLATFORM: Poly YES + Poly NO ===
-            ArbType::PolyOnly => {
-                let yes_fut = self.poly_async.buy_fak(
-                    &pair.poly_yes_token,
-                    cents_to_price(req.yes_price),
-                    contracts as f64,
-                );
-                let no_fut = self.poly_async.buy_fak(
-                    &pair.poly_no_token,
-                    cents_to_price(req.no_price),
-                    contracts as f64,
-                );
-                let (yes_res, no_res) = tokio::join!(yes_fut, no_fut);
-                self.extract_poly_only_results(yes_res, no_res)
-            }
+            let legs = [(platform1, side1, req.yes_price), (platform2, side2, req.no_price)].into_iter()
+                .map(|(venue, side, price)| {
+                    let (yes_ask, no_ask, yes_size, no_size) = if venue == "kalshi" {
+                        market.kalshi.load()
+                    } else {
+                        market.poly.load()
+                    };
+                    let (ask, ask_size) = if side == "yes" { (yes_ask, yes_size) } else { (no_ask, no_size) };
+                    let (instrument, fee) = match (venue, side) {
+                        ("kalshi", _) => (pair.kalshi_market_ticker.to_string(), kalshi_fee_cents(price)),
+                        (_, "yes") => (pair.poly_yes_token.to_string(), poly_fee_cents(price, req.poly_fee_bps)),
+                        _ => (pair.poly_no_token.to_string(), poly_fee_cents(price, req.poly_fee_bps)),
+                    };
+                    ShadowLeg {
+                        venue,
+                        side,
+                        instrument,
+                        price: cents_to_price(price),
+                        contracts: contracts as f64,
+                        fee: cents_to_price(fee),
+                        filled: shadow::leg_fill(cents_to_price(price), contracts as f64,
+                                                 cents_to_price(ask), (ask_size / 100) as f64),
+                    }
+                })
+                .collect();
 
-            // === SAME-PLATFORM: Kalshi YES + Kalshi NO ===
-            ArbType::KalshiOnly => {
-                let yes_fut = self.kalshi.buy_ioc(
-                    &pair.kalshi_market_ticker,
-                    "yes",
-                    req.yes_price as i64,
-                    contracts,
-                );
-                let no_fut = self.kalshi.buy_ioc(
-                    &pair.kalshi_market_ticker,
-                    "no",
-                    req.no_price as i64,
-                    contracts,
-                );
-                let (yes_res, no_res) = tokio::join!(yes_fut, no_fut);
-                self.extract_kalshi_only_results(yes_res, no_res)
-            }
-        }
+            shadow.record(&ShadowTrade::new(&pair.description, &format!("{:?}", req.arb_type), legs, shadow.unwind_cents()));
+        });
     }
 
-    /// Extract results from cross-platform execution
-    fn extract_cross_results(
+    /// Send both legs as a basket under the leg-order policy (each journaled as an intent)
+    async fn execute_both_legs_async(
         &self,
-        kalshi_res: Result<crate::kalshi::KalshiOrderResponse>,
-        poly_res: Result<crate::polymarket_clob::PolyFillAsync>,
-    ) -> Result<(i64, i64, i64, i64, String, String)> {
-        let (kalshi_filled, kalshi_cost, kalshi_order_id) = match kalshi_res {
-            Ok(resp) => {
-                let filled = resp.order.filled_count();
-                let cost = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
-                (filled, cost, resp.order.order_id)
-            }
-            Err(e) => {
-                warn!("[EXEC] Kalshi failed: {}", e);
-                (0, 0, String::new())
-            }
-        };
+        basket: &Basket,
+        strategy: OrderStrategy,
+        pair: &MarketPair,
+        group_id: &str,
+    ) -> Result<((i64, i64, i64, i64, String, String), [bool; 2], [FillDetail; 2])> {
+        let results = basket::submit(basket, &self.leg_order, |i, contracts| {
+            let leg = &basket.legs[i];
+            self.send_leg(group_id, pair, strategy, leg, contracts as i64, units::dollars_to_cents(leg.price))
+        }, |fill: &LegFill| fill.filled as f64).await;
+        let closed = [rejected_closed(&results[0]), rejected_closed(&results[1])];
 
-        let (poly_filled, poly_cost, poly_order_id) = match poly_res {
-            Ok(fill) => {
-                ((fill.filled_size as i64), (fill.fill_cost * 100.0) as i64, fill.order_id)
-            }
-            Err(e) => {
-                warn!("[EXEC] Poly failed: {}", e);
-                (0, 0, String::new())
+        // For same-platform arbs the slots are YES / NO rather than one per venue
+        let mut fills = results.into_iter().zip(&basket.legs).map(|(result, leg)| result.unwrap_or_else(|e| {
+            if !basket::not_sent(&e) {
+                warn!("[EXEC] {} {} failed: {}", leg.venue.name(), leg.side.to_uppercase(), e);
             }
-        };
-
-        Ok((kalshi_filled, poly_filled, kalshi_cost, poly_cost, kalshi_order_id, poly_order_id))
+            LegFill::default()
+        }));
+        let (yes, no) = (fills.next().unwrap_or_default(), fills.next().unwrap_or_default());
+        Ok(((yes.filled, no.filled, yes.cost, no.cost, yes.order_id, no.order_id), closed, [yes.detail, no.detail]))
     }
 
-    /// Extract results from Poly-only execution (same-platform)
-    fn extract_poly_only_results(
+    /// One IOC buy of `leg` on its venue, as fills and cost in cents
+    async fn send_leg(
         &self,
-        yes_res: Result<crate::polymarket_clob::PolyFillAsync>,
-        no_res: Result<crate::polymarket_clob::PolyFillAsync>,
-    ) -> Result<(i64, i64, i64, i64, String, String)> {
-        let (yes_filled, yes_cost, yes_order_id) = match yes_res {
-            Ok(fill) => {
-                ((fill.filled_size as i64), (fill.fill_cost * 100.0) as i64, fill.order_id)
-            }
-            Err(e) => {
-                warn!("[EXEC] Poly YES failed: {}", e);
-                (0, 0, String::new())
-            }
-        };
-
-        let (no_filled, no_cost, no_order_id) = match no_res {
-            Ok(fill) => {
-                ((fill.filled_size as i64), (fill.fill_cost * 100.0) as i64, fill.order_id)
+        group_id: &str,
+        pair: &MarketPair,
+        strategy: OrderStrategy,
+        leg: &BasketLeg,
+        contracts: i64,
+        price_cents: i64,
+    ) -> Result<LegFill> {
+        match leg.venue {
+            Venue::Kalshi => {
+                let resp = self.kalshi_buy(group_id, pair, strategy, leg.side, price_cents, contracts).await?;
+                Ok(LegFill {
+                    filled: resp.order.filled_count(),
+                    cost: resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0),
+                    detail: resp.order.fill_detail(),
+                    order_id: resp.order.order_id,
+                })
             }
-            Err(e) => {
-                warn!("[EXEC] Poly NO failed: {}", e);
-                (0, 0, String::new())
+            Venue::Polymarket => {
+                let fill = self.poly_buy(group_id, pair, leg.side, cents_to_price(price_cents as PriceCents), contracts).await?;
+                Ok(LegFill {
+                    filled: units::shares_to_contracts(fill.filled_size),
+                    cost: units::dollars_to_cents(fill.fill_cost),
+                    order_id: fill.order_id,
+                    detail: fill.detail,
+                })
             }
-        };
-
-        // For same-platform, return YES as "kalshi" slot and NO as "poly" slot
-        // This keeps the existing result handling logic working
-        Ok((yes_filled, no_filled, yes_cost, no_cost, yes_order_id, no_order_id))
+        }
     }
 
-    /// Extract results from Kalshi-only execution (same-platform)
-    fn extract_kalshi_only_results(
+    /// Kalshi IOC buy, journaled under its client_order_id before sending
+    async fn kalshi_buy(
         &self,
-        yes_res: Result<crate::kalshi::KalshiOrderResponse>,
-        no_res: Result<crate::kalshi::KalshiOrderResponse>,
-    ) -> Result<(i64, i64, i64, i64, String, String)> {
-        let (yes_filled, yes_cost, yes_order_id) = match yes_res {
-            Ok(resp) => {
-                let filled = resp.order.filled_count();
-                let cost = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
-                (filled, cost, resp.order.order_id)
-            }
-            Err(e) => {
-                warn!("[EXEC] Kalshi YES failed: {}", e);
-                (0, 0, String::new())
+        group_id: &str,
+        pair: &MarketPair,
+        strategy: OrderStrategy,
+        side: &str,
+        price_cents: i64,
+        contracts: i64,
+    ) -> Result<crate::kalshi::KalshiOrderResponse> {
+        let client_order_id = KalshiApiClient::new_client_order_id(strategy);
+        self.intents.record(OrderIntent::new(
+            &client_order_id, group_id, "kalshi", &pair.pair_id, &pair.description,
+            &pair.kalshi_market_ticker, side, price_cents as f64 / 100.0, contracts as f64,
+        ))?;
+        let sent_at = Instant::now();
+        let result = self.kalshi.buy_ioc_with_client_id(&pair.kalshi_market_ticker, side, price_cents, contracts, &client_order_id).await;
+        if result.is_ok() {
+            leg_order::latency().record(Venue::Kalshi, sent_at.elapsed());
+        }
+        venue_health::health().record_outcome(Venue::Kalshi, result.is_ok());
+        if let Some(rejected) = result.as_ref().err().and_then(order_errors::rejection) {
+            if rejected.reason == RejectReason::MarketClosed {
+                warn!("[EXEC] 🚫 {} closed on Kalshi ({}) - suspending it", pair.description, rejected.message);
+                self.throttle.suspend(&pair.pair_id);
             }
-        };
+        }
+        result
+    }
 
-        let (no_filled, no_cost, no_order_id) = match no_res {
-            Ok(resp) => {
-                let filled = resp.order.filled_count();
-                let cost = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
-                (filled, cost, resp.order.order_id)
-            }
-            Err(e) => {
-                warn!("[EXEC] Kalshi NO failed: {}", e);
-                (0, 0, String::new())
+    /// Polymarket FAK buy, journaled under its order hash before sending
+    async fn poly_buy(
+        &self,
+        group_id: &str,
+        pair: &MarketPair,
+        side: &str,
+        price: f64,
+        contracts: i64,
+    ) -> Result<crate::polymarket_clob::PolyFillAsync> {
+        let token = if side == "yes" { &pair.poly_yes_token } else { &pair.poly_no_token };
+        let mut sent_at = None;
+        let result = self.poly_async.buy_fak_with(token, price, contracts as f64, |order_hash| {
+            sent_at = Some(Instant::now());
+            self.intents.record(OrderIntent::new(
+                order_hash, group_id, "polymarket", &pair.pair_id, &pair.description,
+                token, side, price, contracts as f64,
+            ))
+        }).await;
+        if let (Ok(_), Some(sent_at)) = (&result, sent_at) {
+            leg_order::latency().record(Venue::Polymarket, sent_at.elapsed());
+        }
+        // A rejection is the venue answering - only transport/server errors count against its health
+        let rejected = result.as_ref().err().and_then(order_errors::rejection);
+        venue_health::health().record_outcome(Venue::Polymarket, result.is_ok() || rejected.is_some());
+        if let Some(rejected) = rejected {
+            warn!("[EXEC] Poly {} rejected on {}: {} ({})", side, pair.description, rejected.reason, rejected.message);
+            if rejected.reason == RejectReason::MarketClosed {
+                warn!("[EXEC] 🚫 {} is not accepting orders - suspending it", pair.description);
+                self.throttle.suspend(&pair.pair_id);
             }
-        };
-
-        // For same-platform, return YES as "kalshi" slot and NO as "poly" slot
-        Ok((yes_filled, no_filled, yes_cost, no_cost, yes_order_id, no_order_id))
+        }
+        result
     }
 
     /// Background auto-close for mismatched fills
+    #[allow(clippy::too_many_arguments)]
     async fn auto_close_background(
         kalshi: Arc<KalshiApiClient>,
         poly_async: Arc<SharedAsyncClient>,
@@ -466,7 +887,9 @@ impl ExecutionEngine {
         poly_yes_token: Arc<str>,
         poly_no_token: Arc<str>,
         kalshi_ticker: Arc<str>,
+        market_desc: Arc<str>,
         original_cost_per_contract: i64,
+        unwind_cents: i64,
     ) {
         let excess = (yes_filled - no_filled).abs();
         if excess == 0 {
@@ -474,11 +897,18 @@ impl ExecutionEngine {
         }
 
         // Helper to log P&L after close
-        let log_close_pnl = |platform: &str, closed: i64, proceeds: i64| {
+        let log_close_pnl = |platform: &str, side: &str, closed: i64, proceeds: i64| {
             if closed > 0 {
                 let close_pnl = proceeds - (original_cost_per_contract * excess);
                 info!("[EXEC] ✅ Closed {} {} contracts for {}¢ (P&L: {}¢)",
                     closed, platform, proceeds, close_pnl);
+                events::publish(BotEvent::Hedged {
+                    market: market_desc.to_string(),
+                    platform: if platform == "Poly" { "polymarket" } else { "kalshi" }.to_string(),
+                    side: side.to_string(),
+                    contracts: closed as f64,
+                    proceeds: proceeds as f64 / 100.0,
+                });
             } else {
                 warn!("[EXEC] ⚠️ Failed to close {} excess - 0 filled", platform);
             }
@@ -491,13 +921,13 @@ impl ExecutionEngine {
                 } else {
                     (&poly_no_token, "no", no_price)
                 };
-                let close_price = cents_to_price((price as i16).saturating_sub(10).max(1) as u16);
+                let close_price = cents_to_price((price as i16).saturating_sub(unwind_cents as i16).max(1) as u16);
 
                 info!("[EXEC] 🔄 Waiting 2s for Poly settlement before auto-close ({} {} contracts)", excess, side);
                 tokio::time::sleep(Duration::from_secs(2)).await;
 
                 match poly_async.sell_fak(token, close_price, excess as f64).await {
-                    Ok(fill) => log_close_pnl("Poly", fill.filled_size as i64, (fill.fill_cost * 100.0) as i64),
+                    Ok(fill) => log_close_pnl("Poly", side, units::shares_to_contracts(fill.filled_size), units::dollars_to_cents(fill.fill_cost)),
                     Err(e) => warn!("[EXEC] ⚠️ Failed to close Poly excess: {}", e),
                 }
             }
@@ -508,12 +938,12 @@ impl ExecutionEngine {
                 } else {
                     ("no", no_price as i64)
                 };
-                let close_price = price.saturating_sub(10).max(1);
+                let close_price = price.saturating_sub(unwind_cents).max(1);
 
-                match kalshi.sell_ioc(&kalshi_ticker, side, close_price, excess).await {
+                match kalshi.sell_ioc(&kalshi_ticker, side, close_price, excess, OrderStrategy::Hedge).await {
                     Ok(resp) => {
                         let proceeds = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
-                        log_close_pnl("Kalshi", resp.order.filled_count(), proceeds);
+                        log_close_pnl("Kalshi", side, resp.order.filled_count(), proceeds);
                     }
                     Err(e) => warn!("[EXEC] ⚠️ Failed to close Kalshi excess: {}", e),
                 }
@@ -522,21 +952,21 @@ impl ExecutionEngine {
             ArbType::PolyYesKalshiNo => {
                 if yes_filled > no_filled {
                     // Poly YES excess
-                    let close_price = cents_to_price((yes_price as i16).saturating_sub(10).max(1) as u16);
+                    let close_price = cents_to_price((yes_price as i16).saturating_sub(unwind_cents as i16).max(1) as u16);
                     info!("[EXEC] 🔄 Waiting 2s for Poly settlement before auto-close ({} yes contracts)", excess);
                     tokio::time::sleep(Duration::from_secs(2)).await;
 
                     match poly_async.sell_fak(&poly_yes_token, close_price, excess as f64).await {
-                        Ok(fill) => log_close_pnl("Poly", fill.filled_size as i64, (fill.fill_cost * 100.0) as i64),
+                        Ok(fill) => log_close_pnl("Poly", "yes", units::shares_to_contracts(fill.filled_size), units::dollars_to_cents(fill.fill_cost)),
                         Err(e) => warn!("[EXEC] ⚠️ Failed to close Poly excess: {}", e),
                     }
                 } else {
                     // Kalshi NO excess
-                    let close_price = (no_price as i64).saturating_sub(10).max(1);
-                    match kalshi.sell_ioc(&kalshi_ticker, "no", close_price, excess).await {
+                    let close_price = (no_price as i64).saturating_sub(unwind_cents).max(1);
+                    match kalshi.sell_ioc(&kalshi_ticker, "no", close_price, excess, OrderStrategy::Hedge).await {
                         Ok(resp) => {
                             let proceeds = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
-                            log_close_pnl("Kalshi", resp.order.filled_count(), proceeds);
+                            log_close_pnl("Kalshi", "no", resp.order.filled_count(), proceeds);
                         }
                         Err(e) => warn!("[EXEC] ⚠️ Failed to close Kalshi excess: {}", e),
                     }
@@ -546,22 +976,22 @@ impl ExecutionEngine {
             ArbType::KalshiYesPolyNo => {
                 if yes_filled > no_filled {
                     // Kalshi YES excess
-                    let close_price = (yes_price as i64).saturating_sub(10).max(1);
-                    match kalshi.sell_ioc(&kalshi_ticker, "yes", close_price, excess).await {
+                    let close_price = (yes_price as i64).saturating_sub(unwind_cents).max(1);
+                    match kalshi.sell_ioc(&kalshi_ticker, "yes", close_price, excess, OrderStrategy::Hedge).await {
                         Ok(resp) => {
                             let proceeds = resp.order.taker_fill_cost.unwrap_or(0) + resp.order.maker_fill_cost.unwrap_or(0);
-                            log_close_pnl("Kalshi", resp.order.filled_count(), proceeds);
+                            log_close_pnl("Kalshi", "yes", resp.order.filled_count(), proceeds);
                         }
                         Err(e) => warn!("[EXEC] ⚠️ Failed to close Kalshi excess: {}", e),
                     }
                 } else {
                     // Poly NO excess
-                    let close_price = cents_to_price((no_price as i16).saturating_sub(10).max(1) as u16);
+                    let close_price = cents_to_price((no_price as i16).saturating_sub(unwind_cents as i16).max(1) as u16);
                     info!("[EXEC] 🔄 Waiting 2s for Poly settlement before auto-close ({} no contracts)", excess);
                     tokio::time::sleep(Duration::from_secs(2)).await;
 
                     match poly_async.sell_fak(&poly_no_token, close_price, excess as f64).await {
-                        Ok(fill) => log_close_pnl("Poly", fill.filled_size as i64, (fill.fill_cost * 100.0) as i64),
+                        Ok(fill) => log_close_pnl("Poly", "no", units::shares_to_contracts(fill.filled_size), units::dollars_to_cents(fill.fill_cost)),
                         Err(e) => warn!("[EXEC] ⚠️ Failed to close Poly excess: {}", e),
                     }
                 }
@@ -569,7 +999,121 @@ impl ExecutionEngine {
         }
     }
 
-    #[inline(always)]
+    /// After an IOC pair that matched only part of its size, resend the rest (both legs)
+    /// while the book still shows the arb at no worse prices, for a few attempts. Runs
+    /// inside the first attempt's in-flight hold, so new detections can't double up.
+    /// Returns the contracts the retries matched.
+    pub async fn retry_partial_fill(&self, first: FastExecutionRequest, unfilled: i64) -> i64 {
+        if !self.fill_retry.enabled() || !self.mode.is_live() {
+            return 0;
+        }
+        let Some(mut plan) = RetryPlan::new(first.yes_price, first.no_price, unfilled, self.fill_retry.max_attempts) else {
+            return 0;
+        };
+        let Some(market) = self.state.get_by_id(first.market_id) else {
+            return 0;
+        };
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(self.fill_retry.window_ms);
+        let mut poll = tokio::time::interval(Duration::from_millis(self.fill_retry.poll_ms.max(1)));
+        while !plan.is_done() && tokio::time::Instant::now() < deadline {
+            poll.tick().await;
+            let (yes_price, no_price, yes_size, no_size) = leg_books(market, first.arb_type);
+            let Some(contracts) = plan.next(yes_price, no_price, yes_size, no_size) else {
+                continue;
+            };
+            info!("[EXEC] 🔁 Retrying partial fill on market_id={}: {} of {} unfilled contracts",
+                  first.market_id, contracts, plan.remaining);
+            let size = (contracts * 100).min(SizeCents::MAX as i64) as SizeCents;
+            let req = FastExecutionRequest {
+                yes_price,
+                no_price,
+                yes_size: size,
+                no_size: size,
+                detected_ns: self.clock.now_ns(),
+                ..first
+            };
+            metrics::incr("exec.fill_retry.attempts");
+            match self.execute_claimed(req, true).await {
+                Ok(result) if result.success => {
+                    let matched = contracts - result.unfilled;
+                    metrics::add("exec.fill_retry.contracts", matched as u64);
+                    plan.record(matched);
+                }
+                _ => break,
+            }
+        }
+        if plan.remaining > 0 {
+            debug!("[EXEC] market_id={}: {} contracts left unfilled after retries", first.market_id, plan.remaining);
+        }
+        unfilled - plan.remaining
+    }
+
+    /// After an arb whose size the thin side capped, watch the book for the tranche window
+    /// and take more while the thin side refills at no worse prices
+    pub async fn follow_tranches(&self, first: FastExecutionRequest) {
+        // Dry runs don't consume the book, so the "refill" would just be the same depth again
+        if !self.tranches.enabled() || !self.mode.is_live() {
+            return;
+        }
+        let Some(mut plan) = TranchePlan::new(&first, self.tranches.max_tranches) else {
+            return;
+        };
+        let Some(market) = self.state.get_by_id(first.market_id) else {
+            return;
+        };
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(self.tranches.window_ms);
+        let mut poll = tokio::time::interval(Duration::from_millis(self.tranches.poll_ms.max(1)));
+        let mut number = 1;
+        while !plan.is_done() && tokio::time::Instant::now() < deadline {
+            poll.tick().await;
+            let (yes_price, no_price, yes_size, no_size) = leg_books(market, first.arb_type);
+            let Some(contracts) = plan.next(yes_price, no_price, yes_size, no_size) else {
+                continue;
+            };
+            number += 1;
+            info!("[EXEC] 🧩 Tranche {} on market_id={}: {} contracts ({} left on the deep side)",
+                  number, first.market_id, contracts, plan.remaining);
+            let size = (contracts * 100).min(SizeCents::MAX as i64) as SizeCents;
+            let req = FastExecutionRequest {
+                yes_price,
+                no_price,
+                yes_size: size,
+                no_size: size,
+                detected_ns: self.clock.now_ns(),
+                ..first
+            };
+            match self.execute_claimed(req, true).await {
+                Ok(result) if result.success => {
+                    metrics::incr("exec.tranches");
+                    // A tranche that only partly matched gets the same retries as the first
+                    let retried = self.retry_partial_fill(req, result.unfilled).await;
+                    plan.record(contracts - result.unfilled + retried);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Add a finished request to the opportunity history (taken, or why not)
+    pub fn record_opportunity(&self, req: &FastExecutionRequest, result: Option<&ExecutionResult>, skip_reason: &str) {
+        let Some(pair) = self.state.get_by_id(req.market_id).and_then(|m| m.pair.as_ref()) else {
+            return;
+        };
+        let opp = OpportunityRecord::new(
+            &pair.league, &pair.description, &format!("{:?}", req.arb_type),
+            cents_to_price(req.yes_price), cents_to_price(req.no_price),
+            req.yes_size as f64 / 100.0, req.no_size as f64 / 100.0,
+            cents_to_price(req.estimated_fee_cents()),
+        );
+        opportunities::record(match result {
+            Some(r) if r.success => opp.taken().with_profit(r.profit_cents as f64 / 100.0),
+            Some(r) => opp.skipped(r.error.unwrap_or(skip_reason)),
+            None => opp.skipped(skip_reason),
+        });
+    }
+
     fn release_in_flight(&self, market_id: u16) {
         if market_id < 512 {
             let slot = (market_id / 64) as usize;
@@ -598,9 +1142,74 @@ impl ExecutionEngine {
 pub struct ExecutionResult {
     pub market_id: u16,
     pub success: bool,
-    pub profit_cents: i16,
+    pub profit_cents: i64,
     pub latency_ns: u64,
     pub error: Option<&'static str>,
+    /// Contracts of the intended size not matched on both legs (partial IOC fill)
+    pub unfilled: i64,
+}
+
+/// A leg the venue refused as closed / not accepting orders
+fn rejected_closed<T>(result: &Result<T>) -> bool {
+    result.as_ref().err()
+        .and_then(order_errors::rejection)
+        .is_some_and(|r| r.reason == RejectReason::MarketClosed)
+}
+
+/// Current (yes ask, no ask, yes size, no size) of the books an arb type trades
+fn leg_books(market: &AtomicMarketState, arb_type: ArbType) -> (PriceCents, PriceCents, SizeCents, SizeCents) {
+    let (k_yes, k_no, k_yes_size, k_no_size) = market.kalshi.load();
+    let (p_yes, p_no, p_yes_size, p_no_size) = market.poly.load();
+    match arb_type {
+        ArbType::PolyYesKalshiNo => (p_yes, k_no, p_yes_size, k_no_size),
+        ArbType::KalshiYesPolyNo => (k_yes, p_no, k_yes_size, p_no_size),
+        ArbType::PolyOnly => (p_yes, p_no, p_yes_size, p_no_size),
+        ArbType::KalshiOnly => (k_yes, k_no, k_yes_size, k_no_size),
+    }
+}
+
+/// Strategy name an arb type trades under (as in position files and run windows)
+/// A pair's two legs (YES slot first) as a basket at the request's limit prices
+fn basket_for(req: &FastExecutionRequest, pair: &MarketPair, contracts: i64) -> Basket {
+    let (platform1, side1, platform2, side2) = leg_venues(req.arb_type);
+    let legs = [(platform1, side1, req.yes_price), (platform2, side2, req.no_price)].into_iter()
+        .map(|(platform, side, price)| {
+            let (venue, instrument) = match (platform, side) {
+                ("kalshi", _) => (Venue::Kalshi, &pair.kalshi_market_ticker),
+                (_, "yes") => (Venue::Polymarket, &pair.poly_yes_token),
+                _ => (Venue::Polymarket, &pair.poly_no_token),
+            };
+            BasketLeg { venue, side, instrument: instrument.to_string(), price: cents_to_price(price), contracts: contracts as f64 }
+        })
+        .collect();
+    Basket::new(&pair.description, legs, 1.0)
+}
+
+/// One leg's fill: contracts, cost in cents, order id and what the exchange reported about it
+#[derive(Debug, Default)]
+struct LegFill {
+    filled: i64,
+    cost: i64,
+    order_id: String,
+    detail: FillDetail,
+}
+
+pub fn strategy_name(arb_type: ArbType) -> &'static str {
+    match arb_type {
+        ArbType::PolyYesKalshiNo | ArbType::KalshiYesPolyNo => "cross",
+        ArbType::PolyOnly => "poly_only",
+        ArbType::KalshiOnly => "kalshi_only",
+    }
+}
+
+/// (platform, side) of the YES leg and the NO leg for an arb type
+fn leg_venues(arb_type: ArbType) -> (&'static str, &'static str, &'static str, &'static str) {
+    match arb_type {
+        ArbType::PolyYesKalshiNo => ("polymarket", "yes", "kalshi", "no"),
+        ArbType::KalshiYesPolyNo => ("kalshi", "yes", "polymarket", "no"),
+        ArbType::PolyOnly => ("polymarket", "yes", "polymarket", "no"),
+        ArbType::KalshiOnly => ("kalshi", "yes", "kalshi", "no"),
+    }
 }
 
 /// Create execution channel
@@ -613,22 +1222,41 @@ pub async fn run_execution_loop(
     mut rx: mpsc::Receiver<FastExecutionRequest>,
     engine: Arc<ExecutionEngine>,
 ) {
-    info!("[EXEC] Execution engine started (dry_run={})", engine.dry_run);
+    let pool_config = ExecPoolConfig::from_env();
+    info!("[EXEC] Execution engine started (mode={}, max_concurrent={}, queue_expiry={}ms)",
+          engine.mode, pool_config.max_concurrent, pool_config.queue_expiry_ms);
+    let pool = Arc::new(ExecPool::new(pool_config));
 
     while let Some(req) = rx.recv().await {
         let engine = engine.clone();
+        let pool = pool.clone();
+        let queued_at = tokio::time::Instant::now();
 
-        // Process immediately in spawned task
+        // Process in spawned task once the pool has a free slot
         tokio::spawn(async move {
-            match engine.process(req).await {
+            let Some(_permit) = pool.acquire(queued_at).await else {
+                warn!("[EXEC] ⏳ market_id={}: dropped stale request (execution pool busy)", req.market_id);
+                engine.record_opportunity(&req, None, "Stale");
+                return;
+            };
+            let result = engine.process(req).await;
+            // Repeats of an arb already executing aren't new opportunities
+            match &result {
+                Ok(r) if r.error == Some("Already in-flight") => {}
+                Ok(r) => engine.record_opportunity(&req, Some(r), "Skipped"),
+                Err(_) => engine.record_opportunity(&req, None, "Error"),
+            }
+            match result {
                 Ok(result) if result.success => {
                     info!(
                         "[EXEC] ✅ market_id={} profit={}¢ latency={}µs",
                         result.market_id, result.profit_cents, result.latency_ns / 1000
                     );
+                    engine.retry_partial_fill(req, result.unfilled).await;
+                    engine.follow_tranches(req).await;
                 }
                 Ok(result) => {
-                    if result.error != Some("Already in-flight") {
+                    if !matches!(result.error, Some("Already in-flight") | Some("Throttled") | Some("Venue unhealthy") | Some("Below minimum size") | Some("Outside run window") | Some("Negative expected profit") | Some("Insufficient Kalshi collateral")) {
                         warn!(
                             "[EXEC] ⚠️ market_id={}: {:?}",
                             result.market_id, result.error
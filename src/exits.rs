@@ -0,0 +1,133 @@
+// src/exits.rs
+// Exit rules for unmatched directional legs (take profit / stop loss / max hold)
+
+use crate::position_tracker::ArbPosition;
+
+/// Exit configuration from environment (0 disables a rule)
+#[derive(Debug, Clone)]
+pub struct ExitConfig {
+    /// Seconds between exit checks (0 = exit management off)
+    pub check_secs: u64,
+
+    /// Sell when the bid is this far above entry (dollars per contract)
+    pub take_profit: f64,
+
+    /// Sell when the bid is this far below entry (dollars per contract)
+    pub stop_loss: f64,
+
+    /// Sell once the leg has been unmatched this long
+    pub max_hold_secs: u64,
+}
+
+impl ExitConfig {
+    pub fn from_env() -> Self {
+        Self {
+            check_secs: std::env::var("EXIT_CHECK_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            take_profit: std::env::var("EXIT_TAKE_PROFIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.20),
+
+            stop_loss: std::env::var("EXIT_STOP_LOSS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.20),
+
+            max_hold_secs: std::env::var("EXIT_MAX_HOLD_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.check_secs > 0 && (self.take_profit > 0.0 || self.stop_loss > 0.0 || self.max_hold_secs > 0)
+    }
+
+    /// Decide whether to sell a leg bought at `entry` that can be sold at `bid`
+    pub fn evaluate(&self, entry: f64, bid: f64, held_secs: u64) -> Option<ExitReason> {
+        if bid <= 0.0 {
+            return None;
+        }
+        if self.take_profit > 0.0 && bid >= entry + self.take_profit {
+            return Some(ExitReason::TakeProfit);
+        }
+        if self.stop_loss > 0.0 && bid <= entry - self.stop_loss {
+            return Some(ExitReason::StopLoss);
+        }
+        if self.max_hold_secs > 0 && held_secs >= self.max_hold_secs {
+            return Some(ExitReason::MaxHold);
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+    MaxHold,
+    /// Bid above what the interval is predicted to settle at
+    SettlePrediction,
+}
+
+impl std::fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ExitReason::TakeProfit => "take profit",
+            ExitReason::StopLoss => "stop loss",
+            ExitReason::MaxHold => "max hold",
+            ExitReason::SettlePrediction => "settle prediction",
+        })
+    }
+}
+
+/// The excess Polymarket leg of a position: (side, contracts, average entry price)
+pub fn unmatched_poly_leg(pos: &ArbPosition) -> Option<(&'static str, f64, f64)> {
+    let net = pos.net_exposure();
+    if net > 0.5 && pos.poly_yes.contracts > 0.0 {
+        Some(("yes", net.min(pos.poly_yes.contracts), pos.poly_yes.avg_price))
+    } else if net < -0.5 && pos.poly_no.contracts > 0.0 {
+        Some(("no", (-net).min(pos.poly_no.contracts), pos.poly_no.avg_price))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ExitConfig {
+        ExitConfig { check_secs: 5, take_profit: 0.15, stop_loss: 0.10, max_hold_secs: 300 }
+    }
+
+    #[test]
+    fn test_exit_rules() {
+        let cfg = config();
+        assert_eq!(cfg.evaluate(0.50, 0.66, 10), Some(ExitReason::TakeProfit));
+        assert_eq!(cfg.evaluate(0.50, 0.39, 10), Some(ExitReason::StopLoss));
+        assert_eq!(cfg.evaluate(0.50, 0.52, 301), Some(ExitReason::MaxHold));
+        assert_eq!(cfg.evaluate(0.50, 0.52, 10), None);
+        // No bid, nothing to sell into
+        assert_eq!(cfg.evaluate(0.50, 0.0, 1000), None);
+    }
+
+    #[test]
+    fn test_unmatched_poly_leg() {
+        let mut pos = ArbPosition::new("m", "m");
+        pos.poly_yes.add(10.0, 0.40);
+        pos.poly_no.add(4.0, 0.55);
+        let (side, contracts, entry) = unmatched_poly_leg(&pos).unwrap();
+        assert_eq!(side, "yes");
+        assert!((contracts - 6.0).abs() < 1e-9);
+        assert!((entry - 0.40).abs() < 1e-9);
+
+        pos.poly_no.add(6.0, 0.55);
+        assert!(unmatched_poly_leg(&pos).is_none());
+    }
+}
@@ -0,0 +1,243 @@
+// src/feed_liveness.rs
+// WS feed liveness - transport (any frame, pings / pongs included) tracked apart from market data,
+// so a connection that keeps answering pings but stopped sending books gets snapshot refreshes
+
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::metrics;
+
+/// Feed liveness configuration from environment
+#[derive(Debug, Clone)]
+pub struct LivenessConfig {
+    /// Seconds without any frame (data, ping or pong) before reconnecting
+    pub transport_timeout_secs: u64,
+
+    /// Seconds without market data on any token before refreshing every snapshot (0 = off)
+    pub data_idle_secs: u64,
+
+    /// Seconds without market data on one token before refreshing its snapshot (0 = off)
+    pub token_idle_secs: u64,
+
+    /// Feed-wide refreshes in a row that bring no data before reconnecting
+    pub max_refreshes: u32,
+}
+
+impl LivenessConfig {
+    pub fn from_env() -> Self {
+        Self {
+            transport_timeout_secs: std::env::var("WS_TRANSPORT_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(120),
+
+            data_idle_secs: std::env::var("WS_DATA_IDLE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+
+            token_idle_secs: std::env::var("WS_TOKEN_IDLE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+
+            max_refreshes: std::env::var("WS_IDLE_REFRESHES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+        }
+    }
+}
+
+/// What the feed loop should do after a liveness check
+#[derive(Debug, Clone, PartialEq)]
+pub enum Liveness {
+    Alive,
+    /// Data went quiet while the transport is up: re-request these tokens' snapshots
+    Refresh(Vec<String>),
+    /// Drop the connection (reason)
+    Reconnect(&'static str),
+}
+
+/// Liveness of one WS connection
+#[derive(Debug)]
+pub struct FeedLiveness {
+    config: LivenessConfig,
+    /// Metric prefix, e.g. "ws.polymarket"
+    feed: &'static str,
+    last_transport: Instant,
+    last_data: Instant,
+    /// Last data (or refresh request) per subscribed token
+    tokens: HashMap<String, Instant>,
+    /// Feed-wide refreshes since data last arrived
+    refreshes: u32,
+    last_refresh: Option<Instant>,
+}
+
+impl FeedLiveness {
+    /// New connection: everything counts as fresh
+    pub fn new(config: LivenessConfig, feed: &'static str, now: Instant) -> Self {
+        Self {
+            config,
+            feed,
+            last_transport: now,
+            last_data: now,
+            tokens: HashMap::new(),
+            refreshes: 0,
+            last_refresh: None,
+        }
+    }
+
+    /// Tokens subscribed on this connection (new ones start fresh, dropped ones are forgotten)
+    pub fn set_tokens<'a, I: IntoIterator<Item = &'a String>>(&mut self, tokens: I, now: Instant) {
+        let mut next = HashMap::new();
+        for token in tokens {
+            next.insert(token.clone(), self.tokens.get(token).copied().unwrap_or(now));
+        }
+        self.tokens = next;
+    }
+
+    /// Any frame arrived (ping, pong, or a message we could not parse)
+    pub fn on_transport(&mut self, now: Instant) {
+        self.last_transport = now;
+    }
+
+    /// Market data arrived for `token`
+    pub fn on_data(&mut self, token: &str, now: Instant) {
+        self.last_transport = now;
+        self.last_data = now;
+        self.refreshes = 0;
+        if let Some(at) = self.tokens.get_mut(token) {
+            *at = now;
+        }
+    }
+
+    /// Time since the last market data
+    pub fn data_age(&self, now: Instant) -> Duration {
+        now.duration_since(self.last_data)
+    }
+
+    pub fn check(&mut self, now: Instant) -> Liveness {
+        if now.duration_since(self.last_transport) > Duration::from_secs(self.config.transport_timeout_secs) {
+            metrics::incr(&format!("{}.transport_timeouts", self.feed));
+            return Liveness::Reconnect("no frames");
+        }
+
+        // Whole feed quiet while pings still flow
+        let data_idle = Duration::from_secs(self.config.data_idle_secs);
+        if self.config.data_idle_secs > 0
+            && now.duration_since(self.last_data) > data_idle
+            && self.last_refresh.is_none_or(|at| now.duration_since(at) > data_idle)
+        {
+            if self.refreshes >= self.config.max_refreshes {
+                metrics::incr(&format!("{}.data_idle_reconnects", self.feed));
+                return Liveness::Reconnect("no market data after snapshot refreshes");
+            }
+            self.refreshes += 1;
+            self.last_refresh = Some(now);
+            metrics::incr(&format!("{}.data_idle_refreshes", self.feed));
+            let mut all: Vec<String> = self.tokens.keys().cloned().collect();
+            for at in self.tokens.values_mut() {
+                *at = now;
+            }
+            all.sort();
+            return Liveness::Refresh(all);
+        }
+
+        // Single tokens quiet while the rest of the feed updates
+        if self.config.token_idle_secs > 0 {
+            let token_idle = Duration::from_secs(self.config.token_idle_secs);
+            let mut idle: Vec<String> = self.tokens.iter()
+                .filter(|(_, at)| now.duration_since(**at) > token_idle)
+                .map(|(t, _)| t.clone())
+                .collect();
+            if !idle.is_empty() {
+                for token in &idle {
+                    self.tokens.insert(token.clone(), now);
+                }
+                metrics::add(&format!("{}.token_refreshes", self.feed), idle.len() as u64);
+                idle.sort();
+                return Liveness::Refresh(idle);
+            }
+        }
+
+        Liveness::Alive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> LivenessConfig {
+        LivenessConfig { transport_timeout_secs: 120, data_idle_secs: 60, token_idle_secs: 300, max_refreshes: 2 }
+    }
+
+    fn tokens(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pongs_keep_transport_but_not_data_alive() {
+        let start = Instant::now();
+        let at = |s: u64| start + Duration::from_secs(s);
+        let mut feed = FeedLiveness::new(cfg(), "ws.test", start);
+        feed.set_tokens(&tokens(&["a", "b"]), start);
+
+        // Only pongs for 90s: transport is fine, data is not
+        for s in (30..=90).step_by(30) {
+            feed.on_transport(at(s));
+        }
+        assert_eq!(feed.check(at(90)), Liveness::Refresh(tokens(&["a", "b"])));
+        assert_eq!(feed.data_age(at(90)), Duration::from_secs(90));
+        // No second refresh until another idle period passes
+        assert_eq!(feed.check(at(120)), Liveness::Alive);
+
+        // Data resumes: the refresh count resets
+        feed.on_data("a", at(125));
+        feed.on_data("b", at(125));
+        assert_eq!(feed.check(at(150)), Liveness::Alive);
+
+        // No frames at all: reconnect
+        assert_eq!(feed.check(at(300)), Liveness::Reconnect("no frames"));
+    }
+
+    #[test]
+    fn test_reconnect_after_refreshes_bring_nothing() {
+        let start = Instant::now();
+        let at = |s: u64| start + Duration::from_secs(s);
+        let mut feed = FeedLiveness::new(cfg(), "ws.test", start);
+        feed.set_tokens(&tokens(&["a"]), start);
+
+        let mut results = Vec::new();
+        for s in (30..=270).step_by(30) {
+            feed.on_transport(at(s));
+            results.push(feed.check(at(s)));
+        }
+        let refreshes = results.iter().filter(|r| matches!(r, Liveness::Refresh(_))).count();
+        assert_eq!(refreshes, 2);
+        assert_eq!(results.last(), Some(&Liveness::Reconnect("no market data after snapshot refreshes")));
+    }
+
+    #[test]
+    fn test_single_quiet_token_refreshed() {
+        let start = Instant::now();
+        let at = |s: u64| start + Duration::from_secs(s);
+        let mut feed = FeedLiveness::new(cfg(), "ws.test", start);
+        feed.set_tokens(&tokens(&["busy", "quiet"]), start);
+
+        for s in (10..=310).step_by(10) {
+            feed.on_data("busy", at(s));
+        }
+        assert_eq!(feed.check(at(310)), Liveness::Refresh(tokens(&["quiet"])));
+        assert_eq!(feed.check(at(320)), Liveness::Alive);
+
+        // Token added later starts fresh; dropped tokens are forgotten
+        feed.set_tokens(&tokens(&["busy", "new"]), at(320));
+        feed.on_data("busy", at(600));
+        assert_eq!(feed.check(at(600)), Liveness::Alive);
+        feed.on_data("busy", at(630));
+        assert_eq!(feed.check(at(630)), Liveness::Refresh(tokens(&["new"])));
+    }
+}
@@ -0,0 +1,190 @@
+// src/fill_detail.rs
+// Fill details - exchange trade IDs, maker/taker side, matched maker orders and status transitions
+// of an order, kept on the fill so it can be reconciled exactly against the user channel and data API
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::polymarket_clob::PolymarketOrderResponse;
+use crate::polymarket_ws_types::MakerOrder;
+use crate::units;
+
+/// Which side of the match our order was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+    /// Part of the order rested and was hit later
+    Mixed,
+}
+
+/// A resting order one of our taker orders matched against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchedOrder {
+    pub order_id: String,
+    pub price: f64,
+    pub contracts: f64,
+}
+
+/// Everything the exchange told us about one order beyond its fill size and cost
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FillDetail {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liquidity: Option<Liquidity>,
+
+    /// Order statuses as seen, oldest first ("matched" on the POST, then "MATCHED" on the lookup...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<String>,
+
+    /// Exchange trade IDs (what user-channel `trade` events and the data API key on)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trade_ids: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maker_orders: Vec<MatchedOrder>,
+
+    /// Settlement transactions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tx_hashes: Vec<String>,
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    value.as_array().into_iter().flatten()
+        .filter_map(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !value.is_empty() && !list.iter().any(|v| v == value) {
+        list.push(value.to_string());
+    }
+}
+
+impl FillDetail {
+    /// From a Polymarket FAK: the POST /order response, the order lookup and the
+    /// GET /data/trades entries of its trades (empty if they weren't fetched)
+    pub fn from_polymarket(post: &Value, order: &PolymarketOrderResponse, trades: &[Value]) -> Self {
+        let mut detail = Self::default();
+        for status in [post["status"].as_str().unwrap_or_default(), order.status.as_str()] {
+            if !status.is_empty() && detail.statuses.last().is_none_or(|last| last != status) {
+                detail.statuses.push(status.to_string());
+            }
+        }
+        let associated = order.associate_trades.iter().filter_map(|t| t.as_str()).map(String::from);
+        for id in strings(&post["tradeIDs"]).into_iter().chain(associated) {
+            push_unique(&mut detail.trade_ids, &id);
+        }
+        for hash in strings(&post["transactionsHashes"]) {
+            push_unique(&mut detail.tx_hashes, &hash);
+        }
+
+        let mut sides = Vec::new();
+        for trade in trades {
+            if let Some(hash) = trade["transaction_hash"].as_str() {
+                push_unique(&mut detail.tx_hashes, hash);
+            }
+            if let Some(side) = trade["trader_side"].as_str() {
+                sides.push(side.eq_ignore_ascii_case("MAKER"));
+            }
+            let makers: Vec<MakerOrder> = serde_json::from_value(trade["maker_orders"].clone()).unwrap_or_default();
+            detail.maker_orders.extend(makers.into_iter()
+                .filter(|m| m.order_id != order.id)
+                .map(|m| MatchedOrder {
+                    price: units::parse_amount(&m.price).unwrap_or(0.0),
+                    contracts: units::parse_amount(&m.matched_amount).unwrap_or(0.0),
+                    order_id: m.order_id,
+                }));
+        }
+        let filled = units::parse_amount(&order.size_matched).unwrap_or(0.0) > 0.0;
+        detail.liquidity = match (sides.iter().any(|m| *m), sides.iter().any(|m| !*m)) {
+            (true, true) => Some(Liquidity::Mixed),
+            (true, false) => Some(Liquidity::Maker),
+            (false, true) => Some(Liquidity::Taker),
+            // A FAK never rests, so whatever it filled it took
+            (false, false) => filled.then_some(Liquidity::Taker),
+        };
+        detail
+    }
+
+    /// From Kalshi's taker / maker fill counts and the order's status
+    pub fn from_kalshi(status: &str, taker_fills: i64, maker_fills: i64) -> Self {
+        Self {
+            liquidity: match (taker_fills > 0, maker_fills > 0) {
+                (true, true) => Some(Liquidity::Mixed),
+                (true, false) => Some(Liquidity::Taker),
+                (false, true) => Some(Liquidity::Maker),
+                (false, false) => None,
+            },
+            statuses: if status.is_empty() { Vec::new() } else { vec![status.to_string()] },
+            ..Self::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn order(status: &str, matched: &str, trades: &[&str]) -> PolymarketOrderResponse {
+        serde_json::from_value(json!({
+            "id": "0xours", "status": status, "price": "0.48", "side": "BUY",
+            "size_matched": matched, "original_size": "10",
+            "associate_trades": trades, "type": "FAK",
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_polymarket_fill_with_trades() {
+        let post = json!({
+            "success": true, "orderID": "0xours", "status": "matched",
+            "transactionsHashes": ["0xtx1"], "tradeIDs": ["t1"],
+        });
+        let trade = json!({
+            "id": "t1", "taker_order_id": "0xours", "trader_side": "TAKER", "status": "MATCHED",
+            "transaction_hash": "0xtx2",
+            "maker_orders": [
+                {"asset_id": "1", "matched_amount": "6", "order_id": "0xm1", "outcome": "Up", "owner": "o", "price": "0.47"},
+                {"asset_id": "1", "matched_amount": "4", "order_id": "0xm2", "outcome": "Up", "owner": "o", "price": "0.48"},
+            ],
+        });
+        let detail = FillDetail::from_polymarket(&post, &order("MATCHED", "10", &["t1", "t2"]), &[trade]);
+        assert_eq!(detail.statuses, vec!["matched", "MATCHED"]);
+        assert_eq!(detail.trade_ids, vec!["t1", "t2"]);
+        assert_eq!(detail.tx_hashes, vec!["0xtx1", "0xtx2"]);
+        assert_eq!(detail.liquidity, Some(Liquidity::Taker));
+        assert_eq!(detail.maker_orders[0], MatchedOrder { order_id: "0xm1".into(), price: 0.47, contracts: 6.0 });
+        assert_eq!(detail.maker_orders.len(), 2);
+    }
+
+    #[test]
+    fn test_polymarket_fill_without_trades() {
+        // Trades not fetched: a FAK that filled took liquidity; an empty one has no side
+        let post = json!({"success": true, "orderID": "0xours", "status": "matched"});
+        let filled = FillDetail::from_polymarket(&post, &order("matched", "10", &[]), &[]);
+        assert_eq!(filled.statuses, vec!["matched"]);
+        assert_eq!(filled.liquidity, Some(Liquidity::Taker));
+
+        let empty = FillDetail::from_polymarket(&json!({}), &order("CANCELED", "0", &[]), &[]);
+        assert_eq!((empty.liquidity, empty.statuses), (None, vec!["CANCELED".to_string()]));
+    }
+
+    #[test]
+    fn test_kalshi_liquidity_and_serialization() {
+        assert_eq!(FillDetail::from_kalshi("executed", 5, 0).liquidity, Some(Liquidity::Taker));
+        assert_eq!(FillDetail::from_kalshi("executed", 3, 2).liquidity, Some(Liquidity::Mixed));
+        assert_eq!(FillDetail::from_kalshi("canceled", 0, 0).liquidity, None);
+
+        // Empty lists stay out of positions.json, and old records without them still load
+        let json = serde_json::to_value(FillDetail::from_kalshi("executed", 5, 0)).unwrap();
+        assert_eq!(json, json!({"liquidity": "taker", "statuses": ["executed"]}));
+        assert!(serde_json::from_value::<FillDetail>(json!({})).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+// src/fill_retry.rs
+// Partial-fill retry - resend the unmatched size while the book still shows the arb
+
+use crate::types::{PriceCents, SizeCents};
+
+/// Partial-fill retry configuration from environment
+#[derive(Debug, Clone)]
+pub struct FillRetryConfig {
+    /// Retries of the unfilled size after a partial IOC fill (0 = off)
+    pub max_attempts: u32,
+
+    /// How long after the partial fill the arb may still be retried
+    pub window_ms: u64,
+
+    /// How often the book is re-checked during the window
+    pub poll_ms: u64,
+}
+
+impl FillRetryConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: std::env::var("FILL_RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+
+            window_ms: std::env::var("FILL_RETRY_WINDOW_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1000),
+
+            poll_ms: std::env::var("FILL_RETRY_POLL_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_attempts > 0 && self.window_ms > 0
+    }
+}
+
+/// Size an arb meant to take but didn't get matched on both legs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPlan {
+    /// Prices of the original request; a retry never pays more on either leg
+    pub yes_limit: PriceCents,
+    pub no_limit: PriceCents,
+    /// Contracts still unmatched
+    pub remaining: i64,
+    pub attempts_left: u32,
+}
+
+impl RetryPlan {
+    /// Plan for a fill that left `unfilled` contracts of the intended size unmatched
+    pub fn new(yes_price: PriceCents, no_price: PriceCents, unfilled: i64, max_attempts: u32) -> Option<Self> {
+        if max_attempts == 0 || unfilled < 1 {
+            return None;
+        }
+        Some(Self {
+            yes_limit: yes_price,
+            no_limit: no_price,
+            remaining: unfilled,
+            attempts_left: max_attempts,
+        })
+    }
+
+    /// Contracts to retry against the current book: None once either ask moved above
+    /// the original price (the arb is gone) or either side has no whole contract left
+    pub fn next(&self, yes_price: PriceCents, no_price: PriceCents, yes_size: SizeCents, no_size: SizeCents) -> Option<i64> {
+        if self.attempts_left == 0 || yes_price == 0 || no_price == 0
+            || yes_price > self.yes_limit || no_price > self.no_limit {
+            return None;
+        }
+        let size = ((yes_size.min(no_size) / 100) as i64).min(self.remaining);
+        (size >= 1).then_some(size)
+    }
+
+    /// A retry matched `matched` of its contracts
+    pub fn record(&mut self, matched: i64) {
+        self.remaining -= matched.max(0);
+        self.attempts_left = self.attempts_left.saturating_sub(1);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.attempts_left == 0 || self.remaining < 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_only_for_unfilled_size() {
+        // 100 intended, 60 matched
+        let plan = RetryPlan::new(40, 55, 40, 2).unwrap();
+        assert_eq!(plan.remaining, 40);
+        assert!(RetryPlan::new(40, 55, 0, 2).is_none());
+        assert!(RetryPlan::new(40, 55, 40, 0).is_none());
+    }
+
+    #[test]
+    fn test_retry_only_while_arb_persists() {
+        let plan = RetryPlan::new(40, 55, 40, 2).unwrap();
+        // Price moved up on one leg, or a leg's book emptied
+        assert_eq!(plan.next(41, 55, 10_000, 10_000), None);
+        assert_eq!(plan.next(40, 0, 10_000, 10_000), None);
+        assert_eq!(plan.next(40, 55, 10_000, 50), None);
+        // Still there (cheaper is fine), capped at the remaining size and the thinner side
+        assert_eq!(plan.next(40, 54, 10_000, 10_000), Some(40));
+        assert_eq!(plan.next(40, 55, 2_500, 10_000), Some(25));
+    }
+
+    #[test]
+    fn test_attempts_run_out() {
+        let mut plan = RetryPlan::new(40, 55, 40, 2).unwrap();
+        plan.record(10);
+        assert_eq!(plan.remaining, 30);
+        assert!(!plan.is_done());
+        plan.record(0);
+        assert!(plan.is_done());
+        assert_eq!(plan.next(40, 55, 10_000, 10_000), None);
+
+        let mut plan = RetryPlan::new(40, 55, 40, 3).unwrap();
+        plan.record(40);
+        assert!(plan.is_done());
+    }
+}
@@ -0,0 +1,221 @@
+// src/gamma.rs
+// Gamma API paging - offset / cursor pagination with bounded concurrency, request caps and body limits
+
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::metrics;
+
+/// Cursor value marking the last page (CLOB-style keyset endpoints)
+const END_CURSOR: &str = "LTE=";
+
+/// Gamma paging configuration from environment
+#[derive(Debug, Clone)]
+pub struct GammaPageConfig {
+    /// Items requested per page (`limit`)
+    pub page_size: usize,
+
+    /// Offset pages fetched at once
+    pub concurrency: usize,
+
+    /// Requests one listing may make before it is cut short
+    pub max_requests: usize,
+
+    /// Largest response body accepted per page
+    pub max_body_bytes: usize,
+}
+
+impl GammaPageConfig {
+    pub fn from_env() -> Self {
+        Self {
+            page_size: std::env::var("GAMMA_PAGE_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+
+            concurrency: std::env::var("GAMMA_PAGE_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+
+            max_requests: std::env::var("GAMMA_MAX_REQUESTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20),
+
+            max_body_bytes: std::env::var("GAMMA_MAX_BODY_MB")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(16) * 1024 * 1024,
+        }
+    }
+}
+
+/// One page: a bare array (offset paging) or a keyset page with the cursor of the next one
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PageBody<T> {
+    List(Vec<T>),
+    Cursor {
+        data: Vec<T>,
+        #[serde(default)]
+        next_cursor: Option<String>,
+    },
+}
+
+/// Every item of a listing, and whether the request cap cut it short
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    pub requests: usize,
+    pub truncated: bool,
+}
+
+/// Offsets of the next batch of pages: up to `concurrency` of them, within the requests left
+fn next_offsets(from: usize, page_size: usize, concurrency: usize, requests_left: usize) -> Vec<usize> {
+    (0..concurrency.max(1).min(requests_left))
+        .map(|i| from + i * page_size)
+        .collect()
+}
+
+fn next_cursor(cursor: Option<String>) -> Option<String> {
+    cursor.filter(|c| !c.is_empty() && c != END_CURSOR)
+}
+
+fn with_params(url: &str, params: &str) -> String {
+    format!("{}{}{}", url, if url.contains('?') { '&' } else { '?' }, params)
+}
+
+/// Decode a page body, refusing bodies over `max_bytes`
+fn decode<T: DeserializeOwned>(body: &[u8], max_bytes: usize) -> Result<PageBody<T>> {
+    if body.len() > max_bytes {
+        bail!("Gamma response of {} bytes exceeds the {} byte limit", body.len(), max_bytes);
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+async fn fetch_page<T: DeserializeOwned>(http: &reqwest::Client, url: &str, max_bytes: usize) -> Result<PageBody<T>> {
+    metrics::incr("gamma.requests");
+    let mut resp = http.get(url).send().await?.error_for_status()?;
+    if resp.content_length().is_some_and(|len| len as usize > max_bytes) {
+        bail!("Gamma response of {} bytes exceeds the {} byte limit", resp.content_length().unwrap_or(0), max_bytes);
+    }
+    // Chunked responses carry no length: read chunk by chunk and stop at the limit
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        append_capped(&mut body, &chunk, max_bytes)?;
+    }
+    decode(&body, max_bytes)
+}
+
+/// Append a received chunk, failing once the body would pass `max_bytes`
+fn append_capped(body: &mut Vec<u8>, chunk: &[u8], max_bytes: usize) -> Result<()> {
+    if body.len() + chunk.len() > max_bytes {
+        bail!("Gamma response over the {} byte limit after {} bytes", max_bytes, body.len() + chunk.len());
+    }
+    body.extend_from_slice(chunk);
+    Ok(())
+}
+
+/// Fetch every page of a Gamma listing (`url` with its filters, without limit / offset).
+/// Offset-paged endpoints are fetched `concurrency` pages at a time until a short page;
+/// keyset endpoints follow `next_cursor`. Stops at `max_requests` and flags the result.
+pub async fn fetch_all<T: DeserializeOwned>(http: &reqwest::Client, url: &str, config: &GammaPageConfig) -> Result<Paged<T>> {
+    let page_size = config.page_size.max(1);
+    let page_url = |params: String| with_params(url, &format!("limit={}&{}", page_size, params));
+    let mut paged = Paged { items: Vec::new(), requests: 1, truncated: false };
+
+    match fetch_page::<T>(http, &page_url("offset=0".into()), config.max_body_bytes).await? {
+        PageBody::Cursor { data, next_cursor: cursor } => {
+            paged.items.extend(data);
+            let mut cursor = next_cursor(cursor);
+            while let Some(c) = cursor.take() {
+                if paged.requests >= config.max_requests {
+                    paged.truncated = true;
+                    break;
+                }
+                paged.requests += 1;
+                match fetch_page::<T>(http, &page_url(format!("next_cursor={}", c)), config.max_body_bytes).await? {
+                    PageBody::Cursor { data, next_cursor: c } => {
+                        paged.items.extend(data);
+                        cursor = next_cursor(c);
+                    }
+                    PageBody::List(data) => paged.items.extend(data),
+                }
+            }
+        }
+        PageBody::List(data) => {
+            let mut full = data.len() >= page_size;
+            paged.items.extend(data);
+            let mut offset = page_size;
+            while full {
+                let offsets = next_offsets(offset, page_size, config.concurrency, config.max_requests.saturating_sub(paged.requests));
+                if offsets.is_empty() {
+                    paged.truncated = true;
+                    break;
+                }
+                paged.requests += offsets.len();
+                offset += offsets.len() * page_size;
+                let urls: Vec<String> = offsets.iter().map(|o| page_url(format!("offset={}", o))).collect();
+                let pages = futures_util::future::join_all(urls.iter()
+                    .map(|u| fetch_page::<T>(http, u, config.max_body_bytes))).await;
+                for page in pages {
+                    let data = match page? {
+                        PageBody::List(data) | PageBody::Cursor { data, .. } => data,
+                    };
+                    full = data.len() >= page_size;
+                    paged.items.extend(data);
+                    if !full {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    if paged.truncated {
+        metrics::incr("gamma.truncated");
+        warn!("[GAMMA] {} cut short after {} requests ({} items) - raise GAMMA_MAX_REQUESTS", url, paged.requests, paged.items.len());
+    }
+    Ok(paged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_bounded_by_concurrency_and_cap() {
+        assert_eq!(next_offsets(100, 100, 4, 20), vec![100, 200, 300, 400]);
+        assert_eq!(next_offsets(100, 100, 4, 2), vec![100, 200]);
+        assert!(next_offsets(100, 100, 4, 0).is_empty());
+        // Concurrency 0 still makes progress one page at a time
+        assert_eq!(next_offsets(0, 50, 0, 5), vec![0]);
+    }
+
+    #[test]
+    fn test_decode_list_and_cursor_pages() {
+        let list: PageBody<u32> = decode(b"[1,2,3]", 1024).unwrap();
+        assert!(matches!(list, PageBody::List(v) if v == vec![1, 2, 3]));
+
+        let page: PageBody<u32> = decode(br#"{"data":[4],"next_cursor":"MTAw"}"#, 1024).unwrap();
+        let PageBody::Cursor { data, next_cursor: cursor } = page else { panic!("expected cursor page") };
+        assert_eq!((data, next_cursor(cursor)), (vec![4], Some("MTAw".to_string())));
+        // End-of-listing markers
+        assert_eq!(next_cursor(Some(END_CURSOR.into())), None);
+        assert_eq!(next_cursor(Some(String::new())), None);
+    }
+
+    #[test]
+    fn test_large_bodies_refused() {
+        assert!(decode::<u32>(b"[1,2,3]", 4).is_err());
+        let mut body = Vec::new();
+        append_capped(&mut body, b"[1,", 5).unwrap();
+        assert!(append_capped(&mut body, b"2,3]", 5).is_err());
+        assert_eq!(body, b"[1,");
+        assert_eq!(with_params("https://g/events?series_id=1", "limit=5"), "https://g/events?series_id=1&limit=5");
+        assert_eq!(with_params("https://g/markets", "limit=5"), "https://g/markets?limit=5");
+    }
+}
@@ -0,0 +1,223 @@
+// src/instruments.rs
+// Persistent instrument cache - Up/Down token ids, tick and min sizes and fee rates by slug, validated on load
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::app::env_flag;
+use crate::storage;
+use crate::updown_scanner::ActiveUpDownMarket;
+
+/// Bumped when the stored fields change; other versions are discarded on load
+const VERSION: u32 = 2;
+
+/// Instrument cache configuration from environment
+#[derive(Debug, Clone)]
+pub struct InstrumentConfig {
+    /// Persist instrument metadata across restarts (INSTRUMENT_CACHE)
+    pub persist: bool,
+
+    /// Cache file (under DATA_DIR)
+    pub file: PathBuf,
+}
+
+impl InstrumentConfig {
+    pub fn from_env() -> Self {
+        Self {
+            persist: env_flag("INSTRUMENT_CACHE", true),
+
+            file: storage::data_path(std::env::var("INSTRUMENT_CACHE_FILE")
+                .unwrap_or_else(|_| "instruments.json".to_string())),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.persist
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    instruments: Vec<ActiveUpDownMarket>,
+}
+
+/// Reject entries a bad write or a hand edit could have left behind
+fn validate(m: &ActiveUpDownMarket) -> Result<()> {
+    let is_token = |t: &str| !t.is_empty() && t.chars().all(|c| c.is_ascii_digit());
+    if m.asset.is_empty() || !m.slug.starts_with(&format!("{}-", m.asset)) {
+        bail!("slug {} doesn't belong to asset {:?}", m.slug, m.asset);
+    }
+    if !m.slug.ends_with(&format!("-{}", m.end_timestamp)) {
+        bail!("slug {} doesn't end at {}", m.slug, m.end_timestamp);
+    }
+    if !is_token(&m.yes_token) || !is_token(&m.no_token) || m.yes_token == m.no_token {
+        bail!("{}: malformed token ids", m.slug);
+    }
+    if !(m.tick_size > 0.0 && m.tick_size <= 0.1 && m.min_size >= 0.0 && m.min_size.is_finite()) {
+        bail!("{}: tick {} / min size {} out of range", m.slug, m.tick_size, m.min_size);
+    }
+    if m.fee_rate_bps > 10_000 {
+        bail!("{}: fee rate {}bps out of range", m.slug, m.fee_rate_bps);
+    }
+    Ok(())
+}
+
+/// Valid entries for intervals that haven't ended, and how many were dropped
+fn parse(contents: &str, now: u64) -> Result<(HashMap<String, ActiveUpDownMarket>, usize)> {
+    let file: CacheFile = serde_json::from_str(contents)?;
+    if file.version != VERSION {
+        bail!("version {} (expected {})", file.version, VERSION);
+    }
+    let total = file.instruments.len();
+    let entries: HashMap<String, ActiveUpDownMarket> = file.instruments.into_iter()
+        .filter(|m| m.end_timestamp > now)
+        .filter(|m| match validate(m) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("[INSTRUMENTS] Dropping cached entry: {}", e);
+                false
+            }
+        })
+        .map(|m| (m.slug.clone(), m))
+        .collect();
+    let dropped = total - entries.len();
+    Ok((entries, dropped))
+}
+
+/// Instrument metadata by slug, persisted so a restart doesn't wait on Gamma
+pub struct InstrumentCache {
+    file: PathBuf,
+    entries: Mutex<HashMap<String, ActiveUpDownMarket>>,
+}
+
+impl InstrumentCache {
+    /// Load the cache file; a missing, unreadable or other-version file starts empty
+    pub fn load(config: &InstrumentConfig, now: u64) -> Self {
+        let entries = match std::fs::read_to_string(&config.file) {
+            Ok(contents) => match parse(&contents, now) {
+                Ok((entries, dropped)) => {
+                    info!("[INSTRUMENTS] Loaded {} cached instruments ({} expired or invalid dropped)", entries.len(), dropped);
+                    entries
+                }
+                Err(e) => {
+                    warn!("[INSTRUMENTS] Ignoring {}: {}", config.file.display(), e);
+                    HashMap::new()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                warn!("[INSTRUMENTS] Can't read {}: {}", config.file.display(), e);
+                HashMap::new()
+            }
+        };
+        Self { file: config.file.clone(), entries: Mutex::new(entries) }
+    }
+
+    /// Cached instrument for `slug` if its interval hasn't ended
+    pub fn get(&self, slug: &str, now: u64) -> Option<ActiveUpDownMarket> {
+        self.entries.lock().unwrap().get(slug).filter(|m| m.end_timestamp > now).cloned()
+    }
+
+    /// Remember an instrument; returns true if it was new or changed
+    pub fn insert(&self, market: &ActiveUpDownMarket) -> bool {
+        if let Err(e) = validate(market) {
+            warn!("[INSTRUMENTS] Not caching: {}", e);
+            return false;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.get(&market.slug) == Some(market) {
+            return false;
+        }
+        entries.insert(market.slug.clone(), market.clone());
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop ended intervals and write the rest
+    pub fn save(&self, now: u64) -> Result<()> {
+        let instruments: Vec<ActiveUpDownMarket> = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|_, m| m.end_timestamp > now);
+            entries.values().cloned().collect()
+        };
+        let contents = serde_json::to_vec_pretty(&CacheFile { version: VERSION, instruments })?;
+        storage::write_atomic(&self.file, &contents).with_context(|| format!("writing {}", self.file.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(asset: &str, end: u64) -> ActiveUpDownMarket {
+        ActiveUpDownMarket {
+            slug: format!("{}-updown-15m-{}", asset, end),
+            asset: asset.into(),
+            question: "Up or Down".into(),
+            yes_token: "1234".into(),
+            no_token: "5678".into(),
+            end_timestamp: end,
+            tick_size: 0.01,
+            min_size: 5.0,
+            fee_rate_bps: 0,
+        }
+    }
+
+    fn file(instruments: Vec<ActiveUpDownMarket>) -> String {
+        serde_json::to_string(&CacheFile { version: VERSION, instruments }).unwrap()
+    }
+
+    #[test]
+    fn test_load_keeps_live_valid_entries() {
+        let bad_tokens = ActiveUpDownMarket { no_token: "1234".into(), ..market("eth", 2_000) };
+        let wrong_slug = ActiveUpDownMarket { slug: "sol-updown-15m-1900".into(), ..market("sol", 2_000) };
+        let bad_fee = ActiveUpDownMarket { fee_rate_bps: 20_000, ..market("doge", 2_000) };
+        let (entries, dropped) = parse(&file(vec![market("btc", 2_000), market("xrp", 1_000), bad_tokens, wrong_slug, bad_fee]), 1_500).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(dropped, 4);
+        assert_eq!(entries["btc-updown-15m-2000"].min_size, 5.0);
+    }
+
+    #[test]
+    fn test_other_versions_and_garbage_rejected() {
+        // Version 1 entries predate fee rates: refetched rather than read as fee-free
+        for version in [0, 1] {
+            let old = serde_json::json!({ "version": version, "instruments": [] }).to_string();
+            assert!(parse(&old, 0).is_err());
+        }
+        assert!(parse("{\"version\": 2, \"instruments\": [{\"slug\": 3}]}", 0).is_err());
+        assert!(parse("not json", 0).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_through_file() {
+        let dir = std::env::temp_dir().join(format!("instruments_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = InstrumentConfig { persist: true, file: dir.join("instruments.json") };
+
+        let cache = InstrumentCache::load(&config, 1_000);
+        assert!(cache.is_empty());
+        assert!(cache.insert(&market("btc", 2_000)));
+        assert!(!cache.insert(&market("btc", 2_000)));
+        assert!(cache.insert(&market("eth", 1_200)));
+        cache.save(1_100).unwrap();
+
+        let reloaded = InstrumentCache::load(&config, 1_500);
+        assert_eq!(reloaded.get("btc-updown-15m-2000", 1_500), Some(market("btc", 2_000)));
+        assert_eq!(reloaded.get("eth-updown-15m-1200", 1_500), None);
+        assert_eq!(reloaded.get("btc-updown-15m-2000", 2_000), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+// src/intents.rs
+// Crash-safe order intents - journal every order before it is sent, reconcile on restart
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::kalshi::KalshiApiClient;
+#[cfg(feature = "kalshi")]
+use crate::order_tags::{self, OrderStrategy};
+use crate::polymarket_clob::SharedAsyncClient;
+use crate::position_tracker::{FillRecord, PositionChannel};
+use crate::storage;
+use crate::units;
+
+/// Default journal file (override with INTENT_FILE)
+pub const DEFAULT_INTENT_FILE: &str = "inflight_intents.json";
+
+/// How far below the entry price leftover exposure is flattened (cents)
+const FLATTEN_DISCOUNT_CENTS: f64 = 10.0;
+
+/// One order we are about to send. The intent ID is what the exchange knows the
+/// order by: the Kalshi client_order_id, or the Polymarket order hash.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrderIntent {
+    pub intent_id: String,
+    /// Legs of the same arb share a group
+    pub group_id: String,
+    /// "kalshi" or "polymarket"
+    pub platform: String,
+    pub market_id: String,
+    pub description: String,
+    /// Kalshi ticker or Polymarket token ID
+    pub instrument: String,
+    /// "yes" or "no"
+    pub side: String,
+    /// Limit price (0-1)
+    pub price: f64,
+    pub contracts: f64,
+    /// Unix seconds when the intent was written
+    pub created_ts: i64,
+}
+
+impl OrderIntent {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        intent_id: &str,
+        group_id: &str,
+        platform: &str,
+        market_id: &str,
+        description: &str,
+        instrument: &str,
+        side: &str,
+        price: f64,
+        contracts: f64,
+    ) -> Self {
+        Self {
+            intent_id: intent_id.to_string(),
+            group_id: group_id.to_string(),
+            platform: platform.to_string(),
+            market_id: market_id.to_string(),
+            description: description.to_string(),
+            instrument: instrument.to_string(),
+            side: side.to_string(),
+            price,
+            contracts,
+            created_ts: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Unique group ID for the legs of one execution
+pub fn new_group_id(market_id: &str) -> String {
+    format!("{}-{}", market_id, chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+}
+
+/// Journal of intents whose outcome hasn't been recorded yet.
+/// Every change is written through to disk before returning.
+pub struct IntentJournal {
+    path: PathBuf,
+    intents: Mutex<Vec<OrderIntent>>,
+}
+
+impl IntentJournal {
+    /// Open the journal at INTENT_FILE (default `inflight_intents.json`)
+    pub fn from_env() -> Self {
+        let path = std::env::var("INTENT_FILE").unwrap_or_else(|_| DEFAULT_INTENT_FILE.to_string());
+        Self::open(storage::state_path(path))
+    }
+
+    /// Open (or start) a journal, loading intents left by a previous run
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let intents = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("[INTENT] Failed to parse {}: {} - starting empty", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Self { path, intents: Mutex::new(intents) }
+    }
+
+    /// Persist an intent; the order must not be sent if this fails
+    pub fn record(&self, intent: OrderIntent) -> Result<()> {
+        let mut intents = self.intents.lock().unwrap();
+        intents.push(intent);
+        self.flush(&intents)
+    }
+
+    /// Forget an intent once its fill (or non-fill) has been recorded
+    pub fn complete(&self, intent_id: &str) {
+        let mut intents = self.intents.lock().unwrap();
+        let before = intents.len();
+        intents.retain(|i| i.intent_id != intent_id);
+        if intents.len() != before {
+            if let Err(e) = self.flush(&intents) {
+                warn!("[INTENT] Failed to persist completion of {}: {}", intent_id, e);
+            }
+        }
+    }
+
+    /// Forget every leg of an execution
+    pub fn complete_group(&self, group_id: &str) {
+        let mut intents = self.intents.lock().unwrap();
+        let before = intents.len();
+        intents.retain(|i| i.group_id != group_id);
+        if intents.len() != before {
+            if let Err(e) = self.flush(&intents) {
+                warn!("[INTENT] Failed to persist completion of group {}: {}", group_id, e);
+            }
+        }
+    }
+
+    /// Intents still outstanding
+    pub fn pending(&self) -> Vec<OrderIntent> {
+        self.intents.lock().unwrap().clone()
+    }
+
+    /// Write to a temp file and rename, so a crash mid-write never leaves a torn journal
+    fn flush(&self, intents: &[OrderIntent]) -> Result<()> {
+        storage::write_atomic(&self.path, serde_json::to_string_pretty(intents)?.as_bytes())
+    }
+}
+
+/// What the exchange says happened to an intent
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredLeg {
+    pub intent: OrderIntent,
+    pub filled: f64,
+    /// Average fill price (0-1)
+    pub price: f64,
+    pub order_id: String,
+}
+
+/// Leftover exposure in a group after recovery: (leg index to sell from, contracts)
+pub fn flatten_plan(legs: &[RecoveredLeg]) -> Option<(usize, f64)> {
+    let yes: f64 = legs.iter().filter(|l| l.intent.side == "yes").map(|l| l.filled).sum();
+    let no: f64 = legs.iter().filter(|l| l.intent.side == "no").map(|l| l.filled).sum();
+    let excess = yes - no;
+    if excess.abs() < 1.0 {
+        return None;
+    }
+    let heavy_side = if excess > 0.0 { "yes" } else { "no" };
+    legs.iter()
+        .enumerate()
+        .filter(|(_, l)| l.intent.side == heavy_side && l.filled > 0.0)
+        .max_by(|a, b| a.1.filled.total_cmp(&b.1.filled))
+        .map(|(idx, l)| (idx, excess.abs().min(l.filled).floor()))
+}
+
+/// Ask the exchange what happened to one intent
+async fn query_leg(
+    intent: &OrderIntent,
+    kalshi: Option<&KalshiApiClient>,
+    poly: Option<&SharedAsyncClient>,
+) -> Result<RecoveredLeg> {
+    let (filled, price, order_id) = match intent.platform.as_str() {
+        #[cfg(feature = "kalshi")]
+        "kalshi" => {
+            let client = kalshi.ok_or_else(|| anyhow::anyhow!("no Kalshi client for recovery"))?;
+            // Small margin for clock skew between us and the exchange
+            match client.find_order_by_client_id(&intent.instrument, &intent.intent_id, intent.created_ts - 60).await? {
+                Some(order) => {
+                    if let Some(tag) = order.client_order_id.as_deref().and_then(order_tags::parse) {
+                        info!("[RECOVERY] {} placed by {} (session {})", intent.intent_id, tag.strategy.name(), tag.session);
+                    }
+                    let filled = order.filled_count() as f64;
+                    let cost = order.taker_fill_cost.unwrap_or(0) + order.maker_fill_cost.unwrap_or(0);
+                    let price = if filled > 0.0 { cost as f64 / 100.0 / filled } else { intent.price };
+                    (filled, price, order.order_id)
+                }
+                None => (0.0, intent.price, intent.intent_id.clone()),
+            }
+        }
+        "polymarket" => {
+            let client = poly.ok_or_else(|| anyhow::anyhow!("no Polymarket client for recovery"))?;
+            match client.find_order(&intent.intent_id).await? {
+                Some(order) => {
+                    let filled = units::parse_amount(&order.size_matched).unwrap_or(0.0);
+                    let price = units::parse_amount(&order.price).unwrap_or(intent.price);
+                    (filled, price, order.id)
+                }
+                None => (0.0, intent.price, intent.intent_id.clone()),
+            }
+        }
+        #[cfg(not(feature = "kalshi"))]
+        "kalshi" => {
+            let _ = kalshi;
+            anyhow::bail!("built without the kalshi feature")
+        }
+        other => anyhow::bail!("unknown platform '{}'", other),
+    };
+
+    Ok(RecoveredLeg { intent: intent.clone(), filled, price, order_id })
+}
+
+/// Sell leftover contracts from one leg at a discount to its entry
+async fn flatten(
+    leg: &RecoveredLeg,
+    contracts: f64,
+    kalshi: Option<&KalshiApiClient>,
+    poly: Option<&SharedAsyncClient>,
+) -> Result<f64> {
+    let entry_cents = (leg.price * 100.0).round();
+    let close_cents = (entry_cents - FLATTEN_DISCOUNT_CENTS).max(1.0);
+    match leg.intent.platform.as_str() {
+        #[cfg(feature = "kalshi")]
+        "kalshi" => {
+            let client = kalshi.ok_or_else(|| anyhow::anyhow!("no Kalshi client"))?;
+            let resp = client.sell_ioc(&leg.intent.instrument, &leg.intent.side, close_cents as i64, units::shares_to_contracts(contracts), OrderStrategy::Recovery).await?;
+            Ok(resp.order.filled_count() as f64)
+        }
+        #[cfg(not(feature = "kalshi"))]
+        "kalshi" => {
+            let _ = kalshi;
+            anyhow::bail!("built without the kalshi feature")
+        }
+        _ => {
+            let client = poly.ok_or_else(|| anyhow::anyhow!("no Polymarket client"))?;
+            let fill = client.sell_fak(&leg.intent.instrument, close_cents / 100.0, contracts).await?;
+            Ok(fill.filled_size)
+        }
+    }
+}
+
+/// Reconcile intents left by a crashed run: record any fills the exchange reports,
+/// flatten unmatched exposure, then clear the journal. Groups that can't be queried
+/// stay in the journal for the next start.
+pub async fn recover(
+    journal: &IntentJournal,
+    kalshi: Option<&KalshiApiClient>,
+    poly: Option<&SharedAsyncClient>,
+    position_channel: &PositionChannel,
+) {
+    let pending = journal.pending();
+    if pending.is_empty() {
+        return;
+    }
+    warn!("[INTENT] Found {} unreconciled order intents from a previous run", pending.len());
+
+    let mut groups: Vec<(String, Vec<OrderIntent>)> = Vec::new();
+    for intent in pending {
+        match groups.iter_mut().find(|(g, _)| *g == intent.group_id) {
+            Some((_, legs)) => legs.push(intent),
+            None => groups.push((intent.group_id.clone(), vec![intent])),
+        }
+    }
+
+    for (group_id, intents) in groups {
+        let mut legs = Vec::with_capacity(intents.len());
+        let mut failed = false;
+        for intent in &intents {
+            match query_leg(intent, kalshi, poly).await {
+                Ok(leg) => legs.push(leg),
+                Err(e) => {
+                    error!("[INTENT] Cannot query {} {} ({}): {} - will retry on next start",
+                           intent.platform, intent.intent_id, intent.description, e);
+                    failed = true;
+                }
+            }
+        }
+        if failed {
+            continue;
+        }
+
+        for leg in &legs {
+            info!("[INTENT] {} | {} {} {} → filled {:.2}/{:.2}",
+                  leg.intent.description, leg.intent.platform, leg.intent.side,
+                  leg.order_id, leg.filled, leg.intent.contracts);
+            if leg.filled > 0.0 {
+                position_channel.record_fill(FillRecord::new(
+                    &leg.intent.market_id, &leg.intent.description, &leg.intent.platform,
+                    &leg.intent.side, leg.filled, leg.price, 0.0, &leg.order_id,
+                ));
+            }
+        }
+
+        if let Some((idx, contracts)) = flatten_plan(&legs) {
+            let leg = &legs[idx];
+            warn!("[INTENT] ⚠️ Group {} left {:.0} unmatched {} contracts - flattening",
+                  group_id, contracts, leg.intent.side.to_uppercase());
+            match flatten(leg, contracts, kalshi, poly).await {
+                Ok(closed) => info!("[INTENT] ✅ Flattened {:.0}/{:.0} {} contracts", closed, contracts, leg.intent.platform),
+                Err(e) => error!("[INTENT] ❌ Failed to flatten {}: {} - close manually", leg.intent.description, e),
+            }
+        }
+
+        for intent in &intents {
+            journal.complete(&intent.intent_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(side: &str, filled: f64) -> RecoveredLeg {
+        RecoveredLeg {
+            intent: OrderIntent::new("id", "g", "polymarket", "m", "d", "tok", side, 0.45, 10.0),
+            filled,
+            price: 0.45,
+            order_id: "0xabc".into(),
+        }
+    }
+
+    #[test]
+    fn test_journal_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("intents_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let journal = IntentJournal::open(&path);
+        journal.record(OrderIntent::new("a1", "g1", "kalshi", "m", "d", "KXTEST", "no", 0.5, 5.0)).unwrap();
+        journal.record(OrderIntent::new("0xhash", "g1", "polymarket", "m", "d", "123", "yes", 0.4, 5.0)).unwrap();
+        journal.complete("a1");
+
+        let reopened = IntentJournal::open(&path);
+        let pending = reopened.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].intent_id, "0xhash");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_flatten_plan() {
+        // Both legs filled equally - nothing to do
+        assert_eq!(flatten_plan(&[leg("yes", 10.0), leg("no", 10.0)]), None);
+
+        // Crash after leg one only
+        assert_eq!(flatten_plan(&[leg("yes", 10.0), leg("no", 0.0)]), Some((0, 10.0)));
+
+        // Partial second leg
+        assert_eq!(flatten_plan(&[leg("yes", 3.0), leg("no", 8.0)]), Some((1, 5.0)));
+    }
+}
@@ -15,11 +15,20 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::{http::Request, Message}};
+use tokio_tungstenite::{connect_async, tungstenite::{http::Request, Message}, MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info};
 
 use crate::config::{KALSHI_WS_URL, KALSHI_API_BASE, KALSHI_API_DELAY_MS};
 use crate::execution::NanoClock;
+use crate::fill_detail::FillDetail;
+use crate::kalshi_margin;
+use crate::metrics;
+use crate::order_errors::OrderRejected;
+use crate::order_rate;
+use crate::order_tags::{self, OrderStrategy};
+use crate::own_orders::own_orders;
+use crate::schema_watch::{self, Outcome};
+use crate::venue_health::Venue;
 use crate::types::{
     KalshiEventsResponse, KalshiMarketsResponse, KalshiEvent, KalshiMarket,
     GlobalState, FastExecutionRequest, ArbType, PriceCents, SizeCents, fxhash_str,
@@ -28,7 +37,6 @@ use crate::types::{
 // === Order Types ===
 
 use std::borrow::Cow;
-use std::fmt::Write;
 use arrayvec::ArrayString;
 
 #[derive(Debug, Clone, Serialize)]
@@ -73,6 +81,15 @@ impl<'a> KalshiOrderRequest<'a> {
         }
     }
 
+    /// Create a resting (good-till-cancelled) limit buy, for quoting
+    #[allow(dead_code)]
+    pub fn resting_buy(ticker: Cow<'a, str>, side: &'static str, price_cents: i64, count: i64, client_order_id: Cow<'a, str>) -> Self {
+        Self {
+            time_in_force: None,
+            ..Self::ioc_buy(ticker, side, price_cents, count, client_order_id)
+        }
+    }
+
     /// Create an IOC (immediate-or-cancel) sell order
     pub fn ioc_sell(ticker: Cow<'a, str>, side: &'static str, price_cents: i64, count: i64, client_order_id: Cow<'a, str>) -> Self {
         let (yes_price, no_price) = if side == "yes" {
@@ -128,6 +145,14 @@ pub struct KalshiOrderDetails {
     pub taker_fill_cost: Option<i64>,
     #[serde(default)]
     pub maker_fill_cost: Option<i64>,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KalshiOrdersResponse {
+    #[serde(default)]
+    orders: Vec<KalshiOrderDetails>,
 }
 
 #[allow(dead_code)]
@@ -137,6 +162,11 @@ impl KalshiOrderDetails {
         self.taker_fill_count.unwrap_or(0) + self.maker_fill_count.unwrap_or(0)
     }
 
+    /// Maker/taker split and status, kept on the fill for reconciliation
+    pub fn fill_detail(&self) -> FillDetail {
+        FillDetail::from_kalshi(&self.status, self.taker_fill_count.unwrap_or(0), self.maker_fill_count.unwrap_or(0))
+    }
+
     /// Check if order was fully filled
     pub fn is_filled(&self) -> bool {
         self.status == "executed" || self.remaining_count == Some(0)
@@ -148,6 +178,32 @@ impl KalshiOrderDetails {
     }
 }
 
+/// Response from DELETE /portfolio/orders/{order_id}
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct KalshiCancelResponse {
+    pub order: KalshiOrderDetails,
+    #[serde(default)]
+    pub reduced_by: Option<i64>,
+}
+
+/// Response from GET /exchange/status
+#[derive(Debug, Clone, Deserialize)]
+pub struct KalshiExchangeStatus {
+    pub exchange_active: bool,
+    pub trading_active: bool,
+}
+
+/// Response from GET /portfolio/balance (cents)
+#[derive(Debug, Clone, Deserialize)]
+pub struct KalshiBalance {
+    /// Available cash
+    pub balance: i64,
+    /// Market value of open positions
+    #[serde(default)]
+    pub portfolio_value: i64,
+}
+
 // === Kalshi Auth Config ===
 
 pub struct KalshiConfig {
@@ -189,7 +245,7 @@ const ORDER_TIMEOUT: Duration = Duration::from_secs(5);
 
 use std::sync::atomic::{AtomicU32, Ordering};
 
-/// Global order counter for unique client_order_id generation
+/// Global order counter for unique client_order_id generation (tagged with strategy and session)
 static ORDER_COUNTER: AtomicU32 = AtomicU32::new(0);
 
 pub struct KalshiApiClient {
@@ -208,12 +264,17 @@ impl KalshiApiClient {
         }
     }
 
+    /// Fresh client_order_id, for callers that must persist it before sending
+    pub fn new_client_order_id(strategy: OrderStrategy) -> String {
+        Self::next_order_id(strategy).to_string()
+    }
+
     #[inline]
-    fn next_order_id() -> ArrayString<24> {
+    fn next_order_id(strategy: OrderStrategy) -> ArrayString<40> {
         let counter = ORDER_COUNTER.fetch_add(1, Ordering::Relaxed);
         let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        let mut buf = ArrayString::<24>::new();
-        let _ = write!(&mut buf, "a{}{}", ts, counter);
+        let mut buf = ArrayString::<40>::new();
+        let _ = order_tags::write_id(&mut buf, strategy, order_tags::session_id(), format_args!("{}{}", ts, counter));
         buf
     }
     
@@ -304,6 +365,10 @@ impl KalshiApiClient {
         let status = resp.status();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
+            // Order refusals travel as typed rejections; server errors stay plain
+            if status.is_client_error() {
+                return Err(OrderRejected::kalshi(status.as_u16(), &body).into());
+            }
             anyhow::bail!("Kalshi API error {}: {}", status, body);
         }
         
@@ -311,32 +376,140 @@ impl KalshiApiClient {
         Ok(data)
     }
     
+    /// Generic authenticated DELETE request
+    async fn delete<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", KALSHI_API_BASE, path);
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let full_path = format!("/trade-api/v2{}", path);
+        let signature = self.config.sign(&format!("{}DELETE{}", timestamp_ms, full_path))?;
+
+        let resp = self.http
+            .delete(&url)
+            .header("KALSHI-ACCESS-KEY", &self.config.api_key_id)
+            .header("KALSHI-ACCESS-SIGNATURE", &signature)
+            .header("KALSHI-ACCESS-TIMESTAMP", timestamp_ms.to_string())
+            .timeout(ORDER_TIMEOUT)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Kalshi API error {}: {}", status, body);
+        }
+
+        let data: T = resp.json().await?;
+        Ok(data)
+    }
+
+    /// Get a single order by ID
+    #[allow(dead_code)]
+    pub async fn get_order(&self, order_id: &str) -> Result<KalshiOrderResponse> {
+        let resp: KalshiOrderResponse = self.get(&format!("/portfolio/orders/{}", order_id)).await?;
+        observe_order(&resp.order);
+        Ok(resp)
+    }
+
+    /// Cancel a resting order
+    #[allow(dead_code)]
+    pub async fn cancel_order(&self, order_id: &str) -> Result<KalshiCancelResponse> {
+        let resp: KalshiCancelResponse = self.delete(&format!("/portfolio/orders/{}", order_id)).await?;
+        own_orders().untrack(order_id);
+        Ok(resp)
+    }
+
+    /// Find an order by the client_order_id we sent (orders on `ticker` created since `min_ts`)
+    pub async fn find_order_by_client_id(&self, ticker: &str, client_order_id: &str, min_ts: i64) -> Result<Option<KalshiOrderDetails>> {
+        let path = format!("/portfolio/orders?ticker={}&min_ts={}", ticker, min_ts);
+        let resp: KalshiOrdersResponse = self.get(&path).await?;
+        let order = resp.orders.into_iter().find(|o| o.client_order_id.as_deref() == Some(client_order_id));
+        if let Some(order) = &order {
+            own_orders().rekey(client_order_id, &order.order_id);
+            observe_order(order);
+        }
+        Ok(order)
+    }
+
+    /// Our orders created at or after `min_ts` (Unix seconds)
+    pub async fn recent_orders(&self, min_ts: i64) -> Result<Vec<KalshiOrderDetails>> {
+        let resp: KalshiOrdersResponse = self.get(&format!("/portfolio/orders?min_ts={}&limit=1000", min_ts)).await?;
+        Ok(resp.orders)
+    }
+
+    /// Exchange / trading status (maintenance windows, halts)
+    pub async fn exchange_status(&self) -> Result<KalshiExchangeStatus> {
+        self.get("/exchange/status").await
+    }
+
+    /// Account cash and position value
+    pub async fn get_balance(&self) -> Result<KalshiBalance> {
+        self.get("/portfolio/balance").await
+    }
+
     /// Create an order on Kalshi
     pub async fn create_order(&self, order: &KalshiOrderRequest<'_>) -> Result<KalshiOrderResponse> {
         let path = "/portfolio/orders";
-        self.post(path, order).await
+        order_rate::rates().record(Venue::Kalshi, &self.config.api_key_id);
+
+        // A resting buy is ours in the feed's bid ladder from the moment it may rest; if the
+        // response is lost it stays registered under its client id until it is looked up
+        let rests = order.time_in_force.is_none() && order.action == "buy";
+        if rests {
+            let price = order.yes_price.or(order.no_price).unwrap_or(0);
+            own_orders().track(&order.client_order_id, &order.ticker, order.side, price, order.count);
+        }
+        let resp: KalshiOrderResponse = self.post(path, order).await?;
+        if rests {
+            own_orders().rekey(&order.client_order_id, &resp.order.order_id);
+            observe_order(&resp.order);
+        }
+        // Every order path spends from the balance the collateral check reads (until the next poll)
+        let o = &resp.order;
+        let resting = if o.status == "resting" { o.remaining_count.unwrap_or(0) } else { 0 };
+        kalshi_margin::debit(kalshi_margin::order_outlay(
+            order.action == "buy", order.yes_price.or(order.no_price).unwrap_or(0), o.filled_count(),
+            o.taker_fill_cost.unwrap_or(0) + o.maker_fill_cost.unwrap_or(0), resting,
+        ));
+        Ok(resp)
     }
     
     /// Create an IOC buy order (convenience method)
+    #[allow(dead_code)]
     pub async fn buy_ioc(
         &self,
         ticker: &str,
         side: &str,  // "yes" or "no"
         price_cents: i64,
         count: i64,
+        strategy: OrderStrategy,
+    ) -> Result<KalshiOrderResponse> {
+        let order_id = Self::next_order_id(strategy);
+        self.buy_ioc_with_client_id(ticker, side, price_cents, count, &order_id).await
+    }
+
+    /// IOC buy with a caller-chosen client_order_id (so it can be journaled first)
+    pub async fn buy_ioc_with_client_id(
+        &self,
+        ticker: &str,
+        side: &str,  // "yes" or "no"
+        price_cents: i64,
+        count: i64,
+        order_id: &str,
     ) -> Result<KalshiOrderResponse> {
         debug_assert!(!ticker.is_empty(), "ticker must not be empty");
-        debug_assert!(price_cents >= 1 && price_cents <= 99, "price must be 1-99");
+        debug_assert!((1..=99).contains(&price_cents), "price must be 1-99");
         debug_assert!(count >= 1, "count must be >= 1");
 
         let side_static: &'static str = if side == "yes" { "yes" } else { "no" };
-        let order_id = Self::next_order_id();
         let order = KalshiOrderRequest::ioc_buy(
             Cow::Borrowed(ticker),
             side_static,
             price_cents,
             count,
-            Cow::Borrowed(&order_id)
+            Cow::Borrowed(order_id)
         );
         debug!("[KALSHI] IOC {} {} @{}¢ x{}", side, ticker, price_cents, count);
 
@@ -351,13 +524,14 @@ impl KalshiApiClient {
         side: &str,
         price_cents: i64,
         count: i64,
+        strategy: OrderStrategy,
     ) -> Result<KalshiOrderResponse> {
         debug_assert!(!ticker.is_empty(), "ticker must not be empty");
-        debug_assert!(price_cents >= 1 && price_cents <= 99, "price must be 1-99");
+        debug_assert!((1..=99).contains(&price_cents), "price must be 1-99");
         debug_assert!(count >= 1, "count must be >= 1");
 
         let side_static: &'static str = if side == "yes" { "yes" } else { "no" };
-        let order_id = Self::next_order_id();
+        let order_id = Self::next_order_id(strategy);
         let order = KalshiOrderRequest::ioc_sell(
             Cow::Borrowed(ticker),
             side_static,
@@ -373,6 +547,15 @@ impl KalshiApiClient {
     }
 }
 
+/// Keep the self-trade registry in line with what Kalshi reports of one of our orders
+fn observe_order(order: &KalshiOrderDetails) {
+    if order.status == "resting" {
+        own_orders().set_remaining(&order.order_id, order.remaining_count.unwrap_or(0));
+    } else {
+        own_orders().untrack(&order.order_id);
+    }
+}
+
 // === WebSocket Message Types ===
 
 #[derive(Deserialize, Debug)]
@@ -393,6 +576,22 @@ pub struct KalshiWsMsgBody {
     pub price: Option<i64>,
     pub delta: Option<i64>,
     pub side: Option<String>,
+    // Fill fields (our own orders)
+    #[serde(default)]
+    pub order_id: Option<String>,
+    #[serde(default)]
+    pub count: Option<i64>,
+}
+
+impl KalshiWsMsgBody {
+    /// Carries the fields the book update for `msg_type` relies on
+    pub fn is_populated(&self, msg_type: &str) -> bool {
+        let has_levels = self.yes.is_some() || self.no.is_some();
+        self.market_ticker.is_some() && match msg_type {
+            "orderbook_delta" => has_levels || (self.price.is_some() && self.delta.is_some()),
+            _ => has_levels,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -412,24 +611,10 @@ struct SubscribeParams {
 // WebSocket Runner
 // =============================================================================
 
-/// WebSocket runner
-pub async fn run_ws(
-    config: &KalshiConfig,
-    state: Arc<GlobalState>,
-    exec_tx: mpsc::Sender<FastExecutionRequest>,
-    threshold_cents: PriceCents,
-) -> Result<()> {
-    let tickers: Vec<String> = state.markets.iter()
-        .take(state.market_count())
-        .filter_map(|m| m.pair.as_ref().map(|p| p.kalshi_market_ticker.to_string()))
-        .collect();
-
-    if tickers.is_empty() {
-        info!("[KALSHI] No markets to monitor");
-        tokio::time::sleep(Duration::from_secs(u64::MAX)).await;
-        return Ok(());
-    }
+pub type KalshiWsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
 
+/// Open an authenticated WebSocket connection (signed handshake headers)
+pub async fn connect_ws(config: &KalshiConfig) -> Result<KalshiWsStream> {
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)?
         .as_millis()
@@ -450,30 +635,73 @@ pub async fn run_ws(
         .body(())?;
 
     let (ws_stream, _) = connect_async(request).await.context("Failed to connect to Kalshi")?;
-    info!("[KALSHI] Connected");
-
-    let (mut write, mut read) = ws_stream.split();
+    Ok(ws_stream)
+}
 
-    // Subscribe to all tickers
+/// Orderbook subscription for `tickers` (snapshot, then deltas), plus fills of our own orders
+pub fn orderbook_subscribe(tickers: Vec<String>) -> Result<Message> {
     let subscribe_msg = SubscribeCmd {
         id: 1,
         cmd: "subscribe",
         params: SubscribeParams {
-            channels: vec!["orderbook_delta"],
-            market_tickers: tickers.clone(),
+            channels: vec!["orderbook_delta", "fill"],
+            market_tickers: tickers,
         },
     };
+    Ok(Message::Text(serde_json::to_string(&subscribe_msg)?))
+}
+
+/// WebSocket runner
+pub async fn run_ws(
+    config: &KalshiConfig,
+    state: Arc<GlobalState>,
+    exec_tx: mpsc::Sender<FastExecutionRequest>,
+    threshold_cents: PriceCents,
+) -> Result<()> {
+    let tickers: Vec<String> = state.markets.iter()
+        .take(state.market_count())
+        .filter_map(|m| m.pair.as_ref().map(|p| p.kalshi_market_ticker.to_string()))
+        .collect();
 
-    write.send(Message::Text(serde_json::to_string(&subscribe_msg)?)).await?;
+    if tickers.is_empty() {
+        info!("[KALSHI] No markets to monitor");
+        tokio::time::sleep(Duration::from_secs(u64::MAX)).await;
+        return Ok(());
+    }
+
+    let ws_stream = connect_ws(config).await?;
+    info!("[KALSHI] Connected");
+
+    let (mut write, mut read) = ws_stream.split();
+
+    // Subscribe to all tickers
+    write.send(orderbook_subscribe(tickers.clone())?).await?;
     info!("[KALSHI] Subscribed to {} markets", tickers.len());
 
     let clock = NanoClock::new();
+    let messages = metrics::counter("ws.kalshi.messages");
 
     while let Some(msg) = read.next().await {
         match msg {
             Ok(Message::Text(text)) => {
+                messages.fetch_add(1, Ordering::Relaxed);
                 match serde_json::from_str::<KalshiWsMessage>(&text) {
                     Ok(kalshi_msg) => {
+                        if matches!(kalshi_msg.msg_type.as_str(), "orderbook_snapshot" | "orderbook_delta") {
+                            let populated = kalshi_msg.msg.as_ref().is_some_and(|m| m.is_populated(&kalshi_msg.msg_type));
+                            schema_watch::monitor().observe("kalshi", &kalshi_msg.msg_type, Outcome::Parsed { populated });
+                        }
+
+                        // A resting order of ours filled: that much of it no longer rests
+                        if kalshi_msg.msg_type == "fill" {
+                            if let Some(body) = &kalshi_msg.msg {
+                                if let (Some(order_id), Some(count)) = (&body.order_id, body.count) {
+                                    own_orders().fill(order_id, count);
+                                }
+                            }
+                            continue;
+                        }
+
                         let ticker = kalshi_msg.msg.as_ref()
                             .and_then(|m| m.market_ticker.as_ref());
 
@@ -489,8 +717,8 @@ pub async fn run_ws(
                                     process_kalshi_snapshot(market, body);
 
                                     // Check for arbs
-                                    let arb_mask = market.check_arbs(threshold_cents);
-                                    if arb_mask != 0 {
+                                    let arb_mask = market.check_arbs(market.threshold_cents(threshold_cents));
+                                    if market.persistence.observe(arb_mask != 0) {
                                         send_kalshi_arb_request(market_id, market, arb_mask, &exec_tx, &clock).await;
                                     }
                                 }
@@ -499,8 +727,8 @@ pub async fn run_ws(
                                 if let Some(body) = &kalshi_msg.msg {
                                     process_kalshi_delta(market, body);
 
-                                    let arb_mask = market.check_arbs(threshold_cents);
-                                    if arb_mask != 0 {
+                                    let arb_mask = market.check_arbs(market.threshold_cents(threshold_cents));
+                                    if market.persistence.observe(arb_mask != 0) {
                                         send_kalshi_arb_request(market_id, market, arb_mask, &exec_tx, &clock).await;
                                     }
                                 }
@@ -509,6 +737,7 @@ pub async fn run_ws(
                         }
                     }
                     Err(e) => {
+                        schema_watch::observe_unparsed("kalshi", &text, &["orderbook_snapshot", "orderbook_delta"]);
                         // Log at trace level - unknown message types are normal
                         tracing::trace!("[KALSHI] WS parse error: {} (msg: {}...)", e, &text[..text.len().min(100)]);
                     }
@@ -532,13 +761,18 @@ pub async fn run_ws(
 /// Note: Kalshi sends BIDS - to buy YES you pay (100 - best_NO_bid), to buy NO you pay (100 - best_YES_bid)
 #[inline]
 fn process_kalshi_snapshot(market: &crate::types::AtomicMarketState, body: &KalshiWsMsgBody) {
+    // Our own resting bids are excluded: lifting them would be a self-trade
+    let ticker = body.market_ticker.as_deref().unwrap_or("");
+    let own = own_orders();
+
     // Find best YES bid (highest price) - this determines NO ask
     let (no_ask, no_size) = body.yes.as_ref()
         .and_then(|levels| {
             levels.iter()
                 .filter_map(|l| {
-                    if l.len() >= 2 && l[1] > 0 {  // Has quantity
-                        Some((l[0], l[1]))  // (price, qty)
+                    let qty = if l.len() >= 2 { own.excluding_own(ticker, "yes", l[0], l[1], 1) } else { 0 };
+                    if qty > 0 {  // Has quantity
+                        Some((l[0], qty))  // (price, qty)
                     } else {
                         None
                     }
@@ -557,8 +791,9 @@ fn process_kalshi_snapshot(market: &crate::types::AtomicMarketState, body: &Kals
         .and_then(|levels| {
             levels.iter()
                 .filter_map(|l| {
-                    if l.len() >= 2 && l[1] > 0 {
-                        Some((l[0], l[1]))
+                    let qty = if l.len() >= 2 { own.excluding_own(ticker, "no", l[0], l[1], 1) } else { 0 };
+                    if qty > 0 {
+                        Some((l[0], qty))
                     } else {
                         None
                     }
@@ -583,14 +818,17 @@ fn process_kalshi_delta(market: &crate::types::AtomicMarketState, body: &KalshiW
     // For deltas, recompute from snapshot-like format
     // Kalshi deltas have yes/no as arrays of [price, new_qty]
     let (current_yes, current_no, current_yes_size, current_no_size) = market.kalshi.load();
+    let ticker = body.market_ticker.as_deref().unwrap_or("");
+    let own = own_orders();
 
     // Process YES bid updates (affects NO ask)
     let (no_ask, no_size) = if let Some(levels) = &body.yes {
-        // Find best (highest) YES bid with non-zero quantity
+        // Find best (highest) YES bid with non-zero quantity that isn't ours
         levels.iter()
             .filter_map(|l| {
-                if l.len() >= 2 && l[1] > 0 {
-                    Some((l[0], l[1]))
+                let qty = if l.len() >= 2 { own.excluding_own(ticker, "yes", l[0], l[1], 1) } else { 0 };
+                if qty > 0 {
+                    Some((l[0], qty))
                 } else {
                     None
                 }
@@ -610,8 +848,9 @@ fn process_kalshi_delta(market: &crate::types::AtomicMarketState, body: &KalshiW
     let (yes_ask, yes_size) = if let Some(levels) = &body.no {
         levels.iter()
             .filter_map(|l| {
-                if l.len() >= 2 && l[1] > 0 {
-                    Some((l[0], l[1]))
+                let qty = if l.len() >= 2 { own.excluding_own(ticker, "no", l[0], l[1], 1) } else { 0 };
+                if qty > 0 {
+                    Some((l[0], qty))
                 } else {
                     None
                 }
@@ -662,6 +901,7 @@ async fn send_kalshi_arb_request(
         no_size,
         arb_type,
         detected_ns: clock.now_ns(),
+        poly_fee_bps: market.poly_fee_bps(),
     };
 
     let _ = exec_tx.try_send(req);
@@ -0,0 +1,176 @@
+// src/kalshi_margin.rs
+// Kalshi pre-trade collateral check - premium + fees vs cash left after in-flight orders and unpolled fills
+
+use std::sync::{Mutex, OnceLock};
+use tracing::debug;
+
+use crate::app::env_flag;
+use crate::balances::{self, VenueBalance};
+use crate::metrics;
+use crate::types::kalshi_fee_cents;
+
+/// Kalshi margin check configuration from environment
+#[derive(Debug, Clone)]
+pub struct KalshiMarginConfig {
+    /// Check Kalshi collateral before sending (KALSHI_MARGIN_CHECK)
+    pub check: bool,
+
+    /// Cash kept back from every check, for fee rounding and other clients (cents)
+    pub buffer_cents: i64,
+}
+
+impl KalshiMarginConfig {
+    pub fn from_env() -> Self {
+        Self {
+            check: env_flag("KALSHI_MARGIN_CHECK", true),
+
+            buffer_cents: std::env::var("KALSHI_MARGIN_BUFFER_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.check
+    }
+}
+
+/// Cash a Kalshi buy locks per contract: the full premium plus the taker fee.
+/// Kalshi collects both up front, unlike Polymarket where only the USDC spent leaves the wallet.
+pub fn collateral_per_contract(price_cents: i64) -> i64 {
+    let price = price_cents.clamp(0, 100);
+    price + kalshi_fee_cents(price as u16) as i64
+}
+
+/// Collateral committed since the last balance fetch
+#[derive(Debug, Default)]
+struct Ledger {
+    /// Reserved by executions still in flight
+    reserved_cents: i64,
+    /// Fills (unix seconds, cents) the polled balance may not reflect yet
+    debits: Vec<(i64, i64)>,
+}
+
+impl Ledger {
+    /// Cash not yet spoken for: the polled balance less fills since then and in-flight reservations
+    fn available(&mut self, balance: &VenueBalance) -> i64 {
+        self.debits.retain(|(ts, _)| *ts >= balance.fetched_at);
+        let spent: i64 = self.debits.iter().map(|(_, cents)| cents).sum();
+        (balance.cash * 100.0).round() as i64 - spent - self.reserved_cents
+    }
+
+    /// Reserve collateral for as many of `contracts` as the cash covers.
+    /// Ok(contracts reserved), or Err(available cents) when not even one fits.
+    fn reserve(&mut self, balance: &VenueBalance, per_contract: i64, contracts: i64, buffer_cents: i64) -> Result<i64, i64> {
+        let available = self.available(balance) - buffer_cents;
+        let fits = if per_contract > 0 { (available / per_contract).min(contracts) } else { contracts };
+        if fits < 1 {
+            return Err(available);
+        }
+        self.reserved_cents += fits * per_contract;
+        Ok(fits)
+    }
+}
+
+fn ledger() -> &'static Mutex<Ledger> {
+    static LEDGER: OnceLock<Mutex<Ledger>> = OnceLock::new();
+    LEDGER.get_or_init(|| Mutex::new(Ledger::default()))
+}
+
+/// Collateral held for one execution; released when dropped
+#[derive(Debug)]
+pub struct Reservation {
+    pub contracts: i64,
+    cents: i64,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        ledger().lock().unwrap().reserved_cents -= self.cents;
+    }
+}
+
+/// Why an execution can't fund its Kalshi leg(s)
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsufficientCollateral {
+    pub required_cents: i64,
+    pub available_cents: i64,
+}
+
+/// Reserve Kalshi collateral for up to `contracts` at `per_contract` cents each, cutting the
+/// size to what the cash covers. Ok(None) before the first balance fetch (nothing to check against).
+pub fn reserve(config: &KalshiMarginConfig, per_contract: i64, contracts: i64) -> Result<Option<Reservation>, InsufficientCollateral> {
+    let Some(balance) = balances::snapshot().kalshi else {
+        debug!("[MARGIN] No Kalshi balance yet - skipping collateral check");
+        return Ok(None);
+    };
+    match ledger().lock().unwrap().reserve(&balance, per_contract, contracts, config.buffer_cents) {
+        Ok(fits) => Ok(Some(Reservation { contracts: fits, cents: fits * per_contract })),
+        Err(available) => {
+            metrics::incr("kalshi.margin.insufficient");
+            Err(InsufficientCollateral { required_cents: per_contract, available_cents: available.max(0) })
+        }
+    }
+}
+
+/// Cash an order took from the Kalshi balance: a buy's fill cost, fees and the collateral of
+/// whatever still rests; a sell's fees (its proceeds only count once the balance is polled)
+pub fn order_outlay(buy: bool, price_cents: i64, filled: i64, fill_cost_cents: i64, resting: i64) -> i64 {
+    let fees = filled * kalshi_fee_cents(price_cents.clamp(0, 100) as u16) as i64;
+    if buy {
+        fill_cost_cents + fees + resting.max(0) * collateral_per_contract(price_cents)
+    } else {
+        fees
+    }
+}
+
+/// Record a Kalshi order's cash outlay until the next balance poll picks it up
+pub fn debit(cents: i64) {
+    if cents > 0 {
+        ledger().lock().unwrap().debits.push((chrono::Utc::now().timestamp(), cents));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collateral_includes_fee() {
+        // 50¢ premium + 2¢ taker fee
+        assert_eq!(collateral_per_contract(50), 52);
+        assert_eq!(collateral_per_contract(10), 11);
+        assert_eq!(collateral_per_contract(0), 0);
+    }
+
+    #[test]
+    fn test_order_outlay_per_kind() {
+        // 10 filled at 50¢ (500¢ + 2¢ fee each), 5 more resting at 52¢ collateral
+        assert_eq!(order_outlay(true, 50, 10, 500, 5), 500 + 20 + 5 * 52);
+        // An IOC sell only pays its fees up front
+        assert_eq!(order_outlay(false, 50, 10, 500, 0), 20);
+        assert_eq!(order_outlay(true, 50, 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_reservations_cut_size_to_cash() {
+        let balance = VenueBalance::new(10.0, 0.0, 1_000);
+        let mut ledger = Ledger::default();
+        // $10 less $1 buffer covers 17 contracts at 52¢
+        assert_eq!(ledger.reserve(&balance, 52, 50, 100), Ok(17));
+        // A concurrent execution sees only what's left: 116¢ less the buffer
+        assert_eq!(ledger.reserve(&balance, 52, 5, 100), Err(16));
+        assert_eq!(ledger.reserve(&balance, 11, 5, 100), Ok(1));
+    }
+
+    #[test]
+    fn test_fills_count_until_next_poll() {
+        let mut ledger = Ledger::default();
+        ledger.debits.push((1_010, 400));
+        assert_eq!(ledger.available(&VenueBalance::new(10.0, 0.0, 1_000)), 600);
+        // The next fetch already reflects the fill
+        assert_eq!(ledger.available(&VenueBalance::new(6.0, 4.0, 1_060)), 600);
+        assert!(ledger.debits.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+// src/ladder.rs
+// Size laddering - a large arb sent as a few smaller child IOCs a few ms apart instead of one
+// full-size order, stopping once the edge is gone ([assets] ladder_children / ladder_min_size)
+
+use std::time::Duration;
+
+use crate::asset_params::AssetParams;
+
+/// Child sizes for `contracts`: up to `ladder_children` whole-contract children of at least
+/// `min_size` each, the first taking the remainder. One child (no ladder) below `ladder_min_size`.
+pub fn split(contracts: f64, params: &AssetParams) -> Vec<f64> {
+    if !params.ladders(contracts) {
+        return vec![contracts];
+    }
+    let n = (params.ladder_children as f64).min((contracts / params.min_size).floor());
+    let child = (contracts / n).floor();
+    if n < 2.0 || child < params.min_size {
+        return vec![contracts];
+    }
+    let mut sizes = vec![child; n as usize];
+    sizes[0] = contracts - child * (n - 1.0);
+    sizes
+}
+
+/// Size of the next child against the current book: None once the arb is gone or the
+/// book no longer holds `min_size`
+pub fn next_child(planned: f64, has_arb: bool, depth: f64, min_size: f64) -> Option<f64> {
+    if !has_arb {
+        return None;
+    }
+    let size = planned.min(depth.floor());
+    (size >= min_size).then_some(size)
+}
+
+/// Pause between child orders
+pub fn delay(params: &AssetParams) -> Duration {
+    Duration::from_millis(params.ladder_delay_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(children: u32, ladder_min_size: f64) -> AssetParams {
+        AssetParams { ladder_children: children, ladder_min_size, ..AssetParams::default() }
+    }
+
+    #[test]
+    fn test_split_into_children() {
+        assert_eq!(split(50.0, &params(3, 20.0)), vec![18.0, 16.0, 16.0]);
+        assert_eq!(split(40.0, &params(2, 20.0)), vec![20.0, 20.0]);
+        // Fractional size: the remainder rides on the first child
+        assert_eq!(split(30.5, &params(3, 20.0)), vec![10.5, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn test_small_or_unladdered_sizes_go_whole() {
+        assert_eq!(split(50.0, &params(1, 20.0)), vec![50.0]);
+        assert_eq!(split(15.0, &params(3, 20.0)), vec![15.0]);
+        // Never below min_size per child: fewer children instead
+        let p = AssetParams { min_size: 10.0, ..params(3, 0.0) };
+        assert_eq!(split(25.0, &p), vec![13.0, 12.0]);
+        assert_eq!(split(15.0, &p), vec![15.0]);
+    }
+
+    #[test]
+    fn test_next_child_stops_when_edge_gone() {
+        assert_eq!(next_child(16.0, true, 40.0, 1.0), Some(16.0));
+        // Capped by what the book still shows
+        assert_eq!(next_child(16.0, true, 9.6, 1.0), Some(9.0));
+        assert_eq!(next_child(16.0, false, 40.0, 1.0), None);
+        assert_eq!(next_child(16.0, true, 0.5, 1.0), None);
+    }
+}
@@ -0,0 +1,506 @@
+// src/leader.rs
+// Leader election for a warm standby - two instances share a lease (a file or a database row);
+// only the holder trades, and the standby takes over once the leader stops renewing it
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::exec_mode;
+use crate::metrics;
+use crate::position_store::StoreBackend;
+use crate::sql::SqlConn;
+use crate::storage::{self, FileLock};
+
+/// Where the lease lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaseKind {
+    /// A JSON file both instances can reach (shared disk)
+    File,
+    Sqlite,
+    Postgres,
+}
+
+impl LeaseKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "file" => Some(LeaseKind::File),
+            "sqlite" => Some(LeaseKind::Sqlite),
+            "postgres" | "postgresql" => Some(LeaseKind::Postgres),
+            _ => None,
+        }
+    }
+}
+
+/// Leader election configuration from environment
+#[derive(Debug, Clone)]
+pub struct LeaderConfig {
+    /// Lease backend (None = no election, this instance always trades)
+    pub kind: Option<LeaseKind>,
+
+    /// Lease file (under DATA_DIR unless absolute), SQLite file or Postgres URL
+    pub url: Option<String>,
+
+    /// This instance's name in the lease
+    pub id: String,
+
+    /// Seconds a lease lasts without renewal
+    pub lease_secs: u64,
+
+    /// Seconds between renewals (leader) and takeover attempts (standby)
+    pub renew_secs: u64,
+}
+
+fn default_id() -> String {
+    let host = std::env::var("HOSTNAME").ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "host".to_string());
+    format!("{}-{}", host, std::process::id())
+}
+
+impl LeaderConfig {
+    pub fn from_env() -> Self {
+        Self {
+            kind: std::env::var("LEADER_LEASE").ok().and_then(|v| LeaseKind::parse(&v)),
+
+            url: std::env::var("LEADER_LEASE_URL").ok().filter(|v| !v.trim().is_empty()),
+
+            id: std::env::var("LEADER_ID")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(default_id),
+
+            lease_secs: std::env::var("LEADER_LEASE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+
+            renew_secs: std::env::var("LEADER_RENEW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+        }
+    }
+}
+
+/// The lease as it stands after an attempt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lease {
+    pub holder: String,
+    /// Bumped on every change of holder (fencing token)
+    pub epoch: u64,
+    pub expires_at_ms: i64,
+}
+
+/// Somewhere both instances can take or renew a lease atomically
+pub trait LeaseBackend: Send + Sync {
+    /// Take the lease for `holder` if it is free, expired or already theirs; returns who holds it
+    fn try_acquire(&self, name: &str, holder: &str, ttl_ms: i64, now_ms: i64) -> Result<Lease>;
+
+    /// Give the lease up if `holder` has it
+    fn release(&self, name: &str, holder: &str) -> Result<()>;
+}
+
+/// Lease in a JSON file, read-modify-written under its advisory lock. Expiry is judged
+/// by each instance's wall clock, so hosts sharing it need synced clocks (NTP).
+pub struct FileLease {
+    path: PathBuf,
+}
+
+impl FileLease {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn read(&self) -> Option<Lease> {
+        serde_json::from_str(&std::fs::read_to_string(&self.path).ok()?).ok()
+    }
+}
+
+impl LeaseBackend for FileLease {
+    fn try_acquire(&self, _name: &str, holder: &str, ttl_ms: i64, now_ms: i64) -> Result<Lease> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let current = self.read();
+        if let Some(lease) = current.as_ref().filter(|l| l.holder != holder && l.expires_at_ms > now_ms) {
+            return Ok(lease.clone());
+        }
+        let epoch = match &current {
+            Some(l) if l.holder == holder => l.epoch,
+            Some(l) => l.epoch + 1,
+            None => 1,
+        };
+        let lease = Lease { holder: holder.to_string(), epoch, expires_at_ms: now_ms + ttl_ms };
+        storage::write_atomic(&self.path, serde_json::to_string(&lease)?.as_bytes())?;
+        Ok(lease)
+    }
+
+    fn release(&self, _name: &str, holder: &str) -> Result<()> {
+        let _lock = FileLock::acquire(&self.path)?;
+        if let Some(mut lease) = self.read().filter(|l| l.holder == holder) {
+            lease.expires_at_ms = 0;
+            storage::write_atomic(&self.path, serde_json::to_string(&lease)?.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Lease row in SQLite or Postgres, taken with one conditional upsert. Expiry is judged by
+/// the database's clock, so instance clocks don't matter.
+pub struct SqlLease {
+    db: SqlConn,
+}
+
+impl SqlLease {
+    pub fn open(backend: StoreBackend, target: &str) -> Result<Self> {
+        let db = SqlConn::open(backend, target)?;
+        db.batch("CREATE TABLE IF NOT EXISTS leader_lease (name TEXT PRIMARY KEY, holder TEXT NOT NULL, \
+                  epoch BIGINT NOT NULL, expires_at_ms BIGINT NOT NULL);")?;
+        Ok(Self { db })
+    }
+
+    fn now_sql(&self) -> &'static str {
+        match self.db.backend() {
+            StoreBackend::Postgres => "(extract(epoch from clock_timestamp()) * 1000)::bigint",
+            _ => "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)",
+        }
+    }
+}
+
+impl LeaseBackend for SqlLease {
+    fn try_acquire(&self, name: &str, holder: &str, ttl_ms: i64, _now_ms: i64) -> Result<Lease> {
+        let now = self.now_sql();
+        let params = [name.into(), holder.into(), ttl_ms.into()];
+        let row = self.db.transaction(|db| {
+            db.execute(&format!(
+                "INSERT INTO leader_lease (name, holder, epoch, expires_at_ms) VALUES ($1, $2, 1, {now} + $3) \
+                 ON CONFLICT (name) DO UPDATE SET holder = excluded.holder, expires_at_ms = excluded.expires_at_ms, \
+                 epoch = CASE WHEN leader_lease.holder = excluded.holder THEN leader_lease.epoch ELSE leader_lease.epoch + 1 END \
+                 WHERE leader_lease.holder = excluded.holder OR leader_lease.expires_at_ms < {now}"
+            ), &params)?;
+            db.query("SELECT holder, epoch, expires_at_ms FROM leader_lease WHERE name = $1", &params[..1])?
+                .pop()
+                .ok_or_else(|| anyhow!("lease row missing"))
+        })?;
+        match row.as_slice() {
+            [holder, epoch, expires] => Ok(Lease {
+                holder: holder.as_str().ok_or_else(|| anyhow!("lease holder is not text"))?.to_string(),
+                epoch: epoch.as_i64().ok_or_else(|| anyhow!("lease epoch is not an integer"))? as u64,
+                expires_at_ms: expires.as_i64().ok_or_else(|| anyhow!("lease expiry is not an integer"))?,
+            }),
+            _ => Err(anyhow!("unexpected lease row {:?}", row)),
+        }
+    }
+
+    fn release(&self, name: &str, holder: &str) -> Result<()> {
+        self.db.execute("UPDATE leader_lease SET expires_at_ms = 0 WHERE name = $1 AND holder = $2",
+                        &[name.into(), holder.into()])?;
+        Ok(())
+    }
+}
+
+const STANDBY: u8 = 0;
+/// Lease held, state being taken over
+const PROMOTING: u8 = 1;
+const LEADER: u8 = 2;
+
+/// This instance's side of the election
+pub struct Election {
+    config: LeaderConfig,
+    name: String,
+    backend: Box<dyn LeaseBackend>,
+    state: AtomicU8,
+    epoch: AtomicU64,
+    /// Last successful renewal while holding the lease
+    renewed: Mutex<Option<Instant>>,
+    /// A lease attempt is still running (a hung backend call doesn't stack more behind it)
+    attempting: AtomicBool,
+    holds: watch::Sender<bool>,
+}
+
+impl Election {
+    pub fn new(config: LeaderConfig, name: &str, backend: Box<dyn LeaseBackend>) -> Self {
+        let (holds, _) = watch::channel(false);
+        Self {
+            config,
+            name: name.to_string(),
+            backend,
+            state: AtomicU8::new(STANDBY),
+            epoch: AtomicU64::new(0),
+            renewed: Mutex::new(None),
+            attempting: AtomicBool::new(false),
+            holds,
+        }
+    }
+
+    /// Lease lifetime; a leader that can't renew steps down one renewal interval before it
+    /// runs out, so the standby never finds it still trading
+    fn lease(&self) -> Duration {
+        Duration::from_secs(self.config.lease_secs)
+    }
+
+    fn grace(&self) -> Duration {
+        self.lease().saturating_sub(Duration::from_secs(self.config.renew_secs))
+    }
+
+    /// Trading as leader, judged against the clock: a leader whose last renewal is older
+    /// than the grace period stops at once, even before a renewal attempt notices
+    pub fn is_leader(&self) -> bool {
+        self.is_leader_at(Instant::now())
+    }
+
+    fn is_leader_at(&self, now: Instant) -> bool {
+        self.state.load(Ordering::Relaxed) == LEADER && self.fresh(now)
+    }
+
+    /// The last renewal is recent enough that the lease can't have run out
+    fn fresh(&self, now: Instant) -> bool {
+        self.renewed.lock().unwrap().is_some_and(|t| now.saturating_duration_since(t) < self.grace())
+    }
+
+    pub fn holds_lease(&self) -> bool {
+        self.state.load(Ordering::Relaxed) != STANDBY
+    }
+
+    /// Fencing token of the current lease
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Relaxed)
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.holds.subscribe()
+    }
+
+    /// One renewal / takeover attempt
+    pub fn tick(&self, now: Instant) {
+        let result = self.backend.try_acquire(
+            &self.name, &self.config.id, self.lease().as_millis() as i64, chrono::Utc::now().timestamp_millis());
+        let held = self.holds_lease();
+        let holds = match result {
+            Ok(lease) if lease.holder == self.config.id => {
+                *self.renewed.lock().unwrap() = Some(now);
+                self.epoch.store(lease.epoch, Ordering::Relaxed);
+                if !held {
+                    info!("[LEADER] 👑 {} took the {} lease (epoch {})", self.config.id, self.name, lease.epoch);
+                }
+                true
+            }
+            Ok(lease) => {
+                if held {
+                    warn!("[LEADER] {} lease taken by {} (epoch {})", self.name, lease.holder, lease.epoch);
+                }
+                false
+            }
+            Err(e) => self.failed(now, &e.to_string()),
+        };
+        self.settle(held, holds);
+    }
+
+    /// A lease attempt that errored or didn't answer in time: keep what we had until the
+    /// lease could have run out
+    fn failed(&self, now: Instant, reason: &str) -> bool {
+        metrics::incr("leader.lease_errors");
+        let held = self.holds_lease();
+        let fresh = self.fresh(now);
+        if held && !fresh {
+            warn!("[LEADER] Couldn't renew the {} lease ({}) - stepping down", self.name, reason);
+        } else if !held {
+            tracing::debug!("[LEADER] Lease attempt failed: {}", reason);
+        }
+        held && fresh
+    }
+
+    /// A renewal attempt that hasn't answered within `timeout`
+    pub fn timed_out(&self, now: Instant, timeout: Duration) {
+        let held = self.holds_lease();
+        let holds = self.failed(now, &format!("no answer in {}ms", timeout.as_millis()));
+        self.settle(held, holds);
+    }
+
+    fn settle(&self, held: bool, holds: bool) {
+        if holds != held {
+            self.state.store(if holds { PROMOTING } else { STANDBY }, Ordering::Relaxed);
+            metrics::incr(if holds { "leader.acquired" } else { "leader.lost" });
+            self.holds.send_replace(holds);
+        }
+    }
+
+    /// The bot has taken over the shared state: start trading
+    pub fn promoted(&self) {
+        if self.state.compare_exchange(PROMOTING, LEADER, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            info!("[LEADER] ▶️ Trading as leader (epoch {})", self.epoch());
+        }
+    }
+
+    /// Give the lease up (a takeover that couldn't complete)
+    pub fn resign(&self) {
+        if let Err(e) = self.backend.release(&self.name, &self.config.id) {
+            warn!("[LEADER] Releasing the {} lease failed: {}", self.name, e);
+        }
+        *self.renewed.lock().unwrap() = None;
+        if self.state.swap(STANDBY, Ordering::Relaxed) != STANDBY {
+            self.holds.send_replace(false);
+        }
+    }
+}
+
+static ELECTION: OnceLock<Election> = OnceLock::new();
+
+/// The running election (None unless `start` enabled one)
+pub fn election() -> Option<&'static Election> {
+    ELECTION.get()
+}
+
+/// Whether this instance may trade: always, unless an election is running and it isn't the leader
+pub fn is_leader() -> bool {
+    election().is_none_or(|e| e.is_leader())
+}
+
+/// Fencing token an order is decided under: the lease epoch (0 with no election), or None
+/// on an instance that isn't leading
+pub fn fence() -> Option<u64> {
+    match election() {
+        None => Some(0),
+        Some(e) if e.is_leader() => Some(e.epoch()),
+        Some(_) => None,
+    }
+}
+
+/// Whether an order decided under `epoch` may still go out: this instance leads under that same lease
+pub fn fence_holds(epoch: u64) -> bool {
+    fence() == Some(epoch)
+}
+
+/// Join the `name` election configured by LEADER_LEASE. Makes the first attempt before
+/// returning, then renews in the background; the receiver tracks whether we hold the lease.
+/// None when no lease is configured.
+pub fn start(config: LeaderConfig, name: &str) -> Result<Option<watch::Receiver<bool>>> {
+    let Some(kind) = config.kind else { return Ok(None) };
+    let backend: Box<dyn LeaseBackend> = match kind {
+        LeaseKind::File => Box::new(FileLease::new(storage::state_path(config.url.as_deref().unwrap_or("leader.lease")))),
+        LeaseKind::Sqlite => Box::new(SqlLease::open(StoreBackend::Sqlite,
+            &storage::state_path(config.url.as_deref().unwrap_or("leader.db")).to_string_lossy())?),
+        LeaseKind::Postgres => Box::new(SqlLease::open(StoreBackend::Postgres,
+            config.url.as_deref().ok_or_else(|| anyhow!("LEADER_LEASE=postgres needs LEADER_LEASE_URL"))?)?),
+    };
+    let renew = Duration::from_secs(config.renew_secs.max(1));
+    let name = &exec_mode::state_name(name);
+    info!("[LEADER] Joining the {} election as {} (lease {}s, renewed every {}s)",
+          name, config.id, config.lease_secs, renew.as_secs());
+    let election = ELECTION.get_or_init(|| Election::new(config, name, backend));
+    election.tick(Instant::now());
+    if !election.holds_lease() {
+        info!("[LEADER] 💤 Standing by - books stay warm, no orders until the lease is ours");
+    }
+    let rx = election.subscribe();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(renew);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            // A backend that stops answering counts as a failed renewal; the call that hangs
+            // finishes in the background and no new one starts until it has
+            if election.attempting.swap(true, Ordering::AcqRel) {
+                election.timed_out(Instant::now(), renew);
+                continue;
+            }
+            let attempt = tokio::task::spawn_blocking(move || {
+                election.tick(Instant::now());
+                election.attempting.store(false, Ordering::Release);
+            });
+            if tokio::time::timeout(renew, attempt).await.is_err() {
+                election.timed_out(Instant::now(), renew);
+            }
+        }
+    });
+    Ok(Some(rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("leader_test_{}_{}.lease", name, std::process::id()))
+    }
+
+    fn config(id: &str) -> LeaderConfig {
+        LeaderConfig { kind: Some(LeaseKind::File), url: None, id: id.into(), lease_secs: 10, renew_secs: 2 }
+    }
+
+    #[test]
+    fn test_file_lease_expiry_and_epochs() {
+        let path = temp("file");
+        let lease = FileLease::new(path.clone());
+        assert_eq!(lease.try_acquire("u", "a", 1000, 0).unwrap(), Lease { holder: "a".into(), epoch: 1, expires_at_ms: 1000 });
+        // b waits while a's lease runs, a renews in place
+        assert_eq!(lease.try_acquire("u", "b", 1000, 500).unwrap().holder, "a");
+        assert_eq!(lease.try_acquire("u", "a", 1000, 900).unwrap().expires_at_ms, 1900);
+        // Expired: b takes over with a new epoch; a released lease is free at once
+        let taken = lease.try_acquire("u", "b", 1000, 2000).unwrap();
+        assert_eq!((taken.holder.as_str(), taken.epoch), ("b", 2));
+        lease.release("u", "b").unwrap();
+        assert_eq!(lease.try_acquire("u", "a", 1000, 2100).unwrap().epoch, 3);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("lease.lock"));
+    }
+
+    #[test]
+    fn test_standby_promotes_and_leader_steps_down() {
+        let path = temp("election");
+        let a = Election::new(config("a"), "u", Box::new(FileLease::new(path.clone())));
+        let b = Election::new(config("b"), "u", Box::new(FileLease::new(path.clone())));
+        let mut rx = a.subscribe();
+        let now = Instant::now();
+        a.tick(now);
+        b.tick(now);
+        assert!(a.holds_lease() && !b.holds_lease());
+        assert!(*rx.borrow_and_update());
+        // Holding the lease isn't trading until the bot has taken over
+        assert!(!a.is_leader());
+        a.promoted();
+        assert!(a.is_leader());
+
+        // Stops trading once its last renewal is older than the grace period (lease less one
+        // renewal interval), even with no renewal attempt since
+        assert!(a.is_leader_at(now + Duration::from_secs(7)));
+        assert!(!a.is_leader_at(now + Duration::from_secs(8)));
+        // An unanswered renewal keeps the lease only while it's fresh
+        a.timed_out(now + Duration::from_secs(3), Duration::from_secs(2));
+        assert!(a.holds_lease());
+        a.timed_out(now + Duration::from_secs(9), Duration::from_secs(2));
+        assert!(!a.holds_lease() && !*rx.borrow_and_update());
+        a.tick(now);
+        a.promoted();
+        assert!(*rx.borrow_and_update());
+
+        a.resign();
+        assert!(!a.is_leader() && !*rx.borrow_and_update());
+        b.tick(now);
+        assert!(b.holds_lease());
+        assert_eq!(b.epoch(), 2);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("lease.lock"));
+    }
+
+    #[test]
+    fn test_sqlite_lease() {
+        let path = temp("sql").with_extension("db");
+        let lease = SqlLease::open(StoreBackend::Sqlite, &path.to_string_lossy()).unwrap();
+        assert_eq!(lease.try_acquire("u", "a", 60_000, 0).unwrap().holder, "a");
+        assert_eq!(lease.try_acquire("u", "b", 60_000, 0).unwrap().holder, "a");
+        lease.release("u", "a").unwrap();
+        let taken = lease.try_acquire("u", "b", 60_000, 0).unwrap();
+        assert_eq!((taken.holder.as_str(), taken.epoch), ("b", 2));
+        drop(lease);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+// src/leg_order.rs
+// Leg ordering - measured submission latency per venue, slower venue's leg sent first (or legs sent in sequence)
+
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::metrics;
+use crate::venue_health::Venue;
+
+/// Weight of the newest sample in the moving average
+const EWMA_ALPHA: f64 = 0.2;
+
+/// How the legs of an arb (or basket) are released
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegPolicy {
+    /// All at once
+    Simultaneous,
+    /// Slower venue first, the faster legs held back so all land together
+    Staggered,
+    /// One after another, each sized to what the previous legs filled
+    Sequenced,
+}
+
+impl LegPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "simultaneous" => Some(Self::Simultaneous),
+            "staggered" => Some(Self::Staggered),
+            "sequenced" => Some(Self::Sequenced),
+            _ => None,
+        }
+    }
+}
+
+/// Leg ordering configuration from environment
+#[derive(Debug, Clone)]
+pub struct LegOrderConfig {
+    pub policy: LegPolicy,
+
+    /// Submissions measured per venue before the averages are trusted
+    pub min_samples: u64,
+
+    /// Longest the faster leg is held back (ms)
+    pub max_stagger_ms: u64,
+}
+
+impl LegOrderConfig {
+    pub fn from_env() -> Self {
+        Self {
+            policy: std::env::var("LEG_ORDER")
+                .ok()
+                .and_then(|v| LegPolicy::parse(&v))
+                .unwrap_or(LegPolicy::Simultaneous),
+
+            min_samples: std::env::var("LEG_ORDER_MIN_SAMPLES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20),
+
+            max_stagger_ms: std::env::var("LEG_ORDER_MAX_STAGGER_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(250),
+        }
+    }
+
+    /// Delay before each leg on `venues` is sent. Legs on faster venues wait half their round-trip
+    /// difference to the slowest (one-way latency), so all orders reach their books together.
+    /// No delays unless staggered and every venue measured.
+    pub fn venue_delays(&self, venues: &[Venue], latency: &LatencyStats) -> Vec<Duration> {
+        let zero = vec![Duration::ZERO; venues.len()];
+        if self.policy != LegPolicy::Staggered {
+            return zero;
+        }
+        let Some(means) = venues.iter().map(|v| latency.mean_ms(*v, self.min_samples)).collect::<Option<Vec<f64>>>() else {
+            return zero;
+        };
+        let slowest = means.iter().cloned().fold(0.0, f64::max);
+        means.iter()
+            .map(|mean| Duration::from_micros((((slowest - mean) / 2.0) * 1000.0) as u64)
+                .min(Duration::from_millis(self.max_stagger_ms)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct VenueLatency {
+    mean_ms: f64,
+    samples: u64,
+}
+
+/// Moving average of order submission round-trips per venue
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    venues: Mutex<[VenueLatency; 2]>,
+}
+
+fn slot(venue: Venue) -> usize {
+    match venue {
+        Venue::Kalshi => 0,
+        Venue::Polymarket => 1,
+    }
+}
+
+impl LatencyStats {
+    /// Record one order's send-to-response time (gauge `latency.<venue>.submit_ms`)
+    pub fn record(&self, venue: Venue, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        let mut venues = self.venues.lock().unwrap();
+        let v = &mut venues[slot(venue)];
+        v.mean_ms = if v.samples == 0 { ms } else { v.mean_ms + EWMA_ALPHA * (ms - v.mean_ms) };
+        v.samples += 1;
+        metrics::set_gauge(&format!("latency.{}.submit_ms", venue.name()), v.mean_ms.round() as i64);
+    }
+
+    /// Average round-trip once at least `min_samples` were recorded
+    pub fn mean_ms(&self, venue: Venue, min_samples: u64) -> Option<f64> {
+        let v = self.venues.lock().unwrap()[slot(venue)];
+        (v.samples > 0 && v.samples >= min_samples).then_some(v.mean_ms)
+    }
+}
+
+/// Run `fut` after `delay` (immediately if zero)
+pub async fn delayed<F: Future>(delay: Duration, fut: F) -> F::Output {
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+    fut.await
+}
+
+/// Process-wide submission latencies, fed by the executor's order calls
+pub fn latency() -> &'static LatencyStats {
+    static LATENCY: OnceLock<LatencyStats> = OnceLock::new();
+    LATENCY.get_or_init(LatencyStats::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(policy: LegPolicy) -> LegOrderConfig {
+        LegOrderConfig { policy, min_samples: 3, max_stagger_ms: 250 }
+    }
+
+    /// (kalshi, polymarket) delays of a cross-venue pair
+    fn delays(config: &LegOrderConfig, latency: &LatencyStats) -> (Duration, Duration) {
+        let d = config.venue_delays(&[Venue::Kalshi, Venue::Polymarket], latency);
+        (d[0], d[1])
+    }
+
+    fn stats(kalshi_ms: u64, poly_ms: u64, samples: usize) -> LatencyStats {
+        let s = LatencyStats::default();
+        for _ in 0..samples {
+            s.record(Venue::Kalshi, Duration::from_millis(kalshi_ms));
+            s.record(Venue::Polymarket, Duration::from_millis(poly_ms));
+        }
+        s
+    }
+
+    #[test]
+    fn test_slower_venue_goes_first() {
+        // Polymarket 80ms slower round-trip: Kalshi leg held 40ms
+        let (k, p) = delays(&cfg(LegPolicy::Staggered), &stats(60, 140, 5));
+        assert_eq!((k, p), (Duration::from_millis(40), Duration::ZERO));
+        let (k, p) = delays(&cfg(LegPolicy::Staggered), &stats(200, 100, 5));
+        assert_eq!((k, p), (Duration::ZERO, Duration::from_millis(50)));
+        // Simultaneous and sequenced ignore the measurements
+        assert_eq!(delays(&cfg(LegPolicy::Simultaneous), &stats(60, 140, 5)), (Duration::ZERO, Duration::ZERO));
+        assert_eq!(delays(&cfg(LegPolicy::Sequenced), &stats(60, 140, 5)), (Duration::ZERO, Duration::ZERO));
+        // Baskets: every Kalshi leg held back, same-venue legs released together
+        let venues = [Venue::Polymarket, Venue::Kalshi, Venue::Kalshi];
+        assert_eq!(cfg(LegPolicy::Staggered).venue_delays(&venues, &stats(60, 140, 5)),
+                   vec![Duration::ZERO, Duration::from_millis(40), Duration::from_millis(40)]);
+    }
+
+    #[test]
+    fn test_needs_samples_and_caps_hold() {
+        assert_eq!(delays(&cfg(LegPolicy::Staggered), &stats(60, 140, 2)), (Duration::ZERO, Duration::ZERO));
+        let (k, _) = delays(&cfg(LegPolicy::Staggered), &stats(10, 2_000, 5));
+        assert_eq!(k, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let s = LatencyStats::default();
+        s.record(Venue::Kalshi, Duration::from_millis(100));
+        assert_eq!(s.mean_ms(Venue::Kalshi, 1), Some(100.0));
+        s.record(Venue::Kalshi, Duration::from_millis(200));
+        assert!((s.mean_ms(Venue::Kalshi, 1).unwrap() - 120.0).abs() < 1e-9);
+        assert_eq!(s.mean_ms(Venue::Polymarket, 0), None);
+        assert_eq!(LegPolicy::parse("STAGGERED"), Some(LegPolicy::Staggered));
+        assert_eq!(LegPolicy::parse("sequenced"), Some(LegPolicy::Sequenced));
+    }
+}
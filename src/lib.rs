@@ -1,13 +1,111 @@
 // src/lib.rs
 
+pub mod app;
+pub mod aggregation;
+pub mod aggression;
+pub mod arb_confirm;
+pub mod archive;
+pub mod asset_params;
+pub mod balances;
+pub mod basis_risk;
+pub mod basket;
+pub mod book;
 pub mod cache;
 pub mod circuit_breaker;
+pub mod clock_skew;
+pub mod close_race;
 pub mod config;
+pub mod config_check;
+pub mod control;
+pub mod cost_model;
+#[cfg(feature = "onchain")]
+pub mod ctf_reconcile;
+pub mod depth_guard;
+#[cfg(feature = "kalshi")]
 pub mod discovery;
+pub mod events;
+pub mod exec_mode;
+pub mod exec_pool;
+pub mod exits;
+pub mod feed_liveness;
+pub mod gamma;
+pub mod fill_detail;
+pub mod fill_retry;
+pub mod instruments;
+pub mod intents;
+#[cfg(feature = "kalshi")]
 pub mod execution;
+#[cfg(feature = "kalshi")]
 pub mod kalshi;
+pub mod kalshi_margin;
+#[cfg(not(feature = "kalshi"))]
+pub mod kalshi {
+    //! Built without the `kalshi` feature: venue-agnostic code keeps its
+    //! `Option<&KalshiApiClient>` parameters, which can only be `None`
+
+    pub enum KalshiApiClient {}
+}
+pub mod ladder;
+pub mod leader;
+pub mod leg_order;
+pub mod liquidity_rewards;
+pub mod logging;
+pub mod market_key;
+pub mod market_quality;
+pub mod market_sets;
+pub mod metrics;
+pub mod notify;
+#[cfg(feature = "onchain")]
+pub mod onchain;
+pub mod opp_cost;
+pub mod opp_rate;
+pub mod opportunities;
+pub mod order_errors;
+pub mod order_rate;
+pub mod order_tags;
+pub mod own_orders;
+pub mod overrides;
+pub mod order_manager;
+pub mod pnl_series;
+#[cfg(feature = "kalshi")]
 pub mod polymarket;
 pub mod polymarket_clob;
+pub mod polymarket_ws_types;
+#[cfg(feature = "oracles")]
+pub mod pyth;
+pub mod position_query;
+pub mod position_schema;
+pub mod position_store;
 pub mod position_tracker;
+pub mod rebalance;
+pub mod replay;
+pub mod risk;
+#[cfg(feature = "onchain")]
+pub mod rpc_pool;
+pub mod run_windows;
+pub mod runtimes;
+pub mod scheduler;
+#[cfg(feature = "oracles")]
+pub mod settle_predict;
+pub mod settlement;
+pub mod shadow;
+pub mod snapshots;
+pub mod schema_watch;
+pub mod sql;
+pub mod storage;
+pub mod subscriptions;
+pub mod synthetic;
+pub mod tax_lots;
+pub mod telemetry;
+pub mod throttle;
+pub mod trade_tape;
+pub mod tranches;
+pub mod treasury;
 pub mod types;
-pub mod updown_scanner;
\ No newline at end of file
+pub mod unit_economics;
+pub mod units;
+pub mod updown_lifecycle;
+pub mod updown_scanner;
+pub mod venue_health;
+pub mod watchdog;
+pub mod what_if;
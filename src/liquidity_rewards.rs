@@ -0,0 +1,274 @@
+// src/liquidity_rewards.rs
+// Polymarket liquidity rewards - quoted uptime, spread and size of our resting quotes per market,
+// and the reward share they earn
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::order_manager::Quote;
+
+/// A market's reward program, from Gamma (`rewardsMaxSpread`, `rewardsMinSize`, `clobRewards`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardParams {
+    /// Widest distance from the midpoint that still scores, in cents
+    pub max_spread_cents: f64,
+    /// Smallest quote that scores, in contracts
+    pub min_size: f64,
+    /// Paid out across the market's makers per day
+    pub daily_rate_usd: f64,
+}
+
+impl RewardParams {
+    /// Polymarket's order score: ((v - s) / v)² × size for a quote `spread_cents` from the
+    /// midpoint, 0 outside the band or under the minimum size. Single-sided quotes are
+    /// scored in full (the venue's two-sided adjustment isn't modelled).
+    pub fn score(&self, spread_cents: f64, size: f64) -> f64 {
+        let v = self.max_spread_cents;
+        if v <= 0.0 || size < self.min_size || !(0.0..v).contains(&spread_cents.abs()) {
+            return 0.0;
+        }
+        ((v - spread_cents.abs()) / v).powi(2) * size
+    }
+
+    /// Score of the resting bids in a book (price in cents, size in contracts) around `mid_cents`
+    pub fn book_score(&self, mid_cents: f64, levels: impl IntoIterator<Item = (f64, f64)>) -> f64 {
+        levels.into_iter().map(|(price, size)| self.score(mid_cents - price, size)).sum()
+    }
+}
+
+/// Where one market's reward estimate stands
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RewardStats {
+    /// Seconds the market has been tracked (since its first midpoint)
+    pub tracked_secs: f64,
+    /// Seconds at least one of our quotes scored
+    pub quoted_secs: f64,
+    /// Score-weighted distance from the midpoint × seconds, over quoted time
+    spread_secs: f64,
+    /// Scoring contracts × seconds, over quoted time
+    size_secs: f64,
+    pub estimated_usd: f64,
+}
+
+impl RewardStats {
+    /// Share of tracked time we had a scoring quote up
+    pub fn uptime(&self) -> f64 {
+        if self.tracked_secs > 0.0 { self.quoted_secs / self.tracked_secs } else { 0.0 }
+    }
+
+    /// Average distance of our scoring quotes from the midpoint, in cents
+    pub fn avg_spread_cents(&self) -> f64 {
+        if self.quoted_secs > 0.0 { self.spread_secs / self.quoted_secs } else { 0.0 }
+    }
+
+    /// Average scoring size while quoted, in contracts
+    pub fn avg_size(&self) -> f64 {
+        if self.quoted_secs > 0.0 { self.size_secs / self.quoted_secs } else { 0.0 }
+    }
+}
+
+#[derive(Debug)]
+struct MarketRewards {
+    params: RewardParams,
+    mid_cents: Option<f64>,
+    /// Score of everyone else's resting size, from the book
+    others_score: f64,
+    /// Our resting quotes by order manager key: (price in cents, contracts)
+    quotes: HashMap<String, (i64, i64)>,
+    last: Option<Instant>,
+    stats: RewardStats,
+}
+
+impl MarketRewards {
+    /// Credit the time since the last change to the state that held over it
+    fn accrue(&mut self, now: Instant) {
+        let (Some(last), Some(mid)) = (self.last, self.mid_cents) else {
+            self.last = self.mid_cents.map(|_| now);
+            return;
+        };
+        let secs = now.saturating_duration_since(last).as_secs_f64();
+        self.last = Some(now);
+        self.stats.tracked_secs += secs;
+
+        let (mut ours, mut spread, mut size) = (0.0, 0.0, 0.0);
+        for &(price, count) in self.quotes.values() {
+            let distance = (mid - price as f64).abs();
+            let score = self.params.score(distance, count as f64);
+            if score > 0.0 {
+                ours += score;
+                spread += distance * score;
+                size += count as f64;
+            }
+        }
+        if ours <= 0.0 {
+            return;
+        }
+        self.stats.quoted_secs += secs;
+        self.stats.spread_secs += spread / ours * secs;
+        self.stats.size_secs += size * secs;
+        self.stats.estimated_usd += self.params.daily_rate_usd * secs / 86_400.0 * ours / (ours + self.others_score);
+    }
+}
+
+/// Reward estimates per enrolled market (keyed like the quotes: the token / ticker quoted).
+/// Quotes on markets that aren't enrolled are ignored, so Kalshi quotes pass straight through.
+#[derive(Debug, Default)]
+pub struct RewardTracker {
+    markets: Mutex<HashMap<String, MarketRewards>>,
+}
+
+impl RewardTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking `market` under its reward program (a re-enrollment only updates the program)
+    pub fn enroll(&self, market: &str, params: RewardParams) {
+        let mut markets = self.markets.lock().unwrap();
+        match markets.get_mut(market) {
+            Some(m) => {
+                m.accrue(Instant::now());
+                m.params = params;
+            }
+            None => {
+                markets.insert(market.to_string(), MarketRewards {
+                    params, mid_cents: None, others_score: 0.0, quotes: HashMap::new(), last: None, stats: RewardStats::default(),
+                });
+            }
+        }
+    }
+
+    /// Latest midpoint and the score of the book excluding our own quotes (see `RewardParams::book_score`)
+    pub fn update_book(&self, market: &str, mid_cents: f64, others_score: f64) {
+        self.update_book_at(market, mid_cents, others_score, Instant::now());
+    }
+
+    fn update_book_at(&self, market: &str, mid_cents: f64, others_score: f64, now: Instant) {
+        self.with_market(market, now, |m| {
+            m.mid_cents = Some(mid_cents);
+            m.others_score = others_score.max(0.0);
+        });
+    }
+
+    /// A quote of ours rests (replacing whatever rested under `key`)
+    pub fn quote_live(&self, key: &str, quote: &Quote) {
+        self.quote_live_at(key, quote, Instant::now());
+    }
+
+    fn quote_live_at(&self, key: &str, quote: &Quote, now: Instant) {
+        self.with_market(&quote.ticker, now, |m| {
+            m.quotes.insert(key.to_string(), (quote.price_cents, quote.count));
+        });
+    }
+
+    /// The quote under `key` no longer rests
+    pub fn quote_pulled(&self, key: &str, quote: &Quote) {
+        self.quote_pulled_at(key, quote, Instant::now());
+    }
+
+    fn quote_pulled_at(&self, key: &str, quote: &Quote, now: Instant) {
+        self.with_market(&quote.ticker, now, |m| {
+            m.quotes.remove(key);
+        });
+    }
+
+    fn with_market(&self, market: &str, now: Instant, update: impl FnOnce(&mut MarketRewards)) {
+        if let Some(m) = self.markets.lock().unwrap().get_mut(market) {
+            m.accrue(now);
+            update(m);
+            m.accrue(now);
+        }
+    }
+
+    /// Stats of every enrolled market, accrued to now
+    pub fn stats(&self) -> Vec<(String, RewardStats)> {
+        self.stats_at(Instant::now())
+    }
+
+    fn stats_at(&self, now: Instant) -> Vec<(String, RewardStats)> {
+        let mut markets = self.markets.lock().unwrap();
+        let mut stats: Vec<_> = markets.iter_mut()
+            .map(|(market, m)| {
+                m.accrue(now);
+                (market.clone(), m.stats.clone())
+            })
+            .collect();
+        stats.sort_by(|a, b| a.0.cmp(&b.0));
+        stats
+    }
+}
+
+/// Process-wide tracker; the order manager reports every quote to it
+pub fn tracker() -> &'static RewardTracker {
+    static TRACKER: OnceLock<RewardTracker> = OnceLock::new();
+    TRACKER.get_or_init(RewardTracker::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const PARAMS: RewardParams = RewardParams { max_spread_cents: 3.0, min_size: 50.0, daily_rate_usd: 86.4 };
+
+    fn quote(price_cents: i64, count: i64) -> Quote {
+        Quote { ticker: "tok".into(), side: "yes", price_cents, count }
+    }
+
+    #[test]
+    fn test_score_band_and_min_size() {
+        assert_eq!(PARAMS.score(0.0, 100.0), 100.0);
+        assert!((PARAMS.score(1.5, 100.0) - 25.0).abs() < 1e-9);
+        assert_eq!(PARAMS.score(3.0, 100.0), 0.0);
+        assert_eq!(PARAMS.score(1.0, 49.0), 0.0);
+        // Bids at 49 and 47 around a 50 mid: only the first is inside the band
+        assert!((PARAMS.book_score(50.0, [(49.0, 90.0), (47.0, 500.0)]) - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_uptime_spread_size_and_share() {
+        let t = RewardTracker::new();
+        let at = {
+            let base = Instant::now();
+            move |secs: u64| base + Duration::from_secs(secs)
+        };
+        t.enroll("tok", PARAMS);
+        t.update_book_at("tok", 50.0, 100.0, at(0));
+        // 100s unquoted, then 100s at 49 x 100 (score 44.4 vs 100 for the rest of the book)
+        t.quote_live_at("q1", &quote(49, 100), at(100));
+        t.quote_pulled_at("q1", &quote(49, 100), at(200));
+        let stats = t.stats_at(at(200));
+        let s = &stats[0].1;
+        assert_eq!(stats[0].0, "tok");
+        assert_eq!((s.tracked_secs, s.quoted_secs), (200.0, 100.0));
+        assert_eq!(s.uptime(), 0.5);
+        assert!((s.avg_spread_cents() - 1.0).abs() < 1e-9);
+        assert_eq!(s.avg_size(), 100.0);
+        // $0.001/s paid out, we hold 44.4 / 144.4 of the score
+        let ours = 400.0 / 9.0;
+        assert!((s.estimated_usd - 0.1 * ours / (ours + 100.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unenrolled_and_unscored_quotes() {
+        let t = RewardTracker::new();
+        // Kalshi quotes and markets without a program are ignored
+        t.quote_live("k1", &Quote { ticker: "KXT-1".into(), side: "yes", price_cents: 40, count: 10 });
+        assert!(t.stats().is_empty());
+
+        let at = {
+            let base = Instant::now();
+            move |secs: u64| base + Duration::from_secs(secs)
+        };
+        t.enroll("tok", PARAMS);
+        // No midpoint yet: nothing is tracked
+        t.quote_live_at("q1", &quote(49, 100), at(0));
+        assert_eq!(t.stats_at(at(50))[0].1, RewardStats::default());
+        // Outside the band, then under the minimum size
+        t.update_book_at("tok", 55.0, 0.0, at(50));
+        t.quote_live_at("q1", &quote(54, 10), at(100));
+        let s = t.stats_at(at(150))[0].1.clone();
+        assert_eq!((s.tracked_secs, s.quoted_secs, s.estimated_usd), (100.0, 0.0, 0.0));
+    }
+}
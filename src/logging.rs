@@ -0,0 +1,351 @@
+// src/logging.rs
+// Log sinks from bot.toml - console plus an optional rotating file, each with its own level and format
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Config file read at startup (override with BOT_CONFIG)
+pub const DEFAULT_CONFIG_FILE: &str = "bot.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per event (timestamp, level, target, fields)
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    Hourly,
+    #[default]
+    Daily,
+    /// Size limit only
+    Never,
+}
+
+impl Rotation {
+    /// Period the current file belongs to; a new period starts a new file
+    fn period(&self, now: chrono::DateTime<chrono::Utc>) -> String {
+        match self {
+            Self::Hourly => now.format("%Y%m%d%H").to_string(),
+            Self::Daily => now.format("%Y%m%d").to_string(),
+            Self::Never => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ConsoleSink {
+    /// Level for arb_bot and the binary (RUST_LOG still wins); unset = the binary's default
+    pub level: Option<String>,
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FileSink {
+    /// Log file (relative paths resolve inside DATA_DIR)
+    pub path: PathBuf,
+    pub level: String,
+    pub format: LogFormat,
+    pub rotation: Rotation,
+    /// Rotate when the file reaches this size (0 = no size limit)
+    pub max_size_mb: u64,
+    /// Rotated files kept; older ones are deleted
+    pub max_files: usize,
+}
+
+impl Default for FileSink {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("logs/arb-bot.log"),
+            level: "info".to_string(),
+            format: LogFormat::Json,
+            rotation: Rotation::Daily,
+            max_size_mb: 100,
+            max_files: 7,
+        }
+    }
+}
+
+/// `[logging]` section of bot.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub console: ConsoleSink,
+    /// No file logging unless `[logging.file]` is present
+    pub file: Option<FileSink>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BotToml {
+    #[serde(default)]
+    logging: LoggingConfig,
+}
+
+/// BOT_CONFIG, or bot.toml
+pub fn config_path() -> String {
+    std::env::var("BOT_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_string())
+}
+
+impl LoggingConfig {
+    /// Read BOT_CONFIG (default bot.toml); a missing file means console-only defaults
+    pub fn load() -> Result<Self> {
+        let path = config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("invalid {}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str::<BotToml>(contents)?.logging)
+    }
+}
+
+/// File writer that rolls over on a size limit or a new hour/day, keeping `max_files` old files
+pub struct RotatingWriter {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    period: String,
+    rotation: Rotation,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingWriter {
+    pub fn open(sink: &FileSink, path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)
+            .with_context(|| format!("opening {}", path.display()))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            period: sink.rotation.period(chrono::Utc::now()),
+            rotation: sink.rotation,
+            max_bytes: sink.max_size_mb.saturating_mul(1024 * 1024),
+            max_files: sink.max_files,
+        })
+    }
+
+    /// `<path>.<UTC timestamp>` (with a counter if that name is taken)
+    fn rotated_name(&self) -> PathBuf {
+        let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+        let mut n = 0;
+        loop {
+            let mut name = self.path.as_os_str().to_owned();
+            name.push(format!(".{}", stamp));
+            if n > 0 {
+                name.push(format!("-{}", n));
+            }
+            let candidate = PathBuf::from(name);
+            if !candidate.exists() {
+                return candidate;
+            }
+            n += 1;
+        }
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        std::fs::rename(&self.path, self.rotated_name())?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        self.prune();
+        Ok(())
+    }
+
+    /// Delete the oldest rotated files beyond `max_files`
+    fn prune(&self) {
+        let (Some(dir), Some(name)) = (self.path.parent(), self.path.file_name()) else { return };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let prefix = format!("{}.", name.to_string_lossy());
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        let mut rotated: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            .map(|e| e.path())
+            .collect();
+        // Timestamped names sort oldest first
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.max_files);
+        for old in &rotated[..excess] {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+}
+
+impl Write for RotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let period = self.rotation.period(chrono::Utc::now());
+        let over_size = self.max_bytes > 0 && self.size > 0 && self.size + buf.len() as u64 > self.max_bytes;
+        if period != self.period || over_size {
+            self.period = period;
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// JSON-lines event formatter: {"timestamp", "level", "target", "message", ...fields}
+pub struct JsonFormat;
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::String(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let meta = event.metadata();
+        let mut obj = Map::new();
+        obj.insert("timestamp".into(), Value::String(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)));
+        obj.insert("level".into(), Value::String(meta.level().to_string()));
+        obj.insert("target".into(), Value::String(meta.target().to_string()));
+        event.record(&mut JsonVisitor(&mut obj));
+        writeln!(writer, "{}", Value::Object(obj))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    #[test]
+    fn test_parse_config() {
+        let config = LoggingConfig::parse(r#"
+            [logging.console]
+            level = "warn"
+
+            [logging.file]
+            path = "/var/log/arb/bot.log"
+            level = "debug"
+            rotation = "hourly"
+            max_size_mb = 10
+        "#).unwrap();
+        assert_eq!(config.console.level.as_deref(), Some("warn"));
+        assert_eq!(config.console.format, LogFormat::Pretty);
+        let file = config.file.unwrap();
+        assert_eq!((file.level.as_str(), file.rotation, file.max_size_mb), ("debug", Rotation::Hourly, 10));
+        // Unset keys keep their defaults
+        assert_eq!((file.format, file.max_files), (LogFormat::Json, 7));
+
+        let empty = LoggingConfig::parse("").unwrap();
+        assert!(empty.file.is_none());
+        assert!(LoggingConfig::parse("[logging.file]\nrotation = \"weekly\"").is_err());
+    }
+
+    #[test]
+    fn test_rotation_by_size_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("log_rotate_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("bot.log");
+        let sink = FileSink { rotation: Rotation::Never, max_size_mb: 0, max_files: 2, ..Default::default() };
+        let mut writer = RotatingWriter::open(&sink, &path).unwrap();
+        writer.max_bytes = 100;
+
+        let line = [b'x'; 60];
+        for _ in 0..5 {
+            writer.write_all(&line).unwrap();
+        }
+        // Each 60-byte write after the first exceeds 100 bytes and starts a new file
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 60);
+        let rotated = std::fs::read_dir(&dir).unwrap().count() - 1;
+        assert_eq!(rotated, 2);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Capture;
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_format() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(JsonFormat)
+            .with_writer(capture.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(contracts = 5u64, "[EXEC] filled {}", "BTC");
+        });
+
+        let out = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let json: Value = serde_json::from_str(out.trim()).unwrap();
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["message"], "[EXEC] filled BTC");
+        assert_eq!(json["contracts"], 5);
+        assert!(json["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
@@ -3,84 +3,194 @@
 //! Strategy: BUY YES on Platform A + BUY NO on Platform B
 //! Arb exists when: YES_ask + NO_ask < $1.00
 
+mod app;
+mod aggression;
+mod arb_confirm;
+mod archive;
+#[allow(dead_code)] // validated with bot.toml here; only the Up/Down bot trades per asset
+mod asset_params;
+mod balances;
+mod basis_risk;
+mod basket;
+#[allow(dead_code)] // the feed keeps ask ladders; bids and depth views are for the tools
+mod book;
 mod cache;
 mod circuit_breaker;
+#[allow(dead_code)] // the scanner's exchange clock lives here too; this binary only signs with it
+mod clock_skew;
+mod close_race;
 mod config;
+mod config_check;
+#[allow(dead_code)] // halts here go through the circuit breaker; the halt switch is the Up/Down bot's
+mod control;
+mod cost_model;
+#[cfg(feature = "onchain")]
+mod ctf_reconcile;
 mod discovery;
+mod events;
+mod exec_mode;
+mod exec_pool;
+mod feed_liveness;
+mod fill_detail;
+mod fill_retry;
+mod intents;
 mod execution;
 mod kalshi;
+mod kalshi_margin;
+#[allow(dead_code)] // only the Up/Down bot runs an election; the order fence here never trips
+mod leader;
+mod leg_order;
+mod logging;
+mod market_key;
+#[allow(dead_code)] // validated with bot.toml here; only the Up/Down bot scans market sets
+mod market_sets;
+mod metrics;
+mod notify;
+#[cfg(feature = "onchain")]
+#[allow(dead_code)] // the bot only redeems, replaying deferred redemptions
+mod onchain;
+mod opp_cost;
+mod opp_rate;
+mod opportunities;
+mod order_errors;
+mod order_rate;
+mod order_tags;
+mod own_orders;
+mod overrides;
+mod pnl_series;
 mod polymarket;
 mod polymarket_clob;
+mod polymarket_ws_types;
+mod position_query;
+mod position_schema;
+#[allow(dead_code)] // sessions start fresh here, so the store is only written; loading is the Up/Down bot's
+mod position_store;
 mod position_tracker;
+mod rebalance;
+mod replay;
+#[cfg(feature = "onchain")]
+#[allow(dead_code)] // only the reconciliation reads here; transactions use it through onchain
+mod rpc_pool;
+mod run_windows;
+mod runtimes;
+mod schema_watch;
+#[allow(dead_code)] // Gamma results are read by the Up/Down bot
+mod settlement;
+mod shadow;
+#[allow(dead_code)] // Marks hold the Up/Down bot's halts and hold timers
+mod snapshots;
+mod sql;
+mod storage;
+mod synthetic;
+mod telemetry;
+mod throttle;
+mod trade_tape;
+mod tranches;
 mod types;
-
-use anyhow::{Context, Result};
+#[allow(dead_code)] // only --check-config parses the rules here; the Up/Down bot applies them
+mod unit_economics;
+mod units;
+mod venue_health;
+mod watchdog;
+
+use anyhow::Result;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
 use cache::TeamCache;
-use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use circuit_breaker::{risk_state_path, CircuitBreaker, CircuitBreakerConfig};
 use config::{ARB_THRESHOLD, ENABLED_LEAGUES, WS_RECONNECT_DELAY_SECS};
 use discovery::DiscoveryClient;
 use execution::{ExecutionEngine, create_execution_channel, run_execution_loop};
-use kalshi::{KalshiConfig, KalshiApiClient};
-use polymarket_clob::{PolymarketAsyncClient, PreparedCreds, SharedAsyncClient};
+use kalshi::KalshiApiClient;
+use position_query::PositionQueries;
+use position_store::PositionStoreConfig;
 use position_tracker::{PositionTracker, create_position_channel, position_writer_loop};
+use runtimes::RuntimeConfig;
+use snapshots::{SnapshotConfig, SnapshotStore};
+use throttle::{ThrottleConfig, TradeThrottle};
 use types::{GlobalState, PriceCents};
 
-/// Polymarket CLOB API host
-const POLY_CLOB_HOST: &str = "https://clob.polymarket.com";
-/// Polygon chain ID
-const POLYGON_CHAIN_ID: u64 = 137;
+/// Strategies this binary runs (run windows, config validation)
+const STRATEGIES: &[&str] = &["cross", "poly_only", "kalshi_only"];
+
+/// Strategy state snapshot (under DATA_DIR)
+const SNAPSHOT_FILE: &str = "strategy_state.json";
+
+/// Control API asset pauses, restored on startup (under DATA_DIR)
+const CONTROL_STATE_FILE: &str = "control.json";
+
+/// Control API halts trip the circuit breaker and re-arms reset it, so both persist across restarts
+struct BreakerControl {
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl control::ControlHandler for BreakerControl {
+    async fn halt(&self, _caller: &control::Caller) -> Result<String> {
+        self.breaker.halt().await;
+        Ok("circuit breaker tripped (manual halt)".into())
+    }
+
+    async fn rearm(&self, _caller: &control::Caller) -> Result<String> {
+        let was = self.breaker.status().await.trip_reason;
+        self.breaker.reset().await;
+        Ok(match was {
+            Some(reason) => format!("circuit breaker reset (was: {})", reason),
+            None => "circuit breaker was not tripped".into(),
+        })
+    }
+
+    async fn status(&self) -> serde_json::Value {
+        let status = self.breaker.status().await;
+        serde_json::json!({
+            "exec_mode": exec_mode::current().as_str(),
+            "trading_allowed": self.breaker.is_trading_allowed(),
+            "trip_reason": status.trip_reason.map(|r| r.to_string()),
+            "daily_pnl": status.daily_pnl,
+            "daily_trades": status.daily_trades,
+            "total_position": status.total_position,
+        })
+    }
+}
+
+fn main() -> Result<()> {
+    // Load .env and initialize logging
+    let app = app::bootstrap().init();
+
+    // Market data and execution own this runtime; bookkeeping runs on its own (BOOKKEEPING_THREADS)
+    runtimes::hot(&RuntimeConfig::from_env())?.block_on(run(app))
+}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("arb_bot=info".parse().unwrap()),
-        )
-        .init();
+async fn run(app: app::App) -> Result<()> {
 
-    // Load .env file first
-    dotenvy::dotenv().ok();
+    // Fail fast on bad config; `--check-config` stops here
+    config_check::validate(STRATEGIES)?;
+    if config_check::check_only() {
+        info!("✅ Configuration OK ({})", STRATEGIES.join(", "));
+        return Ok(());
+    }
 
     info!("🎯 Arb Bot v2.0");
     info!("   Threshold: <{:.1}¢ for {:.1}% profit",
           ARB_THRESHOLD * 100.0, (1.0 - ARB_THRESHOLD) * 100.0);
     info!("   Leagues: {:?}", ENABLED_LEAGUES);
-
-    // Check for dry run mode
-    let dry_run = std::env::var("DRY_RUN").map(|v| v == "1" || v == "true").unwrap_or(true);
-    if dry_run {
-        info!("   Mode: DRY RUN (set DRY_RUN=0 to execute)");
-    } else {
-        warn!("   Mode: LIVE EXECUTION");
+    let confirm = arb_confirm::config();
+    if !confirm.is_instant() {
+        info!("   Confirmation: {} updates / {}ms", confirm.min_updates, confirm.min_duration_ms);
     }
 
+    // Live / dry run / simulated / shadow (EXEC_MODE)
+    let mode = app.exec_mode()?;
+
     // Load Kalshi credentials
-    let kalshi_config = KalshiConfig::from_env()?;
+    let kalshi_config = app.kalshi_config()?;
     info!("[KALSHI] API key loaded");
 
-    // Load Polymarket credentials
-    let poly_private_key = std::env::var("POLY_PRIVATE_KEY")
-        .context("POLY_PRIVATE_KEY not set")?;
-    let poly_funder = std::env::var("POLY_FUNDER")
-        .context("POLY_FUNDER not set (your wallet address)")?;
-
     // Create async Polymarket client and derive API credentials
-    info!("[POLYMARKET] Creating async client and deriving API credentials...");
-    let poly_async_client = PolymarketAsyncClient::new(
-        POLY_CLOB_HOST,
-        POLYGON_CHAIN_ID,
-        &poly_private_key,
-        &poly_funder,
-    )?;
-    let api_creds = poly_async_client.derive_api_key(0).await?;
-    let prepared_creds = PreparedCreds::from_api_creds(&api_creds)?;
-    let poly_async = Arc::new(SharedAsyncClient::new(poly_async_client, prepared_creds, POLYGON_CHAIN_ID));
+    let poly_funder = app.poly_credentials()?.funder;
+    let poly_async = Arc::new(app.poly_client().await?);
 
     // Load neg_risk cache from Python script output
     match poly_async.load_cache(".clob_market_cache.json") {
@@ -97,6 +207,9 @@ async fn main() -> Result<()> {
     // Create Kalshi API client
     let kalshi_api = Arc::new(KalshiApiClient::new(kalshi_config));
 
+    // Blacklist, per-market thresholds and pinned outcomes (OVERRIDES_FILE, reloaded on change)
+    overrides::start(overrides::OverridesConfig::from_env())?;
+
     // Run discovery (with caching support)
     let force_discovery = std::env::var("FORCE_DISCOVERY")
         .map(|v| v == "1" || v == "true")
@@ -106,15 +219,16 @@ async fn main() -> Result<()> {
           if force_discovery { " (forced refresh)" } else { "" });
 
     let discovery = DiscoveryClient::new(
-        KalshiApiClient::new(KalshiConfig::from_env()?),
+        app.kalshi_client()?,
         team_cache
     );
 
-    let result = if force_discovery {
+    let mut result = if force_discovery {
         discovery.discover_all_force(ENABLED_LEAGUES).await
     } else {
         discovery.discover_all(ENABLED_LEAGUES).await
     };
+    discovery::apply_overrides(&overrides::current(), &mut result.pairs);
 
     info!("📊 Discovery complete:");
     info!("   - Market pairs found: {}", result.pairs.len());
@@ -149,14 +263,168 @@ async fn main() -> Result<()> {
         s
     });
 
+    // Polymarket fee rates: signed into every order and netted out of each edge, so they are
+    // looked up here rather than on the order path
+    let yes_tokens: Vec<String> = state.markets.iter()
+        .filter_map(|m| m.pair.as_ref())
+        .map(|p| p.poly_yes_token.to_string())
+        .collect();
+    let fee_rates = poly_async.prefetch_fee_rates(&yes_tokens).await;
+    for market in &state.markets {
+        let Some(pair) = &market.pair else { continue };
+        if let Some(&bps) = fee_rates.get(pair.poly_yes_token.as_ref()) {
+            market.set_poly_fee_bps(bps);
+            poly_async.set_fee_rate(&pair.poly_no_token, bps);
+        }
+    }
+    let fee_markets = state.markets.iter().filter(|m| m.poly_fee_bps() > 0).count();
+    info!("💸 Polymarket fee rates loaded for {} tokens ({} markets charge a taker fee)", fee_rates.len(), fee_markets);
+
+    // Event bus consumers (subscribe before anything publishes)
+    events::spawn_metrics_consumer();
+    events::spawn_audit_consumer();
+    notify::start()?;
+    opportunities::spawn_writer();
+    cost_model::spawn(cost_model::CostModelConfig::from_env());
+    opp_rate::spawn(opp_rate::OppRateConfig::from_env());
+    archive::spawn(archive::ArchiveConfig::from_env(), archive::default_sets());
+    telemetry::spawn(telemetry::TelemetryConfig::from_env());
+    run_windows::start(STRATEGIES)?;
+
+    // Venue status polling (degrades / pauses trading during incidents)
+    venue_health::spawn_poller(Some(kalshi_api.clone()), true);
+
+    // Kalshi bracket ladders against Polymarket above-strike markets (SYNTHETIC_PAIRS)
+    synthetic::spawn(synthetic::SyntheticConfig::from_env(), Arc::new(discovery), poly_async.clone());
+
     // Create execution infrastructure
     let (exec_tx, exec_rx) = create_execution_channel();
-    let circuit_breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::from_env()));
-
-    let position_tracker = Arc::new(RwLock::new(PositionTracker::new()));
+    // Today's loss, trade count and trips carry over a restart
+    let circuit_breaker = Arc::new(CircuitBreaker::persistent(CircuitBreakerConfig::from_env(), risk_state_path())?);
+    // Remote halt / pause / re-arm (CONTROL_LISTEN); the halt is the breaker's, pauses persist here
+    control::switches().persist_to(storage::state_path(CONTROL_STATE_FILE))?;
+    control::spawn(control::ControlConfig::from_env(), Arc::new(BreakerControl { breaker: circuit_breaker.clone() }))?;
+
+    let throttle_config = ThrottleConfig::from_env();
+    info!("   Throttle: {}/min global, {}/min per market, {}s min interval",
+          throttle_config.max_per_minute,
+          throttle_config.max_per_market_per_minute,
+          throttle_config.min_interval_secs);
+    let throttle = Arc::new(TradeThrottle::new(throttle_config));
+
+    // Fresh session state, but locked so a second instance can't share the data dir
+    let position_tracker = Arc::new(RwLock::new(PositionTracker::new().locked()?));
     let (position_channel, position_rx) = create_position_channel();
+    // Trades behind Polymarket fills are looked up after the order returns
+    position_channel.collect_fill_details();
+
+    // Strategy state (throttle cooldowns, suspended markets) carried over from the last run
+    let snapshots = Arc::new(SnapshotStore::load(&SnapshotConfig::from_env(SNAPSHOT_FILE), chrono::Utc::now().timestamp()));
+    snapshots.register(throttle.clone());
+    snapshots.spawn();
+
+    // Sessions start fresh here, so a SQL store (POSITION_STORE) only receives this session's fills
+    let position_store = position_store::open_store(&PositionStoreConfig::from_env())?
+        .map(|s| Box::new(s) as Box<dyn position_store::PositionStore>);
+    runtimes::spawn_bookkeeping(position_writer_loop(position_rx, position_tracker.clone(), position_store));
+
+    // Ended pairs resolve from the Kalshi market's result (SETTLE_POLL_SECS)
+    let settle_pairs: Arc<HashMap<String, Arc<types::MarketPair>>> = Arc::new(state.markets.iter()
+        .filter_map(|m| m.pair.as_ref())
+        .map(|p| (p.pair_id.to_string(), p.clone()))
+        .collect());
+    let settle_api = kalshi_api.clone();
+    settlement::spawn(settlement::SettlementConfig::from_env(), position_tracker.clone(), position_channel.clone(), move |position| {
+        let (pairs, api) = (settle_pairs.clone(), settle_api.clone());
+        async move {
+            let Some(pair) = pairs.get(&position.market_id) else { return Ok(None) };
+            let markets = api.get_markets(&pair.kalshi_event_ticker).await?;
+            let result = markets.iter()
+                .find(|m| m.ticker == pair.kalshi_market_ticker.as_ref())
+                .and_then(|m| m.result.as_deref());
+            Ok(settlement::kalshi_settlement(result))
+        }
+    });
+
+    // Resource leak watch + periodic state dumps
+    let watchdog_positions = PositionQueries::new(position_tracker.clone());
+    let watchdog_breaker = circuit_breaker.clone();
+    let watchdog_state = state.clone();
+    watchdog::spawn(watchdog::WatchdogConfig::from_env(), move || {
+        let (positions, breaker, state) = (watchdog_positions.clone(), watchdog_breaker.clone(), watchdog_state.clone());
+        async move {
+            serde_json::json!({
+                "exec_mode": exec_mode::current().as_str(),
+                "markets": state.market_count(),
+                "trading_allowed": breaker.is_trading_allowed(),
+                "positions": positions.summary().await,
+                "exposure_by_asset": positions.exposure_by_asset().await,
+                "realized_pnl": positions.pnl_windows().await,
+                "balances": balances::snapshot().summary(chrono::Utc::now().timestamp(), balances::BalanceConfig::from_env().stale_secs),
+            })
+        }
+    });
+
+    // Polymarket legs vs the CTF tokens actually in the funder wallet
+    #[cfg(feature = "onchain")]
+    ctf_reconcile::spawn(ctf_reconcile::CtfReconcileConfig::from_env(), Arc::new(rpc_pool::RpcPool::new(rpc_pool::RpcConfig::from_env())?),
+                         poly_funder.clone(), state.clone(), position_tracker.clone());
+
+    // Equity / exposure time series for long-run analysis
+    pnl_series::spawn_recorder(position_tracker,
+        storage::state_path(std::env::var("PNL_SERIES_FILE").unwrap_or_else(|_| "pnl_series.csv".to_string())));
+
+    // Reconcile orders journaled by a run that died mid-execution
+    let balance_config = balances::BalanceConfig::from_env();
+    balances::spawn_poller(Some(kalshi_api.clone()), Some(poly_async.clone()), balance_config.clone());
+    rebalance::spawn_monitor(rebalance::RebalanceConfig::from_env(), balance_config.clone());
+
+    let intent_journal = Arc::new(intents::IntentJournal::from_env());
+    intents::recover(&intent_journal, Some(&kalshi_api), Some(&poly_async), &position_channel).await;
+
+    // Exchange-side view of the last day's Kalshi orders, by strategy / session tag
+    match kalshi_api.recent_orders(chrono::Utc::now().timestamp() - 86_400).await {
+        Ok(orders) => {
+            let groups = order_tags::attribute(orders.iter().map(|o| (
+                o.client_order_id.as_deref(),
+                o.filled_count(),
+                o.taker_fill_cost.unwrap_or(0) + o.maker_fill_cost.unwrap_or(0),
+            )));
+            info!("[TAGS] Session {}; {} Kalshi order(s) in the last 24h", order_tags::session_id(), orders.len());
+            for a in &groups {
+                info!("[TAGS]   {:<12} {:<8} {:>4} orders {:>6} filled ${:.2}",
+                      a.strategy, a.session, a.orders, a.contracts, a.cost_cents as f64 / 100.0);
+            }
+        }
+        Err(e) => warn!("[TAGS] Could not fetch recent Kalshi orders for attribution: {}", e),
+    }
 
-    tokio::spawn(position_writer_loop(position_rx, position_tracker));
+    // Deferred cancels, redemptions and settlement checks (queued by any process sharing the
+    // buffer; one replays them)
+    let replay_actions = replay::VenueActions {
+        kalshi: Some(kalshi_api.clone()),
+        polymarket: Some(poly_async.clone()),
+        #[cfg(feature = "onchain")]
+        onchain: app.poly_credentials().and_then(|creds| onchain::OnchainClient::new(
+            onchain::OnchainConfig::from_env(), &creds.private_key, &creds.funder,
+        )).map(Arc::new).map_err(|e| warn!("[REPLAY] No onchain client, redemptions stay queued: {}", e)).ok(),
+    };
+    replay::spawn(replay_actions.clone());
+
+    // Fills from orders that raced a market's close, still to be confirmed at settlement
+    let close_race_fills = close_race::load(&close_race::CloseRaceConfig::from_env().file);
+    if !close_race_fills.is_empty() {
+        warn!("[CLOSE] {} close-race fill(s) awaiting settlement verification", close_race_fills.len());
+        runtimes::spawn_bookkeeping(async move {
+            for fill in close_race_fills {
+                let Some(venue) = venue_health::Venue::from_name(&fill.platform) else { continue };
+                let check = replay::DeferredAction::SettlementCheck { venue, order_id: fill.order_id, market: fill.market };
+                if let Err(e) = replay::run_or_defer(&replay_actions, check).await {
+                    warn!("[CLOSE] Settlement check failed: {}", e);
+                }
+            }
+        });
+    }
 
     let threshold_cents: PriceCents = ((ARB_THRESHOLD * 100.0).round() as u16).max(1);
     info!("   Threshold: {} cents", threshold_cents);
@@ -166,8 +434,10 @@ async fn main() -> Result<()> {
         poly_async,
         state.clone(),
         circuit_breaker.clone(),
+        throttle,
         position_channel,
-        dry_run,
+        intent_journal,
+        mode,
     ));
 
     let exec_handle = tokio::spawn(run_execution_loop(exec_rx, engine));
@@ -178,7 +448,7 @@ async fn main() -> Result<()> {
     if test_arb {
         let test_state = state.clone();
         let test_exec_tx = exec_tx.clone();
-        let test_dry_run = dry_run;
+        let test_mode = mode;
 
         // Parse arb type from environment (default: poly_yes_kalshi_no)
         let arb_type_str = std::env::var("TEST_ARB_TYPE").unwrap_or_else(|_| "poly_yes_kalshi_no".to_string());
@@ -225,12 +495,13 @@ async fn main() -> Result<()> {
                             no_size: 1000,   // 1000¢ = 10 contracts
                             arb_type,
                             detected_ns: 0,
+                            poly_fee_bps: market.poly_fee_bps(),
                         };
 
                         warn!("[TEST] 🧪 Injecting FAKE {:?} arb for: {}", arb_type, pair.description);
                         warn!("[TEST]    {}", description);
                         warn!("[TEST]    SIZE CAPPED TO 10 CONTRACTS for safety!");
-                        warn!("[TEST]    Execution mode: DRY_RUN={}", test_dry_run);
+                        warn!("[TEST]    Execution mode: {}", test_mode);
 
                         if let Err(e) = test_exec_tx.send(fake_req).await {
                             error!("[TEST] Failed to send fake arb: {}", e);
@@ -246,12 +517,14 @@ async fn main() -> Result<()> {
     let kalshi_state = state.clone();
     let kalshi_exec_tx = exec_tx.clone();
     let kalshi_threshold = threshold_cents;
-    let kalshi_ws_config = KalshiConfig::from_env()?;
+    let kalshi_ws_config = app.kalshi_config()?;
     let kalshi_handle = tokio::spawn(async move {
         loop {
             if let Err(e) = kalshi::run_ws(&kalshi_ws_config, kalshi_state.clone(), kalshi_exec_tx.clone(), kalshi_threshold).await {
                 error!("[KALSHI] Disconnected: {} - reconnecting...", e);
+                events::publish(events::BotEvent::WsReconnected { feed: "kalshi".into(), error: e.to_string() });
             }
+            venue_health::health().record_reconnect(venue_health::Venue::Kalshi);
             tokio::time::sleep(tokio::time::Duration::from_secs(WS_RECONNECT_DELAY_SECS)).await;
         }
     });
@@ -264,7 +537,9 @@ async fn main() -> Result<()> {
         loop {
             if let Err(e) = polymarket::run_ws(poly_state.clone(), poly_exec_tx.clone(), poly_threshold).await {
                 error!("[POLYMARKET] Disconnected: {} - reconnecting...", e);
+                events::publish(events::BotEvent::WsReconnected { feed: "polymarket".into(), error: e.to_string() });
             }
+            venue_health::health().record_reconnect(venue_health::Venue::Polymarket);
             tokio::time::sleep(tokio::time::Duration::from_secs(WS_RECONNECT_DELAY_SECS)).await;
         }
     });
@@ -282,6 +557,7 @@ async fn main() -> Result<()> {
             let mut with_poly = 0;
             let mut with_both = 0;
             // (cost, market_id, p_yes, k_no, k_yes, p_no, fee, is_poly_yes_kalshi_no)
+            #[allow(clippy::type_complexity)]
             let mut best_arb: Option<(u16, u16, u16, u16, u16, u16, u16, bool)> = None;
 
             for market in heartbeat_state.markets.iter().take(market_count) {
@@ -336,6 +612,16 @@ async fn main() -> Result<()> {
             } else if with_both == 0 {
                 warn!("   ⚠️  No markets with BOTH Kalshi and Poly prices - check WebSocket connections");
             }
+
+            if balance_config.enabled() {
+                let now = chrono::Utc::now().timestamp();
+                info!("   {}", balances::snapshot().summary(now, balance_config.stale_secs));
+            }
+
+            let metrics = metrics::snapshot();
+            if !metrics.is_empty() {
+                info!("   📈 {}", metrics);
+            }
         }
     });
 
@@ -0,0 +1,85 @@
+// src/market_key.rs
+// Market keys - one identity per tradable market (venue, slug/condition id, token pair, end time)
+
+use serde::{Deserialize, Serialize};
+
+/// Canonical identity of a market. Question text is not one: recurring markets reuse it
+/// (every day's "Bitcoin Up or Down - 9:00AM-9:15AM ET"), so positions, settlement tags
+/// and journaled intents are keyed by `id()` instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MarketKey {
+    /// "kalshi" or "polymarket"
+    pub venue: String,
+    /// Polymarket slug (or condition ID), Kalshi ticker
+    pub market: String,
+    /// Outcome tokens (empty for Kalshi, and for records migrated from question keys)
+    #[serde(default)]
+    pub yes_token: String,
+    #[serde(default)]
+    pub no_token: String,
+    /// Unix seconds the market ends (0 = open-ended)
+    pub end_ts: i64,
+}
+
+impl MarketKey {
+    #[allow(dead_code)]
+    pub fn polymarket(market: &str, yes_token: &str, no_token: &str, end_ts: i64) -> Self {
+        Self {
+            venue: "polymarket".into(),
+            market: market.to_string(),
+            yes_token: yes_token.to_string(),
+            no_token: no_token.to_string(),
+            end_ts,
+        }
+    }
+
+    /// Key string ("polymarket:btc-updown-15m-1766100600:1766100600")
+    pub fn id(&self) -> String {
+        format!("{}:{}:{}", self.venue, self.market, self.end_ts)
+    }
+
+    /// Whether `id` is a key string (as opposed to a question or other legacy identifier)
+    pub fn is_id(id: &str) -> bool {
+        let mut parts = id.rsplitn(3, ':');
+        let (Some(end), Some(market), Some(venue)) = (parts.next(), parts.next(), parts.next()) else {
+            return false;
+        };
+        matches!(venue, "kalshi" | "polymarket") && !market.is_empty() && end.parse::<i64>().is_ok()
+    }
+}
+
+impl std::fmt::Display for MarketKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intervals_sharing_a_question_get_distinct_ids() {
+        let today = MarketKey::polymarket("btc-updown-15m-1766100600", "1", "2", 1766100600);
+        let tomorrow = MarketKey::polymarket("btc-updown-15m-1766187000", "3", "4", 1766187000);
+        assert_ne!(today.id(), tomorrow.id());
+        assert_eq!(today.id(), "polymarket:btc-updown-15m-1766100600:1766100600");
+    }
+
+    #[test]
+    fn test_is_id() {
+        let key = MarketKey::polymarket("btc-updown-15m-1766100600", "1", "2", 1766100600);
+        assert!(MarketKey::is_id(&key.id()));
+        assert!(!MarketKey::is_id("Bitcoin Up or Down - December 19, 9:00AM-9:15AM ET"));
+        assert!(!MarketKey::is_id("KXNBAGAME-25DEC19LALBOS"));
+        assert!(!MarketKey::is_id("polymarket::17"));
+    }
+
+    #[test]
+    fn test_serde_round_trip_without_tokens() {
+        let key: MarketKey = serde_json::from_str(
+            r#"{"venue":"polymarket","market":"eth-updown-15m-1766100600","end_ts":1766100600}"#).unwrap();
+        assert!(key.yes_token.is_empty());
+        assert_eq!(serde_json::from_str::<MarketKey>(&serde_json::to_string(&key).unwrap()).unwrap(), key);
+    }
+}
@@ -0,0 +1,330 @@
+// src/market_quality.rs
+// Market quality gating - a newly listed market is watched for an observation window and
+// only enrolled for trading if its spread, depth and update rate score above a bar
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::metrics;
+use crate::storage;
+
+/// Default file the enrolled markets are kept in (under DATA_DIR)
+const DEFAULT_ENROLLED_FILE: &str = "enrolled_markets.json";
+
+/// Market quality configuration from environment
+#[derive(Debug, Clone)]
+pub struct QualityConfig {
+    /// Seconds a new market is observed before it may trade (0 = no gating)
+    pub window_secs: u64,
+
+    /// Score (0-1) a market needs to be enrolled
+    pub min_score: f64,
+
+    /// Average bid/ask spread that scores zero (and anything wider)
+    pub max_spread: f64,
+
+    /// Average best-ask size that scores full marks
+    pub target_depth: f64,
+
+    /// Book updates per minute that score full marks
+    pub target_updates_per_min: f64,
+}
+
+impl QualityConfig {
+    pub fn from_env() -> Self {
+        Self {
+            window_secs: std::env::var("QUALITY_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+
+            min_score: std::env::var("QUALITY_MIN_SCORE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.5),
+
+            max_spread: std::env::var("QUALITY_MAX_SPREAD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.10),
+
+            target_depth: std::env::var("QUALITY_TARGET_DEPTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100.0),
+
+            target_updates_per_min: std::env::var("QUALITY_TARGET_UPDATES_PER_MIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(6.0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.window_secs > 0
+    }
+}
+
+/// Process-wide quality configuration (QUALITY_*)
+pub fn config() -> &'static QualityConfig {
+    static CONFIG: OnceLock<QualityConfig> = OnceLock::new();
+    CONFIG.get_or_init(QualityConfig::from_env)
+}
+
+/// A market's quality over one observation window, each component in 0-1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityScore {
+    pub spread: f64,
+    pub depth: f64,
+    pub update_rate: f64,
+    /// Mean of the three components
+    pub score: f64,
+}
+
+impl QualityScore {
+    /// Score averages: `spread` and `depth` per book update, `updates_per_min` over the window
+    pub fn compute(config: &QualityConfig, spread: f64, depth: f64, updates_per_min: f64) -> Self {
+        let spread = (1.0 - spread / config.max_spread).clamp(0.0, 1.0);
+        let depth = (depth / config.target_depth).clamp(0.0, 1.0);
+        let update_rate = (updates_per_min / config.target_updates_per_min).clamp(0.0, 1.0);
+        Self { spread, depth, update_rate, score: (spread + depth + update_rate) / 3.0 }
+    }
+}
+
+/// Enrollment state of one market
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enrollment {
+    /// Inside an observation window - not traded
+    Observing,
+    /// Cleared the bar - traded from now on
+    Enrolled,
+}
+
+/// Observation of a newly listed market. A window that ends below the bar starts
+/// another one, so a listing that fills in later can still be enrolled.
+#[derive(Debug, Clone)]
+pub struct QualityGate {
+    state: Enrollment,
+    window_start: Instant,
+    updates: u32,
+    spread_sum: f64,
+    depth_sum: f64,
+    /// Windows that ended below the bar
+    rejections: u32,
+}
+
+impl QualityGate {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            state: Enrollment::Observing,
+            window_start: now,
+            updates: 0,
+            spread_sum: 0.0,
+            depth_sum: 0.0,
+            rejections: 0,
+        }
+    }
+
+    pub fn enrolled(&self) -> bool {
+        self.state == Enrollment::Enrolled
+    }
+
+    /// Score of the window so far
+    pub fn score(&self, config: &QualityConfig, now: Instant) -> QualityScore {
+        let n = self.updates.max(1) as f64;
+        let minutes = now.duration_since(self.window_start).as_secs_f64().max(1.0) / 60.0;
+        let spread = if self.updates == 0 { config.max_spread } else { self.spread_sum / n };
+        QualityScore::compute(config, spread, self.depth_sum / n, self.updates as f64 / minutes)
+    }
+
+    /// Record a book update of one side (its best ask minus best bid, and best-ask size).
+    /// Returns the window's score when the window closes.
+    pub fn observe(&mut self, config: &QualityConfig, spread: f64, depth: f64, now: Instant) -> Option<QualityScore> {
+        if self.enrolled() {
+            return None;
+        }
+        self.updates += 1;
+        self.spread_sum += spread;
+        self.depth_sum += depth;
+        if now.duration_since(self.window_start) < Duration::from_secs(config.window_secs) {
+            return None;
+        }
+
+        let score = self.score(config, now);
+        if score.score >= config.min_score {
+            self.state = Enrollment::Enrolled;
+            metrics::incr("quality.enrolled");
+        } else {
+            self.rejections += 1;
+            metrics::incr("quality.rejected");
+            *self = Self { rejections: self.rejections, ..Self::new(now) };
+        }
+        Some(score)
+    }
+
+    /// Windows that ended below the bar
+    pub fn rejections(&self) -> u32 {
+        self.rejections
+    }
+}
+
+/// Markets that cleared the bar, by slug, with the Unix time they were enrolled. Written
+/// through to QUALITY_ENROLLED_FILE so a restart doesn't put them through observation again.
+#[derive(Debug)]
+pub struct EnrolledMarkets {
+    path: PathBuf,
+    slugs: Mutex<BTreeMap<String, i64>>,
+}
+
+impl EnrolledMarkets {
+    /// Load the set from `path` (missing or unreadable = empty)
+    pub fn open(path: PathBuf) -> Self {
+        let slugs = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                warn!("[QUALITY] Enrolled markets file {} unreadable ({}) - markets are observed again", path.display(), e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self { path, slugs: Mutex::new(slugs) }
+    }
+
+    pub fn contains(&self, slug: &str) -> bool {
+        self.slugs.lock().unwrap().contains_key(slug)
+    }
+
+    pub fn enroll(&self, slug: &str, now: i64) {
+        let mut slugs = self.slugs.lock().unwrap();
+        if slugs.insert(slug.to_string(), now).is_none() {
+            self.save(&slugs);
+        }
+    }
+
+    /// Forget markets that left their set (a relisting is observed afresh)
+    pub fn forget(&self, slugs_gone: &[String]) {
+        let mut slugs = self.slugs.lock().unwrap();
+        let before = slugs.len();
+        slugs.retain(|slug, _| !slugs_gone.contains(slug));
+        if slugs.len() != before {
+            self.save(&slugs);
+        }
+    }
+
+    fn save(&self, slugs: &BTreeMap<String, i64>) {
+        let result = serde_json::to_vec_pretty(slugs).map_err(anyhow::Error::from)
+            .and_then(|data| storage::write_atomic(&self.path, &data));
+        if let Err(e) = result {
+            warn!("[QUALITY] Failed to save enrolled markets to {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Process-wide enrolled set (QUALITY_ENROLLED_FILE)
+pub fn enrolled() -> &'static EnrolledMarkets {
+    static ENROLLED: OnceLock<EnrolledMarkets> = OnceLock::new();
+    ENROLLED.get_or_init(|| EnrolledMarkets::open(storage::state_path(
+        std::env::var("QUALITY_ENROLLED_FILE").unwrap_or_else(|_| DEFAULT_ENROLLED_FILE.to_string()))))
+}
+
+/// Gate for a market found at discovery: None (traded right away) when gating is off or the
+/// market was enrolled before, else a fresh observation window
+pub fn gate_at_discovery(config: &QualityConfig, enrolled: &EnrolledMarkets, slug: &str, now: Instant) -> Option<QualityGate> {
+    (config.enabled() && !enrolled.contains(slug)).then(|| QualityGate::new(now))
+}
+
+/// Log a closed window's verdict for market `name`
+pub fn log_verdict(name: &str, gate: &QualityGate, score: &QualityScore, config: &QualityConfig) {
+    if gate.enrolled() {
+        info!("[QUALITY] ✅ {} enrolled | score {:.2} ≥ {:.2} (spread {:.2}, depth {:.2}, updates {:.2})",
+              name, score.score, config.min_score, score.spread, score.depth, score.update_rate);
+    } else {
+        info!("[QUALITY] ⏳ {} not enrolled | score {:.2} < {:.2} (spread {:.2}, depth {:.2}, updates {:.2}) - observing another {}s",
+              name, score.score, config.min_score, score.spread, score.depth, score.update_rate, config.window_secs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> QualityConfig {
+        QualityConfig { window_secs: 60, min_score: 0.5, max_spread: 0.10, target_depth: 100.0, target_updates_per_min: 6.0 }
+    }
+
+    #[test]
+    fn test_score_components() {
+        let s = QualityScore::compute(&cfg(), 0.02, 50.0, 12.0);
+        assert!((s.spread - 0.8).abs() < 1e-9);
+        assert!((s.depth - 0.5).abs() < 1e-9);
+        assert_eq!(s.update_rate, 1.0);
+        assert!((s.score - 2.3 / 3.0).abs() < 1e-9);
+
+        // Wider than the bar / deeper than the target clamp
+        let s = QualityScore::compute(&cfg(), 0.5, 1_000.0, 0.0);
+        assert_eq!((s.spread, s.depth, s.update_rate), (0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_liquid_market_enrolls_after_window() {
+        let config = cfg();
+        let start = Instant::now();
+        let mut gate = QualityGate::new(start);
+        for i in 0..30 {
+            let verdict = gate.observe(&config, 0.01, 200.0, start + Duration::from_secs(i * 2));
+            assert!(verdict.is_none());
+            assert!(!gate.enrolled());
+        }
+        let score = gate.observe(&config, 0.01, 200.0, start + Duration::from_secs(60)).unwrap();
+        assert!(score.score > 0.9, "{:?}", score);
+        assert!(gate.enrolled());
+        assert!(gate.observe(&config, 0.5, 0.0, start + Duration::from_secs(200)).is_none());
+    }
+
+    #[test]
+    fn test_enrolled_set_survives_restart() {
+        let path = std::env::temp_dir().join(format!("enrolled_markets_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = cfg();
+        let now = Instant::now();
+
+        let enrolled = EnrolledMarkets::open(path.clone());
+        assert!(gate_at_discovery(&config, &enrolled, "fed-cut", now).is_some());
+        enrolled.enroll("fed-cut", 1_766_000_000);
+        enrolled.enroll("btc-150k", 1_766_000_100);
+
+        // Reopened: enrolled markets skip observation, new ones don't
+        let reopened = EnrolledMarkets::open(path.clone());
+        assert!(gate_at_discovery(&config, &reopened, "fed-cut", now).is_none());
+        assert!(gate_at_discovery(&config, &reopened, "new-listing", now).is_some());
+        assert!(gate_at_discovery(&QualityConfig { window_secs: 0, ..cfg() }, &reopened, "new-listing", now).is_none());
+
+        reopened.forget(&["fed-cut".to_string()]);
+        assert!(!EnrolledMarkets::open(path.clone()).contains("fed-cut"));
+        assert!(EnrolledMarkets::open(path.clone()).contains("btc-150k"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_illiquid_market_is_observed_again() {
+        let config = cfg();
+        let start = Instant::now();
+        let mut gate = QualityGate::new(start);
+        // Wide, thin and quiet: one update a minute
+        gate.observe(&config, 0.08, 5.0, start + Duration::from_secs(1));
+        let score = gate.observe(&config, 0.08, 5.0, start + Duration::from_secs(61)).unwrap();
+        assert!(score.score < 0.5, "{:?}", score);
+        assert!(!gate.enrolled());
+        assert_eq!(gate.rejections(), 1);
+
+        // The listing fills in: the next window clears the bar
+        let restart = start + Duration::from_secs(61);
+        for i in 1..=20 {
+            gate.observe(&config, 0.02, 150.0, restart + Duration::from_secs(i * 3));
+        }
+        assert!(gate.enrolled());
+        assert_eq!(gate.rejections(), 1);
+    }
+}
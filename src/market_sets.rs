@@ -0,0 +1,191 @@
+// src/market_sets.rs
+// Market sets - Gamma tag / keyword selections of binary markets traded with the Up/Down YES+NO check
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use tracing::info;
+
+use crate::logging;
+
+/// Names the Up/Down series already uses for its assets (their reference prices and
+/// `[assets]` entries would be mistaken for the set's)
+const RESERVED_NAMES: &[&str] = &["btc", "eth", "sol", "xrp", "default"];
+
+fn default_max_markets() -> usize {
+    20
+}
+
+fn default_max_days() -> f64 {
+    30.0
+}
+
+fn default_rescan_secs() -> u64 {
+    300
+}
+
+/// One `[[market_sets.set]]`: the open binary markets of a Gamma tag, narrowed by keywords
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarketSet {
+    /// Stands in for the asset: in logs, positions by asset, and `[assets.<name>]` parameters
+    pub name: String,
+
+    /// Gamma tag slug ("fed-rates"); without one, every open market is matched on keywords
+    #[serde(default)]
+    pub tag: Option<String>,
+
+    /// A market qualifies if its question contains any of these (case-insensitive; empty = all)
+    #[serde(default)]
+    pub keywords: Vec<String>,
+
+    /// At most this many markets, soonest ending first
+    #[serde(default = "default_max_markets")]
+    pub max_markets: usize,
+
+    /// Markets ending further out than this are left out (capital would sit for too long)
+    #[serde(default = "default_max_days")]
+    pub max_days: f64,
+}
+
+impl MarketSet {
+    pub fn matches(&self, question: &str) -> bool {
+        let question = question.to_lowercase();
+        self.keywords.is_empty() || self.keywords.iter().any(|k| question.contains(&k.to_lowercase()))
+    }
+
+    /// Whether a market ending at `end_secs` is in range at `now_secs`
+    pub fn in_range(&self, end_secs: u64, now_secs: u64) -> bool {
+        end_secs > now_secs && (end_secs - now_secs) as f64 <= self.max_days * 86_400.0
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("name is empty"));
+        }
+        if RESERVED_NAMES.contains(&self.name.to_lowercase().as_str()) {
+            return Err(anyhow!("name {:?} is taken by the Up/Down series", self.name));
+        }
+        if self.tag.is_none() && self.keywords.is_empty() {
+            return Err(anyhow!("needs a tag or keywords"));
+        }
+        if self.max_markets == 0 || self.max_days <= 0.0 {
+            return Err(anyhow!("max_markets and max_days must be positive"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BotToml {
+    #[serde(default)]
+    market_sets: MarketSets,
+}
+
+/// `[market_sets]` of bot.toml
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarketSets {
+    /// Seconds between rescans of every set (new listings, resolved markets)
+    #[serde(default = "default_rescan_secs")]
+    pub rescan_secs: u64,
+
+    #[serde(default, rename = "set")]
+    pub sets: Vec<MarketSet>,
+}
+
+impl Default for MarketSets {
+    fn default() -> Self {
+        Self { rescan_secs: default_rescan_secs(), sets: Vec::new() }
+    }
+}
+
+impl MarketSets {
+    /// `[market_sets]` of BOT_CONFIG (default bot.toml); missing file or section = no sets
+    pub fn load() -> Result<Self> {
+        let path = logging::config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("invalid [market_sets] in {}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let sets = toml::from_str::<BotToml>(contents)?.market_sets;
+        let mut names = HashSet::new();
+        for set in &sets.sets {
+            set.validate().with_context(|| format!("[[market_sets.set]] {:?}", set.name))?;
+            if !names.insert(set.name.to_lowercase()) {
+                return Err(anyhow!("duplicate set name {:?}", set.name));
+            }
+        }
+        if !sets.sets.is_empty() && sets.rescan_secs == 0 {
+            return Err(anyhow!("rescan_secs must be positive"));
+        }
+        Ok(sets)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    pub fn log(&self) {
+        for set in &self.sets {
+            info!("[SETS] {}: tag {} | keywords {:?} | up to {} markets ending within {} days",
+                  set.name, set.tag.as_deref().unwrap_or("(any)"), set.keywords, set.max_markets, set.max_days);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sets() {
+        let sets = MarketSets::parse(r#"
+            [market_sets]
+            rescan_secs = 120
+
+            [[market_sets.set]]
+            name = "fed"
+            tag = "fed-rates"
+            keywords = ["Fed decision", "rate cut"]
+            max_days = 45
+
+            [[market_sets.set]]
+            name = "cpi"
+            keywords = ["CPI"]
+        "#).unwrap();
+        assert_eq!(sets.rescan_secs, 120);
+        assert_eq!(sets.sets.len(), 2);
+        assert_eq!(sets.sets[1].max_markets, 20);
+        assert!(MarketSets::parse("[logging]\nlevel = \"info\"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_sets_refused() {
+        let set = |body: &str| MarketSets::parse(&format!("[[market_sets.set]]\n{}", body));
+        assert!(set("name = \"fed\"").is_err());                              // no tag or keywords
+        assert!(set("name = \"btc\"\ntag = \"crypto\"").is_err());            // Up/Down asset
+        assert!(set("name = \"fed\"\ntag = \"fed\"\nmax_markets = 0").is_err());
+        assert!(set("name = \"fed\"\ntag = \"fed\"\ntypo = 1").is_err());
+        let dup = "[[market_sets.set]]\nname = \"fed\"\ntag = \"a\"\n[[market_sets.set]]\nname = \"FED\"\ntag = \"b\"";
+        assert!(MarketSets::parse(dup).is_err());
+    }
+
+    #[test]
+    fn test_keyword_and_range_filters() {
+        let set = MarketSet {
+            name: "fed".into(), tag: Some("fed-rates".into()), keywords: vec!["fed decision".into()],
+            max_markets: 5, max_days: 10.0,
+        };
+        assert!(set.matches("Fed decision in December?"));
+        assert!(!set.matches("Will the ECB cut rates?"));
+        let now = 1_766_000_000;
+        assert!(set.in_range(now + 86_400, now));
+        assert!(!set.in_range(now + 11 * 86_400, now));
+        assert!(!set.in_range(now, now));
+    }
+}
@@ -0,0 +1,131 @@
+// src/metrics.rs
+// Process-wide counters and gauges for operational visibility
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Global metrics registry (created lazily on first use)
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+#[derive(Default)]
+struct Registry {
+    counters: Mutex<BTreeMap<String, Arc<AtomicU64>>>,
+    gauges: Mutex<BTreeMap<String, Arc<AtomicI64>>>,
+}
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(Registry::default)
+}
+
+/// Get (or register) a monotonically increasing counter.
+/// Hot paths should hold on to the returned handle instead of looking it up per event.
+pub fn counter(name: &str) -> Arc<AtomicU64> {
+    let mut counters = registry().counters.lock().unwrap();
+    counters
+        .entry(name.to_string())
+        .or_insert_with(|| Arc::new(AtomicU64::new(0)))
+        .clone()
+}
+
+/// Get (or register) a gauge that can move in both directions
+#[allow(dead_code)]
+pub fn gauge(name: &str) -> Arc<AtomicI64> {
+    let mut gauges = registry().gauges.lock().unwrap();
+    gauges
+        .entry(name.to_string())
+        .or_insert_with(|| Arc::new(AtomicI64::new(0)))
+        .clone()
+}
+
+/// Increment a counter by one
+#[inline]
+pub fn incr(name: &str) {
+    add(name, 1);
+}
+
+/// Increment a counter by `n`
+#[inline]
+pub fn add(name: &str, n: u64) {
+    counter(name).fetch_add(n, Ordering::Relaxed);
+}
+
+/// Set a gauge to an absolute value
+#[inline]
+#[allow(dead_code)]
+pub fn set_gauge(name: &str, value: i64) {
+    gauge(name).store(value, Ordering::Relaxed);
+}
+
+/// Point-in-time copy of every registered metric
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct MetricsSnapshot {
+    pub counters: BTreeMap<String, u64>,
+    pub gauges: BTreeMap<String, i64>,
+}
+
+#[allow(dead_code)]
+impl MetricsSnapshot {
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn gauge(&self, name: &str) -> i64 {
+        self.gauges.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.gauges.is_empty()
+    }
+}
+
+impl std::fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (name, value) in &self.counters {
+            if !first { write!(f, " ")?; }
+            write!(f, "{}={}", name, value)?;
+            first = false;
+        }
+        for (name, value) in &self.gauges {
+            if !first { write!(f, " ")?; }
+            write!(f, "{}={}", name, value)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+/// Capture the current value of all metrics
+pub fn snapshot() -> MetricsSnapshot {
+    let r = registry();
+    let counters = r.counters.lock().unwrap()
+        .iter()
+        .map(|(k, v)| (k.clone(), v.load(Ordering::Relaxed)))
+        .collect();
+    let gauges = r.gauges.lock().unwrap()
+        .iter()
+        .map(|(k, v)| (k.clone(), v.load(Ordering::Relaxed)))
+        .collect();
+    MetricsSnapshot { counters, gauges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_increments() {
+        incr("test.metrics.counter");
+        add("test.metrics.counter", 4);
+        assert_eq!(snapshot().counter("test.metrics.counter"), 5);
+    }
+
+    #[test]
+    fn test_gauge_set_and_display() {
+        set_gauge("test.metrics.gauge", -3);
+        let snap = snapshot();
+        assert_eq!(snap.gauge("test.metrics.gauge"), -3);
+        assert!(snap.to_string().contains("test.metrics.gauge=-3"));
+    }
+}
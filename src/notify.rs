@@ -0,0 +1,297 @@
+// src/notify.rs
+// Notifications - routes bus events by severity to webhook channels, with per-event message templates
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::events::{self, BotEvent};
+use crate::logging;
+use crate::metrics;
+use crate::runtimes;
+
+/// How urgently an event needs a human
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// Built-in severity of an event (overridable per kind in `[notify.severity]`)
+    pub fn of(event: &BotEvent) -> Self {
+        match event {
+            BotEvent::ArbDetected { .. } | BotEvent::OrderSubmitted { .. }
+            | BotEvent::Filled { .. } | BotEvent::Hedged { .. } => Self::Info,
+            BotEvent::WsReconnected { .. } | BotEvent::OrderRejected { .. }
+            | BotEvent::RebalanceSuggested { .. } | BotEvent::ResourceTrend { .. }
+            | BotEvent::ClockSkew { .. } | BotEvent::ControlAction { .. } => Self::Warning,
+            BotEvent::RiskTripped { .. } | BotEvent::SchemaDrift { .. }
+            | BotEvent::PositionDivergence { .. } => Self::Critical,
+            BotEvent::OppRateAnomaly { cause, .. } if cause == "feed_broken" => Self::Critical,
+            BotEvent::OppRateAnomaly { .. } => Self::Warning,
+        }
+    }
+}
+
+/// Webhook body shape
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// `{"text": ...}` (Slack, Mattermost, most chat webhooks)
+    #[default]
+    Slack,
+    /// `{"content": ...}`
+    Discord,
+    /// `{"severity", "message", "event": {...}}` for custom receivers
+    Json,
+}
+
+/// One destination
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Channel {
+    pub name: String,
+    /// Webhook URL, or the env var holding it (keeps secrets out of bot.toml)
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub url_env: Option<String>,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Lowest severity sent here
+    #[serde(default = "default_min_severity")]
+    pub min_severity: Severity,
+    /// Only these event kinds (empty = all that pass `min_severity`)
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+fn default_min_severity() -> Severity {
+    Severity::Warning
+}
+
+impl Channel {
+    fn accepts(&self, kind: &str, severity: Severity) -> bool {
+        severity >= self.min_severity && (self.events.is_empty() || self.events.iter().any(|e| e == kind))
+    }
+
+    fn resolve_url(&self) -> Result<String> {
+        match (&self.url, &self.url_env) {
+            (Some(url), _) => Ok(url.clone()),
+            (None, Some(var)) => std::env::var(var)
+                .map_err(|_| anyhow!("channel {}: {} is not set", self.name, var)),
+            (None, None) => Err(anyhow!("channel {}: needs url or url_env", self.name)),
+        }
+    }
+}
+
+/// `[notify]` section of bot.toml
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct NotifyConfig {
+    #[serde(default, rename = "channel")]
+    pub channels: Vec<Channel>,
+    /// Severity overrides by event kind
+    #[serde(default)]
+    pub severity: HashMap<String, Severity>,
+    /// Message templates by event kind: `{field}` is replaced by the event's field,
+    /// plus `{event}` (kind) and `{severity}`
+    #[serde(default)]
+    pub templates: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BotToml {
+    #[serde(default)]
+    notify: NotifyConfig,
+}
+
+impl NotifyConfig {
+    /// `[notify]` of BOT_CONFIG (default bot.toml); missing file or section = no channels
+    pub fn load() -> Result<Self> {
+        let path = logging::config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("invalid [notify] in {}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(toml::from_str::<BotToml>(contents)?.notify)
+    }
+
+    pub fn severity_of(&self, event: &BotEvent) -> Severity {
+        self.severity.get(event.kind()).copied().unwrap_or_else(|| Severity::of(event))
+    }
+
+    /// Indices of the channels `event` goes to
+    pub fn route(&self, event: &BotEvent) -> Vec<usize> {
+        let severity = self.severity_of(event);
+        self.channels.iter().enumerate()
+            .filter(|(_, c)| c.accepts(event.kind(), severity))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Message text for `event`: its template, or "[severity] kind: field=value ..."
+    pub fn render(&self, event: &BotEvent) -> String {
+        let severity = self.severity_of(event);
+        let fields = match serde_json::to_value(event) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => Default::default(),
+        };
+        let value = |v: &serde_json::Value| match v {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        let Some(template) = self.templates.get(event.kind()) else {
+            let details: Vec<String> = fields.iter()
+                .filter(|(k, _)| k.as_str() != "event")
+                .map(|(k, v)| format!("{}={}", k, value(v)))
+                .collect();
+            return format!("[{}] {}: {}", severity.as_str(), event.kind(), details.join(" "));
+        };
+        let mut text = template.replace("{event}", event.kind()).replace("{severity}", severity.as_str());
+        for (k, v) in &fields {
+            text = text.replace(&format!("{{{}}}", k), &value(v));
+        }
+        text
+    }
+}
+
+fn body(format: WebhookFormat, severity: Severity, text: &str, event: &BotEvent) -> serde_json::Value {
+    match format {
+        WebhookFormat::Slack => serde_json::json!({ "text": text }),
+        WebhookFormat::Discord => serde_json::json!({ "content": text }),
+        WebhookFormat::Json => serde_json::json!({ "severity": severity.as_str(), "message": text, "event": event }),
+    }
+}
+
+/// Load `[notify]` and forward matching events to each channel (notify.sent.<channel> /
+/// notify.failed.<channel>). An invalid section or unset URL is a startup error.
+pub fn start() -> Result<()> {
+    let config = NotifyConfig::load()?;
+    if config.channels.is_empty() {
+        return Ok(());
+    }
+    let urls = config.channels.iter().map(Channel::resolve_url).collect::<Result<Vec<_>>>()?;
+    for c in &config.channels {
+        info!("[NOTIFY] Channel {}: {}+{}", c.name, c.min_severity.as_str(),
+              if c.events.is_empty() { String::new() } else { format!(" ({})", c.events.join(", ")) });
+    }
+
+    // The bus consumer only queues; webhooks are sent off the bus so slow endpoints can't lag it
+    let (tx, mut rx) = mpsc::unbounded_channel::<BotEvent>();
+    runtimes::spawn_bookkeeping(events::consume("notify", events::subscribe(), move |event| {
+        let _ = tx.send(event);
+    }));
+    runtimes::spawn_bookkeeping(async move {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client");
+        while let Some(event) = rx.recv().await {
+            let targets = config.route(&event);
+            if targets.is_empty() {
+                continue;
+            }
+            let (severity, text) = (config.severity_of(&event), config.render(&event));
+            for i in targets {
+                let channel = &config.channels[i];
+                let sent = http.post(&urls[i]).json(&body(channel.format, severity, &text, &event)).send().await
+                    .and_then(|r| r.error_for_status());
+                match sent {
+                    Ok(_) => metrics::incr(&format!("notify.sent.{}", channel.name)),
+                    Err(e) => {
+                        metrics::incr(&format!("notify.failed.{}", channel.name));
+                        warn!("[NOTIFY] {} delivery failed: {}", channel.name, e);
+                    }
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [[notify.channel]]
+        name = "fills"
+        url = "https://hooks.example/fills"
+        min_severity = "info"
+        events = ["filled", "hedged"]
+
+        [[notify.channel]]
+        name = "pager"
+        url_env = "PAGER_WEBHOOK"
+        format = "json"
+        min_severity = "critical"
+
+        [notify.severity]
+        order_rejected = "critical"
+
+        [notify.templates]
+        filled = "✅ {market}: {contracts} {side} on {platform} for ${cost}"
+    "#;
+
+    fn filled() -> BotEvent {
+        BotEvent::Filled {
+            market: "Chelsea vs Villa".into(), platform: "kalshi".into(), side: "no".into(),
+            contracts: 10.0, cost: 4.5, order_id: "o1".into(),
+        }
+    }
+
+    #[test]
+    fn test_routes_by_severity_and_kind() {
+        let config = NotifyConfig::parse(CONFIG).unwrap();
+        assert_eq!(config.route(&filled()), vec![0]);
+        assert_eq!(config.route(&BotEvent::RiskTripped { reason: "daily loss".into() }), vec![1]);
+        // Warning by default: neither channel
+        let ws = BotEvent::WsReconnected { feed: "kalshi".into(), error: "eof".into() };
+        assert!(config.route(&ws).is_empty());
+        // Promoted to critical by the override
+        let rejected = BotEvent::OrderRejected {
+            platform: "polymarket".into(), instrument: "1".into(), reason: "rate_limited".into(), message: "429".into(),
+        };
+        assert_eq!(config.route(&rejected), vec![1]);
+        assert!(NotifyConfig::parse("").unwrap().channels.is_empty());
+        assert!(NotifyConfig::parse("[[notify.channel]]\nname = \"x\"\nmin_severity = \"loud\"").is_err());
+    }
+
+    #[test]
+    fn test_templates_and_default_message() {
+        let config = NotifyConfig::parse(CONFIG).unwrap();
+        assert_eq!(config.render(&filled()), "✅ Chelsea vs Villa: 10.0 no on kalshi for $4.5");
+        assert_eq!(config.render(&BotEvent::RiskTripped { reason: "daily loss".into() }),
+                   "[critical] risk_tripped: reason=daily loss");
+    }
+
+    #[test]
+    fn test_channel_url_and_body() {
+        let config = NotifyConfig::parse(CONFIG).unwrap();
+        assert_eq!(config.channels[0].resolve_url().unwrap(), "https://hooks.example/fills");
+        assert!(config.channels[1].resolve_url().unwrap_err().to_string().contains("PAGER_WEBHOOK"));
+
+        let ev = BotEvent::RiskTripped { reason: "x".into() };
+        assert_eq!(body(WebhookFormat::Slack, Severity::Critical, "hi", &ev), serde_json::json!({"text": "hi"}));
+        let json = body(WebhookFormat::Json, Severity::Critical, "hi", &ev);
+        assert_eq!((json["severity"].as_str(), json["event"]["event"].as_str()), (Some("critical"), Some("risk_tripped")));
+    }
+}
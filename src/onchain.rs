@@ -0,0 +1,435 @@
+// src/onchain.rs
+// Polymarket on-chain operations (approvals, redemptions) via the gasless relayer
+// or direct Polygon transactions
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::abi::{encode, Token};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::Middleware;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
+use ethers::utils::{id, keccak256};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::Deserialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::app::POLYGON_CHAIN_ID;
+use crate::polymarket_clob::{ApiCreds, PreparedCreds};
+use crate::rpc_pool::{RpcConfig, RpcPool};
+
+/// USDC.e collateral
+pub const USDC_ADDRESS: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+/// Conditional Tokens Framework (ERC-1155 outcome tokens)
+pub const CTF_ADDRESS: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045";
+pub const CTF_EXCHANGE: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+pub const NEG_RISK_EXCHANGE: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+pub const NEG_RISK_ADAPTER: &str = "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296";
+/// Factory that owns Polymarket proxy wallets and forwards their calls
+const PROXY_WALLET_FACTORY: &str = "0xaB45c5A4B0c941a2F231C04C3f49182e1A254052";
+/// GSN relay hub used by the relayer
+const RELAY_HUB: &str = "0xD216153c06E857cD7f72665E0aF1d7D82172F494";
+
+/// Gas limit signed into relayed transactions
+const RELAYER_GAS_LIMIT: u64 = 10_000_000;
+
+/// How on-chain transactions are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxMode {
+    /// Polymarket's gasless relayer (no MATIC needed in the wallet)
+    Relayer,
+    /// Signed and paid for by the signing key (needs MATIC for gas)
+    Direct,
+}
+
+impl TxMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "relayer" => Some(TxMode::Relayer),
+            "direct" => Some(TxMode::Direct),
+            _ => None,
+        }
+    }
+}
+
+/// On-chain configuration from environment
+#[derive(Debug, Clone)]
+pub struct OnchainConfig {
+    /// Preferred transaction path
+    pub mode: TxMode,
+
+    /// Retry with a direct transaction if the relayer rejects or fails
+    pub fallback_direct: bool,
+
+    pub relayer_url: String,
+
+    /// Polygon RPC endpoints for direct transactions
+    pub rpc: RpcConfig,
+
+    /// Builder API credentials for the relayer (optional)
+    pub builder_creds: Option<ApiCreds>,
+}
+
+impl OnchainConfig {
+    pub fn from_env() -> Self {
+        let builder_creds = match (
+            std::env::var("POLY_BUILDER_API_KEY"),
+            std::env::var("POLY_BUILDER_SECRET"),
+            std::env::var("POLY_BUILDER_PASSPHRASE"),
+        ) {
+            (Ok(api_key), Ok(api_secret), Ok(api_passphrase)) => Some(ApiCreds { api_key, api_secret, api_passphrase }),
+            _ => None,
+        };
+
+        Self {
+            mode: std::env::var("POLY_TX_MODE")
+                .ok()
+                .and_then(|v| TxMode::parse(&v))
+                .unwrap_or(TxMode::Direct),
+
+            fallback_direct: std::env::var("POLY_RELAYER_FALLBACK")
+                .map(|v| v != "0" && v.to_lowercase() != "false")
+                .unwrap_or(true),
+
+            relayer_url: std::env::var("POLY_RELAYER_URL")
+                .unwrap_or_else(|_| "https://relayer-v2.polymarket.com".to_string()),
+
+            rpc: RpcConfig::from_env(),
+
+            builder_creds,
+        }
+    }
+
+    /// Transaction paths to try, in order
+    pub fn attempts(&self) -> Vec<TxMode> {
+        match (self.mode, self.fallback_direct) {
+            (TxMode::Relayer, true) => vec![TxMode::Relayer, TxMode::Direct],
+            (mode, _) => vec![mode],
+        }
+    }
+}
+
+/// One contract call made from the funder wallet
+#[derive(Debug, Clone)]
+pub struct ContractCall {
+    pub to: Address,
+    pub data: Bytes,
+    pub description: String,
+}
+
+fn address(s: &str) -> Address {
+    s.parse().expect("valid contract address constant")
+}
+
+fn calldata(signature: &str, args: &[Token]) -> Bytes {
+    let mut data = id(signature).to_vec();
+    data.extend(encode(args));
+    data.into()
+}
+
+/// ERC-20 approve(spender, max)
+pub fn approve_usdc(spender: &str) -> ContractCall {
+    ContractCall {
+        to: address(USDC_ADDRESS),
+        data: calldata("approve(address,uint256)", &[Token::Address(address(spender)), Token::Uint(U256::MAX)]),
+        description: format!("USDC approve {}", spender),
+    }
+}
+
+/// ERC-1155 setApprovalForAll(operator, true) on the CTF
+pub fn approve_outcome_tokens(operator: &str) -> ContractCall {
+    ContractCall {
+        to: address(CTF_ADDRESS),
+        data: calldata("setApprovalForAll(address,bool)", &[Token::Address(address(operator)), Token::Bool(true)]),
+        description: format!("CTF setApprovalForAll {}", operator),
+    }
+}
+
+/// Every approval the exchanges need to trade from the funder wallet
+pub fn trading_approvals() -> Vec<ContractCall> {
+    let mut calls = Vec::new();
+    for spender in [CTF_EXCHANGE, NEG_RISK_EXCHANGE, NEG_RISK_ADAPTER] {
+        calls.push(approve_usdc(spender));
+        calls.push(approve_outcome_tokens(spender));
+    }
+    calls
+}
+
+/// Redeem resolved conditional tokens (both outcomes) for USDC
+pub fn redeem_positions(condition_id: &str) -> Result<ContractCall> {
+    let condition: [u8; 32] = ethers::utils::hex::decode(condition_id.trim_start_matches("0x"))
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("condition id must be 32 bytes hex: {}", condition_id))?;
+
+    Ok(ContractCall {
+        to: address(CTF_ADDRESS),
+        data: calldata("redeemPositions(address,bytes32,bytes32,uint256[])", &[
+            Token::Address(address(USDC_ADDRESS)),
+            Token::FixedBytes(vec![0u8; 32]),
+            Token::FixedBytes(condition.to_vec()),
+            Token::Array(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+        ]),
+        description: format!("CTF redeemPositions {}", condition_id),
+    })
+}
+
+/// ERC-20 transfer of `amount` USDC (dollars, 6 decimals on chain) to `to`.
+/// Not public: transfers out are built through `treasury::usdc_transfer` (allowlist + confirmation).
+pub(crate) fn transfer_usdc(to: &str, amount: f64) -> Result<ContractCall> {
+    let recipient: Address = to.parse().map_err(|_| anyhow!("not a valid address: {}", to))?;
+    if !amount.is_finite() || amount <= 0.0 {
+        bail!("transfer amount must be positive, got {}", amount);
+    }
+    let units = (amount * 1e6).round() as u64;
+    Ok(ContractCall {
+        to: address(USDC_ADDRESS),
+        data: calldata("transfer(address,uint256)", &[Token::Address(recipient), Token::Uint(U256::from(units))]),
+        description: format!("USDC transfer ${:.2} to {}", amount, to),
+    })
+}
+
+/// Calls wrapped for the proxy wallet factory: proxy((typeCode, to, value, data)[])
+fn proxy_calldata(calls: &[ContractCall]) -> Bytes {
+    let tuples = calls.iter()
+        .map(|c| Token::Tuple(vec![
+            Token::Uint(U256::from(1)), // CALL
+            Token::Address(c.to),
+            Token::Uint(U256::zero()),
+            Token::Bytes(c.data.to_vec()),
+        ]))
+        .collect();
+    calldata("proxy((uint8,address,uint256,bytes)[])", &[Token::Array(tuples)])
+}
+
+#[derive(Deserialize)]
+struct RelayPayload {
+    address: String,
+    nonce: String,
+}
+
+#[derive(Deserialize)]
+struct RelayerSubmitResponse {
+    #[serde(rename = "transactionID")]
+    transaction_id: String,
+    #[serde(rename = "transactionHash", default)]
+    transaction_hash: Option<String>,
+}
+
+/// Sends funder-wallet calls through the relayer or directly
+pub struct OnchainClient {
+    config: OnchainConfig,
+    wallet: LocalWallet,
+    funder: Address,
+    http: reqwest::Client,
+    rpc: RpcPool,
+}
+
+impl OnchainClient {
+    pub fn new(config: OnchainConfig, private_key: &str, funder: &str) -> Result<Self> {
+        let wallet = private_key.parse::<LocalWallet>()?.with_chain_id(POLYGON_CHAIN_ID);
+        let funder = funder.parse().context("POLY_FUNDER is not a valid address")?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let rpc = RpcPool::new(config.rpc.clone())?;
+        Ok(Self { config, wallet, funder, http, rpc })
+    }
+
+    /// The signer trades through a Polymarket proxy wallet (funder != signer)
+    fn uses_proxy_wallet(&self) -> bool {
+        self.funder != self.wallet.address()
+    }
+
+    /// Submit calls as one transaction, falling back to a direct transaction if configured.
+    /// Returns the transaction hash (or relayer transaction ID).
+    pub async fn submit(&self, calls: &[ContractCall]) -> Result<String> {
+        if calls.is_empty() {
+            bail!("nothing to submit");
+        }
+        for call in calls {
+            info!("[CHAIN] {}", call.description);
+        }
+
+        let attempts = self.config.attempts();
+        let mut last_err = None;
+        for (i, mode) in attempts.iter().enumerate() {
+            let result = match mode {
+                TxMode::Relayer => self.submit_relayer(calls).await,
+                TxMode::Direct => self.submit_direct(calls).await,
+            };
+            match result {
+                Ok(tx) => {
+                    info!("[CHAIN] ✅ Submitted via {:?}: {}", mode, tx);
+                    return Ok(tx);
+                }
+                Err(e) if i + 1 < attempts.len() => {
+                    warn!("[CHAIN] ⚠️ {:?} submission failed ({}), falling back to {:?}", mode, e, attempts[i + 1]);
+                    last_err = Some(e);
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow!("no transaction path configured")))
+    }
+
+    /// Signed by the key and paid for in MATIC
+    async fn submit_direct(&self, calls: &[ContractCall]) -> Result<String> {
+        if self.rpc.check_health().await == 0 {
+            bail!("no healthy Polygon RPC endpoint");
+        }
+        let client = SignerMiddleware::new(self.rpc.provider(), self.wallet.clone());
+
+        // Proxy wallets can only be driven through the factory; an EOA funder calls directly
+        let txs: Vec<TransactionRequest> = if self.uses_proxy_wallet() {
+            vec![TransactionRequest::new().to(address(PROXY_WALLET_FACTORY)).data(proxy_calldata(calls))]
+        } else {
+            calls.iter().map(|c| TransactionRequest::new().to(c.to).data(c.data.clone())).collect()
+        };
+
+        let mut last_hash = String::new();
+        for tx in txs {
+            let pending = client.send_transaction(tx, None).await?;
+            let receipt = pending.await?.ok_or_else(|| anyhow!("transaction dropped from mempool"))?;
+            if receipt.status.map(|s| s.as_u64()) == Some(0) {
+                bail!("transaction reverted: {:?}", receipt.transaction_hash);
+            }
+            last_hash = format!("{:?}", receipt.transaction_hash);
+        }
+        Ok(last_hash)
+    }
+
+    /// Gasless: the relayer pays gas and forwards the signed call to the proxy wallet factory
+    async fn submit_relayer(&self, calls: &[ContractCall]) -> Result<String> {
+        if !self.uses_proxy_wallet() {
+            bail!("relayer requires a Polymarket proxy wallet (POLY_FUNDER equals the signer)");
+        }
+        let base = self.config.relayer_url.trim_end_matches('/');
+        let from = self.wallet.address();
+
+        let payload: RelayPayload = self.http
+            .get(format!("{}/relay-payload", base))
+            .query(&[("address", format!("{:?}", from)), ("type", "PROXY".to_string())])
+            .send().await?
+            .error_for_status()?
+            .json().await?;
+        let relay: Address = payload.address.parse().context("relay address")?;
+        let nonce = U256::from_dec_str(&payload.nonce).map_err(|e| anyhow!("relay nonce: {}", e))?;
+
+        let to = address(PROXY_WALLET_FACTORY);
+        let data = proxy_calldata(calls);
+        let gas_limit = U256::from(RELAYER_GAS_LIMIT);
+        let hash = relay_struct_hash(from, to, &data, gas_limit, nonce, address(RELAY_HUB), relay);
+        let signature = self.wallet.sign_message(hash).await?;
+
+        let body = serde_json::json!({
+            "from": format!("{:?}", from),
+            "to": format!("{:?}", to),
+            "proxyWallet": format!("{:?}", self.funder),
+            "data": data.to_string(),
+            "nonce": nonce.to_string(),
+            "signature": format!("0x{}", signature),
+            "signatureParams": {
+                "gasPrice": "0",
+                "gasLimit": gas_limit.to_string(),
+                "relayerFee": "0",
+                "relayHub": RELAY_HUB,
+                "relay": format!("{:?}", relay),
+            },
+            "type": "PROXY",
+        }).to_string();
+
+        let path = "/submit";
+        let headers = self.builder_headers("POST", path, &body)?;
+        let resp = self.http
+            .post(format!("{}{}", base, path))
+            .headers(headers)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send().await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            bail!("relayer rejected: {} {}", status, text);
+        }
+        let submitted: RelayerSubmitResponse = resp.json().await?;
+        Ok(submitted.transaction_hash.unwrap_or(submitted.transaction_id))
+    }
+
+    fn builder_headers(&self, method: &str, path: &str, body: &str) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        let Some(creds) = &self.config.builder_creds else {
+            return Ok(headers);
+        };
+        let prepared = PreparedCreds::from_api_creds(creds)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let signature = prepared.sign_b64(format!("{}{}{}{}", timestamp, method, path, body).as_bytes());
+        headers.insert("POLY_BUILDER_API_KEY", prepared.api_key_header());
+        headers.insert("POLY_BUILDER_PASSPHRASE", prepared.passphrase_header());
+        headers.insert("POLY_BUILDER_SIGNATURE", HeaderValue::from_str(&signature)?);
+        headers.insert("POLY_BUILDER_TIMESTAMP", HeaderValue::from_str(&timestamp.to_string())?);
+        Ok(headers)
+    }
+}
+
+/// GSN relay request hash: keccak("rlx:" | from | to | data | fee | gasPrice | gasLimit | nonce | hub | relay)
+fn relay_struct_hash(from: Address, to: Address, data: &Bytes, gas_limit: U256, nonce: U256,
+                     relay_hub: Address, relay: Address) -> [u8; 32] {
+    let word = |v: U256| {
+        let mut buf = [0u8; 32];
+        v.to_big_endian(&mut buf);
+        buf
+    };
+    let mut msg = b"rlx:".to_vec();
+    msg.extend_from_slice(from.as_bytes());
+    msg.extend_from_slice(to.as_bytes());
+    msg.extend_from_slice(data);
+    msg.extend_from_slice(&word(U256::zero())); // relayer fee
+    msg.extend_from_slice(&word(U256::zero())); // gas price
+    msg.extend_from_slice(&word(gas_limit));
+    msg.extend_from_slice(&word(nonce));
+    msg.extend_from_slice(relay_hub.as_bytes());
+    msg.extend_from_slice(relay.as_bytes());
+    keccak256(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_encoding() {
+        let approve = approve_usdc(CTF_EXCHANGE);
+        assert_eq!(&approve.data[..4], &[0x09, 0x5e, 0xa7, 0xb3]);
+        assert_eq!(approve.data.len(), 4 + 64);
+        assert_eq!(trading_approvals().len(), 6);
+
+        let condition = format!("0x{}", "ab".repeat(32));
+        let redeem = redeem_positions(&condition).unwrap();
+        assert_eq!(&redeem.data[..4], &id("redeemPositions(address,bytes32,bytes32,uint256[])"));
+        assert!(redeem_positions("0x1234").is_err());
+
+        let transfer = transfer_usdc(CTF_EXCHANGE, 12.5).unwrap();
+        assert_eq!(&transfer.data[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(U256::from_big_endian(&transfer.data[36..68]), U256::from(12_500_000u64));
+        assert!(transfer_usdc("kalshi", 10.0).is_err());
+        assert!(transfer_usdc(CTF_EXCHANGE, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_relayer_falls_back_to_direct() {
+        let mut config = OnchainConfig {
+            mode: TxMode::Relayer,
+            fallback_direct: true,
+            relayer_url: String::new(),
+            rpc: RpcConfig { urls: Vec::new(), timeout_ms: 0, fail_limit: 0, cooldown_secs: 0, max_lag_blocks: 0, verify_quorum: 0, health_secs: 0 },
+            builder_creds: None,
+        };
+        assert_eq!(config.attempts(), vec![TxMode::Relayer, TxMode::Direct]);
+        config.fallback_direct = false;
+        assert_eq!(config.attempts(), vec![TxMode::Relayer]);
+        config.mode = TxMode::Direct;
+        assert_eq!(config.attempts(), vec![TxMode::Direct]);
+        assert_eq!(TxMode::parse("RELAYER"), Some(TxMode::Relayer));
+    }
+}
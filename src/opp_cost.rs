@@ -0,0 +1,106 @@
+// src/opp_cost.rs
+// Capital opportunity cost - long waits for settlement raise the edge an arb must clear
+
+/// Seconds per year for annualized rates
+const YEAR_SECS: f64 = 365.0 * 24.0 * 3600.0;
+
+/// Opportunity cost configuration from environment
+#[derive(Debug, Clone)]
+pub struct OppCostConfig {
+    /// Annualized return capital could earn elsewhere (OPP_COST_APR, 0.10 = 10%/yr; 0 = off)
+    pub annual_rate: f64,
+    /// Edge (cents) an arb must keep above after the lockup (OPP_COST_MIN_EDGE_CENTS)
+    pub min_net_edge: f64,
+}
+
+impl OppCostConfig {
+    pub fn from_env() -> Self {
+        Self {
+            annual_rate: std::env::var("OPP_COST_APR")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+            min_net_edge: std::env::var("OPP_COST_MIN_EDGE_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.annual_rate > 0.0
+    }
+
+    /// What `cost` (per contract, any unit) would have earned while locked for `secs_to_settle`
+    pub fn lockup_cost(&self, cost: f64, secs_to_settle: u64) -> f64 {
+        if !self.enabled() {
+            return 0.0;
+        }
+        cost * self.annual_rate * secs_to_settle as f64 / YEAR_SECS
+    }
+
+    /// Edge left after paying for the lockup
+    pub fn net_edge(&self, edge: f64, cost: f64, secs_to_settle: u64) -> f64 {
+        edge - self.lockup_cost(cost, secs_to_settle)
+    }
+
+    /// Whether the edge left after the lockup is worth taking
+    pub fn clears(&self, edge: f64, cost: f64, secs_to_settle: u64) -> bool {
+        self.net_edge(edge, cost, secs_to_settle) > self.min_net_edge
+    }
+}
+
+/// When capital spent on both venues is freed: the later of the two legs' settlements
+pub fn locked_until(kalshi_settles_at: Option<i64>, poly_ends_at: Option<i64>) -> Option<i64> {
+    kalshi_settles_at.max(poly_ends_at)
+}
+
+/// Seconds from `now` until `settles_at` (0 if unknown or past)
+pub fn secs_until(settles_at: Option<i64>, now: i64) -> u64 {
+    settles_at.map(|t| t.saturating_sub(now).max(0) as u64).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(rate: f64) -> OppCostConfig {
+        OppCostConfig { annual_rate: rate, min_net_edge: 0.0 }
+    }
+
+    #[test]
+    fn test_lockup_cost_scales_with_time() {
+        let c = cfg(0.10);
+        // 98¢ locked for a year at 10% = 9.8¢; for a day ≈ 0.027¢
+        assert!((c.lockup_cost(98.0, YEAR_SECS as u64) - 9.8).abs() < 1e-9);
+        assert!((c.lockup_cost(98.0, 86_400) - 0.02685).abs() < 1e-4);
+        assert_eq!(cfg(0.0).lockup_cost(98.0, YEAR_SECS as u64), 0.0);
+    }
+
+    #[test]
+    fn test_long_dated_edge_ranks_below_short_dated() {
+        let c = cfg(0.50);
+        // Same 1¢ edge at 99¢ cost: settled in an hour vs in two weeks
+        let short = c.net_edge(1.0, 99.0, 3_600);
+        let long = c.net_edge(1.0, 99.0, 14 * 86_400);
+        // An hour still costs a little, so a 1¢ edge nets just under 1¢
+        assert!(short > 0.99 && short < 1.0);
+        assert!(long < 0.0);
+        // A 1¢ arb settling within the hour is still taken, a 2-week one isn't
+        assert!(c.clears(1.0, 99.0, 3_600));
+        assert!(!c.clears(1.0, 99.0, 14 * 86_400));
+        let strict = OppCostConfig { min_net_edge: 1.0, ..c };
+        assert!(!strict.clears(1.0, 99.0, 3_600));
+        assert_eq!(secs_until(Some(1_000), 400), 600);
+        assert_eq!(secs_until(Some(1_000), 2_000), 0);
+        assert_eq!(secs_until(None, 0), 0);
+    }
+
+    #[test]
+    fn test_locked_until_the_later_leg() {
+        assert_eq!(locked_until(Some(1_000), Some(5_000)), Some(5_000));
+        assert_eq!(locked_until(Some(9_000), Some(5_000)), Some(9_000));
+        assert_eq!(locked_until(None, Some(5_000)), Some(5_000));
+        assert_eq!(locked_until(None, None), None);
+    }
+}
@@ -0,0 +1,345 @@
+// src/opp_rate.rs
+// Opportunity rate baseline - alert when an asset's arbs/hour leaves its historical band
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::PathBuf;
+use tracing::{error, info, warn};
+
+use crate::events::{self, BotEvent};
+use crate::metrics;
+use crate::opportunities::{self, OpportunityRecord};
+use crate::runtimes;
+
+/// Per-feed message-rate samples kept for the trailing average
+const FEED_HISTORY: usize = 24;
+
+/// Opportunity rate monitor configuration from environment
+#[derive(Debug, Clone)]
+pub struct OppRateConfig {
+    /// Minutes between checks (0 = off)
+    pub check_mins: u64,
+
+    /// Days of history the band for the current hour is built from
+    pub baseline_days: i64,
+
+    /// Band half-width in standard deviations of the hourly counts
+    pub band_sigmas: f64,
+
+    /// Days of history an asset needs before it is checked
+    pub min_days: usize,
+
+    /// A feed's messages/min below this fraction of its trailing average counts as broken
+    pub feed_drop_ratio: f64,
+
+    pub opp_file: PathBuf,
+}
+
+impl OppRateConfig {
+    pub fn from_env() -> Self {
+        Self {
+            check_mins: std::env::var("OPP_RATE_CHECK_MINS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+
+            baseline_days: std::env::var("OPP_RATE_BASELINE_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(14),
+
+            band_sigmas: std::env::var("OPP_RATE_BAND_SIGMAS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2.5),
+
+            min_days: std::env::var("OPP_RATE_MIN_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+
+            feed_drop_ratio: std::env::var("OPP_RATE_FEED_DROP")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.2),
+
+            opp_file: crate::storage::data_path(std::env::var("OPP_FILE")
+                .unwrap_or_else(|_| opportunities::DEFAULT_OPP_FILE.to_string())),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.check_mins > 0 && self.baseline_days > 0
+    }
+}
+
+/// Expected opportunities/hour for an asset at this time of day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Band {
+    pub mean: f64,
+    pub low: f64,
+    pub high: f64,
+    /// Days the band was built from
+    pub days: usize,
+}
+
+impl Band {
+    /// Mean ± `sigmas` standard deviations of `counts` (low floored at 0)
+    pub fn of(counts: &[f64], sigmas: f64) -> Self {
+        let n = counts.len().max(1) as f64;
+        let mean = counts.iter().sum::<f64>() / n;
+        let sd = (counts.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n).sqrt();
+        Self {
+            mean,
+            low: (mean - sigmas * sd).max(0.0),
+            high: mean + sigmas * sd,
+            days: counts.len(),
+        }
+    }
+}
+
+/// Why an asset's rate left its band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+    /// Fewer arbs than usual while the feeds are flowing - the market is calm
+    MarketQuiet,
+    /// Fewer arbs than usual and a feed's message rate collapsed - the bot is blind
+    FeedBroken,
+    /// More arbs than usual - new flow, or stale books producing phantom arbs
+    Surge,
+}
+
+impl Cause {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MarketQuiet => "market_quiet",
+            Self::FeedBroken => "feed_broken",
+            Self::Surge => "surge",
+        }
+    }
+}
+
+/// Compare the last hour's count to the band
+pub fn classify(rate: f64, band: &Band, feed_broken: bool) -> Option<Cause> {
+    if rate < band.low {
+        Some(if feed_broken { Cause::FeedBroken } else { Cause::MarketQuiet })
+    } else if rate > band.high {
+        Some(Cause::Surge)
+    } else {
+        None
+    }
+}
+
+fn count_in(records: &[OpportunityRecord], asset: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> usize {
+    records.iter()
+        .filter(|r| r.asset == asset)
+        .filter_map(|r| DateTime::parse_from_rfc3339(&r.ts).ok())
+        .filter(|t| *t >= from && *t < to)
+        .count()
+}
+
+/// Opportunities of `asset` in the hour before `now`
+pub fn current_rate(records: &[OpportunityRecord], asset: &str, now: DateTime<Utc>) -> f64 {
+    count_in(records, asset, now - Duration::hours(1), now) as f64
+}
+
+/// Counts of `asset` for the same hour on each of the previous `days` days, skipping days
+/// before the history begins (the bot wasn't running, so they aren't zero-arb hours)
+pub fn baseline(records: &[OpportunityRecord], asset: &str, now: DateTime<Utc>, days: i64) -> Vec<f64> {
+    let Some(start) = records.iter().filter_map(|r| DateTime::parse_from_rfc3339(&r.ts).ok()).min() else {
+        return Vec::new();
+    };
+    (1..=days)
+        .map(|d| now - Duration::days(d))
+        .filter(|end| *end > start)
+        .map(|end| count_in(records, asset, end - Duration::hours(1), end) as f64)
+        .collect()
+}
+
+/// Message counts per WS feed between checks, to tell a quiet market from a dead feed
+#[derive(Debug, Default)]
+pub struct FeedRates {
+    last: HashMap<String, u64>,
+    history: HashMap<String, VecDeque<f64>>,
+}
+
+impl FeedRates {
+    /// Record each feed's message counter (`ws.<feed>.messages`) after `minutes`; returns the
+    /// feeds whose rate fell below `drop_ratio` of their trailing average
+    pub fn sample(&mut self, counters: &[(String, u64)], minutes: f64, drop_ratio: f64) -> Vec<String> {
+        let mut broken = Vec::new();
+        for (feed, total) in counters {
+            let Some(prev) = self.last.insert(feed.clone(), *total) else { continue };
+            let rate = total.saturating_sub(prev) as f64 / minutes.max(1e-9);
+            let history = self.history.entry(feed.clone()).or_default();
+            let trailing = if history.is_empty() { 0.0 } else { history.iter().sum::<f64>() / history.len() as f64 };
+            if trailing > 0.0 && rate < trailing * drop_ratio {
+                broken.push(feed.clone());
+            }
+            if history.len() == FEED_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(rate);
+        }
+        broken
+    }
+}
+
+/// WS message counters currently registered (`ws.<feed>.messages`)
+fn feed_counters() -> Vec<(String, u64)> {
+    metrics::snapshot().counters.into_iter()
+        .filter_map(|(name, n)| {
+            let feed = name.strip_prefix("ws.")?.strip_suffix(".messages")?;
+            Some((feed.to_string(), n))
+        })
+        .collect()
+}
+
+/// Check every asset seen in the baseline window; returns (asset, rate, band, cause) for
+/// the ones outside their band
+pub fn check(config: &OppRateConfig, records: &[OpportunityRecord], now: DateTime<Utc>, feed_broken: bool)
+    -> Vec<(String, f64, Band, Cause)> {
+    let assets: BTreeSet<&str> = records.iter().map(|r| r.asset.as_str()).collect();
+    assets.into_iter()
+        .filter_map(|asset| {
+            let counts = baseline(records, asset, now, config.baseline_days);
+            if counts.len() < config.min_days.max(1) {
+                return None;
+            }
+            let band = Band::of(&counts, config.band_sigmas);
+            let rate = current_rate(records, asset, now);
+            classify(rate, &band, feed_broken).map(|cause| (asset.to_string(), rate, band, cause))
+        })
+        .collect()
+}
+
+/// Check the opportunity history every OPP_RATE_CHECK_MINS and alert (log, `opp_rate.<cause>`,
+/// `opp_rate_anomaly` event) when an asset leaves its band, once per excursion
+pub fn spawn(config: OppRateConfig) {
+    if !config.enabled() {
+        return;
+    }
+    info!("[OPP_RATE] Watching opportunities/hour against a {}-day baseline (every {} min)",
+          config.baseline_days, config.check_mins);
+
+    runtimes::spawn_bookkeeping(async move {
+        let period = std::time::Duration::from_secs(config.check_mins * 60);
+        let mut interval = tokio::time::interval(period);
+        let mut feeds = FeedRates::default();
+        let mut flagged: HashMap<String, Cause> = HashMap::new();
+        loop {
+            interval.tick().await;
+            let broken = feeds.sample(&feed_counters(), config.check_mins as f64, config.feed_drop_ratio);
+
+            let (path, days) = (config.opp_file.clone(), config.baseline_days + 1);
+            let now = Utc::now();
+            let records = match tokio::task::spawn_blocking(move || opportunities::load(&path)).await {
+                Ok(Ok(records)) => opportunities::within_days(records, days, now),
+                Ok(Err(e)) => {
+                    warn!("[OPP_RATE] Cannot read opportunity history: {}", e);
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            let anomalies = check(&config, &records, now, !broken.is_empty());
+            for (asset, rate, band, cause) in &anomalies {
+                if flagged.get(asset) == Some(cause) {
+                    continue;
+                }
+                let msg = format!("{} arbs in the last hour on {} (usual {:.1}, band {:.1}-{:.1} over {} days)",
+                                  rate, asset, band.mean, band.low, band.high, band.days);
+                match cause {
+                    Cause::FeedBroken => error!("[OPP_RATE] 🚨 {} - feed(s) gone quiet: {}", msg, broken.join(", ")),
+                    Cause::MarketQuiet => warn!("[OPP_RATE] 💤 {} - feeds flowing, market quiet", msg),
+                    Cause::Surge => warn!("[OPP_RATE] 📈 {} - surge", msg),
+                }
+                metrics::incr(&format!("opp_rate.{}", cause.as_str()));
+                events::publish(BotEvent::OppRateAnomaly {
+                    asset: asset.clone(),
+                    rate: *rate,
+                    low: band.low,
+                    high: band.high,
+                    cause: cause.as_str().to_string(),
+                });
+                flagged.insert(asset.clone(), *cause);
+            }
+            flagged.retain(|asset, _| {
+                let still = anomalies.iter().any(|(a, ..)| a == asset);
+                if !still {
+                    info!("[OPP_RATE] ✅ {} back within its usual opportunity rate", asset);
+                }
+                still
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opp(ts: DateTime<Utc>, asset: &str) -> OpportunityRecord {
+        let mut r = OpportunityRecord::new(asset, asset, "PolyOnly", 0.48, 0.49, 10.0, 10.0, 0.0);
+        r.ts = ts.to_rfc3339();
+        r
+    }
+
+    fn cfg() -> OppRateConfig {
+        OppRateConfig { check_mins: 30, baseline_days: 7, band_sigmas: 2.0, min_days: 3,
+                        feed_drop_ratio: 0.2, opp_file: PathBuf::new() }
+    }
+
+    /// `per_hour` BTC arbs in the same hour on each of the last 7 days, `now_count` this hour
+    fn history(now: DateTime<Utc>, per_hour: &[usize], now_count: usize) -> Vec<OpportunityRecord> {
+        let mut records = Vec::new();
+        for (d, n) in per_hour.iter().enumerate() {
+            let end = now - Duration::days(d as i64 + 1);
+            records.extend((0..*n).map(|i| opp(end - Duration::minutes(1 + i as i64), "btc")));
+        }
+        records.extend((0..now_count).map(|i| opp(now - Duration::minutes(1 + i as i64), "btc")));
+        records
+    }
+
+    #[test]
+    fn test_band_and_baseline() {
+        let band = Band::of(&[10.0, 12.0, 8.0, 10.0], 2.0);
+        assert!((band.mean - 10.0).abs() < 1e-9);
+        assert!((band.low - (10.0 - 2.0 * 2f64.sqrt())).abs() < 1e-9);
+
+        let now = Utc::now();
+        let records = history(now, &[10, 12, 8], 9);
+        // Only three days of history: earlier days aren't counted as zero
+        assert_eq!(baseline(&records, "btc", now, 7), vec![10.0, 12.0, 8.0]);
+        assert_eq!(current_rate(&records, "btc", now), 9.0);
+        assert!(check(&cfg(), &records, now, false).is_empty());
+    }
+
+    #[test]
+    fn test_silence_is_quiet_or_broken_by_feed_rate() {
+        let now = Utc::now();
+        let records = history(now, &[10, 12, 8, 10, 11, 9, 10], 0);
+        let flagged = check(&cfg(), &records, now, false);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!((flagged[0].0.as_str(), flagged[0].3), ("btc", Cause::MarketQuiet));
+        assert_eq!(check(&cfg(), &records, now, true)[0].3, Cause::FeedBroken);
+
+        let surge = history(now, &[10, 12, 8, 10, 11, 9, 10], 40);
+        assert_eq!(check(&cfg(), &surge, now, false)[0].3, Cause::Surge);
+        // Too little history to judge
+        assert!(check(&cfg(), &history(now, &[10, 12], 0), now, false).is_empty());
+    }
+
+    #[test]
+    fn test_feed_rate_collapse() {
+        let mut feeds = FeedRates::default();
+        let sample = |feeds: &mut FeedRates, total: u64| feeds.sample(&[("polymarket".to_string(), total)], 10.0, 0.2);
+        assert!(sample(&mut feeds, 0).is_empty()); // first sample only sets the baseline
+        assert!(sample(&mut feeds, 10_000).is_empty());
+        assert!(sample(&mut feeds, 19_000).is_empty());
+        // 100 messages in 10 minutes against ~950/min
+        assert_eq!(sample(&mut feeds, 19_100), vec!["polymarket".to_string()]);
+    }
+}
@@ -0,0 +1,360 @@
+// src/opportunities.rs
+// Opportunity history - every detected arb (taken or not) with its book, for offline queries
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::app::env_flag;
+use crate::archive;
+use crate::runtimes;
+use crate::storage;
+
+/// Default history file in DATA_DIR (override with OPP_FILE)
+pub const DEFAULT_OPP_FILE: &str = "opportunities.jsonl";
+
+/// One detected arb and what became of it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpportunityRecord {
+    /// RFC 3339 detection time
+    pub ts: String,
+    /// Asset (Up/Down) or league (main bot)
+    pub asset: String,
+    pub market: String,
+    pub strategy: String,
+    /// Asks at detection (0-1)
+    pub yes_price: f64,
+    pub no_price: f64,
+    /// Contracts available at those asks
+    pub yes_size: f64,
+    pub no_size: f64,
+    /// Net edge per contract after fees (dollars)
+    pub edge: f64,
+    /// Contracts fillable on both legs
+    pub contracts: f64,
+    pub taken: bool,
+    /// Why it wasn't taken (throttled, circuit breaker, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    /// Seconds left in the market's interval at detection (interval markets only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secs_to_expiry: Option<i64>,
+    /// Length of that interval (seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<i64>,
+    /// Dollars the execution committed (taken only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spent: Option<f64>,
+    /// Net profit the execution locked in: $1 per matched pair less every leg's cost and fees
+    /// (dollars; taken only, leftover unmatched contracts count at zero)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profit: Option<f64>,
+}
+
+impl OpportunityRecord {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(asset: &str, market: &str, strategy: &str, yes_price: f64, no_price: f64,
+               yes_size: f64, no_size: f64, fees: f64) -> Self {
+        Self {
+            ts: chrono::Utc::now().to_rfc3339(),
+            asset: asset.to_string(),
+            market: market.to_string(),
+            strategy: strategy.to_string(),
+            yes_price,
+            no_price,
+            yes_size,
+            no_size,
+            edge: 1.0 - yes_price - no_price - fees,
+            contracts: yes_size.min(no_size),
+            taken: false,
+            skip_reason: None,
+            secs_to_expiry: None,
+            interval_secs: None,
+            spent: None,
+            profit: None,
+        }
+    }
+
+    pub fn taken(mut self) -> Self {
+        self.taken = true;
+        self
+    }
+
+    /// Where in its interval the arb was detected
+    #[allow(dead_code)]
+    pub fn timed(mut self, secs_to_expiry: i64, interval_secs: i64) -> Self {
+        self.secs_to_expiry = Some(secs_to_expiry);
+        self.interval_secs = Some(interval_secs);
+        self
+    }
+
+    /// Taken, committing `spent` dollars
+    #[allow(dead_code)]
+    pub fn filled(mut self, spent: f64) -> Self {
+        self.spent = Some(spent);
+        self.taken()
+    }
+
+    /// Seconds since the interval opened (None for records without interval timing)
+    #[allow(dead_code)]
+    pub fn secs_into_interval(&self) -> Option<i64> {
+        Some(self.interval_secs? - self.secs_to_expiry?).filter(|s| *s >= 0)
+    }
+
+    /// Net profit of the execution (dollars)
+    pub fn with_profit(mut self, profit: f64) -> Self {
+        self.profit = Some(profit);
+        self
+    }
+
+    pub fn skipped(mut self, reason: &str) -> Self {
+        self.skip_reason = Some(reason.to_string());
+        self
+    }
+
+    /// Edge on the full fillable size (dollars)
+    #[allow(dead_code)]
+    pub fn dollar_edge(&self) -> f64 {
+        self.edge * self.contracts
+    }
+
+    #[allow(dead_code)]
+    fn date(&self) -> &str {
+        self.ts.get(..10).unwrap_or(&self.ts)
+    }
+}
+
+fn writer() -> &'static OnceLock<mpsc::UnboundedSender<OpportunityRecord>> {
+    static WRITER: OnceLock<mpsc::UnboundedSender<OpportunityRecord>> = OnceLock::new();
+    &WRITER
+}
+
+/// Queue an opportunity for the history file (no-op until `spawn_writer` ran)
+pub fn record(opp: OpportunityRecord) {
+    if let Some(tx) = writer().get() {
+        let _ = tx.send(opp);
+    }
+}
+
+/// Start appending recorded opportunities to OPP_FILE (OPP_LOG=0 disables)
+pub fn spawn_writer() {
+    if !env_flag("OPP_LOG", true) {
+        return;
+    }
+    let path = storage::data_path(std::env::var("OPP_FILE").unwrap_or_else(|_| DEFAULT_OPP_FILE.to_string()));
+    let file = match std::fs::OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            warn!("[OPPS] Cannot open {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    if writer().set(tx).is_err() {
+        return;
+    }
+    info!("[OPPS] Recording opportunities to {}", path.display());
+
+    runtimes::spawn_bookkeeping(async move {
+        let mut out = std::io::LineWriter::new(file);
+        while let Some(opp) = rx.recv().await {
+            let line = serde_json::to_string(&opp).unwrap_or_default();
+            if let Err(e) = writeln!(out, "{}", line) {
+                warn!("[OPPS] Write failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Read a history file and its archived segments, skipping lines that don't parse
+#[allow(dead_code)]
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<OpportunityRecord>> {
+    let contents = archive::read_all(path.as_ref())?;
+    Ok(contents.lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// Default history path (for the opps CLI)
+#[allow(dead_code)]
+pub fn default_path() -> PathBuf {
+    storage::data_path(DEFAULT_OPP_FILE)
+}
+
+/// Edge per asset over the recorded days
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetEdge {
+    pub asset: String,
+    pub opportunities: usize,
+    pub taken: usize,
+    /// Mean per-contract edge (dollars)
+    pub avg_edge: f64,
+    /// Fillable dollar edge per recorded day
+    pub daily_edge: f64,
+}
+
+/// Per-asset edge; daily figures divide by the days the history covers (any asset)
+#[allow(dead_code)]
+pub fn edge_by_asset(records: &[OpportunityRecord]) -> Vec<AssetEdge> {
+    let days = records.iter().map(|r| r.date()).collect::<BTreeSet<_>>().len().max(1) as f64;
+    let mut by_asset: BTreeMap<&str, Vec<&OpportunityRecord>> = BTreeMap::new();
+    for r in records {
+        by_asset.entry(r.asset.as_str()).or_default().push(r);
+    }
+    by_asset.into_iter()
+        .map(|(asset, opps)| AssetEdge {
+            asset: asset.to_string(),
+            opportunities: opps.len(),
+            taken: opps.iter().filter(|r| r.taken).count(),
+            avg_edge: opps.iter().map(|r| r.edge).sum::<f64>() / opps.len() as f64,
+            daily_edge: opps.iter().map(|r| r.dollar_edge()).sum::<f64>() / days,
+        })
+        .collect()
+}
+
+/// Opportunities in one UTC hour of the day
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HourBucket {
+    pub hour: u32,
+    pub opportunities: usize,
+    pub taken: usize,
+    pub dollar_edge: f64,
+}
+
+/// Opportunity counts for each UTC hour 0-23
+#[allow(dead_code)]
+pub fn by_hour(records: &[OpportunityRecord]) -> Vec<HourBucket> {
+    let mut hours: Vec<HourBucket> = (0..24).map(|hour| HourBucket { hour, ..Default::default() }).collect();
+    for r in records {
+        let Some(hour) = r.ts.get(11..13).and_then(|h| h.parse::<usize>().ok()).filter(|h| *h < 24) else {
+            continue;
+        };
+        hours[hour].opportunities += 1;
+        hours[hour].taken += r.taken as usize;
+        hours[hour].dollar_edge += r.dollar_edge();
+    }
+    hours
+}
+
+/// Opportunities, fills and exposure in one slice of the interval
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CycleBucket {
+    /// Seconds since the interval opened, start of the slice
+    pub start_secs: i64,
+    pub opportunities: usize,
+    pub taken: usize,
+    /// Dollars committed by the fills
+    pub spent: f64,
+    pub dollar_edge: f64,
+}
+
+/// Interval-market records bucketed by time since their interval opened, `bucket_secs` wide,
+/// covering the longest interval recorded (empty if no record carries interval timing)
+#[allow(dead_code)]
+pub fn by_cycle(records: &[OpportunityRecord], bucket_secs: i64) -> Vec<CycleBucket> {
+    let bucket_secs = bucket_secs.max(1);
+    let Some(interval) = records.iter().filter_map(|r| r.interval_secs).max() else {
+        return Vec::new();
+    };
+    let n = ((interval + bucket_secs - 1) / bucket_secs).max(1) as usize;
+    let mut buckets: Vec<CycleBucket> = (0..n)
+        .map(|i| CycleBucket { start_secs: i as i64 * bucket_secs, ..Default::default() })
+        .collect();
+    for r in records {
+        let Some(elapsed) = r.secs_into_interval() else { continue };
+        let b = &mut buckets[((elapsed / bucket_secs) as usize).min(n - 1)];
+        b.opportunities += 1;
+        b.taken += r.taken as usize;
+        b.spent += r.spent.unwrap_or(0.0);
+        b.dollar_edge += r.dollar_edge();
+    }
+    buckets
+}
+
+/// Records detected within the last `days` days of `now`
+#[allow(dead_code)]
+pub fn within_days(records: Vec<OpportunityRecord>, days: i64, now: chrono::DateTime<chrono::Utc>) -> Vec<OpportunityRecord> {
+    let cutoff = now - chrono::Duration::days(days);
+    records.into_iter()
+        .filter(|r| chrono::DateTime::parse_from_rfc3339(&r.ts).is_ok_and(|t| t >= cutoff))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opp(ts: &str, asset: &str, yes: f64, no: f64, size: f64) -> OpportunityRecord {
+        let mut r = OpportunityRecord::new(asset, asset, "PolyOnly", yes, no, size, size * 2.0, 0.0);
+        r.ts = ts.into();
+        r
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let r = opp("2025-12-18T20:00:00+00:00", "btc", 0.45, 0.52, 10.0).skipped("throttled");
+        let line = serde_json::to_string(&r).unwrap();
+        assert_eq!(serde_json::from_str::<OpportunityRecord>(&line).unwrap(), r);
+        assert!((r.edge - 0.03).abs() < 1e-9);
+        assert_eq!(r.contracts, 10.0);
+    }
+
+    #[test]
+    fn test_edge_by_asset_averages_over_recorded_days() {
+        let records = vec![
+            opp("2025-12-18T20:00:00+00:00", "btc", 0.45, 0.52, 10.0).taken(), // 3¢ × 10
+            opp("2025-12-19T09:00:00+00:00", "btc", 0.45, 0.50, 10.0),         // 5¢ × 10
+            opp("2025-12-19T10:00:00+00:00", "eth", 0.48, 0.50, 20.0),         // 2¢ × 20
+        ];
+        let assets = edge_by_asset(&records);
+        assert_eq!(assets.len(), 2);
+        assert_eq!((assets[0].asset.as_str(), assets[0].opportunities, assets[0].taken), ("btc", 2, 1));
+        assert!((assets[0].avg_edge - 0.04).abs() < 1e-9);
+        assert!((assets[0].daily_edge - 0.40).abs() < 1e-9);
+        assert!((assets[1].daily_edge - 0.20).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_by_hour_and_window() {
+        let now = chrono::DateTime::parse_from_rfc3339("2025-12-20T00:00:00+00:00").unwrap().with_timezone(&chrono::Utc);
+        let records = vec![
+            opp("2025-12-19T09:15:00+00:00", "btc", 0.45, 0.52, 10.0),
+            opp("2025-12-19T09:45:00+00:00", "eth", 0.45, 0.52, 10.0).taken(),
+            opp("2025-10-01T21:00:00+00:00", "btc", 0.45, 0.52, 10.0),
+        ];
+        let hours = by_hour(&records);
+        assert_eq!((hours[9].opportunities, hours[9].taken, hours[21].opportunities), (2, 1, 1));
+
+        assert_eq!(within_days(records, 30, now).len(), 2);
+    }
+
+    #[test]
+    fn test_by_cycle_buckets_time_since_open() {
+        let records = vec![
+            opp("2025-12-19T09:00:30+00:00", "btc", 0.45, 0.52, 10.0).timed(870, 900).filled(9.7),
+            opp("2025-12-19T09:01:20+00:00", "eth", 0.45, 0.52, 10.0).timed(820, 900).filled(4.0),
+            opp("2025-12-19T09:14:50+00:00", "btc", 0.45, 0.52, 10.0).timed(10, 900),
+            opp("2025-12-19T09:00:00+00:00", "nba", 0.45, 0.52, 10.0), // no interval
+        ];
+        let buckets = by_cycle(&records, 90);
+        assert_eq!(buckets.len(), 10);
+        assert_eq!((buckets[0].start_secs, buckets[0].opportunities, buckets[0].taken), (0, 2, 2));
+        assert!((buckets[0].spent - 13.7).abs() < 1e-9);
+        assert_eq!((buckets[9].start_secs, buckets[9].opportunities, buckets[9].taken), (810, 1, 0));
+        assert_eq!(buckets.iter().map(|b| b.opportunities).sum::<usize>(), 3);
+
+        // Older history without timing still loads
+        let line = r#"{"ts":"2025-12-18T20:00:00+00:00","asset":"btc","market":"m","strategy":"PolyOnly","yes_price":0.45,"no_price":0.52,"yes_size":1.0,"no_size":1.0,"edge":0.03,"contracts":1.0,"taken":true}"#;
+        let old: OpportunityRecord = serde_json::from_str(line).unwrap();
+        assert_eq!(old.secs_into_interval(), None);
+        assert!(by_cycle(&[old], 90).is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+// src/order_errors.rs
+// Typed order rejections - exchange error codes mapped to reasons callers can act on
+
+/// Why an exchange refused an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// Not enough USDC balance / allowance - needs a top-up, retrying won't help
+    InsufficientBalance,
+    /// Market closed, paused or not accepting orders - stop trading it
+    MarketClosed,
+    /// Price off the tick grid or outside the allowed range - re-round and retry
+    InvalidPrice,
+    /// Below the minimum order size
+    InvalidSize,
+    /// FAK found nothing to match - a zero fill, not a failure
+    NoMatch,
+    /// Rate limited (HTTP 429)
+    RateLimited,
+    Other,
+}
+
+impl RejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InsufficientBalance => "insufficient_balance",
+            Self::MarketClosed => "market_closed",
+            Self::InvalidPrice => "invalid_price",
+            Self::InvalidSize => "invalid_size",
+            Self::NoMatch => "no_match",
+            Self::RateLimited => "rate_limited",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An order the exchange refused (travels inside `anyhow::Error`; see `rejection`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRejected {
+    pub venue: &'static str,
+    pub status: u16,
+    pub reason: RejectReason,
+    /// Exchange's error code / message
+    pub message: String,
+}
+
+impl std::fmt::Display for OrderRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} order rejected ({}) {}: {}", self.venue, self.reason, self.status, self.message)
+    }
+}
+
+impl std::error::Error for OrderRejected {}
+
+impl OrderRejected {
+    /// Classify a Polymarket CLOB error response (`{"error": ...}` / `{"errorMsg": ...}` or plain text)
+    pub fn polymarket(status: u16, body: &str) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| {
+                ["error", "errorMsg", "message"].iter()
+                    .find_map(|k| v.get(*k).and_then(|m| m.as_str()).filter(|m| !m.is_empty()).map(String::from))
+            })
+            .unwrap_or_else(|| body.trim().to_string());
+        Self {
+            venue: "polymarket",
+            status,
+            reason: classify_polymarket(status, &message),
+            message,
+        }
+    }
+
+    /// Classify a Kalshi API error response (`{"error": {"code": ..., "message": ...}}` or plain text)
+    pub fn kalshi(status: u16, body: &str) -> Self {
+        let error = serde_json::from_str::<serde_json::Value>(body).ok().and_then(|v| v.get("error").cloned());
+        let field = |k: &str| error.as_ref().and_then(|e| e.get(k)).and_then(|m| m.as_str()).unwrap_or("").to_string();
+        let (code, message) = (field("code"), field("message"));
+        let message = match (code.is_empty(), message.is_empty()) {
+            (true, true) => body.trim().to_string(),
+            (false, true) => code.clone(),
+            (true, false) => message,
+            (false, false) => format!("{}: {}", code, message),
+        };
+        Self {
+            venue: "kalshi",
+            status,
+            reason: classify_kalshi(status, &message),
+            message,
+        }
+    }
+}
+
+/// Map a Polymarket status + error message to a reason (codes first, then message wording)
+pub fn classify_polymarket(status: u16, message: &str) -> RejectReason {
+    if status == 429 {
+        return RejectReason::RateLimited;
+    }
+    let m = message.to_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| m.contains(n));
+    // "the orderbook <id> does not exist" - either half alone also appears in unrelated errors
+    let missing_book = m.contains("orderbook") && m.contains("does not exist");
+    if has(&["not_enough_balance", "not enough balance", "not enough allowance", "insufficient allowance"]) {
+        RejectReason::InsufficientBalance
+    } else if has(&["no orders found to match", "fak_order_not_filled", "fok_order_not_filled"]) {
+        RejectReason::NoMatch
+    } else if missing_book || has(&["market_not_ready", "market is closed", "market closed", "not accepting orders",
+                                    "trading is paused"]) {
+        RejectReason::MarketClosed
+    } else if has(&["min_tick_size", "tick size", "invalid price", "price must be"]) {
+        RejectReason::InvalidPrice
+    } else if has(&["min_size", "minimum size", "lower than the minimum", "invalid amount"]) {
+        RejectReason::InvalidSize
+    } else if has(&["rate limit", "too many requests"]) {
+        RejectReason::RateLimited
+    } else {
+        RejectReason::Other
+    }
+}
+
+/// Map a Kalshi status + error code / message to a reason
+pub fn classify_kalshi(status: u16, message: &str) -> RejectReason {
+    if status == 429 {
+        return RejectReason::RateLimited;
+    }
+    let m = message.to_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| m.contains(n));
+    if has(&["insufficient_balance", "insufficient balance"]) {
+        RejectReason::InsufficientBalance
+    } else if has(&["market_closed", "market is closed", "market_not_open", "trading_is_paused",
+                    "exchange_closed", "market_inactive", "market is inactive"]) {
+        RejectReason::MarketClosed
+    } else if has(&["invalid_price", "price must be"]) {
+        RejectReason::InvalidPrice
+    } else if has(&["invalid_count", "count must be"]) {
+        RejectReason::InvalidSize
+    } else {
+        RejectReason::Other
+    }
+}
+
+/// The exchange rejection behind an order error, if that's what it was
+pub fn rejection(err: &anyhow::Error) -> Option<&OrderRejected> {
+    err.downcast_ref::<OrderRejected>()
+}
+
+/// Price moved onto the tick grid, rounded in the order's favour-neutral direction:
+/// down for buys (never pay more than intended), up for sells
+pub fn round_to_tick(price: f64, tick: f64, buy: bool) -> f64 {
+    let ticks = price / tick;
+    // Tolerate float noise (0.29 / 0.01 = 28.999999999999996)
+    let ticks = if (ticks - ticks.round()).abs() < 1e-6 {
+        ticks.round()
+    } else if buy {
+        ticks.floor()
+    } else {
+        ticks.ceil()
+    };
+    (ticks * tick * 1e6).round() / 1e6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_polymarket() {
+        let r = OrderRejected::polymarket(400, r#"{"error":"not enough balance / allowance"}"#);
+        assert_eq!((r.reason, r.message.as_str()), (RejectReason::InsufficientBalance, "not enough balance / allowance"));
+
+        let r = OrderRejected::polymarket(200, r#"{"success":false,"errorMsg":"INVALID_ORDER_MIN_TICK_SIZE","orderID":""}"#);
+        assert_eq!(r.reason, RejectReason::InvalidPrice);
+
+        assert_eq!(OrderRejected::polymarket(400, r#"{"error":"MARKET_NOT_READY"}"#).reason, RejectReason::MarketClosed);
+        assert_eq!(OrderRejected::polymarket(400, "the orderbook 123 does not exist").reason, RejectReason::MarketClosed);
+        assert_eq!(OrderRejected::polymarket(400, r#"{"error":"no orders found to match with FAK order"}"#).reason,
+                   RejectReason::NoMatch);
+        assert_eq!(OrderRejected::polymarket(400, r#"{"error":"INVALID_ORDER_MIN_SIZE"}"#).reason, RejectReason::InvalidSize);
+        assert_eq!(OrderRejected::polymarket(429, "slow down").reason, RejectReason::RateLimited);
+        assert_eq!(OrderRejected::polymarket(500, "internal").reason, RejectReason::Other);
+
+        // Loose wording doesn't match on a single word
+        assert_eq!(OrderRejected::polymarket(400, r#"{"error":"order 0xabc does not exist"}"#).reason, RejectReason::Other);
+        assert_eq!(OrderRejected::polymarket(400, "orderbook snapshot unavailable").reason, RejectReason::Other);
+        assert_eq!(OrderRejected::polymarket(400, "invalid allowance signature").reason, RejectReason::Other);
+    }
+
+    #[test]
+    fn test_classify_kalshi() {
+        let r = OrderRejected::kalshi(400, r#"{"error":{"code":"market_closed","message":"market is closed"}}"#);
+        assert_eq!((r.reason, r.message.as_str()), (RejectReason::MarketClosed, "market_closed: market is closed"));
+        assert_eq!(OrderRejected::kalshi(400, r#"{"error":{"code":"trading_is_paused"}}"#).reason, RejectReason::MarketClosed);
+        assert_eq!(OrderRejected::kalshi(400, r#"{"error":{"code":"insufficient_balance"}}"#).reason,
+                   RejectReason::InsufficientBalance);
+        assert_eq!(OrderRejected::kalshi(429, "").reason, RejectReason::RateLimited);
+        assert_eq!(OrderRejected::kalshi(400, r#"{"error":{"code":"order_inactive"}}"#).reason, RejectReason::Other);
+        assert_eq!(OrderRejected::kalshi(502, "bad gateway").message, "bad gateway");
+    }
+
+    #[test]
+    fn test_rejection_survives_anyhow() {
+        let err: anyhow::Error = OrderRejected::polymarket(400, r#"{"error":"MARKET_NOT_READY"}"#).into();
+        let err = err.context("buy BTC yes");
+        assert_eq!(rejection(&err).map(|r| r.reason), Some(RejectReason::MarketClosed));
+        assert!(rejection(&anyhow::anyhow!("timeout")).is_none());
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(0.4567, 0.01, true), 0.45);
+        assert_eq!(round_to_tick(0.4567, 0.01, false), 0.46);
+        assert_eq!(round_to_tick(0.29, 0.01, true), 0.29);
+        assert_eq!(round_to_tick(0.4567, 0.001, true), 0.456);
+    }
+}
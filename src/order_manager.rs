@@ -0,0 +1,416 @@
+// src/order_manager.rs
+// Resting quote management - serialized cancel/replace with replacement chains, at most one live order per quote,
+// and the parent → child relationships of laddered taker orders
+
+use anyhow::Result;
+#[cfg(feature = "kalshi")]
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::{debug, warn};
+
+#[cfg(feature = "kalshi")]
+use crate::kalshi::{KalshiApiClient, KalshiOrderRequest};
+use crate::liquidity_rewards;
+use crate::metrics;
+#[cfg(feature = "kalshi")]
+use crate::order_tags::OrderStrategy;
+use crate::own_orders::own_orders;
+use crate::replay::{self, DeferredAction};
+use crate::venue_health::Venue;
+
+/// A resting limit order to keep on the book
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub ticker: String,
+    /// "yes" / "no"
+    pub side: &'static str,
+    pub price_cents: i64,
+    pub count: i64,
+}
+
+/// Venue operations the manager needs. `cancel` and `find_live` must be safe to repeat.
+pub trait QuoteVenue: Send + Sync {
+    /// Venue the quotes rest on (a cancel it can't take during an outage is deferred)
+    fn venue(&self) -> Venue;
+
+    /// Fresh client order id, sent with the order so an unconfirmed placement can be found again
+    fn new_client_order_id(&self) -> String;
+
+    /// Place `quote` as a resting order; returns the venue order id
+    fn place(&self, quote: &Quote, client_order_id: &str) -> impl Future<Output = Result<String>> + Send;
+
+    /// Cancel a resting order. Ok once it no longer rests (cancelled now or earlier, or filled).
+    fn cancel(&self, quote: &Quote, order_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Order id of the order sent as `client_order_id` (at or after `since`, Unix seconds) if it is resting
+    fn find_live(&self, quote: &Quote, client_order_id: &str, since: i64) -> impl Future<Output = Result<Option<String>>> + Send;
+}
+
+/// Latest order of a replacement chain
+#[derive(Debug, Clone, PartialEq)]
+enum Head {
+    /// Nothing resting
+    Empty,
+    Live { order_id: String, quote: Quote },
+    /// Sent, but the response was lost: it may or may not be resting
+    Unconfirmed { client_order_id: String, quote: Quote, sent_at: i64 },
+}
+
+/// One quote's order and every order it replaced
+#[derive(Debug)]
+struct Chain {
+    head: Head,
+    /// Order ids in placement order (the last one is the head when it is live)
+    history: Vec<String>,
+}
+
+/// Keeps one resting order per quote key. Amendments of the same quote are serialized,
+/// and a replacement is only sent once its predecessor is known not to rest, so a
+/// quote never has two live descendants. Every order that may rest is registered in
+/// `own_orders` so the taker side doesn't trade against it.
+pub struct OrderManager<V> {
+    venue: V,
+    chains: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Chain>>>>,
+}
+
+impl<V: QuoteVenue> OrderManager<V> {
+    pub fn new(venue: V) -> Self {
+        Self { venue, chains: Mutex::new(HashMap::new()) }
+    }
+
+    fn chain(&self, key: &str) -> Arc<tokio::sync::Mutex<Chain>> {
+        self.chains.lock().unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(Chain { head: Head::Empty, history: Vec::new() })))
+            .clone()
+    }
+
+    /// Make sure the chain's head no longer rests, resolving an unconfirmed placement first.
+    /// On error the head is left as it was, so the call can simply be retried.
+    async fn retire_head(&self, key: &str, chain: &mut Chain) -> Result<()> {
+        if let Head::Unconfirmed { client_order_id, quote, sent_at } = &chain.head {
+            chain.head = match self.venue.find_live(quote, client_order_id, *sent_at).await? {
+                Some(order_id) => {
+                    own_orders().rekey(client_order_id, &order_id);
+                    chain.history.push(order_id.clone());
+                    Head::Live { order_id, quote: quote.clone() }
+                }
+                None => {
+                    own_orders().untrack(client_order_id);
+                    Head::Empty
+                }
+            };
+        }
+        if let Head::Live { order_id, quote } = &chain.head {
+            if let Err(e) = self.venue.cancel(quote, order_id).await {
+                // The order is still ours to pull: replayed once the venue is back
+                let venue = self.venue.venue();
+                if replay::is_outage(venue, &e) {
+                    replay::buffer().push(DeferredAction::Cancel { venue, order_id: order_id.clone() }, &e.to_string());
+                }
+                return Err(e);
+            }
+            own_orders().untrack(order_id);
+            liquidity_rewards::tracker().quote_pulled(key, quote);
+            chain.head = Head::Empty;
+        }
+        Ok(())
+    }
+
+    /// Cancel/replace the quote under `key` with `quote` (places it if nothing rests yet).
+    /// Returns the new order id. If the cancel fails, nothing new is sent and the old order
+    /// stays the live one; if the placement's response is lost, the next call resolves it.
+    pub async fn amend_order(&self, key: &str, quote: Quote) -> Result<String> {
+        let chain = self.chain(key);
+        let mut chain = chain.lock().await;
+
+        if let Err(e) = self.retire_head(key, &mut chain).await {
+            metrics::incr("orders.amend.cancel_failed");
+            warn!("[ORDERS] Amend of {} aborted, previous order may still rest: {}", key, e);
+            return Err(e);
+        }
+
+        let client_order_id = self.venue.new_client_order_id();
+        // Kalshi resting buys show up in the feed's bid ladder for their side
+        own_orders().track(&client_order_id, &quote.ticker, quote.side, quote.price_cents, quote.count);
+        chain.head = Head::Unconfirmed {
+            client_order_id: client_order_id.clone(),
+            quote: quote.clone(),
+            sent_at: chrono::Utc::now().timestamp(),
+        };
+        match self.venue.place(&quote, &client_order_id).await {
+            Ok(order_id) => {
+                debug!("[ORDERS] {} → {} @{}¢ x{}", key, order_id, quote.price_cents, quote.count);
+                metrics::incr("orders.amend.ok");
+                own_orders().rekey(&client_order_id, &order_id);
+                chain.history.push(order_id.clone());
+                liquidity_rewards::tracker().quote_live(key, &quote);
+                chain.head = Head::Live { order_id: order_id.clone(), quote };
+                Ok(order_id)
+            }
+            Err(e) => {
+                metrics::incr("orders.amend.place_failed");
+                warn!("[ORDERS] Placement for {} unconfirmed (client id {}): {}", key, client_order_id, e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Pull the quote under `key` (a no-op if nothing rests)
+    pub async fn cancel(&self, key: &str) -> Result<()> {
+        let chain = self.chain(key);
+        let mut chain = chain.lock().await;
+        self.retire_head(key, &mut chain).await
+    }
+
+    /// Order id resting for `key`, if confirmed
+    pub async fn live_order(&self, key: &str) -> Option<String> {
+        match &self.chain(key).lock().await.head {
+            Head::Live { order_id, .. } => Some(order_id.clone()),
+            _ => None,
+        }
+    }
+
+    /// Every order id placed for `key`, oldest first
+    pub async fn history(&self, key: &str) -> Vec<String> {
+        self.chain(key).lock().await.history.clone()
+    }
+}
+
+/// Laddered parents remembered by `ChildOrders`
+const MAX_LADDER_PARENTS: usize = 512;
+
+/// One order of a laddered execution
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildOrder {
+    /// Position in the ladder (0 = first child)
+    pub index: usize,
+    /// "yes" / "no"
+    pub side: &'static str,
+    pub order_id: String,
+    /// Contracts the child was sent for
+    pub contracts: f64,
+}
+
+/// Child orders of each laddered parent (an arb split into several smaller orders),
+/// most recent parents kept
+#[derive(Debug, Default)]
+pub struct ChildOrders {
+    parents: Mutex<VecDeque<(String, Vec<ChildOrder>)>>,
+}
+
+impl ChildOrders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, parent: &str, child: ChildOrder) {
+        let mut parents = self.parents.lock().unwrap();
+        match parents.iter_mut().find(|(p, _)| p == parent) {
+            Some((_, children)) => children.push(child),
+            None => {
+                if parents.len() >= MAX_LADDER_PARENTS {
+                    parents.pop_front();
+                }
+                parents.push_back((parent.to_string(), vec![child]));
+                metrics::incr("orders.ladder.parents");
+            }
+        }
+        metrics::incr("orders.ladder.children");
+    }
+
+    /// Children of `parent` in the order they were sent
+    pub fn children(&self, parent: &str) -> Vec<ChildOrder> {
+        self.parents.lock().unwrap().iter()
+            .find(|(p, _)| p == parent)
+            .map(|(_, children)| children.clone())
+            .unwrap_or_default()
+    }
+
+    /// Parent of a child order id
+    pub fn parent_of(&self, order_id: &str) -> Option<String> {
+        self.parents.lock().unwrap().iter()
+            .find(|(_, children)| children.iter().any(|c| c.order_id == order_id))
+            .map(|(p, _)| p.clone())
+    }
+}
+
+/// Process-wide child order registry
+pub fn child_orders() -> &'static ChildOrders {
+    static CHILDREN: OnceLock<ChildOrders> = OnceLock::new();
+    CHILDREN.get_or_init(ChildOrders::new)
+}
+
+#[cfg(feature = "kalshi")]
+impl QuoteVenue for KalshiApiClient {
+    fn venue(&self) -> Venue {
+        Venue::Kalshi
+    }
+
+    fn new_client_order_id(&self) -> String {
+        KalshiApiClient::new_client_order_id(OrderStrategy::Quote)
+    }
+
+    async fn place(&self, quote: &Quote, client_order_id: &str) -> Result<String> {
+        let order = KalshiOrderRequest::resting_buy(
+            Cow::Borrowed(&quote.ticker), quote.side, quote.price_cents, quote.count, Cow::Borrowed(client_order_id),
+        );
+        Ok(self.create_order(&order).await?.order.order_id)
+    }
+
+    async fn cancel(&self, _quote: &Quote, order_id: &str) -> Result<()> {
+        let Err(e) = self.cancel_order(order_id).await else {
+            return Ok(());
+        };
+        // Already cancelled or filled is what we wanted
+        match self.get_order(order_id).await {
+            Ok(resp) if resp.order.status != "resting" => Ok(()),
+            _ => Err(anyhow::anyhow!("cancel {}: {}", order_id, e)),
+        }
+    }
+
+    async fn find_live(&self, quote: &Quote, client_order_id: &str, since: i64) -> Result<Option<String>> {
+        let found = self.find_order_by_client_id(&quote.ticker, client_order_id, since.saturating_sub(5)).await?;
+        Ok(found.filter(|o| o.status == "resting").map(|o| o.order_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+    /// In-memory venue: resting orders by id, with injectable failures
+    #[derive(Default)]
+    struct MockVenue {
+        resting: Mutex<HashMap<String, String>>, // order id -> client id
+        next_id: AtomicU64,
+        fail_cancel: AtomicBool,
+        /// The order rests but the response is lost
+        lose_response: AtomicBool,
+    }
+
+    impl MockVenue {
+        fn resting(&self) -> usize {
+            self.resting.lock().unwrap().len()
+        }
+    }
+
+    impl QuoteVenue for Arc<MockVenue> {
+        fn venue(&self) -> Venue {
+            Venue::Kalshi
+        }
+
+        fn new_client_order_id(&self) -> String {
+            format!("c{}", self.next_id.fetch_add(1, Ordering::Relaxed))
+        }
+
+        async fn place(&self, _quote: &Quote, client_order_id: &str) -> Result<String> {
+            tokio::task::yield_now().await;
+            let order_id = format!("o{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+            self.resting.lock().unwrap().insert(order_id.clone(), client_order_id.to_string());
+            if self.lose_response.load(Ordering::Relaxed) {
+                return Err(anyhow!("timeout"));
+            }
+            Ok(order_id)
+        }
+
+        async fn cancel(&self, _quote: &Quote, order_id: &str) -> Result<()> {
+            tokio::task::yield_now().await;
+            if self.fail_cancel.load(Ordering::Relaxed) {
+                return Err(anyhow!("503"));
+            }
+            self.resting.lock().unwrap().remove(order_id);
+            Ok(())
+        }
+
+        async fn find_live(&self, _quote: &Quote, client_order_id: &str, _since: i64) -> Result<Option<String>> {
+            Ok(self.resting.lock().unwrap().iter()
+                .find(|(_, c)| c.as_str() == client_order_id)
+                .map(|(o, _)| o.clone()))
+        }
+    }
+
+    fn quote(price: i64) -> Quote {
+        Quote { ticker: "KXTEST-1".into(), side: "yes", price_cents: price, count: 10 }
+    }
+
+    #[tokio::test]
+    async fn test_amend_replaces_and_tracks_chain() {
+        let venue = Arc::new(MockVenue::default());
+        let manager = OrderManager::new(venue.clone());
+        // Own ticker: the self-trade registry is process-wide and the other tests run concurrently
+        let quote = |price| Quote { ticker: "KXTEST-STP".into(), ..quote(price) };
+        let first = manager.amend_order("q", quote(40)).await.unwrap();
+        let second = manager.amend_order("q", quote(41)).await.unwrap();
+        let third = manager.amend_order("q", quote(42)).await.unwrap();
+
+        assert_eq!(venue.resting(), 1);
+        assert_eq!(manager.live_order("q").await, Some(third.clone()));
+        assert_eq!(manager.history("q").await, vec![first, second, third]);
+        // Only the live order is excluded from the book
+        let own = own_orders();
+        assert_eq!((own.resting("KXTEST-STP", "yes", 40), own.resting("KXTEST-STP", "yes", 42)), (0, 10));
+
+        manager.cancel("q").await.unwrap();
+        assert_eq!((venue.resting(), manager.live_order("q").await), (0, None));
+        assert_eq!(own.resting("KXTEST-STP", "yes", 42), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_cancel_sends_nothing_and_retry_recovers() {
+        let venue = Arc::new(MockVenue::default());
+        let manager = OrderManager::new(venue.clone());
+        let first = manager.amend_order("q", quote(40)).await.unwrap();
+
+        venue.fail_cancel.store(true, Ordering::Relaxed);
+        assert!(manager.amend_order("q", quote(41)).await.is_err());
+        assert_eq!(venue.resting(), 1);
+        assert_eq!(manager.live_order("q").await, Some(first));
+
+        venue.fail_cancel.store(false, Ordering::Relaxed);
+        manager.amend_order("q", quote(41)).await.unwrap();
+        assert_eq!(venue.resting(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lost_placement_is_found_before_replacing() {
+        let venue = Arc::new(MockVenue::default());
+        let manager = OrderManager::new(venue.clone());
+        venue.lose_response.store(true, Ordering::Relaxed);
+        assert!(manager.amend_order("q", quote(40)).await.is_err());
+        assert_eq!((venue.resting(), manager.live_order("q").await), (1, None));
+
+        // The next amend finds the orphan by client id and cancels it first
+        venue.lose_response.store(false, Ordering::Relaxed);
+        let id = manager.amend_order("q", quote(41)).await.unwrap();
+        assert_eq!(venue.resting(), 1);
+        assert_eq!(manager.history("q").await.len(), 2);
+        assert_eq!(manager.live_order("q").await, Some(id));
+
+        // Concurrent amends of one quote are serialized
+        let (a, b) = tokio::join!(manager.amend_order("q", quote(42)), manager.amend_order("q", quote(43)));
+        assert!(a.is_ok() && b.is_ok());
+        assert_eq!(venue.resting(), 1);
+    }
+
+    #[test]
+    fn test_child_orders_by_parent() {
+        let children = ChildOrders::new();
+        let child = |index, side, id: &str| ChildOrder { index, side, order_id: id.into(), contracts: 16.0 };
+        children.record("p1", child(0, "yes", "0xa"));
+        children.record("p1", child(0, "no", "0xb"));
+        children.record("p2", child(0, "yes", "0xc"));
+        children.record("p1", child(1, "yes", "0xd"));
+
+        let p1 = children.children("p1");
+        assert_eq!(p1.iter().map(|c| (c.index, c.order_id.as_str())).collect::<Vec<_>>(),
+                   vec![(0, "0xa"), (0, "0xb"), (1, "0xd")]);
+        assert_eq!(children.parent_of("0xc").as_deref(), Some("p2"));
+        assert_eq!(children.parent_of("0xe"), None);
+        assert!(children.children("p3").is_empty());
+    }
+}
@@ -0,0 +1,354 @@
+// src/order_rate.rs
+// Order rates - orders/minute per venue and API key against exchange limits, so new intents are
+// deferred or refused near the limit instead of crowding out market-data calls
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::metrics;
+use crate::venue_health::Venue;
+
+/// Orders are counted over this trailing window
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// How long an admitted intent's room is held for its orders to be sent and recorded
+const RESERVATION_TTL: Duration = Duration::from_secs(5);
+
+/// Order-rate configuration from environment
+#[derive(Debug, Clone)]
+pub struct OrderRateConfig {
+    /// Kalshi order limit per API key (orders/minute, 0 = untracked)
+    pub kalshi_per_min: u32,
+
+    /// Polymarket order limit per API key (orders/minute, 0 = untracked)
+    pub poly_per_min: u32,
+
+    /// Fraction of a limit new intents may use; the rest is left to compensating orders
+    /// and to the calls that share the exchange's budget
+    pub headroom: f64,
+
+    /// Longest an intent waits for the window to free up before it is refused (ms)
+    pub max_defer_ms: u64,
+}
+
+impl OrderRateConfig {
+    pub fn from_env() -> Self {
+        Self {
+            kalshi_per_min: std::env::var("KALSHI_ORDER_LIMIT_PER_MIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(600),
+
+            poly_per_min: std::env::var("POLY_ORDER_LIMIT_PER_MIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+
+            headroom: std::env::var("ORDER_RATE_HEADROOM")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.8),
+
+            max_defer_ms: std::env::var("ORDER_RATE_MAX_DEFER_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(500),
+        }
+    }
+
+    pub fn limit(&self, venue: Venue) -> u32 {
+        match venue {
+            Venue::Kalshi => self.kalshi_per_min,
+            Venue::Polymarket => self.poly_per_min,
+        }
+    }
+
+    /// Orders/minute new intents may bring a key of `venue` to (None = untracked)
+    pub fn budget(&self, venue: Venue) -> Option<usize> {
+        match self.limit(venue) {
+            0 => None,
+            limit => Some((limit as f64 * self.headroom.clamp(0.0, 1.0)).floor() as usize),
+        }
+    }
+}
+
+/// An intent refused because a venue's order budget is used up
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRateBreach {
+    pub venue: Venue,
+    pub per_min: usize,
+    pub budget: usize,
+    pub limit: u32,
+}
+
+impl std::fmt::Display for OrderRateBreach {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {} orders/min (budget {} of the {}/min limit)",
+               self.venue.name(), self.per_min, self.budget, self.limit)
+    }
+}
+
+/// What an intent of some orders may do now
+#[derive(Debug, Clone, PartialEq)]
+pub enum Admission {
+    Send,
+    /// The window frees enough room after this long
+    Defer(Duration),
+    Refuse(OrderRateBreach),
+}
+
+#[derive(Default)]
+struct RateState {
+    /// Sent-order timestamps per venue and API key over the trailing minute
+    sent: HashMap<(Venue, String), VecDeque<Instant>>,
+    /// Orders admitted by `acquire` but not sent yet, per venue (when admitted)
+    reserved: HashMap<Venue, VecDeque<Instant>>,
+}
+
+impl RateState {
+    /// Orders counted against `venue`'s budget: its busiest key plus what is reserved
+    fn load(&mut self, venue: Venue, now: Instant) -> (usize, Option<&VecDeque<Instant>>) {
+        let reserved = self.reserved.get_mut(&venue).map_or(0, |times| {
+            while times.front().is_some_and(|t| now.saturating_duration_since(*t) >= RESERVATION_TTL) {
+                times.pop_front();
+            }
+            times.len()
+        });
+        let busiest = self.sent.iter_mut()
+            .filter(|((v, _), _)| *v == venue)
+            .map(|(_, times)| {
+                prune(times, now);
+                &*times
+            })
+            .max_by_key(|times| times.len());
+        (busiest.map_or(0, |t| t.len()) + reserved, busiest)
+    }
+}
+
+/// Sent-order timestamps per venue and API key over the trailing minute
+pub struct OrderRates {
+    config: OrderRateConfig,
+    state: Mutex<RateState>,
+}
+
+impl OrderRates {
+    pub fn new(config: OrderRateConfig) -> Self {
+        Self { config, state: Mutex::new(RateState::default()) }
+    }
+
+    /// Count an order sent with `key` (publishes `order_rate.<venue>.per_min`)
+    pub fn record(&self, venue: Venue, key: &str) {
+        self.record_at(venue, key, Instant::now());
+    }
+
+    pub fn record_at(&self, venue: Venue, key: &str, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        // The order takes the room its intent reserved
+        if let Some(reserved) = state.reserved.get_mut(&venue) {
+            reserved.pop_front();
+        }
+        let times = state.sent.entry((venue, key.to_string())).or_default();
+        times.push_back(now);
+        prune(times, now);
+        let per_min = times.len();
+        if let Some(budget) = self.config.budget(venue) {
+            if per_min == budget {
+                warn!("[RATE] ⚠️ {} key {} at its order budget ({}/min of {}/min)",
+                      venue.name(), key_label(key), per_min, self.config.limit(venue));
+            }
+        }
+        drop(state);
+        metrics::set_gauge(&format!("order_rate.{}.{}", venue.name(), key_label(key)), per_min as i64);
+        metrics::set_gauge(&format!("order_rate.{}.per_min", venue.name()), self.busiest_at(venue, now) as i64);
+    }
+
+    /// Orders/minute of the busiest key of `venue`
+    pub fn busiest_at(&self, venue: Venue, now: Instant) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.sent.iter_mut()
+            .filter(|((v, _), _)| *v == venue)
+            .map(|(_, times)| {
+                prune(times, now);
+                times.len()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether `orders` more orders on `venue` fit its budget now, later, or not soon enough
+    #[allow(dead_code)]
+    pub fn admit_at(&self, venue: Venue, orders: usize, now: Instant) -> Admission {
+        self.admit_locked(&mut self.state.lock().unwrap(), venue, orders, now)
+    }
+
+    fn admit_locked(&self, state: &mut RateState, venue: Venue, orders: usize, now: Instant) -> Admission {
+        let Some(budget) = self.config.budget(venue) else {
+            return Admission::Send;
+        };
+        let (load, busiest) = state.load(venue, now);
+        if load + orders <= budget {
+            return Admission::Send;
+        }
+        // Room opens once the oldest sends leave the window
+        let excess = load + orders - budget;
+        let refuse = || Admission::Refuse(OrderRateBreach {
+            venue, per_min: load, budget, limit: self.config.limit(venue),
+        });
+        match busiest.and_then(|times| times.get(excess - 1)) {
+            Some(t) if orders <= budget => {
+                let wait = (*t + WINDOW).saturating_duration_since(now);
+                if wait <= Duration::from_millis(self.config.max_defer_ms) { Admission::Defer(wait) } else { refuse() }
+            }
+            _ => refuse(),
+        }
+    }
+
+    /// Admit one order per entry of `venues` if every venue has room, reserving it under the
+    /// same lock so concurrent intents can't both take the last of a budget
+    fn try_reserve(&self, venues: &[Venue], now: Instant) -> Result<(), (Venue, Admission)> {
+        let mut state = self.state.lock().unwrap();
+        let mut wait = None;
+        for venue in [Venue::Kalshi, Venue::Polymarket] {
+            let orders = venues.iter().filter(|v| **v == venue).count();
+            if orders == 0 {
+                continue;
+            }
+            match self.admit_locked(&mut state, venue, orders, now) {
+                Admission::Send => {}
+                Admission::Defer(d) if wait.is_none_or(|(_, w)| d > w) => wait = Some((venue, d)),
+                Admission::Defer(_) => {}
+                refused @ Admission::Refuse(_) => return Err((venue, refused)),
+            }
+        }
+        if let Some((venue, d)) = wait {
+            return Err((venue, Admission::Defer(d)));
+        }
+        for venue in venues {
+            if self.config.budget(*venue).is_some() {
+                state.reserved.entry(*venue).or_default().push_back(now);
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait (up to ORDER_RATE_MAX_DEFER_MS) until one order per entry of `venues` fits every
+    /// venue's budget, and reserve the room until the orders are recorded (or for
+    /// RESERVATION_TTL); refused if it doesn't fit
+    pub async fn acquire(&self, venues: &[Venue]) -> Result<(), OrderRateBreach> {
+        let started = Instant::now();
+        loop {
+            let (venue, wait) = match self.try_reserve(venues, Instant::now()) {
+                Ok(()) => return Ok(()),
+                Err((venue, Admission::Refuse(breach))) => {
+                    metrics::incr(&format!("order_rate.{}.refused", venue.name()));
+                    return Err(breach);
+                }
+                Err((venue, Admission::Defer(wait))) => (venue, wait),
+                Err((_, Admission::Send)) => unreachable!("admitted intents are reserved"),
+            };
+            if started.elapsed() + wait > Duration::from_millis(self.config.max_defer_ms) {
+                // Another intent took the room that was freeing up
+                metrics::incr(&format!("order_rate.{}.refused", venue.name()));
+                return Err(OrderRateBreach {
+                    venue,
+                    per_min: self.busiest_at(venue, Instant::now()),
+                    budget: self.config.budget(venue).unwrap_or(0),
+                    limit: self.config.limit(venue),
+                });
+            }
+            metrics::incr("order_rate.deferred");
+            debug!("[RATE] Deferring intent {}ms for order budget", wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+fn prune(times: &mut VecDeque<Instant>, now: Instant) {
+    while times.front().is_some_and(|t| now.saturating_duration_since(*t) >= WINDOW) {
+        times.pop_front();
+    }
+}
+
+/// Short form of an API key for logs and metric names
+fn key_label(key: &str) -> String {
+    key.chars().filter(|c| c.is_ascii_alphanumeric()).take(8).collect()
+}
+
+/// Process-wide order rates (configured from env on first use)
+pub fn rates() -> &'static OrderRates {
+    static RATES: OnceLock<OrderRates> = OnceLock::new();
+    RATES.get_or_init(|| OrderRates::new(OrderRateConfig::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates(limit: u32) -> OrderRates {
+        OrderRates::new(OrderRateConfig { kalshi_per_min: limit, poly_per_min: 0, headroom: 0.8, max_defer_ms: 500 })
+    }
+
+    #[test]
+    fn test_counts_per_key_over_the_window() {
+        let r = rates(10);
+        let t0 = Instant::now();
+        for i in 0..5 {
+            r.record_at(Venue::Kalshi, "key-a", t0 + Duration::from_secs(i));
+        }
+        r.record_at(Venue::Kalshi, "key-b", t0);
+        assert_eq!(r.busiest_at(Venue::Kalshi, t0 + Duration::from_secs(10)), 5);
+        // The first two have left the window
+        assert_eq!(r.busiest_at(Venue::Kalshi, t0 + Duration::from_secs(61)), 3);
+        assert_eq!(r.busiest_at(Venue::Polymarket, t0), 0);
+    }
+
+    #[test]
+    fn test_admission_near_the_limit() {
+        let r = rates(10); // budget 8/min
+        let t0 = Instant::now();
+        for i in 0..7 {
+            r.record_at(Venue::Kalshi, "key", t0 + Duration::from_secs(i));
+        }
+        let now = t0 + Duration::from_millis(59_800);
+        assert_eq!(r.admit_at(Venue::Kalshi, 1, now), Admission::Send);
+        // Two more need the first send to age out, 200ms from now
+        assert_eq!(r.admit_at(Venue::Kalshi, 2, now), Admission::Defer(Duration::from_millis(200)));
+        // Three need the second one too, a second later than ORDER_RATE_MAX_DEFER_MS allows
+        assert!(matches!(r.admit_at(Venue::Kalshi, 3, now), Admission::Refuse(b) if b.per_min == 7 && b.budget == 8));
+        // Untracked venue
+        assert_eq!(r.admit_at(Venue::Polymarket, 100, now), Admission::Send);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_defers_then_sends() {
+        let r = OrderRates::new(OrderRateConfig { kalshi_per_min: 2, poly_per_min: 0, headroom: 1.0, max_defer_ms: 500 });
+        let now = Instant::now();
+        r.record_at(Venue::Kalshi, "key", now - WINDOW + Duration::from_millis(50));
+        r.record_at(Venue::Kalshi, "key", now);
+        assert!(r.acquire(&[Venue::Kalshi, Venue::Polymarket]).await.is_ok());
+        r.record_at(Venue::Kalshi, "key", Instant::now());
+        assert!(r.acquire(&[Venue::Kalshi, Venue::Kalshi]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_reserves_the_room_it_admits() {
+        let r = OrderRates::new(OrderRateConfig { kalshi_per_min: 3, poly_per_min: 0, headroom: 1.0, max_defer_ms: 0 });
+        r.record_at(Venue::Kalshi, "key", Instant::now());
+        // Two intents racing for the last two slots: the first reserves both
+        assert!(r.acquire(&[Venue::Kalshi, Venue::Kalshi]).await.is_ok());
+        assert!(r.acquire(&[Venue::Kalshi]).await.is_err());
+        // Sending the reserved orders takes their room rather than adding to it
+        r.record_at(Venue::Kalshi, "key", Instant::now());
+        r.record_at(Venue::Kalshi, "key", Instant::now());
+        assert_eq!(r.admit_at(Venue::Kalshi, 0, Instant::now()), Admission::Send);
+        assert!(matches!(r.admit_at(Venue::Kalshi, 1, Instant::now()), Admission::Refuse(b) if b.per_min == 3));
+        // An intent that never sends gives its room back after RESERVATION_TTL
+        let r = OrderRates::new(OrderRateConfig { kalshi_per_min: 1, poly_per_min: 0, headroom: 1.0, max_defer_ms: 0 });
+        let now = Instant::now();
+        assert!(r.try_reserve(&[Venue::Kalshi], now).is_ok());
+        assert!(r.try_reserve(&[Venue::Kalshi], now).is_err());
+        assert!(r.try_reserve(&[Venue::Kalshi], now + RESERVATION_TTL).is_ok());
+    }
+}
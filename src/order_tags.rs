@@ -0,0 +1,192 @@
+// src/order_tags.rs
+// Client order id tags - strategy and session encoded in venue order ids for exchange-side attribution
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::types::ArbType;
+
+/// Prefix of every tagged client order id
+const PREFIX: &str = "ab";
+
+/// Longest session id kept (ORDER_SESSION_ID is truncated to this)
+const MAX_SESSION_LEN: usize = 8;
+
+/// What sent an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OrderStrategy {
+    /// Leg of a cross-venue arb
+    Cross,
+    /// Leg of a same-venue Kalshi arb
+    KalshiOnly,
+    /// Auto-close of an unmatched leg
+    Hedge,
+    /// Resting quote from the order manager
+    Quote,
+    /// Flatten during crash recovery
+    Recovery,
+    /// Sent by hand (manual_order)
+    Manual,
+}
+
+impl OrderStrategy {
+    pub const ALL: [OrderStrategy; 6] = [
+        Self::Cross, Self::KalshiOnly, Self::Hedge, Self::Quote, Self::Recovery, Self::Manual,
+    ];
+
+    /// Strategy of an arb's entry legs
+    pub fn for_arb(arb_type: ArbType) -> Self {
+        match arb_type {
+            ArbType::KalshiOnly => Self::KalshiOnly,
+            _ => Self::Cross,
+        }
+    }
+
+    /// One-letter code carried in the id
+    pub fn code(&self) -> char {
+        match self {
+            Self::Cross => 'c',
+            Self::KalshiOnly => 'k',
+            Self::Hedge => 'h',
+            Self::Quote => 'q',
+            Self::Recovery => 'r',
+            Self::Manual => 'm',
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cross => "cross",
+            Self::KalshiOnly => "kalshi_only",
+            Self::Hedge => "hedge",
+            Self::Quote => "quote",
+            Self::Recovery => "recovery",
+            Self::Manual => "manual",
+        }
+    }
+
+    pub fn from_code(code: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.code() == code)
+    }
+}
+
+/// This process's session id: ORDER_SESSION_ID (alphanumerics, up to 8) or 6 random hex digits
+pub fn session_id() -> &'static str {
+    static SESSION: OnceLock<String> = OnceLock::new();
+    SESSION.get_or_init(|| {
+        std::env::var("ORDER_SESSION_ID").ok()
+            .map(|s| s.chars().filter(|c| c.is_ascii_alphanumeric()).take(MAX_SESSION_LEN).collect::<String>())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| format!("{:06x}", rand::random::<u32>() & 0xff_ffff))
+    })
+}
+
+/// Write `ab-<strategy code>-<session>-<seq>`, e.g. `ab-c-3f9a1c-17290000001234`
+/// (into a fixed buffer on the order hot path)
+pub fn write_id<W: std::fmt::Write>(out: &mut W, strategy: OrderStrategy, session: &str, seq: impl std::fmt::Display) -> std::fmt::Result {
+    write!(out, "{}-{}-{}-{}", PREFIX, strategy.code(), session, seq)
+}
+
+/// Strategy and session decoded from a client order id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderTag {
+    pub strategy: OrderStrategy,
+    pub session: String,
+    pub seq: String,
+}
+
+/// Decode a tagged client order id; None for untagged ids (older runs, other tools)
+pub fn parse(client_order_id: &str) -> Option<OrderTag> {
+    let mut parts = client_order_id.splitn(4, '-');
+    if parts.next()? != PREFIX {
+        return None;
+    }
+    let mut code = parts.next()?.chars();
+    let strategy = OrderStrategy::from_code(code.next()?).filter(|_| code.next().is_none())?;
+    let session = parts.next().filter(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric()))?;
+    let seq = parts.next().filter(|s| !s.is_empty())?;
+    Some(OrderTag { strategy, session: session.to_string(), seq: seq.to_string() })
+}
+
+/// Exchange-side orders of one strategy in one session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attribution {
+    /// Strategy name, or "untagged"
+    pub strategy: String,
+    pub session: String,
+    pub orders: usize,
+    pub contracts: i64,
+    pub cost_cents: i64,
+}
+
+/// Group venue orders (client order id, filled contracts, fill cost in cents) by tag
+pub fn attribute<'a>(orders: impl IntoIterator<Item = (Option<&'a str>, i64, i64)>) -> Vec<Attribution> {
+    let mut groups: BTreeMap<(String, String), Attribution> = BTreeMap::new();
+    for (client_order_id, contracts, cost_cents) in orders {
+        let (strategy, session) = match client_order_id.and_then(parse) {
+            Some(tag) => (tag.strategy.name().to_string(), tag.session),
+            None => ("untagged".to_string(), String::new()),
+        };
+        let entry = groups.entry((strategy.clone(), session.clone()))
+            .or_insert_with(|| Attribution { strategy, session, ..Default::default() });
+        entry.orders += 1;
+        entry.contracts += contracts;
+        entry.cost_cents += cost_cents;
+    }
+    groups.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(strategy: OrderStrategy, session: &str, seq: &str) -> String {
+        let mut id = String::new();
+        write_id(&mut id, strategy, session, seq).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for strategy in OrderStrategy::ALL {
+            let id = encode(strategy, "3f9a1c", "17290000001234");
+            assert!(id.len() <= 40);
+            assert_eq!(parse(&id), Some(OrderTag { strategy, session: "3f9a1c".into(), seq: "17290000001234".into() }));
+        }
+        assert_eq!(OrderStrategy::for_arb(ArbType::KalshiOnly), OrderStrategy::KalshiOnly);
+        assert_eq!(OrderStrategy::for_arb(ArbType::PolyYesKalshiNo), OrderStrategy::Cross);
+    }
+
+    #[test]
+    fn test_untagged_ids() {
+        // Pre-tagging ids and ids from other tools
+        assert_eq!(parse("a17290000001234"), None);
+        assert_eq!(parse("ab-x-3f9a1c-1"), None);
+        assert_eq!(parse("ab-cc-3f9a1c-1"), None);
+        assert_eq!(parse("ab-c--1"), None);
+        assert_eq!(parse("ab-c-3f9a1c-"), None);
+        assert_eq!(parse("9b2e6c1e-0d7a-4f5e-8c1b-2a9f3e4d5c6b"), None);
+    }
+
+    #[test]
+    fn test_attribution_by_strategy_and_session() {
+        let cross = encode(OrderStrategy::Cross, "s1", "1");
+        let cross2 = encode(OrderStrategy::Cross, "s1", "2");
+        let hedge = encode(OrderStrategy::Hedge, "s2", "3");
+        let groups = attribute([
+            (Some(cross.as_str()), 10, 450),
+            (Some(cross2.as_str()), 5, 230),
+            (Some(hedge.as_str()), 3, 120),
+            (Some("a17290000001234"), 1, 40),
+            (None, 2, 90),
+        ]);
+        let summary: Vec<(&str, &str, usize, i64, i64)> = groups.iter()
+            .map(|a| (a.strategy.as_str(), a.session.as_str(), a.orders, a.contracts, a.cost_cents))
+            .collect();
+        assert_eq!(summary, vec![
+            ("cross", "s1", 2, 15, 680),
+            ("hedge", "s2", 1, 3, 120),
+            ("untagged", "", 2, 3, 130),
+        ]);
+    }
+}
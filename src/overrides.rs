@@ -0,0 +1,196 @@
+// src/overrides.rs
+// Operator overrides - blacklisted assets / markets, per-market thresholds and pinned outcome tokens,
+// reloaded from a watched file while the bots run
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use crate::metrics;
+use crate::storage;
+
+/// Overrides configuration from environment
+#[derive(Debug, Clone)]
+pub struct OverridesConfig {
+    /// Overrides file (under DATA_DIR; missing = no overrides)
+    pub file: PathBuf,
+
+    /// Seconds between checks of the file for changes (0 = read once at startup)
+    pub poll_secs: u64,
+}
+
+impl OverridesConfig {
+    pub fn from_env() -> Self {
+        Self {
+            file: storage::data_path(std::env::var("OVERRIDES_FILE")
+                .unwrap_or_else(|_| "overrides.toml".to_string())),
+
+            poll_secs: std::env::var("OVERRIDES_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+        }
+    }
+}
+
+/// One `[markets."<slug>"]` table
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MarketOverride {
+    /// Trade when YES + NO asks sum below this, instead of the asset's threshold
+    #[serde(default)]
+    pub threshold: Option<f64>,
+
+    /// The token that is YES ("Up"); the pair is swapped if the scan had it as NO
+    #[serde(default)]
+    pub yes_token: Option<String>,
+}
+
+/// Contents of the overrides file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    /// Assets, slugs or Kalshi tickers never traded (case-insensitive)
+    #[serde(default)]
+    pub blacklist: Vec<String>,
+
+    /// Per-market overrides by Polymarket slug (or Kalshi ticker, for arb-bot thresholds)
+    #[serde(default)]
+    pub markets: HashMap<String, MarketOverride>,
+}
+
+impl Overrides {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let overrides: Self = toml::from_str(contents)?;
+        for (slug, o) in &overrides.markets {
+            if let Some(t) = o.threshold.filter(|t| !(*t > 0.0 && *t <= 1.0)) {
+                return Err(anyhow!("[markets.{:?}] threshold {} must be in (0, 1]", slug, t));
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Missing file = no overrides
+    pub fn load(config: &OverridesConfig) -> Result<Self> {
+        match std::fs::read_to_string(&config.file) {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("invalid overrides in {}", config.file.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", config.file.display())),
+        }
+    }
+
+    /// Whether any of `ids` (asset, slug, ticker) is blacklisted
+    pub fn is_blacklisted(&self, ids: &[&str]) -> bool {
+        self.blacklist.iter().any(|b| ids.iter().any(|id| b.eq_ignore_ascii_case(id)))
+    }
+
+    pub fn threshold(&self, slug: &str) -> Option<f64> {
+        self.markets.get(slug)?.threshold
+    }
+
+    /// Token pinned as YES for `slug`
+    pub fn pinned_yes(&self, slug: &str) -> Option<&str> {
+        self.markets.get(slug)?.yes_token.as_deref()
+    }
+
+    fn log(&self) {
+        if !self.blacklist.is_empty() {
+            info!("[OVERRIDE] Blacklist: {}", self.blacklist.join(", "));
+        }
+        let mut slugs: Vec<&String> = self.markets.keys().collect();
+        slugs.sort();
+        for slug in slugs {
+            let o = &self.markets[slug];
+            info!("[OVERRIDE] {}: threshold {} | YES token {}", slug,
+                  o.threshold.map(|t| format!("<{:.1}¢", t * 100.0)).unwrap_or_else(|| "(asset)".into()),
+                  o.yes_token.as_deref().unwrap_or("(scanned)"));
+        }
+    }
+}
+
+fn global() -> &'static RwLock<Arc<Overrides>> {
+    static CURRENT: OnceLock<RwLock<Arc<Overrides>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(Overrides::default())))
+}
+
+/// Overrides in effect (none until `start`)
+pub fn current() -> Arc<Overrides> {
+    global().read().unwrap().clone()
+}
+
+fn modified(config: &OverridesConfig) -> Option<SystemTime> {
+    std::fs::metadata(&config.file).and_then(|m| m.modified()).ok()
+}
+
+/// Load the overrides (an invalid file is a startup error) and reload them whenever the file
+/// changes. A reload that doesn't parse keeps the previous overrides.
+pub fn start(config: OverridesConfig) -> Result<()> {
+    let overrides = Overrides::load(&config)?;
+    overrides.log();
+    *global().write().unwrap() = Arc::new(overrides);
+    if config.poll_secs == 0 {
+        return Ok(());
+    }
+    info!("[OVERRIDE] Watching {} every {}s", config.file.display(), config.poll_secs);
+    tokio::spawn(async move {
+        let mut seen = modified(&config);
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.poll_secs));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let now = modified(&config);
+            if now == seen {
+                continue;
+            }
+            seen = now;
+            match Overrides::load(&config) {
+                Ok(overrides) if overrides == *current() => {}
+                Ok(overrides) => {
+                    info!("[OVERRIDE] 🔄 Reloaded {}", config.file.display());
+                    overrides.log();
+                    *global().write().unwrap() = Arc::new(overrides);
+                    metrics::incr("overrides.reloaded");
+                }
+                Err(e) => {
+                    warn!("[OVERRIDE] ⚠️ {:#} - keeping the previous overrides", e);
+                    metrics::incr("overrides.invalid");
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides() {
+        let o = Overrides::parse(r#"
+            blacklist = ["xrp", "KXNBAGAME-25DEC19LALBOS"]
+
+            [markets."btc-updown-15m-1766100600"]
+            threshold = 0.98
+            yes_token = "222"
+        "#).unwrap();
+        assert!(o.is_blacklisted(&["XRP", "xrp-updown-15m-1766100600"]));
+        assert!(o.is_blacklisted(&["nba", "kxnbagame-25dec19lalbos"]));
+        assert!(!o.is_blacklisted(&["btc", "btc-updown-15m-1766100600"]));
+        assert_eq!(o.threshold("btc-updown-15m-1766100600"), Some(0.98));
+        assert_eq!(o.threshold("eth-updown-15m-1766100600"), None);
+        assert_eq!(o.pinned_yes("btc-updown-15m-1766100600"), Some("222"));
+        assert_eq!(Overrides::parse("").unwrap(), Overrides::default());
+    }
+
+    #[test]
+    fn test_invalid_overrides_refused() {
+        assert!(Overrides::parse("[markets.\"m\"]\nthreshold = 1.5").is_err());
+        assert!(Overrides::parse("[markets.\"m\"]\nthresold = 0.9").is_err());
+        assert!(Overrides::parse("blacklist = \"btc\"").is_err());
+    }
+}
@@ -0,0 +1,171 @@
+// src/own_orders.rs
+// Self-trade prevention - our own resting orders per instrument level, excluded from the depth we trade against
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::metrics;
+
+/// Book side a resting order shows up on in the venue feed: Kalshi publishes
+/// bid ladders per outcome ("yes" / "no"), Polymarket asks per token
+pub const POLY_ASK: &str = "ask";
+
+type Level = (String, &'static str, i64);
+
+#[derive(Default)]
+struct Registry {
+    /// Resting contracts per order (keyed by exchange order id, or client order id until
+    /// the venue confirms it)
+    orders: HashMap<String, (Level, i64)>,
+    levels: HashMap<Level, i64>,
+}
+
+impl Registry {
+    fn set(&mut self, order: &str, level: Level, count: i64) {
+        self.clear(order);
+        if count > 0 {
+            *self.levels.entry(level.clone()).or_insert(0) += count;
+            self.orders.insert(order.to_string(), (level, count));
+        }
+    }
+
+    fn clear(&mut self, order: &str) -> Option<(Level, i64)> {
+        let (level, count) = self.orders.remove(order)?;
+        if let Some(resting) = self.levels.get_mut(&level) {
+            *resting -= count;
+            if *resting <= 0 {
+                self.levels.remove(&level);
+            }
+        }
+        Some((level, count))
+    }
+}
+
+/// Resting contracts by (instrument, book side, price in cents), registered per order by
+/// the venue clients as orders are placed and dropped as they fill or are cancelled
+pub struct OwnOrders {
+    registry: Mutex<Registry>,
+    /// Number of levels, read without the lock so feeds skip the lookup while nothing rests
+    active: AtomicUsize,
+}
+
+impl OwnOrders {
+    fn new() -> Self {
+        Self { registry: Mutex::new(Registry::default()), active: AtomicUsize::new(0) }
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut Registry) -> R) -> R {
+        let mut registry = self.registry.lock().unwrap();
+        let out = f(&mut registry);
+        self.active.store(registry.levels.len(), Ordering::Relaxed);
+        out
+    }
+
+    /// Record `order` resting `count` contracts at `price_cents` (replacing what it rested before)
+    pub fn track(&self, order: &str, instrument: &str, side: &'static str, price_cents: i64, count: i64) {
+        self.update(|r| r.set(order, (instrument.to_string(), side, price_cents), count));
+    }
+
+    /// The venue confirmed `from` (a client order id) as `to`
+    pub fn rekey(&self, from: &str, to: &str) {
+        self.update(|r| {
+            if let Some((level, count)) = r.clear(from) {
+                r.set(to, level, count);
+            }
+        });
+    }
+
+    /// `order` now rests `remaining` contracts (0 = filled)
+    pub fn set_remaining(&self, order: &str, remaining: i64) {
+        self.update(|r| {
+            if let Some((level, _)) = r.orders.get(order).cloned() {
+                r.set(order, level, remaining);
+            }
+        });
+    }
+
+    /// `count` contracts of `order` filled
+    pub fn fill(&self, order: &str, count: i64) {
+        self.update(|r| {
+            if let Some((level, resting)) = r.orders.get(order).cloned() {
+                r.set(order, level, resting - count);
+            }
+        });
+    }
+
+    /// Forget `order` (cancelled, filled or never placed)
+    pub fn untrack(&self, order: &str) {
+        self.update(|r| r.clear(order));
+    }
+
+    pub fn resting(&self, instrument: &str, side: &'static str, price_cents: i64) -> i64 {
+        if self.active.load(Ordering::Relaxed) == 0 {
+            return 0;
+        }
+        self.registry.lock().unwrap().levels
+            .get(&(instrument.to_string(), side, price_cents))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Quantity at a book level that isn't ours: what a taker order of ours can actually hit.
+    /// `qty` is in the feed's units, `per_contract` of them to a contract.
+    pub fn excluding_own(&self, instrument: &str, side: &'static str, price_cents: i64, qty: i64, per_contract: i64) -> i64 {
+        let own = self.resting(instrument, side, price_cents) * per_contract;
+        if own > 0 {
+            metrics::incr("book.own_depth_excluded");
+        }
+        (qty - own).max(0)
+    }
+}
+
+/// Process-wide registry, fed by the venue clients and read by the book feeds
+pub fn own_orders() -> &'static OwnOrders {
+    static OWN: OnceLock<OwnOrders> = OnceLock::new();
+    OWN.get_or_init(OwnOrders::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_quantity_excluded_from_level() {
+        let own = OwnOrders::new();
+        assert_eq!(own.excluding_own("KXT-1", "yes", 40, 25, 1), 25);
+        own.track("o1", "KXT-1", "yes", 40, 10);
+        assert_eq!(own.excluding_own("KXT-1", "yes", 40, 25, 1), 15);
+        // Other side, price or instrument untouched
+        assert_eq!(own.excluding_own("KXT-1", "no", 40, 25, 1), 25);
+        assert_eq!(own.excluding_own("KXT-1", "yes", 41, 25, 1), 25);
+        assert_eq!(own.excluding_own("KXT-2", "yes", 40, 25, 1), 25);
+        // A level that is only us is empty
+        assert_eq!(own.excluding_own("KXT-1", "yes", 40, 10, 1), 0);
+    }
+
+    #[test]
+    fn test_orders_leave_on_fill_or_cancel() {
+        let own = OwnOrders::new();
+        own.track("client-1", "tok", POLY_ASK, 55, 10);
+        own.track("o2", "tok", POLY_ASK, 55, 5);
+        // Polymarket sizes are in hundredths of a share
+        assert_eq!(own.excluding_own("tok", POLY_ASK, 55, 2_050, 100), 550);
+        // Confirmed under its exchange id, then partly and fully filled
+        own.rekey("client-1", "o1");
+        own.fill("o1", 4);
+        assert_eq!(own.resting("tok", POLY_ASK, 55), 11);
+        own.set_remaining("o1", 0);
+        assert_eq!(own.resting("tok", POLY_ASK, 55), 5);
+        // Registering the same order twice doesn't double it
+        own.track("o2", "tok", POLY_ASK, 55, 5);
+        assert_eq!(own.resting("tok", POLY_ASK, 55), 5);
+        own.untrack("o2");
+        assert_eq!((own.resting("tok", POLY_ASK, 55), own.active.load(Ordering::Relaxed)), (0, 0));
+        // Unknown orders are harmless
+        own.untrack("o2");
+        own.fill("o9", 3);
+        own.track("o3", "tok", POLY_ASK, 55, 0);
+        assert_eq!(own.active.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,185 @@
+// src/pnl_series.rs
+// Periodic P&L / exposure snapshots appended to a CSV series (equity curve, drawdowns)
+
+use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::position_tracker::PositionTracker;
+use crate::runtimes;
+
+const HEADER: &str = "timestamp,equity,realized_pnl,daily_pnl,locked_profit,open_cost,unmatched_exposure,open_positions";
+
+/// One point of the series
+#[derive(Debug, Clone, PartialEq)]
+pub struct PnlSnapshot {
+    pub timestamp: String,
+    /// All-time realized P&L plus profit locked in by matched open positions
+    pub equity: f64,
+    pub realized_pnl: f64,
+    pub daily_pnl: f64,
+    pub locked_profit: f64,
+    /// Cost basis of open positions
+    pub open_cost: f64,
+    pub unmatched_exposure: f64,
+    pub open_positions: usize,
+}
+
+impl PnlSnapshot {
+    pub fn from_tracker(tracker: &PositionTracker, timestamp: String) -> Self {
+        let summary = tracker.summary();
+        Self {
+            timestamp,
+            equity: tracker.all_time_pnl + summary.total_guaranteed_profit,
+            realized_pnl: tracker.all_time_pnl,
+            daily_pnl: tracker.daily_pnl(),
+            locked_profit: summary.total_guaranteed_profit,
+            open_cost: summary.total_cost_basis,
+            unmatched_exposure: summary.total_unmatched_exposure,
+            open_positions: summary.open_positions,
+        }
+    }
+
+    fn to_row(&self) -> String {
+        format!("{},{:.4},{:.4},{:.4},{:.4},{:.4},{:.4},{}",
+                self.timestamp, self.equity, self.realized_pnl, self.daily_pnl,
+                self.locked_profit, self.open_cost, self.unmatched_exposure, self.open_positions)
+    }
+
+    fn from_row(row: &str) -> Result<Self> {
+        let f: Vec<&str> = row.split(',').collect();
+        if f.len() != 8 {
+            return Err(anyhow!("expected 8 columns, got {}", f.len()));
+        }
+        let num = |i: usize| f[i].parse::<f64>().map_err(|e| anyhow!("column {}: {}", i, e));
+        Ok(Self {
+            timestamp: f[0].to_string(),
+            equity: num(1)?,
+            realized_pnl: num(2)?,
+            daily_pnl: num(3)?,
+            locked_profit: num(4)?,
+            open_cost: num(5)?,
+            unmatched_exposure: num(6)?,
+            open_positions: f[7].parse()?,
+        })
+    }
+}
+
+/// Append-only CSV series
+pub struct PnlSeries {
+    path: PathBuf,
+}
+
+impl PnlSeries {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn append(&self, snapshot: &PnlSnapshot) -> Result<()> {
+        let new_file = !self.path.exists();
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        if new_file {
+            writeln!(file, "{}", HEADER)?;
+        }
+        writeln!(file, "{}", snapshot.to_row())?;
+        Ok(())
+    }
+
+    /// All snapshots, skipping rows that don't parse (e.g. a torn last line)
+    #[allow(dead_code)]
+    pub fn load(&self) -> Result<Vec<PnlSnapshot>> {
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ok(contents.lines()
+            .filter(|l| !l.is_empty() && *l != HEADER)
+            .filter_map(|l| PnlSnapshot::from_row(l).ok())
+            .collect())
+    }
+}
+
+/// Largest peak-to-trough equity decline: (drawdown, peak timestamp, trough timestamp)
+#[allow(dead_code)]
+pub fn max_drawdown(series: &[PnlSnapshot]) -> Option<(f64, &str, &str)> {
+    let mut peak = series.first()?;
+    let mut worst: Option<(f64, &str, &str)> = None;
+    for snap in series {
+        if snap.equity > peak.equity {
+            peak = snap;
+        }
+        let dd = peak.equity - snap.equity;
+        if dd > worst.map(|w| w.0).unwrap_or(0.0) {
+            worst = Some((dd, &peak.timestamp, &snap.timestamp));
+        }
+    }
+    Some(worst.unwrap_or((0.0, &series[0].timestamp, &series[0].timestamp)))
+}
+
+/// Seconds between snapshots (PNL_SNAPSHOT_SECS, 0 = off)
+pub fn snapshot_secs() -> u64 {
+    std::env::var("PNL_SNAPSHOT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60)
+}
+
+/// Snapshot the tracker into `path` every PNL_SNAPSHOT_SECS
+pub fn spawn_recorder(tracker: Arc<RwLock<PositionTracker>>, path: PathBuf) {
+    let secs = snapshot_secs();
+    if secs == 0 {
+        return;
+    }
+    let series = PnlSeries::new(path);
+    runtimes::spawn_bookkeeping(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(secs));
+        loop {
+            ticker.tick().await;
+            let snapshot = {
+                let guard = tracker.read().await;
+                if guard.is_standby() {
+                    continue;
+                }
+                PnlSnapshot::from_tracker(&guard, chrono::Utc::now().to_rfc3339())
+            };
+            if let Err(e) = series.append(&snapshot) {
+                warn!("[PNL] Failed to append snapshot to {:?}: {}", series.path, e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(ts: &str, equity: f64) -> PnlSnapshot {
+        PnlSnapshot {
+            timestamp: ts.into(),
+            equity,
+            realized_pnl: equity,
+            daily_pnl: 0.0,
+            locked_profit: 0.0,
+            open_cost: 12.5,
+            unmatched_exposure: 0.0,
+            open_positions: 2,
+        }
+    }
+
+    #[test]
+    fn test_row_round_trip() {
+        let s = snap("2025-12-18T20:00:00+00:00", 4.25);
+        assert_eq!(PnlSnapshot::from_row(&s.to_row()).unwrap(), s);
+        assert!(PnlSnapshot::from_row("2025-12-18,1.0").is_err());
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        let series = vec![snap("t0", 1.0), snap("t1", 5.0), snap("t2", 2.0), snap("t3", 6.0), snap("t4", 4.5)];
+        let (dd, peak, trough) = max_drawdown(&series).unwrap();
+        assert!((dd - 3.0).abs() < 1e-9);
+        assert_eq!((peak, trough), ("t1", "t2"));
+        assert!(max_drawdown(&[]).is_none());
+    }
+}
@@ -3,64 +3,42 @@
 
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
+use crate::book::{BookSide, LocalBook};
 use crate::config::{POLYMARKET_WS_URL, POLY_PING_INTERVAL_SECS, GAMMA_API_BASE};
 use crate::execution::NanoClock;
+use crate::feed_liveness::{FeedLiveness, Liveness, LivenessConfig};
+use crate::metrics;
+use crate::own_orders::{own_orders, POLY_ASK};
+use crate::polymarket_ws_types::{BookSnapshot, LastTradeEvent, MarketOperation, MarketSubscribe, PriceChangeEvent, PriceChangeItem};
+use crate::trade_tape;
+use crate::schema_watch::{self, Outcome};
 use crate::types::{
     GlobalState, FastExecutionRequest, ArbType, PriceCents, SizeCents,
     parse_price, fxhash_str,
 };
 
-// === WebSocket Message Types ===
-
-#[derive(Deserialize, Debug)]
-pub struct BookSnapshot {
-    pub asset_id: String,
-    #[allow(dead_code)]
-    pub bids: Vec<PriceLevel>,
-    pub asks: Vec<PriceLevel>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct PriceLevel {
-    pub price: String,
-    pub size: String,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct PriceChangeEvent {
-    pub event_type: Option<String>,
-    #[serde(default)]
-    pub price_changes: Option<Vec<PriceChangeItem>>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct PriceChangeItem {
-    pub asset_id: String,
-    pub price: Option<String>,
-    pub side: Option<String>,
-}
-
-#[derive(Serialize)]
-struct SubscribeCmd {
-    assets_ids: Vec<String>,
-    #[serde(rename = "type")]
-    sub_type: &'static str,
-}
-
 // === Gamma API Client ===
 
 pub struct GammaClient {
     http: reqwest::Client,
 }
 
+impl Default for GammaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GammaClient {
     pub fn new() -> Self {
         Self {
@@ -71,9 +49,9 @@ impl GammaClient {
         }
     }
     
-    /// Look up Polymarket market by slug, return (yes_token, no_token)
+    /// Look up Polymarket market by slug, return its tokens and settlement details
     /// Tries both the exact date and next day (timezone handling)
-    pub async fn lookup_market(&self, slug: &str) -> Result<Option<(String, String)>> {
+    pub async fn lookup_market(&self, slug: &str) -> Result<Option<GammaListing>> {
         // Try exact slug first
         if let Some(tokens) = self.try_lookup_slug(slug).await? {
             return Ok(Some(tokens));
@@ -90,7 +68,7 @@ impl GammaClient {
         Ok(None)
     }
     
-    async fn try_lookup_slug(&self, slug: &str) -> Result<Option<(String, String)>> {
+    async fn try_lookup_slug(&self, slug: &str) -> Result<Option<GammaListing>> {
         let url = format!("{}/markets?slug={}", GAMMA_API_BASE, slug);
         
         let resp = self.http.get(&url).send().await?;
@@ -119,7 +97,14 @@ impl GammaClient {
             .unwrap_or_default();
         
         if token_ids.len() >= 2 {
-            Ok(Some((token_ids[0].clone(), token_ids[1].clone())))
+            Ok(Some(GammaListing {
+                yes_token: token_ids[0].clone(),
+                no_token: token_ids[1].clone(),
+                ends_at: market.end_date.as_deref()
+                    .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                    .map(|t| t.timestamp()),
+                resolution_source: market.resolution_source.clone().filter(|s| !s.is_empty()),
+            }))
         } else {
             Ok(None)
         }
@@ -132,6 +117,21 @@ struct GammaMarket {
     clob_token_ids: Option<String>,
     active: Option<bool>,
     closed: Option<bool>,
+    #[serde(rename = "endDate", default)]
+    end_date: Option<String>,
+    #[serde(rename = "resolutionSource", default)]
+    resolution_source: Option<String>,
+}
+
+/// A Polymarket market found by slug
+#[derive(Debug, Clone, PartialEq)]
+pub struct GammaListing {
+    pub yes_token: String,
+    pub no_token: String,
+    /// Unix seconds of the market's end date
+    pub ends_at: Option<i64>,
+    /// URL / name the market resolves against (often blank for sports)
+    pub resolution_source: Option<String>,
 }
 
 /// Increment the date in a Polymarket slug by 1 day
@@ -171,15 +171,6 @@ fn increment_date_in_slug(slug: &str) -> Option<String> {
 // WebSocket Runner
 // =============================================================================
 
-/// Parse size from Polymarket (format: "123.45" dollars)
-#[inline(always)]
-fn parse_size(s: &str) -> SizeCents {
-    // Parse as f64 and convert to cents
-    s.parse::<f64>()
-        .map(|size| (size * 100.0).round() as SizeCents)
-        .unwrap_or(0)
-}
-
 /// WebSocket runner
 pub async fn run_ws(
     state: Arc<GlobalState>,
@@ -207,17 +198,18 @@ pub async fn run_ws(
     let (mut write, mut read) = ws_stream.split();
 
     // Subscribe
-    let subscribe_msg = SubscribeCmd {
-        assets_ids: tokens.clone(),
-        sub_type: "market",
-    };
+    let subscribe_msg = MarketSubscribe::new(tokens.clone());
 
     write.send(Message::Text(serde_json::to_string(&subscribe_msg)?)).await?;
     info!("[POLY] Subscribed to {} tokens", tokens.len());
 
     let clock = NanoClock::new();
     let mut ping_interval = interval(Duration::from_secs(POLY_PING_INTERVAL_SECS));
-    let mut last_message = Instant::now();
+    let mut liveness = FeedLiveness::new(LivenessConfig::from_env(), "ws.polymarket", Instant::now());
+    liveness.set_tokens(&tokens, Instant::now());
+    let messages = metrics::counter("ws.polymarket.messages");
+    // Ask ladders per token, so the best ask can be recomputed when a level is pulled
+    let mut local_books: HashMap<String, LocalBook> = HashMap::new();
 
     loop {
         tokio::select! {
@@ -226,40 +218,82 @@ pub async fn run_ws(
                     error!("[POLY] Failed to send ping: {}", e);
                     break;
                 }
+
+                // Pongs keep the transport alive; books going quiet gets snapshots re-requested
+                match liveness.check(Instant::now()) {
+                    Liveness::Alive => {}
+                    Liveness::Refresh(idle) => {
+                        info!("[POLY] 🔄 Re-requesting {} quiet snapshot(s) (last market data {}s ago)",
+                              idle.len(), liveness.data_age(Instant::now()).as_secs());
+                        let msg = MarketOperation { assets_ids: idle, operation: "subscribe".into() };
+                        write.send(Message::Text(serde_json::to_string(&msg)?)).await?;
+                    }
+                    Liveness::Reconnect(reason) => {
+                        warn!("[POLY] Stale connection ({}), reconnecting...", reason);
+                        break;
+                    }
+                }
             }
 
             msg = read.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        last_message = Instant::now();
+                        liveness.on_transport(Instant::now());
+                        messages.fetch_add(1, Ordering::Relaxed);
 
                         // Try book snapshot first
                         if let Ok(books) = serde_json::from_str::<Vec<BookSnapshot>>(&text) {
                             for book in &books {
-                                process_book(&state, book, &exec_tx, threshold_cents, &clock).await;
+                                schema_watch::monitor().observe("polymarket", "book",
+                                    Outcome::Parsed { populated: book.is_well_formed() });
+                                liveness.on_data(&book.asset_id, Instant::now());
+                                let local = local_books.entry(book.asset_id.clone()).or_default();
+                                process_book(&state, book, local, &exec_tx, threshold_cents, &clock).await;
                             }
                         }
                         // Try price change event
                         else if let Ok(event) = serde_json::from_str::<PriceChangeEvent>(&text) {
-                            if event.event_type.as_deref() == Some("price_change") {
-                                if let Some(changes) = &event.price_changes {
-                                    for change in changes {
-                                        process_price_change(&state, change, &exec_tx, threshold_cents, &clock).await;
+                            match event.event_type.as_deref() {
+                                Some("price_change") => {
+                                    let populated = event.price_changes.as_ref().is_some_and(|changes| {
+                                        !changes.is_empty() && changes.iter().all(|c| c.price.as_deref().is_some_and(|p| parse_price(p) > 0))
+                                    });
+                                    schema_watch::monitor().observe("polymarket", "price_change", Outcome::Parsed { populated });
+                                    if let Some(changes) = &event.price_changes {
+                                        for change in changes {
+                                            liveness.on_data(&change.asset_id, Instant::now());
+                                            let local = local_books.entry(change.asset_id.clone()).or_default();
+                                            process_price_change(&state, change, local, &exec_tx, threshold_cents, &clock).await;
+                                        }
                                     }
                                 }
+                                Some("last_trade_price") => {
+                                    let outcome = match serde_json::from_str::<LastTradeEvent>(&text) {
+                                        Ok(trade) => {
+                                            liveness.on_data(&trade.asset_id, Instant::now());
+                                            let populated = trade.to_print().is_some();
+                                            process_last_trade(&state, &trade);
+                                            Outcome::Parsed { populated }
+                                        }
+                                        Err(_) => Outcome::Unparsed,
+                                    };
+                                    schema_watch::monitor().observe("polymarket", "last_trade_price", outcome);
+                                }
+                                _ => {}
                             }
                         }
                         // Log unknown message types at trace level for debugging
                         else {
+                            schema_watch::observe_unparsed("polymarket", &text, &["book", "price_change", "last_trade_price"]);
                             tracing::trace!("[POLY] Unknown WS message: {}...", &text[..text.len().min(100)]);
                         }
                     }
                     Some(Ok(Message::Ping(data))) => {
                         let _ = write.send(Message::Pong(data)).await;
-                        last_message = Instant::now();
+                        liveness.on_transport(Instant::now());
                     }
                     Some(Ok(Message::Pong(_))) => {
-                        last_message = Instant::now();
+                        liveness.on_transport(Instant::now());
                     }
                     Some(Ok(Message::Close(frame))) => {
                         warn!("[POLY] Server closed: {:?}", frame);
@@ -277,36 +311,40 @@ pub async fn run_ws(
                 }
             }
         }
-
-        if last_message.elapsed() > Duration::from_secs(120) {
-            warn!("[POLY] Stale connection, reconnecting...");
-            break;
-        }
     }
 
     Ok(())
 }
 
+/// Best ask of a token's book (price, size in cents), net of our own resting sells
+/// (self-trade prevention); (0, 0) when nothing else is offered
+fn best_ask(asset_id: &str, book: &LocalBook) -> (PriceCents, SizeCents) {
+    let own = own_orders();
+    book.asks()
+        .filter_map(|(price, size)| {
+            let price = (price * 100.0).round() as PriceCents;
+            let size = (size * 100.0).round() as i64;
+            let size = own.excluding_own(asset_id, POLY_ASK, price as i64, size, 100) as SizeCents;
+            if price > 0 && size > 0 { Some((price, size)) } else { None }
+        })
+        .next()
+        .unwrap_or((0, 0))
+}
+
 /// Process book snapshot
 #[inline]
 async fn process_book(
     state: &GlobalState,
     book: &BookSnapshot,
+    local: &mut LocalBook,
     exec_tx: &mpsc::Sender<FastExecutionRequest>,
     threshold_cents: PriceCents,
     clock: &NanoClock,
 ) {
     let token_hash = fxhash_str(&book.asset_id);
 
-    // Find best ask (lowest price)
-    let (best_ask, ask_size) = book.asks.iter()
-        .filter_map(|l| {
-            let price = parse_price(&l.price);
-            let size = parse_size(&l.size);
-            if price > 0 { Some((price, size)) } else { None }
-        })
-        .min_by_key(|(p, _)| *p)
-        .unwrap_or((0, 0));
+    local.apply_snapshot(book);
+    let (best_ask, ask_size) = best_ask(&book.asset_id, local);
 
     // Check if YES token
     if let Some(&market_id) = state.poly_yes_to_id.get(&token_hash) {
@@ -314,8 +352,8 @@ async fn process_book(
         market.poly.update_yes(best_ask, ask_size);
 
         // Check arbs
-        let arb_mask = market.check_arbs(threshold_cents);
-        if arb_mask != 0 {
+        let arb_mask = market.check_arbs(market.threshold_cents(threshold_cents));
+        if market.persistence.observe(arb_mask != 0) {
             send_arb_request(market_id, market, arb_mask, exec_tx, clock).await;
         }
     }
@@ -325,30 +363,59 @@ async fn process_book(
         market.poly.update_no(best_ask, ask_size);
 
         // Check arbs
-        let arb_mask = market.check_arbs(threshold_cents);
-        if arb_mask != 0 {
+        let arb_mask = market.check_arbs(market.threshold_cents(threshold_cents));
+        if market.persistence.observe(arb_mask != 0) {
             send_arb_request(market_id, market, arb_mask, exec_tx, clock).await;
         }
     }
 }
 
-/// Process price change
+/// Process a last-trade print: record it on the tape and flag prints through our cached ask
+fn process_last_trade(state: &GlobalState, trade: &LastTradeEvent) {
+    let Some(print) = trade.to_print() else { return };
+    trade_tape::tape().record(&trade.asset_id, print);
+    metrics::incr("poly.trades");
+
+    let token_hash = fxhash_str(&trade.asset_id);
+    let (market_id, is_yes) = if let Some(&id) = state.poly_yes_to_id.get(&token_hash) {
+        (id, true)
+    } else if let Some(&id) = state.poly_no_to_id.get(&token_hash) {
+        (id, false)
+    } else {
+        return;
+    };
+
+    // Only asks are cached for Polymarket, so only buy prints can be checked
+    let (yes_ask, no_ask, _, _) = state.markets[market_id as usize].poly.load();
+    let ask_cents = if is_yes { yes_ask } else { no_ask };
+    let best_ask = (ask_cents > 0).then(|| ask_cents as f64 / 100.0);
+    if trade_tape::crosses_quote(&print, None, best_ask) {
+        metrics::incr("poly.trades.crossed_quote");
+        debug!("[POLY] Trade {:.3} x {:.1} printed through cached ask {:.2} ({})",
+               print.price, print.size, best_ask.unwrap_or_default(), trade.asset_id);
+    }
+}
+
+/// Process price change: apply the level to the token's book and re-derive its best ask,
+/// which may have moved up (the best level pulled or taken) as well as down
 #[inline]
 async fn process_price_change(
     state: &GlobalState,
     change: &PriceChangeItem,
+    local: &mut LocalBook,
     exec_tx: &mpsc::Sender<FastExecutionRequest>,
     threshold_cents: PriceCents,
     clock: &NanoClock,
 ) {
     // Only process ASK side updates
-    if !matches!(change.side.as_deref(), Some("ASK" | "ask")) {
+    if !matches!(change.side.as_deref().and_then(BookSide::parse), Some(BookSide::Ask)) {
         return;
     }
+    let (Some(price), Some(size)) = (change.price.as_deref(), change.size.as_deref()) else { return };
+    if parse_price(price) == 0 { return; }
 
-    let Some(price_str) = &change.price else { return };
-    let price = parse_price(price_str);
-    if price == 0 { return; }
+    local.apply_level(BookSide::Ask, price, size);
+    let (best_ask, ask_size) = best_ask(&change.asset_id, local);
 
     let token_hash = fxhash_str(&change.asset_id);
 
@@ -356,31 +423,28 @@ async fn process_price_change(
     if let Some(&market_id) = state.poly_yes_to_id.get(&token_hash) {
         let market = &state.markets[market_id as usize];
         let (current_yes, _, current_yes_size, _) = market.poly.load();
+        if (best_ask, ask_size) == (current_yes, current_yes_size) {
+            return;
+        }
+        market.poly.update_yes(best_ask, ask_size);
 
-        // Only update if new price is better (lower)
-        if price < current_yes || current_yes == 0 {
-            // Keep existing size - it may be stale but FAK orders handle partial fills.
-            // Size is an upper bound anyway; better to attempt arb than miss it.
-            market.poly.update_yes(price, current_yes_size);
-
-            let arb_mask = market.check_arbs(threshold_cents);
-            if arb_mask != 0 {
-                send_arb_request(market_id, market, arb_mask, exec_tx, clock).await;
-            }
+        let arb_mask = market.check_arbs(market.threshold_cents(threshold_cents));
+        if market.persistence.observe(arb_mask != 0) {
+            send_arb_request(market_id, market, arb_mask, exec_tx, clock).await;
         }
     }
     // Check NO token
     else if let Some(&market_id) = state.poly_no_to_id.get(&token_hash) {
         let market = &state.markets[market_id as usize];
         let (_, current_no, _, current_no_size) = market.poly.load();
+        if (best_ask, ask_size) == (current_no, current_no_size) {
+            return;
+        }
+        market.poly.update_no(best_ask, ask_size);
 
-        if price < current_no || current_no == 0 {
-            market.poly.update_no(price, current_no_size);
-
-            let arb_mask = market.check_arbs(threshold_cents);
-            if arb_mask != 0 {
-                send_arb_request(market_id, market, arb_mask, exec_tx, clock).await;
-            }
+        let arb_mask = market.check_arbs(market.threshold_cents(threshold_cents));
+        if market.persistence.observe(arb_mask != 0) {
+            send_arb_request(market_id, market, arb_mask, exec_tx, clock).await;
         }
     }
 }
@@ -422,6 +486,7 @@ async fn send_arb_request(
         no_size,
         arb_type,
         detected_ns: clock.now_ns(),
+        poly_fee_bps: market.poly_fee_bps(),
     };
 
     // send! ~~ 
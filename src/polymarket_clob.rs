@@ -1,7 +1,7 @@
 // src/polymarket_clob.rs
 // Polymarket CLOB Client
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Result, anyhow};
 use base64::Engine;
@@ -18,6 +18,12 @@ use sha2::Sha256;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::clock_skew;
+use crate::fill_detail::FillDetail;
+use crate::order_errors::{round_to_tick, OrderRejected, RejectReason};
+use crate::polymarket_ws_types::BookSnapshot;
+use crate::units;
+
 const USER_AGENT: &str = "py_clob_client";
 const MSG_TO_SIGN: &str = "This message attests that I control the given wallet";
 const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
@@ -103,11 +109,6 @@ fn add_default_headers(headers: &mut HeaderMap) {
     headers.insert("Content-Type", HeaderValue::from_static("application/json"));
 }
 
-#[inline(always)]
-fn current_unix_ts() -> u64 {
-    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
-}
-
 fn clob_auth_digest(chain_id: u64, address_str: &str, timestamp: u64, nonce: u64) -> Result<H256> {
     let typed_json = json!({
         "types": {
@@ -228,7 +229,7 @@ impl SignedOrder {
 
 #[inline(always)]
 fn generate_seed() -> u128 {
-    (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() % u128::from(u32::MAX)) as u128
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() % u128::from(u32::MAX)
 }
 
 // ============================================================================
@@ -236,48 +237,52 @@ fn generate_seed() -> u128 {
 // ============================================================================
 
 /// Convert f64 price (0.0-1.0) to basis points (0-10000)
-/// e.g., 0.65 -> 6500
+/// e.g., 0.65 -> 6500. Non-finite or out-of-range prices are rejected.
 #[inline(always)]
-pub fn price_to_bps(price: f64) -> u64 {
-    ((price * 10000.0).round() as i64).max(0) as u64
+pub fn price_to_bps(price: f64) -> Result<u64> {
+    units::price_to_bps(price)
 }
 
 /// Convert f64 size to micro-units (6 decimal places)
-/// e.g., 100.5 -> 100_500_000
+/// e.g., 100.5 -> 100_500_000. Non-finite, negative or oversized sizes are rejected.
 #[inline(always)]
-pub fn size_to_micro(size: f64) -> u64 {
-    ((size * 1_000_000.0).floor() as i64).max(0) as u64
+pub fn size_to_micro(size: f64) -> Result<u64> {
+    units::shares_to_micro(size)
 }
 
 /// BUY order calculation
 /// Input: size in micro-units, price in basis points
 /// Output: (side=0, maker_amount, taker_amount) in token decimals (6 dp)
 #[inline(always)]
-pub fn get_order_amounts_buy(size_micro: u64, price_bps: u64) -> (i32, u128, u128) {
+pub fn get_order_amounts_buy(size_micro: u64, price_bps: u64) -> Result<(i32, u128, u128)> {
     // For BUY: taker = size (what we receive), maker = size * price (what we pay)
     let taker = size_micro as u128;
     // maker = size * price / 10000 (convert bps to ratio)
-    let maker = (size_micro as u128 * price_bps as u128) / 10000;
-    (0, maker, taker)
+    let maker = units::notional_micro(size_micro, price_bps)?;
+    Ok((0, maker, taker))
 }
 
 /// SELL order calculation
 /// Input: size in micro-units, price in basis points
 /// Output: (side=1, maker_amount, taker_amount) in token decimals (6 dp)
 #[inline(always)]
-pub fn get_order_amounts_sell(size_micro: u64, price_bps: u64) -> (i32, u128, u128) {
+pub fn get_order_amounts_sell(size_micro: u64, price_bps: u64) -> Result<(i32, u128, u128)> {
     // For SELL: maker = size (what we give), taker = size * price (what we receive)
     let maker = size_micro as u128;
     // taker = size * price / 10000 (convert bps to ratio)
-    let taker = (size_micro as u128 * price_bps as u128) / 10000;
-    (1, maker, taker)
+    let taker = units::notional_micro(size_micro, price_bps)?;
+    Ok((1, maker, taker))
 }
 
-/// Validate price is within allowed range for tick=0.01
+/// Polymarket price tick (binary markets) where the market's isn't known
+pub const POLY_TICK: f64 = 0.01;
+
+/// Validate price is within the allowed range for `tick`: one tick from either end
+/// (0.01-0.99 for tick=0.01, 0.001-0.999 for tick=0.001)
 #[inline(always)]
-pub fn price_valid(price_bps: u64) -> bool {
-    // For tick=0.01: price must be >= 0.01 (100 bps) and <= 0.99 (9900 bps)
-    price_bps >= 100 && price_bps <= 9900
+pub fn price_valid(price_bps: u64, tick: f64) -> bool {
+    let tick_bps = ((tick * 10_000.0).round() as u64).max(1);
+    (tick_bps..=10_000 - tick_bps).contains(&price_bps)
 }
 
 fn order_typed_data(chain_id: u64, exchange: &str, data: &OrderData<'_>) -> Result<TypedData> {
@@ -334,13 +339,54 @@ fn get_exchange_address(chain_id: u64, neg_risk: bool) -> Result<String> {
     }
 }
 
+/// Fee rate (bps) an order is signed with when its token's rate isn't known yet
+/// (most markets are fee-free)
+pub const DEFAULT_FEE_RATE_BPS: u32 = 0;
+
+/// Fee rate lookups in flight at once during discovery
+const FEE_PREFETCH_CONCURRENCY: usize = 8;
+
+/// How long the trades behind a fill are looked up before the fill is left without them
+const TRADE_LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Taker fee rate (bps) of a token from the CLOB's /fee-rate endpoint
+pub async fn fetch_fee_rate_bps(http: &reqwest::Client, host: &str, token_id: &str) -> Result<u32> {
+    let url = format!("{}/fee-rate?token_id={}", host, token_id);
+    let val: serde_json::Value = http
+        .get(&url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    val["base_fee"].as_u64()
+        .and_then(|bps| u32::try_from(bps).ok())
+        .filter(|bps| *bps <= 10_000)
+        .ok_or_else(|| anyhow!("unexpected /fee-rate response: {}", val))
+}
+
+/// Current books of `tokens` from the CLOB's batch /books endpoint (one request)
+pub async fn fetch_books(http: &reqwest::Client, host: &str, tokens: &[String], timeout: Duration) -> Result<Vec<BookSnapshot>> {
+    let url = format!("{}/books", host);
+    let body: Vec<serde_json::Value> = tokens.iter().map(|t| serde_json::json!({ "token_id": t })).collect();
+    Ok(http.post(&url)
+        .timeout(timeout)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
 // ============================================================================
 // ORDER TYPES FOR FAK/FOK
 // ============================================================================
 
 /// Order type for Polymarket
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 pub enum PolyOrderType {
     /// Good Till Cancelled (default)
     GTC,
@@ -392,6 +438,20 @@ pub struct PolymarketOrderResponse {
     pub owner: Option<String>,
 }
 
+/// Response from GET /balance-allowance (balance in USDC base units, 6 decimals)
+#[derive(Debug, Clone, Deserialize)]
+struct BalanceAllowanceResponse {
+    balance: String,
+}
+
+/// One page of GET /data/orders
+#[derive(Debug, Clone, Deserialize)]
+struct OpenOrdersPage {
+    #[serde(default)]
+    data: Vec<PolymarketOrderResponse>,
+    next_cursor: Option<String>,
+}
+
 // ============================================================================
 // ASYNC CLIENT
 // ============================================================================
@@ -437,7 +497,7 @@ impl PolymarketAsyncClient {
     /// Build L1 headers for authentication (derive-api-key)
     /// wallet.sign_hash() is CPU-bound (~1ms), safe to call in async context
     fn build_l1_headers(&self, nonce: u64) -> Result<HeaderMap> {
-        let timestamp = current_unix_ts();
+        let timestamp = clock_skew::signing_clock().timestamp();
         let digest = clob_auth_digest(self.chain_id, &self.wallet_address_str, timestamp, nonce)?;
         let sig = self.wallet.sign_hash(digest)?;
         let mut headers = HeaderMap::new();
@@ -449,11 +509,17 @@ impl PolymarketAsyncClient {
         Ok(headers)
     }
 
+    /// Measure the local clock's skew against the CLOB's /time before anything is signed
+    pub async fn sync_clock(&self) -> Result<()> {
+        let clock = clock_skew::signing_clock();
+        clock_skew::sync(&self.http, &self.host, |secs, sent, recv| clock.observe(secs, sent, recv)).await
+    }
+
     /// Derive API credentials from L1 wallet signature
     pub async fn derive_api_key(&self, nonce: u64) -> Result<ApiCreds> {
         let url = format!("{}/auth/derive-api-key", self.host);
         let headers = self.build_l1_headers(nonce)?;
-        let resp = self.http.get(&url).headers(headers).send().await?;
+        let resp = self.send_signed(self.http.get(&url).headers(headers)).await?;
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
@@ -464,7 +530,7 @@ impl PolymarketAsyncClient {
 
     /// Build L2 headers for authenticated requests
     fn build_l2_headers(&self, method: &str, path: &str, body: Option<&str>, creds: &PreparedCreds) -> Result<HeaderMap> {
-        let timestamp = current_unix_ts();
+        let timestamp = clock_skew::signing_clock().timestamp();
         let mut message = format!("{}{}{}", timestamp, method, path);
         if let Some(b) = body { message.push_str(b); }
 
@@ -480,17 +546,40 @@ impl PolymarketAsyncClient {
         Ok(headers)
     }
 
+    /// Send a signed request, timing it so the response's Date header refines the signing
+    /// clock. A 401 while the clock is skewed is reported as the clock problem it likely is.
+    async fn send_signed(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let clock = clock_skew::signing_clock();
+        let sent = clock_skew::local_ms();
+        let resp = request.send().await?;
+        let date = resp.headers().get(reqwest::header::DATE).and_then(|v| v.to_str().ok());
+        clock.observe_date(date, sent, clock_skew::local_ms());
+        if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
+            if let Some(hint) = clock.auth_hint() {
+                tracing::warn!("[POLY] 🔐 401 on {}: {} - likely the clock, not the API key", resp.url().path(), hint);
+                crate::metrics::incr("poly.auth_rejected_skewed");
+            }
+        }
+        Ok(resp)
+    }
+
     /// Post order 
-    pub async fn post_order_async(&self, body: String, creds: &PreparedCreds) -> Result<reqwest::Response> {
+    /// Post a signed order decided under lease `epoch` (see `leader::fence`)
+    pub async fn post_order_async(&self, body: String, creds: &PreparedCreds, epoch: u64) -> Result<reqwest::Response> {
+        // A standby instance never sends orders, whatever path asked for one, and neither does
+        // a leader whose lease changed hands since the order was decided
+        if !crate::leader::fence_holds(epoch) {
+            return Err(anyhow!("lease epoch {} no longer held: orders are sent by the current leader", epoch));
+        }
         let path = "/order";
         let url = format!("{}{}", self.host, path);
         let headers = self.build_l2_headers("POST", path, Some(&body), creds)?;
+        crate::order_rate::rates().record(crate::venue_health::Venue::Polymarket, &creds.api_key);
 
-        let resp = self.http
+        let resp = self.send_signed(self.http
             .post(&url)
             .headers(headers)
-            .body(body)
-            .send()
+            .body(body))
             .await?;
 
         Ok(resp)
@@ -502,10 +591,9 @@ impl PolymarketAsyncClient {
         let url = format!("{}{}", self.host, path);
         let headers = self.build_l2_headers("GET", &path, None, creds)?;
 
-        let resp = self.http
+        let resp = self.send_signed(self.http
             .get(&url)
-            .headers(headers)
-            .send()
+            .headers(headers))
             .await?;
 
         if !resp.status().is_success() {
@@ -517,6 +605,122 @@ impl PolymarketAsyncClient {
         Ok(resp.json().await?)
     }
 
+    /// Trades of one trade ID from GET /data/trades (maker orders, trader side, settlement tx)
+    pub async fn get_trades_async(&self, trade_id: &str, creds: &PreparedCreds) -> Result<Vec<serde_json::Value>> {
+        // Signed over the bare path; the query string only goes on the URL
+        let path = "/data/trades";
+        let url = format!("{}{}?id={}", self.host, path, trade_id);
+        let headers = self.build_l2_headers("GET", path, None, creds)?;
+
+        let resp = self.send_signed(self.http.get(&url).headers(headers)).await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("get_trades failed {}: {}", status, body));
+        }
+
+        // Paginated ({data, next_cursor}) or a bare array, depending on the API version
+        let mut body: serde_json::Value = resp.json().await?;
+        let trades = if body.is_array() { body } else { body["data"].take() };
+        Ok(trades.as_array().cloned().unwrap_or_default())
+    }
+
+    /// USDC collateral balance of the funder wallet (dollars)
+    pub async fn get_collateral_balance_async(&self, creds: &PreparedCreds) -> Result<f64> {
+        // Signed over the bare path; the query string only goes on the URL
+        let path = "/balance-allowance";
+        let url = format!("{}{}?asset_type=COLLATERAL&signature_type=1", self.host, path);
+        let headers = self.build_l2_headers("GET", path, None, creds)?;
+
+        let resp = self.send_signed(self.http.get(&url).headers(headers)).await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("balance-allowance failed {}: {}", status, body));
+        }
+
+        let data: BalanceAllowanceResponse = resp.json().await?;
+        let raw: f64 = data.balance.parse()
+            .map_err(|e| anyhow!("balance-allowance: bad balance {:?}: {}", data.balance, e))?;
+        Ok(raw / 1e6)
+    }
+
+    /// All open orders of this API key (follows pagination)
+    pub async fn get_open_orders_async(&self, creds: &PreparedCreds) -> Result<Vec<PolymarketOrderResponse>> {
+        let path = "/data/orders";
+        let mut orders = Vec::new();
+        let mut cursor = String::from("MA==");
+        // Cursor "LTE=" marks the last page; the cap guards against a cursor that never ends
+        for _ in 0..20 {
+            let url = format!("{}{}?next_cursor={}", self.host, path, cursor);
+            let headers = self.build_l2_headers("GET", path, None, creds)?;
+            let resp = self.send_signed(self.http.get(&url).headers(headers)).await?;
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                return Err(anyhow!("get_orders failed {}: {}", status, body));
+            }
+            let page: OpenOrdersPage = resp.json().await?;
+            orders.extend(page.data);
+            match page.next_cursor {
+                Some(next) if !next.is_empty() && next != "LTE=" => cursor = next,
+                _ => break,
+            }
+        }
+        Ok(orders)
+    }
+
+    /// Get order by ID, `None` if the exchange has no such order (never accepted)
+    pub async fn find_order_async(&self, order_id: &str, creds: &PreparedCreds) -> Result<Option<PolymarketOrderResponse>> {
+        let path = format!("/data/order/{}", order_id);
+        let url = format!("{}{}", self.host, path);
+        let headers = self.build_l2_headers("GET", &path, None, creds)?;
+
+        let resp = self.send_signed(self.http
+            .get(&url)
+            .headers(headers))
+            .await?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("get_order failed {}: {}", status, body));
+        }
+
+        // Unknown IDs come back as an empty body / null
+        let body = resp.text().await?;
+        if body.trim().is_empty() || body.trim() == "null" {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&body)?))
+    }
+
+    /// Cancel order by ID
+    #[allow(dead_code)]
+    pub async fn cancel_order_async(&self, order_id: &str, creds: &PreparedCreds) -> Result<serde_json::Value> {
+        let path = "/order";
+        let url = format!("{}{}", self.host, path);
+        let body = serde_json::json!({ "orderID": order_id }).to_string();
+        let headers = self.build_l2_headers("DELETE", path, Some(&body), creds)?;
+
+        let resp = self.send_signed(self.http
+            .delete(&url)
+            .headers(headers)
+            .body(body))
+            .await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(anyhow!("cancel_order failed {}: {}", status, body));
+        }
+
+        Ok(resp.json().await?)
+    }
+
     /// Check neg_risk for token - with caching
     pub async fn check_neg_risk(&self, token_id: &str) -> Result<bool> {
         let url = format!("{}/neg-risk?token_id={}", self.host, token_id);
@@ -530,6 +734,11 @@ impl PolymarketAsyncClient {
         Ok(val["neg_risk"].as_bool().unwrap_or(false))
     }
 
+    /// Taker fee rate of a token (bps)
+    pub async fn fee_rate_bps(&self, token_id: &str) -> Result<u32> {
+        fetch_fee_rate_bps(&self.http, &self.host, token_id).await
+    }
+
     #[allow(dead_code)]
     pub fn wallet_address(&self) -> &str {
         &self.wallet_address_str
@@ -553,6 +762,10 @@ pub struct SharedAsyncClient {
     chain_id: u64,
     /// Pre-cached neg_risk lookups
     neg_risk_cache: std::sync::RwLock<HashMap<String, bool>>,
+    /// Fee rates (bps) orders are signed with, by token
+    fee_rate_cache: Arc<std::sync::RwLock<HashMap<String, u32>>>,
+    /// Minimum price increments order prices are rounded to, by token
+    tick_size_cache: std::sync::RwLock<HashMap<String, f64>>,
 }
 
 impl SharedAsyncClient {
@@ -562,9 +775,79 @@ impl SharedAsyncClient {
             creds,
             chain_id,
             neg_risk_cache: std::sync::RwLock::new(HashMap::new()),
+            fee_rate_cache: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            tick_size_cache: std::sync::RwLock::new(HashMap::new()),
         }
     }
 
+    /// Remember a token's tick size (from the scan); orders on it are priced on that grid
+    #[allow(dead_code)]
+    pub fn set_tick_size(&self, token_id: &str, tick_size: f64) {
+        if tick_size > 0.0 && tick_size < 1.0 {
+            self.tick_size_cache.write().unwrap().insert(token_id.to_string(), tick_size);
+        }
+    }
+
+    /// Tick size of `token_id`: its market's, else POLY_TICK
+    fn tick_for(&self, token_id: &str) -> f64 {
+        self.tick_size_cache.read().unwrap().get(token_id).copied().unwrap_or(POLY_TICK)
+    }
+
+    /// Remember a token's fee rate (fetched at scan time) so orders don't look it up
+    pub fn set_fee_rate(&self, token_id: &str, fee_rate_bps: u32) {
+        self.fee_rate_cache.write().unwrap().insert(token_id.to_string(), fee_rate_bps);
+    }
+
+    /// Look up and cache the fee rates of tokens at discovery, off the order path. Returns
+    /// the rates found; a token whose lookup failed is left to the default until an order refreshes it.
+    pub async fn prefetch_fee_rates(&self, tokens: &[String]) -> HashMap<String, u32> {
+        use futures_util::StreamExt;
+        let lookups = futures_util::stream::iter(tokens.iter().cloned())
+            .map(|token| async move {
+                let rate = self.inner.fee_rate_bps(&token).await;
+                (token, rate)
+            })
+            .buffer_unordered(FEE_PREFETCH_CONCURRENCY);
+        let results: Vec<(String, Result<u32>)> = lookups.collect().await;
+        let mut rates = HashMap::new();
+        let mut failed = 0;
+        for (token, rate) in results {
+            match rate {
+                Ok(bps) => {
+                    self.set_fee_rate(&token, bps);
+                    rates.insert(token, bps);
+                }
+                Err(e) => {
+                    tracing::debug!("[POLY] Fee rate lookup for {} failed: {}", token, e);
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            tracing::warn!("[POLY] {} of {} fee rate lookups failed - those tokens sign with {}bps until refreshed",
+                  failed, tokens.len(), DEFAULT_FEE_RATE_BPS);
+        }
+        rates
+    }
+
+    /// Fee rate to sign with: the cached one, or the default while a lookup in the
+    /// background fills the cache for the next order
+    fn fee_rate_for_order(&self, token_id: &str) -> u32 {
+        if let Some(bps) = self.fee_rate_cache.read().unwrap().get(token_id).copied() {
+            return bps;
+        }
+        let (inner, cache, token) = (self.inner.clone(), self.fee_rate_cache.clone(), token_id.to_string());
+        tokio::spawn(async move {
+            match inner.fee_rate_bps(&token).await {
+                Ok(bps) => {
+                    cache.write().unwrap().insert(token, bps);
+                }
+                Err(e) => tracing::debug!("[POLY] Fee rate refresh for {} failed: {}", token, e),
+            }
+        });
+        DEFAULT_FEE_RATE_BPS
+    }
+
     /// Load neg_risk cache from JSON file (output of build_sports_cache.py)
     pub fn load_cache(&self, path: &str) -> Result<usize> {
         let data = std::fs::read_to_string(path)?;
@@ -576,11 +859,24 @@ impl SharedAsyncClient {
     }
 
     /// Execute FAK buy order - 
+    #[allow(dead_code)]
     pub async fn buy_fak(&self, token_id: &str, price: f64, size: f64) -> Result<PolyFillAsync> {
         debug_assert!(!token_id.is_empty(), "token_id must not be empty");
         debug_assert!(price > 0.0 && price < 1.0, "price must be 0 < p < 1");
         debug_assert!(size >= 1.0, "size must be >= 1");
-        self.execute_order(token_id, price, size, "BUY").await
+        self.execute_order(token_id, price, size, "BUY", |_| Ok(())).await
+    }
+
+    /// FAK buy that hands the order ID (the signed order's hash) to `before_send`
+    /// before posting; if it fails, nothing is sent. Used to journal intents.
+    pub async fn buy_fak_with<F>(&self, token_id: &str, price: f64, size: f64, before_send: F) -> Result<PolyFillAsync>
+    where
+        F: FnMut(&str) -> Result<()>,
+    {
+        debug_assert!(!token_id.is_empty(), "token_id must not be empty");
+        debug_assert!(price > 0.0 && price < 1.0, "price must be 0 < p < 1");
+        debug_assert!(size >= 1.0, "size must be >= 1");
+        self.execute_order(token_id, price, size, "BUY", before_send).await
     }
 
     /// Execute FAK sell order - 
@@ -588,10 +884,44 @@ impl SharedAsyncClient {
         debug_assert!(!token_id.is_empty(), "token_id must not be empty");
         debug_assert!(price > 0.0 && price < 1.0, "price must be 0 < p < 1");
         debug_assert!(size >= 1.0, "size must be >= 1");
-        self.execute_order(token_id, price, size, "SELL").await
+        self.execute_order(token_id, price, size, "SELL", |_| Ok(())).await
     }
 
-    async fn execute_order(&self, token_id: &str, price: f64, size: f64, side: &str) -> Result<PolyFillAsync> {
+    /// Query an order's current state
+    #[allow(dead_code)]
+    pub async fn get_order(&self, order_id: &str) -> Result<PolymarketOrderResponse> {
+        self.inner.get_order_async(order_id, &self.creds).await
+    }
+
+    /// Query an order, `None` if the exchange never accepted it
+    pub async fn find_order(&self, order_id: &str) -> Result<Option<PolymarketOrderResponse>> {
+        self.inner.find_order_async(order_id, &self.creds).await
+    }
+
+    /// USDC collateral balance (dollars)
+    pub async fn usdc_balance(&self) -> Result<f64> {
+        self.inner.get_collateral_balance_async(&self.creds).await
+    }
+
+    /// Open (resting) orders
+    pub async fn open_orders(&self) -> Result<Vec<PolymarketOrderResponse>> {
+        self.inner.get_open_orders_async(&self.creds).await
+    }
+
+    /// Cancel an open order
+    #[allow(dead_code)]
+    pub async fn cancel_order(&self, order_id: &str) -> Result<serde_json::Value> {
+        self.inner.cancel_order_async(order_id, &self.creds).await
+    }
+
+    async fn execute_order<F>(&self, token_id: &str, price: f64, size: f64, side: &str, mut before_send: F) -> Result<PolyFillAsync>
+    where
+        F: FnMut(&str) -> Result<()>,
+    {
+        // Fencing token: checked again when the order goes out
+        let epoch = crate::leader::fence()
+            .ok_or_else(|| anyhow!("standby instance: orders are sent by the leader"))?;
+
         // Check neg_risk cache first
         let neg_risk = {
             let cache = self.neg_risk_cache.read().unwrap();
@@ -608,40 +938,125 @@ impl SharedAsyncClient {
             }
         };
 
-        // Build signed order
-        let signed = self.build_signed_order(token_id, price, size, side, neg_risk)?;
-        // Owner must be the API key (not wallet address or funder!)
-        let body = signed.post_body(&self.creds.api_key, PolyOrderType::FAK.as_str());
-
-        // Post order
-        let resp = self.inner.post_order_async(body, &self.creds).await?;
-
-        if !resp.status().is_success() {
+        // Orders must carry the market's fee rate or the exchange rejects them (prefetched
+        // at discovery, never looked up on the order path)
+        let fee_rate_bps = self.fee_rate_for_order(token_id);
+
+        // On the market's grid: a buy rounds down, a sell up, so neither pays more than asked
+        let tick = self.tick_for(token_id);
+        let mut price = round_to_tick(price, tick, side == "BUY");
+        let mut rerounded = false;
+        let (order_hash, resp_json) = loop {
+            // Build signed order (its EIP712 hash is the exchange order ID)
+            let (signed, order_hash) = self.build_signed_order(token_id, price, size, side, neg_risk, fee_rate_bps)?;
+            before_send(&order_hash)?;
+            // Owner must be the API key (not wallet address or funder!)
+            let body = signed.post_body(&self.creds.api_key, PolyOrderType::FAK.as_str());
+
+            // Post order
+            let resp = self.inner.post_order_async(body, &self.creds, epoch).await?;
             let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("Polymarket order failed {}: {}", status, body));
-        }
+            let text = resp.text().await.unwrap_or_default();
+            let resp_json: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+
+            // Rejections come back as 4xx, or as 200 with success=false
+            let accepted = status.is_success() && resp_json["success"].as_bool() != Some(false);
+            if accepted {
+                break (order_hash, resp_json);
+            }
+            let mut rejected = OrderRejected::polymarket(status.as_u16(), &text);
+            if status == reqwest::StatusCode::UNAUTHORIZED {
+                if let Some(hint) = clock_skew::signing_clock().auth_hint() {
+                    rejected.message = format!("{} ({})", rejected.message, hint);
+                }
+            }
+            crate::events::publish(crate::events::BotEvent::OrderRejected {
+                platform: "polymarket".into(),
+                instrument: token_id.to_string(),
+                reason: rejected.reason.as_str().into(),
+                message: rejected.message.clone(),
+            });
+            crate::metrics::incr(&format!("poly.rejected.{}", rejected.reason));
+
+            match rejected.reason {
+                // Off-tick price (the cached tick is finer than the market's): snap to the
+                // default grid once and resend
+                RejectReason::InvalidPrice if !rerounded => {
+                    let snapped = round_to_tick(price, POLY_TICK.max(tick), side == "BUY");
+                    rerounded = true;
+                    if (snapped - price).abs() > 1e-9 && snapped > 0.0 && snapped < 1.0 {
+                        tracing::warn!("[POLY] 🔁 {} rejected at {:.4} ({}), re-rounded to {}",
+                                       side, price, rejected.message, snapped);
+                        price = snapped;
+                        continue;
+                    }
+                }
+                // FAK with nothing to hit is an empty fill, not an error
+                RejectReason::NoMatch => {
+                    tracing::debug!("[POLY-ASYNC] FAK {} {}: no match", side, order_hash);
+                    return Ok(PolyFillAsync { order_id: order_hash, filled_size: 0.0, fill_cost: 0.0, detail: FillDetail::default() });
+                }
+                RejectReason::InsufficientBalance => {
+                    tracing::error!("[POLY] 💸 Insufficient balance/allowance for {} {:.0} @ {:.2} - replenish USDC: {}",
+                                    side, size, price, rejected.message);
+                }
+                _ => {}
+            }
+            return Err(rejected.into());
+        };
 
-        let resp_json: serde_json::Value = resp.json().await?;
-        let order_id = resp_json["orderID"].as_str().unwrap_or("unknown").to_string();
+        let order_id = resp_json["orderID"].as_str().filter(|id| !id.is_empty()).unwrap_or(&order_hash).to_string();
 
         // Query fill status
         let order_info = self.inner.get_order_async(&order_id, &self.creds).await?;
-        let filled_size: f64 = order_info.size_matched.parse().unwrap_or(0.0);
-        let order_price: f64 = order_info.price.parse().unwrap_or(price);
+        let filled_size = units::parse_amount(&order_info.size_matched).unwrap_or(0.0);
+        let order_price = units::parse_amount(&order_info.price).unwrap_or(price);
+
+        let detail = FillDetail::from_polymarket(&resp_json, &order_info, &[]);
+        // Trades behind the fill (maker orders, trader side) are looked up off the order path
+        // and attached to the recorded fill when they arrive; a failed lookup only loses those
+        if filled_size > 0.0 && !detail.trade_ids.is_empty() {
+            self.spawn_trade_lookup(order_id.clone(), resp_json, order_info);
+        }
 
         tracing::debug!(
-            "[POLY-ASYNC] FAK {} {}: status={}, filled={:.2}/{:.2}, price={:.4}",
-            side, order_id, order_info.status, filled_size, size, order_price
+            "[POLY-ASYNC] FAK {} {}: status={}, filled={:.2}/{:.2}, price={:.4}, trades={:?}",
+            side, order_id, detail.statuses.join(" -> "), filled_size, size, order_price, detail.trade_ids
         );
 
         Ok(PolyFillAsync {
             order_id,
             filled_size,
             fill_cost: filled_size * order_price,
+            detail,
         })
     }
 
+    /// Look up the trades of a filled order (bounded by `TRADE_LOOKUP_TIMEOUT`) and hand the
+    /// completed detail to the position writer
+    fn spawn_trade_lookup(&self, order_id: String, post: serde_json::Value, order: PolymarketOrderResponse) {
+        let inner = self.inner.clone();
+        let creds = self.creds.clone();
+        tokio::spawn(async move {
+            let ids = FillDetail::from_polymarket(&post, &order, &[]).trade_ids;
+            let lookups = futures_util::future::join_all(ids.iter().map(|id| inner.get_trades_async(id, &creds)));
+            let Ok(results) = tokio::time::timeout(TRADE_LOOKUP_TIMEOUT, lookups).await else {
+                tracing::debug!("[POLY-ASYNC] Trade lookup for {} timed out", order_id);
+                return;
+            };
+            let mut trades = Vec::new();
+            for result in results {
+                match result {
+                    Ok(found) => trades.extend(found),
+                    Err(e) => tracing::debug!("[POLY-ASYNC] Trade lookup for {} failed: {}", order_id, e),
+                }
+            }
+            if !trades.is_empty() {
+                crate::position_tracker::attach_fill_detail(&order_id, FillDetail::from_polymarket(&post, &order, &trades));
+            }
+        });
+    }
+
     /// Build a signed order
     fn build_signed_order(
         &self,
@@ -650,18 +1065,19 @@ impl SharedAsyncClient {
         size: f64,
         side: &str,
         neg_risk: bool,
-    ) -> Result<SignedOrder> {
-        let price_bps = price_to_bps(price);
-        let size_micro = size_to_micro(size);
+        fee_rate_bps: u32,
+    ) -> Result<(SignedOrder, String)> {
+        let price_bps = price_to_bps(price)?;
+        let size_micro = size_to_micro(size)?;
 
-        if !price_valid(price_bps) {
+        if !price_valid(price_bps, self.tick_for(token_id)) {
             return Err(anyhow!("price {} ({}bps) outside allowed range", price, price_bps));
         }
 
         let (side_code, maker_amt, taker_amt) = if side.eq_ignore_ascii_case("BUY") {
-            get_order_amounts_buy(size_micro, price_bps)
+            get_order_amounts_buy(size_micro, price_bps)?
         } else if side.eq_ignore_ascii_case("SELL") {
-            get_order_amounts_sell(size_micro, price_bps)
+            get_order_amounts_sell(size_micro, price_bps)?
         } else {
             return Err(anyhow!("side must be BUY or SELL"));
         };
@@ -669,6 +1085,7 @@ impl SharedAsyncClient {
         let salt = generate_seed();
         let maker_amount_str = maker_amt.to_string();
         let taker_amount_str = taker_amt.to_string();
+        let fee_rate_str = fee_rate_bps.to_string();
 
         // Use references for EIP712 signing 
         let data = OrderData {
@@ -678,7 +1095,7 @@ impl SharedAsyncClient {
             maker_amount: &maker_amount_str,
             taker_amount: &taker_amount_str,
             side: side_code,
-            fee_rate_bps: "0",
+            fee_rate_bps: &fee_rate_str,
             nonce: "0",
             signer: &self.inner.wallet_address_str,
             expiration: "0",
@@ -692,7 +1109,7 @@ impl SharedAsyncClient {
         let sig = self.inner.wallet.sign_hash(H256::from(digest))?;
 
         // Only allocate strings once for the final OrderStruct (serialization needs owned)
-        Ok(SignedOrder {
+        let signed = SignedOrder {
             order: OrderStruct {
                 salt,
                 maker: self.inner.funder.clone(),
@@ -703,19 +1120,22 @@ impl SharedAsyncClient {
                 taker_amount: taker_amount_str,
                 expiration: "0".to_string(),
                 nonce: "0".to_string(),
-                fee_rate_bps: "0".to_string(),
+                fee_rate_bps: fee_rate_str,
                 side: side_code,
                 signature_type: 1,
             },
             signature: format!("0x{}", sig),
-        })
+        };
+        Ok((signed, format!("{:#x}", H256::from(digest))))
     }
 }
 
 /// Async fill result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PolyFillAsync {
     pub order_id: String,
     pub filled_size: f64,
     pub fill_cost: f64,
+    /// Trade IDs, maker orders matched and status transitions
+    pub detail: FillDetail,
 }
\ No newline at end of file
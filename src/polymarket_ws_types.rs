@@ -0,0 +1,306 @@
+// src/polymarket_ws_types.rs
+// Polymarket WebSocket message schema - market and user channel payloads, in one place
+
+use serde::{Deserialize, Serialize};
+
+use crate::trade_tape::{TakerSide, TradePrint};
+use crate::units;
+
+/// Version of the payload shapes below. When Polymarket changes a message, bump it,
+/// record the change here and add the new payload to tests/fixtures/polymarket_ws/.
+///
+/// - 1: `price_change` carried one `asset_id` with a `changes` list
+/// - 2: `price_change` carries `price_changes`, each item with its own `asset_id` and
+///   the token's `best_bid` / `best_ask`; `last_trade_price` gained `fee_rate_bps`
+#[allow(dead_code)]
+pub const SCHEMA_VERSION: u32 = 2;
+
+// === Market channel: inbound ===
+
+/// `book` - full snapshot of one token's book (sent as a JSON array of snapshots)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BookSnapshot {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    pub asset_id: String,
+    /// Condition ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    /// Milliseconds since epoch, as a string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl BookSnapshot {
+    /// Has levels, and every level has a price in (0, 1) and a parseable size
+    pub fn is_well_formed(&self) -> bool {
+        (!self.bids.is_empty() || !self.asks.is_empty())
+            && self.bids.iter().chain(&self.asks).all(|l| {
+                units::parse_amount(&l.price).is_some_and(|p| p > 0.0 && p < 1.0)
+                    && units::parse_amount(&l.size).is_some()
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceLevel {
+    pub price: String,
+    pub size: String,
+}
+
+/// `price_change` - level updates for one or more tokens of a market
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceChangeEvent {
+    pub event_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    #[serde(default)]
+    pub price_changes: Option<Vec<PriceChangeItem>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceChangeItem {
+    pub asset_id: String,
+    pub price: Option<String>,
+    /// New total size at the level ("0" removes it)
+    #[serde(default)]
+    pub size: Option<String>,
+    pub side: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_bid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_ask: Option<String>,
+}
+
+/// `last_trade_price` - one executed trade on a token
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LastTradeEvent {
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub event_type: Option<String>,
+    pub asset_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    pub price: String,
+    #[serde(default)]
+    pub size: Option<String>,
+    /// Taker side ("BUY"/"SELL")
+    #[serde(default)]
+    pub side: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_rate_bps: Option<String>,
+    /// Milliseconds since epoch, as a string
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+impl LastTradeEvent {
+    /// Convert to a tape print (falls back to local time if the timestamp is missing)
+    pub fn to_print(&self) -> Option<TradePrint> {
+        let price: f64 = self.price.parse().ok()?;
+        if !(0.0..=1.0).contains(&price) {
+            return None;
+        }
+        Some(TradePrint {
+            price,
+            size: self.size.as_deref().and_then(|s| s.parse().ok()).unwrap_or(0.0),
+            side: self.side.as_deref().and_then(TakerSide::parse),
+            ts_ms: self.timestamp.as_deref()
+                .and_then(|t| t.parse().ok())
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64),
+        })
+    }
+}
+
+/// `tick_size_change` - a token's minimum tick changed (prices near 0 / 1 go to 0.001)
+#[allow(dead_code)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TickSizeChangeEvent {
+    pub event_type: String,
+    pub asset_id: String,
+    pub market: String,
+    pub old_tick_size: String,
+    pub new_tick_size: String,
+    pub timestamp: String,
+}
+
+// === Market channel: outbound ===
+
+/// Initial subscription on connect
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MarketSubscribe {
+    pub assets_ids: Vec<String>,
+    #[serde(rename = "type")]
+    pub channel: &'static str,
+}
+
+impl MarketSubscribe {
+    pub fn new(assets_ids: Vec<String>) -> Self {
+        Self { assets_ids, channel: "market" }
+    }
+}
+
+/// Dynamic subscribe / unsubscribe on a live connection
+#[allow(dead_code)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MarketOperation {
+    pub assets_ids: Vec<String>,
+    /// "subscribe" or "unsubscribe"
+    pub operation: String,
+}
+
+// === User channel ===
+
+/// Subscription to the authenticated user channel (own orders and trades)
+#[allow(dead_code)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UserSubscribe {
+    pub auth: UserAuth,
+    /// Condition IDs to follow (empty = all)
+    pub markets: Vec<String>,
+    #[serde(rename = "type")]
+    pub channel: &'static str,
+}
+
+#[allow(dead_code)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UserAuth {
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
+/// `trade` - one of our orders matched (status MATCHED -> MINED -> CONFIRMED, or FAILED)
+#[allow(dead_code)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserTradeEvent {
+    pub event_type: String,
+    pub id: String,
+    pub asset_id: String,
+    pub market: String,
+    pub outcome: String,
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trade_owner: Option<String>,
+    pub price: String,
+    pub side: String,
+    pub size: String,
+    pub status: String,
+    pub taker_order_id: String,
+    #[serde(default)]
+    pub maker_orders: Vec<MakerOrder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matchtime: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<String>,
+    pub timestamp: String,
+    /// Always "TRADE"
+    #[serde(rename = "type")]
+    pub message_type: String,
+}
+
+/// Resting order a trade matched against
+#[allow(dead_code)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MakerOrder {
+    pub asset_id: String,
+    pub matched_amount: String,
+    pub order_id: String,
+    pub outcome: String,
+    pub owner: String,
+    pub price: String,
+}
+
+/// `order` - one of our orders was placed, updated (partial match) or cancelled
+#[allow(dead_code)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserOrderEvent {
+    pub event_type: String,
+    pub id: String,
+    pub asset_id: String,
+    pub market: String,
+    pub outcome: String,
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_owner: Option<String>,
+    pub original_size: String,
+    pub size_matched: String,
+    pub price: String,
+    pub side: String,
+    /// Trade IDs (null until the order matches)
+    #[serde(default)]
+    pub associate_trades: Option<Vec<String>>,
+    pub timestamp: String,
+    /// PLACEMENT, UPDATE or CANCELLATION
+    #[serde(rename = "type")]
+    pub message_type: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    /// Parse a fixture, serialize it back and compare: every field the exchange sends is modeled
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(fixture: &str) -> T {
+        let typed: T = serde_json::from_str(fixture).expect("fixture parses");
+        let original: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        assert_eq!(serde_json::to_value(&typed).unwrap(), original);
+        typed
+    }
+
+    #[test]
+    fn test_market_channel_fixtures_round_trip() {
+        let books: Vec<BookSnapshot> = round_trip(include_str!("../tests/fixtures/polymarket_ws/book.json"));
+        assert!(books[0].is_well_formed());
+        assert_eq!(books[0].asks[0].price, "0.52");
+
+        let change: PriceChangeEvent = round_trip(include_str!("../tests/fixtures/polymarket_ws/price_change.json"));
+        let items = change.price_changes.unwrap();
+        assert_eq!((items.len(), items[1].side.as_deref(), items[0].best_ask.as_deref()), (2, Some("SELL"), Some("1")));
+
+        let trade: LastTradeEvent = round_trip(include_str!("../tests/fixtures/polymarket_ws/last_trade_price.json"));
+        let print = trade.to_print().unwrap();
+        assert_eq!((print.price, print.ts_ms), (0.456, 1_750_428_146_322));
+
+        let tick: TickSizeChangeEvent = round_trip(include_str!("../tests/fixtures/polymarket_ws/tick_size_change.json"));
+        assert_eq!(tick.new_tick_size, "0.001");
+    }
+
+    #[test]
+    fn test_user_channel_fixtures_round_trip() {
+        let trade: UserTradeEvent = round_trip(include_str!("../tests/fixtures/polymarket_ws/user_trade.json"));
+        assert_eq!((trade.status.as_str(), trade.maker_orders[0].matched_amount.as_str()), ("MATCHED", "10"));
+
+        let order: UserOrderEvent = round_trip(include_str!("../tests/fixtures/polymarket_ws/user_order.json"));
+        assert_eq!((order.message_type.as_str(), order.associate_trades), ("PLACEMENT", None));
+
+        let sub = UserSubscribe {
+            auth: UserAuth { api_key: "k".into(), secret: "s".into(), passphrase: "p".into() },
+            markets: vec![],
+            channel: "user",
+        };
+        assert_eq!(serde_json::to_value(&sub).unwrap()["auth"]["apiKey"], "k");
+        assert_eq!(serde_json::to_string(&MarketSubscribe::new(vec!["1".into()])).unwrap(),
+                   r#"{"assets_ids":["1"],"type":"market"}"#);
+    }
+
+    #[test]
+    fn test_schema_drift_fails_to_parse() {
+        // Book levels renamed: the snapshot must not parse as an empty book
+        let drifted = r#"[{"asset_id":"1","buys":[],"sells":[]}]"#;
+        assert!(serde_json::from_str::<Vec<BookSnapshot>>(drifted).is_err());
+        // v1 price_change (no price_changes) still parses, with nothing to apply
+        let v1 = r#"{"event_type":"price_change","asset_id":"1","changes":[{"price":"0.5","size":"1","side":"BUY"}]}"#;
+        assert_eq!(serde_json::from_str::<PriceChangeEvent>(v1).unwrap().price_changes, None);
+    }
+}
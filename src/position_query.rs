@@ -0,0 +1,364 @@
+// src/position_query.rs
+// Typed position queries - open positions, matched pairs, exposure by asset and P&L windows
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::position_tracker::{ArbPosition, PositionSummary, PositionTracker, SharedPositionTracker};
+
+/// Which positions a query returns
+#[derive(Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct PositionFilter {
+    /// Asset / market text (btc, eth, sol, xrp, a league or any substring of id or description)
+    pub asset: Option<String>,
+    /// Only positions opened on this (UTC) date, YYYY-MM-DD
+    pub date: Option<String>,
+    /// cross | poly_only | kalshi_only | basket
+    pub strategy: Option<String>,
+    /// Include resolved positions (default: open only)
+    pub include_resolved: bool,
+}
+
+#[allow(dead_code)]
+impl PositionFilter {
+    pub fn matches(&self, pos: &ArbPosition) -> bool {
+        if !self.include_resolved && pos.status != "open" {
+            return false;
+        }
+        if let Some(asset) = &self.asset {
+            let asset = asset.to_lowercase();
+            let haystack = format!("{} {}", pos.market_id, pos.description).to_lowercase();
+            if !haystack.contains(asset.as_str()) && !haystack.contains(asset_name(&asset)) {
+                return false;
+            }
+        }
+        if let Some(date) = &self.date {
+            if !pos.opened_at.starts_with(date.as_str()) {
+                return false;
+            }
+        }
+        if let Some(strategy) = &self.strategy {
+            if pos.strategy() != strategy.to_lowercase() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Up/Down questions spell the asset out ("Bitcoin Up or Down - ...")
+#[allow(dead_code)]
+fn asset_name(asset: &str) -> &str {
+    match asset {
+        "btc" => "bitcoin",
+        "eth" => "ethereum",
+        "sol" => "solana",
+        other => other,
+    }
+}
+
+/// Asset a position is grouped under: the crypto of an Up/Down market, otherwise the
+/// first segment of its market id (the league of a sports pair)
+pub fn asset_of(pos: &ArbPosition) -> String {
+    let description = pos.description.to_lowercase();
+    for (asset, name) in [("btc", "bitcoin"), ("eth", "ethereum"), ("sol", "solana"), ("xrp", "xrp")] {
+        if description.contains(name) {
+            return asset.to_string();
+        }
+    }
+    pos.market_id.split('-').next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("other")
+        .to_lowercase()
+}
+
+/// One position, flattened for display / serialization
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(dead_code)]
+pub struct PositionView {
+    pub market_id: String,
+    pub description: String,
+    pub asset: String,
+    pub strategy: &'static str,
+    pub opened_at: String,
+    pub status: String,
+    pub matched: f64,
+    /// Positive = excess YES, negative = excess NO
+    pub net_exposure: f64,
+    pub unmatched: f64,
+    pub multi_outcome: bool,
+    pub cost: f64,
+    /// Locked-in profit while open, realized P&L once resolved
+    pub pnl: f64,
+}
+
+#[allow(dead_code)]
+impl PositionView {
+    pub fn of(pos: &ArbPosition) -> Self {
+        Self {
+            market_id: pos.market_id.clone(),
+            description: pos.description.clone(),
+            asset: asset_of(pos),
+            strategy: pos.strategy(),
+            opened_at: pos.opened_at.clone(),
+            status: pos.status.clone(),
+            matched: pos.matched_contracts(),
+            net_exposure: pos.net_exposure(),
+            unmatched: pos.unmatched_exposure(),
+            multi_outcome: pos.is_multi_outcome(),
+            cost: pos.total_cost(),
+            pnl: pos.realized_pnl.unwrap_or_else(|| pos.guaranteed_profit()),
+        }
+    }
+}
+
+/// Open position holding offsetting legs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(dead_code)]
+pub struct MatchedPair {
+    pub market_id: String,
+    pub description: String,
+    pub contracts: f64,
+    pub cost: f64,
+    pub locked_profit: f64,
+}
+
+/// Open positions of one asset
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AssetExposure {
+    pub asset: String,
+    pub positions: usize,
+    pub cost: f64,
+    pub matched: f64,
+    pub unmatched: f64,
+    /// Net YES (positive) / NO (negative) contracts across the asset's binary positions
+    pub net_exposure: f64,
+    pub locked_profit: f64,
+}
+
+/// Realized P&L of positions resolved within a trailing window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PnlWindow {
+    pub label: &'static str,
+    pub realized: f64,
+    pub resolved: usize,
+}
+
+/// One fill of a laddered execution's child orders
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(dead_code)]
+pub struct LadderFill {
+    pub market_id: String,
+    pub timestamp: String,
+    pub side: String,
+    pub contracts: f64,
+    pub price: f64,
+    pub order_id: String,
+}
+
+/// Trailing windows reported by `pnl_windows` (None = all time)
+const PNL_WINDOWS: [(&str, Option<i64>); 4] = [("24h", Some(1)), ("7d", Some(7)), ("30d", Some(30)), ("all", None)];
+
+/// Positions matching `filter`, oldest first
+#[allow(dead_code)]
+pub fn positions(tracker: &PositionTracker, filter: &PositionFilter) -> Vec<PositionView> {
+    tracker.all_positions().into_iter()
+        .filter(|p| filter.matches(p))
+        .map(PositionView::of)
+        .collect()
+}
+
+/// Open positions with matched contracts, oldest first
+#[allow(dead_code)]
+pub fn matched_pairs(tracker: &PositionTracker) -> Vec<MatchedPair> {
+    tracker.all_positions().into_iter()
+        .filter(|p| p.status == "open" && p.matched_contracts() > 1e-9)
+        .map(|p| MatchedPair {
+            market_id: p.market_id.clone(),
+            description: p.description.clone(),
+            contracts: p.matched_contracts(),
+            cost: p.total_cost(),
+            locked_profit: p.guaranteed_profit(),
+        })
+        .collect()
+}
+
+/// Open positions aggregated per asset, by asset name
+pub fn exposure_by_asset(tracker: &PositionTracker) -> Vec<AssetExposure> {
+    let mut by_asset: BTreeMap<String, AssetExposure> = BTreeMap::new();
+    for pos in tracker.open_positions() {
+        let asset = asset_of(pos);
+        let e = by_asset.entry(asset.clone()).or_insert_with(|| AssetExposure { asset, ..Default::default() });
+        e.positions += 1;
+        e.cost += pos.total_cost();
+        e.matched += pos.matched_contracts();
+        e.unmatched += pos.unmatched_exposure();
+        if !pos.is_multi_outcome() {
+            e.net_exposure += pos.net_exposure();
+        }
+        e.locked_profit += pos.guaranteed_profit();
+    }
+    by_asset.into_values().collect()
+}
+
+/// Realized P&L over the trailing 24h / 7d / 30d and all time, by resolution time
+pub fn pnl_windows(tracker: &PositionTracker, now: DateTime<Utc>) -> Vec<PnlWindow> {
+    let resolved: Vec<(Option<DateTime<Utc>>, f64)> = tracker.all_positions().into_iter()
+        .filter_map(|p| {
+            let pnl = p.realized_pnl.filter(|_| p.status == "resolved")?;
+            let at = p.resolved_at.as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc));
+            Some((at, pnl))
+        })
+        .collect();
+
+    PNL_WINDOWS.iter()
+        .map(|(label, days)| {
+            let since = days.map(|d| now - Duration::days(d));
+            let (realized, count) = resolved.iter()
+                .filter(|(at, _)| since.is_none_or(|s| at.is_some_and(|at| at >= s)))
+                .fold((0.0, 0), |(sum, n), (_, pnl)| (sum + pnl, n + 1));
+            PnlWindow { label, realized, resolved: count }
+        })
+        .collect()
+}
+
+/// Fills of the child orders of the laddered execution `parent`, in the order recorded
+#[allow(dead_code)]
+pub fn ladder_fills(tracker: &PositionTracker, parent: &str) -> Vec<LadderFill> {
+    tracker.all_positions().into_iter()
+        .flat_map(|p| p.trades.iter().map(move |t| (p, t)))
+        .filter(|(_, t)| t.parent.as_deref() == Some(parent))
+        .map(|(p, t)| LadderFill {
+            market_id: p.market_id.clone(),
+            timestamp: t.timestamp.clone(),
+            side: t.side.clone(),
+            contracts: t.contracts,
+            price: t.price,
+            order_id: t.order_id.clone(),
+        })
+        .collect()
+}
+
+/// Async query handle over a live tracker, for consumers that run alongside the bot
+/// (status dumps, notifications). Each call reads a consistent snapshot.
+#[derive(Clone)]
+pub struct PositionQueries {
+    tracker: SharedPositionTracker,
+}
+
+impl PositionQueries {
+    pub fn new(tracker: SharedPositionTracker) -> Self {
+        Self { tracker }
+    }
+
+    pub async fn summary(&self) -> PositionSummary {
+        self.tracker.read().await.summary()
+    }
+
+    #[allow(dead_code)]
+    pub async fn positions(&self, filter: &PositionFilter) -> Vec<PositionView> {
+        positions(&*self.tracker.read().await, filter)
+    }
+
+    #[allow(dead_code)]
+    pub async fn matched_pairs(&self) -> Vec<MatchedPair> {
+        matched_pairs(&*self.tracker.read().await)
+    }
+
+    pub async fn exposure_by_asset(&self) -> Vec<AssetExposure> {
+        exposure_by_asset(&*self.tracker.read().await)
+    }
+
+    pub async fn pnl_windows(&self) -> Vec<PnlWindow> {
+        pnl_windows(&*self.tracker.read().await, Utc::now())
+    }
+
+    #[allow(dead_code)]
+    pub async fn ladder_fills(&self, parent: &str) -> Vec<LadderFill> {
+        ladder_fills(&*self.tracker.read().await, parent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position_tracker::FillRecord;
+
+    fn tracker() -> PositionTracker {
+        let mut t = PositionTracker::new();
+        // Matched BTC Up/Down arb, 2 unmatched YES left over
+        t.record_fill_internal(&FillRecord::new("btc-1", "Bitcoin Up or Down - 3PM", "polymarket", "yes", 12.0, 0.48, 0.0, "a"));
+        t.record_fill_internal(&FillRecord::new("btc-1", "Bitcoin Up or Down - 3PM", "polymarket", "no", 10.0, 0.49, 0.0, "b"));
+        // Cross-venue NBA pair
+        t.record_fill_internal(&FillRecord::new("nba-lal-bos", "Lakers vs Celtics", "kalshi", "yes", 5.0, 0.40, 0.0, "c"));
+        t.record_fill_internal(&FillRecord::new("nba-lal-bos", "Lakers vs Celtics", "polymarket", "no", 5.0, 0.55, 0.0, "d"));
+        t
+    }
+
+    #[test]
+    fn test_filters_and_views() {
+        let t = tracker();
+        let all = positions(&t, &PositionFilter::default());
+        assert_eq!(all.len(), 2);
+
+        let btc = positions(&t, &PositionFilter { asset: Some("BTC".into()), ..Default::default() });
+        assert_eq!(btc.len(), 1);
+        assert_eq!((btc[0].asset.as_str(), btc[0].strategy), ("btc", "poly_only"));
+        assert!((btc[0].net_exposure - 2.0).abs() < 1e-9);
+
+        let cross = positions(&t, &PositionFilter { strategy: Some("cross".into()), ..Default::default() });
+        assert_eq!(cross[0].market_id, "nba-lal-bos");
+        assert_eq!(matched_pairs(&t).len(), 2);
+    }
+
+    #[test]
+    fn test_exposure_by_asset() {
+        let t = tracker();
+        let exposure = exposure_by_asset(&t);
+        let assets: Vec<&str> = exposure.iter().map(|e| e.asset.as_str()).collect();
+        assert_eq!(assets, vec!["btc", "nba"]);
+        assert_eq!(exposure[0].positions, 1);
+        assert!((exposure[0].matched - 10.0).abs() < 1e-9);
+        assert!((exposure[0].unmatched - 2.0).abs() < 1e-9);
+        assert!((exposure[1].locked_profit - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ladder_fills_by_parent() {
+        let mut t = tracker();
+        for (side, order_id, parent) in [("yes", "c1", Some("btc-1-9")), ("no", "c2", Some("btc-1-9")), ("yes", "x", None)] {
+            t.record_fill_internal(&FillRecord::new("btc-1", "Bitcoin Up or Down - 3PM", "polymarket", side, 5.0, 0.48, 0.0, order_id)
+                .with_parent(parent));
+        }
+        let fills = ladder_fills(&t, "btc-1-9");
+        let orders: Vec<&str> = fills.iter().map(|f| f.order_id.as_str()).collect();
+        assert_eq!(orders, ["c1", "c2"]);
+        assert_eq!(fills[1].side, "no");
+        assert!(ladder_fills(&t, "btc-1-8").is_empty());
+    }
+
+    #[test]
+    fn test_pnl_windows_by_resolution_time() {
+        // Resolved on the positions directly (the tracker's resolve_* also saves to disk)
+        let mut t = tracker();
+        let now = Utc::now();
+        t.get_or_create("nba-lal-bos", "").resolve(true);
+        let btc = t.get_or_create("btc-1", "");
+        btc.resolve(false);
+        btc.resolved_at = Some((now - Duration::days(10)).to_rfc3339());
+
+        let windows = pnl_windows(&t, now);
+        let by_label: BTreeMap<&str, &PnlWindow> = windows.iter().map(|w| (w.label, w)).collect();
+        assert_eq!(by_label["24h"].resolved, 1);
+        assert!((by_label["24h"].realized - 0.25).abs() < 1e-9);
+        assert_eq!(by_label["7d"].resolved, 1);
+        assert_eq!(by_label["30d"].resolved, 2);
+        // NO won: 10 NO pay out against $10.66 of cost
+        assert!((by_label["all"].realized - (0.25 + 10.0 - 10.66)).abs() < 1e-9);
+        assert!(positions(&t, &PositionFilter::default()).is_empty());
+    }
+}
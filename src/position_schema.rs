@@ -0,0 +1,167 @@
+// src/position_schema.rs
+// Position file schema - the version stamped into positions*.json and the migrations that upgrade older files
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::market_key::MarketKey;
+
+/// Layout version written by this build
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Key for a position recorded under its question before market keys, from its question
+/// and when it opened (RFC 3339); supplied by the bot that owns the file
+pub type LegacyKey<'a> = dyn Fn(&str, &str) -> Option<MarketKey> + 'a;
+
+/// Upgrades a file in place from one version to the next
+type Migration = fn(&mut Value, Option<&LegacyKey<'_>>) -> Result<()>;
+
+/// `MIGRATIONS[n]` upgrades version `n` to `n + 1`
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2];
+
+const _: () = assert!(MIGRATIONS.len() == SCHEMA_VERSION as usize, "one migration per schema version");
+
+/// Version of a position file (0 = written before files were versioned)
+pub fn version_of(file: &Value) -> u32 {
+    file.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0) as u32
+}
+
+/// Bring `file` to SCHEMA_VERSION, returning the version it had. A file from a newer build
+/// is refused rather than read with fields this build doesn't know and then saved without them.
+/// Without `legacy_key`, positions still under their question keep it.
+pub fn upgrade(file: &mut Value, legacy_key: Option<&LegacyKey<'_>>) -> Result<u32> {
+    let from = version_of(file);
+    if from > SCHEMA_VERSION {
+        bail!("schema version {} is newer than this build's {} - run the newer build, or restore a backup",
+              from, SCHEMA_VERSION);
+    }
+    for (version, migrate) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migrate(file, legacy_key).with_context(|| format!("migrating schema {} → {}", version, version + 1))?;
+    }
+    file.as_object_mut()
+        .ok_or_else(|| anyhow!("not a JSON object"))?
+        .insert("schema_version".into(), SCHEMA_VERSION.into());
+    Ok(from)
+}
+
+/// Unversioned files: positions written before trade records, and trades before order IDs,
+/// get them explicitly; a position missing its ID takes the key it is stored under
+fn v0_to_v1(file: &mut Value, _: Option<&LegacyKey<'_>>) -> Result<()> {
+    let positions = positions(file)?;
+    for (id, position) in positions.iter_mut() {
+        let position = position.as_object_mut().ok_or_else(|| anyhow!("position {} is not an object", id))?;
+        position.entry("market_id").or_insert_with(|| id.clone().into());
+        let trades = position.entry("trades").or_insert_with(|| Value::Array(Vec::new()));
+        for trade in trades.as_array_mut().into_iter().flatten() {
+            if let Some(trade) = trade.as_object_mut() {
+                trade.entry("order_id").or_insert_with(|| "".into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Positions recorded under their question move to the key `legacy_key` derives for them.
+/// Those it can't place, and those whose key is already taken, stay where they are.
+fn v1_to_v2(file: &mut Value, legacy_key: Option<&LegacyKey<'_>>) -> Result<()> {
+    let Some(legacy_key) = legacy_key else {
+        return Ok(());
+    };
+    let positions = positions(file)?;
+    let legacy: Vec<String> = positions.iter()
+        .filter(|(id, p)| p.get("key").is_none_or(Value::is_null) && !MarketKey::is_id(id))
+        .map(|(id, _)| id.clone())
+        .collect();
+    for old_id in legacy {
+        let position = &positions[&old_id];
+        let field = |name: &str| position.get(name).and_then(Value::as_str).unwrap_or_default();
+        let Some(key) = legacy_key(field("description"), field("opened_at")) else { continue };
+        let new_id = key.id();
+        if positions.contains_key(&new_id) {
+            warn!("[POSITIONS] Not migrating {}: {} already exists", old_id, new_id);
+            continue;
+        }
+        let Some(Value::Object(mut position)) = positions.remove(&old_id) else { continue };
+        info!("[POSITIONS] Migrated {} → {}", old_id, new_id);
+        position.insert("market_id".into(), new_id.clone().into());
+        position.insert("key".into(), serde_json::to_value(&key)?);
+        positions.insert(new_id, Value::Object(position));
+    }
+    Ok(())
+}
+
+fn positions(file: &mut Value) -> Result<&mut serde_json::Map<String, Value>> {
+    file.get_mut("positions")
+        .and_then(|p| p.as_object_mut())
+        .ok_or_else(|| anyhow!("no positions map"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_upgrades_unversioned_file() {
+        let mut file = json!({
+            "positions": { "KXTICKER": { "description": "d", "status": "open" },
+                           "M": { "market_id": "M", "trades": [{ "side": "yes" }] } },
+            "daily_realized_pnl": 0.0, "trading_date": "2025-12-01", "all_time_pnl": 1.5,
+        });
+        assert_eq!(upgrade(&mut file, None).unwrap(), 0);
+        assert_eq!(version_of(&file), SCHEMA_VERSION);
+        assert_eq!(file["positions"]["KXTICKER"]["market_id"], "KXTICKER");
+        assert_eq!(file["positions"]["KXTICKER"]["trades"], json!([]));
+        assert_eq!(file["positions"]["M"]["trades"][0]["order_id"], "");
+    }
+
+    #[test]
+    fn test_refuses_newer_versions() {
+        let mut file = json!({ "schema_version": SCHEMA_VERSION + 1, "positions": {} });
+        let err = upgrade(&mut file, None).unwrap_err().to_string();
+        assert!(err.contains("newer than this build"));
+        // Left as found
+        assert_eq!(version_of(&file), SCHEMA_VERSION + 1);
+    }
+
+    #[test]
+    fn test_current_version_unchanged() {
+        let mut file = json!({ "schema_version": SCHEMA_VERSION, "positions": { "M": { "market_id": "M" } } });
+        let before = file.clone();
+        assert_eq!(upgrade(&mut file, None).unwrap(), SCHEMA_VERSION);
+        assert_eq!(file, before);
+        // A broken old file is an error, not an empty tracker
+        assert!(upgrade(&mut json!({ "positions": [] }), None).is_err());
+    }
+
+    #[test]
+    fn test_moves_question_ids_to_market_keys() {
+        let question = "Bitcoin Up or Down - December 18, 9:00AM-9:15AM ET";
+        let key = MarketKey::polymarket("btc-updown-15m-1766068200", "", "", 1766068200);
+        let taken = MarketKey::polymarket("eth-updown-15m-1766068200", "", "", 1766068200);
+        let mut file = json!({ "schema_version": 1, "positions": {
+            question: { "market_id": question, "description": question, "opened_at": "2025-12-18T14:05:00Z" },
+            "Ethereum Up or Down": { "market_id": "Ethereum Up or Down", "description": "Ethereum Up or Down" },
+            taken.id(): { "market_id": taken.id(), "key": taken },
+            "KXTICKER": { "market_id": "KXTICKER", "description": "Kalshi" },
+        }});
+        let legacy = |description: &str, _: &str| match description {
+            d if d == question => Some(key.clone()),
+            "Ethereum Up or Down" => Some(taken.clone()),
+            _ => None,
+        };
+        // Without the owner's derivation nothing moves
+        let mut unkeyed = file.clone();
+        upgrade(&mut unkeyed, None).unwrap();
+        assert!(unkeyed["positions"].get(question).is_some());
+
+        assert_eq!(upgrade(&mut file, Some(&legacy)).unwrap(), 1);
+        let positions = file["positions"].as_object().unwrap();
+        assert!(positions.get(question).is_none());
+        assert_eq!(positions[&key.id()]["market_id"], key.id());
+        assert_eq!(serde_json::from_value::<MarketKey>(positions[&key.id()]["key"].clone()).unwrap(), key);
+        // A taken key is never overwritten, and what can't be placed stays
+        assert!(positions.contains_key("Ethereum Up or Down") && positions.contains_key("KXTICKER"));
+    }
+}
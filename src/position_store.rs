@@ -0,0 +1,395 @@
+// src/position_store.rs
+// Position stores - the JSON file by default; POSITION_STORE=sqlite / postgres keeps positions
+// and fills in a database instead (shareable by several bots)
+
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::exec_mode;
+use crate::fill_detail::FillDetail;
+use crate::position_tracker::{ArbPosition, FillRecord, PositionTracker};
+use crate::sql::{SqlConn, SqlValue};
+use crate::storage;
+
+/// Tables of the SQL backends (same DDL for SQLite and Postgres); positions keep the
+/// JSON file's record as `body`, with the columns aggregation filters on alongside
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS positions (
+    bot TEXT NOT NULL,
+    market_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    opened_at TEXT NOT NULL,
+    realized_pnl DOUBLE PRECISION,
+    body TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (bot, market_id)
+);
+CREATE TABLE IF NOT EXISTS fills (
+    bot TEXT NOT NULL,
+    market_id TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    side TEXT NOT NULL,
+    outcome TEXT,
+    contracts DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    fees DOUBLE PRECISION NOT NULL,
+    order_id TEXT NOT NULL,
+    ts TEXT NOT NULL,
+    detail TEXT
+);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackend {
+    /// positions.json (the tracker's own file)
+    Json,
+    /// A SQLite database file
+    Sqlite,
+    /// A Postgres database
+    Postgres,
+}
+
+impl StoreBackend {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "json" | "file" => Some(StoreBackend::Json),
+            "sqlite" => Some(StoreBackend::Sqlite),
+            "postgres" | "postgresql" => Some(StoreBackend::Postgres),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StoreBackend::Json => "json",
+            StoreBackend::Sqlite => "sqlite",
+            StoreBackend::Postgres => "postgres",
+        }
+    }
+}
+
+/// Position store configuration from environment
+#[derive(Debug, Clone)]
+pub struct PositionStoreConfig {
+    pub backend: StoreBackend,
+
+    /// SQLite file (under DATA_DIR unless absolute) or Postgres connection URL
+    pub url: Option<String>,
+
+    /// Name this bot's rows are stored under in a shared database
+    pub bot: String,
+}
+
+impl PositionStoreConfig {
+    pub fn from_env() -> Self {
+        Self {
+            backend: std::env::var("POSITION_STORE")
+                .ok()
+                .and_then(|v| StoreBackend::parse(&v))
+                .unwrap_or(StoreBackend::Json),
+
+            url: std::env::var("POSITION_STORE_URL").ok().filter(|v| !v.trim().is_empty()),
+
+            bot: exec_mode::state_name(&std::env::var("POSITION_STORE_BOT")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| "default".to_string())),
+        }
+    }
+}
+
+/// How a position resolved
+#[derive(Debug, Clone, PartialEq)]
+pub enum Settlement {
+    Binary { yes_won: bool },
+    /// Winner of a multi-outcome event
+    Outcome(String),
+}
+
+impl Settlement {
+    fn apply(&self, position: &mut ArbPosition) {
+        match self {
+            Settlement::Binary { yes_won } => position.resolve(*yes_won),
+            Settlement::Outcome(winner) => position.resolve_outcome(winner),
+        }
+    }
+}
+
+/// Where positions are persisted
+pub trait PositionStore: Send {
+    fn backend(&self) -> StoreBackend;
+
+    /// Add a fill to its position (opened on the first fill)
+    fn append_fill(&mut self, fill: &FillRecord) -> Result<()>;
+
+    /// Replace the detail of an order's fills (the trades behind it arrive after the fill)
+    fn attach_detail(&mut self, order_id: &str, detail: &FillDetail) -> Result<()>;
+
+    /// Resolve a position, returning its realized P&L (None if the store has no such position)
+    fn settle(&mut self, market_id: &str, settlement: &Settlement) -> Result<Option<f64>>;
+
+    /// Positions with `status` ("open", "resolved"; None = all), oldest first
+    fn query(&self, status: Option<&str>) -> Result<Vec<ArbPosition>>;
+}
+
+/// The JSON position file
+pub struct JsonFileStore {
+    tracker: PositionTracker,
+}
+
+impl JsonFileStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self { tracker: PositionTracker::load_from(path)? })
+    }
+}
+
+impl PositionStore for JsonFileStore {
+    fn backend(&self) -> StoreBackend {
+        StoreBackend::Json
+    }
+
+    fn append_fill(&mut self, fill: &FillRecord) -> Result<()> {
+        self.tracker.record_fill_internal(fill);
+        self.tracker.save()
+    }
+
+    fn attach_detail(&mut self, order_id: &str, detail: &FillDetail) -> Result<()> {
+        if self.tracker.attach_detail(order_id, detail) {
+            self.tracker.save()?;
+        }
+        Ok(())
+    }
+
+    fn settle(&mut self, market_id: &str, settlement: &Settlement) -> Result<Option<f64>> {
+        let pnl = self.tracker.settle(market_id, settlement);
+        if pnl.is_some() {
+            self.tracker.save()?;
+        }
+        Ok(pnl)
+    }
+
+    fn query(&self, status: Option<&str>) -> Result<Vec<ArbPosition>> {
+        Ok(self.tracker.all_positions().into_iter()
+            .filter(|p| status.is_none_or(|s| p.status == s))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Positions in SQLite or Postgres (shareable by several bots, each under its own name),
+/// through the database driver with every value bound as a parameter
+pub struct SqlStore {
+    db: SqlConn,
+    bot: String,
+}
+
+fn row_text(row: &[SqlValue], i: usize) -> Result<&str> {
+    row.get(i).and_then(SqlValue::as_str).ok_or_else(|| anyhow!("position row: column {} is not text", i))
+}
+
+impl SqlStore {
+    /// Open `target` (SQLite file or Postgres URL) and create the tables if they don't exist
+    pub fn open(backend: StoreBackend, target: &str, bot: &str) -> Result<Self> {
+        let db = SqlConn::open(backend, target)?;
+        db.batch(SCHEMA).with_context(|| format!("creating position tables in {} store", backend.name()))?;
+        Ok(Self { db, bot: bot.to_string() })
+    }
+
+    fn select(&self, condition: &str, params: &[SqlValue]) -> Result<Vec<ArbPosition>> {
+        let mut bound = vec![SqlValue::from(self.bot.as_str())];
+        bound.extend_from_slice(params);
+        self.db.query(&format!("SELECT body FROM positions WHERE bot = $1{} ORDER BY opened_at, market_id", condition), &bound)?
+            .iter()
+            .map(|row| serde_json::from_str(row_text(row, 0)?).context("position row"))
+            .collect()
+    }
+
+    /// Every bot's positions in the shared database, as (bot, position)
+    pub fn positions_by_bot(&self) -> Result<Vec<(String, ArbPosition)>> {
+        self.db.query("SELECT bot, body FROM positions ORDER BY bot, opened_at, market_id", &[])?
+            .iter()
+            .map(|row| Ok((row_text(row, 0)?.to_string(), serde_json::from_str(row_text(row, 1)?).context("position row")?)))
+            .collect()
+    }
+
+    fn position(&self, market_id: &str) -> Result<Option<ArbPosition>> {
+        Ok(self.select(" AND market_id = $2", &[market_id.into()])?.pop())
+    }
+
+    fn upsert(&self, position: &ArbPosition) -> Result<()> {
+        self.db.execute(
+            "INSERT INTO positions (bot, market_id, status, opened_at, realized_pnl, body, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             ON CONFLICT (bot, market_id) DO UPDATE SET status = excluded.status, \
+             realized_pnl = excluded.realized_pnl, body = excluded.body, updated_at = excluded.updated_at",
+            &[
+                self.bot.as_str().into(), position.market_id.as_str().into(), position.status.as_str().into(),
+                position.opened_at.as_str().into(), position.realized_pnl.into(), serde_json::to_string(position)?.into(),
+                chrono::Utc::now().to_rfc3339().into(),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl PositionStore for SqlStore {
+    fn backend(&self) -> StoreBackend {
+        self.db.backend()
+    }
+
+    fn append_fill(&mut self, fill: &FillRecord) -> Result<()> {
+        let detail = (!fill.detail.is_empty()).then(|| serde_json::to_string(&fill.detail)).transpose()?;
+        self.db.transaction(|db| {
+            let mut position = self.position(&fill.market_id)?
+                .unwrap_or_else(|| ArbPosition::new(&fill.market_id, &fill.description));
+            position.apply_fill(fill);
+            db.execute(
+                "INSERT INTO fills (bot, market_id, platform, side, outcome, contracts, price, fees, order_id, ts, detail) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+                &[
+                    self.bot.as_str().into(), fill.market_id.as_str().into(), fill.platform.as_str().into(),
+                    fill.side.as_str().into(), fill.outcome.as_deref().into(), fill.contracts.into(), fill.price.into(),
+                    fill.fees.into(), fill.order_id.as_str().into(), fill.timestamp.as_str().into(), detail.into(),
+                ],
+            )?;
+            self.upsert(&position)
+        })
+    }
+
+    fn attach_detail(&mut self, order_id: &str, detail: &FillDetail) -> Result<()> {
+        let body = serde_json::to_string(detail)?;
+        self.db.transaction(|db| {
+            let markets = db.query("SELECT DISTINCT market_id FROM fills WHERE bot = $1 AND order_id = $2",
+                                   &[self.bot.as_str().into(), order_id.into()])?;
+            for row in &markets {
+                if let Some(mut position) = self.position(row_text(row, 0)?)? {
+                    position.attach_detail(order_id, detail);
+                    self.upsert(&position)?;
+                }
+            }
+            db.execute("UPDATE fills SET detail = $3 WHERE bot = $1 AND order_id = $2",
+                       &[self.bot.as_str().into(), order_id.into(), body.into()])?;
+            Ok(())
+        })
+    }
+
+    fn settle(&mut self, market_id: &str, settlement: &Settlement) -> Result<Option<f64>> {
+        self.db.transaction(|_| {
+            let Some(mut position) = self.position(market_id)? else { return Ok(None) };
+            settlement.apply(&mut position);
+            self.upsert(&position)?;
+            Ok(position.realized_pnl)
+        })
+    }
+
+    fn query(&self, status: Option<&str>) -> Result<Vec<ArbPosition>> {
+        match status {
+            Some(s) => self.select(" AND status = $2", &[s.into()]),
+            None => self.select("", &[]),
+        }
+    }
+}
+
+/// The SQL store of record configured by POSITION_STORE (None with POSITION_STORE=json, where
+/// the tracker's own file is the record)
+pub fn open_store(config: &PositionStoreConfig) -> Result<Option<SqlStore>> {
+    let store = match config.backend {
+        StoreBackend::Json => return Ok(None),
+        StoreBackend::Sqlite => SqlStore::open(StoreBackend::Sqlite, &sqlite_path(config).to_string_lossy(), &config.bot)?,
+        StoreBackend::Postgres => {
+            let url = config.url.as_deref().ok_or_else(|| anyhow!("POSITION_STORE=postgres needs POSITION_STORE_URL"))?;
+            SqlStore::open(StoreBackend::Postgres, url, &config.bot)?
+        }
+    };
+    info!("[POSITIONS] Positions are kept in the {} database as bot {:?}", config.backend.name(), config.bot);
+    Ok(Some(store))
+}
+
+/// SQLite file a relative `POSITION_STORE_URL` resolves to
+pub fn sqlite_path(config: &PositionStoreConfig) -> PathBuf {
+    storage::state_path(config.url.as_deref().unwrap_or("positions.db"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("position_store_test_{}_{}_{}", name, std::process::id(), nanos))
+    }
+
+    /// The suite every backend passes
+    fn conformance(store: &mut dyn PositionStore) {
+        let q = "Will the Fed's December decision be a cut?";
+        let detail = FillDetail::from_kalshi("executed", 10, 0);
+        store.append_fill(&FillRecord::new("fed-dec", q, "kalshi", "yes", 10.0, 0.40, 0.07, "k1").with_detail(&detail)).unwrap();
+        store.append_fill(&FillRecord::new("fed-dec", q, "polymarket", "no", 10.0, 0.55, 0.0, "p1")).unwrap();
+        store.append_fill(&FillRecord::new("btc-1", "Bitcoin Up or Down", "polymarket", "yes", 5.0, 0.48, 0.0, "p2")).unwrap();
+
+        let all = store.query(None).unwrap();
+        assert_eq!(all.len(), 2);
+        let fed = all.iter().find(|p| p.market_id == "fed-dec").unwrap();
+        assert_eq!(fed.description, q);
+        assert_eq!((fed.kalshi_yes.contracts, fed.poly_no.contracts), (10.0, 10.0));
+        assert_eq!(fed.trades.len(), 2);
+        assert_eq!(fed.trades[0].detail, detail);
+        assert!((fed.total_fees - 0.07).abs() < 1e-9);
+
+        // Trades looked up after the fill was recorded land on that fill
+        let late = FillDetail { trade_ids: vec!["t1".into()], ..Default::default() };
+        store.attach_detail("p1", &late).unwrap();
+        let fed = store.query(None).unwrap().into_iter().find(|p| p.market_id == "fed-dec").unwrap();
+        assert_eq!(fed.trades[1].detail, late);
+        assert_eq!(fed.trades[0].detail, detail);
+
+        let pnl = store.settle("fed-dec", &Settlement::Binary { yes_won: false }).unwrap().unwrap();
+        assert!((pnl - (10.0 - 9.5 - 0.07)).abs() < 1e-9);  // fees count against it
+        assert_eq!(store.settle("unknown", &Settlement::Binary { yes_won: true }).unwrap(), None);
+
+        let open = store.query(Some("open")).unwrap();
+        assert_eq!(open.iter().map(|p| p.market_id.as_str()).collect::<Vec<_>>(), vec!["btc-1"]);
+        let resolved = store.query(Some("resolved")).unwrap();
+        assert_eq!((resolved.len(), resolved[0].outcome_yes), (1, Some(false)));
+    }
+
+    #[test]
+    fn test_json_store() {
+        let path = temp("json").with_extension("json");
+        conformance(&mut JsonFileStore::open(&path).unwrap());
+        // What the store wrote is the tracker's file
+        assert_eq!(JsonFileStore::open(&path).unwrap().query(Some("resolved")).unwrap().len(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_sqlite_store() {
+        let path = temp("sqlite").with_extension("db");
+        let target = path.to_string_lossy();
+        conformance(&mut SqlStore::open(StoreBackend::Sqlite, &target, "a").unwrap());
+        // Bots sharing the database only see their own positions
+        let b = SqlStore::open(StoreBackend::Sqlite, &target, "b").unwrap();
+        assert!(b.query(None).unwrap().is_empty());
+        // ...while the aggregator reads them all
+        let rows = b.positions_by_bot().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|(bot, _)| bot == "a"));
+        // Quotes in ids and descriptions are data, not SQL
+        let mut c = SqlStore::open(StoreBackend::Sqlite, &target, "it's").unwrap();
+        c.append_fill(&FillRecord::new("m'1", "Won't it?'; --", "kalshi", "yes", 1.0, 0.5, 0.0, "o'1")).unwrap();
+        assert_eq!(c.query(Some("open")).unwrap()[0].description, "Won't it?'; --");
+        drop((b, c));
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a scratch Postgres database in POSITION_STORE_TEST_URL"]
+    fn test_postgres_store() {
+        let url = std::env::var("POSITION_STORE_TEST_URL").expect("POSITION_STORE_TEST_URL=postgres://localhost/arb_test");
+        let bot = temp("pg").file_name().unwrap().to_string_lossy().into_owned();
+        conformance(&mut SqlStore::open(StoreBackend::Postgres, &url, &bot).unwrap());
+    }
+}
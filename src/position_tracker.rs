@@ -1,17 +1,29 @@
 // src/position_tracker.rs
 // Track positions, cost basis, and P&L across both platforms
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
-use tokio::sync::{mpsc, RwLock};
-use tracing::{info, warn};
+use tokio::sync::{mpsc, watch, RwLock};
+use tracing::{error, info, warn};
+
+use crate::fill_detail::FillDetail;
+use crate::market_key::MarketKey;
+use crate::metrics;
+use crate::position_schema::{self, LegacyKey, SCHEMA_VERSION};
+use crate::position_store::{PositionStore, Settlement};
+use crate::runtimes;
+use crate::storage::{self, FileLock};
 
 const POSITION_FILE: &str = "positions.json";
 
+/// Details kept for fills not recorded yet before the oldest is dropped
+const EARLY_DETAILS_MAX: usize = 64;
+
 /// A single position leg on one platform
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PositionLeg {
@@ -25,11 +37,13 @@ pub struct PositionLeg {
 
 #[allow(dead_code)]
 impl PositionLeg {
+    /// Record a buy (or a sale, with negative contracts: proceeds reduce the net cost
+    /// basis and the entry price of the remaining contracts is kept)
     pub fn add(&mut self, contracts: f64, price: f64) {
         let new_cost = contracts * price;
         self.cost_basis += new_cost;
         self.contracts += contracts;
-        if self.contracts > 0.0 {
+        if contracts > 0.0 && self.contracts > 0.0 {
             self.avg_price = self.cost_basis / self.contracts;
         }
     }
@@ -51,11 +65,46 @@ impl PositionLeg {
     }
 }
 
+/// One fill as it happened (negative contracts = sale)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TradeRecord {
+    pub timestamp: String,
+    pub platform: String,
+    pub side: String,
+    pub contracts: f64,
+    pub price: f64,
+    pub fees: f64,
+    #[serde(default)]
+    pub order_id: String,
+    /// Outcome of a multi-outcome event the fill was in (side is then that outcome's yes/no)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+    /// Exchange trade IDs, maker/taker side and status transitions of the order
+    #[serde(default, skip_serializing_if = "FillDetail::is_empty")]
+    pub detail: FillDetail,
+    /// Laddered execution the order was a child order of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// Holdings in one outcome of a multi-outcome (neg-risk) event
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OutcomeLegs {
+    /// Pays $1 if this outcome wins
+    pub yes: PositionLeg,
+    /// Pays $1 if any other outcome wins
+    pub no: PositionLeg,
+}
+
 /// A paired position (arb position spans both platforms)
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ArbPosition {
-    /// Market identifier (Kalshi ticker)
+    /// Market identifier (Kalshi ticker, or `MarketKey::id()`)
     pub market_id: String,
+
+    /// Full key of the market (venue, slug, tokens, end time) where one is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<MarketKey>,
     
     /// Description for logging
     pub description: String,
@@ -83,6 +132,31 @@ pub struct ArbPosition {
     
     /// Realized P&L (set when position closes/resolves)
     pub realized_pnl: Option<f64>,
+
+    /// Every fill in order (per-lot cost basis for tax reporting)
+    #[serde(default)]
+    pub trades: Vec<TradeRecord>,
+
+    /// Resolution outcome (true = YES won)
+    #[serde(default)]
+    pub outcome_yes: Option<bool>,
+
+    /// When the position was resolved
+    #[serde(default)]
+    pub resolved_at: Option<String>,
+
+    /// Per-outcome holdings of a multi-outcome event (empty for binary markets,
+    /// which use the four legs above)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outcomes: BTreeMap<String, OutcomeLegs>,
+
+    /// Winning outcome of a resolved multi-outcome event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winning_outcome: Option<String>,
+
+    /// Every outcome of a multi-outcome event, held or not (empty when not known)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_outcomes: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -100,15 +174,47 @@ impl ArbPosition {
     /// Total contracts across all legs
     pub fn total_contracts(&self) -> f64 {
         self.kalshi_yes.contracts + self.kalshi_no.contracts +
-        self.poly_yes.contracts + self.poly_no.contracts
+        self.poly_yes.contracts + self.poly_no.contracts +
+        self.outcomes.values().map(|o| o.yes.contracts + o.no.contracts).sum::<f64>()
     }
     
     /// Total cost basis across all legs
     pub fn total_cost(&self) -> f64 {
         self.kalshi_yes.cost_basis + self.kalshi_no.cost_basis +
         self.poly_yes.cost_basis + self.poly_no.cost_basis +
+        self.outcomes.values().map(|o| o.yes.cost_basis + o.no.cost_basis).sum::<f64>() +
         self.total_fees
     }
+
+    /// Whether the position's market has ended by `now_ts` (Unix seconds); false when its end isn't known
+    pub fn ended(&self, now_ts: i64) -> bool {
+        self.key.as_ref().is_some_and(|k| k.end_ts > 0 && k.end_ts <= now_ts)
+    }
+
+    /// Position in a multi-outcome event (outcome legs instead of yes/no legs)
+    pub fn is_multi_outcome(&self) -> bool {
+        !self.outcomes.is_empty()
+    }
+
+    /// Payout of a multi-outcome position if `winner` wins: its YES plus every other outcome's NO
+    pub fn payout_if(&self, winner: &str) -> f64 {
+        self.outcomes.iter()
+            .map(|(name, legs)| if name == winner { legs.yes.contracts } else { legs.no.contracts })
+            .sum()
+    }
+
+    /// (worst, best) payout over the possible winners of a multi-outcome position. An outcome
+    /// nothing is held in pays only the NO legs; when the event's outcomes aren't known, one
+    /// such outcome is assumed to exist.
+    fn payout_range(&self) -> (f64, f64) {
+        let unheld = self.event_outcomes.iter().any(|w| !self.outcomes.contains_key(w))
+            || self.event_outcomes.is_empty();
+        let unheld_payout = unheld.then(|| self.outcomes.values().map(|l| l.no.contracts).sum::<f64>());
+        self.outcomes.keys()
+            .map(|w| self.payout_if(w))
+            .chain(unheld_payout)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p), hi.max(p)))
+    }
     
     /// For a proper arb (YES on one platform + NO on other), one side always wins
     /// This calculates the guaranteed profit assuming the arb is balanced
@@ -119,20 +225,96 @@ impl ArbPosition {
         balanced_contracts - self.total_cost()
     }
     
-    /// Number of matched contract pairs (min of YES and NO across platforms)
+    /// Number of matched contract pairs (min of YES and NO across platforms); for a
+    /// multi-outcome basket, the payout every possible winner guarantees
     pub fn matched_contracts(&self) -> f64 {
+        if self.is_multi_outcome() {
+            return self.payout_range().0;
+        }
         let yes_total = self.kalshi_yes.contracts + self.poly_yes.contracts;
         let no_total = self.kalshi_no.contracts + self.poly_no.contracts;
         yes_total.min(no_total)
     }
     
-    /// Unmatched exposure (contracts without offsetting position)
+    /// Unmatched exposure (contracts without offsetting position); for a basket,
+    /// how much the payout depends on which outcome wins
     pub fn unmatched_exposure(&self) -> f64 {
+        if self.is_multi_outcome() {
+            let (worst, best) = self.payout_range();
+            return best - worst;
+        }
         let yes_total = self.kalshi_yes.contracts + self.poly_yes.contracts;
         let no_total = self.kalshi_no.contracts + self.poly_no.contracts;
         (yes_total - no_total).abs()
     }
     
+    /// Net directional exposure: positive = excess YES, negative = excess NO
+    pub fn net_exposure(&self) -> f64 {
+        (self.kalshi_yes.contracts + self.poly_yes.contracts)
+            - (self.kalshi_no.contracts + self.poly_no.contracts)
+    }
+
+    /// Strategy inferred from which platforms hold legs:
+    /// "cross" (Kalshi + Polymarket), "poly_only", "kalshi_only", "basket" (multi-outcome) or "empty"
+    pub fn strategy(&self) -> &'static str {
+        if self.is_multi_outcome() {
+            return "basket";
+        }
+        let has_kalshi = self.kalshi_yes.contracts > 0.0 || self.kalshi_no.contracts > 0.0;
+        let has_poly = self.poly_yes.contracts > 0.0 || self.poly_no.contracts > 0.0;
+        match (has_kalshi, has_poly) {
+            (true, true) => "cross",
+            (false, true) => "poly_only",
+            (true, false) => "kalshi_only",
+            (false, false) => "empty",
+        }
+    }
+
+    /// Replace the detail of the trades `order_id` filled; false if it has none here
+    pub fn attach_detail(&mut self, order_id: &str, detail: &FillDetail) -> bool {
+        let mut found = false;
+        for trade in self.trades.iter_mut().filter(|t| t.order_id == order_id) {
+            trade.detail = detail.clone();
+            found = true;
+        }
+        found
+    }
+
+    /// Add a fill to its leg, fees and trade history
+    pub fn apply_fill(&mut self, fill: &FillRecord) {
+        match (fill.outcome.as_deref(), fill.platform.as_str(), fill.side.as_str()) {
+            (Some(outcome), _, "yes") => self.outcomes.entry(outcome.to_string()).or_default().yes.add(fill.contracts, fill.price),
+            (Some(outcome), _, "no") => self.outcomes.entry(outcome.to_string()).or_default().no.add(fill.contracts, fill.price),
+            (None, "kalshi", "yes") => self.kalshi_yes.add(fill.contracts, fill.price),
+            (None, "kalshi", "no") => self.kalshi_no.add(fill.contracts, fill.price),
+            (None, "polymarket", "yes") => self.poly_yes.add(fill.contracts, fill.price),
+            (None, "polymarket", "no") => self.poly_no.add(fill.contracts, fill.price),
+            _ => warn!("[POSITIONS] Unknown platform/side: {}/{}", fill.platform, fill.side),
+        }
+
+        if self.key.is_none() {
+            self.key = fill.key.clone();
+        }
+        for outcome in &fill.event_outcomes {
+            if !self.event_outcomes.contains(outcome) {
+                self.event_outcomes.push(outcome.clone());
+            }
+        }
+        self.total_fees += fill.fees;
+        self.trades.push(TradeRecord {
+            timestamp: fill.timestamp.clone(),
+            platform: fill.platform.clone(),
+            side: fill.side.clone(),
+            contracts: fill.contracts,
+            price: fill.price,
+            fees: fill.fees,
+            order_id: fill.order_id.clone(),
+            outcome: fill.outcome.clone(),
+            detail: fill.detail.clone(),
+            parent: fill.parent.clone(),
+        });
+    }
+
     /// Mark position as resolved with outcome
     pub fn resolve(&mut self, outcome_yes_won: bool) {
         let payout = if outcome_yes_won {
@@ -145,6 +327,16 @@ impl ArbPosition {
         
         self.realized_pnl = Some(payout - self.total_cost());
         self.status = "resolved".to_string();
+        self.outcome_yes = Some(outcome_yes_won);
+        self.resolved_at = Some(chrono::Utc::now().to_rfc3339());
+    }
+
+    /// Mark a multi-outcome position as resolved with `winner` winning
+    pub fn resolve_outcome(&mut self, winner: &str) {
+        self.realized_pnl = Some(self.payout_if(winner) - self.total_cost());
+        self.status = "resolved".to_string();
+        self.winning_outcome = Some(winner.to_string());
+        self.resolved_at = Some(chrono::Utc::now().to_rfc3339());
     }
 }
 
@@ -177,6 +369,10 @@ pub struct PositionSummary {
 /// Position tracker with persistence
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PositionTracker {
+    /// File layout version (see position_schema)
+    #[serde(default)]
+    schema_version: u32,
+
     /// All positions keyed by market_id
     positions: HashMap<String, ArbPosition>,
 
@@ -188,11 +384,40 @@ pub struct PositionTracker {
 
     /// Cumulative all-time P&L
     pub all_time_pnl: f64,
+
+    /// File this tracker persists to (default: positions.json in the data directory)
+    #[serde(skip)]
+    path: Option<PathBuf>,
+
+    /// Advisory lock held while this process owns the file
+    #[serde(skip)]
+    lock: Option<FileLock>,
+
+    /// Warm standby: the file belongs to the leader, so saves are skipped
+    #[serde(skip)]
+    standby: bool,
+
+    /// Orders background saves, so an older snapshot never overwrites a newer one
+    #[serde(skip)]
+    saves: Arc<SaveSequence>,
+
+    /// Details of orders whose fills haven't been recorded yet (a trade lookup can beat its
+    /// fill to the writer), oldest first
+    #[serde(skip)]
+    early_details: Vec<(String, FillDetail)>,
+}
+
+/// Generation of each save: issued when the snapshot is taken, written under the lock
+#[derive(Debug, Default)]
+struct SaveSequence {
+    issued: AtomicU64,
+    written: Mutex<u64>,
 }
 
 /// Data structure for serialization
 #[derive(Serialize)]
 struct SaveData {
+    schema_version: u32,
     positions: HashMap<String, ArbPosition>,
     daily_realized_pnl: f64,
     trading_date: String,
@@ -209,77 +434,183 @@ impl Default for PositionTracker {
 impl PositionTracker {
     pub fn new() -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
             positions: HashMap::new(),
             daily_realized_pnl: 0.0,
             trading_date: today_string(),
             all_time_pnl: 0.0,
+            path: None,
+            lock: None,
+            standby: false,
+            saves: Arc::default(),
+            early_details: Vec::new(),
         }
     }
     
-    /// Load from file or create new
-    pub fn load() -> Self {
-        Self::load_from(POSITION_FILE)
+    /// Load from file or create new (fails on a file of a newer schema)
+    pub fn load() -> Result<Self> {
+        Self::load_from(storage::state_path(POSITION_FILE))
     }
-    
-    pub fn load_from<P: AsRef<Path>>(path: P) -> Self {
-        match std::fs::read_to_string(path.as_ref()) {
-            Ok(contents) => {
-                match serde_json::from_str::<Self>(&contents) {
-                    Ok(mut tracker) => {
-                        // Check if we need to reset daily P&L
-                        let today = today_string();
-                        if tracker.trading_date != today {
-                            info!("[POSITIONS] New trading day, resetting daily P&L");
-                            tracker.daily_realized_pnl = 0.0;
-                            tracker.trading_date = today;
-                        }
-                        info!("[POSITIONS] Loaded {} positions from {:?}", 
-                              tracker.positions.len(), path.as_ref());
-                        tracker
-                    }
-                    Err(e) => {
-                        warn!("[POSITIONS] Failed to parse positions file: {}", e);
-                        Self::new()
-                    }
-                }
+
+    /// Load `path` for exclusive use: fails if another process holds its lock, or if the
+    /// file can't be read or brought to this build's schema. An upgraded file is written back,
+    /// with the old one kept as `<file>.v<version>.bak`; `legacy_key` places positions still
+    /// recorded under their question. The lock is released when the tracker is dropped.
+    pub fn open_locked<P: AsRef<Path>>(path: P, legacy_key: Option<&LegacyKey<'_>>) -> Result<Self> {
+        let path = path.as_ref();
+        let lock = FileLock::acquire(path)?;
+        let (mut tracker, upgraded_from) = Self::read_from(path, legacy_key)?;
+        tracker.path = Some(path.to_path_buf());
+        if let Some(from) = upgraded_from {
+            let backup = PathBuf::from(format!("{}.v{}.bak", path.display(), from));
+            std::fs::copy(path, &backup).with_context(|| format!("backing up {}", path.display()))?;
+            tracker.save()?;
+            info!("[POSITIONS] Upgraded {} from schema {} to {} (previous file kept as {})",
+                  path.display(), from, SCHEMA_VERSION, backup.display());
+        }
+        tracker.lock = Some(lock);
+        Ok(tracker)
+    }
+
+    /// Take the lock on this tracker's file without loading it (fresh session state)
+    pub fn locked(mut self) -> Result<Self> {
+        self.lock = Some(FileLock::acquire(self.path())?);
+        Ok(self)
+    }
+
+    /// Read-only copy for a standby instance: loaded without the lock, never saved
+    pub fn into_standby(mut self) -> Self {
+        self.lock = None;
+        self.standby = true;
+        self
+    }
+
+    /// Take the file over from a leader that stepped down: lock it and reload what it wrote
+    /// (upgraded as in `open_locked`)
+    pub fn promote(&mut self, legacy_key: Option<&LegacyKey<'_>>) -> Result<()> {
+        let path = self.path();
+        let lock = FileLock::acquire(&path)?;
+        let (mut fresh, upgraded_from) = Self::read_from(&path, legacy_key)?;
+        fresh.path = Some(path);
+        fresh.lock = Some(lock);
+        if upgraded_from.is_some() {
+            fresh.save()?;
+        }
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Hand the file back (this instance lost the lease), writing it first so the next
+    /// leader loads every fill this one recorded
+    pub fn demote(&mut self) {
+        if !self.standby {
+            if let Some(write) = self.snapshot_writer() {
+                write();
             }
-            Err(_) => {
+        }
+        self.lock = None;
+        self.standby = true;
+    }
+
+    pub fn is_standby(&self) -> bool {
+        self.standby
+    }
+
+    /// File saves go to
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| storage::state_path(POSITION_FILE))
+    }
+
+    /// Load `path` (upgraded in memory only - the file belongs to whoever holds its lock)
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let (mut tracker, _) = Self::read_from(path.as_ref(), None)?;
+        tracker.path = Some(path.as_ref().to_path_buf());
+        Ok(tracker)
+    }
+
+    /// The tracker in `path` and, if it had to be upgraded, the schema version it had. A missing
+    /// file is a fresh tracker; one that can't be read or parsed is an error rather than an
+    /// empty tracker that the next save would write over it.
+    fn read_from(path: &Path, legacy_key: Option<&LegacyKey<'_>>) -> Result<(Self, Option<u32>)> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 info!("[POSITIONS] No positions file found, starting fresh");
-                Self::new()
+                return Ok((Self::new(), None));
             }
+            Err(e) => return Err(e).with_context(|| format!("reading positions file {}", path.display())),
+        };
+        let corrupt = || format!("positions file {} is corrupt (restore it from a backup)", path.display());
+        let mut file: serde_json::Value = serde_json::from_str(&contents).with_context(corrupt)?;
+        let from = position_schema::upgrade(&mut file, legacy_key)
+            .with_context(|| format!("positions file {}", path.display()))?;
+        let mut tracker = serde_json::from_value::<Self>(file).with_context(corrupt)?;
+        // Check if we need to reset daily P&L
+        let today = today_string();
+        if tracker.trading_date != today {
+            info!("[POSITIONS] New trading day, resetting daily P&L");
+            tracker.daily_realized_pnl = 0.0;
+            tracker.trading_date = today;
         }
+        info!("[POSITIONS] Loaded {} positions from {:?}",
+              tracker.positions.len(), path);
+        Ok((tracker, (from < SCHEMA_VERSION).then_some(from)))
     }
     
     /// Save to file
     pub fn save(&self) -> Result<()> {
-        self.save_to(POSITION_FILE)
+        if self.standby {
+            return Ok(());
+        }
+        self.save_to(self.path())
     }
     
     pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let json = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, json)?;
-        Ok(())
+        storage::write_atomic(path, json.as_bytes())
     }
     
     /// Save positions
     pub fn save_async(&self) {
+        let Some(write) = self.snapshot_writer() else { return };
+        // Try to spawn on runtime; if no runtime, save synchronously
+        if tokio::runtime::Handle::try_current().is_ok() {
+            runtimes::spawn_blocking_bookkeeping(write);
+        } else {
+            write();
+        }
+    }
+
+    /// Snapshot the positions and return the write that saves them (None on a standby)
+    fn snapshot_writer(&self) -> Option<impl FnOnce() + Send + 'static> {
+        if self.standby {
+            return None;
+        }
         // Clone data for serialization
         let data = SaveData {
+            schema_version: SCHEMA_VERSION,
             positions: self.positions.clone(),
             daily_realized_pnl: self.daily_realized_pnl,
             trading_date: self.trading_date.clone(),
             all_time_pnl: self.all_time_pnl,
         };
-        // Try to spawn on runtime; if no runtime, save synchronously
-        if tokio::runtime::Handle::try_current().is_ok() {
-            tokio::spawn(async move {
-                if let Ok(json) = serde_json::to_string_pretty(&data) {
-                    let _ = tokio::fs::write(POSITION_FILE, json).await;
+        let path = self.path();
+        let saves = self.saves.clone();
+        let generation = saves.issued.fetch_add(1, Ordering::SeqCst) + 1;
+        let write = move || {
+            // One save at a time; one that lost the race to a newer snapshot is dropped
+            let mut written = saves.written.lock().unwrap();
+            if *written >= generation {
+                return;
+            }
+            if let Ok(json) = serde_json::to_string_pretty(&data) {
+                match storage::write_atomic(&path, json.as_bytes()) {
+                    Ok(()) => *written = generation,
+                    Err(e) => warn!("[POSITIONS] Failed to save {:?}: {}", path, e),
                 }
-            });
-        } else if let Ok(json) = serde_json::to_string_pretty(&data) {
-            let _ = std::fs::write(POSITION_FILE, json);
-        }
+            }
+        };
+        Some(write)
     }
     
     /// Record a fill
@@ -290,25 +621,39 @@ impl PositionTracker {
 
     /// Record a fill without saving
     pub fn record_fill_internal(&mut self, fill: &FillRecord) {
-        let position = self.positions
+        self.positions
             .entry(fill.market_id.clone())
-            .or_insert_with(|| ArbPosition::new(&fill.market_id, &fill.description));
-
-        match (fill.platform.as_str(), fill.side.as_str()) {
-            ("kalshi", "yes") => position.kalshi_yes.add(fill.contracts, fill.price),
-            ("kalshi", "no") => position.kalshi_no.add(fill.contracts, fill.price),
-            ("polymarket", "yes") => position.poly_yes.add(fill.contracts, fill.price),
-            ("polymarket", "no") => position.poly_no.add(fill.contracts, fill.price),
-            _ => warn!("[POSITIONS] Unknown platform/side: {}/{}", fill.platform, fill.side),
-        }
+            .or_insert_with(|| ArbPosition::new(&fill.market_id, &fill.description))
+            .apply_fill(fill);
 
-        position.total_fees += fill.fees;
-
-        info!("[POSITIONS] Recorded fill: {} {} {} @{:.1}¢ x{:.0} (fees: ${:.4})",
-              fill.platform, fill.side, fill.market_id,
+        info!("[POSITIONS] Recorded fill: {} {}{} {} @{:.1}¢ x{:.0} (fees: ${:.4})",
+              fill.platform, fill.outcome.as_deref().map(|o| format!("{} ", o)).unwrap_or_default(),
+              fill.side, fill.market_id,
               fill.price * 100.0, fill.contracts, fill.fees);
     }
     
+    /// Replace the detail of an order's recorded fills. True if any were found; otherwise the
+    /// detail is kept for the order's fill when it's recorded.
+    pub fn attach_detail(&mut self, order_id: &str, detail: &FillDetail) -> bool {
+        let mut found = false;
+        for position in self.positions.values_mut() {
+            found |= position.attach_detail(order_id, detail);
+        }
+        if !found {
+            if self.early_details.len() >= EARLY_DETAILS_MAX {
+                self.early_details.remove(0);
+            }
+            self.early_details.push((order_id.to_string(), detail.clone()));
+        }
+        found
+    }
+
+    /// A detail that arrived before the order's fill, if any
+    fn take_early_detail(&mut self, order_id: &str) -> Option<FillDetail> {
+        let i = self.early_details.iter().position(|(id, _)| id == order_id)?;
+        Some(self.early_details.remove(i).1)
+    }
+
     /// Get or create position for a market
     pub fn get_or_create(&mut self, market_id: &str, description: &str) -> &mut ArbPosition {
         self.positions
@@ -340,6 +685,48 @@ impl PositionTracker {
         }
     }
     
+    /// Mark a multi-outcome position as resolved
+    pub fn resolve_outcome(&mut self, market_id: &str, winner: &str) -> Option<f64> {
+        let position = self.positions.get_mut(market_id)?;
+        position.resolve_outcome(winner);
+        let pnl = position.realized_pnl.unwrap_or(0.0);
+
+        self.daily_realized_pnl += pnl;
+        self.all_time_pnl += pnl;
+
+        info!("[POSITIONS] Resolved {}: {} won, P&L: ${:.2}", market_id, winner, pnl);
+
+        self.save_async();
+        Some(pnl)
+    }
+
+    /// Resolve a position however it settled, returning its realized P&L
+    pub fn settle(&mut self, market_id: &str, settlement: &Settlement) -> Option<f64> {
+        match settlement {
+            Settlement::Binary { yes_won } => self.resolve_position(market_id, *yes_won),
+            Settlement::Outcome(winner) => self.resolve_outcome(market_id, winner),
+        }
+    }
+
+    /// Take positions from the store of record in place of the file's, with realized P&L
+    /// recounted from the resolved ones
+    pub fn load_positions(&mut self, positions: Vec<ArbPosition>) {
+        let today = today_string();
+        self.positions = positions.into_iter().map(|p| (p.market_id.clone(), p)).collect();
+        let resolved = self.positions.values().filter(|p| p.status == "resolved");
+        let (mut daily, mut all_time) = (0.0, 0.0);
+        for p in resolved {
+            let pnl = p.realized_pnl.unwrap_or(0.0);
+            all_time += pnl;
+            if p.resolved_at.as_deref().is_some_and(|t| t.starts_with(&today)) {
+                daily += pnl;
+            }
+        }
+        self.all_time_pnl = all_time;
+        self.daily_realized_pnl = daily;
+        self.trading_date = today;
+    }
+
     /// Get summary statistics
     pub fn summary(&self) -> PositionSummary {
         let mut summary = PositionSummary::default();
@@ -371,6 +758,13 @@ impl PositionTracker {
             .collect()
     }
     
+    /// Get all positions (any status), oldest first
+    pub fn all_positions(&self) -> Vec<&ArbPosition> {
+        let mut positions: Vec<_> = self.positions.values().collect();
+        positions.sort_by(|a, b| a.opened_at.cmp(&b.opened_at));
+        positions
+    }
+
     /// Daily P&L (realized only)
     pub fn daily_pnl(&self) -> f64 {
         self.daily_realized_pnl
@@ -394,13 +788,22 @@ pub struct FillRecord {
     pub contracts: f64,
     pub price: f64,
     pub fees: f64,
-    #[allow(dead_code)]
     pub order_id: String,
-    #[allow(dead_code)]
     pub timestamp: String,
+    /// Outcome within a multi-outcome event (None for binary markets)
+    pub outcome: Option<String>,
+    /// Every outcome of that event, when known
+    pub event_outcomes: Vec<String>,
+    /// Key of the market, when the fill was recorded under one
+    pub key: Option<MarketKey>,
+    /// What the exchange reported about the order beyond size and price
+    pub detail: FillDetail,
+    /// Laddered execution the order was a child order of
+    pub parent: Option<String>,
 }
 
 impl FillRecord {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         market_id: &str,
         description: &str,
@@ -421,47 +824,155 @@ impl FillRecord {
             fees,
             order_id: order_id.to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
+            outcome: None,
+            event_outcomes: Vec::new(),
+            key: None,
+            detail: FillDetail::default(),
+            parent: None,
         }
     }
+
+    /// Fill in the market `key` identifies, recorded under its `id()`
+    #[allow(dead_code, clippy::too_many_arguments)]
+    pub fn keyed(
+        key: &MarketKey,
+        description: &str,
+        platform: &str,
+        side: &str,
+        contracts: f64,
+        price: f64,
+        fees: f64,
+        order_id: &str,
+    ) -> Self {
+        Self {
+            key: Some(key.clone()),
+            ..Self::new(&key.id(), description, platform, side, contracts, price, fees, order_id)
+        }
+    }
+
+    /// The fill was in `outcome` of a multi-outcome event
+    #[allow(dead_code)]
+    pub fn in_outcome(mut self, outcome: &str) -> Self {
+        self.outcome = Some(outcome.to_string());
+        self
+    }
+
+    /// The event of the fill's outcome has exactly `outcomes`
+    #[allow(dead_code)]
+    pub fn among(mut self, outcomes: &[&str]) -> Self {
+        self.event_outcomes = outcomes.iter().map(|o| o.to_string()).collect();
+        self
+    }
+
+    /// Attach the exchange's trade IDs / maker-taker side / statuses of the order
+    pub fn with_detail(mut self, detail: &FillDetail) -> Self {
+        self.detail = detail.clone();
+        self
+    }
+
+    /// The order was a child order of the laddered execution `parent` (if any)
+    #[allow(dead_code)]
+    pub fn with_parent(mut self, parent: Option<&str>) -> Self {
+        self.parent = parent.map(String::from);
+        self
+    }
 }
 
 #[allow(dead_code)]
 pub type SharedPositionTracker = Arc<RwLock<PositionTracker>>;
 
 #[allow(dead_code)]
-pub fn create_position_tracker() -> SharedPositionTracker {
-    Arc::new(RwLock::new(PositionTracker::load()))
+pub fn create_position_tracker() -> Result<SharedPositionTracker> {
+    Ok(Arc::new(RwLock::new(PositionTracker::load()?)))
 }
 
 fn today_string() -> String {
     chrono::Utc::now().format("%Y-%m-%d").to_string()
 }
 
+/// A change sent to the position writer
+#[derive(Debug, Clone)]
+pub enum PositionUpdate {
+    Fill(Box<FillRecord>),
+    Settle { market_id: String, settlement: Settlement },
+    /// Completed detail of an order already sent as a fill
+    Detail { order_id: String, detail: FillDetail },
+}
+
+/// Channel the trade lookups of filled orders report to (see `attach_fill_detail`)
+static FILL_DETAILS: OnceLock<PositionChannel> = OnceLock::new();
+
+/// Attach the completed detail of an order to its fill, through the writer of the channel
+/// that collects them (dropped if none does)
+pub fn attach_fill_detail(order_id: &str, detail: FillDetail) {
+    if let Some(channel) = FILL_DETAILS.get() {
+        channel.send(PositionUpdate::Detail { order_id: order_id.to_string(), detail });
+    }
+}
+
 #[derive(Clone)]
 pub struct PositionChannel {
-    tx: mpsc::UnboundedSender<FillRecord>,
+    tx: mpsc::UnboundedSender<PositionUpdate>,
+    /// Updates sent, and applied by the writer, so `flush` can wait for it to catch up
+    sent: Arc<AtomicU64>,
+    #[allow(dead_code)] // read by `flush`
+    applied: watch::Receiver<u64>,
 }
 
-impl PositionChannel {
-    pub fn new(tx: mpsc::UnboundedSender<FillRecord>) -> Self {
-        Self { tx }
-    }
+/// The writer's end of a `PositionChannel`
+pub struct PositionReceiver {
+    rx: mpsc::UnboundedReceiver<PositionUpdate>,
+    applied: watch::Sender<u64>,
+}
 
+impl PositionChannel {
     #[inline]
     pub fn record_fill(&self, fill: FillRecord) {
-        let _ = self.tx.send(fill);
+        self.send(PositionUpdate::Fill(Box::new(fill)));
+    }
+
+    /// Resolve a position in the tracker and the store
+    pub fn settle(&self, market_id: &str, settlement: Settlement) {
+        self.send(PositionUpdate::Settle { market_id: market_id.to_string(), settlement });
+    }
+
+    /// Make this channel the one order details found after the fill are sent to
+    pub fn collect_fill_details(&self) {
+        let _ = FILL_DETAILS.set(self.clone());
+    }
+
+    fn send(&self, update: PositionUpdate) {
+        if self.tx.send(update).is_ok() {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Wait (up to `timeout`) until the writer has applied every update sent so far to the
+    /// tracker. False if it didn't catch up in time.
+    #[allow(dead_code)] // the main bot has no lease to hand over
+    pub async fn flush(&self, timeout: Duration) -> bool {
+        let target = self.sent.load(Ordering::SeqCst);
+        let mut applied = self.applied.clone();
+        let caught_up = async { applied.wait_for(|n| *n >= target).await.is_ok() };
+        tokio::time::timeout(timeout, caught_up).await.unwrap_or(false)
     }
 }
 
-pub fn create_position_channel() -> (PositionChannel, mpsc::UnboundedReceiver<FillRecord>) {
+pub fn create_position_channel() -> (PositionChannel, PositionReceiver) {
     let (tx, rx) = mpsc::unbounded_channel();
-    (PositionChannel::new(tx), rx)
+    let (applied_tx, applied) = watch::channel(0);
+    (PositionChannel { tx, sent: Arc::default(), applied }, PositionReceiver { rx, applied: applied_tx })
 }
 
+/// Apply updates to the tracker (saved to its file) and write them to the SQL store, if one is
+/// the record. Writes the store refuses stay queued, in order, and are retried with the next batch.
 pub async fn position_writer_loop(
-    mut rx: mpsc::UnboundedReceiver<FillRecord>,
+    receiver: PositionReceiver,
     tracker: Arc<RwLock<PositionTracker>>,
+    store: Option<Box<dyn PositionStore>>,
 ) {
+    let PositionReceiver { mut rx, applied } = receiver;
+    let mut writer = StoreWriter { store, backlog: Vec::new() };
     let mut batch = Vec::with_capacity(16);
     let mut interval = tokio::time::interval(Duration::from_millis(100));
 
@@ -469,26 +980,100 @@ pub async fn position_writer_loop(
         tokio::select! {
             biased;
 
-            Some(fill) = rx.recv() => {
-                batch.push(fill);
+            Some(update) = rx.recv() => {
+                batch.push(update);
                 if batch.len() >= 16 {
-                    let mut guard = tracker.write().await;
-                    for fill in batch.drain(..) {
-                        guard.record_fill_internal(&fill);
-                    }
-                    guard.save_async();
+                    let n = batch.len() as u64;
+                    writer = flush(&mut batch, &tracker, writer).await;
+                    applied.send_modify(|a| *a += n);
                 }
             }
             _ = interval.tick() => {
-                if !batch.is_empty() {
-                    let mut guard = tracker.write().await;
-                    for fill in batch.drain(..) {
-                        guard.record_fill_internal(&fill);
+                if !batch.is_empty() || !writer.backlog.is_empty() {
+                    let n = batch.len() as u64;
+                    writer = flush(&mut batch, &tracker, writer).await;
+                    applied.send_modify(|a| *a += n);
+                }
+            }
+        }
+    }
+}
+
+/// The store and the updates it hasn't taken yet
+struct StoreWriter {
+    store: Option<Box<dyn PositionStore>>,
+    backlog: Vec<PositionUpdate>,
+}
+
+impl StoreWriter {
+    /// Write the backlog in order, stopping at the first failure
+    fn drain(&mut self) {
+        let Some(store) = self.store.as_mut() else {
+            self.backlog.clear();
+            return;
+        };
+        let mut written = 0;
+        for update in &self.backlog {
+            let result = match update {
+                PositionUpdate::Fill(fill) => store.append_fill(fill),
+                PositionUpdate::Settle { market_id, settlement } => store.settle(market_id, settlement).map(|_| ()),
+                PositionUpdate::Detail { order_id, detail } => store.attach_detail(order_id, detail),
+            };
+            if let Err(e) = result {
+                warn!("[POSITIONS] {} store write failed, {} update(s) queued for retry: {:#}",
+                      store.backend().name(), self.backlog.len() - written, e);
+                metrics::incr("position_store.errors");
+                break;
+            }
+            written += 1;
+        }
+        self.backlog.drain(..written);
+        metrics::set_gauge("position_store.backlog", self.backlog.len() as i64);
+    }
+}
+
+/// Apply a batch to the tracker, then hand it to the store
+async fn flush(
+    batch: &mut Vec<PositionUpdate>,
+    tracker: &RwLock<PositionTracker>,
+    mut writer: StoreWriter,
+) -> StoreWriter {
+    {
+        let mut guard = tracker.write().await;
+        for update in batch.iter_mut() {
+            match update {
+                PositionUpdate::Fill(fill) => {
+                    // The store takes the fill with it too
+                    if let Some(detail) = guard.take_early_detail(&fill.order_id) {
+                        fill.detail = detail;
                     }
-                    guard.save_async();
+                    guard.record_fill_internal(fill);
+                }
+                PositionUpdate::Settle { market_id, settlement } => {
+                    guard.settle(market_id, settlement);
+                }
+                PositionUpdate::Detail { order_id, detail } => {
+                    guard.attach_detail(order_id, detail);
                 }
             }
         }
+        guard.save_async();
+    }
+    if writer.store.is_none() {
+        batch.clear();
+        return writer;
+    }
+    writer.backlog.append(batch);
+    // Database calls block; the writer comes back for the next batch
+    match tokio::task::spawn_blocking(move || {
+        writer.drain();
+        writer
+    }).await {
+        Ok(writer) => writer,
+        Err(e) => {
+            error!("[POSITIONS] Store writer panicked, updates since are only in the position file: {}", e);
+            StoreWriter { store: None, backlog: Vec::new() }
+        }
     }
 }
 
@@ -508,6 +1093,90 @@ mod tests {
         // Profit if this leg wins
         assert!((leg.profit_if_win() - 5.50).abs() < 0.001); // $10 payout - $4.50 cost
     }
+
+    #[test]
+    fn test_saves_to_its_own_file() {
+        let path = std::env::temp_dir().join(format!("positions_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut tracker = PositionTracker::open_locked(&path, None).unwrap();
+        // Second owner of the same file is refused while the first holds it
+        assert!(PositionTracker::open_locked(&path, None).is_err());
+
+        tracker.all_time_pnl = 1.25;
+        tracker.save_async(); // no runtime here - saves synchronously
+        assert_eq!(tracker.path(), path);
+        assert!((PositionTracker::load_from(&path).unwrap().all_time_pnl - 1.25).abs() < 1e-9);
+
+        drop(tracker);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("json.lock"));
+    }
+
+    #[test]
+    fn test_standby_takes_over_the_file() {
+        let path = std::env::temp_dir().join(format!("positions_standby_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut leader = PositionTracker::open_locked(&path, None).unwrap();
+        let mut standby = PositionTracker::load_from(&path).unwrap().into_standby();
+        standby.all_time_pnl = 9.0;
+        standby.save().unwrap(); // not its file to write
+        leader.all_time_pnl = 1.5;
+        leader.save().unwrap();
+        assert!(standby.promote(None).is_err(), "leader still holds the lock");
+
+        // A fill applied but not yet saved is written when the leader hands the file over
+        leader.record_fill_internal(&FillRecord::new("m1", "m1", "polymarket", "yes", 5.0, 0.4, 0.0, "o1"));
+        leader.demote();
+        standby.promote(None).unwrap();
+        assert!(!standby.is_standby());
+        assert!((standby.all_time_pnl - 1.5).abs() < 1e-9);
+        assert_eq!(standby.get("m1").map(|p| p.poly_yes.contracts), Some(5.0));
+
+        drop(standby);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("json.lock"));
+    }
+
+    #[test]
+    fn test_detail_attached_after_or_before_the_fill() {
+        let mut tracker = PositionTracker::new();
+        tracker.record_fill_internal(&FillRecord::new("m1", "m1", "polymarket", "yes", 5.0, 0.4, 0.0, "o1"));
+        let late = FillDetail { trade_ids: vec!["t1".into()], ..Default::default() };
+        assert!(tracker.attach_detail("o1", &late));
+        assert_eq!(tracker.get("m1").unwrap().trades[0].detail, late);
+
+        // Ahead of its fill: kept until the fill is recorded
+        let early = FillDetail { trade_ids: vec!["t2".into()], ..Default::default() };
+        assert!(!tracker.attach_detail("o2", &early));
+        assert_eq!(tracker.take_early_detail("o2"), Some(early));
+        assert_eq!(tracker.take_early_detail("o2"), None);
+    }
+
+    #[tokio::test]
+    async fn test_channel_flush_waits_for_the_writer() {
+        let (channel, receiver) = create_position_channel();
+        let tracker = Arc::new(RwLock::new(PositionTracker::new().into_standby()));
+        channel.record_fill(FillRecord::new("m1", "m1", "polymarket", "no", 3.0, 0.5, 0.0, "o1"));
+        // Nothing applies updates yet
+        assert!(!channel.flush(Duration::from_millis(20)).await);
+
+        tokio::spawn(position_writer_loop(receiver, tracker.clone(), None));
+        assert!(channel.flush(Duration::from_secs(2)).await);
+        assert_eq!(tracker.read().await.get("m1").map(|p| p.poly_no.contracts), Some(3.0));
+    }
+
+    #[test]
+    fn test_position_leg_sale_keeps_entry() {
+        let mut leg = PositionLeg::default();
+        leg.add(10.0, 0.40);
+        leg.add(-6.0, 0.60); // Sell 6 at 60¢ (exit)
+
+        assert_eq!(leg.contracts, 4.0);
+        assert!((leg.cost_basis - 0.40).abs() < 0.001); // $4.00 paid - $3.60 proceeds
+        assert!((leg.avg_price - 0.40).abs() < 0.001);
+    }
     
     #[test]
     fn test_arb_position_guaranteed_profit() {
@@ -559,4 +1228,200 @@ mod tests {
         assert!((pos.realized_pnl.unwrap() - 0.50).abs() < 0.001);
         assert_eq!(pos.status, "resolved");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_strategy_and_net_exposure() {
+        let mut pos = ArbPosition::new("TEST-MARKET", "Test");
+        assert_eq!(pos.strategy(), "empty");
+
+        pos.poly_yes.add(10.0, 0.45);
+        pos.poly_no.add(7.0, 0.50);
+        assert_eq!(pos.strategy(), "poly_only");
+        assert!((pos.net_exposure() - 3.0).abs() < 0.001);
+
+        pos.kalshi_no.add(5.0, 0.40);
+        assert_eq!(pos.strategy(), "cross");
+        assert!((pos.net_exposure() + 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_multi_outcome_basket() {
+        let mut tracker = PositionTracker::new();
+        let fill = |outcome: &str, contracts: f64, price: f64| {
+            FillRecord::new("ELECTION", "Who wins?", "polymarket", "yes", contracts, price, 0.0, "")
+                .in_outcome(outcome)
+                .among(&["A", "B", "C"])
+        };
+        // YES on all three outcomes for 30 + 35 + 30 = 95¢ a basket; 10 baskets, 2 extra on B
+        tracker.record_fill_internal(&fill("A", 10.0, 0.30));
+        tracker.record_fill_internal(&fill("B", 12.0, 0.35));
+        tracker.record_fill_internal(&fill("C", 10.0, 0.30));
+
+        let pos = tracker.get("ELECTION").unwrap();
+        assert_eq!(pos.strategy(), "basket");
+        assert_eq!(pos.outcomes.len(), 3);
+        assert!((pos.matched_contracts() - 10.0).abs() < 0.001);
+        assert!((pos.unmatched_exposure() - 2.0).abs() < 0.001);
+        // Cost $3.00 + $4.20 + $3.00: the two spare B eat the basket edge until B wins
+        assert!((pos.guaranteed_profit() - (10.0 - 10.20)).abs() < 0.001);
+        assert!((pos.payout_if("B") - 12.0).abs() < 0.001);
+        assert_eq!(pos.trades[1].outcome.as_deref(), Some("B"));
+
+        let pnl = tracker.resolve_outcome("ELECTION", "B").unwrap();
+        assert!((pnl - 1.80).abs() < 0.001);
+        assert_eq!(tracker.get("ELECTION").unwrap().winning_outcome.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn test_unheld_outcomes_count_as_winners() {
+        let mut tracker = PositionTracker::new();
+        // YES on two of three outcomes: C winning pays nothing
+        for outcome in ["A", "B"] {
+            tracker.record_fill_internal(&FillRecord::new("CUP", "Who wins?", "polymarket", "yes", 10.0, 0.45, 0.0, "")
+                .in_outcome(outcome)
+                .among(&["A", "B", "C"]));
+        }
+        let pos = tracker.get("CUP").unwrap();
+        assert_eq!(pos.matched_contracts(), 0.0);
+        assert!((pos.unmatched_exposure() - 10.0).abs() < 0.001);
+
+        // Without the event's outcome list an unheld winner is assumed
+        let mut unlisted = pos.clone();
+        unlisted.event_outcomes.clear();
+        assert_eq!(unlisted.matched_contracts(), 0.0);
+
+        // NO on A covers the third outcome
+        tracker.record_fill_internal(&FillRecord::new("CUP", "Who wins?", "polymarket", "no", 10.0, 0.50, 0.0, "")
+            .in_outcome("A"));
+        let pos = tracker.get("CUP").unwrap();
+        assert!((pos.payout_range().0 - 10.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_binary_records_load_unchanged() {
+        // A position file written before multi-outcome support
+        let json = r#"{"market_id":"M","description":"d","kalshi_yes":{"contracts":0.0,"cost_basis":0.0,"avg_price":0.0},
+            "kalshi_no":{"contracts":10.0,"cost_basis":5.0,"avg_price":0.5},
+            "poly_yes":{"contracts":10.0,"cost_basis":4.5,"avg_price":0.45},
+            "poly_no":{"contracts":0.0,"cost_basis":0.0,"avg_price":0.0},
+            "total_fees":0.0,"opened_at":"2025-12-01T00:00:00Z","status":"open","realized_pnl":null}"#;
+        let pos: ArbPosition = serde_json::from_str(json).unwrap();
+        assert!(!pos.is_multi_outcome());
+        assert!((pos.guaranteed_profit() - 0.50).abs() < 0.001);
+        // ...and binary positions are still written without the new fields
+        let out = serde_json::to_value(&pos).unwrap();
+        assert!(out.get("outcomes").is_none() && out.get("winning_outcome").is_none());
+    }
+
+    #[test]
+    fn test_keyed_fills() {
+        let mut tracker = PositionTracker::new();
+        let question = "Bitcoin Up or Down - December 19, 9:00AM-9:15AM ET";
+        // Same question, different days: separate positions under their keys
+        let today = MarketKey::polymarket("btc-updown-15m-1766154600", "1", "2", 1766154600);
+        let tomorrow = MarketKey::polymarket("btc-updown-15m-1766241000", "3", "4", 1766241000);
+        tracker.record_fill_internal(&FillRecord::keyed(&today, question, "polymarket", "yes", 10.0, 0.45, 0.0, "a"));
+        tracker.record_fill_internal(&FillRecord::keyed(&tomorrow, question, "polymarket", "no", 5.0, 0.50, 0.0, "b"));
+        assert_eq!(tracker.get(&today.id()).unwrap().key.as_ref(), Some(&today));
+        assert!((tracker.get(&tomorrow.id()).unwrap().poly_no.contracts - 5.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_open_upgrades_old_schema_and_refuses_newer() {
+        let path = std::env::temp_dir().join(format!("positions_schema_test_{}.json", std::process::id()));
+        let backup = PathBuf::from(format!("{}.v0.bak", path.display()));
+        // Written before schema versions and trade records
+        let leg = r#"{"contracts":0.0,"cost_basis":0.0,"avg_price":0.0}"#;
+        std::fs::write(&path, format!(r#"{{"positions":{{"M":{{"market_id":"M","description":"d",
+            "kalshi_yes":{leg},"kalshi_no":{leg},"poly_yes":{leg},"poly_no":{leg},"total_fees":0.0,
+            "opened_at":"2025-12-01T00:00:00Z","status":"open","realized_pnl":null}}}},
+            "daily_realized_pnl":0.0,"trading_date":"2025-12-01","all_time_pnl":2.0}}"#)).unwrap();
+
+        let tracker = PositionTracker::open_locked(&path, None).unwrap();
+        assert!((tracker.all_time_pnl - 2.0).abs() < 1e-9);
+        assert!(tracker.get("M").unwrap().trades.is_empty());
+        assert!(backup.exists());
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(position_schema::version_of(&saved), SCHEMA_VERSION);
+        drop(tracker);
+
+        std::fs::write(&path, format!(r#"{{"schema_version":{},"positions":{{}}}}"#, SCHEMA_VERSION + 1)).unwrap();
+        assert!(PositionTracker::open_locked(&path, None).is_err());
+        assert!(PositionTracker::load_from(&path).is_err());
+
+        // A file that doesn't parse is an error, never a fresh tracker saved over it
+        std::fs::write(&path, "{\"positions\":{").unwrap();
+        assert!(PositionTracker::open_locked(&path, None).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"positions\":{");
+
+        for p in [path.clone(), backup, path.with_extension("json.lock")] {
+            let _ = std::fs::remove_file(p);
+        }
+    }
+
+    /// Store that refuses writes while `down`, recording what it took
+    struct FlakyStore {
+        down: bool,
+        written: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl PositionStore for FlakyStore {
+        fn backend(&self) -> crate::position_store::StoreBackend {
+            crate::position_store::StoreBackend::Sqlite
+        }
+
+        fn append_fill(&mut self, fill: &FillRecord) -> Result<()> {
+            if self.down {
+                return Err(anyhow::anyhow!("database unavailable"));
+            }
+            self.written.lock().unwrap().push(fill.order_id.clone());
+            Ok(())
+        }
+
+        fn settle(&mut self, market_id: &str, _settlement: &Settlement) -> Result<Option<f64>> {
+            self.written.lock().unwrap().push(format!("settle {}", market_id));
+            Ok(None)
+        }
+
+        fn attach_detail(&mut self, order_id: &str, _detail: &FillDetail) -> Result<()> {
+            self.written.lock().unwrap().push(format!("detail {}", order_id));
+            Ok(())
+        }
+
+        fn query(&self, _status: Option<&str>) -> Result<Vec<ArbPosition>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_store_writes_retried_in_order() {
+        let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut writer = StoreWriter { store: Some(Box::new(FlakyStore { down: true, written: written.clone() })), backlog: Vec::new() };
+        let fill = |id: &str| PositionUpdate::Fill(Box::new(FillRecord::new("m", "m", "kalshi", "yes", 1.0, 0.5, 0.0, id)));
+        writer.backlog.extend([fill("a"), fill("b")]);
+        writer.drain();
+        assert_eq!(writer.backlog.len(), 2);
+
+        writer.store = Some(Box::new(FlakyStore { down: false, written: written.clone() }));
+        writer.backlog.push(PositionUpdate::Settle { market_id: "m".into(), settlement: Settlement::Binary { yes_won: true } });
+        writer.drain();
+        assert!(writer.backlog.is_empty());
+        assert_eq!(*written.lock().unwrap(), vec!["a", "b", "settle m"]);
+    }
+
+    #[test]
+    fn test_load_positions_recounts_pnl() {
+        let mut tracker = PositionTracker::new();
+        let mut won = ArbPosition::new("m1", "m1");
+        won.apply_fill(&FillRecord::new("m1", "m1", "polymarket", "yes", 10.0, 0.4, 0.0, "o1"));
+        won.resolve(true);
+        let mut open = ArbPosition::new("m2", "m2");
+        open.apply_fill(&FillRecord::new("m2", "m2", "polymarket", "no", 5.0, 0.5, 0.0, "o2"));
+        tracker.all_time_pnl = 99.0;
+        tracker.load_positions(vec![won, open]);
+        assert!((tracker.all_time_pnl - 6.0).abs() < 1e-9);
+        assert!((tracker.daily_pnl() - 6.0).abs() < 1e-9);
+        assert_eq!(tracker.open_positions().len(), 1);
+        assert_eq!(tracker.settle("m2", &Settlement::Binary { yes_won: false }), Some(2.5));
+    }
+}
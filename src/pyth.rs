@@ -0,0 +1,380 @@
+// src/pyth.rs
+// Pyth reference prices - Hermes price stream for the Up/Down assets (resolution cross-check, book sanity, signals)
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::env_flag;
+use crate::metrics;
+
+/// Pyth USD price feed ids of the Up/Down assets
+pub const FEEDS: &[(&str, &str)] = &[
+    ("btc", "e62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43"),
+    ("eth", "ff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace"),
+    ("sol", "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"),
+    ("xrp", "ec5d399846a9209f3fe5881d70aae9268c94339ff9817e8d18ff19fa05eea1c8"),
+];
+
+/// Seconds of history kept per asset (covers a full 15m interval plus slack)
+const HISTORY_SECS: u64 = 3600;
+
+/// Longest wait between reconnect attempts
+const MAX_BACKOFF_SECS: u64 = 30;
+
+pub fn feed_id(asset: &str) -> Option<&'static str> {
+    FEEDS.iter().find(|(a, _)| a.eq_ignore_ascii_case(asset)).map(|(_, id)| *id)
+}
+
+fn asset_of(feed_id: &str) -> Option<&'static str> {
+    let id = feed_id.trim_start_matches("0x");
+    FEEDS.iter().find(|(_, f)| f.eq_ignore_ascii_case(id)).map(|(a, _)| *a)
+}
+
+/// Pyth configuration from environment
+#[derive(Debug, Clone)]
+pub struct PythConfig {
+    pub enabled: bool,
+
+    pub hermes_url: String,
+
+    /// A price older than this (or a gap this long in the history) isn't used
+    pub max_age_secs: u64,
+
+    /// Move from the interval open (bps) past which the book must agree with the reference
+    /// (0 = no sanity check)
+    pub sanity_bps: f64,
+}
+
+impl PythConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: env_flag("PYTH", false),
+
+            hermes_url: std::env::var("PYTH_HERMES_URL")
+                .unwrap_or_else(|_| "https://hermes.pyth.network".to_string()),
+
+            max_age_secs: std::env::var("PYTH_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            sanity_bps: std::env::var("PYTH_SANITY_BPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15.0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Hermes SSE endpoint streaming parsed updates for every feed
+    pub fn stream_url(&self) -> String {
+        self.endpoint("stream")
+    }
+
+    /// Hermes endpoint returning the current price of every feed once
+    pub fn latest_url(&self) -> String {
+        self.endpoint("latest")
+    }
+
+    fn endpoint(&self, kind: &str) -> String {
+        let ids: Vec<String> = FEEDS.iter().map(|(_, id)| format!("ids[]={}", id)).collect();
+        format!("{}/v2/updates/price/{}?{}&parsed=true", self.hermes_url.trim_end_matches('/'), kind, ids.join("&"))
+    }
+
+    /// Why an Up/Down book disagrees with the reference, if it does. Once the reference has
+    /// moved `sanity_bps` from the interval open, the side it favors trading under 50¢ is a
+    /// stale quote about to be pulled: the other leg would likely fill alone.
+    pub fn sanity(&self, prices: &PythPrices, asset: &str, open_secs: u64, now_secs: u64,
+                  up_price: f64, down_price: f64) -> Option<String> {
+        if !self.enabled || self.sanity_bps <= 0.0 {
+            return None;
+        }
+        let moved = prices.move_bps(asset, open_secs, now_secs, self.max_age_secs)?;
+        if moved >= self.sanity_bps && up_price < 0.5 {
+            Some(format!("reference +{:.1}bps since open but Up at {:.3}", moved, up_price))
+        } else if moved <= -self.sanity_bps && down_price < 0.5 {
+            Some(format!("reference {:.1}bps since open but Down at {:.3}", moved, down_price))
+        } else {
+            None
+        }
+    }
+}
+
+/// One published price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceSample {
+    pub publish_secs: u64,
+    pub price: f64,
+    /// Confidence interval (same units as price)
+    pub conf: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesPrice {
+    price: String,
+    conf: String,
+    expo: i32,
+    publish_time: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesParsed {
+    id: String,
+    price: HermesPrice,
+}
+
+#[derive(Debug, Deserialize)]
+struct HermesUpdate {
+    #[serde(default)]
+    parsed: Vec<HermesParsed>,
+}
+
+/// Prices in one Hermes update (SSE `data:` payload); unknown feeds are skipped
+pub fn parse_update(data: &str) -> Result<Vec<(&'static str, PriceSample)>> {
+    let update: HermesUpdate = serde_json::from_str(data)?;
+    update.parsed.into_iter()
+        .filter_map(|p| asset_of(&p.id).map(|asset| (asset, p.price)))
+        .map(|(asset, p)| {
+            let scale = 10f64.powi(p.expo);
+            let raw = |s: &str| s.parse::<i64>().map_err(|_| anyhow!("bad price field: {}", s));
+            Ok((asset, PriceSample {
+                publish_secs: p.publish_time,
+                price: raw(&p.price)? as f64 * scale,
+                conf: raw(&p.conf)? as f64 * scale,
+            }))
+        })
+        .collect()
+}
+
+/// How an Up/Down interval resolves by the reference
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resolution {
+    /// Close at or above open
+    pub up: bool,
+    pub open: f64,
+    pub close: f64,
+    /// The move is larger than either price's confidence interval
+    pub decisive: bool,
+}
+
+/// Recent price history per asset
+#[derive(Debug, Default)]
+pub struct PythPrices {
+    history: Mutex<HashMap<&'static str, VecDeque<PriceSample>>>,
+}
+
+impl PythPrices {
+    /// Keep one sample per publish second (the latest), pruned to HISTORY_SECS
+    pub fn record(&self, asset: &'static str, sample: PriceSample) {
+        let mut history = self.history.lock().unwrap();
+        let samples = history.entry(asset).or_default();
+        match samples.back() {
+            Some(last) if last.publish_secs > sample.publish_secs => return,
+            Some(last) if last.publish_secs == sample.publish_secs => { samples.pop_back(); }
+            _ => {}
+        }
+        samples.push_back(sample);
+        while samples.front().is_some_and(|s| s.publish_secs + HISTORY_SECS < sample.publish_secs) {
+            samples.pop_front();
+        }
+    }
+
+    /// Latest price if published within `max_age_secs` of `now_secs`
+    pub fn latest(&self, asset: &str, now_secs: u64, max_age_secs: u64) -> Option<PriceSample> {
+        self.price_at(asset, now_secs, max_age_secs)
+    }
+
+    /// Last price published at or before `ts_secs`, if no older than `max_gap_secs` then
+    pub fn price_at(&self, asset: &str, ts_secs: u64, max_gap_secs: u64) -> Option<PriceSample> {
+        let history = self.history.lock().unwrap();
+        history.get(asset)?.iter().rev()
+            .find(|s| s.publish_secs <= ts_secs)
+            .filter(|s| s.publish_secs + max_gap_secs >= ts_secs)
+            .copied()
+    }
+
+    /// Samples published in `[since_secs, until_secs]`, oldest first
+    pub fn samples(&self, asset: &str, since_secs: u64, until_secs: u64) -> Vec<PriceSample> {
+        let history = self.history.lock().unwrap();
+        history.get(asset)
+            .map(|s| s.iter().filter(|s| (since_secs..=until_secs).contains(&s.publish_secs)).copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Reference resolution of an interval (Up wins ties, as on Polymarket)
+    pub fn outcome(&self, asset: &str, open_secs: u64, close_secs: u64, max_gap_secs: u64) -> Option<Resolution> {
+        let open = self.price_at(asset, open_secs, max_gap_secs)?;
+        let close = self.price_at(asset, close_secs, max_gap_secs)?;
+        Some(Resolution {
+            up: close.price >= open.price,
+            open: open.price,
+            close: close.price,
+            decisive: (close.price - open.price).abs() > open.conf.max(close.conf),
+        })
+    }
+
+    /// Move (bps) from `since_secs` to `now_secs`: the signal for directional strategies
+    /// that trade ahead of stale Up/Down books
+    pub fn move_bps(&self, asset: &str, since_secs: u64, now_secs: u64, max_age_secs: u64) -> Option<f64> {
+        let from = self.price_at(asset, since_secs, max_age_secs)?;
+        let to = self.latest(asset, now_secs, max_age_secs)?;
+        (from.price > 0.0).then(|| (to.price - from.price) / from.price * 10_000.0)
+    }
+}
+
+/// Process-wide reference prices, fed by the Hermes stream
+pub fn prices() -> &'static PythPrices {
+    static PRICES: OnceLock<PythPrices> = OnceLock::new();
+    PRICES.get_or_init(PythPrices::default)
+}
+
+/// Read one SSE connection until it ends, recording every update (counted in `received`,
+/// which survives a mid-stream error)
+async fn stream_once(http: &reqwest::Client, url: &str, received: &mut u64) -> Result<()> {
+    let mut response = http.get(url).send().await?.error_for_status()?;
+    let mut buf = String::new();
+    while let Some(chunk) = response.chunk().await? {
+        buf.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = buf.find('\n') {
+            let line: String = buf.drain(..=end).collect();
+            let Some(data) = line.trim_end().strip_prefix("data:") else { continue };
+            match parse_update(data.trim()) {
+                Ok(updates) => {
+                    for (asset, sample) in updates {
+                        prices().record(asset, sample);
+                    }
+                    *received += 1;
+                    metrics::incr("pyth.updates");
+                }
+                Err(e) => {
+                    metrics::incr("pyth.parse_errors");
+                    warn!("[PYTH] Unparseable update: {}", e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Wait before the next connection: reset to 1s if the last one delivered updates
+fn reconnect_delay(backoff: u64, received: u64) -> u64 {
+    if received > 0 { 1 } else { backoff }
+}
+
+/// Subscribe to Hermes for every feed, reconnecting with backoff (pyth.reconnects)
+pub fn spawn(config: PythConfig) {
+    if !config.enabled() {
+        return;
+    }
+    let url = config.stream_url();
+    info!("[PYTH] Streaming {} from {}", FEEDS.iter().map(|(a, _)| a.to_uppercase()).collect::<Vec<_>>().join("/"),
+          config.hermes_url);
+    tokio::spawn(async move {
+        let http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client");
+        let mut backoff = 1;
+        loop {
+            let mut received = 0;
+            let result = stream_once(&http, &url, &mut received).await;
+            // A connection that delivered prices was healthy: start over from the shortest wait
+            backoff = reconnect_delay(backoff, received);
+            match result {
+                Ok(()) => warn!("[PYTH] Stream ended - reconnecting in {}s", backoff),
+                Err(e) => warn!("[PYTH] Stream error: {} - reconnecting in {}s", e, backoff),
+            }
+            metrics::incr("pyth.reconnects");
+            tokio::time::sleep(Duration::from_secs(backoff)).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(secs: u64, price: f64) -> PriceSample {
+        PriceSample { publish_secs: secs, price, conf: 5.0 }
+    }
+
+    #[test]
+    fn test_parse_hermes_update() {
+        let data = format!(r#"{{"binary":{{"encoding":"hex","data":["00"]}},"parsed":[
+            {{"id":"{}","price":{{"price":"6712345000000","conf":"2500000000","expo":-8,"publish_time":1760000000}},
+              "ema_price":{{"price":"1","conf":"1","expo":-8,"publish_time":1760000000}}}},
+            {{"id":"0000","price":{{"price":"1","conf":"1","expo":0,"publish_time":1760000000}}}}]}}"#, FEEDS[0].1);
+        let updates = parse_update(&data).unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].0, "btc");
+        assert!((updates[0].1.price - 67_123.45).abs() < 1e-6 && (updates[0].1.conf - 25.0).abs() < 1e-9);
+        assert!(parse_update("{").is_err());
+        assert_eq!(feed_id("ETH"), Some(FEEDS[1].1));
+        assert_eq!(asset_of(&format!("0x{}", FEEDS[3].1)), Some("xrp"));
+    }
+
+    #[test]
+    fn test_history_and_outcome() {
+        let p = PythPrices::default();
+        p.record("btc", sample(1_000, 100.0));
+        p.record("btc", sample(1_001, 101.0));
+        p.record("btc", sample(1_001, 102.0));
+        // Out of order: ignored
+        p.record("btc", sample(999, 50.0));
+        assert_eq!(p.price_at("btc", 1_001, 5).unwrap().price, 102.0);
+        assert_eq!(p.price_at("btc", 1_000, 5).unwrap().price, 100.0);
+        assert_eq!(p.price_at("btc", 999, 5), None);
+        // Gap too long: no price
+        assert_eq!(p.latest("btc", 1_010, 5), None);
+
+        p.record("btc", sample(1_900, 120.0));
+        let r = p.outcome("btc", 1_000, 1_900, 5).unwrap();
+        assert!(r.up && r.decisive);
+        assert_eq!((r.open, r.close), (100.0, 120.0));
+        p.record("btc", sample(1_901, 97.0));
+        assert!(!p.outcome("btc", 1_000, 1_901, 5).unwrap().decisive);
+        // Old samples pruned
+        p.record("btc", sample(1_000 + HISTORY_SECS + 1, 110.0));
+        assert_eq!(p.price_at("btc", 1_000, 5), None);
+    }
+
+    #[test]
+    fn test_sanity_against_reference() {
+        let config = PythConfig {
+            enabled: true, hermes_url: String::new(), max_age_secs: 5, sanity_bps: 15.0,
+        };
+        let p = PythPrices::default();
+        p.record("eth", sample(1_000, 4_000.0));
+        p.record("eth", sample(1_300, 4_010.0));
+        // +25bps since open: Up should be favored
+        assert!(config.sanity(&p, "eth", 1_000, 1_300, 0.42, 0.55).is_some());
+        assert_eq!(config.sanity(&p, "eth", 1_000, 1_300, 0.70, 0.28), None);
+        // Stale reference or no data: no opinion
+        assert_eq!(config.sanity(&p, "eth", 1_000, 1_400, 0.42, 0.55), None);
+        assert_eq!(config.sanity(&p, "sol", 1_000, 1_300, 0.42, 0.55), None);
+        // Small move: no opinion
+        p.record("eth", sample(1_301, 4_004.0));
+        assert_eq!(config.sanity(&p, "eth", 1_000, 1_301, 0.42, 0.55), None);
+        assert!(config.stream_url().contains(&format!("ids[]={}", FEEDS[2].1)));
+        assert!(config.latest_url().starts_with("/v2/updates/price/latest?"));
+    }
+
+    #[test]
+    fn test_backoff_resets_after_updates() {
+        let mut backoff = 1;
+        for _ in 0..10 {
+            backoff = (reconnect_delay(backoff, 0) * 2).min(MAX_BACKOFF_SECS);
+        }
+        assert_eq!(backoff, MAX_BACKOFF_SECS);
+        assert_eq!(reconnect_delay(backoff, 3), 1);
+        assert_eq!(reconnect_delay(backoff, 0), MAX_BACKOFF_SECS);
+    }
+}
@@ -0,0 +1,223 @@
+// src/rebalance.rs
+// Capital rebalancing - spots cash drained from one venue and suggests the transfer that evens it out
+
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::balances::{self, BalanceConfig, Balances};
+use crate::events::{self, BotEvent};
+use crate::metrics;
+
+pub const POLYMARKET: &str = "polymarket";
+pub const KALSHI: &str = "kalshi";
+
+/// Rebalancing configuration from environment
+#[derive(Debug, Clone)]
+pub struct RebalanceConfig {
+    /// Alert when a venue's cash is off its target by more than this share of total cash (0 = off)
+    pub threshold: f64,
+
+    /// Share of total cash that should sit on Polymarket
+    pub poly_share: f64,
+
+    /// Smallest transfer worth suggesting (dollars)
+    pub min_transfer: f64,
+
+    /// Largest single transfer (dollars)
+    pub max_transfer: f64,
+
+    /// Seconds between repeated alerts / automatic transfers
+    pub cooldown_secs: u64,
+
+    /// Polygon USDC deposit address of the Kalshi account (enables Polymarket -> Kalshi transfers)
+    pub kalshi_deposit_address: Option<String>,
+}
+
+impl RebalanceConfig {
+    pub fn from_env() -> Self {
+        Self {
+            threshold: std::env::var("REBALANCE_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.2),
+
+            poly_share: std::env::var("REBALANCE_POLY_SHARE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.5),
+
+            min_transfer: std::env::var("REBALANCE_MIN_USD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50.0),
+
+            max_transfer: std::env::var("REBALANCE_MAX_USD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1000.0),
+
+            cooldown_secs: std::env::var("REBALANCE_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+
+            kalshi_deposit_address: std::env::var("REBALANCE_KALSHI_DEPOSIT_ADDRESS")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.threshold > 0.0
+    }
+}
+
+/// Cash to move from one venue to the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// Whole dollars
+    pub amount: f64,
+    /// Polymarket's current share of total cash
+    pub poly_share: f64,
+}
+
+impl Transfer {
+    /// Only Polymarket -> Kalshi can be sent by API (an on-chain USDC transfer from the
+    /// funder wallet); Kalshi has no withdrawal API, so the other direction is manual
+    pub fn automatable(&self, config: &RebalanceConfig) -> bool {
+        self.from == POLYMARKET && config.kalshi_deposit_address.is_some()
+    }
+}
+
+/// Transfer that brings cash back to the target split, if it drifted past the threshold.
+/// Needs fresh balances from both venues; committed cash (orders, positions) can't be moved.
+pub fn suggest(balances: &Balances, config: &RebalanceConfig, now: i64, stale_secs: u64) -> Option<Transfer> {
+    let (poly, kalshi) = (balances.poly.as_ref()?, balances.kalshi.as_ref()?);
+    if !config.enabled() || poly.is_stale(now, stale_secs) || kalshi.is_stale(now, stale_secs) {
+        return None;
+    }
+    let total = poly.cash + kalshi.cash;
+    if total <= 0.0 {
+        return None;
+    }
+    // Positive: Polymarket holds more than its target
+    let excess = poly.cash - config.poly_share * total;
+    if excess.abs() / total <= config.threshold {
+        return None;
+    }
+    let amount = excess.abs().min(config.max_transfer).floor();
+    if amount < config.min_transfer {
+        return None;
+    }
+    let (from, to) = if excess > 0.0 { (POLYMARKET, KALSHI) } else { (KALSHI, POLYMARKET) };
+    Some(Transfer { from, to, amount, poly_share: poly.cash / total })
+}
+
+/// How to carry out `transfer` (logged with the alert)
+pub fn instructions(transfer: &Transfer, config: &RebalanceConfig) -> String {
+    if transfer.automatable(config) {
+        "send it with: cargo run --release --bin balances -- --rebalance --confirm".to_string()
+    } else if transfer.from == KALSHI {
+        "withdraw from Kalshi and deposit USDC to the Polymarket funder wallet".to_string()
+    } else {
+        "withdraw USDC from Polymarket and deposit to Kalshi (set REBALANCE_KALSHI_DEPOSIT_ADDRESS to automate)".to_string()
+    }
+}
+
+/// Watch the polled balances and alert (log + `rebalance_suggested` event) when cash drifts
+pub fn spawn_monitor(config: RebalanceConfig, balance_config: BalanceConfig) {
+    if !config.enabled() || !balance_config.enabled() {
+        return;
+    }
+    info!("[REBALANCE] Alerting when venue cash drifts >{:.0}% from a {:.0}/{:.0} Poly/Kalshi split",
+          config.threshold * 100.0, config.poly_share * 100.0, (1.0 - config.poly_share) * 100.0);
+    tokio::spawn(async move {
+        let mut last_alert: Option<(i64, &'static str)> = None;
+        let mut ticker = tokio::time::interval(Duration::from_secs(balance_config.poll_secs));
+        loop {
+            ticker.tick().await;
+            let now = chrono::Utc::now().timestamp();
+            let Some(transfer) = suggest(&balances::snapshot(), &config, now, balance_config.stale_secs) else {
+                metrics::set_gauge("rebalance.suggested_cents", 0);
+                last_alert = None;
+                continue;
+            };
+            metrics::set_gauge("rebalance.suggested_cents", (transfer.amount * 100.0) as i64);
+
+            // Repeat only after the cooldown, unless the direction flipped
+            let due = last_alert.is_none_or(|(at, from)| from != transfer.from || now - at >= config.cooldown_secs as i64);
+            if !due {
+                continue;
+            }
+            last_alert = Some((now, transfer.from));
+            warn!("[REBALANCE] ⚖️  Polymarket holds {:.0}% of cash - move ${:.0} from {} to {}: {}",
+                  transfer.poly_share * 100.0, transfer.amount, transfer.from, transfer.to,
+                  instructions(&transfer, &config));
+            events::publish(BotEvent::RebalanceSuggested {
+                from: transfer.from.to_string(),
+                to: transfer.to.to_string(),
+                amount: transfer.amount,
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balances::VenueBalance;
+
+    fn cfg() -> RebalanceConfig {
+        RebalanceConfig {
+            threshold: 0.2,
+            poly_share: 0.5,
+            min_transfer: 50.0,
+            max_transfer: 1000.0,
+            cooldown_secs: 3600,
+            kalshi_deposit_address: None,
+        }
+    }
+
+    fn balances(poly: f64, kalshi: f64) -> Balances {
+        Balances {
+            poly: Some(VenueBalance::new(poly, 0.0, 1_000)),
+            kalshi: Some(VenueBalance::new(kalshi, 40.0, 1_000)),
+        }
+    }
+
+    #[test]
+    fn test_suggests_transfer_back_to_target_split() {
+        // $900 vs $100: Poly is $400 over its half
+        let t = suggest(&balances(900.0, 100.0), &cfg(), 1_000, 180).unwrap();
+        assert_eq!((t.from, t.to, t.amount), (POLYMARKET, KALSHI, 400.0));
+        assert!((t.poly_share - 0.9).abs() < 1e-9);
+
+        let t = suggest(&balances(50.0, 2950.0), &cfg(), 1_000, 180).unwrap();
+        assert_eq!((t.from, t.to, t.amount), (KALSHI, POLYMARKET, 1000.0));
+
+        // 60/40 is within the 20% band
+        assert!(suggest(&balances(600.0, 400.0), &cfg(), 1_000, 180).is_none());
+    }
+
+    #[test]
+    fn test_no_suggestion_on_stale_missing_or_small() {
+        assert!(suggest(&balances(900.0, 100.0), &cfg(), 2_000, 180).is_none());
+        assert!(suggest(&Balances { kalshi: None, ..balances(900.0, 100.0) }, &cfg(), 1_000, 180).is_none());
+        // $160 split 150/10 is $70 off target, but raise the minimum past it
+        let config = RebalanceConfig { min_transfer: 100.0, ..cfg() };
+        assert!(suggest(&balances(150.0, 10.0), &config, 1_000, 180).is_none());
+    }
+
+    #[test]
+    fn test_only_poly_to_kalshi_is_automatable() {
+        let config = RebalanceConfig { kalshi_deposit_address: Some("0xabc".into()), ..cfg() };
+        let out = suggest(&balances(900.0, 100.0), &config, 1_000, 180).unwrap();
+        let back = suggest(&balances(100.0, 900.0), &config, 1_000, 180).unwrap();
+        assert!(out.automatable(&config));
+        assert!(!back.automatable(&config));
+        assert!(!out.automatable(&cfg()));
+        assert!(instructions(&back, &config).contains("withdraw from Kalshi"));
+    }
+}
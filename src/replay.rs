@@ -0,0 +1,596 @@
+// src/replay.rs
+// Maintenance replay buffer - non-urgent venue actions that hit an outage are kept on disk
+// and replayed once the venue is back, instead of being dropped
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::kalshi::KalshiApiClient;
+use crate::metrics;
+#[cfg(feature = "onchain")]
+use crate::onchain::{self, OnchainClient};
+use crate::polymarket_clob::SharedAsyncClient;
+use crate::runtimes;
+use crate::storage::{self, FileLock};
+use crate::venue_health::{self, HealthLevel, Venue};
+
+/// Default buffer file (under DATA_DIR)
+pub const DEFAULT_REPLAY_FILE: &str = "replay_buffer.json";
+
+/// An action that can run minutes late but must not be lost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeferredAction {
+    /// Cancel a resting order (an expired quote). Done once the order no longer rests.
+    Cancel { venue: Venue, order_id: String },
+    /// Redeem a resolved condition's Polymarket tokens back into USDC
+    Redeem { condition_id: String },
+    /// Look up the final state of an order whose fill is unverified (close-race fills)
+    SettlementCheck { venue: Venue, order_id: String, market: String },
+}
+
+impl DeferredAction {
+    /// Venue that has to be up for the action to run (redemptions go through Polymarket's relayer)
+    pub fn venue(&self) -> Venue {
+        match self {
+            Self::Cancel { venue, .. } | Self::SettlementCheck { venue, .. } => *venue,
+            Self::Redeem { .. } => Venue::Polymarket,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Cancel { venue, order_id } => format!("cancel {} order {}", venue.name(), order_id),
+            Self::Redeem { condition_id } => format!("redeem {}", condition_id),
+            Self::SettlementCheck { venue, order_id, market } =>
+                format!("settlement check of {} order {} ({})", venue.name(), order_id, market),
+        }
+    }
+}
+
+/// A queued action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    #[serde(flatten)]
+    pub action: DeferredAction,
+    /// Unix seconds
+    pub queued_at: i64,
+    /// The error that deferred it
+    pub reason: String,
+    /// Replays that failed for a reason other than the venue being out
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Replay buffer configuration from environment
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub file: PathBuf,
+
+    /// Seconds between replay passes (0 = actions are queued but never replayed)
+    pub poll_secs: u64,
+
+    /// Failed replays (venue up, action rejected) before an action is dropped
+    pub max_attempts: u32,
+
+    /// Actions still queued after this long are dropped (seconds)
+    pub max_age_secs: i64,
+}
+
+impl ReplayConfig {
+    pub fn from_env() -> Self {
+        Self {
+            file: storage::state_path(std::env::var("REPLAY_FILE")
+                .unwrap_or_else(|_| DEFAULT_REPLAY_FILE.to_string())),
+
+            poll_secs: std::env::var("REPLAY_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15),
+
+            max_attempts: std::env::var("REPLAY_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            max_age_secs: std::env::var("REPLAY_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(86_400),
+        }
+    }
+}
+
+/// Durable FIFO of deferred actions. Every change is a read-modify-write of the whole
+/// file under a lock on it, so the bots and the manual_order tool can share one buffer;
+/// only the process that owns it replays (see `spawn`).
+pub struct ReplayBuffer {
+    config: ReplayConfig,
+    /// Serializes this process's read-modify-write cycles (the file lock covers other processes)
+    lock: Mutex<()>,
+}
+
+impl ReplayBuffer {
+    pub fn new(config: ReplayConfig) -> Self {
+        Self { config, lock: Mutex::new(()) }
+    }
+
+    pub fn config(&self) -> &ReplayConfig {
+        &self.config
+    }
+
+    /// Claim the replaying of `venue`'s actions for this process (None while another one has it)
+    pub fn claim(&self, venue: Venue) -> Option<FileLock> {
+        let mut owner = self.config.file.as_os_str().to_owned();
+        owner.push(format!(".{}.owner", venue.name()));
+        FileLock::acquire(PathBuf::from(owner)).ok()
+    }
+
+    fn load(&self) -> Vec<ReplayEntry> {
+        let Ok(data) = std::fs::read(&self.config.file) else {
+            return Vec::new();
+        };
+        serde_json::from_slice(&data).unwrap_or_else(|e| {
+            warn!("[REPLAY] Ignoring unreadable {}: {}", self.config.file.display(), e);
+            Vec::new()
+        })
+    }
+
+    /// Hold the buffer against this process's other threads and other processes
+    fn locked(&self) -> (MutexGuard<'_, ()>, Option<FileLock>) {
+        let guard = self.lock.lock().unwrap();
+        let file_lock = FileLock::wait(&self.config.file)
+            .map_err(|e| warn!("[REPLAY] Cannot lock {}, changing it unlocked: {}", self.config.file.display(), e))
+            .ok();
+        (guard, file_lock)
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut Vec<ReplayEntry>) -> R) -> R {
+        let _locked = self.locked();
+        let mut entries = self.load();
+        let out = f(&mut entries);
+        let written = serde_json::to_vec_pretty(&entries)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| storage::write_atomic(&self.config.file, &data));
+        if let Err(e) = written {
+            warn!("[REPLAY] Cannot write {}: {}", self.config.file.display(), e);
+        }
+        metrics::set_gauge("replay.pending", entries.len() as i64);
+        out
+    }
+
+    /// Queue `action` (false if it is already queued)
+    pub fn push(&self, action: DeferredAction, reason: &str) -> bool {
+        let queued = self.update(|entries| {
+            if entries.iter().any(|e| e.action == action) {
+                return false;
+            }
+            entries.push(ReplayEntry {
+                action: action.clone(),
+                queued_at: chrono::Utc::now().timestamp(),
+                reason: reason.to_string(),
+                attempts: 0,
+                last_error: None,
+            });
+            true
+        });
+        if queued {
+            warn!("[REPLAY] ⏸️ {} deferred until {} recovers: {}", action.describe(), action.venue().name(), reason);
+            metrics::incr("replay.deferred");
+        }
+        queued
+    }
+
+    /// Queued actions, oldest first
+    pub fn pending(&self) -> Vec<ReplayEntry> {
+        let _locked = self.locked();
+        self.load()
+    }
+
+    /// Remove a replayed action
+    pub fn complete(&self, action: &DeferredAction) {
+        self.update(|entries| entries.retain(|e| &e.action != action));
+        metrics::incr("replay.replayed");
+    }
+
+    /// Count a failed replay; returns true when the action ran out of attempts and was dropped
+    pub fn fail(&self, action: &DeferredAction, error: &str) -> bool {
+        let max_attempts = self.config.max_attempts;
+        let dropped = self.update(|entries| {
+            let entry = entries.iter_mut().find(|e| &e.action == action)?;
+            entry.attempts += 1;
+            entry.last_error = Some(error.to_string());
+            let dropped = entry.attempts >= max_attempts;
+            if dropped {
+                entries.retain(|e| &e.action != action);
+            }
+            Some(dropped)
+        }).unwrap_or(false);
+        if dropped {
+            warn!("[REPLAY] ❌ Dropping {} after {} failed replays: {}", action.describe(), max_attempts, error);
+            metrics::incr("replay.dropped");
+        }
+        dropped
+    }
+
+    /// Drop actions queued longer than REPLAY_MAX_AGE_SECS before `now`
+    pub fn expire(&self, now: i64) -> usize {
+        let max_age = self.config.max_age_secs;
+        let expired = self.update(|entries| {
+            let (old, keep): (Vec<_>, Vec<_>) = entries.drain(..).partition(|e| now - e.queued_at > max_age);
+            *entries = keep;
+            old
+        });
+        for entry in &expired {
+            warn!("[REPLAY] ❌ Dropping {} - still queued after {}s ({})",
+                  entry.action.describe(), max_age, entry.last_error.as_deref().unwrap_or(&entry.reason));
+            metrics::incr("replay.dropped");
+        }
+        expired.len()
+    }
+}
+
+/// Process-wide replay buffer (REPLAY_FILE)
+pub fn buffer() -> &'static ReplayBuffer {
+    static BUFFER: OnceLock<ReplayBuffer> = OnceLock::new();
+    BUFFER.get_or_init(|| ReplayBuffer::new(ReplayConfig::from_env()))
+}
+
+/// The failure means the venue is out (health says Down, or the request didn't get an
+/// answer / got a gateway error) rather than that the action itself was refused
+pub fn is_outage(venue: Venue, err: &anyhow::Error) -> bool {
+    if venue_health::health().level(venue) == HealthLevel::Down {
+        return true;
+    }
+    if let Some(e) = err.chain().find_map(|c| c.downcast_ref::<reqwest::Error>()) {
+        return e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| s.is_server_error());
+    }
+    // Venue clients report HTTP failures as "<what> failed|error <status> <reason>: <body>"
+    let msg = err.to_string();
+    [" 502 ", " 503 ", " 504 "].iter().any(|code| msg.contains(code))
+}
+
+/// Runs deferred actions against the venues
+pub trait ReplayExecutor: Send + Sync {
+    /// This process can run actions on `venue` (it has the client)
+    fn serves(&self, venue: Venue) -> bool;
+
+    /// This process has the client `action` needs (other actions are left for one that does)
+    fn handles(&self, action: &DeferredAction) -> bool;
+
+    /// Perform the action. Must be safe to repeat.
+    fn execute(&self, action: &DeferredAction) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Run `action` now, or queue it if its venue is out. Ok(false) = deferred.
+pub async fn run_or_defer<E: ReplayExecutor>(executor: &E, action: DeferredAction) -> Result<bool> {
+    let venue = action.venue();
+    if venue_health::health().level(venue) == HealthLevel::Down {
+        buffer().push(action, &format!("{} is down", venue.name()));
+        return Ok(false);
+    }
+    match executor.execute(&action).await {
+        Ok(()) => Ok(true),
+        Err(e) if is_outage(venue, &e) => {
+            buffer().push(action, &e.to_string());
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Replay what can run, oldest first. A venue that turns out to still be out is skipped
+/// for the rest of the pass, keeping its actions in order. Returns the number replayed.
+pub async fn replay_pass<E: ReplayExecutor>(buffer: &ReplayBuffer, executor: &E, now: i64) -> usize {
+    buffer.expire(now);
+    let mut out: HashSet<Venue> = HashSet::new();
+    let mut replayed = 0;
+    for entry in buffer.pending() {
+        let venue = entry.action.venue();
+        if out.contains(&venue) || !executor.handles(&entry.action) {
+            continue;
+        }
+        if venue_health::health().level(venue) == HealthLevel::Down {
+            out.insert(venue);
+            continue;
+        }
+        match executor.execute(&entry.action).await {
+            Ok(()) => {
+                info!("[REPLAY] ▶️ {} (deferred {}s)", entry.action.describe(), now - entry.queued_at);
+                buffer.complete(&entry.action);
+                replayed += 1;
+            }
+            Err(e) if is_outage(venue, &e) => {
+                debug!("[REPLAY] {} still out: {}", venue.name(), e);
+                out.insert(venue);
+            }
+            Err(e) => {
+                warn!("[REPLAY] {} failed: {}", entry.action.describe(), e);
+                buffer.fail(&entry.action, &e.to_string());
+            }
+        }
+    }
+    replayed
+}
+
+/// Replay the buffer every REPLAY_POLL_SECS on the bookkeeping runtime. Every process can
+/// queue actions, but each venue's are replayed by one process only: the first with a client
+/// for it to claim it, until that process exits (the others keep trying, and take over then).
+pub fn spawn<E: ReplayExecutor + 'static>(executor: E) {
+    let poll_secs = buffer().config().poll_secs;
+    if poll_secs == 0 {
+        return;
+    }
+    runtimes::spawn_bookkeeping(async move {
+        let mut owned: HashMap<Venue, FileLock> = HashMap::new();
+        let mut ticker = tokio::time::interval(Duration::from_secs(poll_secs));
+        loop {
+            ticker.tick().await;
+            for venue in [Venue::Kalshi, Venue::Polymarket] {
+                if !executor.serves(venue) || owned.contains_key(&venue) {
+                    continue;
+                }
+                if let Some(lock) = buffer().claim(venue) {
+                    info!("[REPLAY] Replaying deferred {} actions ({} queued in all)", venue.name(), buffer().pending().len());
+                    owned.insert(venue, lock);
+                }
+            }
+            if owned.is_empty() {
+                debug!("[REPLAY] Other processes replay {}", buffer().config().file.display());
+                continue;
+            }
+            let claimed = Claimed { executor: &executor, venues: owned.keys().copied().collect() };
+            replay_pass(buffer(), &claimed, chrono::Utc::now().timestamp()).await;
+        }
+    });
+}
+
+/// An executor limited to the venues this process claimed
+struct Claimed<'a, E> {
+    executor: &'a E,
+    venues: HashSet<Venue>,
+}
+
+impl<E: ReplayExecutor> ReplayExecutor for Claimed<'_, E> {
+    fn serves(&self, venue: Venue) -> bool {
+        self.venues.contains(&venue) && self.executor.serves(venue)
+    }
+
+    fn handles(&self, action: &DeferredAction) -> bool {
+        self.venues.contains(&action.venue()) && self.executor.handles(action)
+    }
+
+    fn execute(&self, action: &DeferredAction) -> impl Future<Output = Result<()>> + Send {
+        self.executor.execute(action)
+    }
+}
+
+/// Replays through the clients this process has
+#[derive(Clone)]
+pub struct VenueActions {
+    pub kalshi: Option<Arc<KalshiApiClient>>,
+    pub polymarket: Option<Arc<SharedAsyncClient>>,
+    #[cfg(feature = "onchain")]
+    pub onchain: Option<Arc<OnchainClient>>,
+}
+
+impl ReplayExecutor for VenueActions {
+    fn serves(&self, venue: Venue) -> bool {
+        match venue {
+            Venue::Kalshi => self.kalshi.is_some(),
+            Venue::Polymarket => self.polymarket.is_some(),
+        }
+    }
+
+    fn handles(&self, action: &DeferredAction) -> bool {
+        match action {
+            #[cfg(feature = "onchain")]
+            DeferredAction::Redeem { .. } => self.onchain.is_some(),
+            #[cfg(not(feature = "onchain"))]
+            DeferredAction::Redeem { .. } => false,
+            _ => match action.venue() {
+                Venue::Kalshi => self.kalshi.is_some(),
+                Venue::Polymarket => self.polymarket.is_some(),
+            },
+        }
+    }
+
+    async fn execute(&self, action: &DeferredAction) -> Result<()> {
+        let kalshi = || self.kalshi.as_deref().ok_or_else(|| anyhow::anyhow!("no Kalshi client"));
+        let polymarket = || self.polymarket.as_deref().ok_or_else(|| anyhow::anyhow!("no Polymarket client"));
+        match action {
+            DeferredAction::Cancel { venue: Venue::Kalshi, order_id } => cancel_kalshi(kalshi()?, order_id).await,
+            DeferredAction::Cancel { venue: Venue::Polymarket, order_id } => {
+                // A cancel of an order that no longer rests is answered in `not_canceled`
+                let resp = polymarket()?.cancel_order(order_id).await?;
+                debug!("[REPLAY] Polymarket cancel {}: {}", order_id, resp);
+                Ok(())
+            }
+            #[cfg(feature = "onchain")]
+            DeferredAction::Redeem { condition_id } => {
+                let client = self.onchain.as_deref().ok_or_else(|| anyhow::anyhow!("no onchain client"))?;
+                let tx = client.submit(&[onchain::redeem_positions(condition_id)?]).await?;
+                info!("[REPLAY] Redeemed {} ({})", condition_id, tx);
+                Ok(())
+            }
+            #[cfg(not(feature = "onchain"))]
+            DeferredAction::Redeem { .. } => anyhow::bail!("built without the onchain feature"),
+            DeferredAction::SettlementCheck { venue: Venue::Kalshi, order_id, market } =>
+                check_kalshi(kalshi()?, order_id, market).await,
+            DeferredAction::SettlementCheck { venue: Venue::Polymarket, order_id, market } => {
+                let order = polymarket()?.get_order(order_id).await?;
+                info!("[CLOSE] {} polymarket order {}: {} | matched {}/{}",
+                      market, order_id, order.status, order.size_matched, order.original_size);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "kalshi")]
+async fn cancel_kalshi(client: &KalshiApiClient, order_id: &str) -> Result<()> {
+    let Err(e) = client.cancel_order(order_id).await else {
+        return Ok(());
+    };
+    // Already cancelled or filled is what we wanted
+    match client.get_order(order_id).await {
+        Ok(resp) if resp.order.status != "resting" => Ok(()),
+        _ => Err(e),
+    }
+}
+
+#[cfg(not(feature = "kalshi"))]
+async fn cancel_kalshi(client: &KalshiApiClient, _order_id: &str) -> Result<()> {
+    match *client {}
+}
+
+#[cfg(feature = "kalshi")]
+async fn check_kalshi(client: &KalshiApiClient, order_id: &str, market: &str) -> Result<()> {
+    let order = client.get_order(order_id).await?.order;
+    info!("[CLOSE] {} kalshi order {}: {} | filled {}", market, order_id, order.status, order.filled_count());
+    Ok(())
+}
+
+#[cfg(not(feature = "kalshi"))]
+async fn check_kalshi(client: &KalshiApiClient, _order_id: &str, _market: &str) -> Result<()> {
+    match *client {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn buffer_at(name: &str) -> ReplayBuffer {
+        let file = std::env::temp_dir().join(format!("replay_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&file);
+        ReplayBuffer::new(ReplayConfig { file, poll_secs: 0, max_attempts: 2, max_age_secs: 3600 })
+    }
+
+    fn cancel(order_id: &str) -> DeferredAction {
+        DeferredAction::Cancel { venue: Venue::Polymarket, order_id: order_id.into() }
+    }
+
+    /// Polymarket executor that is either out (503) or up, refusing order "bad"
+    #[derive(Default)]
+    struct MockExecutor {
+        out: AtomicBool,
+        ran: Mutex<Vec<DeferredAction>>,
+    }
+
+    impl ReplayExecutor for MockExecutor {
+        fn serves(&self, venue: Venue) -> bool {
+            venue == Venue::Polymarket
+        }
+
+        fn handles(&self, action: &DeferredAction) -> bool {
+            action.venue() == Venue::Polymarket
+        }
+
+        async fn execute(&self, action: &DeferredAction) -> Result<()> {
+            if self.out.load(Ordering::Relaxed) {
+                anyhow::bail!("cancel_order failed 503 Service Unavailable: maintenance");
+            }
+            if action == &cancel("bad") {
+                anyhow::bail!("cancel_order failed 400 Bad Request: invalid order id");
+            }
+            self.ran.lock().unwrap().push(action.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_buffer_is_durable_and_deduped() {
+        let buffer = buffer_at("durable");
+        assert!(buffer.push(cancel("a"), "503"));
+        assert!(!buffer.push(cancel("a"), "503"));
+        assert!(buffer.push(DeferredAction::Redeem { condition_id: "0xabc".into() }, "relayer down"));
+
+        // A fresh process sees the same queue, in order
+        let reopened = ReplayBuffer::new(buffer.config().clone());
+        let pending = reopened.pending();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].action, cancel("a"));
+        assert_eq!(pending[1].action.venue(), Venue::Polymarket);
+
+        assert!(!reopened.fail(&cancel("a"), "400"));
+        assert!(reopened.fail(&cancel("a"), "400"));
+        assert_eq!(buffer.pending().len(), 1);
+        assert_eq!(buffer.expire(chrono::Utc::now().timestamp() + 7200), 1);
+        assert!(buffer.pending().is_empty());
+        let _ = std::fs::remove_file(&buffer.config().file);
+    }
+
+    #[test]
+    fn test_shared_by_processes_with_one_replayer() {
+        let first = buffer_at("shared");
+        // Separate buffers on one file stand in for processes: only the file lock orders them
+        let handles: Vec<_> = (0..4).map(|t| {
+            let buffer = ReplayBuffer::new(first.config().clone());
+            std::thread::spawn(move || {
+                for i in 0..10 {
+                    buffer.push(cancel(&format!("{}-{}", t, i)), "503");
+                }
+            })
+        }).collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(first.pending().len(), 40);
+
+        // One replayer per venue, taken over when it lets go
+        let owner = first.claim(Venue::Polymarket);
+        assert!(owner.is_some());
+        let other = ReplayBuffer::new(first.config().clone());
+        assert!(other.claim(Venue::Polymarket).is_none());
+        assert!(other.claim(Venue::Kalshi).is_some());
+        drop(owner);
+        assert!(other.claim(Venue::Polymarket).is_some());
+        let _ = std::fs::remove_file(&first.config().file);
+    }
+
+    #[test]
+    fn test_outage_classification() {
+        let venue = Venue::Kalshi;
+        assert!(is_outage(venue, &anyhow::anyhow!("Kalshi API error 503 Service Unavailable: maintenance")));
+        assert!(is_outage(venue, &anyhow::anyhow!("cancel_order failed 502 Bad Gateway: ")));
+        assert!(!is_outage(venue, &anyhow::anyhow!("Kalshi API error 404 Not Found: order not found")));
+        assert!(!is_outage(venue, &anyhow::anyhow!("invalid condition id")));
+    }
+
+    #[tokio::test]
+    async fn test_replays_in_order_once_venue_recovers() {
+        let executor = MockExecutor::default();
+        let buffer = buffer_at("recover");
+        for id in ["a", "bad", "b"] {
+            buffer.push(cancel(id), "503");
+        }
+        buffer.push(DeferredAction::Cancel { venue: Venue::Kalshi, order_id: "k".into() }, "503");
+        let now = chrono::Utc::now().timestamp();
+
+        // Still in maintenance: nothing runs, no attempt is spent
+        executor.out.store(true, Ordering::Relaxed);
+        assert_eq!(replay_pass(&buffer, &executor, now).await, 0);
+        assert!(buffer.pending().iter().all(|e| e.attempts == 0));
+
+        executor.out.store(false, Ordering::Relaxed);
+        assert_eq!(replay_pass(&buffer, &executor, now).await, 2);
+        assert_eq!(*executor.ran.lock().unwrap(), vec![cancel("a"), cancel("b")]);
+
+        // The refused cancel stays with its error; the Kalshi one waits for a Kalshi client
+        let pending = buffer.pending();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].attempts, 1);
+        assert!(pending[0].last_error.as_deref().unwrap().contains("400"));
+        assert_eq!(pending[1].action.venue(), Venue::Kalshi);
+        let _ = std::fs::remove_file(&buffer.config().file);
+    }
+}
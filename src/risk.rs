@@ -0,0 +1,278 @@
+// src/risk.rs
+// Portfolio-level risk checks that look across markets
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::market_key::MarketKey;
+use crate::metrics;
+use crate::position_tracker::ArbPosition;
+
+/// Asset symbols and the names used in market questions ("Bitcoin Up or Down - ...")
+const ASSET_ALIASES: &[(&str, &str)] = &[
+    ("btc", "bitcoin"),
+    ("eth", "ethereum"),
+    ("sol", "solana"),
+    ("xrp", "xrp"),
+];
+
+/// Resolve an asset symbol from a market id / question
+pub fn asset_from_text(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    ASSET_ALIASES.iter()
+        .find(|(symbol, name)| {
+            lower.contains(name)
+                || lower.split(|c: char| !c.is_ascii_alphanumeric()).any(|w| w == *symbol)
+        })
+        .map(|(symbol, _)| *symbol)
+}
+
+// =============================================================================
+// CORRELATION GUARD
+// =============================================================================
+
+/// Correlation guard configuration from environment
+#[derive(Debug, Clone)]
+pub struct CorrelationConfig {
+    /// Groups of assets treated as one directional bet
+    pub groups: Vec<Vec<String>>,
+
+    /// Maximum absolute net unmatched exposure per group (contracts, 0 = disabled)
+    pub max_group_exposure: f64,
+}
+
+impl CorrelationConfig {
+    pub fn from_env() -> Self {
+        Self {
+            // Groups separated by ';', assets by ',' e.g. "btc,eth,sol,xrp"
+            groups: std::env::var("CORR_GROUPS")
+                .ok()
+                .map(|v| parse_groups(&v))
+                .unwrap_or_else(|| vec![vec!["btc".into(), "eth".into(), "sol".into(), "xrp".into()]]),
+
+            max_group_exposure: std::env::var("CORR_MAX_GROUP_EXPOSURE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100.0),
+        }
+    }
+
+    fn group_of(&self, asset: &str) -> Option<usize> {
+        self.groups.iter().position(|g| g.iter().any(|a| a == asset))
+    }
+}
+
+fn parse_groups(s: &str) -> Vec<Vec<String>> {
+    s.split(';')
+        .map(|g| {
+            g.split(',')
+                .map(|a| a.trim().to_lowercase())
+                .filter(|a| !a.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|g| !g.is_empty())
+        .collect()
+}
+
+/// Reason a trade was blocked by the correlation guard
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationBreach {
+    pub group: Vec<String>,
+    pub net_exposure: f64,
+    pub worst_case: f64,
+    pub limit: f64,
+}
+
+impl std::fmt::Display for CorrelationBreach {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Correlated exposure [{}]: net {:+.1}, worst case {:.1} contracts (limit: {:.1})",
+               self.group.join(","), self.net_exposure, self.worst_case, self.limit)
+    }
+}
+
+/// Net unmatched exposure held in one market
+#[derive(Debug, Clone)]
+struct MarketExposure {
+    asset: String,
+    net: f64,
+    /// Unix seconds the market ends (0 = open-ended)
+    end_ts: i64,
+}
+
+/// Nets unmatched directional exposure across correlated assets and caps it per group.
+/// Exposure is held per market and released once the market ends; exits release it
+/// as negative fills.
+///
+/// Exposure sign: positive = excess YES/Up contracts, negative = excess NO/Down.
+pub struct CorrelationGuard {
+    config: CorrelationConfig,
+    exposure: Mutex<HashMap<String, MarketExposure>>,
+}
+
+impl CorrelationGuard {
+    pub fn new(config: CorrelationConfig) -> Self {
+        Self {
+            config,
+            exposure: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seed exposure from persisted open positions (asset resolved from the market text)
+    pub fn seed_from_positions<'a>(&self, positions: impl IntoIterator<Item = &'a ArbPosition>) {
+        let mut seeded = 0;
+        for pos in positions {
+            let net = pos.net_exposure();
+            if net.abs() < 1e-9 {
+                continue;
+            }
+            let asset = asset_from_text(&pos.market_id).or_else(|| asset_from_text(&pos.description));
+            if let Some(asset) = asset {
+                let end_ts = pos.key.as_ref().map_or(0, |k| k.end_ts);
+                self.record(&pos.market_id, end_ts, asset, net);
+                seeded += 1;
+            }
+        }
+        if seeded > 0 {
+            info!("[RISK] Seeded correlated exposure from {} open positions", seeded);
+        }
+    }
+
+    /// Forget all exposure (before re-seeding from another instance's positions)
+    pub fn reset(&self) {
+        self.exposure.lock().unwrap().clear();
+    }
+
+    /// Record filled contracts in a market (negative contracts for sales)
+    pub fn record_fill(&self, key: &MarketKey, asset: &str, yes_contracts: f64, no_contracts: f64) {
+        self.record(&key.id(), key.end_ts, asset, yes_contracts - no_contracts);
+    }
+
+    fn record(&self, market: &str, end_ts: i64, asset: &str, net: f64) {
+        let mut exposure = self.exposure.lock().unwrap();
+        exposure.entry(market.to_string())
+            .or_insert_with(|| MarketExposure { asset: asset.to_lowercase(), net: 0.0, end_ts })
+            .net += net;
+    }
+
+    /// Net signed exposure for the group containing `asset` (just the asset if ungrouped)
+    pub fn group_exposure(&self, asset: &str) -> f64 {
+        self.group_exposure_at(asset, chrono::Utc::now().timestamp())
+    }
+
+    /// Group exposure at `now_ts`, first releasing markets that have ended
+    fn group_exposure_at(&self, asset: &str, now_ts: i64) -> f64 {
+        let asset = asset.to_lowercase();
+        let mut exposure = self.exposure.lock().unwrap();
+        exposure.retain(|_, e| e.end_ts <= 0 || e.end_ts > now_ts);
+        let group = self.config.group_of(&asset).map(|idx| &self.config.groups[idx]);
+        exposure.values()
+            .filter(|e| group.map_or(e.asset == asset, |g| g.contains(&e.asset)))
+            .map(|e| e.net)
+            .sum()
+    }
+
+    /// Check a trade of `contracts` per leg: in the worst case the whole size ends up
+    /// unmatched in the direction that adds to the group's existing net exposure.
+    pub fn check(&self, asset: &str, contracts: f64) -> Result<(), CorrelationBreach> {
+        self.check_at(asset, contracts, chrono::Utc::now().timestamp())
+    }
+
+    fn check_at(&self, asset: &str, contracts: f64, now_ts: i64) -> Result<(), CorrelationBreach> {
+        if self.config.max_group_exposure <= 0.0 {
+            return Ok(());
+        }
+
+        let net = self.group_exposure_at(asset, now_ts);
+        let worst_case = net.abs() + contracts;
+        if worst_case > self.config.max_group_exposure {
+            metrics::incr("risk.correlation.rejected");
+            let asset = asset.to_lowercase();
+            let group = self.config.group_of(&asset)
+                .map(|idx| self.config.groups[idx].clone())
+                .unwrap_or_else(|| vec![asset]);
+            return Err(CorrelationBreach {
+                group,
+                net_exposure: net,
+                worst_case,
+                limit: self.config.max_group_exposure,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(limit: f64) -> CorrelationGuard {
+        CorrelationGuard::new(CorrelationConfig {
+            groups: parse_groups("btc,eth,sol,xrp"),
+            max_group_exposure: limit,
+        })
+    }
+
+    #[test]
+    fn test_asset_from_text() {
+        assert_eq!(asset_from_text("Bitcoin Up or Down - December 18, 7:15PM ET"), Some("btc"));
+        assert_eq!(asset_from_text("eth-updown-15m-1766100600"), Some("eth"));
+        assert_eq!(asset_from_text("Lakers vs Celtics"), None);
+    }
+
+    fn key(asset: &str, end_ts: i64) -> MarketKey {
+        MarketKey::polymarket(&format!("{}-updown-15m-{}", asset, end_ts - 900), "", "", end_ts)
+    }
+
+    #[test]
+    fn test_exposure_nets_across_group() {
+        let g = guard(20.0);
+        g.record_fill(&key("btc", 0), "btc", 10.0, 2.0);  // +8 Up
+        g.record_fill(&key("eth", 0), "eth", 0.0, 5.0);   // -5 Down
+        assert!((g.group_exposure("sol") - 3.0).abs() < 1e-9);
+
+        // 3 net + 15 worst case = 18 < 20
+        assert!(g.check("xrp", 15.0).is_ok());
+
+        g.record_fill(&key("sol", 0), "sol", 12.0, 0.0);  // net now +15
+        let breach = g.check("btc", 10.0).unwrap_err();
+        assert!((breach.worst_case - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exposure_released_on_exit_and_market_end() {
+        let g = guard(20.0);
+        let (btc, eth) = (key("btc", 1_000), key("eth", 2_000));
+        g.record_fill(&btc, "btc", 15.0, 0.0);
+        g.record_fill(&eth, "eth", 4.0, 0.0);
+        assert!(g.check_at("sol", 5.0, 500).is_err());
+
+        // Selling the unmatched Up leg releases it
+        g.record_fill(&eth, "eth", -4.0, 0.0);
+        assert!((g.group_exposure_at("sol", 500) - 15.0).abs() < 1e-9);
+
+        // Once the BTC market ends its exposure is gone
+        assert!(g.group_exposure_at("sol", 1_000).abs() < 1e-9);
+        assert!(g.check_at("sol", 20.0, 1_000).is_ok());
+    }
+
+    #[test]
+    fn test_seed_from_positions() {
+        let mut pos = ArbPosition::new("Solana Up or Down - Dec 18", "Solana Up or Down - Dec 18");
+        pos.poly_yes.add(10.0, 0.4);
+        pos.poly_no.add(4.0, 0.5);
+
+        let g = guard(100.0);
+        g.seed_from_positions([&pos]);
+        assert!((g.group_exposure("btc") - 6.0).abs() < 1e-9);
+
+        // A keyed position stops counting once its market has ended
+        let eth = key("eth", 1_000);
+        let mut ended = ArbPosition::new(&eth.id(), "Ethereum Up or Down");
+        ended.key = Some(eth);
+        ended.poly_no.add(5.0, 0.5);
+        g.seed_from_positions([&ended]);
+        assert!((g.group_exposure_at("btc", 999) - 1.0).abs() < 1e-9);
+        assert!((g.group_exposure_at("btc", 1_000) - 6.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,388 @@
+// src/rpc_pool.rs
+// Polygon JSON-RPC provider pool - health checks, failover for reads and sends, cross-verified reads for reconciliation
+
+use anyhow::{anyhow, bail, Result};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockId, BlockNumber, Bytes};
+use futures_util::future::join_all;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::metrics;
+
+/// Verified reads are pinned this many blocks behind the head, so lagging providers have the block
+const VERIFY_CONFIRMATIONS: u64 = 2;
+
+/// RPC pool configuration from environment
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    /// Endpoints in order of preference (POLYGON_RPC_URLS, comma-separated; else POLYGON_RPC_URL)
+    pub urls: Vec<String>,
+
+    /// Per-request timeout (milliseconds)
+    pub timeout_ms: u64,
+
+    /// Consecutive failures before an endpoint is taken out of rotation
+    pub fail_limit: u32,
+
+    /// Seconds an endpoint stays out of rotation
+    pub cooldown_secs: u64,
+
+    /// Blocks an endpoint may trail the best head before it counts as unhealthy
+    pub max_lag_blocks: u64,
+
+    /// Providers that must return the same answer for a verified read (capped at the pool size)
+    pub verify_quorum: usize,
+
+    /// Seconds between background health checks
+    pub health_secs: u64,
+}
+
+impl RpcConfig {
+    pub fn from_env() -> Self {
+        let urls = std::env::var("POLYGON_RPC_URLS")
+            .or_else(|_| std::env::var("POLYGON_RPC_URL"))
+            .unwrap_or_else(|_| "https://polygon-rpc.com".to_string());
+
+        Self {
+            urls: urls.split(',').map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).collect(),
+
+            timeout_ms: std::env::var("RPC_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5000),
+
+            fail_limit: std::env::var("RPC_FAIL_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+
+            cooldown_secs: std::env::var("RPC_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+
+            max_lag_blocks: std::env::var("RPC_MAX_LAG_BLOCKS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+
+            verify_quorum: std::env::var("RPC_VERIFY_QUORUM")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+
+            health_secs: std::env::var("RPC_HEALTH_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+        }
+    }
+}
+
+/// Host part of an endpoint URL (the path often carries an API key and never gets logged)
+pub fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    rest.split(['/', '?']).next().unwrap_or(rest)
+}
+
+/// Rolling health of one endpoint
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Health {
+    failures: u32,
+    /// Out of rotation until this unix time (0 = in rotation)
+    down_until: i64,
+    latency_ms: u64,
+    /// Head block at the last health check
+    block: Option<u64>,
+}
+
+impl Health {
+    fn is_up(&self, now: i64) -> bool {
+        self.down_until <= now
+    }
+
+    /// Returns true when this success brings the endpoint back into rotation
+    fn succeed(&mut self, latency_ms: u64) -> bool {
+        let recovered = self.down_until > 0;
+        self.failures = 0;
+        self.down_until = 0;
+        self.latency_ms = latency_ms;
+        recovered
+    }
+
+    /// Returns true when this failure takes the endpoint out of rotation
+    fn fail(&mut self, fail_limit: u32, cooldown_secs: u64, now: i64) -> bool {
+        self.failures += 1;
+        if self.failures >= fail_limit && self.down_until <= now {
+            self.down_until = now + cooldown_secs as i64;
+            return true;
+        }
+        false
+    }
+}
+
+/// Endpoints to try in order: in rotation first (fewest failures, then fastest), then the
+/// ones out of rotation as a last resort (soonest back first)
+fn ranking(health: &[Health], now: i64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..health.len()).collect();
+    order.sort_by_key(|&i| {
+        let h = &health[i];
+        if h.is_up(now) { (0, h.failures as i64, h.latency_ms as i64) } else { (1, h.down_until, 0) }
+    });
+    order
+}
+
+/// Endpoints whose head trails the best one by more than `max_lag` blocks
+fn lagging(blocks: &[Option<u64>], max_lag: u64) -> Vec<bool> {
+    let best = blocks.iter().flatten().max().copied().unwrap_or(0);
+    blocks.iter().map(|b| matches!(b, Some(b) if b + max_lag < best)).collect()
+}
+
+/// The answer a strict majority of `answers` agree on, if at least `quorum` endpoints gave it
+/// (a tie is no answer)
+fn consensus<T: PartialEq + Clone>(answers: &[T], quorum: usize) -> Option<T> {
+    answers.iter()
+        .map(|a| (a, answers.iter().filter(|b| *b == a).count()))
+        .find(|(_, n)| *n * 2 > answers.len() && *n >= quorum.max(1))
+        .map(|(a, _)| a.clone())
+}
+
+struct Endpoint {
+    url: String,
+    provider: Provider<Http>,
+    health: Mutex<Health>,
+}
+
+/// Health of one endpoint, for status output
+#[derive(Debug, Clone)]
+pub struct EndpointStatus {
+    pub host: String,
+    pub up: bool,
+    pub failures: u32,
+    pub latency_ms: u64,
+    pub block: Option<u64>,
+}
+
+/// Polygon RPC endpoints with health tracking and failover
+pub struct RpcPool {
+    config: RpcConfig,
+    endpoints: Vec<Endpoint>,
+}
+
+impl RpcPool {
+    pub fn new(config: RpcConfig) -> Result<Self> {
+        if config.urls.is_empty() {
+            bail!("no Polygon RPC endpoints configured");
+        }
+        let endpoints = config.urls.iter()
+            .map(|url| Ok(Endpoint {
+                url: url.clone(),
+                provider: Provider::<Http>::try_from(url.as_str())
+                    .map_err(|e| anyhow!("invalid RPC URL for {}: {}", host(url), e))?,
+                health: Mutex::new(Health::default()),
+            }))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { config, endpoints })
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    fn ranked(&self) -> Vec<usize> {
+        let health: Vec<Health> = self.endpoints.iter().map(|e| e.health.lock().unwrap().clone()).collect();
+        ranking(&health, chrono::Utc::now().timestamp())
+    }
+
+    fn record_success(&self, i: usize, latency_ms: u64) {
+        if self.endpoints[i].health.lock().unwrap().succeed(latency_ms) {
+            info!("[RPC] ✅ {} back in rotation", host(&self.endpoints[i].url));
+        }
+    }
+
+    /// A failed request; out of rotation after `fail_limit` in a row
+    fn record_failure(&self, i: usize, error: &anyhow::Error, fail_limit: u32) {
+        metrics::incr("rpc.errors");
+        let down = self.endpoints[i].health.lock().unwrap()
+            .fail(fail_limit, self.config.cooldown_secs, chrono::Utc::now().timestamp());
+        if down {
+            warn!("[RPC] 🔴 {} out of rotation for {}s: {}", host(&self.endpoints[i].url), self.config.cooldown_secs, error);
+        }
+    }
+
+    /// One request against endpoint `i`, timed out and recorded in its health
+    async fn request<T, F, Fut>(&self, i: usize, f: F) -> Result<T>
+    where
+        F: FnOnce(Provider<Http>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let started = Instant::now();
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let result = match tokio::time::timeout(timeout, f(self.endpoints[i].provider.clone())).await {
+            Ok(r) => r,
+            Err(_) => Err(anyhow!("timed out after {}ms", self.config.timeout_ms)),
+        };
+        match &result {
+            Ok(_) => self.record_success(i, started.elapsed().as_millis() as u64),
+            Err(e) => self.record_failure(i, e, self.config.fail_limit),
+        }
+        result
+    }
+
+    /// Provider of the healthiest endpoint, for transactions (sends aren't retried on another
+    /// endpoint: a send that timed out may still have been broadcast)
+    pub fn provider(&self) -> Provider<Http> {
+        self.endpoints[self.ranked()[0]].provider.clone()
+    }
+
+    /// eth_call, failing over through the endpoints in health order
+    pub async fn call(&self, tx: &TypedTransaction) -> Result<Bytes> {
+        let mut last_err = None;
+        for (attempt, i) in self.ranked().into_iter().enumerate() {
+            if attempt > 0 {
+                metrics::incr("rpc.failovers");
+            }
+            match self.request(i, |p| async move { Ok(p.call(tx, None).await?) }).await {
+                Ok(result) => return Ok(result),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow!("no RPC endpoints")))
+    }
+
+    /// eth_call on every endpoint at the same (slightly confirmed) block; the answer must come
+    /// from a strict majority of those that answered, and at least RPC_VERIFY_QUORUM of them. Used for reads that reconciliation acts on.
+    pub async fn call_verified(&self, tx: &TypedTransaction) -> Result<Bytes> {
+        let head = self.block_number().await?;
+        let block: BlockId = BlockNumber::Number(head.saturating_sub(VERIFY_CONFIRMATIONS).into()).into();
+        let results = join_all((0..self.endpoints.len())
+            .map(|i| self.request(i, move |p| async move { Ok(p.call(tx, Some(block)).await?) })))
+            .await;
+        let answers: Vec<Bytes> = results.into_iter().flatten().collect();
+        let quorum = self.config.verify_quorum.min(self.endpoints.len());
+        consensus(&answers, quorum).ok_or_else(|| {
+            metrics::incr("rpc.disagreements");
+            anyhow!("RPC providers disagree or are unavailable ({} of {} answered, a majority and at least {} must agree)",
+                    answers.len(), self.endpoints.len(), quorum)
+        })
+    }
+
+    /// Latest block, failing over through the endpoints in health order
+    pub async fn block_number(&self) -> Result<u64> {
+        let mut last_err = None;
+        for i in self.ranked() {
+            match self.request(i, |p| async move { Ok(p.get_block_number().await?.as_u64()) }).await {
+                Ok(block) => return Ok(block),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow!("no RPC endpoints")))
+    }
+
+    /// Query every endpoint's head; endpoints trailing the best by more than RPC_MAX_LAG_BLOCKS
+    /// leave rotation at once (their reads would be stale). Returns how many are in rotation.
+    pub async fn check_health(&self) -> usize {
+        let results = join_all((0..self.endpoints.len())
+            .map(|i| self.request(i, |p| async move { Ok(p.get_block_number().await?.as_u64()) })))
+            .await;
+        let blocks: Vec<Option<u64>> = results.into_iter().map(|r| r.ok()).collect();
+        for (i, lags) in lagging(&blocks, self.config.max_lag_blocks).into_iter().enumerate() {
+            self.endpoints[i].health.lock().unwrap().block = blocks[i];
+            if lags {
+                let best = blocks.iter().flatten().max().copied().unwrap_or(0);
+                self.record_failure(i, &anyhow!("{} blocks behind", best - blocks[i].unwrap_or(0)), 1);
+            }
+        }
+        let up = self.status().iter().filter(|s| s.up).count();
+        metrics::set_gauge("rpc.healthy", up as i64);
+        up
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = chrono::Utc::now().timestamp();
+        self.endpoints.iter()
+            .map(|e| {
+                let h = e.health.lock().unwrap();
+                EndpointStatus { host: host(&e.url).to_string(), up: h.is_up(now), failures: h.failures,
+                                 latency_ms: h.latency_ms, block: h.block }
+            })
+            .collect()
+    }
+
+    /// Health-check every RPC_HEALTH_SECS in the background
+    pub fn spawn_health(self: &Arc<Self>) {
+        if self.config.health_secs == 0 {
+            return;
+        }
+        info!("[RPC] {} Polygon endpoint(s): {}", self.len(),
+              self.endpoints.iter().map(|e| host(&e.url)).collect::<Vec<_>>().join(", "));
+        let pool = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(pool.config.health_secs));
+            loop {
+                ticker.tick().await;
+                if pool.check_health().await == 0 {
+                    warn!("[RPC] ⚠️ No Polygon RPC endpoint healthy");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_take_endpoint_out_of_rotation() {
+        let mut h = Health::default();
+        assert!(!h.fail(3, 60, 1_000));
+        assert!(!h.fail(3, 60, 1_000));
+        assert!(h.fail(3, 60, 1_000));
+        assert!(!h.is_up(1_030) && h.is_up(1_060));
+        // Further failures while down don't extend the cooldown
+        assert!(!h.fail(3, 60, 1_030));
+        assert_eq!(h.down_until, 1_060);
+        assert!(h.succeed(120));
+        assert_eq!((h.failures, h.down_until, h.latency_ms), (0, 0, 120));
+        assert!(!h.succeed(80));
+    }
+
+    #[test]
+    fn test_ranking_and_lag() {
+        let health = vec![
+            Health { failures: 1, latency_ms: 50, ..Default::default() },
+            Health { failures: 3, down_until: 2_000, ..Default::default() },
+            Health { latency_ms: 200, ..Default::default() },
+            Health { failures: 3, down_until: 1_500, ..Default::default() },
+        ];
+        assert_eq!(ranking(&health, 1_000), vec![2, 0, 3, 1]);
+        // Cooldown over: back in rotation, behind endpoints with fewer failures
+        assert_eq!(ranking(&health, 1_500), vec![2, 0, 3, 1]);
+
+        assert_eq!(lagging(&[Some(100), Some(95), Some(89), None], 10), vec![false, false, true, false]);
+        assert_eq!(host("https://polygon-mainnet.g.alchemy.com/v2/SECRET"), "polygon-mainnet.g.alchemy.com");
+        assert_eq!(host("http://localhost:8545"), "localhost:8545");
+    }
+
+    #[test]
+    fn test_consensus() {
+        assert_eq!(consensus(&["a", "a", "b"], 2), Some("a"));
+        assert_eq!(consensus(&["a", "b", "c"], 2), None);
+        assert_eq!(consensus(&["a"], 2), None);
+        assert_eq!(consensus(&["a"], 1), Some("a"));
+        // Quorum met on both sides of a split: no majority, no answer
+        assert_eq!(consensus(&["a", "a", "b", "b"], 2), None);
+        assert_eq!(consensus(&["a", "b", "a", "c", "a"], 2), Some("a"));
+        assert_eq!(consensus(&["a", "b", "a", "c", "d"], 2), None);
+        assert_eq!(consensus::<&str>(&[], 1), None);
+    }
+}
@@ -0,0 +1,327 @@
+// src/run_windows.rs
+// Scheduled run windows - per-strategy trading hours from bot.toml; feeds stay up outside them
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, Local, Offset, TimeZone, Timelike, Utc};
+use serde::Deserialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::logging;
+use crate::metrics;
+
+/// Zone the windows are written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowZone {
+    Utc,
+    /// System zone (follows TZ, e.g. TZ=America/New_York, including DST)
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl WindowZone {
+    fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "utc" | "z" => Ok(Self::Utc),
+            "local" => Ok(Self::Local),
+            offset => {
+                let (sign, rest) = match offset.split_at_checked(1) {
+                    Some(("+", rest)) => (1, rest),
+                    Some(("-", rest)) => (-1, rest),
+                    _ => return Err(anyhow!("timezone {:?}: expected utc, local or an offset like -05:00", s)),
+                };
+                let (h, m) = rest.split_once(':').unwrap_or((rest, "0"));
+                let secs = h.parse::<i32>()? * 3600 + m.parse::<i32>()? * 60;
+                FixedOffset::east_opt(sign * secs)
+                    .map(Self::Fixed)
+                    .ok_or_else(|| anyhow!("timezone {:?} out of range", s))
+            }
+        }
+    }
+
+    /// (weekday 0 = Monday, minute of day) of `now` in this zone
+    fn local_time(&self, now: DateTime<Utc>) -> (u32, u32) {
+        let offset = match self {
+            Self::Utc => Utc.fix(),
+            Self::Local => Local.offset_from_utc_datetime(&now.naive_utc()).fix(),
+            Self::Fixed(offset) => *offset,
+        };
+        let t = now.with_timezone(&offset);
+        (t.weekday().num_days_from_monday(), t.hour() * 60 + t.minute())
+    }
+}
+
+/// One window: on `days`, from `start` to `end` (an end before the start runs past midnight)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunWindow {
+    /// Strategies it applies to (empty = all)
+    pub strategies: Vec<String>,
+    /// Bit 0 = Monday ... bit 6 = Sunday
+    pub days: u8,
+    pub start_min: u32,
+    pub end_min: u32,
+}
+
+impl RunWindow {
+    fn applies_to(&self, strategy: &str) -> bool {
+        self.strategies.is_empty() || self.strategies.iter().any(|s| s == strategy)
+    }
+
+    fn contains(&self, weekday: u32, minute: u32) -> bool {
+        let on = |day: u32| self.days & (1 << (day % 7)) != 0;
+        if self.start_min <= self.end_min {
+            on(weekday) && minute >= self.start_min && minute < self.end_min
+        } else {
+            // Overnight: the part after midnight belongs to the previous day's window
+            (on(weekday) && minute >= self.start_min) || (on(weekday + 6) && minute < self.end_min)
+        }
+    }
+}
+
+/// Trading hours for every strategy. A strategy with no window trades around the clock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunWindows {
+    pub zone: WindowZone,
+    pub windows: Vec<RunWindow>,
+}
+
+impl Default for RunWindows {
+    fn default() -> Self {
+        Self { zone: WindowZone::Utc, windows: Vec::new() }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawWindow {
+    #[serde(default)]
+    strategies: Vec<String>,
+    /// "mon-fri", "sat,sun", "*" (default: every day)
+    days: Option<String>,
+    /// "HH:MM-HH:MM"
+    hours: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawSchedule {
+    timezone: Option<String>,
+    #[serde(default)]
+    window: Vec<RawWindow>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BotToml {
+    #[serde(default)]
+    schedule: RawSchedule,
+}
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+fn parse_day(s: &str) -> Result<u32> {
+    let s = s.trim().to_lowercase();
+    DAY_NAMES.iter()
+        .position(|d| s.starts_with(d))
+        .map(|i| i as u32)
+        .ok_or_else(|| anyhow!("unknown day {:?}", s))
+}
+
+/// "mon-fri", "sat,sun", "fri-mon" (wraps) or "*"
+fn parse_days(s: &str) -> Result<u8> {
+    if s.trim() == "*" {
+        return Ok(0x7f);
+    }
+    let mut days = 0u8;
+    for part in s.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                let mut day = from;
+                loop {
+                    days |= 1 << day;
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            None => days |= 1 << parse_day(part)?,
+        }
+    }
+    Ok(days)
+}
+
+fn parse_minute(s: &str) -> Result<u32> {
+    let (h, m) = s.trim().split_once(':').ok_or_else(|| anyhow!("time {:?}: expected HH:MM", s))?;
+    let (h, m) = (h.parse::<u32>()?, m.parse::<u32>()?);
+    if h > 24 || m > 59 || (h == 24 && m > 0) {
+        return Err(anyhow!("time {:?} out of range", s));
+    }
+    Ok(h * 60 + m)
+}
+
+impl RunWindows {
+    /// `[schedule]` section of BOT_CONFIG (default bot.toml); missing file or section = always open
+    pub fn load() -> Result<Self> {
+        let path = logging::config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents).with_context(|| format!("invalid [schedule] in {}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let raw = toml::from_str::<BotToml>(contents)?.schedule;
+        let zone = raw.timezone.as_deref().map(WindowZone::parse).transpose()?.unwrap_or(WindowZone::Utc);
+        let windows = raw.window.into_iter()
+            .map(|w| {
+                let (start, end) = w.hours.split_once('-')
+                    .ok_or_else(|| anyhow!("hours {:?}: expected HH:MM-HH:MM", w.hours))?;
+                Ok(RunWindow {
+                    strategies: w.strategies.iter().map(|s| s.to_lowercase()).collect(),
+                    days: w.days.as_deref().map(parse_days).transpose()?.unwrap_or(0x7f),
+                    start_min: parse_minute(start)?,
+                    end_min: parse_minute(end)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { zone, windows })
+    }
+
+    /// Whether `strategy` may trade at `now`
+    pub fn is_open_at(&self, strategy: &str, now: DateTime<Utc>) -> bool {
+        let mut windows = self.windows.iter().filter(|w| w.applies_to(strategy)).peekable();
+        if windows.peek().is_none() {
+            return true;
+        }
+        let (weekday, minute) = self.zone.local_time(now);
+        windows.any(|w| w.contains(weekday, minute))
+    }
+
+    pub fn is_open(&self, strategy: &str) -> bool {
+        self.is_open_at(strategy, Utc::now())
+    }
+
+    pub fn is_scheduled(&self, strategy: &str) -> bool {
+        self.windows.iter().any(|w| w.applies_to(strategy))
+    }
+}
+
+fn global() -> &'static OnceLock<RunWindows> {
+    static WINDOWS: OnceLock<RunWindows> = OnceLock::new();
+    &WINDOWS
+}
+
+/// Process-wide windows (loaded by `start`; without it, loaded on first use)
+pub fn windows() -> &'static RunWindows {
+    global().get_or_init(|| RunWindows::load().unwrap_or_else(|e| {
+        warn!("[SCHEDULE] {:#} - trading without run windows", e);
+        RunWindows::default()
+    }))
+}
+
+/// Whether `strategy` may trade right now
+#[inline]
+pub fn is_open(strategy: &str) -> bool {
+    windows().is_open(strategy)
+}
+
+/// Load the windows (an invalid [schedule] is a startup error, not silently always-open) and
+/// log each scheduled strategy's window opening / closing (gauge `schedule.open.<strategy>`)
+pub fn start(strategies: &'static [&'static str]) -> Result<()> {
+    let _ = global().set(RunWindows::load()?);
+    let scheduled: Vec<&'static str> = strategies.iter().copied().filter(|s| windows().is_scheduled(s)).collect();
+    if scheduled.is_empty() {
+        return Ok(());
+    }
+    tokio::spawn(async move {
+        let mut last: Vec<Option<bool>> = vec![None; scheduled.len()];
+        let mut ticker = tokio::time::interval(Duration::from_secs(30));
+        loop {
+            ticker.tick().await;
+            for (strategy, last) in scheduled.iter().zip(last.iter_mut()) {
+                let open = is_open(strategy);
+                if *last != Some(open) {
+                    if open {
+                        info!("[SCHEDULE] ▶️  {} run window open - trading enabled", strategy);
+                    } else {
+                        info!("[SCHEDULE] ⏸️  {} outside its run window - collecting data, not trading", strategy);
+                    }
+                    metrics::set_gauge(&format!("schedule.open.{}", strategy), open as i64);
+                    *last = Some(open);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let windows = RunWindows::parse(r#"
+            [logging.console]
+            level = "info"
+
+            [schedule]
+            timezone = "-05:00"
+
+            [[schedule.window]]
+            strategies = ["updown"]
+            days = "mon-fri"
+            hours = "09:30-16:00"
+
+            [[schedule.window]]
+            days = "fri-sun"
+            hours = "22:00-02:00"
+        "#).unwrap();
+        assert_eq!(windows.zone, WindowZone::Fixed(FixedOffset::west_opt(5 * 3600).unwrap()));
+        assert_eq!(windows.windows[0].days, 0b0011111);
+        assert_eq!(windows.windows[1].days, 0b1110000);
+        assert_eq!((windows.windows[0].start_min, windows.windows[0].end_min), (570, 960));
+
+        assert!(RunWindows::parse("[schedule]\ntimezone = \"mars\"").is_err());
+        assert!(RunWindows::parse("[[schedule.window]]\nhours = \"9-5\"").is_err());
+        assert!(RunWindows::parse("").unwrap().windows.is_empty());
+    }
+
+    #[test]
+    fn test_windows_per_strategy_in_zone() {
+        let windows = RunWindows::parse(r#"
+            [schedule]
+            timezone = "-05:00"
+            [[schedule.window]]
+            strategies = ["updown"]
+            days = "mon-fri"
+            hours = "09:30-16:00"
+        "#).unwrap();
+        // Wednesday 2025-12-17 14:30Z = 09:30 New York
+        assert!(windows.is_open_at("updown", at("2025-12-17T14:30:00Z")));
+        assert!(!windows.is_open_at("updown", at("2025-12-17T14:29:00Z")));
+        assert!(!windows.is_open_at("updown", at("2025-12-17T21:00:00Z")));
+        // Saturday
+        assert!(!windows.is_open_at("updown", at("2025-12-20T15:00:00Z")));
+        // Unscheduled strategies always trade
+        assert!(windows.is_open_at("cross", at("2025-12-20T15:00:00Z")));
+    }
+
+    #[test]
+    fn test_overnight_window_belongs_to_start_day() {
+        let windows = RunWindows::parse(r#"
+            [[schedule.window]]
+            days = "fri"
+            hours = "22:00-02:00"
+        "#).unwrap();
+        // Friday 2025-12-19 23:00Z, Saturday 01:00Z, but not Friday 01:00Z
+        assert!(windows.is_open_at("cross", at("2025-12-19T23:00:00Z")));
+        assert!(windows.is_open_at("cross", at("2025-12-20T01:00:00Z")));
+        assert!(!windows.is_open_at("cross", at("2025-12-19T01:00:00Z")));
+    }
+}
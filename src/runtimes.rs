@@ -0,0 +1,141 @@
+// src/runtimes.rs
+// Task priorities - market data and order submission own the main runtime; bookkeeping
+// (position writes, metrics, notifications, reports) runs on a runtime of its own
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Runtime layout from environment
+#[derive(Debug, Clone)]
+pub struct RuntimeConfig {
+    /// Worker threads of the hot runtime (market data, detection, execution; 0 = one per core)
+    pub hot_threads: usize,
+
+    /// Worker threads of the bookkeeping runtime (0 = bookkeeping shares the hot runtime)
+    pub bookkeeping_threads: usize,
+}
+
+impl RuntimeConfig {
+    pub fn from_env() -> Self {
+        Self {
+            hot_threads: std::env::var("HOT_WORKER_THREADS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+
+            bookkeeping_threads: std::env::var("BOOKKEEPING_THREADS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+        }
+    }
+}
+
+/// The runtime a trading binary's `main` blocks on
+pub fn hot(config: &RuntimeConfig) -> Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().thread_name("hot");
+    if config.hot_threads > 0 {
+        builder.worker_threads(config.hot_threads);
+    }
+    Ok(builder.build()?)
+}
+
+fn build_bookkeeping(config: &RuntimeConfig) -> Option<Runtime> {
+    if config.bookkeeping_threads == 0 {
+        return None;
+    }
+    match Builder::new_multi_thread()
+        .enable_all()
+        .worker_threads(config.bookkeeping_threads)
+        .thread_name("bookkeeping")
+        .build()
+    {
+        Ok(rt) => {
+            info!("[RUNTIME] Bookkeeping on {} dedicated thread(s)", config.bookkeeping_threads);
+            Some(rt)
+        }
+        Err(e) => {
+            warn!("[RUNTIME] Cannot start the bookkeeping runtime ({}); sharing the hot one", e);
+            None
+        }
+    }
+}
+
+/// The bookkeeping runtime (BOOKKEEPING_THREADS; None when it shares the hot runtime).
+/// Lives for the whole process: it is never dropped, so it can't be torn down from async code.
+pub fn bookkeeping() -> Option<&'static Handle> {
+    static RUNTIME: OnceLock<Option<Runtime>> = OnceLock::new();
+    RUNTIME.get_or_init(|| build_bookkeeping(&RuntimeConfig::from_env()))
+        .as_ref()
+        .map(Runtime::handle)
+}
+
+/// Spawn bookkeeping work where it can't delay the hot path
+pub fn spawn_bookkeeping<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match bookkeeping() {
+        Some(handle) => handle.spawn(future),
+        None => tokio::spawn(future),
+    }
+}
+
+/// Blocking bookkeeping (file writes, store CLIs) on the bookkeeping runtime's blocking pool
+pub fn spawn_blocking_bookkeeping<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match bookkeeping() {
+        Some(handle) => handle.spawn_blocking(f),
+        None => tokio::task::spawn_blocking(f),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(hot_threads: usize, bookkeeping_threads: usize) -> RuntimeConfig {
+        RuntimeConfig { hot_threads, bookkeeping_threads }
+    }
+
+    #[test]
+    fn test_bookkeeping_runs_on_its_own_threads() {
+        let rt = build_bookkeeping(&config(0, 1)).unwrap();
+        let name = rt.block_on(async {
+            tokio::spawn(async { std::thread::current().name().map(String::from) }).await.unwrap()
+        });
+        assert_eq!(name.as_deref(), Some("bookkeeping"));
+        assert!(build_bookkeeping(&config(0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_hot_runtime_threads() {
+        let rt = hot(&config(2, 1)).unwrap();
+        assert_eq!(rt.metrics().num_workers(), 2);
+        let name = rt.block_on(async {
+            tokio::spawn(async { std::thread::current().name().map(String::from) }).await.unwrap()
+        });
+        assert_eq!(name.as_deref(), Some("hot"));
+    }
+
+    #[test]
+    fn test_bookkeeping_spawn_from_hot_runtime() {
+        // A busy hot runtime (one worker, blocked) doesn't hold up bookkeeping work
+        let rt = hot(&config(1, 1)).unwrap();
+        let done = rt.block_on(async {
+            let (tx, rx) = std::sync::mpsc::channel();
+            spawn_bookkeeping(async move { tx.send(std::thread::current().name().map(String::from)).unwrap() });
+            rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap()
+        });
+        assert_eq!(done.as_deref(), Some("bookkeeping"));
+    }
+}
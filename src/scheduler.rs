@@ -0,0 +1,264 @@
+// src/scheduler.rs
+// Ranks concurrent opportunities and allocates limited capital to the best ones first
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// An arb competing for capital
+#[derive(Debug, Clone)]
+pub struct Opportunity<T> {
+    pub item: T,
+    /// Net edge per contract after fees (dollars)
+    pub edge: f64,
+    /// Contracts fillable on both legs
+    pub depth: f64,
+    /// Capital consumed per contract (sum of leg prices, dollars)
+    pub cost_per_contract: f64,
+    /// Seconds until the market resolves and the capital comes back
+    pub secs_to_expiry: u64,
+    /// Smallest size worth sending for this market (0 = only the batch minimum)
+    pub min_contracts: f64,
+}
+
+impl<T> Opportunity<T> {
+    /// Risk-adjusted score: net edge × depth ÷ time-to-expiry.
+    /// Shorter-dated arbs free their capital sooner, so they rank higher at equal edge.
+    pub fn score(&self) -> f64 {
+        if self.edge <= 0.0 || self.depth <= 0.0 {
+            return 0.0;
+        }
+        self.edge * self.depth / self.secs_to_expiry.max(1) as f64
+    }
+}
+
+/// Contracts granted to an opportunity
+#[derive(Debug, Clone)]
+pub struct Allocation<T> {
+    pub item: T,
+    pub contracts: f64,
+    pub score: f64,
+}
+
+/// Rank by score (best first) and hand out capital greedily.
+///
+/// `capital = None` means unconstrained: every opportunity gets its full depth.
+/// Opportunities that can't be funded for at least `min_contracts` (or their own minimum) are dropped.
+pub fn allocate<T>(mut opps: Vec<Opportunity<T>>, capital: Option<f64>, min_contracts: f64) -> Vec<Allocation<T>> {
+    opps.sort_by(|a, b| b.score().total_cmp(&a.score()));
+
+    let mut remaining = capital;
+    let mut out = Vec::with_capacity(opps.len());
+    for opp in opps {
+        let score = opp.score();
+        if score <= 0.0 || opp.cost_per_contract <= 0.0 {
+            continue;
+        }
+        let affordable = match remaining {
+            Some(cash) => (cash / opp.cost_per_contract).floor(),
+            None => f64::INFINITY,
+        };
+        let contracts = opp.depth.min(affordable);
+        if contracts < min_contracts.max(opp.min_contracts) {
+            continue;
+        }
+        if let Some(cash) = remaining.as_mut() {
+            *cash -= contracts * opp.cost_per_contract;
+        }
+        out.push(Allocation { item: opp.item, contracts, score });
+    }
+    out
+}
+
+/// Capital budget shared by executions. Approved executions reserve their expected cost
+/// up front, so overlapping orders can't together spend more than the limit; on completion
+/// the spent part is committed per market (until it resolves and is released) and the
+/// unfilled rest is credited back.
+pub struct CapitalPool {
+    /// Total capital in dollars (0 = unlimited)
+    limit: f64,
+    ledger: Mutex<Ledger>,
+}
+
+#[derive(Default)]
+struct Ledger {
+    committed: HashMap<String, f64>,
+    reserved: HashMap<u64, f64>,
+    next_id: u64,
+}
+
+/// Capital held for an execution in flight; hand it back with `settle` or `cancel`
+#[must_use = "a reservation holds capital until settled or cancelled"]
+#[derive(Debug)]
+pub struct Reservation {
+    id: u64,
+    market: String,
+    amount: f64,
+}
+
+impl Reservation {
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+}
+
+impl CapitalPool {
+    pub fn new(limit: f64) -> Self {
+        Self {
+            limit,
+            ledger: Mutex::new(Ledger::default()),
+        }
+    }
+
+    /// Budget from EXEC_CAPITAL (dollars, 0 or unset = unlimited)
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("EXEC_CAPITAL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0),
+        )
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.limit > 0.0
+    }
+
+    /// Capital neither committed nor reserved (None = unlimited)
+    pub fn available(&self) -> Option<f64> {
+        if !self.is_limited() {
+            return None;
+        }
+        Some(self.ledger.lock().unwrap().available(self.limit))
+    }
+
+    /// Capital held by executions in flight
+    pub fn reserved(&self) -> f64 {
+        self.ledger.lock().unwrap().reserved.values().sum()
+    }
+
+    /// Reserve `amount` for an approved execution on `market`.
+    /// None if it doesn't fit in what's left after commitments and other reservations.
+    pub fn reserve(&self, market: &str, amount: f64) -> Option<Reservation> {
+        let mut ledger = self.ledger.lock().unwrap();
+        if self.is_limited() && amount > ledger.available(self.limit) + 1e-9 {
+            return None;
+        }
+        ledger.next_id += 1;
+        let id = ledger.next_id;
+        ledger.reserved.insert(id, amount);
+        Some(Reservation { id, market: market.to_string(), amount })
+    }
+
+    /// Execution finished: commit what was actually spent, credit back the rest.
+    /// Returns the amount credited back.
+    pub fn settle(&self, reservation: Reservation, spent: f64) -> f64 {
+        let mut ledger = self.ledger.lock().unwrap();
+        ledger.reserved.remove(&reservation.id);
+        if spent > 0.0 {
+            *ledger.committed.entry(reservation.market).or_default() += spent;
+        }
+        (reservation.amount - spent).max(0.0)
+    }
+
+    /// Execution never went out: credit the whole reservation back
+    pub fn cancel(&self, reservation: Reservation) {
+        self.settle(reservation, 0.0);
+    }
+
+    /// Mark capital as spent on a market
+    pub fn commit(&self, market: &str, amount: f64) {
+        if amount > 0.0 {
+            *self.ledger.lock().unwrap().committed.entry(market.to_string()).or_default() += amount;
+        }
+    }
+
+    /// Return a resolved market's capital to the pool
+    pub fn release(&self, market: &str) -> f64 {
+        self.ledger.lock().unwrap().committed.remove(market).unwrap_or(0.0)
+    }
+}
+
+impl Ledger {
+    fn available(&self, limit: f64) -> f64 {
+        let held: f64 = self.committed.values().sum::<f64>() + self.reserved.values().sum::<f64>();
+        (limit - held).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opp(name: &'static str, edge: f64, depth: f64, secs: u64) -> Opportunity<&'static str> {
+        Opportunity { item: name, edge, depth, cost_per_contract: 1.0 - edge, secs_to_expiry: secs, min_contracts: 0.0 }
+    }
+
+    #[test]
+    fn test_ranks_by_edge_depth_and_expiry() {
+        let opps = vec![
+            opp("wide_far", 0.05, 50.0, 600),  // 0.0042
+            opp("thin_near", 0.02, 20.0, 60),  // 0.0067
+            opp("zero", 0.0, 100.0, 60),
+        ];
+        let alloc = allocate(opps, None, 1.0);
+        let order: Vec<_> = alloc.iter().map(|a| a.item).collect();
+        assert_eq!(order, vec!["thin_near", "wide_far"]);
+        assert_eq!(alloc[1].contracts, 50.0);
+    }
+
+    #[test]
+    fn test_capital_goes_to_best_first() {
+        let opps = vec![
+            opp("b", 0.02, 40.0, 300),
+            opp("a", 0.04, 40.0, 300),
+        ];
+        // 0.96 * 40 = 38.4 for "a", leaving 11.6 -> 11 contracts of "b" at 0.98
+        let alloc = allocate(opps, Some(50.0), 1.0);
+        assert_eq!(alloc[0].item, "a");
+        assert_eq!(alloc[0].contracts, 40.0);
+        assert_eq!(alloc[1].contracts, 11.0);
+
+        // Not enough left for the minimum
+        let alloc = allocate(vec![opp("a", 0.04, 40.0, 300)], Some(3.0), 5.0);
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    fn test_per_opportunity_minimum() {
+        let deep_only = Opportunity { min_contracts: 25.0, ..opp("btc", 0.04, 20.0, 300) };
+        let alloc = allocate(vec![deep_only, opp("xrp", 0.02, 20.0, 300)], None, 1.0);
+        let funded: Vec<_> = alloc.iter().map(|a| a.item).collect();
+        assert_eq!(funded, vec!["xrp"]);
+    }
+
+    #[test]
+    fn test_capital_pool_commit_release() {
+        let pool = CapitalPool::new(100.0);
+        pool.commit("m1", 30.0);
+        pool.commit("m2", 50.0);
+        assert_eq!(pool.available(), Some(20.0));
+        assert_eq!(pool.release("m1"), 30.0);
+        assert_eq!(pool.available(), Some(50.0));
+        assert_eq!(CapitalPool::new(0.0).available(), None);
+    }
+
+    #[test]
+    fn test_reservations_block_overlapping_overcommit() {
+        let pool = CapitalPool::new(100.0);
+        // Two arbs approved back to back: the second only sees what the first left
+        let btc = pool.reserve("btc", 60.0).unwrap();
+        assert!(pool.reserve("eth", 60.0).is_none());
+        let eth = pool.reserve("eth", 40.0).unwrap();
+        assert_eq!(pool.available(), Some(0.0));
+
+        // BTC only half filled: 30 committed, 30 credited back
+        assert_eq!(pool.settle(btc, 30.0), 30.0);
+        pool.cancel(eth);
+        assert_eq!(pool.available(), Some(70.0));
+        assert_eq!(pool.reserved(), 0.0);
+        assert_eq!(pool.release("btc"), 30.0);
+
+        // Unlimited pools still hand out reservations
+        assert!(CapitalPool::new(0.0).reserve("x", 1e9).is_some());
+    }
+}
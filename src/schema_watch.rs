@@ -0,0 +1,223 @@
+// src/schema_watch.rs
+// Schema drift detection - parse success and field-population rates per WS message type
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use tracing::{error, info};
+
+use crate::events::{self, BotEvent};
+use crate::metrics;
+
+/// Messages of a type needed before its rates are trusted
+const MIN_SAMPLES: usize = 50;
+
+/// Schema watch configuration from environment
+#[derive(Debug, Clone)]
+pub struct SchemaWatchConfig {
+    /// Recent messages per type the rates are computed over
+    pub window: usize,
+
+    /// Alert when fewer than this fraction of a type's messages parse
+    pub min_parse_rate: f64,
+
+    /// Alert when fewer than this fraction of parsed messages carry usable fields
+    /// (non-zero prices, populated levels)
+    pub min_field_rate: f64,
+}
+
+impl SchemaWatchConfig {
+    pub fn from_env() -> Self {
+        Self {
+            window: std::env::var("SCHEMA_WINDOW")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(200),
+
+            min_parse_rate: std::env::var("SCHEMA_MIN_PARSE_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.9),
+
+            min_field_rate: std::env::var("SCHEMA_MIN_FIELD_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.5),
+        }
+    }
+}
+
+/// What happened to one message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Didn't deserialize into the expected struct
+    Unparsed,
+    /// Deserialized; `populated` = the fields we rely on were present and sane
+    Parsed { populated: bool },
+}
+
+#[derive(Debug, Default)]
+struct TypeStats {
+    /// (parsed, populated) per recent message
+    samples: VecDeque<(bool, bool)>,
+    parsed: usize,
+    populated: usize,
+    drifting: bool,
+}
+
+impl TypeStats {
+    fn parse_rate(&self) -> f64 {
+        self.parsed as f64 / self.samples.len().max(1) as f64
+    }
+
+    fn field_rate(&self) -> f64 {
+        if self.parsed == 0 {
+            0.0
+        } else {
+            self.populated as f64 / self.parsed as f64
+        }
+    }
+}
+
+/// Tracks per-(feed, message type) rates and raises a `SchemaDrift` alert on degradation
+pub struct SchemaMonitor {
+    config: SchemaWatchConfig,
+    types: Mutex<HashMap<(&'static str, String), TypeStats>>,
+}
+
+impl SchemaMonitor {
+    pub fn new(config: SchemaWatchConfig) -> Self {
+        Self {
+            config,
+            types: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record one message. Returns true while the type is flagged as drifting.
+    pub fn observe(&self, feed: &'static str, message_type: &str, outcome: Outcome) -> bool {
+        let (parsed, populated) = match outcome {
+            Outcome::Unparsed => (false, false),
+            Outcome::Parsed { populated } => (true, populated),
+        };
+
+        let mut types = self.types.lock().unwrap();
+        let stats = types.entry((feed, message_type.to_string())).or_default();
+        if stats.samples.len() >= self.config.window.max(MIN_SAMPLES) {
+            if let Some((p, f)) = stats.samples.pop_front() {
+                stats.parsed -= p as usize;
+                stats.populated -= f as usize;
+            }
+        }
+        stats.samples.push_back((parsed, populated));
+        stats.parsed += parsed as usize;
+        stats.populated += populated as usize;
+
+        if stats.samples.len() < MIN_SAMPLES {
+            return false;
+        }
+        let (parse_rate, field_rate) = (stats.parse_rate(), stats.field_rate());
+        let drifting = parse_rate < self.config.min_parse_rate || field_rate < self.config.min_field_rate;
+
+        if drifting && !stats.drifting {
+            error!("[SCHEMA] 🚨 Possible {} schema drift on '{}': {:.0}% parsed, {:.0}% with usable fields (last {} msgs)",
+                   feed, message_type, parse_rate * 100.0, field_rate * 100.0, stats.samples.len());
+            metrics::incr(&format!("schema.drift.{}", feed));
+            events::publish(BotEvent::SchemaDrift {
+                feed: feed.to_string(),
+                message_type: message_type.to_string(),
+                parse_rate,
+                field_rate,
+            });
+        } else if !drifting && stats.drifting {
+            info!("[SCHEMA] ✅ {} '{}' messages parsing normally again", feed, message_type);
+        }
+        stats.drifting = drifting;
+        drifting
+    }
+}
+
+/// Process-wide monitor (configured from env on first use)
+pub fn monitor() -> &'static SchemaMonitor {
+    static MONITOR: OnceLock<SchemaMonitor> = OnceLock::new();
+    MONITOR.get_or_init(|| SchemaMonitor::new(SchemaWatchConfig::from_env()))
+}
+
+/// Record a payload that didn't parse, if it claims to be one of the `expected` types.
+/// Payloads of types the caller doesn't consume are ignored (not drift).
+pub fn observe_unparsed(feed: &'static str, text: &str, expected: &[&str]) {
+    let message_type = message_type_of(text);
+    if expected.contains(&message_type.as_str()) {
+        monitor().observe(feed, &message_type, Outcome::Unparsed);
+    }
+}
+
+/// Best-effort message type of a payload that failed to parse: the `event_type` / `type`
+/// field (of the first element for arrays), "invalid_json" or "unknown"
+pub fn message_type_of(text: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return "invalid_json".to_string();
+    };
+    let obj = match &value {
+        serde_json::Value::Array(items) => match items.first() {
+            Some(first) => first,
+            // Empty array: a book snapshot batch with nothing in it
+            None => return "book".to_string(),
+        },
+        other => other,
+    };
+    obj.get("event_type")
+        .or_else(|| obj.get("type"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> SchemaMonitor {
+        SchemaMonitor::new(SchemaWatchConfig { window: 100, min_parse_rate: 0.9, min_field_rate: 0.5 })
+    }
+
+    #[test]
+    fn test_parse_failures_raise_drift() {
+        let m = monitor();
+        for _ in 0..100 {
+            assert!(!m.observe("polymarket", "book", Outcome::Parsed { populated: true }));
+        }
+        // Format changes: every new message fails to parse
+        let mut flagged_after = None;
+        for i in 0..100 {
+            if m.observe("polymarket", "book", Outcome::Unparsed) {
+                flagged_after = Some(i + 1);
+                break;
+            }
+        }
+        // Parse rate drops below 90% after 11 failures in the 100-message window
+        assert_eq!(flagged_after, Some(11));
+        // Other types are unaffected
+        assert!(!m.observe("polymarket", "price_change", Outcome::Parsed { populated: true }));
+    }
+
+    #[test]
+    fn test_zero_price_books_raise_drift() {
+        let m = monitor();
+        let mut drifting = false;
+        for _ in 0..60 {
+            drifting = m.observe("kalshi", "orderbook_snapshot", Outcome::Parsed { populated: false });
+        }
+        assert!(drifting);
+        for _ in 0..200 {
+            drifting = m.observe("kalshi", "orderbook_snapshot", Outcome::Parsed { populated: true });
+        }
+        assert!(!drifting);
+    }
+
+    #[test]
+    fn test_message_type_of() {
+        assert_eq!(message_type_of(r#"[{"event_type":"book","asset_id":1}]"#), "book");
+        assert_eq!(message_type_of(r#"{"type":"orderbook_delta"}"#), "orderbook_delta");
+        assert_eq!(message_type_of("not json"), "invalid_json");
+        assert_eq!(message_type_of(r#"{"foo":1}"#), "unknown");
+    }
+}
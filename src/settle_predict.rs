@@ -0,0 +1,211 @@
+// src/settle_predict.rs
+// Interval-end settlement predictor - P(Up) from reference spot vs open and time left (Brownian), for exit decisions
+
+use crate::pyth::{PriceSample, PythPrices};
+
+/// Settlement predictor configuration from environment
+#[derive(Debug, Clone)]
+pub struct SettlePredictConfig {
+    /// Seconds before an interval's end in which predictions drive exits (0 = off)
+    pub window_secs: u64,
+
+    /// Minutes of reference history the volatility is measured over
+    pub vol_mins: u64,
+
+    /// Per-minute volatility (bps) used when the history is too thin to measure
+    pub default_vol_bps: f64,
+
+    /// Gap between P(win) and the bid needed to hold or dump regardless of the exit rules
+    pub margin: f64,
+}
+
+impl SettlePredictConfig {
+    pub fn from_env() -> Self {
+        Self {
+            window_secs: std::env::var("SETTLE_PREDICT_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+
+            vol_mins: std::env::var("SETTLE_PREDICT_VOL_MINS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15),
+
+            default_vol_bps: std::env::var("SETTLE_PREDICT_DEFAULT_VOL_BPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(7.0),
+
+            margin: std::env::var("SETTLE_PREDICT_MARGIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.05),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.window_secs > 0
+    }
+
+    /// Whether an interval ending at `end_secs` is inside the prediction window
+    pub fn in_window(&self, end_secs: u64, now_secs: u64) -> bool {
+        self.enabled() && now_secs < end_secs && end_secs - now_secs <= self.window_secs
+    }
+
+    /// Predict the interval [open_secs, end_secs] from the reference prices at `now_secs`
+    pub fn predict(&self, prices: &PythPrices, asset: &str, open_secs: u64, end_secs: u64,
+                   now_secs: u64, max_age_secs: u64) -> Option<Prediction> {
+        let open = prices.price_at(asset, open_secs, max_age_secs)?;
+        let spot = prices.latest(asset, now_secs, max_age_secs)?;
+        let history = prices.samples(asset, now_secs.saturating_sub(self.vol_mins * 60), now_secs);
+        let vol = realized_vol(&history).unwrap_or(self.default_vol_bps / 10_000.0 / 60f64.sqrt());
+        let secs_left = end_secs.saturating_sub(now_secs);
+        Some(Prediction {
+            p_up: prob_up(open.price, spot.price, secs_left, vol),
+            open: open.price,
+            spot: spot.price,
+            secs_left,
+            vol_bps_per_min: vol * 60f64.sqrt() * 10_000.0,
+        })
+    }
+}
+
+/// Where an interval is headed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    pub p_up: f64,
+    pub open: f64,
+    pub spot: f64,
+    pub secs_left: u64,
+    /// Volatility the prediction used
+    pub vol_bps_per_min: f64,
+}
+
+impl Prediction {
+    /// Probability the given side ("yes" = Up) settles at $1
+    pub fn p_win(&self, side: &str) -> f64 {
+        if side == "yes" { self.p_up } else { 1.0 - self.p_up }
+    }
+}
+
+/// What to do with an unmatched leg near the end of its interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Worth more held to settlement than sold at the bid - skip the exit rules
+    Hold,
+    /// Bid is above what settlement is likely to pay - sell now
+    Dump,
+    /// Too close to call - leave it to the exit rules
+    Defer,
+}
+
+impl Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Hold => "hold",
+            Decision::Dump => "dump",
+            Decision::Defer => "defer",
+        }
+    }
+}
+
+/// Compare a leg's chance of paying $1 with what it sells for now
+pub fn decide(p_win: f64, bid: f64, margin: f64) -> Decision {
+    if bid <= 0.0 || p_win >= bid + margin {
+        Decision::Hold
+    } else if bid >= p_win + margin {
+        Decision::Dump
+    } else {
+        Decision::Defer
+    }
+}
+
+/// P(close >= open) for a driftless Brownian log-price with `vol` per √second.
+/// Up wins ties, as on Polymarket.
+pub fn prob_up(open: f64, spot: f64, secs_left: u64, vol: f64) -> f64 {
+    if open <= 0.0 || spot <= 0.0 {
+        return 0.5;
+    }
+    let distance = (spot / open).ln();
+    let spread = vol * (secs_left as f64).sqrt();
+    if spread <= 0.0 {
+        return if distance >= 0.0 { 1.0 } else { 0.0 };
+    }
+    normal_cdf(distance / spread)
+}
+
+/// Realized volatility per √second from log returns between samples (None below 10 samples)
+pub fn realized_vol(samples: &[PriceSample]) -> Option<f64> {
+    if samples.len() < 10 {
+        return None;
+    }
+    let (sum_sq, secs) = samples.windows(2)
+        .filter(|w| w[0].price > 0.0 && w[1].price > 0.0 && w[1].publish_secs > w[0].publish_secs)
+        .fold((0.0, 0u64), |(sum, secs), w| {
+            let r = (w[1].price / w[0].price).ln();
+            (sum + r * r, secs + (w[1].publish_secs - w[0].publish_secs))
+        });
+    (secs > 0).then(|| (sum_sq / secs as f64).sqrt())
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26 erf, |error| < 1.5e-7)
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prob_up_brownian() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-3);
+        // At the open price it's a coin flip; with no time left it's decided
+        assert!((prob_up(100.0, 100.0, 60, 1e-4) - 0.5).abs() < 1e-7);
+        assert_eq!(prob_up(100.0, 100.01, 0, 1e-4), 1.0);
+        assert_eq!(prob_up(100.0, 100.0, 0, 1e-4), 1.0);
+        assert_eq!(prob_up(100.0, 99.99, 0, 1e-4), 0.0);
+        // 10bps above open, 7bps/min vol, 30s left: ~2σ ahead
+        let vol = 7.0 / 10_000.0 / 60f64.sqrt();
+        let p = prob_up(100.0, 100.1, 30, vol);
+        assert!(p > 0.97 && p < 0.99, "{}", p);
+        // More time, less certainty
+        assert!(prob_up(100.0, 100.1, 300, vol) < p);
+    }
+
+    #[test]
+    fn test_decisions() {
+        assert_eq!(decide(0.90, 0.70, 0.05), Decision::Hold);
+        assert_eq!(decide(0.20, 0.40, 0.05), Decision::Dump);
+        assert_eq!(decide(0.50, 0.52, 0.05), Decision::Defer);
+        // Nothing to sell into
+        assert_eq!(decide(0.10, 0.0, 0.05), Decision::Hold);
+    }
+
+    #[test]
+    fn test_predict_from_reference_history() {
+        let config = SettlePredictConfig { window_secs: 60, vol_mins: 15, default_vol_bps: 7.0, margin: 0.05 };
+        let prices = PythPrices::default();
+        // Alternating 1bp moves (~7.7bps/min), then a 50bp jump
+        for i in 0..=880u64 {
+            let wiggle = if i % 2 == 0 { 1.0 } else { 1.0001 };
+            prices.record("btc", PriceSample { publish_secs: 1_000 + i, price: 100.0 * wiggle, conf: 0.01 });
+        }
+        prices.record("btc", PriceSample { publish_secs: 1_881, price: 100.5, conf: 0.01 });
+        assert!(config.in_window(1_900, 1_881) && !config.in_window(1_900, 1_800) && !config.in_window(1_900, 1_900));
+
+        let p = config.predict(&prices, "btc", 1_000, 1_900, 1_881, 5).unwrap();
+        assert_eq!((p.open, p.spot, p.secs_left), (100.0, 100.5, 19));
+        // The jump itself counts: ≈15bps/min
+        assert!(p.vol_bps_per_min > 14.0 && p.vol_bps_per_min < 17.0, "{}", p.vol_bps_per_min);
+        assert!(p.p_win("yes") > 0.99 && p.p_win("no") < 0.01);
+        // No reference price: no prediction
+        assert!(config.predict(&prices, "eth", 1_000, 1_900, 1_881, 5).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+// src/settlement.rs
+// Position settlement - open positions in markets that have ended are looked up on their venue
+// and resolved through the position writer once the venue reports the result
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::metrics;
+use crate::position_store::Settlement;
+use crate::position_tracker::{ArbPosition, PositionChannel, SharedPositionTracker};
+use crate::runtimes;
+
+/// Settlement polling configuration from environment
+#[derive(Debug, Clone)]
+pub struct SettlementConfig {
+    /// Seconds between passes over the open positions (0 = positions are never resolved)
+    pub poll_secs: u64,
+}
+
+impl SettlementConfig {
+    pub fn from_env() -> Self {
+        Self {
+            poll_secs: std::env::var("SETTLE_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+        }
+    }
+}
+
+/// A Polymarket binary's result from its Gamma record: closed, with one outcome priced at 1
+/// (the first outcome is the YES token)
+pub fn polymarket_settlement(closed: Option<bool>, outcome_prices: &[String]) -> Option<Settlement> {
+    if closed != Some(true) || outcome_prices.len() != 2 {
+        return None;
+    }
+    let won = |p: &String| p.trim().parse::<f64>().is_ok_and(|v| v >= 0.999);
+    match (won(&outcome_prices[0]), won(&outcome_prices[1])) {
+        (true, false) => Some(Settlement::Binary { yes_won: true }),
+        (false, true) => Some(Settlement::Binary { yes_won: false }),
+        _ => None,
+    }
+}
+
+/// A Kalshi market's result ("yes" / "no"; empty until it settles)
+pub fn kalshi_settlement(result: Option<&str>) -> Option<Settlement> {
+    match result? {
+        "yes" => Some(Settlement::Binary { yes_won: true }),
+        "no" => Some(Settlement::Binary { yes_won: false }),
+        _ => None,
+    }
+}
+
+/// Open binary positions whose market has ended by `now` (those without a key can't be
+/// timed and are always due)
+pub fn due(positions: &[ArbPosition], now: i64) -> Vec<ArbPosition> {
+    positions.iter()
+        .filter(|p| p.status == "open" && p.outcomes.is_empty())
+        .filter(|p| p.key.as_ref().is_none_or(|k| k.end_ts <= now))
+        .cloned()
+        .collect()
+}
+
+/// Every SETTLE_POLL_SECS, ask `resolve` for the result of each due position and resolve it
+/// through `channel` (tracker and store), which fixes its P&L and releases its lots to the
+/// tax export. `resolve` returns Ok(None) while the venue hasn't settled the market.
+pub fn spawn<F, Fut>(config: SettlementConfig, tracker: SharedPositionTracker, channel: PositionChannel, resolve: F)
+where
+    F: Fn(ArbPosition) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Option<Settlement>>> + Send,
+{
+    if config.poll_secs == 0 {
+        return;
+    }
+    runtimes::spawn_bookkeeping(async move {
+        // Sent to the writer but maybe not applied yet
+        let mut settled: HashSet<String> = HashSet::new();
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.poll_secs));
+        loop {
+            ticker.tick().await;
+            let now = chrono::Utc::now().timestamp();
+            let positions: Vec<ArbPosition> = tracker.read().await.all_positions().into_iter().cloned().collect();
+            for position in due(&positions, now) {
+                if settled.contains(&position.market_id) {
+                    continue;
+                }
+                match resolve(position.clone()).await {
+                    Ok(Some(settlement)) => {
+                        info!("[SETTLE] {} resolved: {:?}", position.description, settlement);
+                        metrics::incr("settlement.resolved");
+                        channel.settle(&position.market_id, settlement);
+                        settled.insert(position.market_id);
+                    }
+                    Ok(None) => debug!("[SETTLE] {} not settled yet", position.description),
+                    Err(e) => {
+                        metrics::incr("settlement.errors");
+                        warn!("[SETTLE] Result of {} unavailable: {}", position.description, e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_key::MarketKey;
+
+    #[test]
+    fn test_venue_results() {
+        let prices = |a: &str, b: &str| vec![a.to_string(), b.to_string()];
+        assert_eq!(polymarket_settlement(Some(true), &prices("1", "0")), Some(Settlement::Binary { yes_won: true }));
+        assert_eq!(polymarket_settlement(Some(true), &prices("0", "1")), Some(Settlement::Binary { yes_won: false }));
+        // Closed but not resolved yet, or still trading
+        assert_eq!(polymarket_settlement(Some(true), &prices("0.5", "0.5")), None);
+        assert_eq!(polymarket_settlement(Some(false), &prices("1", "0")), None);
+
+        assert_eq!(kalshi_settlement(Some("no")), Some(Settlement::Binary { yes_won: false }));
+        assert_eq!(kalshi_settlement(Some("")), None);
+        assert_eq!(kalshi_settlement(None), None);
+    }
+
+    #[test]
+    fn test_due_positions() {
+        let mut ended = ArbPosition::new("a", "ended");
+        ended.key = Some(MarketKey::polymarket("btc-updown-15m-100", "1", "2", 100));
+        let mut running = ArbPosition::new("b", "running");
+        running.key = Some(MarketKey::polymarket("btc-updown-15m-200", "3", "4", 200));
+        let unkeyed = ArbPosition::new("c", "cross pair");
+        let mut resolved = ended.clone();
+        resolved.market_id = "d".into();
+        resolved.resolve(true);
+
+        let due: Vec<String> = due(&[ended, running, unkeyed, resolved], 150).into_iter().map(|p| p.market_id).collect();
+        assert_eq!(due, ["a", "c"]);
+    }
+}
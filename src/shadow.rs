@@ -0,0 +1,260 @@
+// src/shadow.rs
+// Shadow execution - simulated fills next to what live orders could actually have achieved
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::env_flag;
+use crate::exec_mode::ExecMode;
+use crate::storage;
+
+const HEADER: &str = "timestamp,market,strategy,legs,contracts,simulated_pnl,achievable_pnl,matched,unmatched";
+
+/// Shadow mode configuration from environment
+#[derive(Debug, Clone)]
+pub struct ShadowConfig {
+    /// Where shadow trades are appended (SHADOW_FILE, inside DATA_DIR)
+    pub path: PathBuf,
+
+    /// Order round trip assumed before live orders would have reached the book
+    pub latency_ms: u64,
+
+    /// Loss per unmatched contract when flattening the leftover leg (cents)
+    pub unwind_cents: f64,
+}
+
+impl ShadowConfig {
+    pub fn from_env() -> Self {
+        Self {
+            path: storage::data_path(std::env::var("SHADOW_FILE").unwrap_or_else(|_| "shadow_trades.csv".to_string())),
+
+            latency_ms: std::env::var("SHADOW_LATENCY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(250),
+
+            unwind_cents: std::env::var("SHADOW_UNWIND_CENTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10.0),
+        }
+    }
+}
+
+/// One order live execution would have sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowLeg {
+    pub venue: &'static str,
+    pub side: &'static str,
+    /// Polymarket token or Kalshi ticker
+    pub instrument: String,
+    /// Limit price (0-1)
+    pub price: f64,
+    pub contracts: f64,
+    /// Fee per contract (0-1)
+    pub fee: f64,
+    /// Contracts the book could fill once the order would have arrived
+    pub filled: f64,
+}
+
+impl ShadowLeg {
+    fn label(&self) -> String {
+        format!("{}:{}@{:.3}x{:.0}/{:.0}", self.venue, self.side, self.price, self.filled, self.contracts)
+    }
+}
+
+/// Contracts a marketable limit order fills against the ask it would meet:
+/// nothing if the ask moved above the limit (or vanished), else up to the ask size
+pub fn leg_fill(limit: f64, contracts: f64, ask: f64, ask_size: f64) -> f64 {
+    if ask <= 0.0 || ask > limit + 1e-9 {
+        0.0
+    } else {
+        contracts.min(ask_size.max(0.0))
+    }
+}
+
+/// A detected arb with simulated and achievable outcomes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowTrade {
+    pub timestamp: String,
+    pub market: String,
+    pub strategy: String,
+    pub legs: Vec<ShadowLeg>,
+    /// Full fill at detection prices (what dry run assumes)
+    pub simulated_pnl: f64,
+    /// Matched fills at the re-read book, minus unwinding any unmatched leg
+    pub achievable_pnl: f64,
+    pub matched: f64,
+    pub unmatched: f64,
+}
+
+impl ShadowTrade {
+    pub fn new(market: &str, strategy: &str, legs: Vec<ShadowLeg>, unwind_cents: f64) -> Self {
+        let edge = 1.0 - legs.iter().map(|l| l.price + l.fee).sum::<f64>();
+        let contracts = legs.iter().map(|l| l.contracts).fold(f64::INFINITY, f64::min);
+        let matched = legs.iter().map(|l| l.filled).fold(f64::INFINITY, f64::min);
+        let unmatched = legs.iter().map(|l| l.filled - matched).sum::<f64>();
+        let (contracts, matched) = if legs.is_empty() { (0.0, 0.0) } else { (contracts, matched) };
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            market: market.to_string(),
+            strategy: strategy.to_string(),
+            simulated_pnl: contracts * edge,
+            achievable_pnl: matched * edge - unmatched * unwind_cents / 100.0,
+            matched,
+            unmatched,
+            legs,
+        }
+    }
+
+    fn to_row(&self) -> String {
+        let legs: Vec<String> = self.legs.iter().map(ShadowLeg::label).collect();
+        let contracts = self.legs.iter().map(|l| l.contracts).fold(0.0, f64::max);
+        format!("{},{},{},{},{:.0},{:.4},{:.4},{:.0},{:.0}",
+                self.timestamp, self.market.replace(',', " "), self.strategy, legs.join(" "),
+                contracts, self.simulated_pnl, self.achievable_pnl, self.matched, self.unmatched)
+    }
+}
+
+/// Appends shadow trades to a CSV file
+pub struct ShadowRecorder {
+    config: ShadowConfig,
+}
+
+impl ShadowRecorder {
+    /// Recorder in shadow mode; warns and returns None when SHADOW_MODE is set while trading live
+    pub fn from_env(mode: ExecMode) -> Option<Self> {
+        if !mode.records_shadow() {
+            if mode.is_live() && env_flag("SHADOW_MODE", false) {
+                warn!("[SHADOW] SHADOW_MODE ignored - trading live (live orders are being sent)");
+            }
+            return None;
+        }
+        let config = ShadowConfig::from_env();
+        info!("[SHADOW] Recording simulated vs achievable fills to {} ({}ms order latency)",
+              config.path.display(), config.latency_ms);
+        Some(Self { config })
+    }
+
+    #[allow(dead_code)]
+    pub fn new(config: ShadowConfig) -> Self {
+        Self { config }
+    }
+
+    /// Delay before the book is re-read to judge what live orders would have filled
+    pub fn latency(&self) -> Duration {
+        Duration::from_millis(self.config.latency_ms)
+    }
+
+    pub fn unwind_cents(&self) -> f64 {
+        self.config.unwind_cents
+    }
+
+    pub fn record(&self, trade: &ShadowTrade) {
+        info!("[SHADOW] {} | simulated ${:.2} vs achievable ${:.2} ({:.0} matched, {:.0} unmatched)",
+              trade.market, trade.simulated_pnl, trade.achievable_pnl, trade.matched, trade.unmatched);
+        if let Err(e) = self.append(trade) {
+            warn!("[SHADOW] Failed to write {}: {}", self.config.path.display(), e);
+        }
+    }
+
+    fn append(&self, trade: &ShadowTrade) -> Result<()> {
+        let new_file = !self.config.path.exists();
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.config.path)?;
+        if new_file {
+            writeln!(file, "{}", HEADER)?;
+        }
+        writeln!(file, "{}", trade.to_row())?;
+        Ok(())
+    }
+}
+
+/// Per-day totals of a shadow file
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShadowDay {
+    pub date: String,
+    pub trades: usize,
+    pub simulated_pnl: f64,
+    pub achievable_pnl: f64,
+    /// Trades where every leg would have filled completely
+    pub full_fills: usize,
+    pub unmatched: f64,
+}
+
+/// Group a shadow file's rows by UTC date
+#[allow(dead_code)]
+pub fn daily_summary<P: AsRef<Path>>(path: P) -> Result<Vec<ShadowDay>> {
+    let contents = std::fs::read_to_string(path.as_ref())?;
+    let mut days: BTreeMap<String, ShadowDay> = BTreeMap::new();
+    for line in contents.lines().filter(|l| !l.is_empty() && *l != HEADER) {
+        let f: Vec<&str> = line.split(',').collect();
+        if f.len() != 9 {
+            return Err(anyhow!("expected 9 columns, got {}: {}", f.len(), line));
+        }
+        let num = |i: usize| f[i].parse::<f64>().map_err(|e| anyhow!("column {}: {}", i, e));
+        let (contracts, matched, unmatched) = (num(4)?, num(7)?, num(8)?);
+        let date = f[0].get(..10).unwrap_or(f[0]).to_string();
+        let day = days.entry(date.clone()).or_insert_with(|| ShadowDay { date, ..Default::default() });
+        day.trades += 1;
+        day.simulated_pnl += num(5)?;
+        day.achievable_pnl += num(6)?;
+        day.full_fills += (matched >= contracts && unmatched == 0.0) as usize;
+        day.unmatched += unmatched;
+    }
+    Ok(days.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(side: &'static str, price: f64, filled: f64) -> ShadowLeg {
+        ShadowLeg { venue: "polymarket", side, instrument: side.into(), price, contracts: 10.0, fee: 0.0, filled }
+    }
+
+    #[test]
+    fn test_leg_fill() {
+        assert_eq!(leg_fill(0.45, 10.0, 0.44, 25.0), 10.0);
+        assert_eq!(leg_fill(0.45, 10.0, 0.45, 4.0), 4.0);
+        // Ask moved through the limit, or the book emptied
+        assert_eq!(leg_fill(0.45, 10.0, 0.46, 25.0), 0.0);
+        assert_eq!(leg_fill(0.45, 10.0, 0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_simulated_vs_achievable() {
+        // 45 + 52 = 97¢: 3¢ × 10 simulated; only 6 NO filled → 6 matched, 4 YES unwound at 10¢
+        let trade = ShadowTrade::new("BTC", "PolyOnly", vec![leg("yes", 0.45, 10.0), leg("no", 0.52, 6.0)], 10.0);
+        assert!((trade.simulated_pnl - 0.30).abs() < 1e-9);
+        assert!((trade.achievable_pnl - (0.18 - 0.40)).abs() < 1e-9);
+        assert_eq!((trade.matched, trade.unmatched), (6.0, 4.0));
+    }
+
+    #[test]
+    fn test_daily_summary() {
+        let path = std::env::temp_dir().join(format!("shadow_test_{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let recorder = ShadowRecorder::new(ShadowConfig { path: path.clone(), latency_ms: 0, unwind_cents: 10.0 });
+        let mut full = ShadowTrade::new("BTC, Up", "PolyOnly", vec![leg("yes", 0.45, 10.0), leg("no", 0.52, 10.0)], 10.0);
+        full.timestamp = "2025-12-18T20:00:00+00:00".into();
+        let mut missed = ShadowTrade::new("ETH", "PolyOnly", vec![leg("yes", 0.45, 0.0), leg("no", 0.52, 0.0)], 10.0);
+        missed.timestamp = "2025-12-18T21:00:00+00:00".into();
+        let mut next_day = full.clone();
+        next_day.timestamp = "2025-12-19T01:00:00+00:00".into();
+        for t in [&full, &missed, &next_day] {
+            recorder.record(t);
+        }
+
+        let days = daily_summary(&path).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!((days[0].date.as_str(), days[0].trades, days[0].full_fills), ("2025-12-18", 2, 1));
+        assert!((days[0].simulated_pnl - 0.60).abs() < 1e-9);
+        assert!((days[0].achievable_pnl - 0.30).abs() < 1e-9);
+        let _ = std::fs::remove_file(&path);
+    }
+}
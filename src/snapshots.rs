@@ -0,0 +1,330 @@
+// src/snapshots.rs
+// Strategy state snapshots - cooldowns, halts and hold timers saved periodically and restored on restart
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::app::env_flag;
+use crate::runtimes;
+use crate::storage;
+
+/// Bumped when the file layout changes; other versions are discarded on load
+const VERSION: u32 = 1;
+
+/// Snapshot configuration from environment
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    /// Snapshot strategy state and restore it on start (STATE_SNAPSHOTS)
+    pub persist: bool,
+
+    /// Snapshot file (under DATA_DIR)
+    pub file: PathBuf,
+
+    /// Seconds between snapshots
+    pub interval_secs: u64,
+
+    /// Snapshots older than this are ignored on start (the state would be stale)
+    pub max_age_secs: i64,
+}
+
+impl SnapshotConfig {
+    pub fn from_env(default_file: &str) -> Self {
+        Self {
+            persist: env_flag("STATE_SNAPSHOTS", true),
+
+            file: storage::state_path(std::env::var("STATE_SNAPSHOT_FILE")
+                .unwrap_or_else(|_| default_file.to_string())),
+
+            interval_secs: std::env::var("STATE_SNAPSHOT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(15),
+
+            max_age_secs: std::env::var("STATE_SNAPSHOT_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.persist && self.interval_secs > 0
+    }
+}
+
+/// Strategy-local state that survives a restart
+pub trait StrategyState: Send + Sync {
+    /// Name in the snapshot file
+    fn key(&self) -> &'static str;
+
+    /// Bumped when the state's layout changes; a stored state of another version is dropped
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn snapshot(&self) -> serde_json::Value;
+
+    /// Merge a stored state in, returning how many entries were restored
+    fn restore(&self, state: serde_json::Value) -> Result<usize>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredState {
+    version: u32,
+    state: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    saved_at: i64,
+    states: HashMap<String, StoredState>,
+}
+
+fn parse(contents: &str, now: i64, max_age_secs: i64) -> Result<HashMap<String, StoredState>> {
+    let file: SnapshotFile = serde_json::from_str(contents)?;
+    if file.version != VERSION {
+        bail!("version {} (expected {})", file.version, VERSION);
+    }
+    if now - file.saved_at > max_age_secs {
+        bail!("saved {}s ago (STATE_SNAPSHOT_MAX_AGE_SECS={})", now - file.saved_at, max_age_secs);
+    }
+    Ok(file.states)
+}
+
+/// Registered strategy states, restored from the last snapshot as they register
+pub struct SnapshotStore {
+    config: SnapshotConfig,
+    loaded: Mutex<HashMap<String, StoredState>>,
+    parts: Mutex<Vec<Arc<dyn StrategyState>>>,
+}
+
+impl SnapshotStore {
+    /// Read the snapshot file; a missing, stale or other-version file restores nothing
+    pub fn load(config: &SnapshotConfig, now: i64) -> Self {
+        let loaded = if !config.enabled() {
+            HashMap::new()
+        } else {
+            match std::fs::read_to_string(&config.file) {
+                Ok(contents) => parse(&contents, now, config.max_age_secs).unwrap_or_else(|e| {
+                    warn!("[SNAPSHOT] Ignoring {}: {}", config.file.display(), e);
+                    HashMap::new()
+                }),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+                Err(e) => {
+                    warn!("[SNAPSHOT] Can't read {}: {}", config.file.display(), e);
+                    HashMap::new()
+                }
+            }
+        };
+        Self { config: config.clone(), loaded: Mutex::new(loaded), parts: Mutex::new(Vec::new()) }
+    }
+
+    /// Restore `part` from the loaded snapshot and include it in the next ones
+    pub fn register(&self, part: Arc<dyn StrategyState>) {
+        if !self.config.enabled() {
+            return;
+        }
+        match self.loaded.lock().unwrap().remove(part.key()) {
+            Some(stored) if stored.version == part.version() => match part.restore(stored.state) {
+                Ok(n) => info!("[SNAPSHOT] Restored {} {} entries", n, part.key()),
+                Err(e) => warn!("[SNAPSHOT] Can't restore {}: {}", part.key(), e),
+            },
+            Some(stored) => warn!("[SNAPSHOT] Dropping {} state: version {} (expected {})",
+                                  part.key(), stored.version, part.version()),
+            None => {}
+        }
+        self.parts.lock().unwrap().push(part);
+    }
+
+    pub fn save(&self, now: i64) -> Result<()> {
+        let states = self.parts.lock().unwrap().iter()
+            .map(|p| (p.key().to_string(), StoredState { version: p.version(), state: p.snapshot() }))
+            .collect();
+        let contents = serde_json::to_vec(&SnapshotFile { version: VERSION, saved_at: now, states })?;
+        storage::write_atomic(&self.config.file, &contents).with_context(|| format!("writing {}", self.config.file.display()))
+    }
+
+    /// Snapshot every STATE_SNAPSHOT_SECS, and once more on Ctrl-C / SIGTERM before exiting,
+    /// so a restart resumes from the state at shutdown rather than the last interval
+    pub fn spawn(self: Arc<Self>) {
+        if !self.config.enabled() {
+            return;
+        }
+        info!("[SNAPSHOT] Saving strategy state to {} every {}s", self.config.file.display(), self.config.interval_secs);
+        runtimes::spawn_bookkeeping(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(self.config.interval_secs));
+            ticker.tick().await;
+            let shutdown = shutdown_signal();
+            tokio::pin!(shutdown);
+            loop {
+                let stopping = tokio::select! {
+                    _ = ticker.tick() => false,
+                    _ = &mut shutdown => true,
+                };
+                // A standby's state isn't the one to resume from
+                if crate::leader::is_leader() {
+                    match self.save(chrono::Utc::now().timestamp()) {
+                        Ok(()) if stopping => info!("[SNAPSHOT] Saved strategy state on shutdown"),
+                        Ok(()) => {}
+                        Err(e) => warn!("[SNAPSHOT] {:#}", e),
+                    }
+                }
+                if stopping {
+                    std::process::exit(0);
+                }
+            }
+        });
+    }
+}
+
+/// Ctrl-C, or SIGTERM where there is one
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let Ok(mut term) = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) else {
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Timestamps (unix seconds) by name - halts, hold timers and similar small state
+pub struct Marks {
+    key: &'static str,
+    entries: Mutex<HashMap<String, i64>>,
+}
+
+impl Marks {
+    pub fn new(key: &'static str) -> Self {
+        Self { key, entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.entries.lock().unwrap().get(name).copied()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.lock().unwrap().contains_key(name)
+    }
+
+    pub fn insert(&self, name: &str, at: i64) {
+        self.entries.lock().unwrap().insert(name.to_string(), at);
+    }
+
+    /// The mark for `name`, set to `at` if there was none
+    pub fn get_or_insert(&self, name: &str, at: i64) -> i64 {
+        *self.entries.lock().unwrap().entry(name.to_string()).or_insert(at)
+    }
+
+    pub fn retain(&self, keep: impl Fn(&str, i64) -> bool) {
+        self.entries.lock().unwrap().retain(|name, at| keep(name, *at));
+    }
+}
+
+impl StrategyState for Marks {
+    fn key(&self) -> &'static str {
+        self.key
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::json!(*self.entries.lock().unwrap())
+    }
+
+    fn restore(&self, state: serde_json::Value) -> Result<usize> {
+        let stored: HashMap<String, i64> = serde_json::from_value(state)?;
+        let n = stored.len();
+        self.entries.lock().unwrap().extend(stored);
+        Ok(n)
+    }
+}
+
+/// `t` as wall-clock milliseconds, given the same moment on both clocks
+pub fn to_unix_ms(t: Instant, now: Instant, now_ms: i64) -> i64 {
+    if t >= now {
+        now_ms + (t - now).as_millis() as i64
+    } else {
+        now_ms - (now - t).as_millis() as i64
+    }
+}
+
+/// Wall-clock milliseconds back to an `Instant` (None if it predates this clock)
+pub fn from_unix_ms(ms: i64, now: Instant, now_ms: i64) -> Option<Instant> {
+    if ms >= now_ms {
+        Some(now + Duration::from_millis((ms - now_ms) as u64))
+    } else {
+        now.checked_sub(Duration::from_millis((now_ms - ms) as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &std::path::Path) -> SnapshotConfig {
+        SnapshotConfig { persist: true, file: dir.join("state.json"), interval_secs: 15, max_age_secs: 600 }
+    }
+
+    #[test]
+    fn test_round_trip_restores_on_register() {
+        let dir = std::env::temp_dir().join(format!("snapshots_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = SnapshotStore::load(&config(&dir), 1_000);
+        let halts = Arc::new(Marks::new("halts"));
+        store.register(halts.clone());
+        halts.insert("btc-updown-15m-1900", 1_900);
+        store.save(1_000).unwrap();
+
+        let restored = Arc::new(Marks::new("halts"));
+        SnapshotStore::load(&config(&dir), 1_100).register(restored.clone());
+        assert_eq!(restored.get("btc-updown-15m-1900"), Some(1_900));
+        // Too old: nothing comes back
+        let stale = Arc::new(Marks::new("halts"));
+        SnapshotStore::load(&config(&dir), 2_000).register(stale.clone());
+        assert!(!stale.contains("btc-updown-15m-1900"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_versions_checked() {
+        let state = |v: u32| serde_json::json!({ "version": v, "saved_at": 0, "states": {} }).to_string();
+        assert!(parse(&state(VERSION), 10, 600).is_ok());
+        assert!(parse(&state(VERSION + 1), 10, 600).is_err());
+        assert!(parse("{}", 10, 600).is_err());
+
+        // A part whose layout changed starts empty instead of misreading the old one
+        struct V2(Marks);
+        impl StrategyState for V2 {
+            fn key(&self) -> &'static str { "marks" }
+            fn version(&self) -> u32 { 2 }
+            fn snapshot(&self) -> serde_json::Value { self.0.snapshot() }
+            fn restore(&self, state: serde_json::Value) -> Result<usize> { self.0.restore(state) }
+        }
+        let store = SnapshotStore::load(&config(&std::env::temp_dir().join("snapshots_missing")), 0);
+        store.loaded.lock().unwrap().insert("marks".into(), StoredState { version: 1, state: serde_json::json!({ "a": 1 }) });
+        let part = Arc::new(V2(Marks::new("marks")));
+        store.register(part.clone());
+        assert!(!part.0.contains("a"));
+    }
+
+    #[test]
+    fn test_instant_conversions() {
+        let now = Instant::now() + Duration::from_secs(10);
+        let later = now + Duration::from_millis(1_500);
+        assert_eq!(to_unix_ms(later, now, 10_000), 11_500);
+        assert_eq!(from_unix_ms(11_500, now, 10_000), Some(later));
+        assert_eq!(to_unix_ms(now - Duration::from_millis(250), now, 10_000), 9_750);
+        assert_eq!(from_unix_ms(9_750, now, 10_000), Some(now - Duration::from_millis(250)));
+    }
+}
@@ -0,0 +1,256 @@
+// src/sql.rs
+// SQL connections - SQLite (rusqlite) and Postgres (postgres) behind one handle; values are
+// always bound as parameters ($1, $2, ... in both dialects)
+
+use anyhow::{anyhow, Context, Result};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::position_store::StoreBackend;
+
+/// How long a SQLite writer waits on another process's lock before failing
+const SQLITE_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Postgres connect timeout
+const PG_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A bound parameter or a column value
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Int(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl SqlValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SqlValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            SqlValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(s: &str) -> Self {
+        SqlValue::Text(s.to_string())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(s: String) -> Self {
+        SqlValue::Text(s)
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(i: i64) -> Self {
+        SqlValue::Int(i)
+    }
+}
+
+impl From<f64> for SqlValue {
+    fn from(f: f64) -> Self {
+        SqlValue::Real(f)
+    }
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(SqlValue::Null)
+    }
+}
+
+impl rusqlite::ToSql for SqlValue {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        use rusqlite::types::{ToSqlOutput, ValueRef};
+        Ok(ToSqlOutput::Borrowed(match self {
+            SqlValue::Null => ValueRef::Null,
+            SqlValue::Int(i) => ValueRef::Integer(*i),
+            SqlValue::Real(f) => ValueRef::Real(*f),
+            SqlValue::Text(s) => ValueRef::Text(s.as_bytes()),
+        }))
+    }
+}
+
+impl postgres::types::ToSql for SqlValue {
+    fn to_sql(
+        &self,
+        ty: &postgres::types::Type,
+        out: &mut postgres::types::private::BytesMut,
+    ) -> std::result::Result<postgres::types::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        match self {
+            SqlValue::Null => Ok(postgres::types::IsNull::Yes),
+            SqlValue::Int(i) => i.to_sql(ty, out),
+            SqlValue::Real(f) => f.to_sql(ty, out),
+            SqlValue::Text(s) => s.to_sql(ty, out),
+        }
+    }
+
+    fn accepts(_ty: &postgres::types::Type) -> bool {
+        true
+    }
+
+    postgres::types::to_sql_checked!();
+}
+
+enum Conn {
+    Sqlite(rusqlite::Connection),
+    Postgres(postgres::Client),
+}
+
+/// One database connection, shared behind a lock
+pub struct SqlConn {
+    backend: StoreBackend,
+    conn: Mutex<Conn>,
+}
+
+/// Run a blocking Postgres call from anywhere: the sync client drives its own runtime,
+/// which can't be started from inside a tokio worker
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match tokio::runtime::Handle::try_current() {
+        Ok(h) if h.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => tokio::task::block_in_place(f),
+        _ => f(),
+    }
+}
+
+impl SqlConn {
+    /// Open a SQLite file or connect to a Postgres URL (TLS as the URL's sslmode asks)
+    pub fn open(backend: StoreBackend, target: &str) -> Result<Self> {
+        let conn = match backend {
+            StoreBackend::Json => return Err(anyhow!("the JSON file is not a SQL database")),
+            StoreBackend::Sqlite => {
+                let conn = rusqlite::Connection::open(target).with_context(|| format!("opening SQLite {}", target))?;
+                conn.busy_timeout(SQLITE_BUSY_TIMEOUT)?;
+                // Several bots and the aggregator share the file
+                conn.pragma_update(None, "journal_mode", "WAL")?;
+                Conn::Sqlite(conn)
+            }
+            StoreBackend::Postgres => {
+                let mut config: postgres::Config = target.parse().context("parsing the Postgres URL")?;
+                config.connect_timeout(PG_CONNECT_TIMEOUT);
+                let tls = postgres_native_tls::MakeTlsConnector::new(native_tls::TlsConnector::new()?);
+                // The error never includes the URL, so the password stays out of logs
+                Conn::Postgres(blocking(|| config.connect(tls)).context("connecting to Postgres")?)
+            }
+        };
+        Ok(Self { backend, conn: Mutex::new(conn) })
+    }
+
+    pub fn backend(&self) -> StoreBackend {
+        self.backend
+    }
+
+    /// Run statements without parameters (schema setup)
+    pub fn batch(&self, sql: &str) -> Result<()> {
+        match &mut *self.conn.lock().unwrap() {
+            Conn::Sqlite(c) => c.execute_batch(sql)?,
+            Conn::Postgres(c) => blocking(|| c.batch_execute(sql))?,
+        }
+        Ok(())
+    }
+
+    /// Run one statement, returning the rows it changed
+    pub fn execute(&self, sql: &str, params: &[SqlValue]) -> Result<u64> {
+        Ok(match &mut *self.conn.lock().unwrap() {
+            Conn::Sqlite(c) => c.execute(sql, rusqlite::params_from_iter(params))? as u64,
+            Conn::Postgres(c) => {
+                let params: Vec<&(dyn postgres::types::ToSql + Sync)> = params.iter().map(|p| p as _).collect();
+                blocking(|| c.execute(sql, &params))?
+            }
+        })
+    }
+
+    /// Rows of a query, each as its column values
+    pub fn query(&self, sql: &str, params: &[SqlValue]) -> Result<Vec<Vec<SqlValue>>> {
+        match &mut *self.conn.lock().unwrap() {
+            Conn::Sqlite(c) => {
+                let mut stmt = c.prepare(sql)?;
+                let width = stmt.column_count();
+                let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                    (0..width).map(|i| {
+                        use rusqlite::types::ValueRef;
+                        Ok(match row.get_ref(i)? {
+                            ValueRef::Null => SqlValue::Null,
+                            ValueRef::Integer(i) => SqlValue::Int(i),
+                            ValueRef::Real(f) => SqlValue::Real(f),
+                            ValueRef::Text(t) | ValueRef::Blob(t) => SqlValue::Text(String::from_utf8_lossy(t).into_owned()),
+                        })
+                    }).collect()
+                })?;
+                Ok(rows.collect::<rusqlite::Result<_>>()?)
+            }
+            Conn::Postgres(c) => {
+                let params: Vec<&(dyn postgres::types::ToSql + Sync)> = params.iter().map(|p| p as _).collect();
+                let rows = blocking(|| c.query(sql, &params))?;
+                rows.iter().map(pg_row).collect()
+            }
+        }
+    }
+
+    /// Run `f` in a transaction, committed if it returns Ok
+    pub fn transaction<T>(&self, f: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+        // SQLite takes the write lock up front, so a read-then-write never hits a busy upgrade
+        self.batch(if self.backend == StoreBackend::Sqlite { "BEGIN IMMEDIATE" } else { "BEGIN" })?;
+        match f(self) {
+            Ok(v) => {
+                self.batch("COMMIT")?;
+                Ok(v)
+            }
+            Err(e) => {
+                let _ = self.batch("ROLLBACK");
+                Err(e)
+            }
+        }
+    }
+}
+
+fn pg_row(row: &postgres::Row) -> Result<Vec<SqlValue>> {
+    use postgres::types::Type;
+    row.columns().iter().enumerate().map(|(i, col)| {
+        Ok(match *col.type_() {
+            Type::INT8 => row.try_get::<_, Option<i64>>(i)?.into(),
+            Type::INT4 => row.try_get::<_, Option<i32>>(i)?.map(i64::from).into(),
+            Type::FLOAT8 => row.try_get::<_, Option<f64>>(i)?.into(),
+            _ => row.try_get::<_, Option<String>>(i)?.into(),
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_binds_parameters() {
+        let path = std::env::temp_dir().join(format!("sql_test_{}.db", std::process::id()));
+        let db = SqlConn::open(StoreBackend::Sqlite, &path.to_string_lossy()).unwrap();
+        db.batch("CREATE TABLE t (name TEXT NOT NULL, n BIGINT, x DOUBLE PRECISION);").unwrap();
+        // Quotes and semicolons reach the table as data
+        let name = "it's'; DROP TABLE t; --";
+        assert_eq!(db.execute("INSERT INTO t (name, n, x) VALUES ($1, $2, $3)",
+                              &[name.into(), 7i64.into(), SqlValue::Null]).unwrap(), 1);
+        let rows = db.query("SELECT name, n, x FROM t WHERE name = $1", &[name.into()]).unwrap();
+        assert_eq!(rows, vec![vec![SqlValue::Text(name.into()), SqlValue::Int(7), SqlValue::Null]]);
+
+        // A failed transaction leaves nothing behind
+        let failed: Result<()> = db.transaction(|db| {
+            db.execute("INSERT INTO t (name) VALUES ($1)", &["b".into()])?;
+            Err(anyhow!("abort"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(db.query("SELECT count(*) FROM t", &[]).unwrap()[0][0], SqlValue::Int(1));
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
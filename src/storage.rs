@@ -0,0 +1,247 @@
+// src/storage.rs
+// Data directory resolution, atomic writes and cross-platform advisory file locks
+
+use anyhow::{anyhow, Context, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use crate::exec_mode::{self, ExecMode};
+
+/// Directory name under the platform data dir (DATA_DIR=auto)
+const APP_DIR: &str = "poly-kalshi-arb";
+
+/// Platform data directory: %APPDATA% on Windows, ~/Library/Application Support on
+/// macOS, $XDG_DATA_HOME or ~/.local/share elsewhere
+fn platform_data_dir(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let non_empty = |k: &str| env(k).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        non_empty("APPDATA")?
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME")?.join("Library").join("Application Support")
+    } else {
+        non_empty("XDG_DATA_HOME").or_else(|| non_empty("HOME").map(|h| h.join(".local").join("share")))?
+    };
+    Some(base.join(APP_DIR))
+}
+
+/// DATA_DIR value → directory. Unset keeps the working directory (where the files
+/// have always lived); "auto" picks the platform data directory.
+fn resolve_data_dir(setting: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Result<PathBuf> {
+    match setting.map(str::trim).filter(|s| !s.is_empty()) {
+        None => Ok(PathBuf::from(".")),
+        Some("auto") => platform_data_dir(env).ok_or_else(|| anyhow!("DATA_DIR=auto but no home / APPDATA directory is set")),
+        Some(dir) => Ok(PathBuf::from(dir)),
+    }
+}
+
+/// Directory for positions, intents and series files (DATA_DIR), created on first use
+pub fn data_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let setting = std::env::var("DATA_DIR").ok();
+        let dir = resolve_data_dir(setting.as_deref(), |k| std::env::var(k).ok()).unwrap_or_else(|e| {
+            tracing::warn!("[STORAGE] {} - using working directory", e);
+            PathBuf::from(".")
+        });
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("[STORAGE] Cannot create data directory {}: {}", dir.display(), e);
+        }
+        dir
+    })
+}
+
+/// Resolve a state file name inside the data directory (absolute paths are kept)
+pub fn data_path<P: AsRef<Path>>(file: P) -> PathBuf {
+    let file = file.as_ref();
+    if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        data_dir().join(file)
+    }
+}
+
+/// Subdirectory simulated (paper) runs keep their trading state in, so paper positions,
+/// journals, risk state and leases never mix with a live bot's
+const SIMULATED_DIR: &str = "simulated";
+
+fn state_dir_for(data_dir: &Path, mode: ExecMode) -> PathBuf {
+    match mode {
+        ExecMode::Simulated => data_dir.join(SIMULATED_DIR),
+        _ => data_dir.to_path_buf(),
+    }
+}
+
+/// Directory for trading state (positions, intents, snapshots, risk state, leases): the data
+/// directory, or its `simulated` subdirectory when EXEC_MODE=simulated
+pub fn state_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let mode = exec_mode::from_env().unwrap_or(ExecMode::DryRun);
+        let dir = state_dir_for(data_dir(), mode);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("[STORAGE] Cannot create state directory {}: {}", dir.display(), e);
+        }
+        dir
+    })
+}
+
+/// Resolve a trading state file name inside the state directory (absolute paths are kept)
+pub fn state_path<P: AsRef<Path>>(file: P) -> PathBuf {
+    let file = file.as_ref();
+    if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        state_dir().join(file)
+    }
+}
+
+/// Write via a temp file + rename, so readers never see a torn file (replaces the
+/// destination on Windows too). Each write has its own temp file (pid + counter), so
+/// concurrent writers never truncate or rename each other's.
+pub fn write_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> Result<()> {
+    static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+    let path = path.as_ref();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.{}.tmp", std::process::id(), NEXT_TMP.fetch_add(1, Ordering::Relaxed)));
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, contents).with_context(|| format!("writing {}", tmp.display()))?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("replacing {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Exclusive advisory lock on `<file>.lock`, held until dropped. Uses flock on Unix and
+/// LockFileEx on Windows, so a second bot pointed at the same file fails fast instead
+/// of interleaving writes.
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+    #[allow(dead_code)]
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn open(target: &Path) -> Result<(File, PathBuf)> {
+        let mut path = target.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("opening lock file {}", path.display()))?;
+        Ok((file, path))
+    }
+
+    pub fn acquire<P: AsRef<Path>>(target: P) -> Result<Self> {
+        let (file, path) = Self::open(target.as_ref())?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self { _file: file, path }),
+            Err(TryLockError::WouldBlock) => Err(anyhow!(
+                "{} is locked by another process - is a second bot running on the same data?",
+                target.as_ref().display()
+            )),
+            Err(TryLockError::Error(e)) => Err(anyhow!("locking {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Like `acquire`, but waits for another process to let go instead of failing. For
+    /// short read-modify-write cycles on a file several processes share.
+    pub fn wait<P: AsRef<Path>>(target: P) -> Result<Self> {
+        let (file, path) = Self::open(target.as_ref())?;
+        file.lock().with_context(|| format!("locking {}", path.display()))?;
+        Ok(Self { _file: file, path })
+    }
+
+    #[allow(dead_code)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_dir_resolution() {
+        let env = |k: &str| match k {
+            "HOME" => Some("/home/bot".to_string()),
+            "APPDATA" => Some("C:\\Users\\bot\\AppData\\Roaming".to_string()),
+            _ => None,
+        };
+        assert_eq!(resolve_data_dir(None, env).unwrap(), PathBuf::from("."));
+        assert_eq!(resolve_data_dir(Some("/srv/arb"), env).unwrap(), PathBuf::from("/srv/arb"));
+
+        let auto = resolve_data_dir(Some("auto"), env).unwrap();
+        assert!(auto.ends_with(APP_DIR));
+        if cfg!(all(unix, not(target_os = "macos"))) {
+            assert_eq!(auto, PathBuf::from("/home/bot/.local/share/poly-kalshi-arb"));
+            let xdg = |k: &str| (k == "XDG_DATA_HOME").then(|| "/data".to_string());
+            assert_eq!(resolve_data_dir(Some("auto"), xdg).unwrap(), PathBuf::from("/data/poly-kalshi-arb"));
+        }
+        assert!(resolve_data_dir(Some("auto"), |_| None).is_err());
+    }
+
+    #[test]
+    fn test_simulated_state_kept_apart() {
+        let data = Path::new("/srv/arb");
+        for mode in [ExecMode::Live, ExecMode::DryRun, ExecMode::Shadow] {
+            assert_eq!(state_dir_for(data, mode), data);
+        }
+        assert_eq!(state_dir_for(data, ExecMode::Simulated), PathBuf::from("/srv/arb/simulated"));
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_and_released_on_drop() {
+        let target = std::env::temp_dir().join(format!("storage_lock_test_{}.json", std::process::id()));
+        let lock = FileLock::acquire(&target).unwrap();
+        assert!(FileLock::acquire(&target).is_err());
+        drop(lock);
+        let again = FileLock::acquire(&target).unwrap();
+        let _ = std::fs::remove_file(again.path());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces() {
+        let path = std::env::temp_dir().join(format!("storage_atomic_test_{}.json", std::process::id()));
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_concurrent_write_atomic() {
+        let path = std::env::temp_dir().join(format!("storage_concurrent_test_{}.json", std::process::id()));
+        let writers: Vec<_> = (0..8).map(|i| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for _ in 0..20 {
+                    write_atomic(&path, format!("writer {}", i).as_bytes()).unwrap();
+                }
+            })
+        }).collect();
+        for w in writers {
+            w.join().unwrap();
+        }
+        // Always one writer's whole contents, and no temp files left behind
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with("writer "));
+        let prefix = path.file_name().unwrap().to_string_lossy().into_owned();
+        let leftovers = std::fs::read_dir(path.parent().unwrap()).unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.starts_with(&prefix) && name.ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        let _ = std::fs::remove_file(&path);
+    }
+}
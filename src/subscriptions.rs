@@ -0,0 +1,166 @@
+// src/subscriptions.rs
+// WebSocket subscription manager - canonical token set that survives reconnects
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::polymarket_ws_types::MarketOperation;
+
+/// Subscription change to send on the current connection
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubscriptionDiff {
+    /// Tokens to (re)subscribe - the server answers each with a fresh book snapshot
+    pub subscribe: Vec<String>,
+    /// Tokens no longer wanted
+    pub unsubscribe: Vec<String>,
+}
+
+impl SubscriptionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.subscribe.is_empty() && self.unsubscribe.is_empty()
+    }
+}
+
+/// Owns the set of tokens the feed should be subscribed to and tracks, per connection,
+/// which of them the server has acknowledged with a book snapshot.
+#[derive(Debug)]
+pub struct SubscriptionManager {
+    /// Canonical token set (kept across reconnects)
+    desired: BTreeSet<String>,
+    /// Tokens requested on the current connection, with the time of the last request
+    requested: HashMap<String, Instant>,
+    /// Tokens whose snapshot arrived on the current connection
+    acknowledged: BTreeSet<String>,
+    /// Re-request a snapshot if none arrived within this long
+    ack_timeout: Duration,
+}
+
+impl SubscriptionManager {
+    pub fn new(ack_timeout: Duration) -> Self {
+        Self {
+            desired: BTreeSet::new(),
+            requested: HashMap::new(),
+            acknowledged: BTreeSet::new(),
+            ack_timeout,
+        }
+    }
+
+    /// Replace the canonical token set (e.g. from the market map after a scan)
+    pub fn set_desired<I: IntoIterator<Item = String>>(&mut self, tokens: I) {
+        self.desired = tokens.into_iter().collect();
+    }
+
+    pub fn desired(&self) -> &BTreeSet<String> {
+        &self.desired
+    }
+
+    /// New connection: nothing is subscribed and every cached book is invalid.
+    /// Returns the full token set for the initial subscribe message.
+    pub fn on_connect(&mut self, now: Instant) -> Vec<String> {
+        self.requested.clear();
+        self.acknowledged.clear();
+        let tokens: Vec<String> = self.desired.iter().cloned().collect();
+        for token in &tokens {
+            self.requested.insert(token.clone(), now);
+        }
+        tokens
+    }
+
+    /// Server sent a book snapshot for a token
+    pub fn acknowledge(&mut self, token: &str) {
+        if self.requested.contains_key(token) {
+            self.acknowledged.insert(token.to_string());
+        }
+    }
+
+    pub fn is_acknowledged(&self, token: &str) -> bool {
+        self.acknowledged.contains(token)
+    }
+
+    /// Diff the canonical set against the connection: subscribe new tokens, re-request
+    /// snapshots that never arrived, and drop tokens that are no longer wanted.
+    pub fn diff(&mut self, now: Instant) -> SubscriptionDiff {
+        let mut diff = SubscriptionDiff::default();
+
+        for token in &self.desired {
+            let due = match self.requested.get(token) {
+                None => true,
+                Some(at) => !self.acknowledged.contains(token) && now.duration_since(*at) >= self.ack_timeout,
+            };
+            if due {
+                diff.subscribe.push(token.clone());
+            }
+        }
+        for token in &diff.subscribe {
+            self.requested.insert(token.clone(), now);
+        }
+
+        let stale: Vec<String> = self.requested.keys()
+            .filter(|t| !self.desired.contains(*t))
+            .cloned()
+            .collect();
+        for token in &stale {
+            self.requested.remove(token);
+            self.acknowledged.remove(token);
+        }
+        diff.unsubscribe = stale;
+        diff.unsubscribe.sort();
+
+        diff
+    }
+}
+
+/// Dynamic (un)subscribe message for the Polymarket market channel
+pub fn operation_message(tokens: &[String], operation: &str) -> MarketOperation {
+    MarketOperation {
+        assets_ids: tokens.to_vec(),
+        operation: operation.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_adds_and_removes_tokens() {
+        let now = Instant::now();
+        let mut subs = SubscriptionManager::new(Duration::from_secs(5));
+        subs.set_desired(tokens(&["a", "b"]));
+        assert_eq!(subs.on_connect(now), tokens(&["a", "b"]));
+        subs.acknowledge("a");
+        subs.acknowledge("b");
+
+        // Token added mid-scan, another expired
+        subs.set_desired(tokens(&["b", "c"]));
+        let diff = subs.diff(now);
+        assert_eq!(diff.subscribe, tokens(&["c"]));
+        assert_eq!(diff.unsubscribe, tokens(&["a"]));
+        assert!(subs.diff(now).is_empty());
+    }
+
+    #[test]
+    fn test_reconnect_invalidates_and_rerequests_missing_snapshots() {
+        let now = Instant::now();
+        let mut subs = SubscriptionManager::new(Duration::from_secs(5));
+        subs.set_desired(tokens(&["a", "b"]));
+        subs.on_connect(now);
+        subs.acknowledge("a");
+        subs.acknowledge("b");
+
+        // Reconnect: acknowledgements from the old connection don't count
+        subs.on_connect(now);
+        assert!(!subs.is_acknowledged("a"));
+        subs.acknowledge("a");
+
+        // "b" never got a snapshot - re-request after the timeout only
+        assert!(subs.diff(now + Duration::from_secs(1)).is_empty());
+        let diff = subs.diff(now + Duration::from_secs(6));
+        assert_eq!(diff.subscribe, tokens(&["b"]));
+    }
+}
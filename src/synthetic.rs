@@ -0,0 +1,466 @@
+// src/synthetic.rs
+// Synthetic binaries - Kalshi range brackets composed into the payoff of a Polymarket above/below market
+
+use anyhow::{anyhow, bail, Result};
+use tracing::warn;
+
+use crate::basket::{Basket, BasketLeg};
+use crate::types::{cents_to_price, kalshi_fee_cents, poly_fee_cents, KalshiMarket, PriceCents};
+use crate::venue_health::Venue;
+#[cfg(feature = "kalshi")]
+use {
+    std::sync::Arc,
+    std::time::Duration,
+    tracing::info,
+    crate::discovery::DiscoveryClient,
+    crate::metrics,
+    crate::opportunities::{self, OpportunityRecord},
+    crate::polymarket_clob::{self, SharedAsyncClient},
+    crate::polymarket_ws_types::BookSnapshot,
+    crate::types::parse_price,
+};
+
+/// Kalshi ranges end a cent below the next one's floor ("$99,750 to $99,999.99"), so
+/// bounds this close are treated as touching
+const STRIKE_GAP_TOLERANCE: f64 = 0.01 + 1e-6;
+
+/// Limit on one batch book request
+#[cfg(feature = "kalshi")]
+const BOOKS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Which side of the strike the synthetic pays on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payoff {
+    Above,
+    Below,
+}
+
+impl Payoff {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Payoff::Above => "above",
+            Payoff::Below => "below",
+        }
+    }
+}
+
+/// One Kalshi market paying YES while the underlying settles in [floor, cap]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bracket {
+    pub ticker: String,
+    /// -inf for "less" markets
+    pub floor: f64,
+    /// +inf for "greater" markets
+    pub cap: f64,
+    pub yes_ask: Option<PriceCents>,
+}
+
+impl Bracket {
+    /// The bracket a Kalshi market covers (None for strike types that aren't a range)
+    pub fn from_market(m: &KalshiMarket) -> Option<Self> {
+        let (floor, cap) = match (m.strike_type.as_deref()?, m.floor_strike, m.cap_strike) {
+            ("between", Some(floor), Some(cap)) if floor < cap => (floor, cap),
+            ("greater" | "greater_or_equal", Some(floor), _) => (floor, f64::INFINITY),
+            ("less" | "less_or_equal", _, Some(cap)) => (f64::NEG_INFINITY, cap),
+            _ => return None,
+        };
+        Some(Self {
+            ticker: m.ticker.clone(),
+            floor,
+            cap,
+            yes_ask: m.yes_ask.filter(|a| (1..100).contains(a)).map(|a| a as PriceCents),
+        })
+    }
+}
+
+fn touches(a: f64, b: f64) -> bool {
+    (a - b).abs() <= STRIKE_GAP_TOLERANCE
+}
+
+/// Kalshi YES legs that together pay $1 exactly when the underlying settles on one side of `strike`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticBinary {
+    pub strike: f64,
+    pub payoff: Payoff,
+    /// Contiguous, lowest floor first
+    pub legs: Vec<Bracket>,
+}
+
+impl SyntheticBinary {
+    /// Compose the brackets of one Kalshi event into the binary paying on `payoff` of
+    /// `strike`. Refused if the brackets on that side leave a gap, overlap, stop short of
+    /// the open end, or one of them straddles the strike; every leg needs a YES ask.
+    pub fn compose(markets: &[KalshiMarket], strike: f64, payoff: Payoff) -> Result<Self> {
+        let mut brackets: Vec<Bracket> = markets.iter()
+            .filter_map(Bracket::from_market)
+            .filter(|b| match payoff {
+                Payoff::Above => b.floor >= strike - STRIKE_GAP_TOLERANCE,
+                Payoff::Below => b.cap <= strike + STRIKE_GAP_TOLERANCE,
+            })
+            .collect();
+        brackets.sort_by(|a, b| a.floor.total_cmp(&b.floor));
+
+        let (Some(first), Some(last)) = (brackets.first(), brackets.last()) else {
+            bail!("no brackets {} {}", payoff.name(), strike);
+        };
+        match payoff {
+            Payoff::Above if !touches(first.floor, strike) => {
+                bail!("lowest bracket {} starts at {}, not the strike {}", first.ticker, first.floor, strike)
+            }
+            Payoff::Above if last.cap.is_finite() => bail!("nothing above {} ({})", last.cap, last.ticker),
+            Payoff::Below if !touches(last.cap, strike) => {
+                bail!("highest bracket {} ends at {}, not the strike {}", last.ticker, last.cap, strike)
+            }
+            Payoff::Below if first.floor.is_finite() => bail!("nothing below {} ({})", first.floor, first.ticker),
+            _ => {}
+        }
+        for pair in brackets.windows(2) {
+            if !touches(pair[0].cap, pair[1].floor) {
+                bail!("{} ends at {} but {} starts at {}", pair[0].ticker, pair[0].cap, pair[1].ticker, pair[1].floor);
+            }
+        }
+        if let Some(b) = brackets.iter().find(|b| b.yes_ask.is_none()) {
+            bail!("{} has no YES ask", b.ticker);
+        }
+        Ok(Self { strike, payoff, legs: brackets })
+    }
+
+    /// YES asks of every leg (cents per synthetic contract)
+    pub fn ask_cents(&self) -> u32 {
+        self.legs.iter().filter_map(|b| b.yes_ask).map(u32::from).sum()
+    }
+
+    /// Kalshi fees of every leg at its ask
+    pub fn fee_cents(&self) -> u32 {
+        self.legs.iter().filter_map(|b| b.yes_ask).map(|a| u32::from(kalshi_fee_cents(a))).sum()
+    }
+
+    /// All-in cost of one synthetic contract
+    pub fn cost_cents(&self) -> u32 {
+        self.ask_cents() + self.fee_cents()
+    }
+
+    /// The synthetic's Kalshi legs plus the Polymarket leg that completes it, `contracts` each
+    pub fn basket(&self, label: &str, poly_side: &'static str, poly_token: &str, poly_ask: PriceCents, contracts: f64) -> Basket {
+        let mut legs: Vec<BasketLeg> = self.legs.iter()
+            .map(|b| BasketLeg {
+                venue: Venue::Kalshi,
+                side: "yes",
+                instrument: b.ticker.clone(),
+                price: cents_to_price(b.yes_ask.unwrap_or(0)),
+                contracts,
+            })
+            .collect();
+        legs.push(BasketLeg {
+            venue: Venue::Polymarket,
+            side: poly_side,
+            instrument: poly_token.to_string(),
+            price: cents_to_price(poly_ask),
+            contracts,
+        });
+        Basket::new(label, legs, 1.0)
+    }
+}
+
+/// A Polymarket "above `strike`" market against both Kalshi synthetics of the same strike
+#[derive(Debug, Clone)]
+pub struct SyntheticPair {
+    pub description: String,
+    pub kalshi_event_ticker: String,
+    pub poly_yes_token: String,
+    pub poly_no_token: String,
+    /// Pays with Polymarket YES
+    pub above: SyntheticBinary,
+    /// Pays with Polymarket NO
+    pub below: SyntheticBinary,
+}
+
+/// The cheaper way to hold both outcomes of a synthetic pair
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticArb {
+    /// Kalshi side bought; Polymarket takes the other
+    pub kalshi: Payoff,
+    /// Kalshi asks + fees + the Polymarket ask + its taker fee (cents)
+    pub cost_cents: u32,
+    /// Polymarket taker fee included in the cost
+    pub poly_fee_cents: u32,
+    /// 100 - cost
+    pub edge_cents: i32,
+}
+
+impl SyntheticPair {
+    /// Both synthetics of `strike` from one Kalshi event's markets
+    pub fn build(description: &str, event_ticker: &str, markets: &[KalshiMarket], strike: f64,
+                 poly_yes_token: &str, poly_no_token: &str) -> Result<Self> {
+        let compose = |payoff| SyntheticBinary::compose(markets, strike, payoff)
+            .map_err(|e| anyhow!("{} {} {}: {}", event_ticker, payoff.name(), strike, e));
+        Ok(Self {
+            description: description.to_string(),
+            kalshi_event_ticker: event_ticker.to_string(),
+            poly_yes_token: poly_yes_token.to_string(),
+            poly_no_token: poly_no_token.to_string(),
+            above: compose(Payoff::Above)?,
+            below: compose(Payoff::Below)?,
+        })
+    }
+
+    /// The same pair priced from a fresh read of its Kalshi event's markets
+    pub fn rebuild(&self, markets: &[KalshiMarket]) -> Result<Self> {
+        Self::build(&self.description, &self.kalshi_event_ticker, markets, self.above.strike,
+                     &self.poly_yes_token, &self.poly_no_token)
+    }
+
+    /// Best of Kalshi-above + Polymarket NO and Polymarket YES + Kalshi-below after both
+    /// venues' fees (`poly_fee_bps` is the market's Polymarket taker rate), if it pays
+    pub fn check(&self, poly_yes_ask: PriceCents, poly_no_ask: PriceCents, poly_fee_bps: u16) -> Option<SyntheticArb> {
+        [(Payoff::Above, &self.above, poly_no_ask), (Payoff::Below, &self.below, poly_yes_ask)]
+            .into_iter()
+            .filter(|(_, _, poly_ask)| (1..100).contains(poly_ask))
+            .map(|(kalshi, synthetic, poly_ask)| {
+                let poly_fee_cents = u32::from(poly_fee_cents(poly_ask, poly_fee_bps));
+                let cost_cents = synthetic.cost_cents() + u32::from(poly_ask) + poly_fee_cents;
+                SyntheticArb { kalshi, cost_cents, poly_fee_cents, edge_cents: 100 - cost_cents as i32 }
+            })
+            .filter(|arb| arb.edge_cents > 0)
+            .max_by_key(|arb| arb.edge_cents)
+    }
+
+    /// The basket that takes `arb`, `contracts` per leg
+    pub fn basket(&self, arb: &SyntheticArb, poly_yes_ask: PriceCents, poly_no_ask: PriceCents, contracts: f64) -> Basket {
+        match arb.kalshi {
+            Payoff::Above => self.above.basket(&self.description, "no", &self.poly_no_token, poly_no_ask, contracts),
+            Payoff::Below => self.below.basket(&self.description, "yes", &self.poly_yes_token, poly_yes_ask, contracts),
+        }
+    }
+}
+
+/// One Polymarket above-strike market and the Kalshi event whose brackets it is matched to
+/// (`EVENT:slug:strike`), checked by hand for strike and settlement source
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticSpec {
+    pub event_ticker: String,
+    pub poly_slug: String,
+    pub strike: f64,
+}
+
+impl SyntheticSpec {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().splitn(3, ':');
+        let (event_ticker, poly_slug, strike) = (parts.next()?, parts.next()?, parts.next()?);
+        let strike = strike.trim().parse::<f64>().ok().filter(|k| k.is_finite() && *k > 0.0)?;
+        if event_ticker.is_empty() || poly_slug.is_empty() {
+            return None;
+        }
+        Some(Self { event_ticker: event_ticker.to_string(), poly_slug: poly_slug.to_string(), strike })
+    }
+}
+
+/// Synthetic pairs watched by the main bot
+#[derive(Debug, Clone)]
+pub struct SyntheticConfig {
+    /// SYNTHETIC_PAIRS, comma-separated `EVENT:slug:strike` (empty = off)
+    pub pairs: Vec<SyntheticSpec>,
+    /// How often both venues are re-priced
+    pub poll_secs: u64,
+    /// Least edge after fees that counts as an arb (every bracket is another order that can miss)
+    pub min_edge_cents: i32,
+}
+
+impl SyntheticConfig {
+    pub fn from_env() -> Self {
+        let pairs = std::env::var("SYNTHETIC_PAIRS").unwrap_or_default()
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .filter_map(|s| {
+                let spec = SyntheticSpec::parse(s);
+                if spec.is_none() {
+                    warn!("[SYNTH] Ignoring SYNTHETIC_PAIRS entry {:?} (expected EVENT:slug:strike)", s.trim());
+                }
+                spec
+            })
+            .collect();
+        Self {
+            pairs,
+            poll_secs: std::env::var("SYNTHETIC_POLL_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(10).max(1),
+            min_edge_cents: std::env::var("SYNTHETIC_MIN_EDGE_CENTS").ok().and_then(|v| v.parse().ok()).unwrap_or(2),
+        }
+    }
+}
+
+/// Lowest ask of a book (0 if it has none)
+#[cfg(feature = "kalshi")]
+fn best_ask(book: Option<&BookSnapshot>) -> PriceCents {
+    book.into_iter()
+        .flat_map(|b| b.asks.iter())
+        .map(|l| parse_price(&l.price))
+        .filter(|p| *p > 0)
+        .min()
+        .unwrap_or(0)
+}
+
+/// Match the configured pairs, then re-price both venues every `poll_secs`. Arbs past
+/// `min_edge_cents` are logged and recorded in the opportunity history; synthetic baskets
+/// aren't executed, since each needs its strike and settlement source checked by hand.
+#[cfg(feature = "kalshi")]
+pub fn spawn(config: SyntheticConfig, discovery: Arc<DiscoveryClient>, poly: Arc<SharedAsyncClient>) {
+    if config.pairs.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut pairs = Vec::new();
+        for spec in &config.pairs {
+            match discovery.discover_synthetic(&spec.event_ticker, &spec.poly_slug, spec.strike).await {
+                Ok(Some(pair)) => pairs.push(pair),
+                Ok(None) => warn!("[SYNTH] {} is not listed on Polymarket", spec.poly_slug),
+                Err(e) => warn!("[SYNTH] {} vs {}: {}", spec.poly_slug, spec.event_ticker, e),
+            }
+        }
+        if pairs.is_empty() {
+            return;
+        }
+        let tokens: Vec<String> = pairs.iter().map(|p| p.poly_yes_token.clone()).collect();
+        let fee_rates = poly.prefetch_fee_rates(&tokens).await;
+        info!("[SYNTH] Watching {} synthetic pair(s) every {}s (min edge {}¢)", pairs.len(), config.poll_secs, config.min_edge_cents);
+
+        let http = reqwest::Client::new();
+        let mut last_edge: Vec<Option<i32>> = vec![None; pairs.len()];
+        let mut interval = tokio::time::interval(Duration::from_secs(config.poll_secs));
+        loop {
+            interval.tick().await;
+            let tokens: Vec<String> = pairs.iter()
+                .flat_map(|p| [p.poly_yes_token.clone(), p.poly_no_token.clone()])
+                .collect();
+            let books = match polymarket_clob::fetch_books(&http, crate::app::POLY_CLOB_HOST, &tokens, BOOKS_TIMEOUT).await {
+                Ok(books) => books,
+                Err(e) => {
+                    warn!("[SYNTH] Polymarket books unavailable: {}", e);
+                    continue;
+                }
+            };
+            let book = |token: &str| books.iter().find(|b| b.asset_id == token);
+
+            for (pair, last) in pairs.iter_mut().zip(last_edge.iter_mut()) {
+                match discovery.kalshi_event_markets(&pair.kalshi_event_ticker).await.and_then(|m| pair.rebuild(&m)) {
+                    Ok(fresh) => *pair = fresh,
+                    Err(e) => {
+                        warn!("[SYNTH] {} not priced: {}", pair.description, e);
+                        continue;
+                    }
+                }
+                let (yes_ask, no_ask) = (best_ask(book(&pair.poly_yes_token)), best_ask(book(&pair.poly_no_token)));
+                let fee_bps = fee_rates.get(&pair.poly_yes_token).map_or(0, |bps| (*bps).min(u32::from(u16::MAX)) as u16);
+                let arb = pair.check(yes_ask, no_ask, fee_bps).filter(|a| a.edge_cents >= config.min_edge_cents);
+                let edge = arb.as_ref().map(|a| a.edge_cents);
+                if edge == *last {
+                    continue;
+                }
+                *last = edge;
+                let Some(arb) = arb else { continue };
+
+                let (synthetic, poly_ask) = match arb.kalshi {
+                    Payoff::Above => (&pair.above, no_ask),
+                    Payoff::Below => (&pair.below, yes_ask),
+                };
+                let basket = pair.basket(&arb, yes_ask, no_ask, 1.0);
+                let skip = match basket.precheck() {
+                    Ok(()) => "synthetic baskets are alert-only".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                info!("[SYNTH] 🧩 {} | Kalshi {} ({} legs) {}¢ + fees {}¢ + Poly {}¢ + fee {}¢ = {}¢ → {}¢ edge",
+                      pair.description, arb.kalshi.name(), synthetic.legs.len(), synthetic.ask_cents(), synthetic.fee_cents(),
+                      poly_ask, arb.poly_fee_cents, arb.cost_cents, arb.edge_cents);
+                metrics::incr("synthetic.detected");
+                opportunities::record(OpportunityRecord::new(
+                    "synthetic", &pair.description, &format!("synthetic_{}", arb.kalshi.name()),
+                    cents_to_price(synthetic.ask_cents() as PriceCents), cents_to_price(poly_ask), 0.0, 0.0,
+                    (synthetic.fee_cents() + arb.poly_fee_cents) as f64 / 100.0,
+                ).skipped(&skip));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(ticker: &str, strike_type: &str, floor: Option<f64>, cap: Option<f64>, yes_ask: i64) -> KalshiMarket {
+        serde_json::from_value(serde_json::json!({
+            "ticker": ticker, "title": ticker, "yes_ask": yes_ask, "yes_bid": null, "no_ask": null, "no_bid": null,
+            "strike_type": strike_type, "floor_strike": floor, "cap_strike": cap, "volume": null, "liquidity": null,
+        })).unwrap()
+    }
+
+    /// BTC below 99,500 | 99,500-99,749.99 | 99,750-99,999.99 | 100,000-100,249.99 | above 100,250
+    fn ladder() -> Vec<KalshiMarket> {
+        vec![
+            market("KXBTC-T99500", "less", None, Some(99_499.99), 20),
+            market("KXBTC-B99625", "between", Some(99_500.0), Some(99_749.99), 12),
+            market("KXBTC-B99875", "between", Some(99_750.0), Some(99_999.99), 15),
+            market("KXBTC-B100125", "between", Some(100_000.0), Some(100_249.99), 18),
+            market("KXBTC-T100250", "greater", Some(100_250.0), None, 30),
+        ]
+    }
+
+    #[test]
+    fn test_compose_both_sides_of_a_strike() {
+        let above = SyntheticBinary::compose(&ladder(), 100_000.0, Payoff::Above).unwrap();
+        let tickers: Vec<_> = above.legs.iter().map(|b| b.ticker.as_str()).collect();
+        assert_eq!(tickers, ["KXBTC-B100125", "KXBTC-T100250"]);
+        assert_eq!(above.ask_cents(), 48);
+        assert_eq!(above.fee_cents(), u32::from(kalshi_fee_cents(18) + kalshi_fee_cents(30)));
+
+        let below = SyntheticBinary::compose(&ladder(), 100_000.0, Payoff::Below).unwrap();
+        assert_eq!(below.legs.len(), 3);
+        assert_eq!(below.ask_cents(), 47);
+    }
+
+    #[test]
+    fn test_gaps_and_straddles_refused() {
+        let mut gapped = ladder();
+        gapped.remove(1);
+        assert!(SyntheticBinary::compose(&gapped, 100_000.0, Payoff::Below).unwrap_err().to_string().contains("ends at"));
+        gapped.remove(2);
+        assert!(SyntheticBinary::compose(&gapped, 100_000.0, Payoff::Above).unwrap_err().to_string().contains("not the strike"));
+        // 99,900 falls inside a bracket: neither side can be built from whole brackets
+        assert!(SyntheticBinary::compose(&ladder(), 99_900.0, Payoff::Above).is_err());
+        assert!(SyntheticBinary::compose(&ladder(), 99_900.0, Payoff::Below).is_err());
+        // No open end
+        let capped: Vec<_> = ladder().into_iter().take(4).collect();
+        assert!(SyntheticBinary::compose(&capped, 100_000.0, Payoff::Above).is_err());
+        // A leg without an ask can't be priced
+        let mut no_ask = ladder();
+        no_ask[4].yes_ask = None;
+        assert!(SyntheticBinary::compose(&no_ask, 100_000.0, Payoff::Above).unwrap_err().to_string().contains("no YES ask"));
+    }
+
+    #[test]
+    fn test_cross_venue_check_and_basket() {
+        let pair = SyntheticPair::build("BTC above 100k", "KXBTC-25DEC1917", &ladder(), 100_000.0, "111", "222").unwrap();
+        // Kalshi above costs 48 + fees (4) = 52; with Polymarket NO at 45 that's 97
+        let arb = pair.check(58, 45, 0).unwrap();
+        assert_eq!(arb.kalshi, Payoff::Above);
+        assert_eq!(arb.cost_cents, 97);
+        assert_eq!(arb.edge_cents, 3);
+        let basket = pair.basket(&arb, 58, 45, 10.0);
+        assert_eq!(basket.legs.len(), 3);
+        assert_eq!(basket.legs[2].venue, Venue::Polymarket);
+        assert_eq!((basket.legs[2].side, basket.legs[2].instrument.as_str()), ("no", "222"));
+        // Priced at parity: nothing
+        assert!(pair.check(60, 55, 0).is_none());
+        // A Polymarket taker fee comes out of the edge: 1000bps on a 45¢ ask is 5¢
+        assert!(pair.check(58, 45, 1000).is_none());
+        let arb = pair.check(58, 45, 200).unwrap();
+        assert_eq!((arb.poly_fee_cents, arb.cost_cents, arb.edge_cents), (1, 98, 2));
+    }
+
+    #[test]
+    fn test_spec_parse() {
+        let spec = SyntheticSpec::parse(" KXBTC-25DEC1917:bitcoin-above-100k-on-december-19:100000 ").unwrap();
+        assert_eq!((spec.event_ticker.as_str(), spec.poly_slug.as_str(), spec.strike),
+                   ("KXBTC-25DEC1917", "bitcoin-above-100k-on-december-19", 100_000.0));
+        assert!(SyntheticSpec::parse("KXBTC-25DEC1917:slug").is_none());
+        assert!(SyntheticSpec::parse("KXBTC-25DEC1917:slug:abc").is_none());
+        assert!(SyntheticSpec::parse(":slug:100").is_none());
+    }
+}
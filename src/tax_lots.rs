@@ -0,0 +1,282 @@
+// src/tax_lots.rs
+// Per-lot acquisition/disposal records from settled positions (tax export)
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write as _;
+
+use crate::position_tracker::{ArbPosition, TradeRecord};
+
+/// Days a lot must be held to count as long-term
+const LONG_TERM_DAYS: i64 = 365;
+
+/// One acquired lot and how it was disposed of
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxLot {
+    /// "<platform> [<outcome>] <YES|NO> | <market description>"
+    pub asset: String,
+    pub quantity: f64,
+    pub date_acquired: String,
+    pub date_disposed: String,
+    /// Dollars received (net of fees)
+    pub proceeds: f64,
+    /// Dollars paid (including fees)
+    pub cost_basis: f64,
+    /// "sale", "redemption" (winning tokens redeemed at $1) or "expired_worthless"
+    pub disposal: &'static str,
+}
+
+impl TaxLot {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost_basis
+    }
+
+    pub fn is_long_term(&self) -> bool {
+        let parse = |s: &str| chrono::DateTime::parse_from_rfc3339(s).ok();
+        match (parse(&self.date_acquired), parse(&self.date_disposed)) {
+            (Some(a), Some(d)) => (d - a).num_days() > LONG_TERM_DAYS,
+            _ => false,
+        }
+    }
+}
+
+/// Open lot: (quantity, basis per contract, acquired at)
+type OpenLot = (f64, f64, String);
+
+/// Lots for a settled (resolved) position, FIFO per platform/side (and outcome of a
+/// multi-outcome event). Open positions return nothing - their lots aren't disposed of yet.
+pub fn lots_for_position(pos: &ArbPosition) -> Vec<TaxLot> {
+    let Some(resolved_at) = pos.resolved_at.as_deref() else {
+        return Vec::new();
+    };
+    if pos.status != "resolved" || (pos.outcome_yes.is_none() && pos.winning_outcome.is_none()) {
+        return Vec::new();
+    }
+    // Whether a leg pays out: YES of the winner and NO of every other outcome, or the
+    // winning side of a binary market
+    let won = |outcome: Option<&str>, side: &str| match (outcome, pos.winning_outcome.as_deref(), pos.outcome_yes) {
+        (Some(outcome), Some(winner), _) => (side == "yes") == (outcome == winner),
+        (None, _, Some(yes_won)) => (side == "yes") == yes_won,
+        _ => false,
+    };
+
+    let trades = if pos.trades.is_empty() { synthesize_trades(pos) } else { pos.trades.clone() };
+    let outcomes: BTreeSet<Option<&str>> = trades.iter().map(|t| t.outcome.as_deref()).collect();
+
+    let mut lots = Vec::new();
+    for outcome in outcomes {
+        for (platform, side) in [("kalshi", "yes"), ("kalshi", "no"), ("polymarket", "yes"), ("polymarket", "no")] {
+            let leg = |t: &&TradeRecord| t.platform == platform && t.side == side && t.outcome.as_deref() == outcome;
+            dispose_leg(pos, trades.iter().filter(leg), outcome, platform, side, won(outcome, side), resolved_at, &mut lots);
+        }
+    }
+    lots
+}
+
+/// FIFO lots of one leg: sales as they happened, whatever is left at resolution
+#[allow(clippy::too_many_arguments)]
+fn dispose_leg<'a>(
+    pos: &ArbPosition,
+    trades: impl Iterator<Item = &'a TradeRecord>,
+    outcome: Option<&str>,
+    platform: &str,
+    side: &str,
+    won: bool,
+    resolved_at: &str,
+    lots: &mut Vec<TaxLot>,
+) {
+    let asset = match outcome {
+        Some(outcome) => format!("{} {} {} | {}", platform, outcome, side.to_uppercase(), pos.description),
+        None => format!("{} {} | {}", platform, side.to_uppercase(), pos.description),
+    };
+    let mut open: VecDeque<OpenLot> = VecDeque::new();
+
+    for t in trades {
+        let qty = t.contracts.abs();
+        if qty < 1e-9 {
+            continue;
+        }
+        let fee_per_contract = t.fees / qty;
+        if t.contracts > 0.0 {
+            open.push_back((qty, t.price + fee_per_contract, t.timestamp.clone()));
+        } else {
+            dispose(&mut open, lots, &asset, qty, t.price - fee_per_contract, &t.timestamp, "sale");
+        }
+    }
+
+    // Whatever is left was redeemed at resolution
+    let remaining: f64 = open.iter().map(|l| l.0).sum();
+    let (payout, disposal) = if won { (1.0, "redemption") } else { (0.0, "expired_worthless") };
+    dispose(&mut open, lots, &asset, remaining, payout, resolved_at, disposal);
+}
+
+fn dispose(
+    open: &mut VecDeque<OpenLot>,
+    lots: &mut Vec<TaxLot>,
+    asset: &str,
+    mut qty: f64,
+    unit_proceeds: f64,
+    date: &str,
+    disposal: &'static str,
+) {
+    while qty > 1e-9 {
+        let Some(front) = open.front_mut() else { break };
+        let take = qty.min(front.0);
+        lots.push(TaxLot {
+            asset: asset.to_string(),
+            quantity: take,
+            date_acquired: front.2.clone(),
+            date_disposed: date.to_string(),
+            proceeds: take * unit_proceeds,
+            cost_basis: take * front.1,
+            disposal,
+        });
+        front.0 -= take;
+        qty -= take;
+        if front.0 <= 1e-9 {
+            open.pop_front();
+        }
+    }
+}
+
+/// Positions recorded before per-trade history existed: one lot per leg at the
+/// average price, fees spread across legs by contract count (outcome legs are
+/// Polymarket's, the only venue baskets trade on)
+fn synthesize_trades(pos: &ArbPosition) -> Vec<TradeRecord> {
+    let legs = [
+        ("kalshi", "yes", &pos.kalshi_yes),
+        ("kalshi", "no", &pos.kalshi_no),
+        ("polymarket", "yes", &pos.poly_yes),
+        ("polymarket", "no", &pos.poly_no),
+    ];
+    let outcome_legs = pos.outcomes.iter().flat_map(|(outcome, legs)| {
+        [(Some(outcome), "polymarket", "yes", &legs.yes), (Some(outcome), "polymarket", "no", &legs.no)]
+    });
+    let total = pos.total_contracts();
+    legs.into_iter()
+        .map(|(platform, side, leg)| (None, platform, side, leg))
+        .chain(outcome_legs)
+        .filter(|(_, _, _, leg)| leg.contracts > 0.0)
+        .map(|(outcome, platform, side, leg)| TradeRecord {
+            timestamp: pos.opened_at.clone(),
+            platform: platform.to_string(),
+            side: side.to_string(),
+            contracts: leg.contracts,
+            price: leg.cost_basis / leg.contracts,
+            fees: if total > 0.0 { pos.total_fees * leg.contracts / total } else { 0.0 },
+            order_id: String::new(),
+            outcome: outcome.cloned(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// CSV in the generic capital-gains layout accepted by common crypto tax tools
+pub fn to_csv(lots: &[TaxLot]) -> String {
+    let mut out = String::from("asset,quantity,date_acquired,date_disposed,proceeds,cost_basis,gain,term,disposal\n");
+    for lot in lots {
+        let _ = writeln!(out, "{},{:.6},{},{},{:.6},{:.6},{:.6},{},{}",
+                         csv_field(&lot.asset), lot.quantity, lot.date_acquired, lot.date_disposed,
+                         lot.proceeds, lot.cost_basis, lot.gain(),
+                         if lot.is_long_term() { "long" } else { "short" },
+                         lot.disposal);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(ts: &str, side: &str, contracts: f64, price: f64, fees: f64) -> TradeRecord {
+        TradeRecord {
+            timestamp: ts.into(),
+            platform: "polymarket".into(),
+            side: side.into(),
+            contracts,
+            price,
+            fees,
+            order_id: String::new(),
+            outcome: None,
+            ..Default::default()
+        }
+    }
+
+    fn resolved(trades: Vec<TradeRecord>, yes_won: bool) -> ArbPosition {
+        let mut pos = ArbPosition::new("m", "BTC, Up or Down");
+        pos.trades = trades;
+        pos.status = "resolved".into();
+        pos.outcome_yes = Some(yes_won);
+        pos.resolved_at = Some("2025-12-18T20:00:00+00:00".into());
+        pos
+    }
+
+    #[test]
+    fn test_fifo_sale_then_redemption() {
+        let pos = resolved(vec![
+            trade("2025-12-18T19:00:00+00:00", "yes", 10.0, 0.40, 0.0),
+            trade("2025-12-18T19:01:00+00:00", "yes", 5.0, 0.50, 0.0),
+            trade("2025-12-18T19:02:00+00:00", "yes", -12.0, 0.60, 0.0),
+            trade("2025-12-18T19:00:00+00:00", "no", 3.0, 0.55, 0.0),
+        ], true);
+        let lots = lots_for_position(&pos);
+
+        // 10 from the first lot sold, 2 from the second, 3 redeemed, NO leg worthless
+        assert_eq!(lots.len(), 4);
+        assert_eq!((lots[0].quantity, lots[0].disposal), (10.0, "sale"));
+        assert!((lots[0].gain() - 2.0).abs() < 1e-9);
+        assert_eq!((lots[1].quantity, lots[1].disposal), (2.0, "sale"));
+        assert_eq!((lots[2].quantity, lots[2].disposal), (3.0, "redemption"));
+        assert!((lots[2].proceeds - 3.0).abs() < 1e-9);
+        assert_eq!(lots[3].disposal, "expired_worthless");
+        assert!((lots[3].gain() + 1.65).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_open_positions_and_csv() {
+        let mut pos = resolved(vec![trade("2025-12-18T19:00:00+00:00", "yes", 1.0, 0.40, 0.0)], true);
+        pos.status = "open".into();
+        assert!(lots_for_position(&pos).is_empty());
+
+        pos.status = "resolved".into();
+        let csv = to_csv(&lots_for_position(&pos));
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with("\"polymarket YES | BTC, Up or Down\",1.000000,"));
+        assert!(row.ends_with(",0.600000,short,redemption"));
+    }
+
+    #[test]
+    fn test_multi_outcome_lots() {
+        let in_outcome = |outcome: &str, side: &str, contracts: f64, price: f64| TradeRecord {
+            outcome: Some(outcome.into()),
+            ..trade("2025-12-18T19:00:00+00:00", side, contracts, price, 0.0)
+        };
+        let mut pos = resolved(vec![
+            in_outcome("A", "yes", 10.0, 0.30),
+            in_outcome("B", "yes", 10.0, 0.35),
+            in_outcome("C", "no", 4.0, 0.70),
+        ], true);
+        pos.outcome_yes = None;
+        pos.winning_outcome = Some("B".into());
+
+        let lots = lots_for_position(&pos);
+        let disposals: Vec<(&str, &str)> = lots.iter().map(|l| (l.asset.as_str(), l.disposal)).collect();
+        assert_eq!(disposals, [
+            ("polymarket A YES | BTC, Up or Down", "expired_worthless"),
+            ("polymarket B YES | BTC, Up or Down", "redemption"),
+            ("polymarket C NO | BTC, Up or Down", "redemption"),
+        ]);
+
+        // A file without per-trade history gets the same lots from its outcome legs
+        pos.trades.clear();
+        pos.outcomes.entry("B".into()).or_default().yes.add(10.0, 0.35);
+        assert_eq!(lots_for_position(&pos)[0].disposal, "redemption");
+    }
+}
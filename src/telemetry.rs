@@ -0,0 +1,320 @@
+// src/telemetry.rs
+// Opt-in weekly performance summary - anonymized opportunity / capture / slippage aggregates for comparing setups
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::app::env_flag;
+use crate::archive;
+use crate::opportunities::{self, OpportunityRecord};
+use crate::runtimes;
+use crate::storage;
+
+/// Bumped when fields change meaning, so aggregators can tell summaries apart
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Random id in DATA_DIR tying one install's weeks together (nothing else identifies it)
+const INSTALL_ID_FILE: &str = "telemetry_id";
+
+/// Slippage histogram buckets are whole cents, clamped to ±this
+const HISTOGRAM_CLAMP_CENTS: i64 = 5;
+
+/// Telemetry configuration from environment
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// Off unless the operator opts in
+    pub enabled: bool,
+
+    /// Directory the weekly files are written to (in DATA_DIR unless absolute)
+    pub dir: PathBuf,
+
+    /// Hours between checks for a finished week without a summary
+    pub check_hours: u64,
+}
+
+impl TelemetryConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: env_flag("TELEMETRY", false),
+
+            dir: storage::data_path(std::env::var("TELEMETRY_DIR").unwrap_or_else(|_| "telemetry".to_string())),
+
+            check_hours: std::env::var("TELEMETRY_CHECK_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(6),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled && self.check_hours > 0
+    }
+}
+
+/// Spread of a set of samples
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    /// Samples per whole cent, clamped to ±5
+    pub histogram: BTreeMap<i64, usize>,
+}
+
+impl Distribution {
+    pub fn of(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let pct = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        let mut histogram = BTreeMap::new();
+        for s in &sorted {
+            let bucket = (s.round() as i64).clamp(-HISTOGRAM_CLAMP_CENTS, HISTOGRAM_CLAMP_CENTS);
+            *histogram.entry(bucket).or_insert(0) += 1;
+        }
+        Self {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p10: pct(0.1),
+            p50: pct(0.5),
+            p90: pct(0.9),
+            histogram,
+        }
+    }
+}
+
+/// Detections for one strategy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrategyStats {
+    pub detected: usize,
+    pub taken: usize,
+    /// Taken / detected
+    pub capture_rate: f64,
+    /// Net edge per contract at detection (cents)
+    pub edge_cents: Distribution,
+    /// Untaken detections by skip reason
+    pub skipped: BTreeMap<String, usize>,
+}
+
+/// One week's aggregates. Holds no market names, order ids, wallet addresses or
+/// dollar amounts: only counts, rates and per-contract cents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklySummary {
+    pub schema: u32,
+    /// ISO week, e.g. "2026-W41"
+    pub week: String,
+    pub bot_version: String,
+    pub install_id: String,
+    pub strategies: BTreeMap<String, StrategyStats>,
+    /// Fill price minus the detected ask, per contract (cents, positive = paid more)
+    pub slippage_cents: Distribution,
+}
+
+/// Monday 00:00 UTC of the week before the one `now` falls in, and the week's label
+pub fn previous_week(now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>, String) {
+    let monday = now.date_naive() - ChronoDuration::days(now.weekday().num_days_from_monday() as i64);
+    let end = monday.and_time(NaiveTime::MIN).and_utc();
+    let start = end - ChronoDuration::weeks(1);
+    let iso = start.iso_week();
+    (start, end, format!("{}-W{:02}", iso.year(), iso.week()))
+}
+
+fn in_window(ts: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(ts).is_ok_and(|t| t >= start && t < end)
+}
+
+/// Per-contract slippage of every fill in an audit log (EVENT_LOG_FILE lines) within the
+/// window, against the ask of the same market's most recent arb_detected
+pub fn slippage_samples(audit: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<f64> {
+    let mut detected: HashMap<String, (f64, f64)> = HashMap::new();
+    let mut samples = Vec::new();
+    for line in audit.lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+        let data = &entry["data"];
+        let market = data["market"].as_str().unwrap_or_default().to_string();
+        match data["event"].as_str() {
+            Some("arb_detected") => {
+                if let (Some(yes), Some(no)) = (data["yes_price"].as_f64(), data["no_price"].as_f64()) {
+                    detected.insert(market, (yes, no));
+                }
+            }
+            Some("filled") if in_window(entry["ts"].as_str().unwrap_or_default(), start, end) => {
+                let (Some(contracts), Some(cost)) = (data["contracts"].as_f64(), data["cost"].as_f64()) else { continue };
+                let Some(&(yes, no)) = detected.get(&market) else { continue };
+                let ask = if data["side"].as_str() == Some("no") { no } else { yes };
+                if contracts > 0.0 {
+                    samples.push((cost / contracts - ask) * 100.0);
+                }
+            }
+            _ => {}
+        }
+    }
+    samples
+}
+
+/// Aggregate one week of opportunity records and fill slippage
+pub fn summarize(week: &str, install_id: &str, opps: &[OpportunityRecord], slippage: &[f64]) -> WeeklySummary {
+    let mut by_strategy: BTreeMap<String, Vec<&OpportunityRecord>> = BTreeMap::new();
+    for r in opps {
+        by_strategy.entry(r.strategy.clone()).or_default().push(r);
+    }
+    let strategies = by_strategy.into_iter()
+        .map(|(strategy, records)| {
+            let taken = records.iter().filter(|r| r.taken).count();
+            let mut skipped = BTreeMap::new();
+            for r in records.iter().filter(|r| !r.taken) {
+                let reason = r.skip_reason.clone().unwrap_or_else(|| "unknown".to_string());
+                *skipped.entry(reason).or_insert(0) += 1;
+            }
+            let edges: Vec<f64> = records.iter().map(|r| r.edge * 100.0).collect();
+            (strategy, StrategyStats {
+                detected: records.len(),
+                taken,
+                capture_rate: taken as f64 / records.len() as f64,
+                edge_cents: Distribution::of(&edges),
+                skipped,
+            })
+        })
+        .collect();
+
+    WeeklySummary {
+        schema: SCHEMA_VERSION,
+        week: week.to_string(),
+        bot_version: env!("CARGO_PKG_VERSION").to_string(),
+        install_id: install_id.to_string(),
+        strategies,
+        slippage_cents: Distribution::of(slippage),
+    }
+}
+
+/// This install's random id, created on first use
+fn install_id() -> Result<String> {
+    let path = storage::data_path(INSTALL_ID_FILE);
+    if let Ok(id) = std::fs::read_to_string(&path) {
+        if !id.trim().is_empty() {
+            return Ok(id.trim().to_string());
+        }
+    }
+    let id: String = (0..16).map(|_| format!("{:02x}", rand::random::<u8>())).collect();
+    storage::write_atomic(&path, id.as_bytes())?;
+    Ok(id)
+}
+
+/// Read a history file (and its archived segments), empty if it doesn't exist
+fn read_history(path: &Path) -> String {
+    archive::read_all(path).unwrap_or_default()
+}
+
+/// Write the summary of the last finished week unless it already exists; the written path
+pub fn write_previous_week(config: &TelemetryConfig, now: DateTime<Utc>) -> Result<Option<PathBuf>> {
+    let (start, end, week) = previous_week(now);
+    let path = config.dir.join(format!("summary-{}.json", week));
+    if path.exists() {
+        return Ok(None);
+    }
+
+    let opp_path = storage::data_path(std::env::var("OPP_FILE").unwrap_or_else(|_| opportunities::DEFAULT_OPP_FILE.to_string()));
+    let opps: Vec<OpportunityRecord> = read_history(&opp_path).lines()
+        .filter_map(|l| serde_json::from_str::<OpportunityRecord>(l).ok())
+        .filter(|r| in_window(&r.ts, start, end))
+        .collect();
+    let slippage = std::env::var("EVENT_LOG_FILE")
+        .map(|p| slippage_samples(&read_history(Path::new(&p)), start, end))
+        .unwrap_or_default();
+
+    let summary = summarize(&week, &install_id()?, &opps, &slippage);
+    std::fs::create_dir_all(&config.dir)?;
+    storage::write_atomic(&path, serde_json::to_string_pretty(&summary)?.as_bytes())?;
+    Ok(Some(path))
+}
+
+/// Check every TELEMETRY_CHECK_HOURS for a finished week without a summary (TELEMETRY=1 only).
+/// Files stay local; sharing them is up to the operator.
+pub fn spawn(config: TelemetryConfig) {
+    if !config.enabled() {
+        return;
+    }
+    info!("[TELEMETRY] Weekly summaries in {}", config.dir.display());
+    runtimes::spawn_bookkeeping(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.check_hours * 3600));
+        loop {
+            ticker.tick().await;
+            let cfg = config.clone();
+            match tokio::task::spawn_blocking(move || write_previous_week(&cfg, Utc::now())).await {
+                Ok(Ok(Some(path))) => info!("[TELEMETRY] 📊 Wrote {}", path.display()),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => warn!("[TELEMETRY] Summary failed: {}", e),
+                Err(e) => warn!("[TELEMETRY] Summary task failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ts: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(ts).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_previous_week_bounds() {
+        // Wednesday 2026-10-14 → week of Monday 2026-10-05
+        let (start, end, week) = previous_week(at("2026-10-14T15:00:00Z"));
+        assert_eq!((start, end), (at("2026-10-05T00:00:00Z"), at("2026-10-12T00:00:00Z")));
+        assert_eq!(week, "2026-W41");
+        // Monday just after midnight: the week that just ended
+        assert_eq!(previous_week(at("2026-10-12T00:00:01Z")).2, "2026-W41");
+        // ISO year differs from the calendar year
+        assert_eq!(previous_week(at("2027-01-06T00:00:00Z")).2, "2026-W53");
+    }
+
+    #[test]
+    fn test_slippage_from_audit_log() {
+        let audit = [
+            r#"{"ts":"2026-10-06T10:00:00Z","data":{"event":"arb_detected","market":"A","arb_type":"PolyOnly","yes_price":0.40,"no_price":0.55,"contracts":10.0}}"#,
+            r#"{"ts":"2026-10-06T10:00:01Z","data":{"event":"filled","market":"A","platform":"polymarket","side":"yes","contracts":10.0,"cost":4.1,"order_id":"1"}}"#,
+            r#"{"ts":"2026-10-06T10:00:01Z","data":{"event":"filled","market":"A","platform":"polymarket","side":"no","contracts":10.0,"cost":5.5,"order_id":"2"}}"#,
+            // No detection for this market, and a fill outside the week
+            r#"{"ts":"2026-10-06T11:00:00Z","data":{"event":"filled","market":"B","platform":"kalshi","side":"yes","contracts":5.0,"cost":2.0,"order_id":"3"}}"#,
+            r#"{"ts":"2026-10-13T10:00:00Z","data":{"event":"filled","market":"A","platform":"polymarket","side":"yes","contracts":1.0,"cost":0.5,"order_id":"4"}}"#,
+            "not json",
+        ].join("\n");
+        let samples = slippage_samples(&audit, at("2026-10-05T00:00:00Z"), at("2026-10-12T00:00:00Z"));
+        assert_eq!(samples.len(), 2);
+        assert!((samples[0] - 1.0).abs() < 1e-9 && samples[1].abs() < 1e-9);
+
+        let d = Distribution::of(&[-9.0, 0.0, 0.2, 1.0, 3.0]);
+        assert_eq!((d.count, d.p50), (5, 0.2));
+        assert_eq!(d.histogram, BTreeMap::from([(-5, 1), (0, 2), (1, 1), (3, 1)]));
+    }
+
+    #[test]
+    fn test_summary_is_anonymized() {
+        let mut taken = OpportunityRecord::new("btc", "Bitcoin Up or Down", "PolyOnly", 0.45, 0.50, 10.0, 10.0, 0.0);
+        taken.taken = true;
+        let mut skipped = OpportunityRecord::new("btc", "Bitcoin Up or Down", "PolyOnly", 0.47, 0.50, 10.0, 10.0, 0.0);
+        skipped.skip_reason = Some("throttled".into());
+        let s = summarize("2026-W41", "abc", &[taken, skipped], &[1.0]);
+
+        let poly = &s.strategies["PolyOnly"];
+        assert_eq!((poly.detected, poly.taken, poly.capture_rate), (2, 1, 0.5));
+        assert_eq!(poly.skipped, BTreeMap::from([("throttled".to_string(), 1)]));
+        assert_eq!(poly.edge_cents.count, 2);
+
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(!json.contains("Bitcoin") && !json.contains("btc"));
+        assert_eq!(serde_json::from_str::<WeeklySummary>(&json).unwrap(), s);
+    }
+}
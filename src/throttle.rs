@@ -0,0 +1,445 @@
+// src/throttle.rs
+// Trade throttle - caps execution frequency globally and per market, backs off after failures
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::metrics;
+use crate::snapshots::{self, StrategyState};
+
+/// Sliding window used for the per-minute limits
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Throttle configuration from environment (0 disables an individual limit)
+#[derive(Debug, Clone)]
+pub struct ThrottleConfig {
+    /// Maximum executions per minute across all markets
+    pub max_per_minute: u32,
+
+    /// Maximum executions per minute on a single market
+    pub max_per_market_per_minute: u32,
+
+    /// Minimum seconds between executions on the same market
+    pub min_interval_secs: u64,
+
+    /// Cooldown after a market's first failed order; doubles per consecutive failure (0 = off)
+    pub failure_cooldown_secs: u64,
+
+    /// Upper bound for the failure cooldown
+    pub max_cooldown_secs: u64,
+
+    /// Consecutive failures on one market that count as a retry storm
+    pub retry_storm_failures: u32,
+}
+
+impl ThrottleConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_per_minute: std::env::var("THROTTLE_MAX_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+
+            max_per_market_per_minute: std::env::var("THROTTLE_MAX_PER_MARKET_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(6),
+
+            min_interval_secs: std::env::var("THROTTLE_MIN_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            failure_cooldown_secs: std::env::var("THROTTLE_FAILURE_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+
+            max_cooldown_secs: std::env::var("THROTTLE_MAX_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+
+            retry_storm_failures: std::env::var("THROTTLE_RETRY_STORM_FAILURES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+        }
+    }
+}
+
+/// Reason an execution was throttled
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThrottleReason {
+    GlobalRate { count: u32, limit: u32 },
+    MarketRate { market: String, count: u32, limit: u32 },
+    MinInterval { market: String, elapsed_secs: u64, min_secs: u64 },
+    Cooldown { market: String, failures: u32, remaining_secs: u64 },
+}
+
+impl std::fmt::Display for ThrottleReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThrottleReason::GlobalRate { count, limit } => {
+                write!(f, "Global rate: {} executions in last minute (limit: {})", count, limit)
+            }
+            ThrottleReason::MarketRate { market, count, limit } => {
+                write!(f, "Market rate: {} has {} executions in last minute (limit: {})", market, count, limit)
+            }
+            ThrottleReason::MinInterval { market, elapsed_secs, min_secs } => {
+                write!(f, "Min interval: {} traded {}s ago (min: {}s)", market, elapsed_secs, min_secs)
+            }
+            ThrottleReason::Cooldown { market, failures, remaining_secs } => {
+                write!(f, "Cooldown: {} failed {} time(s) in a row ({}s left)", market, failures, remaining_secs)
+            }
+        }
+    }
+}
+
+/// Consecutive order failures on one market
+struct FailureState {
+    consecutive: u32,
+    until: Instant,
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    global: VecDeque<Instant>,
+    per_market: HashMap<String, VecDeque<Instant>>,
+    failures: HashMap<String, FailureState>,
+}
+
+impl ThrottleState {
+    /// Forget streaks whose cooldown ended more than `keep` ago: a market that is never
+    /// retried (an ended interval, a delisted pair) would otherwise stay in the map for good
+    fn prune_failures(&mut self, now: Instant, keep: Duration) {
+        self.failures.retain(|_, f| now.saturating_duration_since(f.until) <= keep);
+    }
+}
+
+/// Throttle state in a snapshot (wall-clock ms): failure streaks with their cooldown end,
+/// and each market's executions (the global window is their union)
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredThrottle {
+    failures: HashMap<String, (u32, i64)>,
+    executions: HashMap<String, Vec<i64>>,
+}
+
+/// Sliding-window execution throttle
+pub struct TradeThrottle {
+    config: ThrottleConfig,
+    state: Mutex<ThrottleState>,
+}
+
+impl TradeThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(ThrottleState::default()),
+        }
+    }
+
+    /// Check the limits and, if allowed, reserve an execution slot for `market`
+    pub fn try_acquire(&self, market: &str) -> Result<(), ThrottleReason> {
+        self.try_acquire_at(market, Instant::now())
+    }
+
+    /// Same as `try_acquire` with an explicit clock (for tests)
+    pub fn try_acquire_at(&self, market: &str, now: Instant) -> Result<(), ThrottleReason> {
+        let result = self.check_and_record(market, now);
+        match &result {
+            Ok(()) => metrics::incr("throttle.allowed"),
+            Err(ThrottleReason::GlobalRate { .. }) => metrics::incr("throttle.rejected.global"),
+            Err(ThrottleReason::MarketRate { .. }) => metrics::incr("throttle.rejected.market"),
+            Err(ThrottleReason::MinInterval { .. }) => metrics::incr("throttle.rejected.interval"),
+            Err(ThrottleReason::Cooldown { .. }) => metrics::incr("throttle.rejected.cooldown"),
+        }
+        result
+    }
+
+    /// How long a streak outlives its cooldown before it is forgotten (the longest cooldown)
+    fn streak_retention(&self) -> Duration {
+        Duration::from_secs(self.config.max_cooldown_secs.max(self.config.failure_cooldown_secs))
+    }
+
+    /// An order on `market` was rejected or errored: back off exponentially before the next try
+    pub fn record_failure(&self, market: &str) {
+        self.record_failure_at(market, Instant::now())
+    }
+
+    pub fn record_failure_at(&self, market: &str, now: Instant) {
+        metrics::incr("throttle.failures");
+        if self.config.failure_cooldown_secs == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.prune_failures(now, self.streak_retention());
+        let failure = state.failures.entry(market.to_string()).or_insert(FailureState { consecutive: 0, until: now });
+        failure.consecutive += 1;
+        let cooldown = self.config.failure_cooldown_secs
+            .saturating_mul(1u64 << (failure.consecutive - 1).min(20))
+            .min(self.config.max_cooldown_secs.max(self.config.failure_cooldown_secs));
+        failure.until = now + Duration::from_secs(cooldown);
+
+        if failure.consecutive == self.config.retry_storm_failures {
+            metrics::incr("throttle.retry_storm");
+            warn!("[THROTTLE] 🌪️ Retry storm on {}: {} consecutive failures - backing off {}s",
+                  market, failure.consecutive, cooldown);
+        }
+        metrics::set_gauge("throttle.cooling_markets", state.failures.values().filter(|f| f.until > now).count() as i64);
+    }
+
+    /// Stop trading `market` for the longest cooldown (venue says it's closed / not accepting orders)
+    pub fn suspend(&self, market: &str) {
+        self.suspend_at(market, Instant::now())
+    }
+
+    pub fn suspend_at(&self, market: &str, now: Instant) {
+        metrics::incr("throttle.suspended");
+        let mut state = self.state.lock().unwrap();
+        let failure = state.failures.entry(market.to_string()).or_insert(FailureState { consecutive: 0, until: now });
+        failure.consecutive += 1;
+        failure.until = now + Duration::from_secs(self.config.max_cooldown_secs);
+        metrics::set_gauge("throttle.cooling_markets", state.failures.values().filter(|f| f.until > now).count() as i64);
+    }
+
+    /// An order on `market` went through: clear its failure backoff
+    pub fn record_success(&self, market: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.failures.remove(market).is_some() {
+            let now = Instant::now();
+            metrics::set_gauge("throttle.cooling_markets", state.failures.values().filter(|f| f.until > now).count() as i64);
+        }
+    }
+
+    fn check_and_record(&self, market: &str, now: Instant) -> Result<(), ThrottleReason> {
+        let mut state = self.state.lock().unwrap();
+        let cutoff = now.checked_sub(WINDOW);
+        state.prune_failures(now, self.streak_retention());
+
+        // Backing off after failures (the streak is kept until a success clears it, or for
+        // the longest cooldown after its own ends)
+        if let Some(failure) = state.failures.get(market).filter(|f| now < f.until) {
+            return Err(ThrottleReason::Cooldown {
+                market: market.to_string(),
+                failures: failure.consecutive,
+                remaining_secs: failure.until.saturating_duration_since(now).as_secs(),
+            });
+        }
+
+        // Expire entries outside the window
+        let expired = |t: &Instant| cutoff.is_some_and(|c| *t <= c);
+        while state.global.front().is_some_and(expired) {
+            state.global.pop_front();
+        }
+        state.per_market.retain(|_, times| {
+            while times.front().is_some_and(expired) {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+
+        if self.config.max_per_minute > 0 && state.global.len() as u32 >= self.config.max_per_minute {
+            return Err(ThrottleReason::GlobalRate {
+                count: state.global.len() as u32,
+                limit: self.config.max_per_minute,
+            });
+        }
+
+        if let Some(times) = state.per_market.get(market) {
+            if self.config.max_per_market_per_minute > 0
+                && times.len() as u32 >= self.config.max_per_market_per_minute
+            {
+                return Err(ThrottleReason::MarketRate {
+                    market: market.to_string(),
+                    count: times.len() as u32,
+                    limit: self.config.max_per_market_per_minute,
+                });
+            }
+
+            if let Some(last) = times.back() {
+                let elapsed = now.saturating_duration_since(*last);
+                if elapsed < Duration::from_secs(self.config.min_interval_secs) {
+                    return Err(ThrottleReason::MinInterval {
+                        market: market.to_string(),
+                        elapsed_secs: elapsed.as_secs(),
+                        min_secs: self.config.min_interval_secs,
+                    });
+                }
+            }
+        }
+
+        state.global.push_back(now);
+        state.per_market.entry(market.to_string()).or_default().push_back(now);
+        Ok(())
+    }
+
+    fn snapshot_at(&self, now: Instant, now_ms: i64) -> StoredThrottle {
+        let state = self.state.lock().unwrap();
+        let ms = |t: &Instant| snapshots::to_unix_ms(*t, now, now_ms);
+        StoredThrottle {
+            failures: state.failures.iter().map(|(m, f)| (m.clone(), (f.consecutive, ms(&f.until)))).collect(),
+            executions: state.per_market.iter().map(|(m, times)| (m.clone(), times.iter().map(ms).collect())).collect(),
+        }
+    }
+
+    fn restore_at(&self, stored: StoredThrottle, now: Instant, now_ms: i64) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let instant = |ms: i64| snapshots::from_unix_ms(ms, now, now_ms);
+        let restored = stored.failures.len() + stored.executions.len();
+        for (market, (consecutive, until)) in stored.failures {
+            let until = instant(until).unwrap_or(now);
+            state.failures.entry(market).or_insert(FailureState { consecutive, until });
+        }
+        for (market, times) in stored.executions {
+            let mut times: Vec<Instant> = times.into_iter().filter_map(instant).filter(|t| *t <= now).collect();
+            times.sort();
+            state.global.extend(&times);
+            state.per_market.entry(market).or_default().extend(times);
+        }
+        state.global.make_contiguous().sort();
+        metrics::set_gauge("throttle.cooling_markets", state.failures.values().filter(|f| f.until > now).count() as i64);
+        restored
+    }
+}
+
+/// Cooldowns, suspensions and the rate windows survive a restart
+impl StrategyState for TradeThrottle {
+    fn key(&self) -> &'static str {
+        "throttle"
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        let stored = self.snapshot_at(Instant::now(), chrono::Utc::now().timestamp_millis());
+        serde_json::to_value(stored).unwrap_or_default()
+    }
+
+    fn restore(&self, state: serde_json::Value) -> Result<usize> {
+        let stored: StoredThrottle = serde_json::from_value(state)?;
+        Ok(self.restore_at(stored, Instant::now(), chrono::Utc::now().timestamp_millis()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(global: u32, per_market: u32, interval: u64) -> TradeThrottle {
+        TradeThrottle::new(ThrottleConfig {
+            max_per_minute: global,
+            max_per_market_per_minute: per_market,
+            min_interval_secs: interval,
+            failure_cooldown_secs: 2,
+            max_cooldown_secs: 30,
+            retry_storm_failures: 3,
+        })
+    }
+
+    #[test]
+    fn test_min_interval_per_market() {
+        let t = throttle(0, 0, 5);
+        let now = Instant::now();
+
+        assert!(t.try_acquire_at("m1", now).is_ok());
+        assert!(matches!(
+            t.try_acquire_at("m1", now + Duration::from_secs(2)),
+            Err(ThrottleReason::MinInterval { .. })
+        ));
+        // Other markets are unaffected
+        assert!(t.try_acquire_at("m2", now + Duration::from_secs(2)).is_ok());
+        assert!(t.try_acquire_at("m1", now + Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn test_per_market_and_global_rate() {
+        let t = throttle(3, 2, 0);
+        let now = Instant::now();
+
+        assert!(t.try_acquire_at("m1", now).is_ok());
+        assert!(t.try_acquire_at("m1", now).is_ok());
+        assert!(matches!(t.try_acquire_at("m1", now), Err(ThrottleReason::MarketRate { .. })));
+
+        assert!(t.try_acquire_at("m2", now).is_ok());
+        assert!(matches!(t.try_acquire_at("m3", now), Err(ThrottleReason::GlobalRate { .. })));
+
+        // Window slides after a minute
+        assert!(t.try_acquire_at("m3", now + Duration::from_secs(61)).is_ok());
+    }
+
+    #[test]
+    fn test_failure_cooldown_backs_off_exponentially() {
+        let t = throttle(0, 0, 0);
+        let now = Instant::now();
+        let at = |secs: u64| now + Duration::from_secs(secs);
+
+        t.record_failure_at("m1", now);
+        assert!(matches!(t.try_acquire_at("m1", at(1)), Err(ThrottleReason::Cooldown { failures: 1, .. })));
+        assert!(t.try_acquire_at("m2", at(1)).is_ok());
+        assert!(t.try_acquire_at("m1", at(2)).is_ok());
+
+        // 2s → 4s → 8s → ... capped at 30s
+        t.record_failure_at("m1", at(2));
+        assert!(t.try_acquire_at("m1", at(5)).is_err());
+        assert!(t.try_acquire_at("m1", at(6)).is_ok());
+        for _ in 0..5 {
+            t.record_failure_at("m1", at(6));
+        }
+        assert!(t.try_acquire_at("m1", at(35)).is_err());
+        assert!(t.try_acquire_at("m1", at(36)).is_ok());
+
+        // A success clears the streak
+        t.record_failure_at("m1", at(36));
+        t.record_success("m1");
+        assert!(t.try_acquire_at("m1", at(36)).is_ok());
+    }
+
+    #[test]
+    fn test_stale_failure_streaks_are_pruned() {
+        let t = throttle(0, 0, 0);
+        let now = Instant::now();
+        let at = |secs: u64| now + Duration::from_secs(secs);
+
+        // Cooldown ends at 2s; the streak survives a retry within the next 30s
+        t.record_failure_at("m1", now);
+        t.record_failure_at("m2", now);
+        assert!(t.try_acquire_at("m2", at(20)).is_ok());
+        t.record_failure_at("m2", at(20));
+        assert!(matches!(t.try_acquire_at("m2", at(23)), Err(ThrottleReason::Cooldown { failures: 2, .. })));
+
+        // m1 was never retried: forgotten once its cooldown is 30s past
+        assert!(t.try_acquire_at("m3", at(33)).is_ok());
+        let state = t.state.lock().unwrap();
+        assert!(!state.failures.contains_key("m1"));
+        assert!(state.failures.contains_key("m2"));
+    }
+
+    #[test]
+    fn test_snapshot_carries_cooldowns_and_windows() {
+        let t = throttle(0, 0, 5);
+        let now = Instant::now();
+        t.suspend_at("closed", now);
+        assert!(t.try_acquire_at("m1", now).is_ok());
+        let stored = t.snapshot_at(now, 1_000_000);
+
+        // Restarted 10s later: 20s of the suspension and none of the interval are left
+        let restarted = throttle(0, 0, 5);
+        let later = now + Duration::from_secs(10);
+        assert_eq!(restarted.restore_at(stored, later, 1_010_000), 2);
+        assert!(matches!(restarted.try_acquire_at("closed", later), Err(ThrottleReason::Cooldown { remaining_secs: 20, .. })));
+        assert!(restarted.try_acquire_at("closed", later + Duration::from_secs(20)).is_ok());
+        assert!(restarted.try_acquire_at("m1", later).is_ok());
+    }
+
+    #[test]
+    fn test_suspend_uses_max_cooldown() {
+        let t = throttle(0, 0, 0);
+        let now = Instant::now();
+        t.suspend_at("closed", now);
+        assert!(t.try_acquire_at("closed", now + Duration::from_secs(29)).is_err());
+        assert!(t.try_acquire_at("closed", now + Duration::from_secs(30)).is_ok());
+    }
+}
@@ -0,0 +1,220 @@
+// src/trade_tape.rs
+// Last-trade prints from the Polymarket market channel (realized prices, trade-based marks)
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Prints kept per token for the rolling VWAP
+const MAX_PRINTS_PER_TOKEN: usize = 256;
+
+/// Aggressor side of a print (Polymarket reports the taker's side)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TakerSide {
+    Buy,
+    Sell,
+}
+
+impl TakerSide {
+    pub fn parse(side: &str) -> Option<Self> {
+        match side.to_ascii_uppercase().as_str() {
+            "BUY" => Some(TakerSide::Buy),
+            "SELL" => Some(TakerSide::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// One executed trade
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradePrint {
+    pub price: f64,
+    pub size: f64,
+    pub side: Option<TakerSide>,
+    /// Exchange timestamp (ms since epoch)
+    pub ts_ms: u64,
+}
+
+/// True when a print happened through our cached quote - a buy above our best ask or a
+/// sell below our best bid means the cached book is stale (or was swept between updates).
+pub fn crosses_quote(print: &TradePrint, best_bid: Option<f64>, best_ask: Option<f64>) -> bool {
+    const EPS: f64 = 1e-9;
+    match print.side {
+        Some(TakerSide::Buy) => best_ask.is_some_and(|ask| ask > 0.0 && print.price > ask + EPS),
+        Some(TakerSide::Sell) => best_bid.is_some_and(|bid| bid > 0.0 && print.price < bid - EPS),
+        None => false,
+    }
+}
+
+/// Trade tape configuration from environment
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct TradeTapeConfig {
+    /// Window for the realized VWAP (ms)
+    pub vwap_window_ms: u64,
+
+    /// A book is "illiquid" when a side is missing or the spread is wider than this (dollars)
+    pub max_mark_spread: f64,
+
+    /// Last trade older than this is not used as a mark (ms)
+    pub max_trade_age_ms: u64,
+}
+
+impl TradeTapeConfig {
+    pub fn from_env() -> Self {
+        Self {
+            vwap_window_ms: std::env::var("TRADE_VWAP_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60) * 1000,
+
+            max_mark_spread: std::env::var("MARK_MAX_SPREAD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.10),
+
+            max_trade_age_ms: std::env::var("MARK_MAX_TRADE_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(300) * 1000,
+        }
+    }
+}
+
+/// Where a mark came from
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkSource {
+    /// Mid of a two-sided, tight book
+    Mid,
+    /// Last trade (book one-sided or too wide)
+    LastTrade,
+    /// Whatever single quote was available
+    Quote,
+}
+
+/// Per-token last trades, shared by the WS feed (writer) and mark-to-market (reader)
+pub struct TradeTape {
+    #[allow(dead_code)]
+    config: TradeTapeConfig,
+    prints: Mutex<HashMap<String, VecDeque<TradePrint>>>,
+}
+
+impl TradeTape {
+    pub fn new(config: TradeTapeConfig) -> Self {
+        Self {
+            config,
+            prints: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a print (out-of-order prints older than the latest are dropped)
+    pub fn record(&self, asset_id: &str, print: TradePrint) {
+        let mut prints = self.prints.lock().unwrap();
+        let tape = prints.entry(asset_id.to_string()).or_default();
+        if tape.back().is_some_and(|last| print.ts_ms < last.ts_ms) {
+            return;
+        }
+        if tape.len() == MAX_PRINTS_PER_TOKEN {
+            tape.pop_front();
+        }
+        tape.push_back(print);
+    }
+}
+
+#[allow(dead_code)]
+impl TradeTape {
+    pub fn last_trade(&self, asset_id: &str) -> Option<TradePrint> {
+        self.prints.lock().unwrap().get(asset_id).and_then(|t| t.back().copied())
+    }
+
+    /// Volume-weighted traded price over the window ending at the latest print
+    pub fn vwap(&self, asset_id: &str) -> Option<f64> {
+        let prints = self.prints.lock().unwrap();
+        let tape = prints.get(asset_id)?;
+        let cutoff = tape.back()?.ts_ms.saturating_sub(self.config.vwap_window_ms);
+        let (notional, volume) = tape.iter()
+            .filter(|p| p.ts_ms >= cutoff)
+            .fold((0.0, 0.0), |(n, v), p| (n + p.price * p.size, v + p.size));
+        if volume > 0.0 { Some(notional / volume) } else { None }
+    }
+
+    /// Mark price for a token: the mid when the book is two-sided and tight, otherwise the
+    /// last trade (if recent), otherwise whichever quote exists.
+    pub fn mark(&self, asset_id: &str, best_bid: Option<f64>, best_ask: Option<f64>, now_ms: u64) -> Option<(f64, MarkSource)> {
+        let bid = best_bid.filter(|p| *p > 0.0);
+        let ask = best_ask.filter(|p| *p > 0.0);
+        if let (Some(bid), Some(ask)) = (bid, ask) {
+            if ask - bid <= self.config.max_mark_spread {
+                return Some(((bid + ask) / 2.0, MarkSource::Mid));
+            }
+        }
+
+        if let Some(trade) = self.last_trade(asset_id) {
+            if now_ms.saturating_sub(trade.ts_ms) <= self.config.max_trade_age_ms {
+                return Some((trade.price, MarkSource::LastTrade));
+            }
+        }
+
+        match (bid, ask) {
+            (Some(bid), Some(ask)) => Some(((bid + ask) / 2.0, MarkSource::Quote)),
+            (Some(p), None) | (None, Some(p)) => Some((p, MarkSource::Quote)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Process-wide tape (configured from env on first use)
+pub fn tape() -> &'static TradeTape {
+    static TAPE: OnceLock<TradeTape> = OnceLock::new();
+    TAPE.get_or_init(|| TradeTape::new(TradeTapeConfig::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tape() -> TradeTape {
+        TradeTape::new(TradeTapeConfig { vwap_window_ms: 10_000, max_mark_spread: 0.10, max_trade_age_ms: 60_000 })
+    }
+
+    fn print(price: f64, size: f64, ts_ms: u64) -> TradePrint {
+        TradePrint { price, size, side: Some(TakerSide::Buy), ts_ms }
+    }
+
+    #[test]
+    fn test_vwap_window_and_ordering() {
+        let t = tape();
+        t.record("tok", print(0.40, 100.0, 1_000));
+        t.record("tok", print(0.50, 10.0, 20_000));
+        t.record("tok", print(0.60, 30.0, 25_000));
+        t.record("tok", print(0.99, 1.0, 5_000)); // stale, dropped
+
+        // First print is outside the 10s window
+        let vwap = t.vwap("tok").unwrap();
+        assert!((vwap - (0.50 * 10.0 + 0.60 * 30.0) / 40.0).abs() < 1e-9);
+        assert_eq!(t.last_trade("tok").unwrap().price, 0.60);
+    }
+
+    #[test]
+    fn test_mark_falls_back_to_last_trade_when_illiquid() {
+        let t = tape();
+        assert_eq!(t.mark("tok", Some(0.48), Some(0.52), 0), Some((0.50, MarkSource::Mid)));
+        assert_eq!(t.mark("tok", None, Some(0.90), 0), Some((0.90, MarkSource::Quote)));
+
+        t.record("tok", print(0.55, 5.0, 1_000));
+        assert_eq!(t.mark("tok", None, Some(0.90), 2_000), Some((0.55, MarkSource::LastTrade)));
+        assert_eq!(t.mark("tok", Some(0.20), Some(0.90), 2_000), Some((0.55, MarkSource::LastTrade)));
+        // Too old to trust
+        assert_eq!(t.mark("tok", None, Some(0.90), 100_000), Some((0.90, MarkSource::Quote)));
+    }
+
+    #[test]
+    fn test_crosses_quote() {
+        let buy = print(0.53, 1.0, 0);
+        assert!(crosses_quote(&buy, None, Some(0.52)));
+        assert!(!crosses_quote(&buy, None, Some(0.53)));
+        let sell = TradePrint { side: Some(TakerSide::Sell), ..print(0.47, 1.0, 0) };
+        assert!(crosses_quote(&sell, Some(0.48), None));
+        assert!(!crosses_quote(&sell, None, Some(0.40)));
+    }
+}
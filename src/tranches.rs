@@ -0,0 +1,134 @@
+// src/tranches.rs
+// Follow-up tranches - when one side's depth capped an arb, take more as the thin book refills
+
+use crate::types::{FastExecutionRequest, PriceCents, SizeCents};
+
+/// Tranche configuration from environment
+#[derive(Debug, Clone)]
+pub struct TrancheConfig {
+    /// Follow-up tranches after the first fill (0 = off)
+    pub max_tranches: u32,
+
+    /// How long after the first tranche the thin side may refill
+    pub window_ms: u64,
+
+    /// How often the book is re-checked during the window
+    pub poll_ms: u64,
+}
+
+impl TrancheConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_tranches: std::env::var("TRANCHE_MAX")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+
+            window_ms: std::env::var("TRANCHE_WINDOW_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2000),
+
+            poll_ms: std::env::var("TRANCHE_POLL_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_tranches > 0 && self.window_ms > 0
+    }
+}
+
+/// Depth left on the deep side of an arb whose size the thin side capped
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranchePlan {
+    /// Prices of the first tranche; later tranches never pay more on either leg
+    pub yes_limit: PriceCents,
+    pub no_limit: PriceCents,
+    /// Deep-side contracts not yet matched
+    pub remaining: i64,
+    pub tranches_left: u32,
+}
+
+impl TranchePlan {
+    /// Plan for `first` if one side had at least a contract more depth than the other
+    pub fn new(first: &FastExecutionRequest, max_tranches: u32) -> Option<Self> {
+        let (thin, deep) = (first.yes_size.min(first.no_size), first.yes_size.max(first.no_size));
+        let remaining = contracts(deep) - contracts(thin);
+        if max_tranches == 0 || remaining < 1 {
+            return None;
+        }
+        Some(Self {
+            yes_limit: first.yes_price,
+            no_limit: first.no_price,
+            remaining,
+            tranches_left: max_tranches,
+        })
+    }
+
+    /// Contracts for the next tranche against the current book: None while either ask
+    /// is above the first tranche's price or the thin side hasn't refilled
+    pub fn next(&self, yes_price: PriceCents, no_price: PriceCents, yes_size: SizeCents, no_size: SizeCents) -> Option<i64> {
+        if self.tranches_left == 0 || yes_price == 0 || no_price == 0
+            || yes_price > self.yes_limit || no_price > self.no_limit {
+            return None;
+        }
+        let size = contracts(yes_size.min(no_size)).min(self.remaining);
+        (size >= 1).then_some(size)
+    }
+
+    /// A tranche of `matched` contracts went out
+    pub fn record(&mut self, matched: i64) {
+        self.remaining -= matched.max(0);
+        self.tranches_left = self.tranches_left.saturating_sub(1);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.tranches_left == 0 || self.remaining < 1
+    }
+}
+
+/// Whole contracts in a book size (sizes are contracts × 100)
+fn contracts(size: SizeCents) -> i64 {
+    (size / 100) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ArbType;
+
+    fn req(yes_size: SizeCents, no_size: SizeCents) -> FastExecutionRequest {
+        FastExecutionRequest {
+            market_id: 0, yes_price: 40, no_price: 55, yes_size, no_size,
+            arb_type: ArbType::PolyOnly, detected_ns: 0, poly_fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_plan_only_for_one_sided_depth() {
+        // 400 YES vs 30 NO: first tranche takes 30, 370 left on YES
+        let plan = TranchePlan::new(&req(40_000, 3_000), 2).unwrap();
+        assert_eq!(plan.remaining, 370);
+        assert!(TranchePlan::new(&req(3_000, 3_050), 2).is_none());
+        assert!(TranchePlan::new(&req(40_000, 3_000), 0).is_none());
+    }
+
+    #[test]
+    fn test_next_tranche_waits_for_refill_at_same_prices() {
+        let mut plan = TranchePlan::new(&req(40_000, 3_000), 2).unwrap();
+        // NO book still empty, or price moved up
+        assert_eq!(plan.next(40, 55, 37_000, 50), None);
+        assert_eq!(plan.next(40, 56, 37_000, 5_000), None);
+        // Refilled (cheaper is fine)
+        assert_eq!(plan.next(40, 54, 37_000, 5_000), Some(50));
+
+        plan.record(50);
+        assert_eq!(plan.next(40, 55, 32_000, 50_000), Some(320));
+        plan.record(320);
+        assert!(plan.is_done());
+        assert_eq!(plan.next(40, 55, 32_000, 50_000), None);
+    }
+}
@@ -0,0 +1,233 @@
+// src/treasury.rs
+// Treasury guard - destination allowlist and two-step confirmation for every outbound fund transfer
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::metrics;
+#[cfg(feature = "onchain")]
+use crate::onchain::{self, ContractCall};
+use crate::storage;
+
+/// Treasury configuration from environment
+#[derive(Debug, Clone)]
+pub struct TreasuryConfig {
+    /// Addresses funds may be sent to (TREASURY_ALLOWLIST, comma-separated; empty = no transfers)
+    pub allowlist: Vec<String>,
+
+    /// Transfers above this (dollars) need a second, confirmed step
+    pub confirm_above: f64,
+
+    /// Seconds a confirmation code stays valid
+    pub confirm_ttl_secs: u64,
+
+    /// Confirmation codes waiting for their second step (under DATA_DIR)
+    pub pending_file: PathBuf,
+}
+
+impl TreasuryConfig {
+    pub fn from_env() -> Self {
+        Self {
+            allowlist: std::env::var("TREASURY_ALLOWLIST")
+                .ok()
+                .map(|v| v.split(',').map(normalize).filter(|a| !a.is_empty()).collect())
+                .unwrap_or_default(),
+
+            confirm_above: std::env::var("TREASURY_CONFIRM_ABOVE_USD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(500.0),
+
+            confirm_ttl_secs: std::env::var("TREASURY_CONFIRM_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(600),
+
+            pending_file: storage::data_path(std::env::var("TREASURY_PENDING_FILE")
+                .unwrap_or_else(|_| "treasury_pending.json".to_string())),
+        }
+    }
+
+    pub fn is_allowed(&self, to: &str) -> bool {
+        self.allowlist.contains(&normalize(to))
+    }
+}
+
+/// Addresses compare case-insensitively (EIP-55 checksums are only a casing)
+fn normalize(address: &str) -> String {
+    address.trim().to_lowercase()
+}
+
+/// A large transfer that was requested once and waits for its confirmation code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Pending {
+    code: String,
+    to: String,
+    amount_cents: i64,
+    expires_at: i64,
+}
+
+/// Outcome of asking to send funds
+#[derive(Debug, Clone, PartialEq)]
+pub enum Approval {
+    Approved,
+    /// Above the confirmation threshold: repeat the request with this code before `expires_at`
+    Confirm { code: String, expires_at: i64 },
+}
+
+/// Check a transfer of `amount` dollars to `to`. Non-allowlisted destinations are refused.
+/// Amounts above `confirm_above` are approved only with a code issued for the same
+/// destination and amount on an earlier request (codes are single-use and expire).
+fn authorize_at(config: &TreasuryConfig, pending: &mut Vec<Pending>, to: &str, amount: f64,
+                code: Option<&str>, now: i64) -> Result<Approval> {
+    if !amount.is_finite() || amount <= 0.0 {
+        bail!("transfer amount must be positive, got {}", amount);
+    }
+    if !config.is_allowed(to) {
+        bail!("destination {} is not in TREASURY_ALLOWLIST", to);
+    }
+    if amount <= config.confirm_above {
+        return Ok(Approval::Approved);
+    }
+
+    pending.retain(|p| p.expires_at > now);
+    let (to, amount_cents) = (normalize(to), (amount * 100.0).round() as i64);
+    if let Some(code) = code {
+        let found = pending.iter().position(|p| p.code == code && p.to == to && p.amount_cents == amount_cents);
+        match found {
+            Some(i) => {
+                pending.remove(i);
+                return Ok(Approval::Approved);
+            }
+            None => bail!("confirmation code {} doesn't match a pending ${:.2} transfer to {} (expired or different amount?)",
+                          code, amount, to),
+        }
+    }
+    let code = format!("{:06x}", rand::random::<u32>() & 0xff_ffff);
+    let expires_at = now + config.confirm_ttl_secs as i64;
+    pending.push(Pending { code: code.clone(), to, amount_cents, expires_at });
+    Ok(Approval::Confirm { code, expires_at })
+}
+
+fn pending_amount_at(pending: &[Pending], to: &str, code: &str, now: i64) -> Option<f64> {
+    pending.iter()
+        .find(|p| p.code == code && p.to == normalize(to) && p.expires_at > now)
+        .map(|p| p.amount_cents as f64 / 100.0)
+}
+
+fn load_pending(path: &Path) -> Result<Vec<Pending>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).with_context(|| format!("parsing {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+/// Check a transfer against the allowlist and confirmation rules (pending codes persist
+/// across runs, so the second step can come from a separate invocation)
+pub fn authorize(config: &TreasuryConfig, to: &str, amount: f64, code: Option<&str>) -> Result<Approval> {
+    let mut pending = load_pending(&config.pending_file)?;
+    let result = authorize_at(config, &mut pending, to, amount, code, chrono::Utc::now().timestamp());
+    storage::write_atomic(&config.pending_file, serde_json::to_string_pretty(&pending)?.as_bytes())?;
+    match &result {
+        Ok(Approval::Approved) => {
+            metrics::incr("treasury.approved");
+            info!("[TREASURY] ✅ ${:.2} to {} approved", amount, to);
+        }
+        Ok(Approval::Confirm { code, .. }) => {
+            metrics::incr("treasury.pending");
+            warn!("[TREASURY] ${:.2} to {} is above ${:.2} - confirm with code {} within {}s",
+                  amount, to, config.confirm_above, code, config.confirm_ttl_secs);
+        }
+        Err(e) => {
+            metrics::incr("treasury.blocked");
+            warn!("[TREASURY] 🚫 ${:.2} to {} blocked: {}", amount, to, e);
+        }
+    }
+    result
+}
+
+/// Amount (dollars, whole cents) of the unexpired transfer to `to` that `code` was issued for.
+/// The confirming run sends exactly this instead of recomputing it from live balances.
+pub fn pending_amount(config: &TreasuryConfig, to: &str, code: &str) -> Result<Option<f64>> {
+    let pending = load_pending(&config.pending_file)?;
+    Ok(pending_amount_at(&pending, to, code, chrono::Utc::now().timestamp()))
+}
+
+/// A USDC transfer ready to submit, or the code its second step needs
+#[cfg(feature = "onchain")]
+#[derive(Debug, Clone)]
+pub enum Withdrawal {
+    Ready(ContractCall),
+    NeedsConfirmation { code: String, expires_at: i64 },
+}
+
+/// The only way to build a USDC transfer out of the funder wallet
+#[cfg(feature = "onchain")]
+pub fn usdc_transfer(config: &TreasuryConfig, to: &str, amount: f64, code: Option<&str>) -> Result<Withdrawal> {
+    Ok(match authorize(config, to, amount, code)? {
+        Approval::Approved => Withdrawal::Ready(onchain::transfer_usdc(to, amount)?),
+        Approval::Confirm { code, expires_at } => Withdrawal::NeedsConfirmation { code, expires_at },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPOSIT: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+
+    fn config() -> TreasuryConfig {
+        TreasuryConfig {
+            allowlist: vec![normalize(DEPOSIT)],
+            confirm_above: 500.0,
+            confirm_ttl_secs: 600,
+            pending_file: PathBuf::from("unused.json"),
+        }
+    }
+
+    #[test]
+    fn test_only_allowlisted_destinations() {
+        let cfg = config();
+        let mut pending = Vec::new();
+        assert_eq!(authorize_at(&cfg, &mut pending, &DEPOSIT.to_lowercase(), 100.0, None, 0).unwrap(), Approval::Approved);
+        assert!(authorize_at(&cfg, &mut pending, "0x000000000000000000000000000000000000dEaD", 1.0, None, 0).is_err());
+        assert!(authorize_at(&cfg, &mut pending, DEPOSIT, 0.0, None, 0).is_err());
+        // Empty allowlist: nothing can be sent
+        let locked = TreasuryConfig { allowlist: Vec::new(), ..config() };
+        assert!(authorize_at(&locked, &mut pending, DEPOSIT, 1.0, None, 0).is_err());
+    }
+
+    #[test]
+    fn test_large_transfers_need_matching_code() {
+        let cfg = config();
+        let mut pending = Vec::new();
+        let Approval::Confirm { code, expires_at } = authorize_at(&cfg, &mut pending, DEPOSIT, 900.0, None, 1_000).unwrap() else {
+            panic!("expected a confirmation step");
+        };
+        assert_eq!(expires_at, 1_600);
+        // The confirming run reads back the amount the code was issued for
+        assert_eq!(pending_amount_at(&pending, &DEPOSIT.to_lowercase(), &code, 1_100), Some(900.0));
+        assert_eq!(pending_amount_at(&pending, DEPOSIT, &code, 1_600), None);
+        assert_eq!(pending_amount_at(&pending, DEPOSIT, "zzzzzz", 1_100), None);
+        // Wrong amount, wrong code
+        assert!(authorize_at(&cfg, &mut pending, DEPOSIT, 950.0, Some(&code), 1_100).is_err());
+        assert!(authorize_at(&cfg, &mut pending, DEPOSIT, 900.0, Some("zzzzzz"), 1_100).is_err());
+        assert_eq!(authorize_at(&cfg, &mut pending, DEPOSIT, 900.0, Some(&code), 1_100).unwrap(), Approval::Approved);
+        // Single use
+        assert!(authorize_at(&cfg, &mut pending, DEPOSIT, 900.0, Some(&code), 1_200).is_err());
+    }
+
+    #[test]
+    fn test_codes_expire() {
+        let cfg = config();
+        let mut pending = Vec::new();
+        let Approval::Confirm { code, .. } = authorize_at(&cfg, &mut pending, DEPOSIT, 900.0, None, 1_000).unwrap() else {
+            panic!("expected a confirmation step");
+        };
+        assert!(authorize_at(&cfg, &mut pending, DEPOSIT, 900.0, Some(&code), 1_600).is_err());
+        assert!(pending.is_empty());
+    }
+}
@@ -2,10 +2,13 @@
 // Shared data structures
 
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU16, AtomicU64, Ordering};
 use std::sync::Arc;
 use rustc_hash::FxHashMap;
 
+use crate::arb_confirm::ArbPersistence;
+use crate::overrides;
+
 // === Market Types ===
 
 /// Market type for a matched pair
@@ -42,6 +45,15 @@ pub struct MarketPair {
     pub poly_no_token: Arc<str>,
     pub line_value: Option<f64>,
     pub team_suffix: Option<Arc<str>>,
+    /// Expected settlement (Unix seconds, from Kalshi) - capital is locked until then
+    #[serde(default)]
+    pub settles_at: Option<i64>,
+    /// Polymarket market end (Unix seconds, from Gamma) - the other leg's capital is locked until then
+    #[serde(default)]
+    pub poly_ends_at: Option<i64>,
+    /// Settlement-time / reference-source mismatch score between the venues (see basis_risk)
+    #[serde(default)]
+    pub basis_risk: f64,
 }
 
 /// Price in cents (1-99 for 0.01-0.99), 0 = no price available
@@ -139,6 +151,10 @@ pub struct AtomicMarketState {
     pub pair: Option<Arc<MarketPair>>,
     /// Market ID for lookups
     pub market_id: u16,
+    /// How long the current arb has persisted (time-weighted confirmation)
+    pub persistence: ArbPersistence,
+    /// Polymarket taker fee rate (bps), prefetched at discovery
+    poly_fee_bps: AtomicU16,
 }
 
 impl AtomicMarketState {
@@ -148,9 +164,32 @@ impl AtomicMarketState {
             poly: AtomicOrderbook::new(),
             pair: None,
             market_id,
+            persistence: ArbPersistence::new(),
+            poly_fee_bps: AtomicU16::new(0),
         }
     }
 
+    #[inline(always)]
+    pub fn poly_fee_bps(&self) -> u16 {
+        self.poly_fee_bps.load(Ordering::Relaxed)
+    }
+
+    pub fn set_poly_fee_bps(&self, fee_rate_bps: u32) {
+        self.poly_fee_bps.store(fee_rate_bps.min(10_000) as u16, Ordering::Relaxed);
+    }
+
+    /// Arb threshold for this market: its OVERRIDES_FILE threshold (by Polymarket slug, then
+    /// Kalshi ticker), else `default`
+    pub fn threshold_cents(&self, default: PriceCents) -> PriceCents {
+        let Some(pair) = &self.pair else {
+            return default;
+        };
+        let overrides = overrides::current();
+        overrides.threshold(&pair.poly_slug)
+            .or_else(|| overrides.threshold(&pair.kalshi_market_ticker))
+            .map_or(default, |t| ((t * 100.0).round() as PriceCents).max(1))
+    }
+
     #[inline(always)]
     pub fn check_arbs(&self, threshold_cents: PriceCents) -> u8 {
         use wide::{i16x8, CmpLt};
@@ -164,11 +203,13 @@ impl AtomicMarketState {
 
         let k_yes_fee = KALSHI_FEE_TABLE[k_yes as usize];
         let k_no_fee = KALSHI_FEE_TABLE[k_no as usize];
+        let fee_bps = self.poly_fee_bps();
+        let (p_yes_fee, p_no_fee) = (poly_fee_cents(p_yes, fee_bps), poly_fee_cents(p_no, fee_bps));
 
         let costs = i16x8::new([
-            (p_yes + k_no + k_no_fee) as i16,
-            (k_yes + k_yes_fee + p_no) as i16,
-            (p_yes + p_no) as i16,
+            (p_yes + p_yes_fee + k_no + k_no_fee) as i16,
+            (k_yes + k_yes_fee + p_no + p_no_fee) as i16,
+            (p_yes + p_yes_fee + p_no + p_no_fee) as i16,
             (k_yes + k_yes_fee + k_no + k_no_fee) as i16,
             i16::MAX, i16::MAX, i16::MAX, i16::MAX,
         ]);
@@ -209,6 +250,24 @@ pub fn kalshi_fee_cents(price_cents: PriceCents) -> PriceCents {
     KALSHI_FEE_TABLE[price_cents as usize]
 }
 
+/// Polymarket taker fee in dollars for a single contract bought or sold at `price`
+/// Formula (CTF exchange): fee_rate_bps / 10000 × min(P, 1-P)
+#[inline(always)]
+#[allow(dead_code)]
+pub fn poly_fee(price: f64, fee_rate_bps: u32) -> f64 {
+    fee_rate_bps as f64 / 10_000.0 * price.min(1.0 - price).max(0.0)
+}
+
+/// Polymarket taker fee in whole cents for a single contract (rounded up, like Kalshi's)
+#[inline(always)]
+pub fn poly_fee_cents(price_cents: PriceCents, fee_rate_bps: u16) -> PriceCents {
+    if price_cents >= 100 || fee_rate_bps == 0 {
+        return 0;
+    }
+    let edge = price_cents.min(100 - price_cents) as u32;
+    ((fee_rate_bps as u32 * edge).div_ceil(10_000)) as PriceCents
+}
+
 /// Convert f64 price (0.01-0.99) to PriceCents (1-99)
 #[inline(always)]
 pub fn price_to_cents(price: f64) -> PriceCents {
@@ -243,7 +302,7 @@ pub fn parse_price(s: &str) -> PriceCents {
     }
     // Fallback to standard parse
     s.parse::<f64>()
-        .map(|p| price_to_cents(p))
+        .map(price_to_cents)
         .unwrap_or(0)
 }
 
@@ -277,6 +336,8 @@ pub struct FastExecutionRequest {
     pub arb_type: ArbType,
     /// Detection timestamp (nanoseconds since start)
     pub detected_ns: u64,
+    /// Polymarket taker fee rate (bps) of the market at detection
+    pub poly_fee_bps: u16,
 }
 
 impl FastExecutionRequest {
@@ -287,12 +348,13 @@ impl FastExecutionRequest {
 
     #[inline(always)]
     pub fn estimated_fee_cents(&self) -> PriceCents {
+        let poly = |price| poly_fee_cents(price, self.poly_fee_bps);
         match self.arb_type {
-            // Cross-platform: fee on the Kalshi side only
-            ArbType::PolyYesKalshiNo => kalshi_fee_cents(self.no_price),
-            ArbType::KalshiYesPolyNo => kalshi_fee_cents(self.yes_price),
-            // Poly-only: no fees
-            ArbType::PolyOnly => 0,
+            // Cross-platform: Kalshi's fee plus Polymarket's taker fee (0 on fee-free markets)
+            ArbType::PolyYesKalshiNo => poly(self.yes_price) + kalshi_fee_cents(self.no_price),
+            ArbType::KalshiYesPolyNo => kalshi_fee_cents(self.yes_price) + poly(self.no_price),
+            // Poly-only: the taker fee on both legs
+            ArbType::PolyOnly => poly(self.yes_price) + poly(self.no_price),
             // Kalshi-only: fees on both sides
             ArbType::KalshiOnly => kalshi_fee_cents(self.yes_price) + kalshi_fee_cents(self.no_price),
         }
@@ -628,6 +690,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_poly_fee_symmetric_around_half() {
+        assert_eq!(poly_fee(0.40, 0), 0.0);
+        // 10% fee rate: charged on the cheaper of P and 1-P
+        assert!((poly_fee(0.50, 1000) - 0.05).abs() < 1e-12);
+        assert!((poly_fee(0.20, 1000) - poly_fee(0.80, 1000)).abs() < 1e-12);
+        assert!((poly_fee(0.20, 1000) - 0.02).abs() < 1e-12);
+        assert_eq!(poly_fee(1.0, 1000), 0.0);
+    }
+
     // =========================================================================
     // Price Conversion Tests
     // =========================================================================
@@ -769,6 +841,31 @@ mod tests {
         assert!(mask & 1 == 0, "Fees should eliminate marginal arb");
     }
 
+    #[test]
+    fn test_check_arbs_includes_poly_fee() {
+        // Poly YES 45¢ + Kalshi NO 50¢ + 2¢ Kalshi fee = 97¢: an arb on a fee-free market
+        let state = make_market_state(55, 50, 45, 60);
+        assert!(state.check_arbs(99) & 1 != 0);
+
+        // At 1000bps Polymarket charges ceil(10% × 45¢) = 5¢ more: 102¢
+        state.set_poly_fee_bps(1000);
+        assert_eq!(poly_fee_cents(45, 1000), 5);
+        assert!(state.check_arbs(99) & 1 == 0);
+
+        let req = FastExecutionRequest {
+            market_id: 0,
+            yes_price: 45,
+            no_price: 50,
+            yes_size: 1000,
+            no_size: 1000,
+            arb_type: ArbType::PolyYesKalshiNo,
+            detected_ns: 0,
+            poly_fee_bps: state.poly_fee_bps(),
+        };
+        assert_eq!(req.estimated_fee_cents(), 7);
+        assert_eq!(req.profit_cents(), -2);
+    }
+
     #[test]
     fn test_check_arbs_multiple_arbs() {
         // Scenario where multiple arbs exist
@@ -802,6 +899,9 @@ mod tests {
             poly_no_token: format!("no_token_{}", id).into(),
             line_value: None,
             team_suffix: None,
+            settles_at: None,
+            poly_ends_at: None,
+            basis_risk: 0.0,
         }
     }
 
@@ -917,6 +1017,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert_eq!(req.profit_cents(), 8);
@@ -935,6 +1036,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::KalshiYesPolyNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert_eq!(req.profit_cents(), 8);
@@ -953,6 +1055,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::PolyOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert_eq!(req.profit_cents(), 12);
@@ -972,6 +1075,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::KalshiOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert_eq!(req.profit_cents(), 12);
@@ -989,6 +1093,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert!(req.profit_cents() < 0, "Should have negative profit");
@@ -1005,6 +1110,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
         assert_eq!(req1.estimated_fee_cents(), kalshi_fee_cents(50));
 
@@ -1017,6 +1123,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::KalshiYesPolyNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
         assert_eq!(req2.estimated_fee_cents(), kalshi_fee_cents(40));
 
@@ -1029,6 +1136,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::PolyOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
         assert_eq!(req3.estimated_fee_cents(), 0);
 
@@ -1041,6 +1149,7 @@ mod tests {
             no_size: 1000,
             arb_type: ArbType::KalshiOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
         assert_eq!(req4.estimated_fee_cents(), kalshi_fee_cents(40) + kalshi_fee_cents(50));
     }
@@ -1085,6 +1194,9 @@ mod tests {
             poly_no_token: "no_token_cfc".into(),
             line_value: None,
             team_suffix: Some("CFC".into()),
+            settles_at: None,
+            poly_ends_at: None,
+            basis_risk: 0.0,
         };
 
         let poly_yes_token = pair.poly_yes_token.clone();
@@ -1124,6 +1236,7 @@ mod tests {
             no_size: k_no_sz,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert!(req.profit_cents() > 0, "Should have positive profit");
@@ -1191,6 +1304,18 @@ pub struct KalshiEvent {
     #[serde(default)]
     #[allow(dead_code)]
     pub sub_title: Option<String>,
+    /// Where the event's result comes from
+    #[serde(default)]
+    pub settlement_sources: Vec<KalshiSettlementSource>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct KalshiSettlementSource {
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1211,8 +1336,32 @@ pub struct KalshiMarket {
     pub yes_sub_title: Option<String>,
     #[serde(default)]
     pub floor_strike: Option<f64>,
+    /// Upper bound of a range market ("between", "less")
+    #[serde(default)]
+    pub cap_strike: Option<f64>,
+    /// "between", "greater", "less", ... (how floor/cap apply)
+    #[serde(default)]
+    pub strike_type: Option<String>,
     pub volume: Option<i64>,
     pub liquidity: Option<i64>,
+    /// RFC 3339; when the market is expected to settle
+    #[serde(default)]
+    pub expected_expiration_time: Option<String>,
+    #[serde(default)]
+    pub close_time: Option<String>,
+    /// "yes" / "no" once the market has settled
+    #[serde(default)]
+    pub result: Option<String>,
+}
+
+impl KalshiMarket {
+    /// Expected settlement as Unix seconds (falls back to the close time)
+    pub fn settles_at(&self) -> Option<i64> {
+        self.expected_expiration_time.as_deref()
+            .or(self.close_time.as_deref())
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp())
+    }
 }
 
 // === Polymarket/Gamma API Types ===
@@ -0,0 +1,303 @@
+// src/unit_economics.rs
+// Unit economics - executed arbs bucketed by asset, edge size and time in the interval, with the
+// mean and variance of net profit per trade; losing buckets can be switched off (UNIT_DISABLED_BUCKETS)
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::OnceLock;
+use tracing::warn;
+
+use crate::opportunities::OpportunityRecord;
+
+/// Edge band boundaries in cents (UNIT_EDGE_BANDS default)
+const DEFAULT_EDGE_BANDS: &str = "0.7,1.5,3";
+
+/// Where in its interval an arb was detected (thirds of the interval)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    Open,
+    Mid,
+    Close,
+    /// No interval (main bot, market sets)
+    Untimed,
+}
+
+impl Phase {
+    pub fn of(record: &OpportunityRecord) -> Self {
+        let (Some(elapsed), Some(interval)) = (record.secs_into_interval(), record.interval_secs) else {
+            return Phase::Untimed;
+        };
+        match elapsed * 3 / interval.max(1) {
+            0 => Phase::Open,
+            1 => Phase::Mid,
+            _ => Phase::Close,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Open => "open",
+            Phase::Mid => "mid",
+            Phase::Close => "close",
+            Phase::Untimed => "untimed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(Phase::Open),
+            "mid" => Some(Phase::Mid),
+            "close" => Some(Phase::Close),
+            "untimed" => Some(Phase::Untimed),
+            _ => None,
+        }
+    }
+}
+
+/// Label of the band an edge (cents) falls in: "<0.7", "0.7-1.5", ..., "3+"
+pub fn edge_band(edge_cents: f64, bands: &[f64]) -> String {
+    let Some(first) = bands.first() else {
+        return "all".into();
+    };
+    if edge_cents < *first {
+        return format!("<{}", first);
+    }
+    match bands.windows(2).find(|w| edge_cents < w[1]) {
+        Some(w) => format!("{}-{}", w[0], w[1]),
+        None => format!("{}+", bands[bands.len() - 1]),
+    }
+}
+
+/// An opportunity class
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bucket {
+    pub asset: String,
+    /// Edge band label (cents)
+    pub edge: String,
+    pub phase: Phase,
+}
+
+impl Bucket {
+    pub fn of(record: &OpportunityRecord, bands: &[f64]) -> Self {
+        Self {
+            asset: record.asset.to_lowercase(),
+            edge: edge_band(record.edge * 100.0, bands),
+            phase: Phase::of(record),
+        }
+    }
+}
+
+impl fmt::Display for Bucket {
+    /// The UNIT_DISABLED_BUCKETS rule that matches exactly this bucket
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.asset, self.edge, self.phase.as_str())
+    }
+}
+
+/// `asset:edge:phase` with `*` (or a missing trailing part) matching anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketRule {
+    asset: Option<String>,
+    edge: Option<String>,
+    phase: Option<Phase>,
+}
+
+impl BucketRule {
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts: Vec<&str> = s.trim().split(':').map(str::trim).collect();
+        if parts.len() > 3 || parts[0].is_empty() {
+            return None;
+        }
+        let part = |i: usize| parts.get(i).copied().filter(|p| *p != "*");
+        Some(Self {
+            asset: part(0).map(str::to_lowercase),
+            edge: part(1).map(String::from),
+            phase: match part(2) {
+                Some(p) => Some(Phase::parse(p)?),
+                None => None,
+            },
+        })
+    }
+
+    pub fn matches(&self, bucket: &Bucket) -> bool {
+        self.asset.as_ref().is_none_or(|a| *a == bucket.asset)
+            && self.edge.as_ref().is_none_or(|e| *e == bucket.edge)
+            && self.phase.is_none_or(|p| p == bucket.phase)
+    }
+}
+
+impl fmt::Display for BucketRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.asset.as_deref().unwrap_or("*"), self.edge.as_deref().unwrap_or("*"),
+               self.phase.map_or("*", |p| p.as_str()))
+    }
+}
+
+/// Unit economics configuration from environment
+#[derive(Debug, Clone)]
+pub struct UnitConfig {
+    /// Edge band boundaries in cents, ascending
+    pub edge_bands: Vec<f64>,
+
+    /// Buckets not traded (UNIT_DISABLED_BUCKETS, comma-separated rules)
+    pub disabled: Vec<BucketRule>,
+}
+
+impl UnitConfig {
+    pub fn from_env() -> Self {
+        let mut edge_bands: Vec<f64> = std::env::var("UNIT_EDGE_BANDS")
+            .unwrap_or_else(|_| DEFAULT_EDGE_BANDS.to_string())
+            .split(',')
+            .filter_map(|b| b.trim().parse().ok())
+            .collect();
+        edge_bands.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        edge_bands.dedup();
+
+        Self {
+            edge_bands,
+
+            disabled: std::env::var("UNIT_DISABLED_BUCKETS")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|r| !r.trim().is_empty())
+                .filter_map(|r| {
+                    let rule = BucketRule::parse(r);
+                    if rule.is_none() {
+                        warn!("[UNITS] Ignoring invalid UNIT_DISABLED_BUCKETS rule '{}' (asset:edge:phase)", r.trim());
+                    }
+                    rule
+                })
+                .collect(),
+        }
+    }
+
+    /// The rule that switches off the bucket of `record`, if any
+    pub fn disabled_by(&self, record: &OpportunityRecord) -> Option<&BucketRule> {
+        let bucket = Bucket::of(record, &self.edge_bands);
+        self.disabled.iter().find(|r| r.matches(&bucket))
+    }
+}
+
+/// Process-wide unit economics configuration (UNIT_*)
+pub fn config() -> &'static UnitConfig {
+    static CONFIG: OnceLock<UnitConfig> = OnceLock::new();
+    CONFIG.get_or_init(UnitConfig::from_env)
+}
+
+/// Net profit per trade of one bucket (running mean / variance)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BucketStats {
+    pub trades: usize,
+    /// Net profit of all trades (dollars)
+    pub total: f64,
+    /// Mean net profit per trade (dollars)
+    pub mean: f64,
+    m2: f64,
+}
+
+impl BucketStats {
+    pub fn add(&mut self, profit: f64) {
+        self.trades += 1;
+        self.total += profit;
+        let delta = profit - self.mean;
+        self.mean += delta / self.trades as f64;
+        self.m2 += delta * (profit - self.mean);
+    }
+
+    /// Sample variance of per-trade profit (0 below two trades)
+    pub fn variance(&self) -> f64 {
+        if self.trades < 2 { 0.0 } else { self.m2 / (self.trades - 1) as f64 }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Loses money on average over at least `min_trades` trades
+    pub fn unprofitable(&self, min_trades: usize) -> bool {
+        self.trades >= min_trades.max(1) && self.mean < 0.0
+    }
+}
+
+/// Executed arbs with a recorded profit, by bucket
+pub fn report(records: &[OpportunityRecord], bands: &[f64]) -> BTreeMap<Bucket, BucketStats> {
+    let mut buckets: BTreeMap<Bucket, BucketStats> = BTreeMap::new();
+    for r in records.iter().filter(|r| r.taken) {
+        let Some(profit) = r.profit else { continue };
+        buckets.entry(Bucket::of(r, bands)).or_default().add(profit);
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(asset: &str, yes: f64, no: f64, secs_to_expiry: i64, profit: f64) -> OpportunityRecord {
+        OpportunityRecord::new(asset, asset, "PolyOnly", yes, no, 10.0, 10.0, 0.0)
+            .timed(secs_to_expiry, 900)
+            .filled(9.9)
+            .with_profit(profit)
+    }
+
+    #[test]
+    fn test_buckets() {
+        let bands = [0.7, 1.5, 3.0];
+        assert_eq!(edge_band(0.5, &bands), "<0.7");
+        assert_eq!(edge_band(0.7, &bands), "0.7-1.5");
+        assert_eq!(edge_band(2.0, &bands), "1.5-3");
+        assert_eq!(edge_band(4.0, &bands), "3+");
+
+        // 0.5¢ edge, 100s into a 900s interval
+        let b = Bucket::of(&trade("XRP", 0.495, 0.5, 800, 0.0), &bands);
+        assert_eq!(b.to_string(), "xrp:<0.7:open");
+        assert_eq!(Bucket::of(&trade("btc", 0.45, 0.5, 100, 0.0), &bands).phase, Phase::Close);
+        let untimed = OpportunityRecord::new("nba", "g", "KalshiYesPolyNo", 0.4, 0.5, 1.0, 1.0, 0.0);
+        assert_eq!(Phase::of(&untimed), Phase::Untimed);
+    }
+
+    #[test]
+    fn test_rules() {
+        let bands = [0.7, 1.5, 3.0];
+        let xrp_thin = Bucket::of(&trade("xrp", 0.495, 0.5, 800, 0.0), &bands);
+        let btc_thin = Bucket::of(&trade("btc", 0.495, 0.5, 800, 0.0), &bands);
+
+        let rule = BucketRule::parse("XRP:<0.7").unwrap();
+        assert_eq!(rule.to_string(), "xrp:<0.7:*");
+        assert!(rule.matches(&xrp_thin) && !rule.matches(&btc_thin));
+        assert!(BucketRule::parse("*:*:open").unwrap().matches(&btc_thin));
+        assert!(!BucketRule::parse("*:*:close").unwrap().matches(&btc_thin));
+        assert!(BucketRule::parse("xrp:<0.7:noon").is_none());
+        assert!(BucketRule::parse("a:b:c:d").is_none());
+
+        let config = UnitConfig { edge_bands: bands.to_vec(), disabled: vec![rule] };
+        assert!(config.disabled_by(&trade("xrp", 0.495, 0.5, 800, 0.0)).is_some());
+        assert!(config.disabled_by(&trade("xrp", 0.45, 0.5, 800, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_report_mean_and_variance() {
+        let records = vec![
+            trade("xrp", 0.495, 0.5, 800, -0.10),
+            trade("xrp", 0.495, 0.5, 700, 0.02),
+            trade("xrp", 0.495, 0.5, 750, -0.04),
+            trade("btc", 0.45, 0.5, 800, 0.40),
+            // Not taken / no profit recorded: left out
+            OpportunityRecord::new("xrp", "xrp", "PolyOnly", 0.495, 0.5, 10.0, 10.0, 0.0).timed(800, 900),
+        ];
+        let report = report(&records, &[0.7, 1.5, 3.0]);
+        assert_eq!(report.len(), 2);
+
+        let xrp = report.iter().find(|(b, _)| b.asset == "xrp").unwrap().1;
+        assert_eq!(xrp.trades, 3);
+        assert!((xrp.mean + 0.04).abs() < 1e-9);
+        assert!((xrp.total + 0.12).abs() < 1e-9);
+        // Deviations -0.06, 0.06, 0: sample variance 0.0072 / 2
+        assert!((xrp.variance() - 0.0036).abs() < 1e-9);
+        assert!(xrp.unprofitable(3) && !xrp.unprofitable(4));
+
+        let btc = report.iter().find(|(b, _)| b.asset == "btc").unwrap().1;
+        assert_eq!((btc.trades, btc.variance()), (1, 0.0));
+        assert!(!btc.unprofitable(1));
+    }
+}
@@ -0,0 +1,129 @@
+// src/units.rs
+// Checked unit conversions (shares ↔ dollars ↔ cents ↔ CLOB micro-units)
+//
+// Every size that crosses an API boundary goes through here, so a misparsed
+// string ("1e30", "inf", "NaN") is rejected or saturates instead of becoming
+// a giant order.
+
+use anyhow::{bail, Result};
+
+/// Largest order the bot will ever sign, in shares/contracts
+pub const MAX_ORDER_SHARES: f64 = 100_000.0;
+
+/// CLOB token amounts use 6 decimals
+const MICRO_PER_SHARE: f64 = 1_000_000.0;
+
+/// Parse a size/price string from an exchange: finite and non-negative, else None
+#[inline]
+pub fn parse_amount(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0)
+}
+
+/// Shares → CLOB micro-units, rejecting anything that isn't a sane order size
+pub fn shares_to_micro(shares: f64) -> Result<u64> {
+    if !shares.is_finite() || shares < 0.0 {
+        bail!("invalid order size {}", shares);
+    }
+    if shares > MAX_ORDER_SHARES {
+        bail!("order size {} exceeds limit {}", shares, MAX_ORDER_SHARES);
+    }
+    Ok((shares * MICRO_PER_SHARE).floor() as u64)
+}
+
+/// Price (0-1) → basis points, rejecting non-finite or out-of-range prices
+pub fn price_to_bps(price: f64) -> Result<u64> {
+    if !price.is_finite() || !(0.0..=1.0).contains(&price) {
+        bail!("invalid price {}", price);
+    }
+    Ok((price * 10_000.0).round() as u64)
+}
+
+/// micro-units × basis points → micro-units of collateral (checked)
+pub fn notional_micro(size_micro: u64, price_bps: u64) -> Result<u128> {
+    match (size_micro as u128).checked_mul(price_bps as u128) {
+        Some(v) => Ok(v / 10_000),
+        None => bail!("notional overflow: {} x {}bps", size_micro, price_bps),
+    }
+}
+
+/// CLOB micro-units → shares
+#[inline]
+#[allow(dead_code)]
+pub fn micro_to_shares(micro: u128) -> f64 {
+    micro as f64 / MICRO_PER_SHARE
+}
+
+/// Shares → whole contracts (floor). Non-finite or negative → 0, huge values saturate.
+#[inline]
+pub fn shares_to_contracts(shares: f64) -> i64 {
+    if !shares.is_finite() || shares <= 0.0 {
+        return 0;
+    }
+    shares.floor().min(i64::MAX as f64) as i64
+}
+
+/// Dollars → cents (rounded). Non-finite → 0, huge values saturate.
+#[inline]
+pub fn dollars_to_cents(dollars: f64) -> i64 {
+    if !dollars.is_finite() {
+        return 0;
+    }
+    (dollars * 100.0).round().clamp(i64::MIN as f64, i64::MAX as f64) as i64
+}
+
+/// Profit in cents of `matched` complete pairs (each paying out $1) bought for `cost_cents`.
+/// Saturates instead of wrapping.
+#[inline]
+pub fn pair_profit_cents(matched: i64, cost_cents: i64) -> i64 {
+    matched.saturating_mul(100).saturating_sub(cost_cents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_misparsed_sizes_never_become_orders() {
+        for s in ["inf", "-inf", "NaN", "-1", "abc", ""] {
+            assert_eq!(parse_amount(s), None, "{}", s);
+        }
+        assert_eq!(parse_amount(" 12.5 "), Some(12.5));
+
+        // "1e30" parses fine but is far beyond any real order
+        let huge = parse_amount("1e30").unwrap();
+        assert!(shares_to_micro(huge).is_err());
+        assert!(shares_to_micro(f64::NAN).is_err());
+        assert!(shares_to_micro(-1.0).is_err());
+        assert_eq!(shares_to_micro(MAX_ORDER_SHARES).unwrap(), 100_000_000_000);
+        assert_eq!(shares_to_micro(100.5).unwrap(), 100_500_000);
+    }
+
+    #[test]
+    fn test_price_and_notional_bounds() {
+        assert_eq!(price_to_bps(0.65).unwrap(), 6500);
+        assert!(price_to_bps(1.01).is_err());
+        assert!(price_to_bps(f64::INFINITY).is_err());
+        assert!(price_to_bps(-0.01).is_err());
+
+        assert_eq!(notional_micro(10_000_000, 6500).unwrap(), 6_500_000);
+        // u64 × u64 always fits in u128 - extremes don't panic
+        assert!(notional_micro(u64::MAX, u64::MAX).is_ok());
+        assert_eq!(micro_to_shares(6_500_000), 6.5);
+    }
+
+    #[test]
+    fn test_saturating_conversions() {
+        assert_eq!(shares_to_contracts(f64::NAN), 0);
+        assert_eq!(shares_to_contracts(-5.0), 0);
+        assert_eq!(shares_to_contracts(7.9), 7);
+        assert_eq!(shares_to_contracts(1e300), i64::MAX);
+
+        assert_eq!(dollars_to_cents(f64::INFINITY), 0);
+        assert_eq!(dollars_to_cents(1.235), 124);
+        assert_eq!(dollars_to_cents(-1e300), i64::MIN);
+
+        // Well past i16: 400 pairs bought for $392
+        assert_eq!(pair_profit_cents(400, 39_200), 800);
+        assert_eq!(pair_profit_cents(i64::MAX, -1), i64::MAX);
+    }
+}
@@ -0,0 +1,330 @@
+// src/updown_lifecycle.rs
+// Up/Down market lifecycle steps - discovery from a Gamma listing, book delivery and warm-up,
+// arb detection and basket execution against a taker venue - behind an injectable clock and
+// exchange, so a whole interval can be driven deterministically
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::arb_confirm::BookWarmup;
+use crate::basket::{self, Basket, BasketConfig, Repair, RepairSide};
+use crate::book::LocalBook;
+use crate::clock_skew::ExchangeClock;
+use crate::leg_order::LegOrderConfig;
+use crate::market_key::MarketKey;
+use crate::polymarket_ws_types::{BookSnapshot, PriceLevel};
+use crate::position_tracker::FillRecord;
+use crate::subscriptions::SubscriptionManager;
+use crate::updown_scanner::{pick_event, ActiveUpDownMarket, GammaEvent, MarketSeries};
+
+/// Exchange time and the monotonic instant timers run on
+pub trait Clock {
+    /// Exchange time in Unix milliseconds
+    fn now_ms(&self) -> u64;
+
+    fn instant(&self) -> Instant;
+
+    /// Exchange time in Unix seconds
+    fn now_secs(&self) -> u64 {
+        self.now_ms() / 1000
+    }
+
+    /// Exchange time as an RFC 3339 timestamp (how fills are stamped)
+    fn rfc3339(&self) -> String {
+        let ms = self.now_ms();
+        chrono::DateTime::from_timestamp((ms / 1000) as i64, (ms % 1000) as u32 * 1_000_000)
+            .unwrap_or_default()
+            .to_rfc3339()
+    }
+}
+
+impl Clock for ExchangeClock {
+    fn now_ms(&self) -> u64 {
+        ExchangeClock::now_ms(self)
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when told to: exchange time and the instant advance together
+#[derive(Debug)]
+pub struct ManualClock {
+    start_ms: u64,
+    base: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn starting_at(secs: u64) -> Self {
+        Self { start_ms: secs * 1000, base: Instant::now(), elapsed: Mutex::new(Duration::ZERO) }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+
+    /// Jump to exchange time `secs` (never backwards)
+    pub fn advance_to(&self, secs: u64) {
+        let target = Duration::from_millis((secs * 1000).saturating_sub(self.start_ms));
+        let mut elapsed = self.elapsed.lock().unwrap();
+        *elapsed = (*elapsed).max(target);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.start_ms + self.elapsed.lock().unwrap().as_millis() as u64
+    }
+
+    fn instant(&self) -> Instant {
+        self.base + *self.elapsed.lock().unwrap()
+    }
+}
+
+/// The market of `asset` for the interval ending at `end`, from a series' Gamma events
+/// (the event's first market, as the scanner's events discovery takes it)
+pub fn discover(events: &[GammaEvent], series: MarketSeries, asset: &str, end: u64, fee_rate_bps: u32) -> Option<ActiveUpDownMarket> {
+    let market = pick_event(events, end, series.interval_secs())?.markets.first()?;
+    if !market.is_active() {
+        return None;
+    }
+    ActiveUpDownMarket::from_market(market, asset, end, fee_rate_bps)
+}
+
+/// Both outcome tokens of every market - the token set to subscribe to
+pub fn tokens<'a>(markets: impl IntoIterator<Item = &'a ActiveUpDownMarket>) -> Vec<String> {
+    markets.into_iter().flat_map(|m| [m.yes_token.clone(), m.no_token.clone()]).collect()
+}
+
+/// Markets still tracked at `now`, given the series' expiry grace
+pub fn live<'a>(markets: impl IntoIterator<Item = &'a ActiveUpDownMarket>, series: MarketSeries, now: u64) -> Vec<&'a ActiveUpDownMarket> {
+    markets.into_iter().filter(|m| m.is_live(now, series.expiry_grace_secs())).collect()
+}
+
+/// Local books of the subscribed tokens and the warm-up state of each market
+#[derive(Debug, Default)]
+pub struct BookFeed {
+    books: HashMap<String, LocalBook>,
+    warmups: HashMap<String, BookWarmup>,
+}
+
+impl BookFeed {
+    /// Apply a token's `book` snapshot: replaces its book, acknowledges the subscription and
+    /// feeds the market's warm-up. Returns whether the market just became warm.
+    pub fn deliver(&mut self, market: &ActiveUpDownMarket, snapshot: &BookSnapshot, subs: &mut SubscriptionManager, min_depth: f64) -> bool {
+        let book = self.books.entry(snapshot.asset_id.clone()).or_default();
+        book.apply_snapshot(snapshot);
+        subs.acknowledge(&snapshot.asset_id);
+        let depth = book.best_ask().map_or(0.0, |(_, size)| size);
+        self.warmups.entry(market.yes_token.clone()).or_default()
+            .observe(snapshot.asset_id == market.yes_token, depth, min_depth)
+    }
+
+    pub fn book(&self, token: &str) -> Option<&LocalBook> {
+        self.books.get(token)
+    }
+
+    pub fn is_warm(&self, market: &ActiveUpDownMarket) -> bool {
+        self.warmups.get(&market.yes_token).is_some_and(|w| w.is_warm())
+    }
+
+    /// Best YES and NO asks of a market as (price, size)
+    pub fn best_asks(&self, market: &ActiveUpDownMarket) -> Option<((f64, f64), (f64, f64))> {
+        Some((self.book(&market.yes_token)?.best_ask()?, self.book(&market.no_token)?.best_ask()?))
+    }
+
+    /// YES + NO asks under `threshold`
+    pub fn has_arb(&self, market: &ActiveUpDownMarket, threshold: f64) -> bool {
+        self.best_asks(market).is_some_and(|((yes, _), (no, _))| yes + no < threshold)
+    }
+
+    /// Drop the books and warm-ups of tokens no longer subscribed
+    pub fn retain(&mut self, subs: &SubscriptionManager) {
+        self.books.retain(|token, _| subs.desired().contains(token));
+        self.warmups.retain(|token, _| subs.desired().contains(token));
+    }
+
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+}
+
+/// One fill-and-kill order's result
+#[derive(Debug, Clone, PartialEq)]
+pub struct Taken {
+    pub order_id: String,
+    pub token: String,
+    pub side: RepairSide,
+    pub contracts: f64,
+    pub price: f64,
+}
+
+impl Taken {
+    /// Position record of the fill on the market's `side` ("yes" / "no"); sells reduce it
+    pub fn record(&self, key: &MarketKey, question: &str, side: &str, timestamp: String) -> FillRecord {
+        let signed = if self.side == RepairSide::Sell { -self.contracts } else { self.contracts };
+        let mut record = FillRecord::keyed(key, question, "polymarket", side, signed, self.price, 0.0, &self.order_id);
+        record.timestamp = timestamp;
+        record
+    }
+}
+
+/// A venue taking fill-and-kill orders
+pub trait Exchange {
+    fn take(&self, token: &str, side: RepairSide, contracts: f64, limit: f64) -> Result<Taken>;
+}
+
+/// Legs, unwind plan and repairs of one executed basket
+#[derive(Debug)]
+pub struct Execution {
+    pub legs: Vec<Result<Taken>>,
+    pub plan: Vec<Repair>,
+    pub repairs: Vec<Result<Taken>>,
+}
+
+impl Execution {
+    /// Contracts filled per leg (0 for a failed leg)
+    pub fn filled(&self) -> Vec<f64> {
+        self.legs.iter().map(|r| r.as_ref().map_or(0.0, |t| t.contracts)).collect()
+    }
+
+    /// Every fill in order - legs, then repairs - with the outcome side it was on
+    pub fn fills<'a>(&'a self, basket: &'a Basket) -> impl Iterator<Item = (&'a Taken, &'static str)> + 'a {
+        let legs = self.legs.iter().zip(&basket.legs).map(|(r, leg)| (r, leg.side));
+        let repairs = self.repairs.iter().zip(&self.plan).map(|(r, p)| (r, basket.legs[p.leg].side));
+        legs.chain(repairs).filter_map(|(r, side)| Some((r.as_ref().ok()?, side)))
+    }
+}
+
+/// Send a basket's legs, then unwind or complete whatever didn't match
+pub async fn execute(basket: &Basket, order: &LegOrderConfig, hedge: &BasketConfig, exchange: &impl Exchange) -> Execution {
+    let legs = basket::submit(basket, order, |i, contracts| {
+        let leg = &basket.legs[i];
+        let taken = exchange.take(&leg.instrument, RepairSide::Buy, contracts, leg.price);
+        async move { taken }
+    }, |t: &Taken| t.contracts).await;
+    let mut execution = Execution { legs, plan: Vec::new(), repairs: Vec::new() };
+    execution.plan = basket.plan(&execution.filled(), hedge);
+    execution.repairs = basket::compensate(basket, &execution.plan, |r: &Repair| {
+        let taken = exchange.take(&basket.legs[r.leg].instrument, r.side, r.contracts, r.price);
+        async move { taken }
+    }).await;
+    execution
+}
+
+/// Best bid and ask of a token as (price, size)
+#[derive(Debug, Clone, Copy, Default)]
+struct MockBook {
+    bid: (f64, f64),
+    ask: (f64, f64),
+}
+
+/// In-memory CLOB with one level a side per token: FAK orders take what the level holds
+#[derive(Debug, Default)]
+pub struct MockExchange {
+    books: Mutex<HashMap<String, MockBook>>,
+    fills: Mutex<Vec<Taken>>,
+}
+
+impl MockExchange {
+    pub fn set_book(&self, token: &str, bid: (f64, f64), ask: (f64, f64)) {
+        self.books.lock().unwrap().insert(token.to_string(), MockBook { bid, ask });
+    }
+
+    /// Another taker lifts `contracts` of a token's ask
+    pub fn lift_ask(&self, token: &str, contracts: f64) {
+        if let Some(book) = self.books.lock().unwrap().get_mut(token) {
+            book.ask.1 = (book.ask.1 - contracts).max(0.0);
+        }
+    }
+
+    /// The `book` message the market channel sends for a token
+    pub fn snapshot(&self, token: &str) -> BookSnapshot {
+        let book = self.books.lock().unwrap().get(token).copied().unwrap_or_default();
+        let level = |(price, size): (f64, f64)| PriceLevel { price: price.to_string(), size: size.to_string() };
+        BookSnapshot {
+            asset_id: token.to_string(),
+            bids: vec![level(book.bid)],
+            asks: vec![level(book.ask)],
+            ..Default::default()
+        }
+    }
+
+    /// Every fill so far
+    pub fn fills(&self) -> Vec<Taken> {
+        self.fills.lock().unwrap().clone()
+    }
+}
+
+impl Exchange for MockExchange {
+    /// Filled at the resting level's price, up to its size
+    fn take(&self, token: &str, side: RepairSide, contracts: f64, limit: f64) -> Result<Taken> {
+        let mut books = self.books.lock().unwrap();
+        let Some(book) = books.get_mut(token) else {
+            bail!("FAK {:?} {} rejected: unknown token", side, token);
+        };
+        let crosses = match side {
+            RepairSide::Buy => book.ask.0 <= limit,
+            RepairSide::Sell => book.bid.0 >= limit,
+        };
+        let level = match side {
+            RepairSide::Buy => &mut book.ask,
+            RepairSide::Sell => &mut book.bid,
+        };
+        if !crosses || level.1 <= 0.0 {
+            bail!("FAK {:?} {} @ {} killed: no liquidity", side, token, limit);
+        }
+        let filled = contracts.min(level.1);
+        level.1 -= filled;
+        let mut fills = self.fills.lock().unwrap();
+        let taken = Taken {
+            order_id: format!("0x{:02}", fills.len() + 1),
+            token: token.to_string(),
+            side,
+            contracts: filled,
+            price: level.0,
+        };
+        fills.push(taken.clone());
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::starting_at(1766099705);
+        let start = clock.instant();
+        clock.advance(Duration::from_millis(700));
+        assert_eq!(clock.now_ms(), 1766099705700);
+        assert_eq!(clock.rfc3339(), "2025-12-18T23:15:05.700+00:00");
+        clock.advance_to(1766100600);
+        assert_eq!(clock.now_secs(), 1766100600);
+        assert_eq!(clock.instant() - start, Duration::from_secs(895));
+        clock.advance_to(1766100000);
+        assert_eq!(clock.now_secs(), 1766100600, "never backwards");
+    }
+
+    #[test]
+    fn test_mock_exchange_fak() {
+        let exchange = MockExchange::default();
+        exchange.set_book("t", (0.44, 5.0), (0.46, 10.0));
+        assert!(exchange.take("t", RepairSide::Buy, 10.0, 0.45).is_err(), "limit under the ask");
+        assert_eq!(exchange.take("t", RepairSide::Buy, 15.0, 0.50).unwrap().contracts, 10.0);
+        assert!(exchange.take("t", RepairSide::Buy, 1.0, 0.50).is_err(), "level exhausted");
+        let sold = exchange.take("t", RepairSide::Sell, 2.0, 0.40).unwrap();
+        assert_eq!((sold.contracts, sold.price, sold.order_id.as_str()), (2.0, 0.44, "0x02"));
+        assert_eq!(exchange.fills().len(), 2);
+    }
+}
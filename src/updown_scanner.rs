@@ -1,29 +1,273 @@
 // src/updown_scanner.rs
-// Polymarket Up/Down 15-minute market scanner
+// Polymarket Up/Down market scanner
 //
 // Strategy: Find imbalances where YES + NO < 100¢
-// Markets: BTC, ETH, SOL, XRP 15-minute Up/Down markets
+// Markets: BTC, ETH, SOL, XRP Up/Down markets (15-minute, 5-minute or 1-minute series)
 
 use anyhow::Result;
-use serde::Deserialize;
-use std::time::{SystemTime, UNIX_EPOCH};
+use reqwest::header::{DATE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
 use tokio::time::{sleep, Duration};
 use tracing::{info, warn, debug};
 
+use crate::app::POLY_CLOB_HOST;
+use crate::clock_skew::{self, local_ms};
 use crate::config::GAMMA_API_BASE;
+use crate::gamma::{self, GammaPageConfig};
+use crate::instruments::InstrumentCache;
+use crate::liquidity_rewards::RewardParams;
+use crate::market_key::MarketKey;
+use crate::market_sets::MarketSet;
+use crate::overrides::{self, Overrides};
+use crate::polymarket_clob::{self, fetch_fee_rate_bps};
+use crate::polymarket_ws_types::BookSnapshot;
+use crate::position_store::Settlement;
+use crate::settlement;
+
+pub use crate::clock_skew::ExchangeClock;
 
 /// Assets to track for Up/Down markets
-const UPDOWN_ASSETS: &[&str] = &["btc", "eth", "sol", "xrp"];
+pub const UPDOWN_ASSETS: &[&str] = &["btc", "eth", "sol", "xrp"];
+
+/// How each asset's questions start ("Bitcoin Up or Down - December 19, 9:00AM-9:15AM ET")
+const ASSET_NAMES: &[(&str, &str)] = &[("bitcoin", "btc"), ("ethereum", "eth"), ("solana", "sol"), ("xrp", "xrp")];
 
 /// 15 minutes in seconds
 const MARKET_INTERVAL_SECS: u64 = 900;
 
+/// REST book fetches give up sooner than metadata queries: the WS snapshot is on its way anyway
+const BOOKS_TIMEOUT_SECS: u64 = 2;
+
+/// Up/Down market cycle length. Each series has its own slug tag and timing:
+/// shorter cycles preload later, stop trading closer to expiry and rescan faster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarketSeries {
+    #[default]
+    FifteenMin,
+    FiveMin,
+    OneMin,
+}
+
+impl MarketSeries {
+    /// Read from UPDOWN_SERIES (15m | 5m | 1m), defaulting to 15m
+    pub fn from_env() -> Self {
+        std::env::var("UPDOWN_SERIES")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "15m" | "15" => Some(Self::FifteenMin),
+            "5m" | "5" => Some(Self::FiveMin),
+            "1m" | "1" => Some(Self::OneMin),
+            _ => None,
+        }
+    }
+
+    /// Tag used in market slugs ("btc-updown-15m-<end_ts>")
+    pub fn slug_tag(&self) -> &'static str {
+        match self {
+            Self::FifteenMin => "15m",
+            Self::FiveMin => "5m",
+            Self::OneMin => "1m",
+        }
+    }
+
+    /// Market cycle length in seconds
+    pub fn interval_secs(&self) -> u64 {
+        match self {
+            Self::FifteenMin => MARKET_INTERVAL_SECS,
+            Self::FiveMin => 300,
+            Self::OneMin => 60,
+        }
+    }
+
+    /// How early the next interval's markets are loaded before the current ones expire
+    pub fn preload_buffer_secs(&self) -> u64 {
+        match self {
+            Self::FifteenMin => 60,
+            Self::FiveMin => 20,
+            Self::OneMin => 5,
+        }
+    }
+
+    /// No new trades this close to a market's end (book is thin and about to freeze)
+    pub fn no_trade_window_secs(&self) -> u64 {
+        match self {
+            Self::FifteenMin => 10,
+            Self::FiveMin => 5,
+            Self::OneMin => 3,
+        }
+    }
+
+    /// Delay between re-queries of next-interval slugs that weren't listed at preload
+    pub fn preload_retry_secs(&self) -> u64 {
+        match self {
+            Self::FifteenMin => 5,
+            Self::FiveMin => 3,
+            Self::OneMin => 2,
+        }
+    }
+
+    /// Delay before retrying when no active markets were found
+    pub fn retry_secs(&self) -> u64 {
+        match self {
+            Self::FifteenMin => 10,
+            Self::FiveMin => 5,
+            Self::OneMin => 2,
+        }
+    }
+
+    /// Grace period after expiry before markets are dropped
+    pub fn expiry_grace_secs(&self) -> u64 {
+        match self {
+            Self::FifteenMin | Self::FiveMin => 5,
+            Self::OneMin => 2,
+        }
+    }
+
+    /// End timestamp of the interval `offset` cycles away from the one containing `now`
+    pub fn interval_end(&self, now: u64, offset: i32) -> u64 {
+        let interval = self.interval_secs();
+        let base_interval_end = ((now / interval) + 1) * interval;
+        if offset >= 0 {
+            base_interval_end + (offset as u64 * interval)
+        } else {
+            base_interval_end.saturating_sub((-offset) as u64 * interval)
+        }
+    }
+
+    pub fn slug(&self, asset: &str, interval_end: u64) -> String {
+        format!("{}-updown-{}-{}", asset, self.slug_tag(), interval_end)
+    }
+
+    /// Key for a position recorded under its question before market keys: the asset from the
+    /// question, the interval from when the position opened (RFC 3339). Tokens are unknown.
+    pub fn legacy_key(&self, question: &str, opened_at: &str) -> Option<MarketKey> {
+        let first = question.split_whitespace().next()?.to_lowercase();
+        let (_, asset) = ASSET_NAMES.iter().find(|(name, _)| *name == first)?;
+        let opened = u64::try_from(chrono::DateTime::parse_from_rfc3339(opened_at).ok()?.timestamp()).ok()?;
+        let end = self.interval_end(opened, 0);
+        Some(MarketKey::polymarket(&self.slug(asset, end), "", "", end as i64))
+    }
+
+    /// Gamma series grouping every instance of an asset's market ("btc-up-or-down-15m")
+    pub fn series_slug(&self, asset: &str) -> String {
+        format!("{}-up-or-down-{}", asset, self.slug_tag())
+    }
+}
+
+/// How the scanner finds an interval's markets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiscoveryMode {
+    /// Construct the slug from the interval end timestamp and query it directly
+    #[default]
+    Slugs,
+    /// List the asset's series events and pick the one ending closest to the interval
+    /// end (paging through the series' open events when the listing doesn't embed it);
+    /// falls back to the slug per asset when the series lookup fails
+    Events,
+}
+
+impl DiscoveryMode {
+    /// Read from UPDOWN_DISCOVERY (slugs | events), defaulting to slugs
+    pub fn from_env() -> Self {
+        match std::env::var("UPDOWN_DISCOVERY").ok().as_deref().map(str::trim) {
+            Some("events") => Self::Events,
+            _ => Self::Slugs,
+        }
+    }
+}
+
+impl std::fmt::Display for MarketSeries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.slug_tag())
+    }
+}
+
 /// Only watch the current active 15-minute interval
+#[allow(dead_code)]
 const LOOKAHEAD_INTERVALS: u64 = 1;
 
 /// Scan interval - check for new markets every 30 seconds
 const SCAN_INTERVAL_SECS: u64 = 30;
 
+/// Default lifetime of a cached Gamma response (override with SCANNER_CACHE_TTL_SECS)
+const DEFAULT_CACHE_TTL_SECS: u64 = 20;
+
+/// Cached Gamma response for one slug, with validators for conditional requests
+#[derive(Debug, Clone)]
+struct CachedMarket {
+    market: UpDownMarket,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: Instant,
+}
+
+/// In-memory Gamma response cache keyed by slug.
+///
+/// Entries younger than the TTL are served without a request; stale entries are
+/// revalidated with If-None-Match / If-Modified-Since so an unchanged market costs a 304.
+/// Misses are never cached, so a market that doesn't exist yet is picked up on the next scan.
+struct ScanCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedMarket>>,
+}
+
+impl ScanCache {
+    fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: Mutex::new(HashMap::new()) }
+    }
+
+    fn fresh_at(&self, slug: &str, now: Instant) -> Option<UpDownMarket> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(slug)
+            .filter(|e| now.saturating_duration_since(e.fetched_at) < self.ttl)
+            .map(|e| e.market.clone())
+    }
+
+    fn validators(&self, slug: &str) -> (Option<String>, Option<String>) {
+        let entries = self.entries.lock().unwrap();
+        entries.get(slug)
+            .map(|e| (e.etag.clone(), e.last_modified.clone()))
+            .unwrap_or((None, None))
+    }
+
+    /// 304 Not Modified: keep the cached body, restart its TTL
+    fn revalidate_at(&self, slug: &str, now: Instant) -> Option<UpDownMarket> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.get_mut(slug).map(|e| {
+            e.fetched_at = now;
+            e.market.clone()
+        })
+    }
+
+    fn store_at(&self, slug: &str, market: UpDownMarket, etag: Option<String>, last_modified: Option<String>, now: Instant) {
+        self.entries.lock().unwrap().insert(slug.to_string(), CachedMarket {
+            market,
+            etag,
+            last_modified,
+            fetched_at: now,
+        });
+    }
+
+    /// Drop entries for intervals that have ended
+    fn prune(&self, now_ts: u64) {
+        self.entries.lock().unwrap().retain(|slug, _| {
+            slug.rsplit('-').next()
+                .and_then(|ts| ts.parse::<u64>().ok())
+                .map(|end| end > now_ts)
+                .unwrap_or(true)
+        });
+    }
+}
+
 /// Gamma API market response
 #[derive(Debug, Deserialize, Clone)]
 pub struct UpDownMarket {
@@ -49,6 +293,34 @@ pub struct UpDownMarket {
 
     #[serde(default, deserialize_with = "deserialize_json_string_array")]
     pub outcomes: Option<Vec<String>>,  // ["Up", "Down"] - comes as JSON string
+
+    #[serde(rename = "outcomePrices", default, deserialize_with = "deserialize_json_string_array")]
+    pub outcome_prices: Option<Vec<String>>,  // ["1", "0"] once resolved
+
+    #[serde(rename = "orderPriceMinTickSize")]
+    pub order_price_min_tick_size: Option<f64>,
+
+    #[serde(rename = "orderMinSize")]
+    pub order_min_size: Option<f64>,
+
+    #[serde(rename = "takerBaseFee")]
+    pub taker_base_fee: Option<f64>,  // bps (the CLOB's /fee-rate is preferred)
+
+    #[serde(rename = "rewardsMaxSpread")]
+    pub rewards_max_spread: Option<f64>,  // cents from the midpoint
+
+    #[serde(rename = "rewardsMinSize")]
+    pub rewards_min_size: Option<f64>,
+
+    #[serde(rename = "clobRewards", default)]
+    pub clob_rewards: Vec<ClobReward>,
+}
+
+/// One liquidity-reward allocation of a market
+#[derive(Debug, Deserialize, Clone)]
+pub struct ClobReward {
+    #[serde(rename = "rewardsDailyRate", default)]
+    pub rewards_daily_rate: f64,
 }
 
 impl UpDownMarket {
@@ -71,14 +343,86 @@ impl UpDownMarket {
             && self.accepting_orders.unwrap_or(false)
     }
 
+    /// How the market resolved (Up = YES), once Gamma reports it closed with a winner
+    pub fn settlement(&self) -> Option<Settlement> {
+        settlement::polymarket_settlement(self.closed, self.outcome_prices.as_deref().unwrap_or_default())
+    }
+
     /// Extract asset symbol from slug (e.g., "btc-updown-15m-1766100600" -> "btc")
     pub fn get_asset(&self) -> Option<&str> {
         self.slug.split('-').next()
     }
+
+    /// Liquidity-reward program of the market, if it pays anything
+    pub fn reward_params(&self) -> Option<RewardParams> {
+        let daily_rate_usd: f64 = self.clob_rewards.iter().map(|r| r.rewards_daily_rate).sum();
+        let max_spread_cents = self.rewards_max_spread.filter(|v| *v > 0.0)?;
+        (daily_rate_usd > 0.0).then_some(RewardParams {
+            max_spread_cents,
+            min_size: self.rewards_min_size.unwrap_or(0.0),
+            daily_rate_usd,
+        })
+    }
+
+    /// End time from Gamma's endDate (RFC 3339) as a Unix timestamp
+    pub fn end_timestamp(&self) -> Option<u64> {
+        let end = chrono::DateTime::parse_from_rfc3339(self.end_date.as_deref()?).ok()?;
+        u64::try_from(end.timestamp()).ok()
+    }
 }
 
+/// Gamma event: one instance of a recurring series, with its markets
+#[derive(Debug, Deserialize, Clone)]
+pub struct GammaEvent {
+    pub slug: String,
+
+    #[serde(rename = "endDate")]
+    pub end_date: Option<String>,
+
+    pub closed: Option<bool>,
+
+    /// Embedded by /events; the /series listing leaves it empty
+    #[serde(default)]
+    pub markets: Vec<UpDownMarket>,
+}
+
+impl GammaEvent {
+    /// End time from endDate, or the timestamp at the end of the slug
+    pub fn end_timestamp(&self) -> Option<u64> {
+        self.end_date.as_deref()
+            .and_then(|d| chrono::DateTime::parse_from_rfc3339(d).ok())
+            .and_then(|d| u64::try_from(d.timestamp()).ok())
+            .or_else(|| self.slug.rsplit('-').next()?.parse().ok())
+    }
+}
+
+/// Gamma series with its event instances (the listing embeds a limited number of them)
+#[derive(Debug, Deserialize)]
+struct GammaSeries {
+    #[serde(default, deserialize_with = "deserialize_string_or_number")]
+    id: u64,
+
+    #[serde(default)]
+    events: Vec<GammaEvent>,
+}
+
+/// Open event ending closest to `interval_end`, within half an interval either side
+/// (tolerates end times that aren't exactly on the boundary)
+pub fn pick_event(events: &[GammaEvent], interval_end: u64, interval_secs: u64) -> Option<&GammaEvent> {
+    let tolerance = interval_secs / 2;
+    events.iter()
+        .filter(|e| !e.closed.unwrap_or(false))
+        .filter_map(|e| Some((e, e.end_timestamp()?.abs_diff(interval_end))))
+        .filter(|(_, diff)| *diff < tolerance)
+        .min_by_key(|(_, diff)| *diff)
+        .map(|(e, _)| e)
+}
+
+/// Price tick assumed when Gamma doesn't report one
+const DEFAULT_TICK_SIZE: f64 = 0.01;
+
 /// Active market with token IDs
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ActiveUpDownMarket {
     pub slug: String,
     pub asset: String,
@@ -86,25 +430,139 @@ pub struct ActiveUpDownMarket {
     pub yes_token: String,  // "Up" token
     pub no_token: String,   // "Down" token
     pub end_timestamp: u64, // Unix timestamp when market closes
+    pub tick_size: f64,     // Minimum price increment (dollars)
+    pub min_size: f64,      // Venue minimum order size (shares, 0 = not reported)
+    #[serde(default)]
+    pub fee_rate_bps: u32,  // Taker fee rate (bps) orders are signed with and profit is net of
+}
+
+impl ActiveUpDownMarket {
+    /// The market tracked for a Gamma market record ending at `end_timestamp` (None without
+    /// both token IDs)
+    pub fn from_market(market: &UpDownMarket, asset: &str, end_timestamp: u64, fee_rate_bps: u32) -> Option<Self> {
+        let (yes_token, no_token) = market.get_token_ids()?;
+        Some(Self {
+            slug: market.slug.clone(),
+            asset: asset.to_string(),
+            question: market.question.clone(),
+            yes_token,
+            no_token,
+            end_timestamp,
+            tick_size: market.order_price_min_tick_size.filter(|t| *t > 0.0).unwrap_or(DEFAULT_TICK_SIZE),
+            min_size: market.order_min_size.unwrap_or(0.0),
+            fee_rate_bps,
+        })
+    }
+
+    /// Whether the market is still tracked at `now`: it ended less than `grace_secs` ago
+    pub fn is_live(&self, now: u64, grace_secs: u64) -> bool {
+        self.end_timestamp + grace_secs > now
+    }
+
+    /// Key positions, settlement tags and intents are recorded under. An Up/Down market's end
+    /// comes from its slug, so it stays put if Gamma later moves the end date.
+    pub fn key(&self) -> MarketKey {
+        let end = Some(&self.slug).filter(|s| s.contains("-updown-"))
+            .and_then(|s| s.rsplit('-').next()?.parse().ok())
+            .unwrap_or(self.end_timestamp);
+        MarketKey::polymarket(&self.slug, &self.yes_token, &self.no_token, end as i64)
+    }
+}
+
+/// Drop blacklisted markets from a scan and pin the outcome tokens of the rest (OVERRIDES_FILE)
+pub fn apply_overrides(overrides: &Overrides, markets: &mut Vec<ActiveUpDownMarket>) {
+    markets.retain(|m| {
+        let keep = !overrides.is_blacklisted(&[&m.asset, &m.slug]);
+        if !keep {
+            info!("[OVERRIDE] 🚫 {} blacklisted - not tracked", m.slug);
+        }
+        keep
+    });
+    for m in markets.iter_mut() {
+        let Some(pin) = overrides.pinned_yes(&m.slug) else {
+            continue;
+        };
+        if pin == m.no_token {
+            warn!("[OVERRIDE] 🔀 {}: pinned YES token was scanned as NO - swapping outcomes", m.slug);
+            std::mem::swap(&mut m.yes_token, &mut m.no_token);
+        } else if pin != m.yes_token {
+            warn!("[OVERRIDE] ⚠️ {}: pinned YES token {} is not one of the market's tokens - ignored", m.slug, pin);
+        }
+    }
 }
 
 pub struct UpDownScanner {
     http: reqwest::Client,
+    series: MarketSeries,
+    cache: ScanCache,
+    clock: ExchangeClock,
+    discovery: DiscoveryMode,
+    paging: GammaPageConfig,
+    instruments: Option<InstrumentCache>,
+}
+
+impl Default for UpDownScanner {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl UpDownScanner {
     pub fn new() -> Self {
+        Self::with_series(MarketSeries::FifteenMin)
+    }
+
+    pub fn with_series(series: MarketSeries) -> Self {
         Self {
             http: reqwest::Client::builder()
                 .timeout(Duration::from_secs(5))
                 .build()
                 .expect("Failed to build HTTP client"),
+            series,
+            cache: ScanCache::new(Duration::from_secs(
+                std::env::var("SCANNER_CACHE_TTL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_CACHE_TTL_SECS),
+            )),
+            clock: ExchangeClock::new(),
+            discovery: DiscoveryMode::from_env(),
+            paging: GammaPageConfig::from_env(),
+            instruments: None,
         }
     }
 
+    /// Serve known slugs from a persistent instrument cache (and add what Gamma returns to it)
+    pub fn with_instruments(mut self, instruments: InstrumentCache) -> Self {
+        self.instruments = Some(instruments);
+        self
+    }
+
+    pub fn series(&self) -> MarketSeries {
+        self.series
+    }
+
+    /// Markets per interval when every asset is listed
+    pub fn expected_markets(&self) -> usize {
+        UPDOWN_ASSETS.len()
+    }
+
+    /// Exchange-synchronized clock (also refined from every Gamma response's Date header)
+    pub fn clock(&self) -> &ExchangeClock {
+        &self.clock
+    }
+
+    /// Estimate the exchange clock offset from the CLOB's /time endpoint
+    pub async fn sync_clock(&self) -> Result<()> {
+        clock_skew::sync(&self.http, POLY_CLOB_HOST, |secs, sent, recv| self.clock.observe(secs, sent, recv)).await?;
+        info!("[UPDOWN] Exchange clock offset {:+}ms (±{}ms)",
+              self.clock.offset_ms(), self.clock.uncertainty_ms().unwrap_or(0) / 2);
+        Ok(())
+    }
+
     /// Scan for active Up/Down markets
     ///
-    /// Returns only the CURRENT active 15-minute market for each asset
+    /// Returns only the CURRENT active market of the series for each asset
     pub async fn scan_active_markets(&self) -> Result<Vec<ActiveUpDownMarket>> {
         self.scan_markets_for_interval(0).await
     }
@@ -112,47 +570,49 @@ impl UpDownScanner {
     /// Scan for markets N intervals ahead
     ///
     /// offset = 0: current interval
-    /// offset = 1: next interval (15 min ahead)
-    /// offset = -1: previous interval (15 min ago)
+    /// offset = 1: next interval (one cycle ahead)
+    /// offset = -1: previous interval (one cycle ago)
     pub async fn scan_markets_for_interval(&self, offset: i32) -> Result<Vec<ActiveUpDownMarket>> {
-        let now = current_timestamp();
+        // Markets are identified by the END timestamp of their interval
+        let target_interval_end = self.series.interval_end(self.clock.now_secs(), offset);
+        self.scan_markets_ending(target_interval_end).await
+    }
 
-        // Generate candidate slugs
+    /// Scan for the markets of the interval ending at `interval_end` (fixed target, so
+    /// retries near a boundary can't slide onto the following interval)
+    pub async fn scan_markets_ending(&self, interval_end: u64) -> Result<Vec<ActiveUpDownMarket>> {
+        let now = self.clock.now_secs();
+
+        // Generate candidate slugs (instruments already cached need no Gamma request)
         let mut candidates = Vec::new();
+        let mut cached = Vec::new();
 
         for asset in UPDOWN_ASSETS {
-            // Find the END of the target 15-minute interval
-            // Markets are identified by their end timestamp
-            let base_interval_end = ((now / MARKET_INTERVAL_SECS) + 1) * MARKET_INTERVAL_SECS;
-            let target_interval_end = if offset >= 0 {
-                base_interval_end + (offset as u64 * MARKET_INTERVAL_SECS)
-            } else {
-                base_interval_end - ((-offset) as u64 * MARKET_INTERVAL_SECS)
-            };
-
-            let slug = format!("{}-updown-15m-{}", asset, target_interval_end);
-            candidates.push((asset.to_string(), slug, target_interval_end));
+            let slug = self.series.slug(asset, interval_end);
+            match self.instruments.as_ref().and_then(|c| c.get(&slug, now)) {
+                Some(market) => cached.push(market),
+                None => candidates.push((asset.to_string(), slug, interval_end)),
+            }
         }
 
-        info!("[UPDOWN] Scanning {} candidate market slugs...", candidates.len());
+        if cached.is_empty() {
+            info!("[UPDOWN] Scanning {} candidate market slugs...", candidates.len());
+        } else {
+            info!("[UPDOWN] {} markets from the instrument cache, scanning {} candidate slugs...", cached.len(), candidates.len());
+        }
 
         // Query all candidates in parallel
         let mut tasks = Vec::new();
 
+        self.cache.prune(now);
+
         for (asset, slug, end_time) in candidates {
-            let http = self.http.clone();
             tasks.push(async move {
-                match query_market_by_slug(&http, &slug).await {
+                match self.query_market(&asset, &slug, end_time).await {
                     Ok(Some(market)) if market.is_active() => {
-                        if let Some((yes_token, no_token)) = market.get_token_ids() {
-                            Some(ActiveUpDownMarket {
-                                slug: slug.clone(),
-                                asset: asset.clone(),
-                                question: market.question.clone(),
-                                yes_token,
-                                no_token,
-                                end_timestamp: end_time,
-                            })
+                        if let Some((yes_token, _)) = market.get_token_ids() {
+                            let fee_rate_bps = self.fee_rate_bps(&market, &yes_token).await?;
+                            ActiveUpDownMarket::from_market(&market, &asset, end_time, fee_rate_bps)
                         } else {
                             debug!("[UPDOWN] Market {} has no token IDs", slug);
                             None
@@ -176,19 +636,186 @@ impl UpDownScanner {
 
         // Wait for all queries
         let results = futures_util::future::join_all(tasks).await;
-        let active_markets: Vec<_> = results.into_iter().filter_map(|r| r).collect();
+        let fetched: Vec<_> = results.into_iter().flatten().collect();
+
+        if let Some(instruments) = &self.instruments {
+            if fetched.iter().filter(|m| instruments.insert(m)).count() > 0 {
+                if let Err(e) = instruments.save(now) {
+                    warn!("[UPDOWN] Failed to save instrument cache: {}", e);
+                }
+            }
+        }
+        let mut active_markets = cached;
+        active_markets.extend(fetched);
+        apply_overrides(&overrides::current(), &mut active_markets);
 
         info!("[UPDOWN] Found {} active markets", active_markets.len());
         for market in &active_markets {
-            info!("  ✅ {} | {} | ends in {}s",
+            info!("  ✅ {} | {} | ends in {}s | fee {}bps",
                   market.asset.to_uppercase(),
                   market.question,
-                  market.end_timestamp.saturating_sub(now));
+                  market.end_timestamp.saturating_sub(now),
+                  market.fee_rate_bps);
         }
 
         Ok(active_markets)
     }
 
+    /// Open binary markets of a configured market set, soonest ending first (up to its
+    /// max_markets), as markets the YES+NO check runs on with the set name as their asset
+    pub async fn scan_market_set(&self, set: &MarketSet) -> Result<Vec<ActiveUpDownMarket>> {
+        let markets: Vec<UpDownMarket> = match &set.tag {
+            Some(tag) => {
+                let url = format!("{}/events?tag_slug={}&closed=false&active=true", GAMMA_API_BASE, tag);
+                gamma::fetch_all::<GammaEvent>(&self.http, &url, &self.paging).await?
+                    .items.into_iter().flat_map(|e| e.markets).collect()
+            }
+            None => {
+                let url = format!("{}/markets?closed=false&active=true", GAMMA_API_BASE);
+                gamma::fetch_all::<UpDownMarket>(&self.http, &url, &self.paging).await?.items
+            }
+        };
+        let now = self.clock.now_secs();
+        let mut selected: Vec<(UpDownMarket, String, u64)> = markets.into_iter()
+            .filter(|m| m.is_active() && set.matches(&m.question))
+            // Binary markets only: a multi-outcome event lists each outcome as its own YES/NO market
+            .filter(|m| m.outcomes.as_ref().is_none_or(|o| o.len() == 2))
+            .filter_map(|m| {
+                let (yes, _) = m.get_token_ids()?;
+                let end = m.end_timestamp().filter(|end| set.in_range(*end, now))?;
+                Some((m, yes, end))
+            })
+            .collect();
+        selected.sort_by_key(|(m, .., end)| (*end, m.slug.clone()));
+        selected.dedup_by(|a, b| a.0.slug == b.0.slug);
+        selected.truncate(set.max_markets);
+
+        let mut active = Vec::with_capacity(selected.len());
+        for (market, yes_token, end_timestamp) in selected {
+            let Some(fee_rate_bps) = self.fee_rate_bps(&market, &yes_token).await else { continue };
+            active.extend(ActiveUpDownMarket::from_market(&market, &set.name.to_lowercase(), end_timestamp, fee_rate_bps));
+        }
+        apply_overrides(&overrides::current(), &mut active);
+        Ok(active)
+    }
+
+    /// Taker fee rate of a market from the CLOB, else Gamma's takerBaseFee; None if neither
+    /// answers (the market is skipped and rescanned rather than traded as fee-free)
+    async fn fee_rate_bps(&self, market: &UpDownMarket, token_id: &str) -> Option<u32> {
+        match fetch_fee_rate_bps(&self.http, POLY_CLOB_HOST, token_id).await {
+            Ok(bps) => Some(bps),
+            Err(e) => match market.taker_base_fee.filter(|f| (0.0..=10_000.0).contains(f)) {
+                Some(bps) => {
+                    debug!("[UPDOWN] Fee rate of {} from Gamma ({})", market.slug, e);
+                    Some(bps.round() as u32)
+                }
+                None => {
+                    warn!("[UPDOWN] Fee rate of {} unavailable: {}", market.slug, e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// Find one asset's market for the interval using the configured discovery mode
+    async fn query_market(&self, asset: &str, slug: &str, interval_end: u64) -> Result<Option<UpDownMarket>> {
+        if self.discovery == DiscoveryMode::Events {
+            match self.query_market_via_events(asset, interval_end).await {
+                Ok(Some(market)) => return Ok(Some(market)),
+                Ok(None) => debug!("[UPDOWN] No {} event for {} - trying slug", self.series.series_slug(asset), interval_end),
+                Err(e) => warn!("[UPDOWN] Series lookup for {} failed: {} - trying slug", asset, e),
+            }
+        }
+        self.query_market_by_slug(slug).await
+    }
+
+    /// Enumerate the asset's series events and take the market of the one ending at the interval
+    async fn query_market_via_events(&self, asset: &str, interval_end: u64) -> Result<Option<UpDownMarket>> {
+        let series_slug = self.series.series_slug(asset);
+        let url = format!("{}/series?slug={}", GAMMA_API_BASE, series_slug);
+        let series: Vec<GammaSeries> = self.http.get(&url).send().await?.error_for_status()?.json().await?;
+        let ids: Vec<u64> = series.iter().map(|s| s.id).filter(|id| *id > 0).collect();
+        let mut events: Vec<GammaEvent> = series.into_iter().flat_map(|s| s.events).collect();
+
+        // Busy series (1m) have more open events than the listing embeds: page through them
+        if pick_event(&events, interval_end, self.series.interval_secs()).is_none() {
+            for id in ids {
+                let url = format!("{}/events?series_id={}&closed=false", GAMMA_API_BASE, id);
+                events.extend(gamma::fetch_all::<GammaEvent>(&self.http, &url, &self.paging).await?.items);
+            }
+        }
+
+        let Some(event) = pick_event(&events, interval_end, self.series.interval_secs()) else {
+            return Ok(None);
+        };
+        if let Some(market) = event.markets.first() {
+            return Ok(Some(market.clone()));
+        }
+
+        // Series listings omit the markets - fetch the event itself
+        let url = format!("{}/events?slug={}", GAMMA_API_BASE, event.slug);
+        let events: Vec<GammaEvent> = self.http.get(&url).send().await?.error_for_status()?.json().await?;
+        Ok(events.into_iter().next().and_then(|e| e.markets.into_iter().next()))
+    }
+
+    /// Query Gamma API for a market by slug (cached, conditional on ETag / Last-Modified)
+    async fn query_market_by_slug(&self, slug: &str) -> Result<Option<UpDownMarket>> {
+        if let Some(market) = self.cache.fresh_at(slug, Instant::now()) {
+            return Ok(Some(market));
+        }
+        self.fetch_market_by_slug(slug).await
+    }
+
+    /// Ask Gamma for the market whatever the cache's TTL says (conditionally, so an unchanged
+    /// market costs a 304)
+    async fn fetch_market_by_slug(&self, slug: &str) -> Result<Option<UpDownMarket>> {
+        let url = format!("{}/markets?slug={}", GAMMA_API_BASE, slug);
+        let mut req = self.http.get(&url);
+        let (etag, last_modified) = self.cache.validators(slug);
+        if let Some(etag) = &etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &last_modified {
+            req = req.header(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let sent = local_ms();
+        let resp = req.send().await?;
+        if let Some(date) = header_string(&resp, DATE).as_deref().and_then(clock_skew::http_date_secs) {
+            self.clock.observe(date, sent, local_ms());
+        }
+
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            debug!("[UPDOWN] {} not modified", slug);
+            return Ok(self.cache.revalidate_at(slug, Instant::now()));
+        }
+
+        if !resp.status().is_success() {
+            return Ok(None);
+        }
+
+        let etag = header_string(&resp, ETAG);
+        let last_modified = header_string(&resp, LAST_MODIFIED);
+
+        let markets: Vec<UpDownMarket> = resp.json().await?;
+        let market = markets.into_iter().next();
+        if let Some(market) = &market {
+            self.cache.store_at(slug, market.clone(), etag, last_modified, Instant::now());
+        }
+        Ok(market)
+    }
+
+    /// Current books of `tokens` from the CLOB's batch /books endpoint (one request)
+    pub async fn fetch_books(&self, tokens: &[String]) -> Result<Vec<BookSnapshot>> {
+        polymarket_clob::fetch_books(&self.http, POLY_CLOB_HOST, tokens, Duration::from_secs(BOOKS_TIMEOUT_SECS)).await
+    }
+
+    /// Re-fetch one market's metadata (accepting_orders / closed / endDate) by slug. Always
+    /// goes to Gamma: a halt or close must not wait out the cache TTL.
+    pub async fn refresh_market(&self, slug: &str) -> Result<Option<UpDownMarket>> {
+        self.fetch_market_by_slug(slug).await
+    }
+
     /// Continuous scanner - runs in a loop, refreshing active markets
     pub async fn run_continuous_scan<F>(&self, mut on_update: F) -> Result<()>
     where
@@ -211,26 +838,9 @@ impl UpDownScanner {
     }
 }
 
-/// Query Gamma API for a market by slug
-async fn query_market_by_slug(http: &reqwest::Client, slug: &str) -> Result<Option<UpDownMarket>> {
-    let url = format!("{}/markets?slug={}", GAMMA_API_BASE, slug);
-
-    let resp = http.get(&url).send().await?;
-
-    if !resp.status().is_success() {
-        return Ok(None);
-    }
-
-    let markets: Vec<UpDownMarket> = resp.json().await?;
-    Ok(markets.into_iter().next())
-}
-
-/// Get current Unix timestamp
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
+/// Response header as an owned string (validators for the next conditional request)
+fn header_string(resp: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    resp.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from)
 }
 
 /// Deserialize a field that can be either a string or a number
@@ -238,7 +848,7 @@ fn deserialize_string_or_number<'de, D>(deserializer: D) -> Result<u64, D::Error
 where
     D: serde::Deserializer<'de>,
 {
-    use serde::de::{self, Deserialize};
+    use serde::de;
 
     struct StringOrNumber;
 
@@ -336,4 +946,177 @@ mod tests {
         let current_interval_start = (now / MARKET_INTERVAL_SECS) * MARKET_INTERVAL_SECS;
         assert_eq!(current_interval_start, 1766099700); // Should round down to interval start
     }
+
+    #[test]
+    fn test_series_slugs_and_intervals() {
+        let now = 1766100550u64;
+        assert_eq!(MarketSeries::FifteenMin.slug("btc", MarketSeries::FifteenMin.interval_end(now, 0)),
+                   "btc-updown-15m-1766100600");
+
+        let five = MarketSeries::parse("5m").unwrap();
+        assert_eq!(five.interval_end(now, 0), 1766100600);
+        assert_eq!(five.interval_end(now, 1), 1766100900);
+        assert_eq!(five.slug("eth", 1766100900), "eth-updown-5m-1766100900");
+
+        let one = MarketSeries::OneMin;
+        assert_eq!(one.interval_end(now, 0), 1766100600);
+        assert_eq!(one.interval_end(now, -1), 1766100540);
+        assert!(one.preload_buffer_secs() < one.interval_secs());
+        // Missing next-interval slugs are retried several times inside the preload window
+        for series in [MarketSeries::FifteenMin, five, one] {
+            assert!((2..=5).contains(&series.preload_retry_secs()));
+            assert!(series.preload_retry_secs() * 2 <= series.preload_buffer_secs());
+        }
+        assert!(MarketSeries::parse("2m").is_none());
+    }
+
+    #[test]
+    fn test_market_and_legacy_keys() {
+        let market = ActiveUpDownMarket {
+            slug: "btc-updown-15m-1766100600".into(), asset: "btc".into(), question: "Bitcoin Up or Down".into(),
+            yes_token: "1".into(), no_token: "2".into(), end_timestamp: 1766100601, tick_size: 0.01,
+            min_size: 5.0, fee_rate_bps: 0,
+        };
+        // End from the slug, not Gamma's (movable) end date
+        assert_eq!(market.key().id(), "polymarket:btc-updown-15m-1766100600:1766100600");
+
+        // Opened 23:20 UTC → the interval ending 23:30 (6:30PM ET)
+        let key = MarketSeries::FifteenMin
+            .legacy_key("Bitcoin Up or Down - December 18, 6:15PM-6:30PM ET", "2025-12-18T23:20:11+00:00")
+            .unwrap();
+        assert_eq!(key.market, market.slug);
+        assert!(key.yes_token.is_empty());
+        assert!(MarketSeries::FifteenMin.legacy_key("Will the Lakers win?", "2025-12-18T23:20:11+00:00").is_none());
+    }
+
+    #[test]
+    fn test_overrides_drop_blacklisted_and_pin_tokens() {
+        let market = |asset: &str| ActiveUpDownMarket {
+            slug: format!("{}-updown-15m-1766100600", asset), asset: asset.into(), question: "q".into(),
+            yes_token: "111".into(), no_token: "222".into(), end_timestamp: 1766100600, tick_size: 0.01,
+            min_size: 5.0, fee_rate_bps: 0,
+        };
+        let overrides = Overrides::parse(r#"
+            blacklist = ["sol-updown-15m-1766100600"]
+            [markets."btc-updown-15m-1766100600"]
+            yes_token = "222"
+            [markets."eth-updown-15m-1766100600"]
+            yes_token = "999"
+        "#).unwrap();
+        let mut markets = vec![market("btc"), market("eth"), market("sol")];
+        apply_overrides(&overrides, &mut markets);
+        assert_eq!(markets.len(), 2);
+        assert_eq!((markets[0].yes_token.as_str(), markets[0].no_token.as_str()), ("222", "111"));
+        // A pin matching neither token leaves the scan as it was
+        assert_eq!(markets[1].yes_token, "111");
+    }
+
+    #[test]
+    fn test_rest_books_parse_as_snapshots() {
+        // POST /books response: WS book fields plus market metadata
+        let json = r#"[{"market": "0xabc", "asset_id": "123", "timestamp": "1766100000000", "hash": "0x1",
+                        "bids": [{"price": "0.47", "size": "120"}], "asks": [{"price": "0.49", "size": "80"}],
+                        "min_order_size": "5", "tick_size": "0.01", "neg_risk": false}]"#;
+        let books: Vec<BookSnapshot> = serde_json::from_str(json).unwrap();
+        assert_eq!(books[0].asset_id, "123");
+        assert!(books[0].is_well_formed());
+        assert_eq!(books[0].event_type, None);
+    }
+
+    fn sample_market(slug: &str) -> UpDownMarket {
+        serde_json::from_value(serde_json::json!({
+            "id": "1", "question": "Bitcoin Up or Down", "slug": slug,
+            "active": true, "closed": false, "acceptingOrders": true
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_end_timestamp_and_halt_flags() {
+        let mut market = sample_market("btc-updown-15m-1766100600");
+        market.end_date = Some("2025-12-18T23:30:00Z".into());
+        assert_eq!(market.end_timestamp(), Some(1766100600));
+        assert!(market.is_active());
+
+        market.accepting_orders = Some(false);
+        assert!(!market.is_active());
+    }
+
+    #[test]
+    fn test_reward_params() {
+        let mut market = sample_market("btc-updown-15m-1766100600");
+        assert_eq!(market.reward_params(), None);
+        market = serde_json::from_value(serde_json::json!({
+            "id": 1, "question": "q", "slug": "s", "rewardsMaxSpread": 3.5, "rewardsMinSize": 50,
+            "clobRewards": [{"rewardsDailyRate": 20}, {"rewardsDailyRate": 5.5}]
+        })).unwrap();
+        assert_eq!(market.reward_params(), Some(RewardParams { max_spread_cents: 3.5, min_size: 50.0, daily_rate_usd: 25.5 }));
+        market.clob_rewards.clear();
+        assert_eq!(market.reward_params(), None);
+    }
+
+    #[test]
+    fn test_pick_event_tolerates_rounding() {
+        let json = r#"[
+            {"slug": "btc-updown-15m-1766099700", "endDate": "2025-12-18T23:15:00Z", "closed": true},
+            {"slug": "btc-updown-15m-1766100600", "endDate": "2025-12-18T23:30:01Z", "closed": false,
+             "markets": [{"id": "7", "question": "Bitcoin Up or Down", "slug": "btc-updown-15m-1766100600",
+                          "clobTokenIds": "[\"1\", \"2\"]", "active": true, "closed": false, "acceptingOrders": true}]},
+            {"slug": "btc-updown-15m-1766101500", "closed": false}
+        ]"#;
+        let events: Vec<GammaEvent> = serde_json::from_str(json).unwrap();
+        assert_eq!(events[2].end_timestamp(), Some(1766101500)); // from slug
+
+        // endDate one second past the boundary still matches
+        let event = pick_event(&events, 1766100600, 900).unwrap();
+        assert_eq!(event.slug, "btc-updown-15m-1766100600");
+        assert_eq!(event.markets[0].get_token_ids(), Some(("1".into(), "2".into())));
+
+        // Closed events are skipped; nothing within half an interval -> None
+        assert!(pick_event(&events, 1766099700, 900).is_none());
+        assert!(pick_event(&events, 1766103300, 900).is_none());
+    }
+
+    #[test]
+    fn test_exchange_clock_bounds_narrow() {
+        let clock = ExchangeClock::new();
+        assert_eq!(clock.offset_ms(), 0);
+        assert_eq!(clock.uncertainty_ms(), None);
+
+        // Exchange runs exactly 1.25s ahead; each reply takes 20ms round trip
+        let true_offset = 1250i64;
+        for local in [10_000_000u64, 10_000_230, 10_000_470, 10_000_690, 10_000_910] {
+            let server_secs = (local as i64 + 10 + true_offset) as u64 / 1000;
+            clock.observe(server_secs, local, local + 20);
+        }
+        let width = clock.uncertainty_ms().unwrap();
+        assert!(width < 300, "bounds still {}ms wide", width);
+        assert!((clock.offset_ms() - true_offset).abs() <= width);
+
+        // Local clock stepped by 10s: contradictory sample restarts the bounds
+        clock.observe(20_000, 10_000_000, 10_000_020);
+        assert_eq!(clock.uncertainty_ms(), Some(1019));
+        assert!((clock.offset_ms() - 10_000_000).abs() < 1000);
+    }
+
+    #[test]
+    fn test_scan_cache_ttl_and_revalidation() {
+        let cache = ScanCache::new(Duration::from_secs(20));
+        let t0 = Instant::now();
+        let slug = "btc-updown-15m-1766100600";
+
+        assert!(cache.fresh_at(slug, t0).is_none());
+        cache.store_at(slug, sample_market(slug), Some("\"abc\"".into()), None, t0);
+
+        assert!(cache.fresh_at(slug, t0 + Duration::from_secs(10)).is_some());
+        assert!(cache.fresh_at(slug, t0 + Duration::from_secs(25)).is_none());
+        assert_eq!(cache.validators(slug).0.as_deref(), Some("\"abc\""));
+
+        // 304 restarts the TTL
+        assert!(cache.revalidate_at(slug, t0 + Duration::from_secs(25)).is_some());
+        assert!(cache.fresh_at(slug, t0 + Duration::from_secs(30)).is_some());
+
+        // Ended intervals are pruned
+        cache.prune(1766100600);
+        assert!(cache.validators(slug).0.is_none());
+    }
 }
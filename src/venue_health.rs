@@ -0,0 +1,495 @@
+// src/venue_health.rs
+// Venue health - polls exchange status endpoints and tracks API error rates,
+// scaling trade size down or pausing a venue while it is unhealthy or its WS is flapping
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::kalshi::KalshiApiClient;
+use crate::metrics;
+
+/// Polymarket public status page (Atlassian Statuspage format)
+const POLY_STATUS_URL: &str = "https://status.polymarket.com/api/v2/status.json";
+
+/// Outcomes needed before the error rate is trusted
+const MIN_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Venue {
+    Kalshi,
+    Polymarket,
+}
+
+impl Venue {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Venue::Kalshi => "kalshi",
+            Venue::Polymarket => "polymarket",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "kalshi" => Some(Venue::Kalshi),
+            "polymarket" => Some(Venue::Polymarket),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Venue::Kalshi => 0,
+            Venue::Polymarket => 1,
+        }
+    }
+}
+
+/// Health of a venue, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum HealthLevel {
+    #[default]
+    Healthy,
+    /// Incident reported or elevated errors - trade reduced size
+    Degraded,
+    /// Outage / trading halted - no new trades
+    Down,
+}
+
+impl HealthLevel {
+    /// Map a Statuspage indicator (none / minor / major / critical)
+    pub fn from_indicator(indicator: &str) -> Self {
+        match indicator {
+            "none" => HealthLevel::Healthy,
+            "minor" | "maintenance" => HealthLevel::Degraded,
+            _ => HealthLevel::Down,
+        }
+    }
+}
+
+/// Venue health configuration from environment
+#[derive(Debug, Clone)]
+pub struct VenueHealthConfig {
+    /// Seconds between status polls (0 = disabled)
+    pub poll_secs: u64,
+
+    /// Size multiplier while a venue is degraded
+    pub degraded_size_factor: f64,
+
+    /// Number of recent API calls the error rate is computed over
+    pub error_window: usize,
+
+    /// Seconds an API call outcome counts toward the error rate; older ones age out,
+    /// so a venue paused for errors recovers without needing new trades
+    pub error_max_age_secs: u64,
+
+    /// Error rate at which a venue counts as degraded
+    pub degraded_error_rate: f64,
+
+    /// Error rate at which a venue counts as down
+    pub down_error_rate: f64,
+
+    /// WS reconnects within `storm_window_secs` that pause a venue (0 = disabled)
+    pub storm_reconnects: usize,
+
+    /// Window reconnects are counted over
+    pub storm_window_secs: u64,
+
+    /// Seconds without a reconnect before a paused venue resumes
+    pub storm_cooldown_secs: u64,
+}
+
+impl VenueHealthConfig {
+    pub fn from_env() -> Self {
+        Self {
+            poll_secs: std::env::var("VENUE_HEALTH_POLL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+
+            degraded_size_factor: std::env::var("VENUE_DEGRADED_SIZE_FACTOR")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.5),
+
+            error_window: std::env::var("VENUE_ERROR_WINDOW")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20),
+
+            error_max_age_secs: std::env::var("VENUE_ERROR_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+
+            degraded_error_rate: std::env::var("VENUE_DEGRADED_ERROR_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.25),
+
+            down_error_rate: std::env::var("VENUE_DOWN_ERROR_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.6),
+
+            storm_reconnects: std::env::var("WS_STORM_RECONNECTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            storm_window_secs: std::env::var("WS_STORM_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+
+            storm_cooldown_secs: std::env::var("WS_STORM_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(120),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct VenueState {
+    /// Level from the venue's status endpoint
+    reported: HealthLevel,
+    /// Recent API call outcomes (when, true = ok)
+    outcomes: VecDeque<(Instant, bool)>,
+    /// Recent WS reconnects
+    reconnects: VecDeque<Instant>,
+    /// Reconnect storm in progress: paused until this long after the last reconnect
+    storm_until: Option<Instant>,
+    /// Last effective level (for transition logging)
+    effective: HealthLevel,
+}
+
+/// Per-venue health combining reported status and observed error rates
+pub struct VenueHealth {
+    config: VenueHealthConfig,
+    venues: Mutex<[VenueState; 2]>,
+}
+
+impl VenueHealth {
+    pub fn new(config: VenueHealthConfig) -> Self {
+        Self {
+            config,
+            venues: Mutex::new(Default::default()),
+        }
+    }
+
+    pub fn config(&self) -> &VenueHealthConfig {
+        &self.config
+    }
+
+    /// Record the outcome of an order API call
+    pub fn record_outcome(&self, venue: Venue, ok: bool) {
+        self.record_outcome_at(venue, ok, Instant::now());
+    }
+
+    fn record_outcome_at(&self, venue: Venue, ok: bool, now: Instant) {
+        let mut venues = self.venues.lock().unwrap();
+        let state = &mut venues[venue.index()];
+        if state.outcomes.len() >= self.config.error_window.max(1) {
+            state.outcomes.pop_front();
+        }
+        state.outcomes.push_back((now, ok));
+        self.refresh(venue, state, now);
+    }
+
+    /// Record a WS reconnect of the venue's market data feed
+    pub fn record_reconnect(&self, venue: Venue) {
+        self.record_reconnect_at(venue, Instant::now());
+    }
+
+    /// Record the end of a WS feed session: only one that connected and then dropped counts as
+    /// a reconnect (an idle session with nothing to subscribe, or a failed connect, opened no socket)
+    #[allow(dead_code)] // the main bot's feeds never return idle
+    pub fn record_feed_exit(&self, venue: Venue, connected: bool) {
+        self.record_feed_exit_at(venue, connected, Instant::now());
+    }
+
+    fn record_feed_exit_at(&self, venue: Venue, connected: bool, now: Instant) {
+        if connected {
+            self.record_reconnect_at(venue, now);
+        }
+    }
+
+    fn record_reconnect_at(&self, venue: Venue, now: Instant) {
+        if self.config.storm_reconnects == 0 {
+            return;
+        }
+        let mut venues = self.venues.lock().unwrap();
+        let state = &mut venues[venue.index()];
+        let window = Duration::from_secs(self.config.storm_window_secs);
+        while state.reconnects.front().is_some_and(|t| now.duration_since(*t) > window) {
+            state.reconnects.pop_front();
+        }
+        state.reconnects.push_back(now);
+        if state.storm_until.is_some() || state.reconnects.len() >= self.config.storm_reconnects {
+            if state.storm_until.is_none() {
+                warn!("[HEALTH] 🌪️ {} WS reconnected {} times in {}s - pausing execution until stable for {}s",
+                      venue.name(), state.reconnects.len(), self.config.storm_window_secs,
+                      self.config.storm_cooldown_secs);
+                metrics::incr(&format!("venue.{}.ws_storms", venue.name()));
+            }
+            state.storm_until = Some(now + Duration::from_secs(self.config.storm_cooldown_secs));
+        }
+        self.refresh(venue, state, now);
+    }
+
+    /// Apply a level read from the venue's status endpoint
+    pub fn report_status(&self, venue: Venue, level: HealthLevel) {
+        let mut venues = self.venues.lock().unwrap();
+        let state = &mut venues[venue.index()];
+        state.reported = level;
+        self.refresh(venue, state, Instant::now());
+    }
+
+    fn synthesized(&self, state: &VenueState) -> HealthLevel {
+        if state.outcomes.len() < MIN_SAMPLES {
+            return HealthLevel::Healthy;
+        }
+        let errors = state.outcomes.iter().filter(|(_, ok)| !*ok).count();
+        let rate = errors as f64 / state.outcomes.len() as f64;
+        if rate >= self.config.down_error_rate {
+            HealthLevel::Down
+        } else if rate >= self.config.degraded_error_rate {
+            HealthLevel::Degraded
+        } else {
+            HealthLevel::Healthy
+        }
+    }
+
+    fn refresh(&self, venue: Venue, state: &mut VenueState, now: Instant) {
+        if state.storm_until.is_some_and(|until| now >= until) {
+            info!("[HEALTH] {} WS stable for {}s - reconnect storm over", venue.name(), self.config.storm_cooldown_secs);
+            state.storm_until = None;
+            state.reconnects.clear();
+        }
+        let max_age = Duration::from_secs(self.config.error_max_age_secs);
+        while state.outcomes.front().is_some_and(|(t, _)| now.duration_since(*t) > max_age) {
+            state.outcomes.pop_front();
+        }
+        let storm = if state.storm_until.is_some() { HealthLevel::Down } else { HealthLevel::Healthy };
+        let level = state.reported.max(self.synthesized(state)).max(storm);
+        if level != state.effective {
+            match level {
+                HealthLevel::Healthy => info!("[HEALTH] ✅ {} healthy again - resuming full size", venue.name()),
+                HealthLevel::Degraded => warn!("[HEALTH] ⚠️ {} degraded - trading at {:.0}% size",
+                                               venue.name(), self.config.degraded_size_factor * 100.0),
+                HealthLevel::Down => warn!("[HEALTH] 🛑 {} down - pausing new trades", venue.name()),
+            }
+            metrics::incr(&format!("venue.{}.transitions", venue.name()));
+            state.effective = level;
+        }
+    }
+
+    /// Effective level: the worst of reported status, error-rate health and a reconnect storm
+    pub fn level(&self, venue: Venue) -> HealthLevel {
+        self.level_at(venue, Instant::now())
+    }
+
+    fn level_at(&self, venue: Venue, now: Instant) -> HealthLevel {
+        let mut venues = self.venues.lock().unwrap();
+        let state = &mut venues[venue.index()];
+        self.refresh(venue, state, now);
+        state.effective
+    }
+
+    /// Size multiplier for new trades on a venue (1 = full, 0 = paused)
+    pub fn size_factor(&self, venue: Venue) -> f64 {
+        match self.level(venue) {
+            HealthLevel::Healthy => 1.0,
+            HealthLevel::Degraded => self.config.degraded_size_factor.clamp(0.0, 1.0),
+            HealthLevel::Down => 0.0,
+        }
+    }
+}
+
+/// Process-wide venue health (configured from env on first use)
+pub fn health() -> &'static VenueHealth {
+    static HEALTH: OnceLock<VenueHealth> = OnceLock::new();
+    HEALTH.get_or_init(|| VenueHealth::new(VenueHealthConfig::from_env()))
+}
+
+#[derive(Deserialize)]
+struct StatusPage {
+    status: StatusPageStatus,
+}
+
+#[derive(Deserialize)]
+struct StatusPageStatus {
+    indicator: String,
+}
+
+async fn poll_polymarket(http: &reqwest::Client) -> Result<HealthLevel> {
+    let page: StatusPage = http.get(POLY_STATUS_URL).send().await?.error_for_status()?.json().await?;
+    Ok(HealthLevel::from_indicator(&page.status.indicator))
+}
+
+#[cfg(feature = "kalshi")]
+async fn poll_kalshi(kalshi: &KalshiApiClient) -> Result<HealthLevel> {
+    let status = kalshi.exchange_status().await?;
+    Ok(if status.exchange_active && status.trading_active {
+        HealthLevel::Healthy
+    } else {
+        HealthLevel::Down
+    })
+}
+
+/// Poll status endpoints for the venues in use. A failed poll leaves the last reported
+/// level in place - only the error-rate tracking reacts to API failures.
+pub fn spawn_poller(kalshi: Option<Arc<KalshiApiClient>>, polymarket: bool) {
+    let poll_secs = health().config().poll_secs;
+    if poll_secs == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client");
+        let mut ticker = tokio::time::interval(Duration::from_secs(poll_secs));
+        loop {
+            ticker.tick().await;
+            if polymarket {
+                match poll_polymarket(&http).await {
+                    Ok(level) => health().report_status(Venue::Polymarket, level),
+                    Err(e) => debug!("[HEALTH] Polymarket status poll failed: {}", e),
+                }
+            }
+            match &kalshi {
+                #[cfg(feature = "kalshi")]
+                Some(kalshi) => match poll_kalshi(kalshi).await {
+                    Ok(level) => health().report_status(Venue::Kalshi, level),
+                    Err(e) => debug!("[HEALTH] Kalshi status poll failed: {}", e),
+                },
+                #[cfg(not(feature = "kalshi"))]
+                Some(kalshi) => match **kalshi {},
+                None => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health() -> VenueHealth {
+        VenueHealth::new(VenueHealthConfig {
+            poll_secs: 0,
+            degraded_size_factor: 0.5,
+            error_window: 10,
+            error_max_age_secs: 300,
+            degraded_error_rate: 0.3,
+            down_error_rate: 0.6,
+            storm_reconnects: 3,
+            storm_window_secs: 60,
+            storm_cooldown_secs: 30,
+        })
+    }
+
+    #[test]
+    fn test_reported_incident_degrades_then_clears() {
+        let h = health();
+        h.report_status(Venue::Polymarket, HealthLevel::from_indicator("minor"));
+        assert_eq!(h.size_factor(Venue::Polymarket), 0.5);
+        assert_eq!(h.size_factor(Venue::Kalshi), 1.0);
+
+        h.report_status(Venue::Polymarket, HealthLevel::from_indicator("major"));
+        assert_eq!(h.size_factor(Venue::Polymarket), 0.0);
+
+        h.report_status(Venue::Polymarket, HealthLevel::from_indicator("none"));
+        assert_eq!(h.level(Venue::Polymarket), HealthLevel::Healthy);
+    }
+
+    #[test]
+    fn test_error_rate_synthesizes_health() {
+        let h = health();
+        for _ in 0..6 {
+            h.record_outcome(Venue::Kalshi, true);
+        }
+        for _ in 0..4 {
+            h.record_outcome(Venue::Kalshi, false);
+        }
+        // 4/10 errors
+        assert_eq!(h.level(Venue::Kalshi), HealthLevel::Degraded);
+
+        for _ in 0..3 {
+            h.record_outcome(Venue::Kalshi, false);
+        }
+        // Window slides: 7/10 errors
+        assert_eq!(h.level(Venue::Kalshi), HealthLevel::Down);
+
+        for _ in 0..10 {
+            h.record_outcome(Venue::Kalshi, true);
+        }
+        assert_eq!(h.level(Venue::Kalshi), HealthLevel::Healthy);
+    }
+
+    #[test]
+    fn test_errors_age_out() {
+        let h = health();
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        for i in 0..8 {
+            h.record_outcome_at(Venue::Polymarket, false, at(i));
+        }
+        assert_eq!(h.level_at(Venue::Polymarket, at(10)), HealthLevel::Down);
+        assert_eq!(h.size_factor(Venue::Kalshi), 1.0);
+
+        // No trades go out while down, yet the venue recovers once the errors are old
+        assert_eq!(h.level_at(Venue::Polymarket, at(302)), HealthLevel::Down);
+        assert_eq!(h.level_at(Venue::Polymarket, at(320)), HealthLevel::Healthy);
+    }
+
+    #[test]
+    fn test_reconnect_storm_pauses_until_stable() {
+        let h = health();
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        // Reconnects spread wider than the window never make a storm
+        h.record_reconnect_at(Venue::Polymarket, at(0));
+        h.record_reconnect_at(Venue::Polymarket, at(61));
+        h.record_reconnect_at(Venue::Polymarket, at(122));
+        assert_eq!(h.level_at(Venue::Polymarket, at(123)), HealthLevel::Healthy);
+
+        h.record_reconnect_at(Venue::Polymarket, at(130));
+        h.record_reconnect_at(Venue::Polymarket, at(140));
+        assert_eq!(h.level_at(Venue::Polymarket, at(141)), HealthLevel::Down);
+        assert_eq!(h.level_at(Venue::Kalshi, at(141)), HealthLevel::Healthy);
+
+        // Still flapping: the cooldown restarts from the last reconnect
+        h.record_reconnect_at(Venue::Polymarket, at(165));
+        assert_eq!(h.level_at(Venue::Polymarket, at(180)), HealthLevel::Down);
+        assert_eq!(h.level_at(Venue::Polymarket, at(195)), HealthLevel::Healthy);
+
+        // One reconnect after the storm doesn't re-trigger it
+        h.record_reconnect_at(Venue::Polymarket, at(200));
+        assert_eq!(h.level_at(Venue::Polymarket, at(201)), HealthLevel::Healthy);
+    }
+
+    #[test]
+    fn test_idle_feed_sessions_are_not_reconnects() {
+        let h = health();
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        // Nothing to monitor: the feed returns every 10s without opening a socket
+        for i in 0..10 {
+            h.record_feed_exit_at(Venue::Polymarket, false, at(i * 10));
+        }
+        assert_eq!(h.level_at(Venue::Polymarket, at(101)), HealthLevel::Healthy);
+
+        // Connections that were made and dropped still make a storm
+        for i in 11..14 {
+            h.record_feed_exit_at(Venue::Polymarket, true, at(i * 10));
+        }
+        assert_eq!(h.level_at(Venue::Polymarket, at(131)), HealthLevel::Down);
+    }
+}
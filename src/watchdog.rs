@@ -0,0 +1,261 @@
+// src/watchdog.rs
+// Process self-monitoring - RSS, open FDs and tokio tasks with leak alerts, plus periodic state dumps
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::events::{self, BotEvent};
+use crate::metrics;
+use crate::storage;
+
+/// Watchdog configuration from environment
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    /// Seconds between resource samples (0 = off)
+    pub interval_secs: u64,
+
+    /// Samples a trend is judged over
+    pub window: usize,
+
+    /// Growth (0.25 = 25%) from the older to the newer half of the window that counts as a leak
+    pub growth: f64,
+
+    /// Seconds between state dumps (0 = off)
+    pub dump_secs: u64,
+
+    /// State dumps kept in the dump directory
+    pub dump_keep: usize,
+}
+
+impl WatchdogConfig {
+    pub fn from_env() -> Self {
+        Self {
+            interval_secs: std::env::var("WATCHDOG_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+
+            window: std::env::var("WATCHDOG_WINDOW")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+
+            growth: std::env::var("WATCHDOG_GROWTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.25),
+
+            dump_secs: std::env::var("STATE_DUMP_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+
+            dump_keep: std::env::var("STATE_DUMP_KEEP")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(24),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.interval_secs > 0
+    }
+}
+
+/// Resource usage at one point in time (None where the platform doesn't expose it)
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ResourceSample {
+    pub ts: String,
+    pub rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+    pub tasks: Option<u64>,
+}
+
+/// `VmRSS` from /proc/self/status, in bytes
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+fn rss_bytes() -> Option<u64> {
+    parse_vm_rss(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+fn open_fds() -> Option<u64> {
+    let dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+    Some(std::fs::read_dir(dir).ok()?.count() as u64)
+}
+
+/// Sample this process (tasks only when called inside a tokio runtime)
+pub fn sample() -> ResourceSample {
+    ResourceSample {
+        ts: chrono::Utc::now().to_rfc3339(),
+        rss_bytes: rss_bytes(),
+        open_fds: open_fds(),
+        tasks: tokio::runtime::Handle::try_current().ok().map(|h| h.metrics().num_alive_tasks() as u64),
+    }
+}
+
+/// A full window whose newer half averages `growth` above the older half and whose latest
+/// value is at the top of the older half - a leak, not a spike that already drained
+pub fn trending_up(values: &[u64], window: usize, growth: f64) -> bool {
+    if window < 2 || values.len() < window {
+        return false;
+    }
+    let recent = &values[values.len() - window..];
+    let (older, newer) = recent.split_at(window / 2);
+    let mean = |v: &[u64]| v.iter().sum::<u64>() as f64 / v.len() as f64;
+    let older_max = older.iter().copied().max().unwrap_or(0);
+    mean(older) > 0.0
+        && mean(newer) >= mean(older) * (1.0 + growth)
+        && recent[window - 1] >= older_max
+}
+
+/// Recent values of one resource; alerts once per upward trend
+struct Trend {
+    name: &'static str,
+    values: VecDeque<u64>,
+    alerted: bool,
+}
+
+impl Trend {
+    fn new(name: &'static str) -> Self {
+        Self { name, values: VecDeque::new(), alerted: false }
+    }
+
+    fn push(&mut self, value: Option<u64>, config: &WatchdogConfig) {
+        let Some(value) = value else {
+            return;
+        };
+        metrics::set_gauge(&format!("process.{}", self.name), value as i64);
+        self.values.push_back(value);
+        while self.values.len() > config.window {
+            self.values.pop_front();
+        }
+
+        let values = self.values.make_contiguous();
+        if !trending_up(values, config.window, config.growth) {
+            self.alerted = false;
+            return;
+        }
+        if !self.alerted {
+            self.alerted = true;
+            let (from, to) = (values[0], values[values.len() - 1]);
+            warn!("[WATCHDOG] 📈 {} trending up: {} → {} over {} samples - possible leak",
+                  self.name, from, to, values.len());
+            metrics::incr(&format!("watchdog.trend.{}", self.name));
+            events::publish(BotEvent::ResourceTrend { resource: self.name.to_string(), from, to });
+        }
+    }
+}
+
+fn dump_dir() -> PathBuf {
+    storage::data_path("state_dumps")
+}
+
+/// Write one state dump and prune the oldest beyond `keep`
+fn write_dump(contents: &serde_json::Value, keep: usize) -> anyhow::Result<PathBuf> {
+    let dir = dump_dir();
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("state-{}.json", chrono::Utc::now().format("%Y%m%dT%H%M%SZ")));
+    storage::write_atomic(&path, serde_json::to_string_pretty(contents)?.as_bytes())?;
+
+    let mut dumps: Vec<PathBuf> = std::fs::read_dir(&dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("state-") && n.ends_with(".json")))
+        .collect();
+    dumps.sort();
+    let excess = dumps.len().saturating_sub(keep.max(1));
+    for old in &dumps[..excess] {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(path)
+}
+
+/// Sample resources every WATCHDOG_SECS (gauges `process.rss_bytes` / `open_fds` / `tasks`,
+/// alert on upward trends) and every STATE_DUMP_SECS write the samples, all metrics and
+/// `state()` (bot-specific internals) to DATA_DIR/state_dumps/
+pub fn spawn<F, Fut>(config: WatchdogConfig, state: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = serde_json::Value> + Send,
+{
+    if !config.enabled() {
+        return;
+    }
+    info!("[WATCHDOG] Sampling RSS / FDs / tasks every {}s{}", config.interval_secs,
+          if config.dump_secs > 0 { format!(", state dumps every {}s", config.dump_secs) } else { String::new() });
+
+    tokio::spawn(async move {
+        let mut trends = [Trend::new("rss_bytes"), Trend::new("open_fds"), Trend::new("tasks")];
+        let mut recent: VecDeque<ResourceSample> = VecDeque::new();
+        let mut last_dump = tokio::time::Instant::now();
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        loop {
+            ticker.tick().await;
+            let s = sample();
+            for (trend, value) in trends.iter_mut().zip([s.rss_bytes, s.open_fds, s.tasks]) {
+                trend.push(value, &config);
+            }
+            recent.push_back(s);
+            while recent.len() > config.window {
+                recent.pop_front();
+            }
+
+            if config.dump_secs == 0 || last_dump.elapsed() < Duration::from_secs(config.dump_secs) {
+                continue;
+            }
+            last_dump = tokio::time::Instant::now();
+            let dump = serde_json::json!({
+                "ts": chrono::Utc::now().to_rfc3339(),
+                "pid": std::process::id(),
+                "resources": recent,
+                "metrics": metrics::snapshot(),
+                "state": state().await,
+            });
+            match write_dump(&dump, config.dump_keep) {
+                Ok(path) => info!("[WATCHDOG] State dump: {}", path.display()),
+                Err(e) => warn!("[WATCHDOG] State dump failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status = "Name:\tarb-bot\nVmPeak:\t  200000 kB\nVmRSS:\t   51200 kB\nThreads:\t8\n";
+        assert_eq!(parse_vm_rss(status), Some(51200 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tx\n"), None);
+    }
+
+    #[test]
+    fn test_trend_flags_leaks_not_spikes() {
+        // Sockets piling up across reconnects
+        assert!(trending_up(&[100, 104, 110, 115, 121, 130, 138, 150, 161, 170], 10, 0.25));
+        // Reconnect storm that drained back down
+        assert!(!trending_up(&[100, 102, 101, 250, 240, 230, 110, 101, 100, 99], 10, 0.25));
+        // Steady sawtooth
+        assert!(!trending_up(&[100, 120, 100, 120, 100, 120, 100, 120, 100, 120], 10, 0.25));
+        // Not enough samples yet
+        assert!(!trending_up(&[100, 200, 300], 10, 0.25));
+    }
+
+    #[test]
+    fn test_sample_reads_this_process() {
+        let s = sample();
+        if cfg!(target_os = "linux") {
+            assert!(s.rss_bytes.unwrap() > 0);
+            assert!(s.open_fds.unwrap() > 0);
+        }
+        assert_eq!(s.tasks, None, "no runtime in a plain test");
+    }
+}
@@ -0,0 +1,174 @@
+// src/what_if.rs
+// What-if fills - a YES + NO pair of a given size walked through the current books the way the
+// bot's FAK orders would fill, with fees and net edge
+
+use crate::book::LocalBook;
+use crate::types::poly_fee;
+
+/// Fills below this are rounding
+const EPSILON: f64 = 1e-9;
+
+/// One leg's FAK buy swept through the asks up to its limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegFill {
+    pub limit: f64,
+    pub wanted: f64,
+    /// (price, contracts) per level taken, best first
+    pub fills: Vec<(f64, f64)>,
+    pub fee_rate_bps: u32,
+}
+
+impl LegFill {
+    /// Take asks at or below `limit` until `contracts` are filled (FAK: the rest is cancelled)
+    pub fn sweep(book: &LocalBook, contracts: f64, limit: f64, fee_rate_bps: u32) -> Self {
+        let mut left = contracts;
+        let mut fills = Vec::new();
+        for (price, size) in book.top_asks(usize::MAX) {
+            if left <= EPSILON || price > limit + EPSILON {
+                break;
+            }
+            let take = size.min(left);
+            fills.push((price, take));
+            left -= take;
+        }
+        Self { limit, wanted: contracts, fills, fee_rate_bps }
+    }
+
+    pub fn filled(&self) -> f64 {
+        self.fills.iter().map(|(_, s)| s).sum()
+    }
+
+    /// Cost and fees of the first `contracts` filled (the cheapest levels)
+    pub fn cost_of(&self, contracts: f64) -> (f64, f64) {
+        let mut left = contracts;
+        let (mut cost, mut fees) = (0.0, 0.0);
+        for &(price, size) in &self.fills {
+            let take = size.min(left);
+            cost += take * price;
+            fees += take * poly_fee(price, self.fee_rate_bps);
+            left -= take;
+            if left <= EPSILON {
+                break;
+            }
+        }
+        (cost, fees)
+    }
+
+    /// Average fill price (None if nothing filled)
+    pub fn vwap(&self) -> Option<f64> {
+        let filled = self.filled();
+        (filled > EPSILON).then(|| self.cost_of(filled).0 / filled)
+    }
+}
+
+/// A YES + NO pair of `contracts` per leg against the current books
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairFill {
+    pub contracts: f64,
+    pub yes: LegFill,
+    pub no: LegFill,
+}
+
+impl PairFill {
+    /// Each leg limited `ticks` ticks above its best ask (0 = at the ask, as the bot sends them)
+    pub fn simulate(yes_book: &LocalBook, no_book: &LocalBook, contracts: f64, ticks: u32, tick_size: f64,
+                    fee_rate_bps: u32) -> Self {
+        let limit = |book: &LocalBook| book.best_ask()
+            .map(|(ask, _)| (ask + ticks as f64 * tick_size).min(1.0 - tick_size))
+            .unwrap_or(0.0);
+        Self {
+            contracts,
+            yes: LegFill::sweep(yes_book, contracts, limit(yes_book), fee_rate_bps),
+            no: LegFill::sweep(no_book, contracts, limit(no_book), fee_rate_bps),
+        }
+    }
+
+    /// Complete YES + NO pairs, each paying $1 at resolution
+    pub fn matched(&self) -> f64 {
+        self.yes.filled().min(self.no.filled())
+    }
+
+    /// Contracts one leg filled beyond the other (held as a directional position)
+    pub fn unmatched(&self) -> f64 {
+        (self.yes.filled() - self.no.filled()).abs()
+    }
+
+    /// Cost + fees of the matched pairs
+    pub fn matched_cost(&self) -> (f64, f64) {
+        let (yes_cost, yes_fees) = self.yes.cost_of(self.matched());
+        let (no_cost, no_fees) = self.no.cost_of(self.matched());
+        (yes_cost + no_cost, yes_fees + no_fees)
+    }
+
+    /// Payout of the matched pairs minus what they cost, fees included (dollars)
+    pub fn net_edge(&self) -> f64 {
+        let (cost, fees) = self.matched_cost();
+        self.matched() - cost - fees
+    }
+
+    /// Net edge per matched pair (cents)
+    pub fn edge_per_contract_cents(&self) -> Option<f64> {
+        (self.matched() > EPSILON).then(|| self.net_edge() / self.matched() * 100.0)
+    }
+
+    /// Cost of the unmatched excess (capital left in one outcome)
+    pub fn unmatched_cost(&self) -> f64 {
+        let longer = if self.yes.filled() > self.no.filled() { &self.yes } else { &self.no };
+        let (all, all_fees) = longer.cost_of(longer.filled());
+        let (matched, matched_fees) = longer.cost_of(self.matched());
+        (all + all_fees) - (matched + matched_fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::BookSide;
+
+    fn book(asks: &[(&str, &str)]) -> LocalBook {
+        let mut book = LocalBook::new();
+        for (price, size) in asks {
+            book.apply_level(BookSide::Ask, price, size);
+        }
+        book
+    }
+
+    #[test]
+    fn test_sweep_stops_at_limit_and_size() {
+        let asks = book(&[("0.45", "10"), ("0.46", "5"), ("0.50", "100")]);
+        let leg = LegFill::sweep(&asks, 12.0, 0.46, 0);
+        assert_eq!(leg.fills, vec![(0.45, 10.0), (0.46, 2.0)]);
+        assert!((leg.vwap().unwrap() - (4.5 + 0.92) / 12.0).abs() < 1e-9);
+        // At the ask only the best level fills
+        assert_eq!(LegFill::sweep(&asks, 30.0, 0.45, 0).filled(), 10.0);
+        assert!(LegFill::sweep(&book(&[]), 5.0, 0.5, 0).vwap().is_none());
+    }
+
+    #[test]
+    fn test_pair_at_the_ask_vs_with_ticks() {
+        let yes = book(&[("0.48", "20"), ("0.49", "30")]);
+        let no = book(&[("0.49", "50")]);
+        let at_ask = PairFill::simulate(&yes, &no, 40.0, 0, 0.01, 0);
+        assert_eq!((at_ask.yes.filled(), at_ask.no.filled()), (20.0, 40.0));
+        assert_eq!(at_ask.matched(), 20.0);
+        assert!((at_ask.net_edge() - 20.0 * 0.03).abs() < 1e-9);
+        assert!((at_ask.unmatched_cost() - 20.0 * 0.49).abs() < 1e-9);
+
+        let one_tick = PairFill::simulate(&yes, &no, 40.0, 1, 0.01, 0);
+        assert_eq!(one_tick.matched(), 40.0);
+        // 20 at 0.97 and 20 at 0.98 per pair
+        assert!((one_tick.net_edge() - (20.0 * 0.03 + 20.0 * 0.02)).abs() < 1e-9);
+        assert!((one_tick.edge_per_contract_cents().unwrap() - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fees_reduce_edge() {
+        let yes = book(&[("0.48", "10")]);
+        let no = book(&[("0.49", "10")]);
+        let free = PairFill::simulate(&yes, &no, 10.0, 0, 0.01, 0);
+        let fees = PairFill::simulate(&yes, &no, 10.0, 0, 0.01, 1000);
+        let (_, fee) = fees.matched_cost();
+        assert!(fee > 0.0);
+        assert!((free.net_edge() - fees.net_edge() - fee).abs() < 1e-9);
+    }
+}
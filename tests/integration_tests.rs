@@ -476,6 +476,9 @@ mod infra_integration_tests {
             poly_no_token: "arb_no_token".into(),
             line_value: None,
             team_suffix: Some("CFC".into()),
+            settles_at: None,
+            poly_ends_at: None,
+            basis_risk: 0.0,
         };
 
         let market_id = state.add_pair(pair).unwrap();
@@ -622,6 +625,7 @@ mod infra_integration_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert_eq!(req.profit_cents(), 8, "Profit should be 8¢");
@@ -639,6 +643,7 @@ mod infra_integration_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         assert!(req.profit_cents() < 0, "Should calculate negative profit");
@@ -690,6 +695,9 @@ mod infra_integration_tests {
                 poly_no_token: format!("no_{}", i).into(),
                 line_value: None,
                 team_suffix: None,
+                settles_at: None,
+                poly_ends_at: None,
+                basis_risk: 0.0,
             };
 
             let id = state.add_pair(pair).unwrap();
@@ -729,6 +737,17 @@ mod infra_integration_tests {
         assert_eq!(parse_price("invalid"), 0);  // Invalid
     }
 
+    /// Test: Polymarket price range follows the market's tick
+    #[test]
+    fn test_poly_price_range_per_tick() {
+        use arb_bot::polymarket_clob::price_valid;
+        assert!(price_valid(100, 0.01) && price_valid(9900, 0.01));
+        assert!(!price_valid(50, 0.01) && !price_valid(9950, 0.01));
+        // Prices near 0 / 1 move to a 0.001 tick
+        assert!(price_valid(50, 0.001) && price_valid(9990, 0.001));
+        assert!(!price_valid(5, 0.001));
+    }
+
     // =========================================================================
     // Full Flow Integration Test
     // =========================================================================
@@ -758,6 +777,7 @@ mod infra_integration_tests {
             no_size: k_no_sz,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // 5. Verify request is valid
@@ -798,6 +818,7 @@ mod execution_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // 50 + 50 + 2 (fee) = 102 > 100 → negative profit
@@ -861,6 +882,7 @@ mod execution_tests {
 
     /// Test: NanoClock provides monotonic timing
     #[test]
+    #[cfg(feature = "kalshi")]
     fn test_nano_clock_monotonic() {
         use arb_bot::execution::NanoClock;
 
@@ -1323,6 +1345,9 @@ mod process_mock_tests {
             poly_no_token: "pf_no_token".into(),
             line_value: None,
             team_suffix: None,
+            settles_at: None,
+            poly_ends_at: None,
+            basis_risk: 0.0,
         }
     }
 
@@ -1352,6 +1377,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         let result = MockExecutionResult {
@@ -1402,6 +1428,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         let result = MockExecutionResult {
@@ -1443,6 +1470,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::KalshiYesPolyNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         let result = MockExecutionResult {
@@ -1483,6 +1511,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         let result = MockExecutionResult {
@@ -1517,6 +1546,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Kalshi only fills 7 out of 10
@@ -1559,6 +1589,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Poly only fills 6 out of 10
@@ -1594,6 +1625,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Kalshi fills 0, Poly fills 10 (complete failure on one side)
@@ -1615,7 +1647,7 @@ mod process_mock_tests {
 
         // But position tracker still records the Poly fill (for exposure tracking)
         let tracker_guard = tracker.read().await;
-        let pos = tracker_guard.get(&pair.pair_id).expect("Should have position even with partial fills");
+        let _pos = tracker_guard.get(&pair.pair_id).expect("Should have position even with partial fills");
 
         // Poly fill should still be recorded (matched=0 means 0 recorded as matched)
         // The position exists but has 0 matched contracts
@@ -1636,6 +1668,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Kalshi fills 10, Poly fills 0
@@ -1670,6 +1703,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         let result = MockExecutionResult {
@@ -1708,6 +1742,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Partial fill: Kalshi 7, Poly 10
@@ -1747,6 +1782,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // First execution: 10 contracts
@@ -1805,6 +1841,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Execute multiple times
@@ -1834,9 +1871,9 @@ mod process_mock_tests {
     /// Test: PolyOnly arb (Poly YES + Poly NO on same platform - zero Kalshi fees)
     #[tokio::test]
     async fn test_process_poly_only_arb() {
-        let tracker = Arc::new(RwLock::new(PositionTracker::new()));
-        let cb = CircuitBreaker::new(test_circuit_breaker_config());
-        let pair = test_market_pair();
+        let _tracker = Arc::new(RwLock::new(PositionTracker::new()));
+        let _cb = CircuitBreaker::new(test_circuit_breaker_config());
+        let _pair = test_market_pair();
 
         // PolyOnly: Buy YES and NO both on Polymarket
         // This is unusual but profitable when Poly YES + Poly NO < $1
@@ -1848,11 +1885,12 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // For PolyOnly, both fills are from Polymarket
         // In real execution: leg1 = Poly YES, leg2 = Poly NO
-        let result = MockExecutionResult {
+        let _result = MockExecutionResult {
             kalshi_filled: 0,   // No Kalshi in PolyOnly
             poly_filled: 10,    // Both YES and NO filled on Poly (combined)
             kalshi_cost: 0,
@@ -1870,9 +1908,9 @@ mod process_mock_tests {
     /// Test: KalshiOnly arb (Kalshi YES + Kalshi NO on same platform - double fees)
     #[tokio::test]
     async fn test_process_kalshi_only_arb() {
-        let tracker = Arc::new(RwLock::new(PositionTracker::new()));
-        let cb = CircuitBreaker::new(test_circuit_breaker_config());
-        let pair = test_market_pair();
+        let _tracker = Arc::new(RwLock::new(PositionTracker::new()));
+        let _cb = CircuitBreaker::new(test_circuit_breaker_config());
+        let _pair = test_market_pair();
 
         // KalshiOnly: Buy YES and NO both on Kalshi
         // Must overcome DOUBLE fees (fee on YES side + fee on NO side)
@@ -1884,6 +1922,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::KalshiOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Double fee: kalshi_fee(44) + kalshi_fee(44)
@@ -1909,6 +1948,7 @@ mod process_mock_tests {
                     no_size: 1000,
                     arb_type: ArbType::PolyOnly,
                     detected_ns: 0,
+                    poly_fee_bps: 0,
                 };
                 assert_eq!(req.estimated_fee_cents(), 0,
                     "PolyOnly should always have 0 fees, got {} for prices ({}, {})",
@@ -1932,6 +1972,7 @@ mod process_mock_tests {
                     no_size: 1000,
                     arb_type: ArbType::KalshiOnly,
                     detected_ns: 0,
+                    poly_fee_bps: 0,
                 };
                 let expected = kalshi_fee_cents(yes_price) + kalshi_fee_cents(no_price);
                 assert_eq!(req.estimated_fee_cents(), expected,
@@ -1955,6 +1996,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
         assert_eq!(req1.estimated_fee_cents(), kalshi_fee_cents(50),
             "PolyYesKalshiNo fee should be on NO side (50¢)");
@@ -1968,6 +2010,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::KalshiYesPolyNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
         assert_eq!(req2.estimated_fee_cents(), kalshi_fee_cents(40),
             "KalshiYesPolyNo fee should be on YES side (40¢)");
@@ -1976,8 +2019,6 @@ mod process_mock_tests {
     /// Test: Profit comparison across all arb types with same prices
     #[test]
     fn test_profit_comparison_all_arb_types() {
-        use arb_bot::types::kalshi_fee_cents;
-
         let yes_price = 45u16;
         let no_price = 45u16;
         // Raw cost = 90¢, payout = 100¢
@@ -1991,6 +2032,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // KalshiOnly: double fees → less profit
@@ -2002,6 +2044,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::KalshiOnly,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // Cross-platform: single fee
@@ -2013,6 +2056,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::PolyYesKalshiNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         let cross2 = FastExecutionRequest {
@@ -2023,6 +2067,7 @@ mod process_mock_tests {
             no_size: 1000,
             arb_type: ArbType::KalshiYesPolyNo,
             detected_ns: 0,
+            poly_fee_bps: 0,
         };
 
         // PolyOnly should always be most profitable (no fees)
@@ -2043,4 +2088,239 @@ mod process_mock_tests {
         assert_eq!(cross1.profit_cents(), cross2.profit_cents(),
             "Both cross-platform types should have equal profit");
     }
-}
\ No newline at end of file
+}
+// ============================================================================
+// EVENT BUS TESTS - Risk controls publish to independent consumers
+// ============================================================================
+
+mod event_bus_tests {
+    use arb_bot::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+    use arb_bot::events::{self, BotEvent};
+
+    /// Test: Tripping the circuit breaker publishes RiskTripped to subscribers
+    #[tokio::test]
+    async fn test_circuit_breaker_trip_publishes_event() {
+        let mut rx = events::subscribe();
+        let cb = CircuitBreaker::new(CircuitBreakerConfig {
+            max_position_per_market: 50,
+            max_total_position: 200,
+            max_daily_loss: 25.0,
+            max_consecutive_errors: 1,
+            cooldown_secs: 60,
+            enabled: true,
+        });
+
+        cb.record_error().await;
+
+        // Other tests trip breakers on the same global bus - wait for ours
+        loop {
+            let event = rx.recv().await.expect("event published");
+            if let BotEvent::RiskTripped { reason } = event {
+                if reason == "Consecutive errors: 1 (limit: 1)" {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// UP/DOWN LIFECYCLE TESTS - One 15-minute market from discovery to realized P&L
+// ============================================================================
+// Drives a market through every stage against a mock exchange on an injected
+// clock, so each run is identical:
+// 1. Discovery and preload of the next interval from a Gamma listing
+// 2. Subscription, snapshots and warm-up
+// 3. Arb confirmation, execution with a partial fill and the unwind of the excess
+// 4. Expiry cleanup, settlement and the exact position store contents
+
+mod updown_lifecycle_tests {
+    use arb_bot::arb_confirm::{ArbConfirmConfig, ArbStreak};
+    use arb_bot::basket::{Basket, BasketConfig, BasketLeg, Compensation, Repair, RepairSide};
+    use arb_bot::leg_order::{LegOrderConfig, LegPolicy};
+    use arb_bot::position_store::{JsonFileStore, PositionStore, Settlement};
+    use arb_bot::subscriptions::SubscriptionManager;
+    use arb_bot::updown_lifecycle::{self, BookFeed, Clock, ManualClock, MockExchange};
+    use arb_bot::updown_scanner::{GammaEvent, MarketSeries};
+    use arb_bot::venue_health::Venue;
+    use serde_json::json;
+    use std::time::Duration;
+
+    /// 5s into the interval ending at 1766100600 (2025-12-18T23:30:00Z)
+    const START_SECS: u64 = 1766099705;
+
+    /// Gamma /events listing of the BTC market ending at each of `ends`
+    fn gamma_listing(series: MarketSeries, ends: &[u64]) -> Vec<GammaEvent> {
+        let events: Vec<_> = ends.iter().map(|end| {
+            let slug = series.slug("btc", *end);
+            let end_date = chrono::DateTime::from_timestamp(*end as i64, 0).unwrap().to_rfc3339();
+            json!({
+                "slug": slug, "endDate": end_date, "closed": false,
+                "markets": [{
+                    "id": end.to_string(), "question": "Bitcoin Up or Down", "slug": slug,
+                    "clobTokenIds": format!("[\"up-{}\", \"down-{}\"]", end, end),
+                    "active": true, "closed": false, "acceptingOrders": true, "endDate": end_date,
+                    "outcomes": "[\"Up\", \"Down\"]", "orderPriceMinTickSize": 0.01, "orderMinSize": 5
+                }]
+            })
+        }).collect();
+        serde_json::from_value(json!(events)).unwrap()
+    }
+
+    /// Test: discovery → preload → subscription → detection → execution → partial fill
+    /// unwind → expiry cleanup → settlement, ending in exactly one resolved position
+    #[test]
+    fn test_full_15m_lifecycle() {
+        let series = MarketSeries::FifteenMin;
+        let exchange = MockExchange::default();
+        let clock = ManualClock::starting_at(START_SECS);
+        let path = std::env::temp_dir().join(format!("lifecycle_positions_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut store = JsonFileStore::open(&path).unwrap();
+
+        // === Discovery: the interval containing now ===
+        let current_end = series.interval_end(clock.now_secs(), 0);
+        assert_eq!(current_end, 1766100600);
+        let next_end = current_end + series.interval_secs();
+        let listing = gamma_listing(series, &[current_end, next_end]);
+        let market = updown_lifecycle::discover(&listing, series, "btc", current_end, 0).expect("listed");
+        assert_eq!(market.slug, "btc-updown-15m-1766100600");
+        assert_eq!((market.yes_token.as_str(), market.no_token.as_str()), ("up-1766100600", "down-1766100600"));
+        assert_eq!(market.min_size, 5.0);
+        let key = market.key();
+
+        // === Subscription: both tokens, acknowledged by their snapshots ===
+        exchange.set_book(&market.yes_token, (0.44, 50.0), (0.46, 100.0));
+        exchange.set_book(&market.no_token, (0.48, 50.0), (0.52, 100.0));
+        let mut subs = SubscriptionManager::new(Duration::from_secs(5));
+        subs.set_desired(updown_lifecycle::tokens([&market]));
+        let requested = subs.on_connect(clock.instant());
+        assert_eq!(requested, vec!["down-1766100600", "up-1766100600"]);
+
+        let mut feed = BookFeed::default();
+        let warmed: Vec<bool> = requested.iter()
+            .map(|token| feed.deliver(&market, &exchange.snapshot(token), &mut subs, 1.0))
+            .collect();
+        assert_eq!(warmed, vec![false, true], "warm once both sides are seen");
+        assert!(subs.is_acknowledged(&market.yes_token) && subs.is_acknowledged(&market.no_token));
+        assert!(feed.is_warm(&market));
+        assert!(subs.diff(clock.instant()).is_empty());
+
+        // === Detection: 0.46 + 0.52 has no edge; the NO ask drops to 0.50 and must persist ===
+        let confirm = ArbConfirmConfig { min_updates: 2, min_duration_ms: 500 };
+        let mut streak = ArbStreak::default();
+        let threshold = 0.99;
+        assert!(!streak.observe_at(feed.has_arb(&market, threshold), clock.instant().into(), &confirm));
+
+        clock.advance(Duration::from_millis(100));
+        exchange.set_book(&market.no_token, (0.48, 50.0), (0.50, 20.0));
+        feed.deliver(&market, &exchange.snapshot(&market.no_token), &mut subs, 1.0);
+        assert!(!streak.observe_at(feed.has_arb(&market, threshold), clock.instant().into(), &confirm), "first update is a blip");
+
+        clock.advance(Duration::from_millis(600));
+        feed.deliver(&market, &exchange.snapshot(&market.no_token), &mut subs, 1.0);
+        assert!(streak.observe_at(feed.has_arb(&market, threshold), clock.instant().into(), &confirm), "confirmed after 600ms");
+
+        // === Execution: 20 pairs, but another taker lifts 8 of the NO ask first ===
+        let ((yes_ask, yes_size), (no_ask, no_size)) = feed.best_asks(&market).unwrap();
+        let size = yes_size.min(no_size);
+        assert_eq!(size, 20.0);
+        let leg = |token: &str, side: &'static str, price: f64| BasketLeg {
+            venue: Venue::Polymarket, side, instrument: token.to_string(), price, contracts: size,
+        };
+        let arb = Basket::new(&market.slug, vec![leg(&market.yes_token, "yes", yes_ask), leg(&market.no_token, "no", no_ask)], 1.0);
+        arb.precheck().unwrap();
+        exchange.lift_ask(&market.no_token, 8.0);
+
+        // === Partial fill hedging: the 8 unmatched YES are sold ===
+        let order = LegOrderConfig { policy: LegPolicy::Simultaneous, min_samples: 20, max_stagger_ms: 0 };
+        let hedge = BasketConfig { compensation: Compensation::Unwind, unwind_cents: 10.0, complete_slack_cents: 2.0 };
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let execution = rt.block_on(updown_lifecycle::execute(&arb, &order, &hedge, &exchange));
+        assert_eq!(execution.filled(), vec![20.0, 12.0]);
+        assert_eq!(execution.plan, vec![Repair { leg: 0, side: RepairSide::Sell, contracts: 8.0, price: 0.46 - 0.10 }]);
+
+        for (taken, side) in execution.fills(&arb) {
+            store.append_fill(&taken.record(&key, &market.question, side, clock.rfc3339())).unwrap();
+            clock.advance(Duration::from_millis(50));
+        }
+        assert_eq!(exchange.fills().len(), 3);
+
+        // === Preload: inside the buffer before expiry, the next interval is found and subscribed ===
+        clock.advance_to(current_end - series.preload_buffer_secs());
+        assert_eq!(series.interval_end(clock.now_secs(), 1), next_end);
+        let next = updown_lifecycle::discover(&listing, series, "btc", next_end, 0).expect("listed");
+        exchange.set_book(&next.yes_token, (0.47, 50.0), (0.49, 100.0));
+        exchange.set_book(&next.no_token, (0.49, 50.0), (0.51, 100.0));
+        subs.set_desired(updown_lifecycle::tokens([&market, &next]));
+        let diff = subs.diff(clock.instant());
+        assert_eq!(diff.subscribe, vec!["down-1766101500", "up-1766101500"]);
+        assert!(diff.unsubscribe.is_empty());
+        for token in &diff.subscribe {
+            feed.deliver(&next, &exchange.snapshot(token), &mut subs, 1.0);
+        }
+        assert!(feed.is_warm(&next));
+
+        // === Expiry cleanup: after the grace period the expired tokens are dropped ===
+        clock.advance_to(current_end + series.expiry_grace_secs());
+        let live = updown_lifecycle::live([&market, &next], series, clock.now_secs());
+        assert_eq!(live, vec![&next]);
+        subs.set_desired(updown_lifecycle::tokens(live));
+        let diff = subs.diff(clock.instant());
+        assert!(diff.subscribe.is_empty());
+        assert_eq!(diff.unsubscribe, vec!["down-1766100600", "up-1766100600"]);
+        feed.retain(&subs);
+        assert_eq!(feed.len(), 2);
+        assert!(!feed.is_warm(&market));
+        assert_eq!(store.query(Some("open")).unwrap().len(), 1, "held to settlement");
+
+        // === Settlement and P&L: Up won ===
+        // (outside the runtime the tracker saves synchronously, so the file is final here)
+        let yes_cost = 20.0 * 0.46 + -8.0 * 0.44;
+        let no_cost = 12.0 * 0.50;
+        let pnl = 12.0 - (yes_cost + no_cost);
+        assert_eq!(store.settle(&key.id(), &Settlement::Binary { yes_won: true }).unwrap(), Some(pnl));
+        assert!((pnl - 0.32).abs() < 1e-9);
+        assert_eq!(store.settle("polymarket:btc-updown-15m-1766101500:1766101500", &Settlement::Binary { yes_won: true }).unwrap(), None);
+
+        // === Final store contents, reloaded from disk ===
+        let positions = JsonFileStore::open(&path).unwrap().query(None).unwrap();
+        let mut actual = serde_json::to_value(&positions).unwrap();
+        for field in ["opened_at", "resolved_at"] {
+            let at = actual[0][field].as_str().expect(field).to_string();
+            assert!(chrono::DateTime::parse_from_rfc3339(&at).is_ok(), "{} = {}", field, at);
+            actual[0][field] = json!("<now>");
+        }
+        let empty = json!({"contracts": 0.0, "cost_basis": 0.0, "avg_price": 0.0});
+        let trade = |timestamp: &str, contracts: f64, price: f64, order_id: &str, side: &str| json!({
+            "timestamp": timestamp, "platform": "polymarket", "side": side, "contracts": contracts,
+            "price": price, "fees": 0.0, "order_id": order_id,
+        });
+        let expected = json!([{
+            "market_id": "polymarket:btc-updown-15m-1766100600:1766100600",
+            "key": {
+                "venue": "polymarket", "market": "btc-updown-15m-1766100600",
+                "yes_token": "up-1766100600", "no_token": "down-1766100600", "end_ts": 1766100600,
+            },
+            "description": "Bitcoin Up or Down",
+            "kalshi_yes": empty,
+            "kalshi_no": empty,
+            "poly_yes": {"contracts": 12.0, "cost_basis": yes_cost, "avg_price": 20.0 * 0.46 / 20.0},
+            "poly_no": {"contracts": 12.0, "cost_basis": no_cost, "avg_price": 0.50},
+            "total_fees": 0.0,
+            "opened_at": "<now>",
+            "status": "resolved",
+            "realized_pnl": pnl,
+            "trades": [
+                trade("2025-12-18T23:15:05.700+00:00", 20.0, 0.46, "0x01", "yes"),
+                trade("2025-12-18T23:15:05.750+00:00", 12.0, 0.50, "0x02", "no"),
+                trade("2025-12-18T23:15:05.800+00:00", -8.0, 0.44, "0x03", "yes"),
+            ],
+            "outcome_yes": true,
+            "resolved_at": "<now>",
+        }]);
+        assert_eq!(actual, expected);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}